@@ -0,0 +1,69 @@
+use std::path::Path;
+
+use colored::*;
+use octasine::sync::inspect::{inspect_file, PatchInspection};
+
+/// Print summary of patch or patch bank file and warn about suspicious
+/// values. Returns error if any warnings were printed and deny_warnings is
+/// set.
+pub fn run(path: &Path, all_parameters: bool, deny_warnings: bool) -> anyhow::Result<()> {
+    let inspection = inspect_file(path)?;
+
+    println!("File:             {}", path.display());
+    println!("Format version:   {}", inspection.format_version);
+    println!("OctaSine version: {}", inspection.octasine_version);
+    println!("Patches:          {}", inspection.patches.len());
+
+    let mut num_warnings = 0;
+
+    for (index, patch) in inspection.patches.iter() {
+        println!();
+        println!("{}", format!("{:03}: {}", index + 1, patch.name).bold());
+
+        print_patch(patch, all_parameters);
+
+        num_warnings += patch.warnings.len();
+    }
+
+    println!();
+
+    if num_warnings == 0 {
+        println!("{}", "No warnings".green());
+
+        Ok(())
+    } else {
+        println!("{}", format!("{} warning(s)", num_warnings).yellow());
+
+        if deny_warnings {
+            Err(anyhow::anyhow!("Patch file has warnings"))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+fn print_patch(patch: &PatchInspection, all_parameters: bool) {
+    if let Some(category) = patch.category {
+        println!("  Category: {}", category);
+    }
+    if !patch.metadata.author.is_empty() {
+        println!("  Author:   {}", patch.metadata.author);
+    }
+    if !patch.metadata.license.is_empty() {
+        println!("  License:  {}", patch.metadata.license);
+    }
+
+    for (operator_index, name) in patch.wavetables.iter() {
+        println!("  Operator {} wavetable: {}", operator_index + 1, name);
+    }
+
+    for parameter in patch.parameters.iter() {
+        if all_parameters || !parameter.is_default {
+            println!("  {}: {}", parameter.name, parameter.value);
+        }
+    }
+
+    for warning in patch.warnings.iter() {
+        println!("  {} {}", "warning:".yellow(), warning);
+    }
+}
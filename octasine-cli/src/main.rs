@@ -1,4 +1,5 @@
 mod bench_process;
+mod inspect;
 #[cfg(feature = "plot")]
 mod plot;
 
@@ -18,9 +19,29 @@ enum Commands {
     RunGui,
     /// Benchmark OctaSine process functions and check output sample accuracy
     BenchProcess,
+    /// Print summary of patch (.fxp) or patch bank (.fxb) file and warn about
+    /// suspicious values, such as silent patches
+    Inspect {
+        /// Path to patch or patch bank file
+        path: std::path::PathBuf,
+        /// Print all parameter values, not only those differing from defaults
+        #[arg(long)]
+        all: bool,
+        /// Exit with error if there are any warnings
+        #[arg(long)]
+        deny_warnings: bool,
+    },
     /// Plot envelope and LFO curves (useful during development)
     #[cfg(feature = "plot")]
     Plot,
+    /// Generate patch bank from Rhai script and save it as fxb file
+    #[cfg(feature = "scripting")]
+    GeneratePatches {
+        /// Path to script file
+        script: std::path::PathBuf,
+        /// Path to output patch bank file
+        output: std::path::PathBuf,
+    },
 }
 
 fn main() -> anyhow::Result<()> {
@@ -51,8 +72,24 @@ fn main() -> anyhow::Result<()> {
             Ok(())
         }
         Commands::BenchProcess => bench_process::run(),
+        Commands::Inspect {
+            path,
+            all,
+            deny_warnings,
+        } => inspect::run(&path, all, deny_warnings),
         #[cfg(feature = "plot")]
         Commands::Plot => plot::run(),
+        #[cfg(feature = "scripting")]
+        Commands::GeneratePatches { script, output } => {
+            use anyhow::Context;
+
+            let bank = octasine::scripting::generate_patch_bank_from_script_file(&script)?;
+
+            std::fs::write(&output, bank.export_fxb_bytes())
+                .with_context(|| format!("write patch bank to {}", output.display()))?;
+
+            Ok(())
+        }
     }
 }
 
@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use octasine::sync::PatchBank;
+
+fuzz_target!(|data: &[u8]| {
+    PatchBank::default().import_bytes_into_current_patch(data);
+});
@@ -0,0 +1,21 @@
+#![no_main]
+
+//! Skip header search and go straight to decoding of uncompressed v2 data,
+//! as used in host project state (fxb/fxp chunks)
+
+use libfuzzer_sys::fuzz_target;
+use octasine::sync::PatchBank;
+
+const PREFIX_PLAIN: &[u8] = b"\n\nOCTASINE-DATA-V2-PLAIN\n\n";
+
+fuzz_target!(|data: &[u8]| {
+    let mut bytes = PREFIX_PLAIN.to_vec();
+
+    bytes.extend_from_slice(data);
+
+    let bank = PatchBank::default();
+
+    let _ = bank.import_bank_from_bytes(&bytes);
+
+    bank.import_bytes_into_current_patch(&bytes);
+});
@@ -0,0 +1,49 @@
+/*
+ * C API for the OctaSine audio engine. Build octasine with the "capi"
+ * feature (and without default features) to export these functions.
+ */
+
+#ifndef OCTASINE_H
+#define OCTASINE_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct OctaSineSynth OctaSineSynth;
+
+/* Create synth. Free with octasine_synth_free. */
+OctaSineSynth *octasine_synth_new(double sample_rate);
+
+void octasine_synth_free(OctaSineSynth *synth);
+
+void octasine_synth_set_sample_rate(OctaSineSynth *synth, double sample_rate);
+
+/*
+ * Enqueue MIDI message of one to three bytes, to be processed at given frame
+ * offset into next rendered buffer. Returns false if message length is
+ * invalid.
+ */
+bool octasine_synth_send_midi(OctaSineSynth *synth, const uint8_t *data, size_t len,
+                              uint32_t delta_frames);
+
+uint32_t octasine_synth_num_parameters(const OctaSineSynth *synth);
+
+/* Get parameter value in range 0.0 to 1.0. Negative if index is invalid. */
+float octasine_synth_get_parameter(const OctaSineSynth *synth, uint32_t index);
+
+/* Set parameter value in range 0.0 to 1.0 */
+void octasine_synth_set_parameter(OctaSineSynth *synth, uint32_t index, float value);
+
+/* Render len samples into each of the left and right buffers */
+void octasine_synth_render(OctaSineSynth *synth, float *lefts, float *rights, size_t len);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
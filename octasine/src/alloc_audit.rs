@@ -0,0 +1,60 @@
+//! Allocation tracking for verifying that audio processing doesn't allocate
+//!
+//! Enable with the `audit-allocations` feature. The tracking allocator is
+//! installed as global allocator in tests.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+thread_local! {
+    /// Number of allocations on this thread while auditing, or None if not
+    /// auditing
+    static ALLOCATIONS: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+/// System allocator that counts allocations made during `count_allocations`
+pub struct AuditAllocator;
+
+unsafe impl GlobalAlloc for AuditAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        record_allocation();
+
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        record_allocation();
+
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        record_allocation();
+
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+fn record_allocation() {
+    // Ignore errors, which occur during thread teardown
+    let _ = ALLOCATIONS.try_with(|allocations| {
+        if let Some(n) = allocations.get() {
+            allocations.set(Some(n + 1));
+        }
+    });
+}
+
+/// Run function and return number of allocations it made on current thread
+pub fn count_allocations<F: FnOnce() -> R, R>(f: F) -> (R, usize) {
+    ALLOCATIONS.with(|allocations| allocations.set(Some(0)));
+
+    let result = f();
+
+    let allocations = ALLOCATIONS.with(|allocations| allocations.take().unwrap_or(0));
+
+    (result, allocations)
+}
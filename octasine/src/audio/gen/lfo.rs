@@ -1,13 +1,25 @@
 use arrayvec::ArrayVec;
 
 use crate::audio::parameters::{common::AudioParameter, LfoAudioParameters};
-use crate::audio::voices::lfos::VoiceLfo;
+use crate::audio::voices::{lfos::VoiceLfo, KeyVelocity};
 use crate::common::*;
-use crate::parameters::{LfoParameter, PARAMETERS};
+use crate::parameters::{
+    lfo_mode::LfoMode, lfo_transport_sync::LfoTransportSync, LfoParameter, PARAMETERS,
+};
+
+/// Maximum number of distinct targets of all LFOs
+const MAX_LFO_TARGETS: usize = NUM_LFOS * 3;
+/// Maximum number of LFO target ramps. Targets that are no longer set ramp
+/// towards zero alongside the currently set ones.
+const MAX_LFO_TARGET_RAMPS: usize = MAX_LFO_TARGETS * 2;
+/// Random per note value, MPE pressure and MPE timbre
+const NUM_NOTE_MODULATION_SOURCES: usize = 3;
 
 pub struct LfoTargetValues {
     values: [Option<f32>; PARAMETERS.len()],
-    set_indices: ArrayVec<u8, NUM_LFOS>,
+    /// Room for all LFO target ramps and one target each for note
+    /// modulation sources
+    set_indices: ArrayVec<u8, { MAX_LFO_TARGET_RAMPS + NUM_NOTE_MODULATION_SOURCES }>,
 }
 
 impl Default for LfoTargetValues {
@@ -24,7 +36,7 @@ impl LfoTargetValues {
         self.values[target as usize]
     }
 
-    fn set_or_add(&mut self, target: u8, value: f32) {
+    pub fn set_or_add(&mut self, target: u8, value: f32) {
         match &mut self.values[target as usize] {
             Some(v) => *v += value,
             v @ None => {
@@ -35,7 +47,7 @@ impl LfoTargetValues {
         }
     }
 
-    fn clear_set(&mut self) {
+    pub fn clear_set(&mut self) {
         for i in self.set_indices.iter().copied() {
             self.values[i as usize] = None;
         }
@@ -44,13 +56,84 @@ impl LfoTargetValues {
     }
 }
 
+/// Per-voice LFO target values for running LFOs at control rate
+///
+/// LFOs are advanced a full control interval at a time. In between, the
+/// target values are linearly interpolated towards the latest LFO outputs.
+#[derive(Debug, Clone, Default)]
+pub struct LfoTargetRamps {
+    /// Target index, current value, step size and end value
+    ramps: ArrayVec<(u8, f32, f32, f32), MAX_LFO_TARGET_RAMPS>,
+    samples_until_update: usize,
+}
+
+impl LfoTargetRamps {
+    pub fn needs_update(&self) -> bool {
+        self.samples_until_update == 0
+    }
+
+    /// Request update on next sample, e.g., after LFOs were restarted
+    pub fn request_update(&mut self) {
+        self.samples_until_update = 0;
+    }
+
+    /// Start ramping towards LFO target values over given number of samples
+    pub fn update(&mut self, lfo_values: &LfoTargetValues, num_samples: usize) {
+        let num_samples_f32 = num_samples as f32;
+
+        // Finish previous ramps and drop the ones that ended at zero and are
+        // no longer targeted
+        self.ramps.retain(|(index, value, _, end)| {
+            *value = *end;
+
+            (*end != 0.0) | lfo_values.get(*index).is_some()
+        });
+
+        for (index, value, step, end) in self.ramps.iter_mut() {
+            *end = lfo_values.get(*index).unwrap_or(0.0);
+            *step = (*end - *value) / num_samples_f32;
+        }
+
+        for index in lfo_values.set_indices.iter().copied() {
+            if !self.ramps.iter().any(|(i, _, _, _)| *i == index) {
+                let end = lfo_values.get(index).unwrap_or(0.0);
+
+                self.ramps.push((index, 0.0, end / num_samples_f32, end));
+            }
+        }
+
+        self.samples_until_update = num_samples;
+    }
+
+    /// Advance ramps and write current values to lfo_values
+    pub fn advance_one_sample(&mut self, lfo_values: &mut LfoTargetValues) {
+        lfo_values.clear_set();
+
+        self.samples_until_update = self.samples_until_update.saturating_sub(1);
+
+        for (index, value, step, end) in self.ramps.iter_mut() {
+            *value = if self.samples_until_update == 0 {
+                *end
+            } else {
+                *value + *step
+            };
+
+            lfo_values.set_or_add(*index, *value);
+        }
+    }
+}
+
 pub fn update_lfo_target_values(
     lfo_values: &mut LfoTargetValues,
+    num_samples: usize,
     lfo_parameters: &mut [LfoAudioParameters; NUM_LFOS],
     voice_lfos: &mut [VoiceLfo; NUM_LFOS],
     sample_rate: SampleRate,
     time_per_sample: TimePerSample,
     bpm_lfo_multiplier: BpmLfoMultiplier,
+    opt_song_position: Option<f64>,
+    key_velocity: KeyVelocity,
+    mod_wheel: f32,
 ) {
     const AMOUNT_PARAMETER_INDICES: [u8; NUM_LFOS] = LfoParameter::Amount.index_array();
     const SHAPE_PARAMETER_INDICES: [u8; NUM_LFOS] = LfoParameter::Shape.index_array();
@@ -67,19 +150,45 @@ pub fn update_lfo_target_values(
     {
         assert!(lfo_index < NUM_LFOS);
 
-        let target_index = lfo_parameter.target.get_value().index();
+        // Target indices with depths
+        let targets = [
+            (lfo_parameter.target.get_value().index(), 1.0),
+            (
+                lfo_parameter.target_2.get_value().index(),
+                lfo_parameter.target_2_depth.get_value(),
+            ),
+            (
+                lfo_parameter.target_3.get_value().index(),
+                lfo_parameter.target_3_depth.get_value(),
+            ),
+        ];
 
-        let target_index = match (target_index, voice_lfo.is_stopped()) {
-            (None, _) | (_, true) => continue,
-            (Some(index), false) => index,
-        };
+        if voice_lfo.is_stopped() | targets.iter().all(|(index, _)| index.is_none()) {
+            continue;
+        }
+
+        let velocity_factor = sensitivity_factor(
+            lfo_parameter.velocity_sensitivity.get_value(),
+            key_velocity.0,
+        );
+        let mod_wheel_factor =
+            sensitivity_factor(lfo_parameter.mod_wheel_sensitivity.get_value(), mod_wheel);
 
         let amount = lfo_parameter.active.get_value()
+            * velocity_factor
+            * mod_wheel_factor
             * lfo_parameter
                 .amount
                 .get_value_with_lfo_addition(lfo_values.get(AMOUNT_PARAMETER_INDICES[lfo_index]));
 
-        let mode = lfo_parameter.mode.get_value();
+        let envelope_curve = lfo_parameter.envelope_curve.get_value();
+
+        // Envelope mode always runs a single cycle
+        let mode = if envelope_curve.is_active() {
+            LfoMode::Once
+        } else {
+            lfo_parameter.mode.get_value()
+        };
         let bpm_sync = lfo_parameter.bpm_sync.get_value();
 
         let shape = lfo_parameter
@@ -98,17 +207,119 @@ pub fn update_lfo_target_values(
             BpmLfoMultiplier(1.0)
         };
 
-        voice_lfo.advance_one_sample(
+        let frequency = frequency_ratio * frequency_free;
+
+        let opt_synced_phase = match (bpm_sync, lfo_parameter.transport_sync.get_value()) {
+            (true, LfoTransportSync::SongPosition) => opt_song_position
+                // With BPM sync, frequency 1.0 means one cycle per two beats
+                .map(|beats| Phase((beats * frequency * 0.5).rem_euclid(1.0))),
+            _ => None,
+        };
+
+        voice_lfo.advance_samples(
+            num_samples,
             sample_rate,
             time_per_sample,
             bpm_lfo_multiplier,
             shape,
             mode,
-            frequency_ratio * frequency_free,
+            frequency,
+            opt_synced_phase,
+        );
+
+        let addition = voice_lfo.get_value(
+            amount,
+            lfo_parameter.polarity.get_value(),
+            envelope_curve,
+            lfo_parameter.envelope_attack.get_value(),
+            lfo_parameter.smoothing.get_value(),
         );
 
-        let addition = voice_lfo.get_value(amount);
+        for (opt_target_index, depth) in targets {
+            if let Some(target_index) = opt_target_index {
+                lfo_values.set_or_add(target_index, addition * depth);
+            }
+        }
+    }
+}
+
+/// With sensitivity 0.0, return 1.0. With sensitivity 1.0, return value.
+fn sensitivity_factor(sensitivity: f32, value: f32) -> f32 {
+    sensitivity * value + (1.0 - sensitivity)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lfo_target_ramps() {
+        let mut ramps = LfoTargetRamps::default();
+        let mut lfo_values = LfoTargetValues::default();
+
+        assert!(ramps.needs_update());
+
+        lfo_values.set_or_add(3, 1.0);
+        ramps.update(&lfo_values, 4);
+
+        let mut values = Vec::new();
+
+        for _ in 0..4 {
+            ramps.advance_one_sample(&mut lfo_values);
+            values.push(lfo_values.get(3).unwrap());
+        }
+
+        assert_eq!(values, vec![0.25, 0.5, 0.75, 1.0]);
+        assert!(ramps.needs_update());
 
-        lfo_values.set_or_add(target_index, addition);
+        // Target no longer set, so ramp down to zero and then drop it
+        lfo_values.clear_set();
+        ramps.update(&lfo_values, 2);
+        ramps.advance_one_sample(&mut lfo_values);
+
+        assert_eq!(lfo_values.get(3), Some(0.5));
+
+        ramps.advance_one_sample(&mut lfo_values);
+
+        assert_eq!(lfo_values.get(3), Some(0.0));
+
+        lfo_values.clear_set();
+        ramps.update(&lfo_values, 2);
+        ramps.advance_one_sample(&mut lfo_values);
+
+        assert_eq!(lfo_values.get(3), None);
+    }
+
+    /// All LFO targets change at once, e.g., on patch switch, while note
+    /// modulation sources are set too
+    #[test]
+    fn test_lfo_target_ramps_all_targets() {
+        let mut ramps = LfoTargetRamps::default();
+        let mut lfo_values = LfoTargetValues::default();
+
+        let num_targets = MAX_LFO_TARGETS as u8;
+
+        for targets in [0..num_targets, num_targets..num_targets * 2] {
+            lfo_values.clear_set();
+
+            for target in targets {
+                lfo_values.set_or_add(target, 1.0);
+            }
+
+            ramps.update(&lfo_values, 4);
+            ramps.advance_one_sample(&mut lfo_values);
+
+            for i in 0..NUM_NOTE_MODULATION_SOURCES {
+                lfo_values.set_or_add((MAX_LFO_TARGET_RAMPS + i) as u8, 1.0);
+            }
+        }
+
+        // Previous targets ramp down while new ones ramp up
+        assert_eq!(lfo_values.get(0), Some(0.75));
+        assert_eq!(lfo_values.get(num_targets), Some(0.25));
+        assert_eq!(
+            lfo_values.set_indices.len(),
+            MAX_LFO_TARGET_RAMPS + NUM_NOTE_MODULATION_SOURCES
+        );
     }
 }
@@ -1,22 +1,31 @@
 pub mod lfo;
 
 use std::f64::consts::TAU;
+use std::sync::Arc;
 
 use duplicate::duplicate_item;
-use ringbuf::ring_buffer::RbBase;
 
 use crate::audio::parameters::{common::AudioParameter, OperatorAudioParameters};
-use crate::audio::voices::log10_table::Log10Table;
+use crate::audio::voices::{
+    log10_table::Log10Table, spread::SpreadDelayLine, OperatorParameterModulation, Voice,
+};
 use crate::audio::AudioState;
 use crate::common::*;
 use crate::parameters::operator_wave_type::WaveType;
 use crate::parameters::{MasterParameter, ModTargetStorage, OperatorParameter, Parameter};
 use crate::simd::*;
+use crate::wavetable::Wavetable;
 
 use lfo::*;
 
 const MASTER_VOLUME_FACTOR: f64 = 0.2;
 const LIMIT: f64 = 10.0;
+/// Maximum distance that phase distortion moves the sine wave midpoint
+/// towards the start of the cycle
+const PHASE_DISTORTION_MAX_KNEE_OFFSET: f64 = 0.49;
+/// Operator frequency (as a fraction of the sample rate) above which
+/// modulation input is attenuated when anti-aliasing is active
+const ANTI_ALIAS_ROLLOFF_START: f64 = 1.0 / 32.0;
 
 pub trait AudioGen {
     #[allow(clippy::missing_safety_doc)]
@@ -35,6 +44,8 @@ pub trait AudioGen {
 pub struct AudioGenData<const W: usize> {
     lfo_target_values: LfoTargetValues,
     volume_velocity_sensitivity: [f64; W],
+    /// Amount of output summed to mono, for checking mono compatibility
+    mono_fold_down: [f64; W],
     /// Allocate room for data for 128 polyphonic voices as well as the mono
     /// voice, even if they won't all be used at once in practice.
     voices: [VoiceData<W>; 129],
@@ -45,6 +56,7 @@ impl<const W: usize> Default for AudioGenData<W> {
         Self {
             lfo_target_values: Default::default(),
             volume_velocity_sensitivity: [0.0; W],
+            mono_fold_down: [0.0; W],
             voices: array_init::array_init(|_| Default::default()),
         }
     }
@@ -57,6 +69,9 @@ struct VoiceData<const W: usize> {
     /// Master volume is calculated per-voice, since it can be an LFO target
     master_volume: [f64; W],
     operators: [VoiceOperatorData<W>; 4],
+    /// Cross feedback (left and right) generated during last sample,
+    /// indexed by target operator
+    cross_feedback_outputs: [[f64; 2]; NUM_OPERATORS],
 }
 
 impl<const W: usize> Default for VoiceData<W> {
@@ -66,6 +81,7 @@ impl<const W: usize> Default for VoiceData<W> {
             key_velocity: [0.0; W],
             master_volume: [0.0; W],
             operators: Default::default(),
+            cross_feedback_outputs: Default::default(),
         }
     }
 }
@@ -92,10 +108,24 @@ struct VoiceOperatorData<const W: usize> {
     constant_power_panning: [f64; W],
     envelope_volume: [f64; W],
     phase: [f64; W],
+    /// Phase change per sample, used by band-limited wave forms
+    phase_increment: [f64; W],
     wave_type: WaveType,
+    /// White noise samples, only generated when wave type is white noise
+    noise: [f64; W],
+    wavetable_position: [f64; W],
+    /// Phase at which distorted phase ramp reaches 0.5
+    phase_distortion_knee: [f64; W],
+    /// Factor applied to modulation input, used for anti-aliasing
+    modulation_input_factor: [f64; W],
     modulation_targets: ModTargetStorage,
+    cross_feedback: [f64; W],
+    cross_feedback_target: Option<usize>,
+    cross_feedback_input: [f64; W],
     velocity_sensitivity_mod_out: [f64; W],
     velocity_sensitivity_feedback: [f64; W],
+    /// Delay of right channel output in samples
+    spread_delay: [f64; W],
 }
 
 impl<const W: usize> Default for VoiceOperatorData<W> {
@@ -109,14 +139,25 @@ impl<const W: usize> Default for VoiceOperatorData<W> {
             constant_power_panning: [0.0; W],
             envelope_volume: [0.0; W],
             phase: [0.0; W],
+            phase_increment: [0.0; W],
             wave_type: Default::default(),
+            noise: [0.0; W],
+            wavetable_position: [0.0; W],
+            phase_distortion_knee: [0.5; W],
+            modulation_input_factor: [1.0; W],
             modulation_targets: Default::default(),
             velocity_sensitivity_mod_out: [0.0; W],
             velocity_sensitivity_feedback: [0.0; W],
+            cross_feedback: [0.0; W],
+            cross_feedback_target: None,
+            cross_feedback_input: [0.0; W],
+            spread_delay: [0.0; W],
         }
     }
 }
 
+/// Generate audio. Returns true if output was skipped because no voices are
+/// active and no note events are pending, in which case it is silent.
 #[inline]
 pub fn process_f32_runtime_select<F>(
     audio_state: &mut AudioState,
@@ -124,22 +165,49 @@ pub fn process_f32_runtime_select<F>(
     rights: &mut [f32],
     frame_offset: usize,
     updater: F,
-) where
+) -> bool
+where
     F: Fn(&mut AudioState),
 {
+    updater(audio_state);
+
+    // Events due after end of buffer, which hosts shouldn't send, are
+    // processed at its last sample instead of being left queued
+    if let Some(last_sample_index) = (frame_offset + lefts.len()).checked_sub(1) {
+        audio_state
+            .pending_note_events
+            .clamp_delta_frames(last_sample_index as u32);
+    }
+
+    if audio_state.process_events_if_silent() {
+        audio_state.advance_silent_samples(lefts.len());
+
+        lefts.fill(0.0);
+        rights.fill(0.0);
+
+        return true;
+    }
+
     let num_samples = lefts.len();
 
     let mut position = 0;
 
     loop {
-        updater(audio_state);
+        if position != 0 {
+            updater(audio_state);
+        }
 
         let num_remaining_samples = (num_samples - position) as u64;
 
         unsafe {
             match num_remaining_samples {
+                // Cross feedback is delayed by exactly one sample, so
+                // process one sample at a time when it is active
                 #[cfg(target_arch = "x86_64")]
-                (2..) if is_x86_feature_detected!("avx") => {
+                (2..)
+                    if is_x86_feature_detected!("avx")
+                        && !audio_state.parameters.cross_feedback_active() =>
+                {
                     let new_position = position + 2;
 
                     Avx::process_f32(
@@ -183,6 +251,50 @@ pub fn process_f32_runtime_select<F>(
             }
         }
     }
+
+    false
+}
+
+/// Apply master processing (bypass, auto-gain, mono fold-down, master volume
+/// factor and limiting) to output summed from engines with master
+/// processing turned off. Advances per-sample state of audio_state, which
+/// doesn't render any voices itself.
+pub fn process_master_f32(audio_state: &mut AudioState, lefts: &mut [f32], rights: &mut [f32]) {
+    for (left, right) in lefts.iter_mut().zip(rights.iter_mut()) {
+        audio_state.advance_one_sample();
+
+        let gain =
+            audio_state.bypass_gain.get_value() as f64 * audio_state.auto_gain.get_value() as f64;
+        let mono_fold_down = audio_state.mono_fold_down.get_value() as f64;
+
+        let mut samples = [*left as f64 * gain, *right as f64 * gain];
+
+        let mono = (samples[0] + samples[1]) * 0.5;
+
+        for sample in samples.iter_mut() {
+            *sample = (*sample + (mono - *sample) * mono_fold_down) * MASTER_VOLUME_FACTOR;
+
+            // Detect output above 0 dBFS before hard limit is applied
+            if sample.abs() > 1.0 {
+                audio_state.clipped = true;
+            }
+        }
+
+        *left = samples[0].clamp(-LIMIT, LIMIT) as f32;
+        *right = samples[1].clamp(-LIMIT, LIMIT) as f32;
+    }
+}
+
+/// Modulation input factor for operator with given frequency (as a fraction
+/// of the sample rate). Effective modulation index is reduced in proportion
+/// to frequency above ANTI_ALIAS_ROLLOFF_START, so that sideband bandwidth
+/// stays roughly constant for high notes.
+fn anti_alias_modulation_factor(relative_frequency: f64) -> f64 {
+    if relative_frequency <= ANTI_ALIAS_ROLLOFF_START {
+        1.0
+    } else {
+        ANTI_ALIAS_ROLLOFF_START / relative_frequency
+    }
 }
 
 #[duplicate_item(
@@ -227,10 +339,9 @@ mod gen {
             assert_eq!(lefts.len(), Pd::SAMPLES);
             assert_eq!(rights.len(), Pd::SAMPLES);
 
-            if audio_state.pending_note_events.is_empty()
-                & audio_state.polyphonic_voices.is_empty()
-                & !audio_state.monophonic_voice.active
-            {
+            if audio_state.is_silent() {
+                audio_state.advance_silent_samples(Pd::SAMPLES);
+
                 for (l, r) in lefts.iter_mut().zip(rights.iter_mut()) {
                     *l = 0.0;
                     *r = 0.0;
@@ -242,12 +353,49 @@ mod gen {
             let num_valid_voice_datas = extract_voice_data(audio_state, position);
 
             gen_audio(
-                &mut audio_state.rng,
+                &audio_state.wavetables,
                 audio_state.audio_gen_data_field.volume_velocity_sensitivity,
-                &audio_state.audio_gen_data_field.voices[..num_valid_voice_datas],
+                audio_state.audio_gen_data_field.mono_fold_down,
+                &mut audio_state.audio_gen_data_field.voices[..num_valid_voice_datas],
+                &mut audio_state.modulation_input_peaks,
+                audio_state
+                    .master_processing
+                    .then_some(&mut audio_state.clipped),
+                &mut audio_state.polyphonic_voices,
+                &mut audio_state.monophonic_voice,
                 lefts,
                 rights,
             );
+
+            if audio_state.parameters.cross_feedback_active() {
+                store_cross_feedback(audio_state, num_valid_voice_datas);
+            }
+        }
+    }
+
+    /// Store generated cross feedback in voices for use during next sample.
+    /// Only valid when processing a single sample at a time.
+    #[feature_gate]
+    #[target_feature_enable]
+    unsafe fn store_cross_feedback(audio_state: &mut AudioState, num_valid_voice_datas: usize) {
+        for voice_data in audio_state.audio_gen_data_field.voices[..num_valid_voice_datas].iter() {
+            let opt_voice = if voice_data.voice_index == 128 {
+                Some(&mut audio_state.monophonic_voice)
+            } else {
+                audio_state
+                    .polyphonic_voices
+                    .get_mut(&voice_data.voice_index)
+            };
+
+            if let Some(voice) = opt_voice {
+                for (voice_operator, output) in voice
+                    .operators
+                    .iter_mut()
+                    .zip(voice_data.cross_feedback_outputs)
+                {
+                    voice_operator.cross_feedback_input = output;
+                }
+            }
         }
     }
 
@@ -270,6 +418,11 @@ mod gen {
                     .volume_velocity_sensitivity
                     .get_value() as f64,
             );
+            set_value_for_both_channels(
+                &mut audio_state.audio_gen_data_field.mono_fold_down,
+                sample_index,
+                audio_state.mono_fold_down.get_value() as f64,
+            );
 
             let operators = &mut audio_state.parameters.operators;
             let lfo_values = &mut audio_state.audio_gen_data_field.lfo_target_values;
@@ -341,19 +494,79 @@ mod gen {
                         );
                 }
 
-                update_lfo_target_values(
-                    lfo_values,
-                    &mut audio_state.parameters.lfos,
-                    &mut voice.lfos,
-                    audio_state.sample_rate,
-                    time_per_sample,
-                    audio_state.bpm_lfo_multiplier,
-                );
+                let key_velocity = voice.get_key_velocity();
+
+                let control_interval = audio_state.samples_per_control_update;
+
+                if control_interval == 1 || voice.lfo_target_ramps.needs_update() {
+                    update_lfo_target_values(
+                        lfo_values,
+                        control_interval,
+                        &mut audio_state.parameters.lfos,
+                        &mut voice.lfos,
+                        audio_state.sample_rate,
+                        time_per_sample,
+                        audio_state.bpm_lfo_multiplier,
+                        audio_state.song_position,
+                        key_velocity,
+                        audio_state.mod_wheel.get_value(),
+                    );
+                }
+
+                if control_interval > 1 {
+                    if voice.lfo_target_ramps.needs_update() {
+                        voice.lfo_target_ramps.update(lfo_values, control_interval);
+                    }
+
+                    voice.lfo_target_ramps.advance_one_sample(lfo_values);
+                }
+
+                // LFOs keep running, but don't modulate their targets
+                if audio_state.lfos_disabled {
+                    lfo_values.clear_set();
+                }
+
+                if let Some(target_index) = audio_state
+                    .parameters
+                    .note_random_target
+                    .get_value()
+                    .index()
+                {
+                    let amount = audio_state.parameters.note_random_amount.get_value();
+
+                    lfo_values.set_or_add(target_index, voice.note_random_value * amount);
+                }
+
+                if audio_state
+                    .parameters
+                    .mpe_zone
+                    .get_value()
+                    .is_member_channel(voice.midi_channel)
+                {
+                    let channel = usize::from(voice.midi_channel);
+
+                    for (target, amount, value) in [
+                        (
+                            audio_state.parameters.mpe_pressure_target.get_value(),
+                            audio_state.parameters.mpe_pressure_amount.get_value(),
+                            audio_state.channel_pressures[channel].get_value(),
+                        ),
+                        (
+                            audio_state.parameters.mpe_timbre_target.get_value(),
+                            audio_state.parameters.mpe_timbre_amount.get_value(),
+                            audio_state.channel_timbres[channel].get_value(),
+                        ),
+                    ] {
+                        if let Some(target_index) = target.index() {
+                            lfo_values.set_or_add(target_index, value * amount);
+                        }
+                    }
+                }
 
                 set_value_for_both_channels(
                     &mut voice_data.key_velocity,
                     sample_index,
-                    voice.get_key_velocity().0 as f64,
+                    key_velocity.0 as f64,
                 );
 
                 const MASTER_VOLUME_INDEX: u8 =
@@ -364,10 +577,17 @@ mod gen {
                     .master_volume
                     .get_value_with_lfo_addition(lfo_values.get(MASTER_VOLUME_INDEX));
 
+                let master_gain = if audio_state.master_processing {
+                    audio_state.bypass_gain.get_value() as f64
+                        * audio_state.auto_gain.get_value() as f64
+                } else {
+                    1.0
+                };
+
                 set_value_for_both_channels(
                     &mut voice_data.master_volume,
                     sample_index,
-                    master_volume as f64,
+                    master_volume as f64 * voice.expression.volume * master_gain,
                 );
 
                 const MASTER_FREQUENCY_INDEX: u8 =
@@ -395,14 +615,60 @@ mod gen {
 
                 master_frequency *= pitch_bend_frequency_multiplier;
 
-                let voice_base_frequency =
-                    voice.pitch_interpolator.get_value() as f64 * master_frequency;
+                let vibrato_frequency_multiplier = {
+                    let mut depth = audio_state.parameters.vibrato_depth.get_value();
+
+                    if audio_state.parameters.vibrato_mod_wheel.get_value() {
+                        depth *= audio_state.mod_wheel.get_value();
+                    }
+
+                    voice.vibrato.advance_one_sample(
+                        time_per_sample,
+                        audio_state.parameters.vibrato_rate.get_value().into(),
+                        depth.into(),
+                        audio_state.parameters.vibrato_delay.get_value().into(),
+                    )
+                };
+
+                master_frequency *= vibrato_frequency_multiplier;
+
+                let anti_alias = audio_state.parameters.anti_alias.get_value();
+
+                let channel_pitch_bend_frequency_multiplier = if audio_state
+                    .parameters
+                    .mpe_zone
+                    .get_value()
+                    .is_member_channel(voice.midi_channel)
+                {
+                    let range = f32::from(
+                        audio_state
+                            .parameters
+                            .mpe_member_pitch_bend_range
+                            .get_value(),
+                    );
+
+                    audio_state.channel_pitch_bends[usize::from(voice.midi_channel)]
+                        .as_frequency_multiplier(range, -range)
+                } else {
+                    1.0
+                };
+
+                let voice_base_frequency = voice.pitch_interpolator.get_value() as f64
+                    * master_frequency
+                    * channel_pitch_bend_frequency_multiplier
+                    * voice.expression.frequency_factor;
 
                 for (operator_index, operator) in operators.iter_mut().enumerate() {
                     if voice.operators[operator_index].volume_envelope.is_ended() {
                         continue;
                     }
 
+                    let mut modulation = voice.operators[operator_index]
+                        .parameter_modulation
+                        .combine(audio_state.global_operator_modulation[operator_index]);
+
+                    modulation.panning += voice.expression.panning;
+
                     extract_voice_operator_data(
                         &audio_state.log10table,
                         sample_index,
@@ -411,9 +677,25 @@ mod gen {
                         &mut voice.operators[operator_index],
                         &mut voice_data.operators[operator_index],
                         lfo_values,
+                        modulation,
                         time_per_sample,
                         voice_base_frequency,
-                    )
+                        anti_alias,
+                    );
+
+                    if let Some(listen_operator) = audio_state.listen_operator {
+                        let mix_out = if listen_operator == operator_index {
+                            1.0
+                        } else {
+                            0.0
+                        };
+
+                        set_value_for_both_channels(
+                            &mut voice_data.operators[operator_index].mix_out,
+                            sample_index,
+                            mix_out,
+                        );
+                    }
                 }
 
                 #[cfg_attr(not(feature = "clap"), allow(unused_variables))]
@@ -454,10 +736,12 @@ mod gen {
                     clap_note_id: i32,
                 ) -> bool {
                     if voice_was_monophonic {
-                        if let Some(voice) = audio_state.polyphonic_voices.get(&key) {
-                            if voice.active && voice.clap_note_id == Some(clap_note_id) {
-                                return false;
-                            }
+                        let key_is_active = audio_state.polyphonic_voices.values().any(|v| {
+                            v.active && v.key() == key && v.clap_note_id == Some(clap_note_id)
+                        });
+
+                        if key_is_active {
+                            return false;
                         }
                     } else {
                         let v = &audio_state.monophonic_voice;
@@ -503,8 +787,10 @@ mod gen {
         voice_operator: &mut crate::audio::voices::VoiceOperator,
         operator_data: &mut VoiceOperatorData<{ Pd::WIDTH }>,
         lfo_values: &LfoTargetValues,
+        modulation: OperatorParameterModulation,
         time_per_sample: TimePerSample,
         voice_base_frequency: f64,
+        anti_alias: bool,
     ) {
         const VOLUME_INDICES: [u8; NUM_OPERATORS] = OperatorParameter::Volume.index_array();
         const MIX_INDICES: [u8; NUM_OPERATORS] = OperatorParameter::MixOut.index_array();
@@ -520,6 +806,35 @@ mod gen {
 
         operator_data.wave_type = operator_parameters.wave_type.get_value();
 
+        if operator_data.wave_type == WaveType::WhiteNoise {
+            let [l, r] = voice_operator.noise.next_pair();
+
+            if operator_parameters.noise_stereo.get_value() {
+                let offset = sample_index * 2;
+
+                operator_data.noise[offset..offset + 2].copy_from_slice(&[l, r]);
+            } else {
+                set_value_for_both_channels(&mut operator_data.noise, sample_index, l);
+            }
+        }
+
+        set_value_for_both_channels(
+            &mut operator_data.wavetable_position,
+            sample_index,
+            operator_parameters.wavetable_position.get_value() as f64,
+        );
+        set_value_for_both_channels(
+            &mut operator_data.phase_distortion_knee,
+            sample_index,
+            0.5 - PHASE_DISTORTION_MAX_KNEE_OFFSET
+                * operator_parameters.phase_distortion.get_value() as f64,
+        );
+        set_value_for_both_channels(
+            &mut operator_data.spread_delay,
+            sample_index,
+            operator_parameters.spread.get_value() as f64 / 1000.0 / time_per_sample.0,
+        );
+
         if let Some(p) = &mut operator_parameters.mod_targets {
             operator_data.modulation_targets = p.get_value();
         }
@@ -534,16 +849,24 @@ mod gen {
             envelope_volume as f64,
         );
 
-        let volume = operator_parameters
-            .volume
-            .get_value_with_lfo_addition(lfo_values.get(VOLUME_INDICES[operator_index]));
+        let volume = operator_parameters.volume.get_value_with_lfo_addition(
+            OperatorParameterModulation::add_to_lfo_addition(
+                modulation.volume,
+                lfo_values.get(VOLUME_INDICES[operator_index]),
+            ),
+        );
 
         let volume_active = operator_parameters.active.get_value();
+        let volume_in_note_range = if voice_operator.in_note_range {
+            1.0
+        } else {
+            0.0
+        };
 
         set_value_for_both_channels(
             &mut operator_data.volume,
             sample_index,
-            (volume * volume_active) as f64,
+            (volume * volume_active * volume_in_note_range) as f64,
         );
 
         let mix_out = operator_parameters
@@ -553,7 +876,10 @@ mod gen {
         set_value_for_both_channels(&mut operator_data.mix_out, sample_index, mix_out as f64);
 
         let mod_out = operator_parameters.mod_out.as_mut().map_or(0.0, |p| {
-            p.get_value_with_lfo_addition(lfo_values.get(MOD_INDICES[operator_index]))
+            p.get_value_with_lfo_addition(OperatorParameterModulation::add_to_lfo_addition(
+                modulation.mod_out,
+                lfo_values.get(MOD_INDICES[operator_index]),
+            ))
         });
 
         set_value_for_both_channels(&mut operator_data.mod_out, sample_index, mod_out as f64);
@@ -564,9 +890,38 @@ mod gen {
 
         set_value_for_both_channels(&mut operator_data.feedback, sample_index, feedback as f64);
 
-        let panning = operator_parameters
-            .panning
-            .get_value_with_lfo_addition(lfo_values.get(PANNING_INDICES[operator_index]));
+        operator_data.cross_feedback_target = operator_parameters
+            .cross_feedback_target
+            .as_ref()
+            .and_then(|p| p.get_value().index());
+
+        let cross_feedback = match operator_parameters.cross_feedback.as_ref() {
+            Some(p) if operator_data.cross_feedback_target.is_some() => p.get_value(),
+            _ => 0.0,
+        };
+
+        set_value_for_both_channels(
+            &mut operator_data.cross_feedback,
+            sample_index,
+            cross_feedback as f64,
+        );
+
+        {
+            // Consume input so that it is only used once
+            let [l, r] = ::std::mem::take(&mut voice_operator.cross_feedback_input);
+
+            let sample_index_offset = sample_index * 2;
+
+            operator_data.cross_feedback_input[sample_index_offset] = l;
+            operator_data.cross_feedback_input[sample_index_offset + 1] = r;
+        }
+
+        let panning = operator_parameters.panning.get_value_with_lfo_addition(
+            OperatorParameterModulation::add_to_lfo_addition(
+                modulation.panning,
+                lfo_values.get(PANNING_INDICES[operator_index]),
+            ),
+        );
 
         set_value_for_both_channels(&mut operator_data.panning, sample_index, panning as f64);
 
@@ -600,13 +955,34 @@ mod gen {
             .get_value_with_lfo_addition(lfo_values.get(FREE_INDICES[operator_index]));
         let frequency_fine = operator_parameters
             .frequency_fine
-            .get_value_with_lfo_addition(lfo_values.get(FINE_INDICES[operator_index]));
+            .get_value_with_lfo_addition(OperatorParameterModulation::add_to_lfo_addition(
+                modulation.frequency_fine,
+                lfo_values.get(FINE_INDICES[operator_index]),
+            ));
 
         let frequency =
             voice_base_frequency * frequency_ratio.value * frequency_free * frequency_fine;
-        let new_phase = voice_operator.last_phase.0 + frequency * time_per_sample.0;
+        let phase_increment = frequency * time_per_sample.0;
+        let new_phase = voice_operator.last_phase.0 + phase_increment;
 
         set_value_for_both_channels(&mut operator_data.phase, sample_index, new_phase);
+        set_value_for_both_channels(
+            &mut operator_data.phase_increment,
+            sample_index,
+            phase_increment,
+        );
+
+        let modulation_input_factor = if anti_alias {
+            anti_alias_modulation_factor(phase_increment)
+        } else {
+            1.0
+        };
+
+        set_value_for_both_channels(
+            &mut operator_data.modulation_input_factor,
+            sample_index,
+            modulation_input_factor,
+        );
 
         // Save phase
         voice_operator.last_phase.0 = new_phase;
@@ -615,24 +991,54 @@ mod gen {
     #[feature_gate]
     #[target_feature_enable]
     unsafe fn gen_audio(
-        rng: &mut fastrand::Rng,
+        wavetables: &[Option<Arc<Wavetable>>; NUM_OPERATORS],
         volume_velocity_sensitivity: [f64; Pd::WIDTH],
-        active_voices: &[VoiceData<{ Pd::WIDTH }>],
+        mono_fold_down: [f64; Pd::WIDTH],
+        active_voices: &mut [VoiceData<{ Pd::WIDTH }>],
+        modulation_input_peaks: &mut [f64; NUM_OPERATORS],
+        // Clipping flag, or None if master processing is off, in which
+        // case output is written before mono fold-down, master volume
+        // factor and limiting
+        opt_clipped: Option<&mut bool>,
+        polyphonic_voices: &mut IndexMap<u8, Voice>,
+        monophonic_voice: &mut Voice,
         audio_buffer_lefts: &mut [f32],
         audio_buffer_rights: &mut [f32],
     ) {
         // Pd::SAMPLES * 2 because of two channels. Even index = left channel
         let mut total_mix_out = Pd::new_zeroed();
+        let mut max_modulation_inputs = [Pd::new_zeroed(); 4];
 
-        for voice_data in active_voices.iter() {
+        for voice_data in active_voices.iter_mut() {
             let operator_generate_audio = run_operator_dependency_analysis(voice_data);
 
-            // Voice modulation input storage, indexed by operator
-            let mut voice_modulation_inputs = [Pd::new_zeroed(); 4];
+            // Voice modulation input storage, indexed by operator. Starts
+            // out with cross feedback from previous sample.
+            let mut voice_modulation_inputs: [Pd; 4] = ::std::array::from_fn(|operator_index| {
+                Pd::from_arr(voice_data.operators[operator_index].cross_feedback_input)
+            });
+            let mut cross_feedback_outputs = [Pd::new_zeroed(); 4];
             let mut voice_mix_out = Pd::new_zeroed();
 
             let key_velocity = Pd::from_arr(voice_data.key_velocity);
 
+            // Spread delay lines are stored in voice. Voice might already
+            // have been removed if it was deactivated during extraction,
+            // in which case spread is skipped.
+            let mut opt_voice = if voice_data
+                .operators
+                .iter()
+                .any(|operator| operator.spread_delay.iter().any(|delay| *delay > 0.0))
+            {
+                if voice_data.voice_index == 128 {
+                    Some(&mut *monophonic_voice)
+                } else {
+                    polyphonic_voices.get_mut(&voice_data.voice_index)
+                }
+            } else {
+                None
+            };
+
             // Go through operators downwards, starting with operator 4
             for operator_index in (0..4).map(|i| 3 - i) {
                 // Possibly skip generation based on previous dependency analysis
@@ -642,21 +1048,50 @@ mod gen {
 
                 let operator_voice_data = &voice_data.operators[operator_index];
 
-                let (mix_out, mod_out) = gen_voice_operator_audio(
-                    rng,
+                voice_modulation_inputs[operator_index] = voice_modulation_inputs[operator_index]
+                    * Pd::from_arr(operator_voice_data.modulation_input_factor);
+
+                max_modulation_inputs[operator_index] = max_modulation_inputs[operator_index]
+                    .max(voice_modulation_inputs[operator_index].abs());
+
+                let (mix_out, mod_out, cross_feedback_out) = gen_voice_operator_audio(
+                    wavetables[operator_index].as_deref(),
                     operator_voice_data,
                     voice_modulation_inputs[operator_index],
                     key_velocity,
                 );
 
+                let mix_out = match opt_voice.as_deref_mut() {
+                    Some(voice) if operator_voice_data.spread_delay.iter().any(|d| *d > 0.0) => {
+                        spread(
+                            mix_out,
+                            &mut voice.operators[operator_index].spread_delay,
+                            operator_voice_data.spread_delay,
+                        )
+                    }
+                    _ => mix_out,
+                };
+
                 voice_mix_out += mix_out;
 
                 // Add modulation output to target operators' modulation inputs
                 for target in operator_voice_data.modulation_targets.active_indices() {
                     voice_modulation_inputs[target] += mod_out;
                 }
+
+                // Cross feedback targets have already been processed, so
+                // their input will be used during next sample
+                if let Some(target) = operator_voice_data.cross_feedback_target {
+                    cross_feedback_outputs[target] += cross_feedback_out;
+                }
             }
 
+            voice_data.cross_feedback_outputs = ::std::array::from_fn(|target| {
+                let arr = cross_feedback_outputs[target].to_arr();
+
+                [arr[Pd::WIDTH - 2], arr[Pd::WIDTH - 1]]
+            });
+
             let master_volume = Pd::from_arr(voice_data.master_volume);
             let volume_velocity_factor =
                 velocity_factor(Pd::from_arr(volume_velocity_sensitivity), key_velocity);
@@ -664,7 +1099,46 @@ mod gen {
             total_mix_out += voice_mix_out * volume_velocity_factor * master_volume;
         }
 
-        let total_mix_out_arr = (total_mix_out * Pd::new(MASTER_VOLUME_FACTOR))
+        for (peak, max_modulation_input) in
+            modulation_input_peaks.iter_mut().zip(max_modulation_inputs)
+        {
+            for value in max_modulation_input.to_arr() {
+                *peak = peak.max(value);
+            }
+        }
+
+        let clipped = match opt_clipped {
+            Some(clipped) => clipped,
+            None => {
+                for (sample_index, chunk) in total_mix_out.to_arr().chunks_exact(2).enumerate() {
+                    audio_buffer_lefts[sample_index] = chunk[0] as f32;
+                    audio_buffer_rights[sample_index] = chunk[1] as f32;
+                }
+
+                return;
+            }
+        };
+
+        let total_mix_out = {
+            let mono_fold_down = Pd::from_arr(mono_fold_down);
+            let mono = total_mix_out.pairwise_horizontal_sum() * Pd::new(0.5);
+
+            total_mix_out + (mono - total_mix_out) * mono_fold_down
+        };
+
+        let total_mix_out = total_mix_out * Pd::new(MASTER_VOLUME_FACTOR);
+
+        // Detect output above 0 dBFS before hard limit is applied
+        if total_mix_out
+            .abs()
+            .to_arr()
+            .iter()
+            .any(|sample| *sample > 1.0)
+        {
+            *clipped = true;
+        }
+
+        let total_mix_out_arr = total_mix_out
             .min(Pd::new(LIMIT))
             .max(Pd::new(-LIMIT))
             .to_arr();
@@ -678,11 +1152,11 @@ mod gen {
     #[feature_gate]
     #[target_feature_enable]
     unsafe fn gen_voice_operator_audio(
-        rng: &mut fastrand::Rng,
+        wavetable: Option<&Wavetable>,
         operator_data: &VoiceOperatorData<{ Pd::WIDTH }>,
         modulation_inputs: Pd,
         key_velocity: Pd,
-    ) -> (Pd, Pd) {
+    ) -> (Pd, Pd, Pd) {
         let phase = Pd::from_arr(operator_data.phase);
         let feedback = {
             let feedback = Pd::from_arr(operator_data.feedback);
@@ -691,40 +1165,64 @@ mod gen {
             feedback * velocity_factor(velocity_sensitivity, key_velocity)
         };
 
-        let sample = match operator_data.wave_type {
-            WaveType::Sine => {
-                let phase = phase * Pd::new(TAU);
-                let feedback = feedback * phase.fast_sin();
+        // A loaded wavetable replaces the selected wave type
+        let sample = if let Some(wavetable) = wavetable {
+            let phase = phase.to_arr();
+            let position = operator_data.wavetable_position;
 
-                (phase + feedback + modulation_inputs).fast_sin()
-            }
-            WaveType::Square => {
-                let feedback = feedback * phase.square();
+            let mut samples = <Pd as SimdPackedDouble>::Arr::default();
 
-                (phase + feedback + modulation_inputs).square()
+            for (i, sample) in samples.iter_mut().enumerate() {
+                *sample = wavetable.sample(phase[i], position[i]);
             }
-            WaveType::Triangle => {
-                let feedback = feedback * phase.triangle();
 
-                (phase + feedback + modulation_inputs).triangle()
-            }
-            WaveType::Saw => {
-                let feedback = feedback * phase.saw();
+            let feedback = feedback * Pd::from_arr(samples);
+            let phase = Pd::from_arr(phase) + feedback + modulation_inputs;
+
+            let phase = phase.to_arr();
 
-                (phase + feedback + modulation_inputs).saw()
+            for (i, sample) in samples.iter_mut().enumerate() {
+                *sample = wavetable.sample(phase[i], position[i]);
             }
-            WaveType::WhiteNoise => {
-                let mut random_numbers = <Pd as SimdPackedDouble>::Arr::default();
 
-                for chunk in random_numbers.chunks_exact_mut(2) {
-                    let random = rng.f64();
+            Pd::from_arr(samples)
+        } else {
+            match operator_data.wave_type {
+                WaveType::Sine => {
+                    let phase = phase_distortion(phase, operator_data.phase_distortion_knee);
+                    let phase = phase * Pd::new(TAU);
+                    let feedback = feedback * phase.fast_sin();
+
+                    (phase + feedback + modulation_inputs).fast_sin()
+                }
+                WaveType::Square => {
+                    let feedback = feedback * phase.square();
+
+                    (phase + feedback + modulation_inputs).square()
+                }
+                WaveType::Triangle => {
+                    let feedback = feedback * phase.triangle();
+
+                    (phase + feedback + modulation_inputs).triangle()
+                }
+                WaveType::Saw => {
+                    let feedback = feedback * phase.saw();
+
+                    (phase + feedback + modulation_inputs).saw()
+                }
+                WaveType::SquareBandLimited => {
+                    let phase_increment = Pd::from_arr(operator_data.phase_increment);
+                    let feedback = feedback * phase.square_band_limited(phase_increment);
 
-                    chunk[0] = random;
-                    chunk[1] = random;
+                    (phase + feedback + modulation_inputs).square_band_limited(phase_increment)
                 }
+                WaveType::SawBandLimited => {
+                    let phase_increment = Pd::from_arr(operator_data.phase_increment);
+                    let feedback = feedback * phase.saw_band_limited(phase_increment);
 
-                // Convert random numbers to range -1.0 to 1.0
-                Pd::new(2.0) * (Pd::from_arr(random_numbers) - Pd::new(0.5))
+                    (phase + feedback + modulation_inputs).saw_band_limited(phase_increment)
+                }
+                WaveType::WhiteNoise => Pd::from_arr(operator_data.noise),
             }
         };
 
@@ -760,8 +1258,14 @@ mod gen {
 
             sample * pan_factor * velocity_factor * mod_out
         };
+        let cross_feedback_out = {
+            let pan_factor = linear_panning_factor(panning);
+            let cross_feedback = Pd::from_arr(operator_data.cross_feedback);
+
+            sample * pan_factor * cross_feedback
+        };
 
-        (mix_out, mod_out)
+        (mix_out, mod_out, cross_feedback_out)
     }
 
     /// Operator dependency analysis to allow skipping audio generation when possible
@@ -770,6 +1274,7 @@ mod gen {
     unsafe fn run_operator_dependency_analysis(voice_data: &VoiceData<{ Pd::WIDTH }>) -> [bool; 4] {
         let mut operator_generate_audio = [true; 4];
         let mut operator_mix_out_active = [false; 4];
+        let mut operator_cross_feedback_active = [false; 4];
 
         for operator_index in 0..4 {
             let volume = Pd::from_arr(voice_data.operators[operator_index].volume);
@@ -779,10 +1284,13 @@ mod gen {
             let volume_active = volume.any_over_zero();
             let mix_out_active = mix_out.any_over_zero();
             let mod_out_active = mod_out.any_over_zero();
+            let cross_feedback_active =
+                Pd::from_arr(voice_data.operators[operator_index].cross_feedback).any_over_zero();
 
             operator_generate_audio[operator_index] =
-                volume_active & (mod_out_active | mix_out_active);
+                volume_active & (mod_out_active | mix_out_active | cross_feedback_active);
             operator_mix_out_active[operator_index] = mix_out_active;
+            operator_cross_feedback_active[operator_index] = cross_feedback_active;
         }
 
         for operator_index in 1..4 {
@@ -791,7 +1299,12 @@ mod gen {
                 .active_indices()
                 .all(|mod_target| !operator_generate_audio[mod_target]);
 
-            if all_targets_inactive & !operator_mix_out_active[operator_index] {
+            // Cross feedback targets are processed before the source
+            // operator, so sending cross feedback keeps operator active
+            if all_targets_inactive
+                & !operator_mix_out_active[operator_index]
+                & !operator_cross_feedback_active[operator_index]
+            {
                 operator_generate_audio[operator_index] = false;
             }
         }
@@ -829,6 +1342,34 @@ mod gen {
         (pan * Pd::new_from_pair(-1.0, 1.0)).max(Pd::new_zeroed())
     }
 
+    /// Delay right channel of operator output, one sample at a time
+    #[feature_gate]
+    #[target_feature_enable]
+    unsafe fn spread(mix_out: Pd, delay_line: &mut SpreadDelayLine, delay: [f64; Pd::WIDTH]) -> Pd {
+        let mut samples = mix_out.to_arr();
+
+        for (channels, delay) in samples.chunks_exact_mut(2).zip(delay.chunks_exact(2)) {
+            channels[1] = delay_line.process(channels[1], delay[1]);
+        }
+
+        Pd::from_arr(samples)
+    }
+
+    /// Casio CZ style phase distortion. Fractional phase rises to 0.5 at
+    /// knee and then to 1.0 at end of cycle, each segment linearly.
+    #[feature_gate]
+    #[target_feature_enable]
+    #[inline]
+    unsafe fn phase_distortion(phase: Pd, knee: [f64; Pd::WIDTH]) -> Pd {
+        let phase = phase - phase.floor();
+
+        let rise_factor = Pd::from_arr(knee.map(|knee| 0.5 / knee));
+        let fall_factor = Pd::from_arr(knee.map(|knee| 0.5 / (1.0 - knee)));
+        let knee = Pd::from_arr(knee);
+
+        phase.min(knee) * rise_factor + (phase - knee).max(Pd::new_zeroed()) * fall_factor
+    }
+
     #[feature_gate]
     #[target_feature_enable]
     #[inline]
@@ -868,6 +1409,39 @@ mod gen {
             }
         }
 
+        #[feature_gate]
+        #[test_feature_gate]
+        #[test]
+        fn test_phase_distortion() {
+            unsafe {
+                let undistorted = [0.5; Pd::WIDTH];
+
+                assert_eq!(
+                    Pd::to_arr(phase_distortion(Pd::new(0.25), undistorted)),
+                    Pd::to_arr(Pd::new(0.25))
+                );
+                assert_eq!(
+                    Pd::to_arr(phase_distortion(Pd::new(1.75), undistorted)),
+                    Pd::to_arr(Pd::new(0.75))
+                );
+
+                let distorted = [0.25; Pd::WIDTH];
+
+                assert_eq!(
+                    Pd::to_arr(phase_distortion(Pd::new(0.125), distorted)),
+                    Pd::to_arr(Pd::new(0.25))
+                );
+                assert_eq!(
+                    Pd::to_arr(phase_distortion(Pd::new(0.25), distorted)),
+                    Pd::to_arr(Pd::new(0.5))
+                );
+                assert_eq!(
+                    Pd::to_arr(phase_distortion(Pd::new(1.625), distorted)),
+                    Pd::to_arr(Pd::new(0.75))
+                );
+            }
+        }
+
         #[feature_gate]
         #[test_feature_gate]
         #[test]
@@ -897,3 +1471,573 @@ mod gen {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::common::{NoteEvent, NoteEventInner, SampleRate};
+
+    use super::*;
+
+    /// Audio state at 44.1 kHz set up by configure, with a note (key 60,
+    /// velocity 100) enqueued at the first sample
+    fn note_audio_state(configure: impl FnOnce(&mut AudioState)) -> AudioState {
+        let mut audio = AudioState::default();
+
+        audio.set_sample_rate(SampleRate(44100.0));
+
+        configure(&mut audio);
+
+        audio.enqueue_note_event(NoteEvent {
+            delta_frames: 0,
+            event: NoteEventInner::Midi {
+                data: [0x90, 60, 100],
+            },
+        });
+
+        audio
+    }
+
+    fn render(audio: &mut AudioState, num_samples: usize) -> (Vec<f32>, Vec<f32>) {
+        let mut lefts = vec![0.0f32; num_samples];
+        let mut rights = vec![0.0f32; num_samples];
+
+        process_f32_runtime_select(audio, &mut lefts, &mut rights, 0, |_| ());
+
+        (lefts, rights)
+    }
+
+    /// Render note (see note_audio_state) in a single buffer
+    fn render_note(
+        configure: impl FnOnce(&mut AudioState),
+        num_samples: usize,
+    ) -> (Vec<f32>, Vec<f32>) {
+        render(&mut note_audio_state(configure), num_samples)
+    }
+
+    #[test]
+    fn test_anti_alias_modulation_factor() {
+        assert_eq!(anti_alias_modulation_factor(0.0), 1.0);
+        assert_eq!(anti_alias_modulation_factor(ANTI_ALIAS_ROLLOFF_START), 1.0);
+        assert_eq!(
+            anti_alias_modulation_factor(ANTI_ALIAS_ROLLOFF_START * 4.0),
+            0.25
+        );
+    }
+
+    #[test]
+    fn test_silent_buffer_is_skipped() {
+        let mut audio = AudioState::default();
+
+        audio.set_sample_rate(SampleRate(44100.0));
+
+        let mut lefts = [1.0f32; 64];
+        let mut rights = [1.0f32; 64];
+
+        // Hosts send transport info every buffer, which must not prevent
+        // skipping generation
+        audio.enqueue_note_event(NoteEvent {
+            delta_frames: 0,
+            event: NoteEventInner::Transport {
+                playing: true,
+                song_position: Some(0.0),
+            },
+        });
+
+        assert!(process_f32_runtime_select(
+            &mut audio,
+            &mut lefts,
+            &mut rights,
+            0,
+            |_| ()
+        ));
+        assert!(lefts.iter().chain(rights.iter()).all(|s| *s == 0.0));
+        assert!(audio.is_silent());
+
+        audio.enqueue_note_event(NoteEvent {
+            delta_frames: 8,
+            event: NoteEventInner::Midi {
+                data: [0x90, 60, 100],
+            },
+        });
+
+        assert!(!process_f32_runtime_select(
+            &mut audio,
+            &mut lefts,
+            &mut rights,
+            0,
+            |_| ()
+        ));
+        assert!(lefts.iter().any(|s| *s != 0.0));
+    }
+
+    #[test]
+    fn test_overdue_note_event_processed_at_buffer_end() {
+        let mut audio = AudioState::default();
+
+        audio.set_sample_rate(SampleRate(44100.0));
+        audio.enqueue_note_event(NoteEvent {
+            delta_frames: 100,
+            event: NoteEventInner::Midi {
+                data: [0x90, 60, 100],
+            },
+        });
+
+        let (lefts, _) = render(&mut audio, 64);
+
+        assert_eq!(audio.get_held_keys(), 1 << 60);
+        assert!(audio.pending_note_events.is_empty());
+        assert!(lefts[..63].iter().all(|s| *s == 0.0));
+    }
+
+    #[test]
+    fn test_silent_buffers_advance_state() {
+        use crate::parameters::{MasterParameter, MasterVolumeValue, Parameter, ParameterValue};
+
+        const NUM_SAMPLES: usize = 4410;
+
+        let mut audio = AudioState::default();
+
+        audio.set_sample_rate(SampleRate(44100.0));
+        audio.set_parameter_from_patch(Parameter::Master(MasterParameter::Volume), 0.2);
+
+        let mut lefts = [0.0f32; NUM_SAMPLES];
+        let mut rights = [0.0f32; NUM_SAMPLES];
+
+        assert!(process_f32_runtime_select(
+            &mut audio,
+            &mut lefts,
+            &mut rights,
+            0,
+            |_| ()
+        ));
+        assert_eq!(audio.sample_counter, NUM_SAMPLES as u64);
+
+        audio.enqueue_note_event(NoteEvent {
+            delta_frames: 0,
+            event: NoteEventInner::Midi {
+                data: [0x90, 60, 100],
+            },
+        });
+
+        assert!(!process_f32_runtime_select(
+            &mut audio,
+            &mut lefts[..1],
+            &mut rights[..1],
+            0,
+            |_| ()
+        ));
+
+        // Interpolation finished while silent
+        let target = MasterVolumeValue::new_from_patch(0.2).get();
+
+        assert!((audio.parameters.master_volume.get_value() - target).abs() < 1.0e-4);
+    }
+
+    #[test]
+    fn test_lfos_disabled() {
+        use crate::parameters::lfo_target::LfoTargetParameter;
+        use crate::parameters::{
+            Lfo1TargetParameterValue, LfoAmountValue, LfoParameter, Parameter, ParameterValue,
+        };
+
+        fn render(lfo_active: bool, lfos_disabled: bool) -> Vec<f32> {
+            let configure = |audio: &mut AudioState| {
+                audio.set_lfos_disabled(lfos_disabled);
+
+                if lfo_active {
+                    audio.set_parameter_from_patch(
+                        Parameter::Lfo(0, LfoParameter::Target),
+                        Lfo1TargetParameterValue::new_from_audio(LfoTargetParameter::new(
+                            Parameter::Operator(0, OperatorParameter::Volume),
+                        ))
+                        .to_patch(),
+                    );
+                    audio.set_parameter_from_patch(
+                        Parameter::Lfo(0, LfoParameter::Amount),
+                        LfoAmountValue::new_from_audio(1.0).to_patch(),
+                    );
+                }
+            };
+
+            render_note(configure, 4096).0
+        }
+
+        let unmodulated = render(false, false);
+
+        assert!(render(true, false) != unmodulated);
+        assert!(render(true, true) == unmodulated);
+    }
+
+    #[test]
+    fn test_listen_operator() {
+        use crate::parameters::{OperatorWaveTypeValue, Parameter, ParameterValue};
+
+        fn render(opt_listen_operator: Option<usize>) -> Vec<f32> {
+            let configure = |audio: &mut AudioState| {
+                audio.set_listen_operator(opt_listen_operator);
+                audio.set_parameter_from_patch(
+                    Parameter::Operator(1, OperatorParameter::WaveType),
+                    OperatorWaveTypeValue::new_from_audio(WaveType::Square).to_patch(),
+                );
+            };
+
+            render_note(configure, 1024).0
+        }
+
+        let reference = render(None);
+
+        // Only operator 1 is mixed to output by default
+        assert!(render(Some(0)) == reference);
+
+        let listen_operator_2 = render(Some(1));
+
+        assert!(listen_operator_2.iter().any(|s| *s != 0.0));
+        assert!(listen_operator_2 != reference);
+    }
+
+    #[test]
+    fn test_noise_stereo() {
+        use crate::parameters::operator_noise_stereo::OperatorNoiseStereoValue;
+        use crate::parameters::{OperatorWaveTypeValue, Parameter, ParameterValue};
+
+        fn render(stereo: bool) -> (Vec<f32>, Vec<f32>) {
+            let configure = |audio: &mut AudioState| {
+                audio.set_parameter_from_patch(
+                    Parameter::Operator(0, OperatorParameter::WaveType),
+                    OperatorWaveTypeValue::new_from_audio(WaveType::WhiteNoise).to_patch(),
+                );
+                audio.set_parameter_from_patch(
+                    Parameter::Operator(0, OperatorParameter::NoiseStereo),
+                    OperatorNoiseStereoValue::new_from_audio(stereo).to_patch(),
+                );
+            };
+
+            render_note(configure, 256)
+        }
+
+        let (lefts, rights) = render(false);
+
+        assert!(lefts.iter().any(|s| *s != 0.0));
+        assert_eq!(lefts, rights);
+
+        let (lefts, rights) = render(true);
+
+        assert!(lefts.iter().any(|s| *s != 0.0));
+        assert!(rights.iter().any(|s| *s != 0.0));
+        assert_ne!(lefts, rights);
+    }
+
+    #[test]
+    fn test_mono_fold_down() {
+        use crate::parameters::operator_noise_stereo::OperatorNoiseStereoValue;
+        use crate::parameters::{OperatorWaveTypeValue, Parameter, ParameterValue};
+
+        let mut audio = note_audio_state(|audio| {
+            audio.set_parameter_from_patch(
+                Parameter::Operator(0, OperatorParameter::WaveType),
+                OperatorWaveTypeValue::new_from_audio(WaveType::WhiteNoise).to_patch(),
+            );
+            audio.set_parameter_from_patch(
+                Parameter::Operator(0, OperatorParameter::NoiseStereo),
+                OperatorNoiseStereoValue::new_from_audio(true).to_patch(),
+            );
+            audio.set_mono_fold_down(true);
+        });
+
+        // Wait for fade to mono to finish
+        render(&mut audio, 4096);
+
+        let (lefts, rights) = render(&mut audio, 256);
+
+        assert!(lefts.iter().any(|s| *s != 0.0));
+
+        for (l, r) in lefts.into_iter().zip(rights) {
+            assert!((l - r).abs() < 1.0e-4);
+        }
+    }
+
+    /// Rendering the same MIDI sequence must give identical output regardless
+    /// of how the host splits it into buffers
+    #[test]
+    fn test_output_independent_of_buffer_size() {
+        const NUM_SAMPLES: usize = 4096;
+
+        // MIDI events with absolute sample positions
+        const EVENTS: &[(usize, [u8; 3])] = &[
+            (0, [0x90, 60, 100]),
+            (1, [0x90, 64, 90]),
+            (17, [0xE0, 0, 80]),
+            (100, [0xB0, 1, 64]),
+            (1023, [0x90, 67, 80]),
+            (1024, [0x80, 60, 0]),
+            (1025, [0xE0, 0, 40]),
+            (2049, [0x80, 64, 0]),
+            (3000, [0x90, 72, 127]),
+            (3001, [0x80, 67, 0]),
+        ];
+
+        /// Process with fallback implementation only, one sample at a time
+        fn process_fallback(audio: &mut AudioState, lefts: &mut [f32], rights: &mut [f32]) {
+            for position in 0..lefts.len() {
+                unsafe {
+                    Fallback::process_f32(
+                        audio,
+                        &mut lefts[position..position + 1],
+                        &mut rights[position..position + 1],
+                        position,
+                    );
+                }
+            }
+        }
+
+        fn process_runtime_select(audio: &mut AudioState, lefts: &mut [f32], rights: &mut [f32]) {
+            process_f32_runtime_select(audio, lefts, rights, 0, |_| ());
+        }
+
+        fn render(
+            process: fn(&mut AudioState, &mut [f32], &mut [f32]),
+            buffer_size: usize,
+        ) -> (Vec<f32>, Vec<f32>) {
+            let mut audio = AudioState::default();
+
+            audio.set_sample_rate(SampleRate(44100.0));
+
+            let mut lefts = vec![0.0f32; NUM_SAMPLES];
+            let mut rights = vec![0.0f32; NUM_SAMPLES];
+
+            for (buffer_index, (lefts, rights)) in lefts
+                .chunks_mut(buffer_size)
+                .zip(rights.chunks_mut(buffer_size))
+                .enumerate()
+            {
+                let start = buffer_index * buffer_size;
+                let end = start + lefts.len();
+
+                audio.enqueue_note_events(
+                    EVENTS
+                        .iter()
+                        .filter(|(time, _)| (start..end).contains(time))
+                        .map(|(time, data)| NoteEvent {
+                            delta_frames: (time - start) as u32,
+                            event: NoteEventInner::Midi { data: *data },
+                        }),
+                );
+
+                process(&mut audio, lefts, rights);
+            }
+
+            (lefts, rights)
+        }
+
+        const BUFFER_SIZES: [usize; 6] = [1, 2, 3, 17, 64, 1024];
+
+        let reference = render(process_fallback, NUM_SAMPLES);
+
+        assert!(reference.0.iter().any(|s| *s != 0.0));
+
+        for buffer_size in BUFFER_SIZES {
+            assert!(
+                render(process_fallback, buffer_size) == reference,
+                "output differs with buffer size {}",
+                buffer_size
+            );
+        }
+
+        // With runtime selection, full buffers and leftover samples may be
+        // processed by different SIMD implementations (e.g., AVX for sample
+        // pairs and SSE2 for a trailing sample), whose sin approximations
+        // don't give bit-identical results
+        const TOLERANCE: f32 = 1.0e-4;
+
+        let reference = render(process_runtime_select, NUM_SAMPLES);
+
+        for buffer_size in BUFFER_SIZES {
+            let (lefts, rights) = render(process_runtime_select, buffer_size);
+
+            let max_difference = lefts
+                .iter()
+                .chain(rights.iter())
+                .zip(reference.0.iter().chain(reference.1.iter()))
+                .map(|(a, b)| (a - b).abs())
+                .fold(0.0f32, f32::max);
+
+            assert!(
+                max_difference < TOLERANCE,
+                "output differs by {} with buffer size {}",
+                max_difference,
+                buffer_size
+            );
+        }
+    }
+
+    #[test]
+    fn test_clipping_is_detected() {
+        use crate::parameters::{MasterVolumeValue, ParameterValue};
+
+        /// Render note at key 60 and additional keys at full velocity
+        fn clipped(additional_keys: &[u8]) -> bool {
+            let mut audio = note_audio_state(|audio| {
+                audio.set_parameter_from_patch(
+                    Parameter::Master(MasterParameter::Volume),
+                    MasterVolumeValue::new_from_audio(2.0).to_patch(),
+                );
+                audio.enqueue_note_events(additional_keys.iter().map(|key| NoteEvent {
+                    delta_frames: 0,
+                    event: NoteEventInner::Midi {
+                        data: [0x90, *key, 127],
+                    },
+                }));
+            });
+
+            render(&mut audio, 4096);
+
+            audio.take_clipped()
+        }
+
+        assert!(!clipped(&[]));
+        assert!(clipped(&[48, 52, 55, 64, 67, 72, 76, 79, 84]));
+    }
+
+    /// Feed random note on/off and sustain pedal events, including
+    /// overlapping same-key notes and missing or stray note offs, and check
+    /// that held keys and voices always match keys that are down, and that
+    /// no voices remain once all keys are released
+    #[test]
+    fn test_voice_accounting() {
+        use crate::parameters::voice_mode::{VoiceMode, VoiceModeValue};
+        use crate::parameters::ParameterValue;
+
+        const BUFFER_SIZE: usize = 64;
+        const NUM_BUFFERS: usize = 200;
+        const FIRST_KEY: u8 = 60;
+        const NUM_KEYS: u8 = 8;
+
+        fn check_invariants(audio: &AudioState, keys_down: u128, context: &str) {
+            assert_eq!(audio.get_held_keys(), keys_down, "{}", context);
+
+            match audio.parameters.voice_mode.get_value() {
+                VoiceMode::Polyphonic => {
+                    let pressed_voices = audio
+                        .polyphonic_voices
+                        .values()
+                        .filter(|voice| voice.key_pressed)
+                        .fold(0u128, |keys, voice| keys | (1 << voice.key()));
+
+                    assert_eq!(pressed_voices, keys_down, "{}", context);
+                }
+                VoiceMode::Monophonic => {
+                    let pressed_keys = audio
+                        .monophonic_pressed_keys
+                        .keys()
+                        .fold(0u128, |keys, key| keys | (1 << key));
+
+                    assert_eq!(pressed_keys, keys_down, "{}", context);
+                    assert_eq!(
+                        audio.monophonic_voice.key_pressed,
+                        keys_down != 0,
+                        "{}",
+                        context
+                    );
+                }
+            }
+        }
+
+        fn run(voice_mode: VoiceMode, seed: u64) {
+            let mut rng = fastrand::Rng::with_seed(seed);
+
+            let mut audio = AudioState::default();
+
+            audio.set_sample_rate(SampleRate(44100.0));
+            audio.set_parameter_from_patch(
+                Parameter::Master(MasterParameter::VoiceMode),
+                VoiceModeValue::new_from_audio(voice_mode).to_patch(),
+            );
+
+            let mut lefts = [0.0f32; BUFFER_SIZE];
+            let mut rights = [0.0f32; BUFFER_SIZE];
+
+            let mut keys_down = 0u128;
+
+            for buffer_index in 0..NUM_BUFFERS {
+                let mut events: Vec<(u32, [u8; 3])> = (0..rng.usize(0..4))
+                    .filter_map(|_| {
+                        let delta_frames = rng.u32(0..BUFFER_SIZE as u32);
+                        let key = FIRST_KEY + rng.u8(0..NUM_KEYS);
+
+                        let data = match rng.u8(0..10) {
+                            0..=4 => [0x90, key, rng.u8(1..128)],
+                            // Note on with zero velocity is note off
+                            5 => [0x90, key, 0],
+                            6 | 7 => [0x80, key, 0],
+                            // Note off lost by host, so key stays down
+                            8 => return None,
+                            _ => [0xB0, 64, if rng.bool() { 127 } else { 0 }],
+                        };
+
+                        Some((delta_frames, data))
+                    })
+                    .collect();
+
+                events.sort_by_key(|(delta_frames, _)| *delta_frames);
+
+                for (_, data) in events.iter() {
+                    match data {
+                        [0x90, key, velocity] if *velocity > 0 => keys_down |= 1 << key,
+                        [0x90 | 0x80, key, _] => keys_down &= !(1 << key),
+                        _ => (),
+                    }
+                }
+
+                audio.enqueue_note_events(events.into_iter().map(|(delta_frames, data)| {
+                    NoteEvent {
+                        delta_frames,
+                        event: NoteEventInner::Midi { data },
+                    }
+                }));
+
+                process_f32_runtime_select(&mut audio, &mut lefts, &mut rights, 0, |_| ());
+
+                check_invariants(
+                    &audio,
+                    keys_down,
+                    &format!("{:?}, seed {}, buffer {}", voice_mode, seed, buffer_index),
+                );
+            }
+
+            // Release sustain pedal and all keys that are still down
+            let release_events = ::std::iter::once([0xB0, 64, 0])
+                .chain(
+                    (FIRST_KEY..FIRST_KEY + NUM_KEYS)
+                        .filter(|key| keys_down & (1 << key) != 0)
+                        .map(|key| [0x80, key, 0]),
+                )
+                .map(|data| NoteEvent {
+                    delta_frames: 0,
+                    event: NoteEventInner::Midi { data },
+                });
+
+            audio.enqueue_note_events(release_events);
+
+            let num_tail_buffers =
+                audio.get_tail_length_in_samples() as usize / BUFFER_SIZE + 2 * NUM_BUFFERS;
+
+            for _ in 0..num_tail_buffers {
+                process_f32_runtime_select(&mut audio, &mut lefts, &mut rights, 0, |_| ());
+            }
+
+            let context = format!("{:?}, seed {}, after release", voice_mode, seed);
+
+            check_invariants(&audio, 0, &context);
+
+            assert!(audio.polyphonic_voices.is_empty(), "{}", context);
+            assert!(!audio.monophonic_voice.active, "{}", context);
+            assert!(audio.is_silent(), "{}", context);
+        }
+
+        for voice_mode in [VoiceMode::Polyphonic, VoiceMode::Monophonic] {
+            for seed in 0..8 {
+                run(voice_mode, seed);
+            }
+        }
+    }
+}
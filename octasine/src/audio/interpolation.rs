@@ -106,6 +106,11 @@ impl Interpolator {
         self.cached_value
     }
 
+    /// Returns true if both current and target values are zero
+    pub fn is_zero(&self) -> bool {
+        self.cached_value == 0.0 && self.target_value == 0.0
+    }
+
     fn restart_interpolation(&mut self) {
         let num_steps = self.interpolation_duration.samples(self.sample_rate);
         let step_size = (self.target_value - self.current_value) / (num_steps as f32);
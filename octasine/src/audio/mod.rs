@@ -1,25 +1,35 @@
 pub mod gen;
 mod interpolation;
+mod note_event_queue;
 pub mod parameters;
 pub mod voices;
 
 use std::mem::MaybeUninit;
+use std::sync::Arc;
 
-use fastrand::Rng;
 use ringbuf::{LocalRb, Rb};
 
 use crate::{
     common::*,
     parameters::{
-        glide_active::GlideActive, glide_mode::GlideMode, voice_mode::VoiceMode, Parameter,
+        chord_memory::ChordMemory, glide_active::GlideActive, glide_mode::GlideMode,
+        lfo_transport_sync::LfoTransportSync, note_priority::NotePriority, voice_mode::VoiceMode,
+        MasterParameter, Parameter,
     },
+    wavetable::Wavetable,
 };
 
+use note_event_queue::NoteEventQueue;
+
 use parameters::*;
 use voices::*;
 
 use self::{
-    gen::AudioGenData, parameters::common::AudioParameter, voices::log10_table::Log10Table,
+    gen::AudioGenData,
+    interpolation::{InterpolationDuration, Interpolator},
+    parameters::common::AudioParameter,
+    voices::allocator::VoiceAllocator,
+    voices::log10_table::Log10Table,
 };
 
 #[cfg(feature = "clap")]
@@ -34,26 +44,139 @@ pub struct ClapNoteEnded {
 pub type ClapEndedNotesRb =
     ringbuf::LocalRb<ClapNoteEnded, Vec<::std::mem::MaybeUninit<ClapNoteEnded>>>;
 
+/// Note generated by the plugin, to be sent to host MIDI output
+#[derive(Debug, Clone, Copy)]
+pub struct MidiOutEvent {
+    pub note_on: bool,
+    pub key: u8,
+    pub velocity: KeyVelocity,
+    pub sample_index: u32,
+}
+
+impl MidiOutEvent {
+    pub fn midi_data(&self) -> [u8; 3] {
+        if self.note_on {
+            let velocity = (self.velocity.0 * 127.0).round().clamp(1.0, 127.0) as u8;
+
+            [0b_1001_0000, self.key, velocity]
+        } else {
+            [0b_1000_0000, self.key, 0]
+        }
+    }
+}
+
+pub type MidiOutEventsRb = LocalRb<MidiOutEvent, Vec<MaybeUninit<MidiOutEvent>>>;
+
+/// Maximum number of samples between LFO updates at base sample rate
+pub const MAX_CONTROL_INTERVAL: usize = 64;
+/// Sample rate at which control interval is used as-is. At higher sample
+/// rates, it is scaled up proportionally.
+const CONTROL_INTERVAL_BASE_SAMPLE_RATE: f64 = 48_000.0;
+
 pub struct AudioState {
     sample_rate: SampleRate,
     time_per_sample: TimePerSample,
+    /// Number of samples between LFO updates at base sample rate
+    control_interval: usize,
+    /// Number of samples between LFO updates at current sample rate. LFO
+    /// target values are linearly interpolated in between.
+    samples_per_control_update: usize,
     bpm: BeatsPerMinute,
     bpm_lfo_multiplier: BpmLfoMultiplier,
-    pub global_pitch_bend: GlobalPitchBend,
+    /// Host song position in beats while transport is playing
+    song_position: Option<f64>,
+    transport_playing: bool,
+    pub global_pitch_bend: PitchBend,
+    /// Pitch bend of each MIDI channel, only used for MPE member channels
+    channel_pitch_bends: [PitchBend; 16],
+    /// Channel pressure of each MIDI channel, only used for MPE member
+    /// channels
+    channel_pressures: [Interpolator; 16],
+    /// CC 74 (timbre) of each MIDI channel, only used for MPE member
+    /// channels
+    channel_timbres: [Interpolator; 16],
     sustain_pedal_on: bool,
     parameters: AudioParameters,
-    rng: Rng,
+    /// Wavetables of current patch. Replaced wavetables are kept alive by
+    /// the patch, so dropping references here never deallocates.
+    wavetables: [Option<Arc<Wavetable>>; NUM_OPERATORS],
+    /// Peak modulation input magnitude of each operator since last fetched
+    modulation_input_peaks: [f64; NUM_OPERATORS],
     log10table: Log10Table,
+    /// Polyphonic voices by slot, from least to most recently triggered
     pub polyphonic_voices: IndexMap<u8, Voice>,
+    voice_allocator: VoiceAllocator,
     pub monophonic_voice: Voice,
     monophonic_pressed_keys: IndexMap<u8, Option<i32>>,
-    pending_note_events: LocalRb<NoteEvent, Vec<MaybeUninit<NoteEvent>>>,
+    /// Bitmask of physically held keys (not including keys triggered by
+    /// chord memory)
+    held_keys: u128,
+    /// Chord triggered by each held key, so that chord memory changes
+    /// while keys are held don't cause hanging notes
+    held_key_chords: [ChordMemory; 128],
+    /// Key (after scale quantization) triggered by each held key
+    held_key_quantized: [u8; 128],
+    /// Number of held keys triggering each key (after scale quantization
+    /// and chord memory), so that keys are only released when no held key
+    /// triggers them anymore
+    triggered_key_counts: [u8; 128],
+    /// Bitmask of held keys for which chord notes were sent to MIDI output
+    held_keys_midi_out: u128,
+    /// Number of held keys sending each chord note to MIDI output, so that
+    /// note on and note off are only sent once per note
+    midi_out_key_counts: [u8; 128],
+    pending_note_events: NoteEventQueue,
     opt_last_voice_mode: Option<VoiceMode>,
+    /// Parameter modulation applied to all voices
+    global_operator_modulation: [OperatorParameterModulation; NUM_OPERATORS],
+    /// Output gain, faded to zero when bypassed to avoid clicks
+    bypass_gain: Interpolator,
+    /// Estimated output level when auto-gain was turned on, or None if it
+    /// is off
+    auto_gain_reference_level: Option<f32>,
+    /// Output gain compensating for estimated level changes since
+    /// auto-gain was turned on
+    auto_gain: Interpolator,
+    /// Amount of output summed to mono (0.0 or 1.0 when not fading), for
+    /// checking mono compatibility
+    mono_fold_down: Interpolator,
+    /// Apply bypass, auto-gain, mono fold-down and limiting to output. Off
+    /// for engines whose output is summed with that of other engines before
+    /// master processing is applied once (see gen::process_master_f32).
+    master_processing: bool,
+    /// Ignore LFO target values, e.g., for auditioning unmodulated patch
+    lfos_disabled: bool,
+    /// Operator routed alone to output with full mix, for listening to it
+    /// in isolation
+    listen_operator: Option<usize>,
+    /// Key and remaining samples of preview note played when patch was
+    /// selected in GUI
+    audition_note: Option<(u8, usize)>,
+    /// Bitmask of keys held on GUI keyboard that note-on events were sent
+    /// for
+    gui_keys: u128,
+    /// Most recent note or MIDI event enqueued by host since last fetched
+    last_midi_event: Option<[u8; 3]>,
+    /// Whether output exceeded 0 dBFS before limiting since last fetched
+    clipped: bool,
+    /// Release keys held longer than this number of seconds while host
+    /// transport is stopped, if set
+    stuck_note_timeout: Option<f64>,
+    /// Number of samples processed, used for measuring how long keys are
+    /// held
+    sample_counter: u64,
+    /// Sample counter value when each held key was pressed
+    held_key_since: [u64; 128],
+    /// Sample counter value when host transport was last stopped
+    transport_stopped_at: u64,
+    /// Mod wheel (CC1) position in range [0.0, 1.0]
+    mod_wheel: Interpolator,
     audio_gen_data_w2: Box<AudioGenData<2>>,
     #[cfg(target_arch = "x86_64")]
     audio_gen_data_w4: Box<AudioGenData<4>>,
     #[cfg(feature = "clap")]
     pub clap_ended_notes: ClapEndedNotesRb,
+    pub midi_out_events: MidiOutEventsRb,
 }
 
 impl Default for AudioState {
@@ -76,23 +199,60 @@ impl Default for AudioState {
         Self {
             sample_rate: SampleRate::default(),
             time_per_sample: SampleRate::default().into(),
+            control_interval: 1,
+            samples_per_control_update: 1,
             bpm: Default::default(),
             bpm_lfo_multiplier: BeatsPerMinute::default().into(),
+            song_position: None,
+            transport_playing: false,
             global_pitch_bend: Default::default(),
+            channel_pitch_bends: Default::default(),
+            channel_pressures: ::std::array::from_fn(|_| {
+                Interpolator::new(0.0, InterpolationDuration::exactly_10ms())
+            }),
+            channel_timbres: ::std::array::from_fn(|_| {
+                Interpolator::new(0.0, InterpolationDuration::exactly_10ms())
+            }),
             sustain_pedal_on: false,
             parameters: AudioParameters::default(),
-            rng: Rng::new(),
+            wavetables: Default::default(),
+            modulation_input_peaks: [0.0; NUM_OPERATORS],
             log10table: Default::default(),
             polyphonic_voices,
+            voice_allocator: VoiceAllocator::default(),
             monophonic_voice: Voice::new(MidiPitch::new(0), true),
             monophonic_pressed_keys,
-            pending_note_events: LocalRb::new(1024),
+            held_keys: 0,
+            held_key_chords: [ChordMemory::default(); 128],
+            held_key_quantized: ::std::array::from_fn(|key| key as u8),
+            triggered_key_counts: [0; 128],
+            held_keys_midi_out: 0,
+            midi_out_key_counts: [0; 128],
+            pending_note_events: NoteEventQueue::new(1024),
             opt_last_voice_mode: None,
+            global_operator_modulation: Default::default(),
+            bypass_gain: Interpolator::new(1.0, InterpolationDuration::exactly_20ms()),
+            auto_gain_reference_level: None,
+            auto_gain: Interpolator::new(1.0, InterpolationDuration::exactly_50ms()),
+            mono_fold_down: Interpolator::new(0.0, InterpolationDuration::exactly_50ms()),
+            master_processing: true,
+            lfos_disabled: false,
+            listen_operator: None,
+            audition_note: None,
+            gui_keys: 0,
+            last_midi_event: None,
+            clipped: false,
+            stuck_note_timeout: None,
+            sample_counter: 0,
+            held_key_since: [0; 128],
+            transport_stopped_at: 0,
+            mod_wheel: Interpolator::new(0.0, InterpolationDuration::exactly_10ms()),
             audio_gen_data_w2: Default::default(),
             #[cfg(target_arch = "x86_64")]
             audio_gen_data_w4: Default::default(),
             #[cfg(feature = "clap")]
             clap_ended_notes: ringbuf::LocalRb::new(256),
+            midi_out_events: LocalRb::new(512),
         }
     }
 }
@@ -100,11 +260,42 @@ impl Default for AudioState {
 impl AudioState {
     pub fn set_parameter_from_patch(&mut self, parameter: Parameter, value: f32) {
         self.parameters.set_parameter_from_patch(parameter, value);
+
+        if parameter == Parameter::Master(MasterParameter::MaxPolyphony) {
+            VoiceAllocator::trim(
+                &mut self.polyphonic_voices,
+                self.parameters.max_polyphony.get_value(),
+            );
+        }
+    }
+
+    pub fn set_wavetables(&mut self, wavetables: [Option<Arc<Wavetable>>; NUM_OPERATORS]) {
+        self.wavetables = wavetables;
     }
 
     pub fn set_sample_rate(&mut self, sample_rate: SampleRate) {
         self.sample_rate = sample_rate;
         self.time_per_sample = sample_rate.into();
+
+        self.update_samples_per_control_update();
+    }
+
+    /// Set number of samples between LFO updates at 48 kHz or lower (clamped
+    /// to 1 to MAX_CONTROL_INTERVAL). At higher sample rates, the interval is
+    /// scaled up so that LFOs are updated at approximately the same rate.
+    /// Higher values reduce CPU usage.
+    pub fn set_control_interval(&mut self, samples: usize) {
+        self.control_interval = samples.clamp(1, MAX_CONTROL_INTERVAL);
+
+        self.update_samples_per_control_update();
+    }
+
+    fn update_samples_per_control_update(&mut self) {
+        let factor = (self.sample_rate.0 / CONTROL_INTERVAL_BASE_SAMPLE_RATE)
+            .round()
+            .max(1.0) as usize;
+
+        self.samples_per_control_update = self.control_interval * factor;
     }
 
     pub fn set_bpm(&mut self, bpm: BeatsPerMinute) {
@@ -112,22 +303,331 @@ impl AudioState {
         self.bpm_lfo_multiplier = bpm.into();
     }
 
-    pub fn enqueue_note_events<I: Iterator<Item = NoteEvent>>(&mut self, mut events: I) {
-        self.pending_note_events.push_iter(&mut events);
+    pub fn set_bypass(&mut self, bypass: bool) {
+        self.bypass_gain.set_value(if bypass { 0.0 } else { 1.0 });
+    }
 
-        if events.next().is_some() {
-            ::log::error!("Audio note event buffer full");
+    /// Turn on or off compensating output level for operators being muted,
+    /// listened to in isolation or having their volume or mix changed.
+    /// Level is kept close to estimated level at the time auto-gain was
+    /// turned on.
+    pub fn set_auto_gain(&mut self, enabled: bool) {
+        match (enabled, self.auto_gain_reference_level) {
+            (true, None) => {
+                self.auto_gain_reference_level = Some(self.estimate_output_level());
+            }
+            (false, Some(_)) => {
+                self.auto_gain_reference_level = None;
+                self.auto_gain.set_value(1.0);
+            }
+            _ => (),
+        }
+    }
+
+    /// Turn on or off summing output to mono, e.g., to check how stereo
+    /// spread and panning behave when played back in mono
+    pub fn set_mono_fold_down(&mut self, enabled: bool) {
+        self.mono_fold_down
+            .set_value(if enabled { 1.0 } else { 0.0 });
+    }
+
+    /// Turn off master processing, e.g., for engines rendering a subset of
+    /// voices whose summed output is passed to gen::process_master_f32
+    pub fn set_master_processing(&mut self, enabled: bool) {
+        self.master_processing = enabled;
+    }
+
+    /// Rough estimate of output level based on operator activity, volume
+    /// and mix, ignoring modulation and envelopes
+    fn estimate_output_level(&self) -> f32 {
+        self.parameters
+            .operators
+            .iter()
+            .enumerate()
+            .map(|(operator_index, operator)| {
+                let mix_out = match self.listen_operator {
+                    Some(listen_operator) if listen_operator == operator_index => 1.0,
+                    Some(_) => 0.0,
+                    None => operator.mix_out.get_value(),
+                };
+
+                operator.active.get_value() * operator.volume.get_value() * mix_out
+            })
+            .sum()
+    }
+
+    fn update_auto_gain(&mut self) {
+        /// Maximum compensation in either direction (12 dB)
+        const MAX_AUTO_GAIN: f32 = 4.0;
+
+        if let Some(reference_level) = self.auto_gain_reference_level {
+            let level = self.estimate_output_level();
+
+            let gain = if level > 0.0 {
+                (reference_level / level).clamp(1.0 / MAX_AUTO_GAIN, MAX_AUTO_GAIN)
+            } else {
+                1.0
+            };
+
+            self.auto_gain.set_value(gain);
+        }
+    }
+
+    pub fn set_lfos_disabled(&mut self, disabled: bool) {
+        self.lfos_disabled = disabled;
+    }
+
+    pub fn set_listen_operator(&mut self, opt_operator_index: Option<usize>) {
+        self.listen_operator = opt_operator_index.filter(|index| *index < NUM_OPERATORS);
+    }
+
+    /// Play preview note, released after given duration. A previous
+    /// preview note still sounding is released first.
+    pub fn start_audition(&mut self, key: u8, velocity: u8, duration: f64) {
+        if let Some((previous_key, _)) = self.audition_note.take() {
+            self.enqueue_internal_note_event(NoteEvent {
+                delta_frames: 0,
+                event: NoteEventInner::Midi {
+                    data: [0x80, previous_key, 0],
+                },
+            });
+        }
+
+        let key = key.min(127);
+
+        self.enqueue_internal_note_event(NoteEvent {
+            delta_frames: 0,
+            event: NoteEventInner::Midi {
+                data: [0x90, key, velocity.clamp(1, 127)],
+            },
+        });
+
+        let duration_samples = (duration.max(0.0) * self.sample_rate.0).round() as usize;
+
+        self.audition_note = Some((key, duration_samples));
+    }
+
+    /// Release all held keys and quickly fade out all voices. Pending note
+    /// events are discarded.
+    pub fn panic(&mut self) {
+        self.pending_note_events.clear();
+        self.audition_note = None;
+
+        let mut held_keys = self.held_keys;
+
+        while held_keys != 0 {
+            let key = held_keys.trailing_zeros() as u8;
+
+            self.note_off(key, 0);
+
+            held_keys &= !(1 << key);
+        }
+
+        for voice in self
+            .polyphonic_voices
+            .values_mut()
+            .chain(::std::iter::once(&mut self.monophonic_voice))
+            .filter(|voice| voice.active)
+        {
+            voice.release_key();
+            voice.kill_envelopes();
+        }
+
+        self.monophonic_pressed_keys.clear();
+        self.sustain_pedal_on = false;
+    }
+
+    /// Automatically release keys held longer than timeout in seconds while
+    /// host transport is stopped, or never if None
+    pub fn set_stuck_note_timeout(&mut self, opt_timeout: Option<f64>) {
+        self.stuck_note_timeout = opt_timeout;
+    }
+
+    /// Send note events for keys pressed or released on GUI keyboard since
+    /// last call
+    pub fn set_gui_keys(&mut self, gui_keys: u128) {
+        /// GUI keyboard doesn't detect velocity
+        const VELOCITY: u8 = 100;
+
+        let mut changed = gui_keys ^ self.gui_keys;
+
+        while changed != 0 {
+            let key = changed.trailing_zeros() as u8;
+            let bit = 1u128 << key;
+
+            let data = if gui_keys & bit != 0 {
+                [0x90, key, VELOCITY]
+            } else {
+                [0x80, key, 0]
+            };
+
+            self.enqueue_internal_note_event(NoteEvent {
+                delta_frames: 0,
+                event: NoteEventInner::Midi { data },
+            });
+
+            changed &= !bit;
+        }
+
+        self.gui_keys = gui_keys;
+    }
+
+    /// Returns true if no voices are active and no note events are pending,
+    /// meaning that output will be silent
+    pub fn is_silent(&self) -> bool {
+        self.pending_note_events.is_empty()
+            & self.audition_note.is_none()
+            & self.polyphonic_voices.is_empty()
+            & !self.monophonic_voice.active
+    }
+
+    /// Returns true if output will be silent for the next buffer. Pending
+    /// tempo and transport events, which hosts send every buffer, can't start
+    /// voices, so they are processed immediately if no voices are active.
+    fn process_events_if_silent(&mut self) -> bool {
+        if !self.polyphonic_voices.is_empty()
+            | self.monophonic_voice.active
+            | self.audition_note.is_some()
+        {
+            return false;
+        }
+
+        let only_timing_events = self.pending_note_events.iter().all(|event| {
+            matches!(
+                event.event,
+                NoteEventInner::ClapBpm { .. } | NoteEventInner::Transport { .. }
+            )
+        });
+
+        if !only_timing_events {
+            return false;
+        }
+
+        while let Some(event) = self.pending_note_events.pop_due(usize::MAX) {
+            self.process_note_event(event.event, event.delta_frames as usize);
+        }
+
+        true
+    }
+
+    /// Number of samples that audio may keep sounding after the last note
+    /// has been released, based on longest operator release duration
+    pub fn get_tail_length_in_samples(&self) -> u32 {
+        let max_release_duration = self
+            .parameters
+            .operators
+            .iter()
+            .map(|operator| operator.volume_envelope.release_duration.get_value())
+            .fold(0.0, f64::max);
+
+        (max_release_duration * self.sample_rate.0).ceil() as u32
+    }
+
+    fn most_recently_triggered_voice(&self) -> Option<&Voice> {
+        if self.monophonic_voice.active {
+            Some(&self.monophonic_voice)
+        } else {
+            self.polyphonic_voices.values().last()
+        }
+    }
+
+    /// Get LFO output values (including amount) of most recently triggered
+    /// active voice
+    pub fn get_lfo_outputs(&self) -> [f32; NUM_LFOS] {
+        self.most_recently_triggered_voice()
+            .map(|voice| ::std::array::from_fn(|i| voice.lfos[i].get_last_output()))
+            .unwrap_or_default()
+    }
+
+    /// Get volume envelope stage and seconds since it started for each
+    /// operator of most recently triggered active voice
+    pub fn get_envelope_positions(&self) -> [Option<(EnvelopeStage, f32)>; NUM_OPERATORS] {
+        self.most_recently_triggered_voice()
+            .map(|voice| {
+                ::std::array::from_fn(|i| {
+                    voice.operators[i]
+                        .volume_envelope
+                        .get_position()
+                        .map(|(stage, duration)| (stage, duration as f32))
+                })
+            })
+            .unwrap_or_default()
+    }
+
+    /// Enqueue note events. They don't need to be sent in order of delta
+    /// frames.
+    pub fn enqueue_note_events<I: Iterator<Item = NoteEvent>>(&mut self, events: I) {
+        for event in events {
+            self.record_midi_activity(&event);
+
+            if self.pending_note_events.push(event).is_err() {
+                ::log::error!("Audio note event buffer full");
+
+                break;
+            }
         }
     }
 
     pub fn enqueue_note_event(&mut self, event: NoteEvent) {
+        self.record_midi_activity(&event);
+        self.enqueue_internal_note_event(event);
+    }
+
+    /// Enqueue note event generated by plugin itself, e.g., from GUI
+    /// keyboard, which is not shown as received MIDI activity
+    fn enqueue_internal_note_event(&mut self, event: NoteEvent) {
         if self.pending_note_events.push(event).is_err() {
             ::log::error!("Audio note event buffer full");
         }
     }
 
+    fn record_midi_activity(&mut self, event: &NoteEvent) {
+        if let Some(data) = event.event.as_midi_data() {
+            self.last_midi_event = Some(data);
+        }
+    }
+
+    /// Get most recent note or MIDI event received from host since last
+    /// call
+    pub fn take_last_midi_event(&mut self) -> Option<[u8; 3]> {
+        self.last_midi_event.take()
+    }
+
+    /// Returns true if output exceeded 0 dBFS before limiting since last
+    /// call
+    pub fn take_clipped(&mut self) -> bool {
+        ::std::mem::take(&mut self.clipped)
+    }
+
     pub fn advance_one_sample(&mut self) {
+        self.sample_counter += 1;
+
+        if let Some(beats) = self.song_position.as_mut() {
+            *beats += self.bpm.0 / 60.0 * self.time_per_sample.0;
+        }
+
         self.parameters.advance_one_sample(self.sample_rate);
+        self.bypass_gain
+            .advance_one_sample(self.sample_rate, &mut |_| ());
+        self.update_auto_gain();
+        self.auto_gain
+            .advance_one_sample(self.sample_rate, &mut |_| ());
+        self.mono_fold_down
+            .advance_one_sample(self.sample_rate, &mut |_| ());
+        self.mod_wheel
+            .advance_one_sample(self.sample_rate, &mut |_| ());
+        self.global_pitch_bend.advance_one_sample();
+
+        for pitch_bend in self.channel_pitch_bends.iter_mut() {
+            pitch_bend.advance_one_sample();
+        }
+        for interpolator in self
+            .channel_pressures
+            .iter_mut()
+            .chain(self.channel_timbres.iter_mut())
+        {
+            interpolator.advance_one_sample(self.sample_rate, &mut |_| ());
+        }
 
         let voice_mode = self.parameters.voice_mode.get_value();
 
@@ -152,52 +652,110 @@ impl AudioState {
         self.opt_last_voice_mode = Some(voice_mode);
     }
 
-    #[cfg(feature = "vst2")]
-    pub fn sort_note_events(&mut self) {
-        let (a, b) = self.pending_note_events.as_mut_slices();
-
-        a.sort_unstable_by_key(|e| e.delta_frames);
-        b.sort_unstable_by_key(|e| e.delta_frames);
+    /// Advance per-sample state such as parameter interpolators, song
+    /// position and counters when voice generation is skipped because output
+    /// is silent
+    pub fn advance_silent_samples(&mut self, num_samples: usize) {
+        for _ in 0..num_samples {
+            self.advance_one_sample();
+        }
     }
 
     fn process_events_for_sample(&mut self, buffer_offset: usize) {
-        loop {
-            match self
-                .pending_note_events
-                .iter()
-                .next()
-                .map(|e| e.delta_frames as usize)
-            {
-                Some(event_delta_frames) if event_delta_frames == buffer_offset => {
-                    let event = self.pending_note_events.pop().unwrap();
+        while let Some(event) = self.pending_note_events.pop_due(buffer_offset) {
+            self.process_note_event(event.event, buffer_offset);
+        }
 
-                    self.process_note_event(event.event, event_delta_frames);
-                }
-                _ => break,
+        if let Some((key, samples_remaining)) = self.audition_note.as_mut() {
+            if *samples_remaining == 0 {
+                let key = *key;
+
+                self.audition_note = None;
+                self.note_off(key, buffer_offset);
+            } else {
+                *samples_remaining -= 1;
+            }
+        }
+
+        if let Some(timeout) = self.stuck_note_timeout {
+            if !self.transport_playing && self.held_keys != 0 {
+                self.release_stuck_keys(timeout, buffer_offset);
             }
         }
     }
 
+    /// Release keys held longer than timeout since they were pressed or
+    /// since transport was stopped, whichever happened last
+    fn release_stuck_keys(&mut self, timeout: f64, sample_index: usize) {
+        let timeout_samples = (timeout * self.sample_rate.0) as u64;
+
+        let mut held_keys = self.held_keys;
+
+        while held_keys != 0 {
+            let key = held_keys.trailing_zeros() as u8;
+
+            let held_since = self.held_key_since[key as usize].max(self.transport_stopped_at);
+
+            if self.sample_counter - held_since > timeout_samples {
+                ::log::warn!("Releasing stuck key {}", key);
+
+                self.note_off(key, sample_index);
+            }
+
+            held_keys &= !(1 << key);
+        }
+    }
+
     fn process_note_event(&mut self, event: NoteEventInner, sample_index: usize) {
         match event {
             NoteEventInner::Midi { mut data } => {
+                let channel = data[0] & 0b_1111;
+
                 // Discard channel bits of status byte
                 data[0] >>= 4;
 
                 match data {
-                    [0b_1000, key, _] => self.key_off(key, sample_index),
-                    [0b_1001, key, 0] => self.key_off(key, sample_index),
-                    [0b_1001, key, velocity] => {
-                        self.key_on(key, KeyVelocity::from_midi_velocity(velocity), None)
-                    }
+                    [0b_1000, key, _] => self.note_off(key, sample_index),
+                    [0b_1001, key, 0] => self.note_off(key, sample_index),
+                    [0b_1001, key, velocity] => self.note_on(
+                        key,
+                        KeyVelocity::from_midi_velocity(velocity),
+                        None,
+                        channel,
+                        sample_index,
+                    ),
                     [0b_1010, key, pressure] => {
                         self.aftertouch(key, KeyVelocity::from_midi_velocity(pressure));
                     }
+                    [0b_1011, 1, v] => {
+                        self.mod_wheel.set_value(f32::from(v) / 127.0);
+                    }
                     [0b_1011, 64, v] => {
                         self.sustain_pedal_on = v >= 64;
                     }
+                    [0b_1011, 74, v] if self.is_mpe_member_channel(channel) => {
+                        self.channel_timbres[usize::from(channel)].set_value(f32::from(v) / 127.0);
+                    }
+                    [0b_1101, pressure, _] if self.is_mpe_member_channel(channel) => {
+                        self.channel_pressures[usize::from(channel)]
+                            .set_value(f32::from(pressure) / 127.0);
+                    }
                     [0b_1110, lsb, msb] => {
-                        self.global_pitch_bend.update_from_midi(lsb, msb);
+                        let smoothing_samples = (self
+                            .parameters
+                            .master_pitch_bend_smoothing
+                            .get_value()
+                            .as_seconds()
+                            * self.sample_rate.0)
+                            .round() as usize;
+
+                        let pitch_bend = if self.is_mpe_member_channel(channel) {
+                            &mut self.channel_pitch_bends[usize::from(channel)]
+                        } else {
+                            &mut self.global_pitch_bend
+                        };
+
+                        pitch_bend.update_from_midi(lsb, msb, smoothing_samples);
                     }
                     _ => (),
                 }
@@ -207,21 +765,293 @@ impl AudioState {
                 velocity,
                 clap_note_id,
             } => {
-                self.key_on(key, KeyVelocity(velocity as f32), Some(clap_note_id));
+                self.note_on(
+                    key,
+                    KeyVelocity(velocity as f32),
+                    Some(clap_note_id),
+                    0,
+                    sample_index,
+                );
             }
             NoteEventInner::ClapNotePressure { key, pressure } => {
                 self.aftertouch(key, KeyVelocity(pressure as f32));
             }
             NoteEventInner::ClapNoteOff { key } => {
-                self.key_off(key, sample_index);
+                self.note_off(key, sample_index);
             }
             NoteEventInner::ClapBpm { bpm } => {
                 self.set_bpm(bpm);
             }
+            NoteEventInner::Transport {
+                playing,
+                song_position,
+            } => {
+                if playing && !self.transport_playing {
+                    self.restart_lfos_on_transport_start();
+                }
+                if !playing && self.transport_playing {
+                    self.transport_stopped_at = self.sample_counter;
+                }
+
+                self.transport_playing = playing;
+                self.song_position = song_position;
+            }
+            NoteEventInner::ClapParameterModulation {
+                parameter,
+                key,
+                clap_note_id,
+                amount,
+            } => {
+                self.modulate_parameter(parameter, key, clap_note_id, amount);
+            }
+            NoteEventInner::ClapNoteExpression {
+                key,
+                clap_note_id,
+                expression,
+            } => {
+                self.note_expression(key, clap_note_id, expression);
+            }
         }
     }
 
-    fn key_on(&mut self, key: u8, velocity: KeyVelocity, opt_clap_note_id: Option<i32>) {
+    fn is_mpe_member_channel(&self, channel: u8) -> bool {
+        self.parameters
+            .mpe_zone
+            .get_value()
+            .is_member_channel(channel)
+    }
+
+    fn restart_lfos_on_transport_start(&mut self) {
+        let lfo_parameters = &self.parameters.lfos;
+
+        for voice in self
+            .polyphonic_voices
+            .values_mut()
+            .chain(::std::iter::once(&mut self.monophonic_voice))
+            .filter(|voice| voice.active)
+        {
+            for (lfo, parameters) in voice.lfos.iter_mut().zip(lfo_parameters.iter()) {
+                if parameters.transport_sync.get_value() == LfoTransportSync::Restart {
+                    lfo.restart_phase();
+                }
+            }
+        }
+    }
+
+    /// Set parameter modulation offset for voices matching note id or key,
+    /// or globally if neither is set
+    fn modulate_parameter(
+        &mut self,
+        parameter: Parameter,
+        opt_key: Option<u8>,
+        opt_clap_note_id: Option<i32>,
+        amount: f32,
+    ) {
+        let (operator_index, operator_parameter) = match parameter {
+            Parameter::Operator(index, p) => (index as usize, p),
+            _ => return,
+        };
+
+        if opt_key.is_none() && opt_clap_note_id.is_none() {
+            if let Some(offset) =
+                self.global_operator_modulation[operator_index].get_mut(operator_parameter)
+            {
+                *offset = amount;
+            }
+        } else {
+            self.for_each_matching_voice(opt_key, opt_clap_note_id, |voice| {
+                if let Some(offset) = voice.operators[operator_index]
+                    .parameter_modulation
+                    .get_mut(operator_parameter)
+                {
+                    *offset = amount;
+                }
+            });
+        }
+    }
+
+    fn note_expression(
+        &mut self,
+        opt_key: Option<u8>,
+        opt_clap_note_id: Option<i32>,
+        expression: NoteExpression,
+    ) {
+        self.for_each_matching_voice(opt_key, opt_clap_note_id, |voice| {
+            voice.expression.apply(expression);
+        });
+    }
+
+    /// Call f for active voices matching note id if set, else key if set
+    fn for_each_matching_voice<F: FnMut(&mut Voice)>(
+        &mut self,
+        opt_key: Option<u8>,
+        #[cfg_attr(not(feature = "clap"), allow(unused_variables))] opt_clap_note_id: Option<i32>,
+        f: F,
+    ) {
+        let voices = self
+            .polyphonic_voices
+            .values_mut()
+            .chain(::std::iter::once(&mut self.monophonic_voice))
+            .filter(|voice| voice.active);
+
+        #[cfg(feature = "clap")]
+        if let Some(clap_note_id) = opt_clap_note_id {
+            voices
+                .filter(|voice| voice.clap_note_id == Some(clap_note_id))
+                .for_each(f);
+
+            return;
+        }
+
+        if let Some(key) = opt_key {
+            voices.filter(|voice| voice.key() == key).for_each(f);
+        }
+    }
+
+    /// Get bitmask of physically held keys
+    pub fn get_held_keys(&self) -> u128 {
+        self.held_keys
+    }
+
+    /// Get peak modulation input magnitude of each operator since last call
+    pub fn take_modulation_input_peaks(&mut self) -> [f32; NUM_OPERATORS] {
+        let peaks = self.modulation_input_peaks.map(|peak| peak as f32);
+
+        self.modulation_input_peaks = [0.0; NUM_OPERATORS];
+
+        peaks
+    }
+
+    /// Handle played key, quantizing it to selected scale and triggering
+    /// stored chord when chord memory is active in polyphonic mode
+    fn note_on(
+        &mut self,
+        key: u8,
+        velocity: KeyVelocity,
+        opt_clap_note_id: Option<i32>,
+        midi_channel: u8,
+        sample_index: usize,
+    ) {
+        if key >= 128 {
+            return;
+        }
+
+        // Repeated note on without note off: release notes triggered by
+        // previous one first, so that trigger counts stay balanced
+        if self.held_keys & (1 << key) != 0 {
+            self.note_off(key, sample_index);
+        }
+
+        let chord = match self.parameters.voice_mode.get_value() {
+            VoiceMode::Polyphonic => self.parameters.chord_memory.get_value(),
+            VoiceMode::Monophonic => ChordMemory::default(),
+        };
+
+        let quantized_key = self
+            .parameters
+            .scale
+            .get_value()
+            .quantize(self.parameters.scale_root.get_value(), key);
+
+        self.held_keys |= 1 << key;
+        self.held_key_since[key as usize] = self.sample_counter;
+        self.held_key_chords[key as usize] = chord;
+        self.held_key_quantized[key as usize] = quantized_key;
+
+        if chord.is_active() && self.parameters.midi_out.get_value() {
+            self.held_keys_midi_out |= 1 << key;
+
+            for chord_key in chord.keys(quantized_key) {
+                let count = &mut self.midi_out_key_counts[chord_key as usize];
+
+                *count += 1;
+
+                if *count == 1 {
+                    self.push_midi_out_event(true, chord_key, velocity, sample_index);
+                }
+            }
+        }
+
+        for (i, chord_key) in chord.keys(quantized_key).enumerate() {
+            // Only played key is associated with CLAP note id
+            let opt_clap_note_id = if i == 0 { opt_clap_note_id } else { None };
+
+            // Key is retriggered even if another held key already triggers
+            // it, but only released once neither does
+            self.triggered_key_counts[chord_key as usize] += 1;
+
+            self.key_on(
+                chord_key,
+                velocity,
+                opt_clap_note_id,
+                midi_channel,
+                sample_index,
+            );
+        }
+    }
+
+    fn note_off(&mut self, key: u8, sample_index: usize) {
+        if key >= 128 || self.held_keys & (1 << key) == 0 {
+            return;
+        }
+
+        self.held_keys &= !(1 << key);
+
+        let chord = ::std::mem::take(&mut self.held_key_chords[key as usize]);
+        let quantized_key = self.held_key_quantized[key as usize];
+
+        if self.held_keys_midi_out & (1 << key) != 0 {
+            self.held_keys_midi_out &= !(1 << key);
+
+            for chord_key in chord.keys(quantized_key) {
+                let count = &mut self.midi_out_key_counts[chord_key as usize];
+
+                *count = count.saturating_sub(1);
+
+                if *count == 0 {
+                    self.push_midi_out_event(false, chord_key, KeyVelocity(0.0), sample_index);
+                }
+            }
+        }
+
+        for chord_key in chord.keys(quantized_key) {
+            let count = &mut self.triggered_key_counts[chord_key as usize];
+
+            *count = count.saturating_sub(1);
+
+            if *count == 0 {
+                self.key_off(chord_key, sample_index);
+            }
+        }
+    }
+
+    fn push_midi_out_event(
+        &mut self,
+        note_on: bool,
+        key: u8,
+        velocity: KeyVelocity,
+        sample_index: usize,
+    ) {
+        let event = MidiOutEvent {
+            note_on,
+            key,
+            velocity,
+            sample_index: sample_index as u32,
+        };
+
+        if self.midi_out_events.push(event).is_err() {
+            ::log::error!("MIDI out event buffer full");
+        }
+    }
+
+    fn key_on(
+        &mut self,
+        key: u8,
+        velocity: KeyVelocity,
+        opt_clap_note_id: Option<i32>,
+        midi_channel: u8,
+        #[cfg_attr(not(feature = "clap"), allow(unused_variables))] sample_index: usize,
+    ) {
         let voice_mode = self.parameters.voice_mode.get_value();
         let glide_active = self.parameters.glide_active.get_value();
         let glide_retrigger = self.parameters.glide_retrigger.get_value();
@@ -230,10 +1060,10 @@ impl AudioState {
             VoiceMode::Polyphonic => {
                 let mut most_recent_still_pressed_keys = self
                     .polyphonic_voices
-                    .iter()
+                    .values()
                     .rev()
-                    .filter(|(k, v)| **k != key && v.key_pressed)
-                    .map(|(key, _)| *key);
+                    .filter(|v| v.key() != key && v.key_pressed)
+                    .map(|v| v.key());
 
                 let opt_glide_from_key = match glide_active {
                     GlideActive::Off => None,
@@ -243,25 +1073,54 @@ impl AudioState {
                             // Additionally look at voices in release phase. Don't filter out
                             // current voice here, since if is most recently added, we want to
                             // return None later instead of gliding from next one
-                            .chain(self.polyphonic_voices.iter().rev().map(|(key, _)| *key))
+                            .chain(self.polyphonic_voices.values().rev().map(|v| v.key()))
                             .next()
                             .filter(|k| *k != key)
                     }
                 };
 
-                let voice = if let Some(voice) = self.polyphonic_voices.shift_remove(&key) {
-                    // Shift voice to last position (most recently pressed)
-                    self.polyphonic_voices.entry(key).or_insert(voice)
-                } else {
-                    self.polyphonic_voices
-                        .entry(key)
-                        .or_insert(Voice::new(MidiPitch::new(key), false))
-                };
+                // Repeated key gets a new voice, so let previous one ring out
+                for voice in self.polyphonic_voices.values_mut() {
+                    if voice.key() == key && voice.key_pressed {
+                        voice.release_key();
+                    }
+                }
+
+                // Envelopes of stolen voices are retriggered from their
+                // current levels, which avoids clicks
+                #[cfg_attr(not(feature = "clap"), allow(unused_variables))]
+                let (voice, opt_stolen_note) = self.voice_allocator.allocate(
+                    &mut self.polyphonic_voices,
+                    self.parameters.max_polyphony.get_value(),
+                    key,
+                );
+
+                #[cfg(feature = "clap")]
+                if let Some(allocator::StolenNote {
+                    key,
+                    active: true,
+                    clap_note_id: Some(clap_note_id),
+                }) = opt_stolen_note
+                {
+                    let note_ended = ClapNoteEnded {
+                        key,
+                        clap_note_id,
+                        sample_index: sample_index as u32,
+                    };
+
+                    if self.clap_ended_notes.push(note_ended).is_err() {
+                        ::log::error!("Clap ended notes buffer full");
+                    }
+                }
 
                 if let Some(glide_from_key) = opt_glide_from_key {
                     let glide = VoiceGlide {
                         to_key: key,
-                        time: Self::glide_time(&self.parameters, self.bpm, glide_from_key, key),
+                        time: Self::glide_time(
+                            &self.parameters,
+                            self.bpm,
+                            f64::from(key) - f64::from(glide_from_key),
+                        ),
                         retrigger_envelopes: true,
                         retrigger_lfos: true,
                     };
@@ -282,11 +1141,22 @@ impl AudioState {
                         opt_clap_note_id,
                     );
                 }
+
+                voice.midi_channel = midi_channel;
             }
             VoiceMode::Monophonic => {
                 self.monophonic_pressed_keys.shift_remove(&key);
                 self.monophonic_pressed_keys.insert(key, opt_clap_note_id);
 
+                if self.monophonic_voice.active
+                    && self.monophonic_voice.key_pressed
+                    && self.monophonic_priority_key() != Some(key)
+                {
+                    // Another held key has priority, so only remember key
+                    // for when that is released
+                    return;
+                }
+
                 if glide_active == GlideActive::Off || !self.monophonic_voice.active {
                     self.monophonic_voice.press_key(
                         &self.parameters,
@@ -326,8 +1196,7 @@ impl AudioState {
                             time: Self::glide_time(
                                 &self.parameters,
                                 self.bpm,
-                                self.monophonic_voice.key(),
-                                key,
+                                self.monophonic_voice.semitones_to_key(key),
                             ),
                             retrigger_envelopes: true,
                             retrigger_lfos: glide_retrigger,
@@ -353,8 +1222,7 @@ impl AudioState {
                         time: Self::glide_time(
                             &self.parameters,
                             self.bpm,
-                            self.monophonic_voice.key(),
-                            key,
+                            self.monophonic_voice.semitones_to_key(key),
                         ),
                         retrigger_envelopes: glide_retrigger,
                         retrigger_lfos: glide_retrigger,
@@ -368,6 +1236,8 @@ impl AudioState {
                         opt_clap_note_id,
                     )
                 }
+
+                self.monophonic_voice.midi_channel = midi_channel;
             }
         }
     }
@@ -383,25 +1253,21 @@ impl AudioState {
 
         match voice_mode {
             VoiceMode::Polyphonic => {
-                if let Some(voice) = self.polyphonic_voices.get_mut(&key) {
-                    voice.release_key();
+                for voice in self.polyphonic_voices.values_mut() {
+                    if voice.key() == key && voice.key_pressed {
+                        voice.release_key();
+                    }
                 }
             }
             VoiceMode::Monophonic => {
-                let key_was_most_recently_pressed = self
-                    .monophonic_pressed_keys
-                    .last()
-                    .map(|(k, _)| *k == key)
-                    .unwrap_or(false);
+                let key_had_priority = self.monophonic_priority_key() == Some(key);
 
                 #[cfg_attr(not(feature = "clap"), allow(unused_variables))]
                 let opt_removed_clap_note_id =
                     self.monophonic_pressed_keys.shift_remove(&key).flatten();
 
-                if key_was_most_recently_pressed {
-                    if let Some(next_most_recently_pressed_key) =
-                        self.monophonic_pressed_keys.last().map(|(k, _)| *k)
-                    {
+                if key_had_priority {
+                    if let Some(next_key) = self.monophonic_priority_key() {
                         // FIXME: maybe previous velocity should be stored in pressed_keys?
                         let current_velocity = self.monophonic_voice.get_key_velocity();
 
@@ -409,18 +1275,17 @@ impl AudioState {
                             self.monophonic_voice.press_key(
                                 &self.parameters,
                                 current_velocity,
-                                Some(next_most_recently_pressed_key),
+                                Some(next_key),
                                 None,
                                 opt_removed_clap_note_id,
                             );
                         } else {
                             let glide = VoiceGlide {
-                                to_key: next_most_recently_pressed_key,
+                                to_key: next_key,
                                 time: Self::glide_time(
                                     &self.parameters,
                                     self.bpm,
-                                    key,
-                                    next_most_recently_pressed_key,
+                                    self.monophonic_voice.semitones_to_key(next_key),
                                 ),
                                 retrigger_envelopes: glide_retrigger,
                                 retrigger_lfos: glide_retrigger,
@@ -456,6 +1321,18 @@ impl AudioState {
         }
     }
 
+    /// Held key that monophonic voice should play according to note
+    /// priority
+    fn monophonic_priority_key(&self) -> Option<u8> {
+        let keys = self.monophonic_pressed_keys.keys().copied();
+
+        match self.parameters.note_priority.get_value() {
+            NotePriority::Last => keys.last(),
+            NotePriority::Low => keys.min(),
+            NotePriority::High => keys.max(),
+        }
+    }
+
     #[allow(unused_variables)]
     fn aftertouch(&mut self, key: u8, velocity: KeyVelocity) {
         // Disabled for now
@@ -471,19 +1348,16 @@ impl AudioState {
             .unwrap()
     }
 
-    fn glide_time(
-        parameters: &AudioParameters,
-        bpm: BeatsPerMinute,
-        from_key: u8,
-        to_key: u8,
-    ) -> f64 {
+    /// Glide duration in seconds for pitch interval in semitones
+    fn glide_time(parameters: &AudioParameters, bpm: BeatsPerMinute, semitones: f64) -> f64 {
         let mut glide_time = parameters.glide_time.get_value() as f64;
 
         if parameters.glide_bpm_sync.get_value() {
             glide_time *= 120.0 / bpm.0;
         }
         if let GlideMode::Lcr = parameters.glide_mode.get_value() {
-            glide_time *= (from_key as f64 - to_key as f64).abs() * (1.0 / 12.0);
+            // Glide time is time per octave
+            glide_time *= semitones.abs() * (1.0 / 12.0);
         }
 
         glide_time
@@ -491,18 +1365,28 @@ impl AudioState {
 }
 
 #[derive(Clone, Copy, Debug)]
-pub struct GlobalPitchBend {
+pub struct PitchBend {
     factor: f32,
+    target_factor: f32,
+    step_size: f32,
+    steps_remaining: usize,
 }
 
-impl Default for GlobalPitchBend {
+impl Default for PitchBend {
     fn default() -> Self {
-        Self { factor: 0.0 }
+        Self {
+            factor: 0.0,
+            target_factor: 0.0,
+            step_size: 0.0,
+            steps_remaining: 0,
+        }
     }
 }
 
-impl GlobalPitchBend {
-    pub fn update_from_midi(&mut self, lsb: u8, msb: u8) {
+impl PitchBend {
+    /// Linearly move towards new value over smoothing_samples. With zero
+    /// samples, the new value is applied immediately.
+    pub fn update_from_midi(&mut self, lsb: u8, msb: u8, smoothing_samples: usize) {
         let amount = ((msb as u16) << 7) | (lsb as u16);
 
         let mut x = (amount as f32) - 8_192.0;
@@ -516,7 +1400,28 @@ impl GlobalPitchBend {
             x *= 1.0 / 8_192.0;
         }
 
-        self.factor = x;
+        if smoothing_samples == 0 {
+            self.factor = x;
+            self.target_factor = x;
+            self.steps_remaining = 0;
+        } else {
+            self.target_factor = x;
+            self.step_size = (x - self.factor) / smoothing_samples as f32;
+            self.steps_remaining = smoothing_samples;
+        }
+    }
+    pub fn advance_one_sample(&mut self) {
+        if self.steps_remaining == 0 {
+            return;
+        }
+
+        self.steps_remaining -= 1;
+
+        if self.steps_remaining == 0 {
+            self.factor = self.target_factor;
+        } else {
+            self.factor += self.step_size;
+        }
     }
     pub fn as_frequency_multiplier(&self, range_up: f32, range_down: f32) -> f64 {
         let semitone_range = if self.factor >= 0.0 {
@@ -531,19 +1436,625 @@ impl GlobalPitchBend {
 
 #[cfg(test)]
 mod tests {
-    use super::GlobalPitchBend;
+    use crate::parameters::{
+        chord_memory::{ChordMemory, ChordMemoryValue},
+        glide_bpm_sync::GlideBpmSyncValue,
+        glide_mode::GlideModeValue,
+        glide_time::GlideTimeValue,
+        list::{MasterParameter, OperatorParameter},
+        max_polyphony::MaxPolyphonyValue,
+        midi_out::MidiOutValue,
+        mpe::{MpeZone, MpeZoneValue},
+        note_priority::NotePriorityValue,
+        operator_mix_out::OperatorMixOutValue,
+        scale_quantization::{Scale, ScaleValue},
+        voice_mode::VoiceModeValue,
+        ParameterValue,
+    };
+
+    use super::*;
+
+    /// Most recently triggered polyphonic voice for key
+    fn polyphonic_voice(audio: &mut AudioState, key: u8) -> &mut Voice {
+        audio
+            .polyphonic_voices
+            .values_mut()
+            .rev()
+            .find(|voice| voice.key() == key)
+            .expect("no voice for key")
+    }
+
+    #[test]
+    fn test_max_polyphony() {
+        let mut audio = AudioState::default();
+
+        audio.set_parameter_from_patch(
+            Parameter::Master(MasterParameter::MaxPolyphony),
+            MaxPolyphonyValue::new_from_audio(2).to_patch(),
+        );
+
+        for key in [60, 62, 64] {
+            audio.process_note_event(
+                NoteEventInner::Midi {
+                    data: [0x90, key, 100],
+                },
+                0,
+            );
+        }
+
+        // Oldest voice was stolen
+        assert_eq!(audio.polyphonic_voices.len(), 2);
+        assert!(audio.polyphonic_voices.values().all(|v| v.key() != 60));
+
+        // Repeated key releases previous voice, which is then stolen before
+        // voices with held keys
+        audio.process_note_event(
+            NoteEventInner::Midi {
+                data: [0x90, 64, 100],
+            },
+            0,
+        );
+
+        let keys_pressed = audio
+            .polyphonic_voices
+            .values()
+            .map(|v| (v.key(), v.key_pressed))
+            .collect::<Vec<_>>();
+
+        assert_eq!(keys_pressed, vec![(62, true), (64, true)]);
+
+        audio.process_note_event(
+            NoteEventInner::Midi {
+                data: [0x80, 64, 0],
+            },
+            0,
+        );
+
+        assert!(polyphonic_voice(&mut audio, 62).key_pressed);
+        assert!(!polyphonic_voice(&mut audio, 64).key_pressed);
+
+        // Lowering max polyphony releases least recently triggered voice
+        audio.process_note_event(
+            NoteEventInner::Midi {
+                data: [0x90, 65, 100],
+            },
+            0,
+        );
+        audio.set_parameter_from_patch(
+            Parameter::Master(MasterParameter::MaxPolyphony),
+            MaxPolyphonyValue::new_from_audio(1).to_patch(),
+        );
+
+        assert!(!polyphonic_voice(&mut audio, 62).key_pressed);
+        assert!(polyphonic_voice(&mut audio, 65).key_pressed);
+    }
 
     #[test]
     fn test_global_pitch_bend_from_midi() {
-        let mut pitch_bend = GlobalPitchBend::default();
+        let mut pitch_bend = PitchBend::default();
 
-        pitch_bend.update_from_midi(0, 64);
+        pitch_bend.update_from_midi(0, 64, 0);
         assert_eq!(pitch_bend.factor, 0.0);
 
-        pitch_bend.update_from_midi(0, 0);
+        pitch_bend.update_from_midi(0, 0, 0);
         assert_eq!(pitch_bend.factor, -1.0);
 
-        pitch_bend.update_from_midi(127, 127);
+        pitch_bend.update_from_midi(127, 127, 0);
+        assert_eq!(pitch_bend.factor, 1.0);
+    }
+
+    #[test]
+    fn test_global_pitch_bend_smoothing() {
+        let mut pitch_bend = PitchBend::default();
+
+        pitch_bend.update_from_midi(127, 127, 4);
+        assert_eq!(pitch_bend.factor, 0.0);
+
+        pitch_bend.advance_one_sample();
+        assert_eq!(pitch_bend.factor, 0.25);
+
+        for _ in 0..3 {
+            pitch_bend.advance_one_sample();
+        }
+        assert_eq!(pitch_bend.factor, 1.0);
+
+        pitch_bend.advance_one_sample();
         assert_eq!(pitch_bend.factor, 1.0);
     }
+
+    #[test]
+    fn test_glide_time() {
+        let mut parameters = AudioParameters::default();
+        let bpm = BeatsPerMinute(120.0);
+
+        parameters.set_parameter_from_patch(
+            Parameter::Master(MasterParameter::GlideTime),
+            GlideTimeValue::new_from_audio(1.0).to_patch(),
+        );
+
+        assert_eq!(AudioState::glide_time(&parameters, bpm, 24.0), 1.0);
+
+        parameters.set_parameter_from_patch(
+            Parameter::Master(MasterParameter::GlideMode),
+            GlideModeValue::new_from_audio(GlideMode::Lcr).to_patch(),
+        );
+
+        assert_eq!(AudioState::glide_time(&parameters, bpm, 24.0), 2.0);
+        assert_eq!(AudioState::glide_time(&parameters, bpm, -6.0), 0.5);
+
+        parameters.set_parameter_from_patch(
+            Parameter::Master(MasterParameter::GlideBpmSync),
+            GlideBpmSyncValue::new_from_audio(true).to_patch(),
+        );
+
+        assert_eq!(
+            AudioState::glide_time(&parameters, BeatsPerMinute(60.0), 12.0),
+            2.0
+        );
+    }
+
+    #[test]
+    fn test_monophonic_note_priority() {
+        fn note_on(audio: &mut AudioState, key: u8) {
+            audio.process_note_event(
+                NoteEventInner::Midi {
+                    data: [0x90, key, 100],
+                },
+                0,
+            );
+        }
+        fn note_off(audio: &mut AudioState, key: u8) {
+            audio.process_note_event(
+                NoteEventInner::Midi {
+                    data: [0x80, key, 0],
+                },
+                0,
+            );
+        }
+
+        for (priority, expected_keys) in [
+            (NotePriority::Last, [62, 64, 62, 60]),
+            (NotePriority::Low, [60, 60, 60, 60]),
+            (NotePriority::High, [62, 64, 62, 60]),
+        ] {
+            let mut audio = AudioState::default();
+
+            audio.set_parameter_from_patch(
+                Parameter::Master(MasterParameter::VoiceMode),
+                VoiceModeValue::new_from_audio(VoiceMode::Monophonic).to_patch(),
+            );
+            audio.set_parameter_from_patch(
+                Parameter::Master(MasterParameter::NotePriority),
+                NotePriorityValue::new_from_audio(priority).to_patch(),
+            );
+
+            note_on(&mut audio, 60);
+            note_on(&mut audio, 62);
+            assert_eq!(audio.monophonic_voice.key(), expected_keys[0]);
+
+            note_on(&mut audio, 64);
+            assert_eq!(audio.monophonic_voice.key(), expected_keys[1]);
+
+            note_off(&mut audio, 64);
+            assert_eq!(audio.monophonic_voice.key(), expected_keys[2]);
+
+            note_off(&mut audio, 62);
+            assert_eq!(audio.monophonic_voice.key(), expected_keys[3]);
+
+            note_off(&mut audio, 60);
+            assert!(!audio.monophonic_voice.key_pressed);
+        }
+    }
+
+    #[test]
+    fn test_mpe_channel_pitch_bend() {
+        let mut audio = AudioState::default();
+
+        audio.set_parameter_from_patch(
+            Parameter::Master(MasterParameter::MpeZone),
+            MpeZoneValue::new_from_audio(MpeZone::Lower).to_patch(),
+        );
+
+        // Note on and full upward pitch bend on MIDI channel 2
+        for data in [[0x91, 60, 100], [0xE1, 127, 127]] {
+            audio.process_note_event(NoteEventInner::Midi { data }, 0);
+        }
+
+        assert_eq!(polyphonic_voice(&mut audio, 60).midi_channel, 1);
+        assert_eq!(audio.channel_pitch_bends[1].factor, 1.0);
+        assert_eq!(audio.global_pitch_bend.factor, 0.0);
+
+        // Pitch bend on master channel
+        audio.process_note_event(NoteEventInner::Midi { data: [0xE0, 0, 0] }, 0);
+
+        assert_eq!(audio.global_pitch_bend.factor, -1.0);
+    }
+
+    #[test]
+    fn test_mpe_channel_pressure_and_timbre() {
+        let mut audio = AudioState::default();
+
+        audio.set_sample_rate(SampleRate(1000.0));
+
+        // Ignored when MPE is off
+        audio.process_note_event(
+            NoteEventInner::Midi {
+                data: [0xD1, 127, 0],
+            },
+            0,
+        );
+
+        audio.set_parameter_from_patch(
+            Parameter::Master(MasterParameter::MpeZone),
+            MpeZoneValue::new_from_audio(MpeZone::Lower).to_patch(),
+        );
+
+        // Timbre and pressure on MIDI channel 3 and master channel
+        for data in [
+            [0xB2, 74, 127],
+            [0xD2, 127, 0],
+            [0xB0, 74, 127],
+            [0xD0, 127, 0],
+        ] {
+            audio.process_note_event(NoteEventInner::Midi { data }, 0);
+        }
+
+        for _ in 0..100 {
+            audio.advance_one_sample();
+        }
+
+        assert_eq!(audio.channel_pressures[1].get_value(), 0.0);
+        assert!((audio.channel_pressures[2].get_value() - 1.0).abs() < 1.0e-4);
+        assert!((audio.channel_timbres[2].get_value() - 1.0).abs() < 1.0e-4);
+        assert_eq!(audio.channel_pressures[0].get_value(), 0.0);
+        assert_eq!(audio.channel_timbres[0].get_value(), 0.0);
+    }
+
+    #[test]
+    fn test_audition_note() {
+        let mut audio = AudioState::default();
+
+        audio.set_sample_rate(SampleRate(1000.0));
+        audio.start_audition(60, 100, 0.01);
+
+        assert!(!audio.is_silent());
+
+        audio.process_events_for_sample(0);
+
+        assert!(polyphonic_voice(&mut audio, 60).key_pressed);
+
+        for i in 1..10 {
+            audio.process_events_for_sample(i);
+        }
+
+        assert!(polyphonic_voice(&mut audio, 60).key_pressed);
+
+        audio.process_events_for_sample(10);
+
+        assert!(!polyphonic_voice(&mut audio, 60).key_pressed);
+        assert!(audio.audition_note.is_none());
+    }
+
+    #[test]
+    fn test_gui_keys() {
+        let mut audio = AudioState::default();
+
+        audio.set_gui_keys((1 << 60) | (1 << 100));
+        audio.process_events_for_sample(0);
+
+        assert!(polyphonic_voice(&mut audio, 60).key_pressed);
+        assert!(polyphonic_voice(&mut audio, 100).key_pressed);
+
+        // Unchanged keys don't generate events
+        audio.set_gui_keys((1 << 60) | (1 << 100));
+
+        assert!(audio.pending_note_events.is_empty());
+
+        audio.set_gui_keys(1 << 100);
+        audio.process_events_for_sample(1);
+
+        assert!(!polyphonic_voice(&mut audio, 60).key_pressed);
+        assert!(polyphonic_voice(&mut audio, 100).key_pressed);
+
+        // Keys played in GUI are not received MIDI activity
+        assert_eq!(audio.take_last_midi_event(), None);
+    }
+
+    #[test]
+    fn test_last_midi_event() {
+        let mut audio = AudioState::default();
+
+        audio.enqueue_note_events([[0x90, 60, 100], [0xB1, 74, 127]].into_iter().map(|data| {
+            NoteEvent {
+                delta_frames: 0,
+                event: NoteEventInner::Midi { data },
+            }
+        }));
+
+        assert_eq!(audio.take_last_midi_event(), Some([0xB1, 74, 127]));
+        assert_eq!(audio.take_last_midi_event(), None);
+
+        audio.enqueue_note_event(NoteEvent {
+            delta_frames: 0,
+            event: NoteEventInner::Transport {
+                playing: true,
+                song_position: None,
+            },
+        });
+
+        assert_eq!(audio.take_last_midi_event(), None);
+
+        audio.enqueue_note_event(NoteEvent {
+            delta_frames: 0,
+            event: NoteEventInner::ClapNoteOn {
+                key: 64,
+                velocity: 1.0,
+                clap_note_id: 0,
+            },
+        });
+
+        assert_eq!(audio.take_last_midi_event(), Some([0x90, 64, 127]));
+    }
+
+    #[test]
+    fn test_envelope_positions() {
+        let mut audio = AudioState::default();
+
+        assert_eq!(audio.get_envelope_positions(), [None; NUM_OPERATORS]);
+
+        for key in [60, 64] {
+            audio.process_note_event(
+                NoteEventInner::Midi {
+                    data: [0x90, key, 100],
+                },
+                0,
+            );
+        }
+
+        assert_eq!(
+            audio.get_envelope_positions(),
+            [Some((EnvelopeStage::Attack, 0.0)); NUM_OPERATORS]
+        );
+
+        // Most recently triggered voice is used
+        polyphonic_voice(&mut audio, 60).kill_envelopes();
+
+        assert!(audio.get_envelope_positions()[0].is_some());
+
+        polyphonic_voice(&mut audio, 64).kill_envelopes();
+
+        assert_eq!(audio.get_envelope_positions(), [None; NUM_OPERATORS]);
+    }
+
+    #[test]
+    fn test_panic() {
+        let mut audio = AudioState::default();
+
+        // Note on, sustain pedal down, note off and a pending note on
+        for data in [
+            [0x90, 60, 100],
+            [0xB0, 64, 127],
+            [0x90, 62, 100],
+            [0x80, 60, 0],
+        ] {
+            audio.process_note_event(NoteEventInner::Midi { data }, 0);
+        }
+
+        audio.enqueue_note_event(NoteEvent {
+            delta_frames: 1,
+            event: NoteEventInner::Midi {
+                data: [0x90, 64, 100],
+            },
+        });
+
+        audio.panic();
+
+        assert_eq!(audio.get_held_keys(), 0);
+        assert!(!audio.sustain_pedal_on);
+        assert!(audio.pending_note_events.is_empty());
+        assert!(audio
+            .polyphonic_voices
+            .values()
+            .all(|voice| !voice.key_pressed));
+    }
+
+    #[test]
+    fn test_auto_gain() {
+        fn run(audio: &mut AudioState) {
+            for _ in 0..1000 {
+                audio.advance_one_sample();
+            }
+        }
+
+        let mut audio = AudioState::default();
+
+        audio.set_sample_rate(SampleRate(1000.0));
+        audio.set_auto_gain(true);
+
+        run(&mut audio);
+
+        assert!((audio.auto_gain.get_value() - 1.0).abs() < 1.0e-4);
+
+        // Doubling estimated level halves gain
+        audio.parameters.set_parameter_from_patch(
+            Parameter::Operator(1, OperatorParameter::MixOut),
+            OperatorMixOutValue::new_from_audio(1.0).to_patch(),
+        );
+
+        run(&mut audio);
+
+        assert!((audio.auto_gain.get_value() - 0.5).abs() < 1.0e-4);
+
+        // Listening to operator with no volume doesn't cause extreme gain
+        audio
+            .parameters
+            .set_parameter_from_patch(Parameter::Operator(2, OperatorParameter::Volume), 0.0);
+        audio.set_listen_operator(Some(2));
+
+        run(&mut audio);
+
+        assert!((audio.auto_gain.get_value() - 1.0).abs() < 1.0e-4);
+
+        audio.set_listen_operator(None);
+        audio.set_auto_gain(false);
+
+        run(&mut audio);
+
+        assert!((audio.auto_gain.get_value() - 1.0).abs() < 1.0e-4);
+    }
+
+    #[test]
+    fn test_stuck_note_watchdog() {
+        fn run(audio: &mut AudioState, num_samples: usize) {
+            for i in 0..num_samples {
+                audio.advance_one_sample();
+                audio.process_events_for_sample(i);
+            }
+        }
+
+        let mut audio = AudioState::default();
+
+        audio.set_sample_rate(SampleRate(1000.0));
+        audio.set_stuck_note_timeout(Some(0.1));
+
+        audio.process_note_event(
+            NoteEventInner::Transport {
+                playing: true,
+                song_position: None,
+            },
+            0,
+        );
+        audio.process_note_event(
+            NoteEventInner::Midi {
+                data: [0x90, 60, 100],
+            },
+            0,
+        );
+
+        // Keys are never released while transport is playing
+        run(&mut audio, 200);
+
+        assert_eq!(audio.get_held_keys(), 1 << 60);
+
+        audio.process_note_event(
+            NoteEventInner::Transport {
+                playing: false,
+                song_position: None,
+            },
+            0,
+        );
+
+        run(&mut audio, 100);
+
+        assert_eq!(audio.get_held_keys(), 1 << 60);
+
+        run(&mut audio, 2);
+
+        assert_eq!(audio.get_held_keys(), 0);
+        assert!(!polyphonic_voice(&mut audio, 60).key_pressed);
+    }
+
+    #[test]
+    fn test_chord_memory_keys_released_when_no_longer_triggered() {
+        let mut audio = AudioState::default();
+
+        // Major third above played key
+        audio.set_parameter_from_patch(
+            Parameter::Master(MasterParameter::ChordMemory),
+            ChordMemoryValue::new_from_audio(ChordMemory::from_held_keys((1 << 60) | (1 << 64)))
+                .to_patch(),
+        );
+
+        // Key 64 is triggered both by chord of key 60 and by playing it
+        for data in [[0x90, 60, 100], [0x90, 64, 100], [0x80, 60, 0]] {
+            audio.process_note_event(NoteEventInner::Midi { data }, 0);
+        }
+
+        assert!(!polyphonic_voice(&mut audio, 60).key_pressed);
+        assert!(polyphonic_voice(&mut audio, 64).key_pressed);
+        assert!(polyphonic_voice(&mut audio, 68).key_pressed);
+
+        audio.process_note_event(
+            NoteEventInner::Midi {
+                data: [0x80, 64, 0],
+            },
+            0,
+        );
+
+        assert!(!polyphonic_voice(&mut audio, 64).key_pressed);
+        assert!(!polyphonic_voice(&mut audio, 68).key_pressed);
+        assert_eq!(audio.triggered_key_counts, [0; 128]);
+    }
+
+    #[test]
+    fn test_chord_memory_midi_out_sent_once_per_key() {
+        let mut audio = AudioState::default();
+
+        audio.set_parameter_from_patch(
+            Parameter::Master(MasterParameter::MidiOut),
+            MidiOutValue::new_from_audio(true).to_patch(),
+        );
+        audio.set_parameter_from_patch(
+            Parameter::Master(MasterParameter::ChordMemory),
+            ChordMemoryValue::new_from_audio(ChordMemory::from_held_keys((1 << 60) | (1 << 64)))
+                .to_patch(),
+        );
+
+        for data in [[0x90, 60, 100], [0x90, 64, 100], [0x80, 60, 0]] {
+            audio.process_note_event(NoteEventInner::Midi { data }, 0);
+        }
+
+        let events: Vec<(bool, u8)> = audio
+            .midi_out_events
+            .pop_iter()
+            .map(|event| (event.note_on, event.key))
+            .collect();
+
+        // Key 64 is still triggered by held key 64
+        assert_eq!(events, [(true, 60), (true, 64), (true, 68), (false, 60)]);
+
+        audio.process_note_event(
+            NoteEventInner::Midi {
+                data: [0x80, 64, 0],
+            },
+            0,
+        );
+
+        let events: Vec<(bool, u8)> = audio
+            .midi_out_events
+            .pop_iter()
+            .map(|event| (event.note_on, event.key))
+            .collect();
+
+        assert_eq!(events, [(false, 64), (false, 68)]);
+        assert_eq!(audio.midi_out_key_counts, [0; 128]);
+    }
+
+    #[test]
+    fn test_scale_quantized_key_released_when_no_longer_triggered() {
+        let mut audio = AudioState::default();
+
+        audio.set_parameter_from_patch(
+            Parameter::Master(MasterParameter::Scale),
+            ScaleValue::new_from_audio(Scale::Major).to_patch(),
+        );
+
+        // C# is quantized to C in C major
+        for data in [[0x90, 60, 100], [0x90, 61, 100], [0x80, 61, 0]] {
+            audio.process_note_event(NoteEventInner::Midi { data }, 0);
+        }
+
+        assert!(polyphonic_voice(&mut audio, 60).key_pressed);
+        assert!(audio.polyphonic_voices.values().all(|v| v.key() != 61));
+
+        audio.process_note_event(
+            NoteEventInner::Midi {
+                data: [0x80, 60, 0],
+            },
+            0,
+        );
+
+        assert!(!polyphonic_voice(&mut audio, 60).key_pressed);
+        assert_eq!(audio.triggered_key_counts, [0; 128]);
+    }
 }
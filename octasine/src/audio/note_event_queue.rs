@@ -0,0 +1,137 @@
+use std::collections::VecDeque;
+
+use crate::common::NoteEvent;
+
+/// Bounded queue of note events, kept sorted by delta frames
+///
+/// Storage is preallocated and events are inserted in order, so enqueuing
+/// never allocates and no sorting is needed before processing. Events
+/// usually arrive in order, making insertion O(1) in the common case.
+pub struct NoteEventQueue {
+    events: VecDeque<NoteEvent>,
+}
+
+impl NoteEventQueue {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            events: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Insert event after any events with the same or lower delta frames.
+    /// Returns event back if queue is full.
+    pub fn push(&mut self, event: NoteEvent) -> Result<(), NoteEvent> {
+        if self.events.len() == self.events.capacity() {
+            return Err(event);
+        }
+
+        let index = self
+            .events
+            .iter()
+            .rposition(|e| e.delta_frames <= event.delta_frames)
+            .map(|i| i + 1)
+            .unwrap_or(0);
+
+        self.events.insert(index, event);
+
+        Ok(())
+    }
+
+    /// Remove and return first event if it is due at or before buffer offset
+    pub fn pop_due(&mut self, buffer_offset: usize) -> Option<NoteEvent> {
+        match self.events.front() {
+            Some(event) if event.delta_frames as usize <= buffer_offset => self.events.pop_front(),
+            _ => None,
+        }
+    }
+
+    /// Move events due after max delta frames to max delta frames. Order is
+    /// kept, since queue is sorted.
+    pub fn clamp_delta_frames(&mut self, max_delta_frames: u32) {
+        for event in self.events.iter_mut().rev() {
+            if event.delta_frames <= max_delta_frames {
+                break;
+            }
+
+            event.delta_frames = max_delta_frames;
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &NoteEvent> {
+        self.events.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.events.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::NoteEventInner;
+
+    use super::*;
+
+    #[test]
+    fn test_note_event_queue_order() {
+        let mut queue = NoteEventQueue::new(4);
+
+        let event = |delta_frames: u32, key: u8| NoteEvent {
+            delta_frames,
+            event: NoteEventInner::Midi {
+                data: [0x90, key, 100],
+            },
+        };
+
+        for (delta_frames, key) in [(8, 0), (0, 1), (8, 2), (4, 3)] {
+            assert!(queue.push(event(delta_frames, key)).is_ok());
+        }
+
+        assert!(queue.push(event(0, 4)).is_err());
+
+        assert!(queue.pop_due(0).is_some());
+        assert!(queue.pop_due(0).is_none());
+
+        let keys: Vec<u8> = ::std::iter::from_fn(|| queue.pop_due(16))
+            .map(|e| match e.event {
+                NoteEventInner::Midi { data } => data[1],
+                _ => unreachable!(),
+            })
+            .collect();
+
+        // Events with equal delta frames keep insertion order
+        assert_eq!(keys, vec![3, 0, 2]);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_note_event_queue_clamp_delta_frames() {
+        let mut queue = NoteEventQueue::new(4);
+
+        for (delta_frames, key) in [(2, 0), (20, 1), (10, 2)] {
+            let event = NoteEvent {
+                delta_frames,
+                event: NoteEventInner::Midi {
+                    data: [0x90, key, 100],
+                },
+            };
+
+            assert!(queue.push(event).is_ok());
+        }
+
+        queue.clamp_delta_frames(7);
+
+        let events: Vec<(u32, u8)> = ::std::iter::from_fn(|| queue.pop_due(7))
+            .map(|e| match e.event {
+                NoteEventInner::Midi { data } => (e.delta_frames, data[1]),
+                _ => unreachable!(),
+            })
+            .collect();
+
+        assert_eq!(events, vec![(2, 0), (7, 2), (7, 1)]);
+    }
+}
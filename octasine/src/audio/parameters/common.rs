@@ -42,6 +42,13 @@ where
     }
 }
 
+impl<V: ParameterValue> InterpolatableAudioParameter<V> {
+    /// Returns true if both current and target values are zero
+    pub fn is_zero(&self) -> bool {
+        self.interpolator.is_zero()
+    }
+}
+
 impl<V> AudioParameter for InterpolatableAudioParameter<V>
 where
     V: ParameterValue<Value = f32>,
@@ -0,0 +1,38 @@
+use crate::audio::interpolation::{InterpolationDuration, Interpolator};
+use crate::common::SampleRate;
+use crate::parameters::{LfoTargetDepthValue, ParameterValue};
+
+use super::common::AudioParameter;
+
+/// Interpolates patch value, since interpolator doesn't support negative values
+#[derive(Debug, Clone)]
+pub struct LfoTargetDepthAudioParameter(Interpolator);
+
+impl Default for LfoTargetDepthAudioParameter {
+    fn default() -> Self {
+        Self(Interpolator::new(
+            LfoTargetDepthValue::default().to_patch(),
+            InterpolationDuration::approx_1ms(),
+        ))
+    }
+}
+
+impl AudioParameter for LfoTargetDepthAudioParameter {
+    type ParameterValue = LfoTargetDepthValue;
+
+    fn advance_one_sample(&mut self, sample_rate: SampleRate) {
+        self.0.advance_one_sample(sample_rate, &mut |_| ())
+    }
+    fn get_value(&self) -> <Self::ParameterValue as ParameterValue>::Value {
+        Self::ParameterValue::new_from_patch(self.0.get_value()).get()
+    }
+    fn set_from_patch(&mut self, value: f32) {
+        self.0.set_value(value)
+    }
+    fn get_value_with_lfo_addition(
+        &mut self,
+        _lfo_addition: Option<f32>,
+    ) -> <Self::ParameterValue as ParameterValue>::Value {
+        self.get_value()
+    }
+}
@@ -3,9 +3,11 @@ mod lfo_active;
 mod lfo_amount;
 mod lfo_frequency_free;
 mod lfo_target;
+mod lfo_target_depth;
 mod master_frequency;
 mod master_volume;
 mod operator_active;
+mod operator_cross_feedback_target;
 mod operator_frequency_fine;
 mod operator_frequency_free;
 mod operator_mix;
@@ -17,15 +19,44 @@ mod operator_volume;
 use array_init::array_init;
 
 use crate::common::{SampleRate, NUM_LFOS, NUM_OPERATORS};
+use crate::parameters::anti_alias::AntiAliasValue;
+use crate::parameters::chord_memory::ChordMemoryValue;
 use crate::parameters::glide_active::GlideActiveValue;
 use crate::parameters::glide_bpm_sync::GlideBpmSyncValue;
 use crate::parameters::glide_mode::GlideModeValue;
 use crate::parameters::glide_retrigger::GlideRetriggerValue;
 use crate::parameters::glide_time::GlideTimeValue;
+use crate::parameters::legato_envelope_mode::LegatoEnvelopeModeValue;
+use crate::parameters::lfo_amount_sensitivity::LfoAmountSensitivityValue;
+use crate::parameters::lfo_envelope::{LfoEnvelopeAttackValue, LfoEnvelopeCurveValue};
+use crate::parameters::lfo_key_sync_spread::LfoKeySyncSpreadValue;
+use crate::parameters::lfo_smoothing::LfoSmoothingValue;
 use crate::parameters::master_pitch_bend_range::{
     MasterPitchBendRangeDownValue, MasterPitchBendRangeUpValue,
 };
+use crate::parameters::master_pitch_bend_smoothing::MasterPitchBendSmoothingValue;
+use crate::parameters::max_polyphony::MaxPolyphonyValue;
+use crate::parameters::midi_out::MidiOutValue;
+use crate::parameters::mod_snapshot::ModSnapshotSlotValue;
+use crate::parameters::mpe::{
+    MpeMemberPitchBendRangeValue, MpeModulationAmountValue, MpeModulationTargetValue, MpeZoneValue,
+};
+use crate::parameters::note_priority::NotePriorityValue;
+use crate::parameters::note_random::{NoteRandomAmountValue, NoteRandomTargetValue};
+use crate::parameters::operator_key_range::{OperatorKeyRangeHighValue, OperatorKeyRangeLowValue};
+use crate::parameters::operator_noise_stereo::OperatorNoiseStereoValue;
+use crate::parameters::operator_phase_distortion::OperatorPhaseDistortionValue;
+use crate::parameters::operator_spread::OperatorSpreadValue;
+use crate::parameters::operator_velocity_range::{
+    OperatorVelocityRangeHighValue, OperatorVelocityRangeLowValue,
+};
+use crate::parameters::operator_wavetable_position::OperatorWavetablePositionValue;
+use crate::parameters::patch_select::PatchSelectValue;
+use crate::parameters::scale_quantization::{ScaleRootValue, ScaleValue};
 use crate::parameters::velocity_sensitivity::VelocitySensitivityValue;
+use crate::parameters::vibrato::{
+    VibratoDelayValue, VibratoDepthValue, VibratoModWheelValue, VibratoRateValue,
+};
 use crate::parameters::voice_mode::VoiceModeValue;
 use crate::parameters::*;
 
@@ -34,8 +65,10 @@ use self::lfo_active::LfoActiveAudioParameter;
 use self::lfo_amount::LfoAmountAudioParameter;
 use self::lfo_frequency_free::LfoFrequencyFreeAudioParameter;
 use self::lfo_target::LfoTargetAudioParameter;
+use self::lfo_target_depth::LfoTargetDepthAudioParameter;
 use self::master_frequency::MasterFrequencyAudioParameter;
 use self::master_volume::MasterVolumeAudioParameter;
+use self::operator_cross_feedback_target::OperatorCrossFeedbackTargetAudioParameter;
 use self::operator_frequency_fine::OperatorFrequencyFineAudioParameter;
 use self::operator_frequency_free::OperatorFrequencyFreeAudioParameter;
 use self::operator_mix::OperatorMixAudioParameter;
@@ -75,6 +108,29 @@ pub struct AudioParameters {
     pub glide_bpm_sync: SimpleAudioParameter<GlideBpmSyncValue>,
     pub glide_mode: SimpleAudioParameter<GlideModeValue>,
     pub glide_retrigger: SimpleAudioParameter<GlideRetriggerValue>,
+    pub chord_memory: SimpleAudioParameter<ChordMemoryValue>,
+    pub midi_out: SimpleAudioParameter<MidiOutValue>,
+    pub scale: SimpleAudioParameter<ScaleValue>,
+    pub scale_root: SimpleAudioParameter<ScaleRootValue>,
+    pub legato_envelope_mode: SimpleAudioParameter<LegatoEnvelopeModeValue>,
+    pub note_priority: SimpleAudioParameter<NotePriorityValue>,
+    pub anti_alias: SimpleAudioParameter<AntiAliasValue>,
+    pub note_random_target: SimpleAudioParameter<NoteRandomTargetValue>,
+    pub note_random_amount: SimpleAudioParameter<NoteRandomAmountValue>,
+    pub vibrato_rate: SimpleAudioParameter<VibratoRateValue>,
+    pub vibrato_depth: SimpleAudioParameter<VibratoDepthValue>,
+    pub vibrato_delay: SimpleAudioParameter<VibratoDelayValue>,
+    pub vibrato_mod_wheel: SimpleAudioParameter<VibratoModWheelValue>,
+    pub master_pitch_bend_smoothing: SimpleAudioParameter<MasterPitchBendSmoothingValue>,
+    pub mpe_zone: SimpleAudioParameter<MpeZoneValue>,
+    pub mpe_member_pitch_bend_range: SimpleAudioParameter<MpeMemberPitchBendRangeValue>,
+    pub mpe_pressure_target: SimpleAudioParameter<MpeModulationTargetValue>,
+    pub mpe_pressure_amount: SimpleAudioParameter<MpeModulationAmountValue>,
+    pub mpe_timbre_target: SimpleAudioParameter<MpeModulationTargetValue>,
+    pub mpe_timbre_amount: SimpleAudioParameter<MpeModulationAmountValue>,
+    pub max_polyphony: SimpleAudioParameter<MaxPolyphonyValue>,
+    pub mod_snapshot: SimpleAudioParameter<ModSnapshotSlotValue>,
+    pub patch_select: SimpleAudioParameter<PatchSelectValue>,
     pub operators: [OperatorAudioParameters; NUM_OPERATORS],
     pub lfos: [LfoAudioParameters; NUM_LFOS],
 }
@@ -93,6 +149,29 @@ impl Default for AudioParameters {
             glide_bpm_sync: Default::default(),
             glide_mode: Default::default(),
             glide_retrigger: Default::default(),
+            chord_memory: Default::default(),
+            midi_out: Default::default(),
+            scale: Default::default(),
+            scale_root: Default::default(),
+            legato_envelope_mode: Default::default(),
+            note_priority: Default::default(),
+            anti_alias: Default::default(),
+            note_random_target: Default::default(),
+            note_random_amount: Default::default(),
+            vibrato_rate: Default::default(),
+            vibrato_depth: Default::default(),
+            vibrato_delay: Default::default(),
+            vibrato_mod_wheel: Default::default(),
+            master_pitch_bend_smoothing: Default::default(),
+            mpe_zone: Default::default(),
+            mpe_member_pitch_bend_range: Default::default(),
+            mpe_pressure_target: Default::default(),
+            mpe_pressure_amount: Default::default(),
+            mpe_timbre_target: Default::default(),
+            mpe_timbre_amount: Default::default(),
+            max_polyphony: Default::default(),
+            mod_snapshot: Default::default(),
+            patch_select: Default::default(),
             operators: array_init(OperatorAudioParameters::new),
             lfos: array_init(LfoAudioParameters::new),
         }
@@ -122,6 +201,35 @@ macro_rules! impl_patch_interaction {
                     MasterParameter::GlideBpmSync => $f(&mut self.glide_bpm_sync, input),
                     MasterParameter::GlideMode => $f(&mut self.glide_mode, input),
                     MasterParameter::GlideRetrigger => $f(&mut self.glide_retrigger, input),
+                    MasterParameter::ChordMemory => $f(&mut self.chord_memory, input),
+                    MasterParameter::MidiOut => $f(&mut self.midi_out, input),
+                    MasterParameter::Scale => $f(&mut self.scale, input),
+                    MasterParameter::ScaleRoot => $f(&mut self.scale_root, input),
+                    MasterParameter::LegatoEnvelopeMode => {
+                        $f(&mut self.legato_envelope_mode, input)
+                    }
+                    MasterParameter::NotePriority => $f(&mut self.note_priority, input),
+                    MasterParameter::AntiAlias => $f(&mut self.anti_alias, input),
+                    MasterParameter::NoteRandomTarget => $f(&mut self.note_random_target, input),
+                    MasterParameter::NoteRandomAmount => $f(&mut self.note_random_amount, input),
+                    MasterParameter::VibratoRate => $f(&mut self.vibrato_rate, input),
+                    MasterParameter::VibratoDepth => $f(&mut self.vibrato_depth, input),
+                    MasterParameter::VibratoDelay => $f(&mut self.vibrato_delay, input),
+                    MasterParameter::VibratoModWheel => $f(&mut self.vibrato_mod_wheel, input),
+                    MasterParameter::PitchBendSmoothing => {
+                        $f(&mut self.master_pitch_bend_smoothing, input)
+                    }
+                    MasterParameter::MpeZone => $f(&mut self.mpe_zone, input),
+                    MasterParameter::MpeMemberPitchBendRange => {
+                        $f(&mut self.mpe_member_pitch_bend_range, input)
+                    }
+                    MasterParameter::MpePressureTarget => $f(&mut self.mpe_pressure_target, input),
+                    MasterParameter::MpePressureAmount => $f(&mut self.mpe_pressure_amount, input),
+                    MasterParameter::MpeTimbreTarget => $f(&mut self.mpe_timbre_target, input),
+                    MasterParameter::MpeTimbreAmount => $f(&mut self.mpe_timbre_amount, input),
+                    MasterParameter::MaxPolyphony => $f(&mut self.max_polyphony, input),
+                    MasterParameter::ModSnapshot => $f(&mut self.mod_snapshot, input),
+                    MasterParameter::PatchSelect => $f(&mut self.patch_select, input),
                 },
                 Parameter::Operator(index, p) => {
                     use OperatorParameter::*;
@@ -159,6 +267,37 @@ macro_rules! impl_patch_interaction {
                             $f(&mut operator.volume_envelope.release_duration, input)
                         }
                         EnvelopeLockGroup => $f(&mut operator.volume_envelope.lock_group, input),
+                        ReleaseMode => $f(&mut operator.volume_envelope.release_mode, input),
+                        VelocitySensitivityPeak => $f(
+                            &mut operator.volume_envelope.velocity_sensitivity_peak,
+                            input,
+                        ),
+                        VelocitySensitivitySustain => $f(
+                            &mut operator.volume_envelope.velocity_sensitivity_sustain,
+                            input,
+                        ),
+                        WavetablePosition => $f(&mut operator.wavetable_position, input),
+                        PhaseDistortion => $f(&mut operator.phase_distortion, input),
+                        KeyRangeLow => $f(&mut operator.key_range_low, input),
+                        KeyRangeHigh => $f(&mut operator.key_range_high, input),
+                        VelocityRangeLow => $f(&mut operator.velocity_range_low, input),
+                        VelocityRangeHigh => $f(&mut operator.velocity_range_high, input),
+                        NoiseStereo => $f(&mut operator.noise_stereo, input),
+                        Spread => $f(&mut operator.spread, input),
+                        CrossFeedback => {
+                            if let Some(p) = operator.cross_feedback.as_mut() {
+                                $f(p, input)
+                            } else {
+                                None
+                            }
+                        }
+                        CrossFeedbackTarget => {
+                            if let Some(p) = operator.cross_feedback_target.as_mut() {
+                                $f(p, input)
+                            } else {
+                                None
+                            }
+                        }
                         VelocitySensitivityModOut => {
                             $f(&mut operator.velocity_sensitivity_mod_out, input)
                         }
@@ -180,6 +319,22 @@ macro_rules! impl_patch_interaction {
                         LfoParameter::Amount => $f(&mut lfo.amount, input),
                         LfoParameter::Active => $f(&mut lfo.active, input),
                         LfoParameter::KeySync => $f(&mut lfo.key_sync, input),
+                        LfoParameter::TransportSync => $f(&mut lfo.transport_sync, input),
+                        LfoParameter::Target2 => $f(&mut lfo.target_2, input),
+                        LfoParameter::Target2Depth => $f(&mut lfo.target_2_depth, input),
+                        LfoParameter::Target3 => $f(&mut lfo.target_3, input),
+                        LfoParameter::Target3Depth => $f(&mut lfo.target_3_depth, input),
+                        LfoParameter::Polarity => $f(&mut lfo.polarity, input),
+                        LfoParameter::VelocitySensitivity => {
+                            $f(&mut lfo.velocity_sensitivity, input)
+                        }
+                        LfoParameter::ModWheelSensitivity => {
+                            $f(&mut lfo.mod_wheel_sensitivity, input)
+                        }
+                        LfoParameter::EnvelopeCurve => $f(&mut lfo.envelope_curve, input),
+                        LfoParameter::EnvelopeAttack => $f(&mut lfo.envelope_attack, input),
+                        LfoParameter::KeySyncSpread => $f(&mut lfo.key_sync_spread, input),
+                        LfoParameter::Smoothing => $f(&mut lfo.smoothing, input),
                     }
                 }
             }
@@ -206,6 +361,9 @@ impl AudioParameters {
         |p: &mut dyn AudioParameterPatchInteraction, v| Some(p.compare_patch_value(v))
     );
 
+    /// Advance parameter interpolation by one sample. Unlike LFOs, this is
+    /// not done at control rate, since interpolators are already linear
+    /// ramps and only cost an addition per sample while changing.
     pub fn advance_one_sample(&mut self, sample_rate: SampleRate) {
         self.master_volume.advance_one_sample(sample_rate);
         self.master_frequency.advance_one_sample(sample_rate);
@@ -220,6 +378,12 @@ impl AudioParameters {
             lfo.advance_one_sample(sample_rate);
         }
     }
+
+    pub fn cross_feedback_active(&self) -> bool {
+        self.operators
+            .iter()
+            .any(OperatorAudioParameters::cross_feedback_active)
+    }
 }
 
 pub struct OperatorAudioParameters {
@@ -237,6 +401,16 @@ pub struct OperatorAudioParameters {
     pub volume_envelope: OperatorEnvelopeAudioParameters,
     pub velocity_sensitivity_mod_out: InterpolatableAudioParameter<VelocitySensitivityValue>,
     pub velocity_sensitivity_feedback: InterpolatableAudioParameter<VelocitySensitivityValue>,
+    pub wavetable_position: InterpolatableAudioParameter<OperatorWavetablePositionValue>,
+    pub phase_distortion: InterpolatableAudioParameter<OperatorPhaseDistortionValue>,
+    pub cross_feedback: Option<InterpolatableAudioParameter<OperatorFeedbackValue>>,
+    pub cross_feedback_target: Option<OperatorCrossFeedbackTargetAudioParameter>,
+    pub key_range_low: SimpleAudioParameter<OperatorKeyRangeLowValue>,
+    pub key_range_high: SimpleAudioParameter<OperatorKeyRangeHighValue>,
+    pub velocity_range_low: SimpleAudioParameter<OperatorVelocityRangeLowValue>,
+    pub velocity_range_high: SimpleAudioParameter<OperatorVelocityRangeHighValue>,
+    pub noise_stereo: SimpleAudioParameter<OperatorNoiseStereoValue>,
+    pub spread: InterpolatableAudioParameter<OperatorSpreadValue>,
 }
 
 impl OperatorAudioParameters {
@@ -262,6 +436,19 @@ impl OperatorAudioParameters {
             volume_envelope: Default::default(),
             velocity_sensitivity_mod_out: Default::default(),
             velocity_sensitivity_feedback: Default::default(),
+            wavetable_position: Default::default(),
+            phase_distortion: Default::default(),
+            cross_feedback: OperatorCrossFeedbackTargetAudioParameter::opt_new(operator_index)
+                .map(|_| Default::default()),
+            cross_feedback_target: OperatorCrossFeedbackTargetAudioParameter::opt_new(
+                operator_index,
+            ),
+            key_range_low: Default::default(),
+            key_range_high: Default::default(),
+            velocity_range_low: Default::default(),
+            velocity_range_high: Default::default(),
+            noise_stereo: Default::default(),
+            spread: Default::default(),
         }
     }
 
@@ -286,6 +473,44 @@ impl OperatorAudioParameters {
             .advance_one_sample(sample_rate);
         self.velocity_sensitivity_feedback
             .advance_one_sample(sample_rate);
+        self.wavetable_position.advance_one_sample(sample_rate);
+        self.phase_distortion.advance_one_sample(sample_rate);
+        self.spread.advance_one_sample(sample_rate);
+        if let Some(cross_feedback) = self.cross_feedback.as_mut() {
+            cross_feedback.advance_one_sample(sample_rate);
+        }
+        if let Some(cross_feedback_target) = self.cross_feedback_target.as_mut() {
+            cross_feedback_target.advance_one_sample(sample_rate);
+        }
+    }
+
+    /// Returns true if key is within operator key range. Bounds are
+    /// swapped if low bound is above high bound.
+    pub fn key_in_range(&self, key: u8) -> bool {
+        let low = self.key_range_low.get_value();
+        let high = self.key_range_high.get_value();
+
+        (low.min(high)..=low.max(high)).contains(&key)
+    }
+
+    /// Returns true if MIDI velocity is within operator velocity range.
+    /// Bounds are swapped if low bound is above high bound.
+    pub fn velocity_in_range(&self, velocity: u8) -> bool {
+        let low = self.velocity_range_low.get_value();
+        let high = self.velocity_range_high.get_value();
+
+        (low.min(high)..=low.max(high)).contains(&velocity)
+    }
+
+    /// Returns true if operator might send cross feedback before its
+    /// parameters are next updated from patch
+    pub fn cross_feedback_active(&self) -> bool {
+        match (&self.cross_feedback, &self.cross_feedback_target) {
+            (Some(amount), Some(target)) => {
+                target.get_value().index().is_some() && !amount.is_zero()
+            }
+            _ => false,
+        }
     }
 }
 
@@ -296,6 +521,10 @@ pub struct OperatorEnvelopeAudioParameters {
     pub sustain_volume: OperatorSustainVolumeAudioParameter,
     pub release_duration: SimpleAudioParameter<OperatorReleaseDurationValue>,
     pub lock_group: SimpleAudioParameter<OperatorEnvelopeGroupValue>,
+    pub release_mode: SimpleAudioParameter<OperatorReleaseModeValue>,
+    pub velocity_sensitivity_peak: SimpleAudioParameter<OperatorEnvelopeVelocitySensitivityValue>,
+    pub velocity_sensitivity_sustain:
+        SimpleAudioParameter<OperatorEnvelopeVelocitySensitivityValue>,
 }
 
 impl OperatorEnvelopeAudioParameters {
@@ -305,6 +534,11 @@ impl OperatorEnvelopeAudioParameters {
         self.sustain_volume.advance_one_sample(sample_rate);
         self.release_duration.advance_one_sample(sample_rate);
         self.lock_group.advance_one_sample(sample_rate);
+        self.release_mode.advance_one_sample(sample_rate);
+        self.velocity_sensitivity_peak
+            .advance_one_sample(sample_rate);
+        self.velocity_sensitivity_sustain
+            .advance_one_sample(sample_rate);
     }
 }
 
@@ -318,6 +552,18 @@ pub struct LfoAudioParameters {
     pub amount: LfoAmountAudioParameter,
     pub active: LfoActiveAudioParameter,
     pub key_sync: SimpleAudioParameter<LfoKeySyncValue>,
+    pub transport_sync: SimpleAudioParameter<LfoTransportSyncValue>,
+    pub target_2: LfoTargetAudioParameter,
+    pub target_2_depth: LfoTargetDepthAudioParameter,
+    pub target_3: LfoTargetAudioParameter,
+    pub target_3_depth: LfoTargetDepthAudioParameter,
+    pub polarity: SimpleAudioParameter<LfoPolarityValue>,
+    pub velocity_sensitivity: InterpolatableAudioParameter<LfoAmountSensitivityValue>,
+    pub mod_wheel_sensitivity: InterpolatableAudioParameter<LfoAmountSensitivityValue>,
+    pub envelope_curve: SimpleAudioParameter<LfoEnvelopeCurveValue>,
+    pub envelope_attack: InterpolatableAudioParameter<LfoEnvelopeAttackValue>,
+    pub key_sync_spread: SimpleAudioParameter<LfoKeySyncSpreadValue>,
+    pub smoothing: SimpleAudioParameter<LfoSmoothingValue>,
 }
 
 impl LfoAudioParameters {
@@ -332,6 +578,18 @@ impl LfoAudioParameters {
             amount: Default::default(),
             active: Default::default(),
             key_sync: Default::default(),
+            transport_sync: Default::default(),
+            target_2: LfoTargetAudioParameter::new(lfo_index),
+            target_2_depth: Default::default(),
+            target_3: LfoTargetAudioParameter::new(lfo_index),
+            target_3_depth: Default::default(),
+            polarity: Default::default(),
+            velocity_sensitivity: Default::default(),
+            mod_wheel_sensitivity: Default::default(),
+            envelope_curve: Default::default(),
+            envelope_attack: Default::default(),
+            key_sync_spread: Default::default(),
+            smoothing: Default::default(),
         }
     }
 
@@ -344,6 +602,13 @@ impl LfoAudioParameters {
         self.shape.advance_one_sample(sample_rate);
         self.amount.advance_one_sample(sample_rate);
         self.active.advance_one_sample(sample_rate);
+        self.target_2.advance_one_sample(sample_rate);
+        self.target_2_depth.advance_one_sample(sample_rate);
+        self.target_3.advance_one_sample(sample_rate);
+        self.target_3_depth.advance_one_sample(sample_rate);
+        self.velocity_sensitivity.advance_one_sample(sample_rate);
+        self.mod_wheel_sensitivity.advance_one_sample(sample_rate);
+        self.envelope_attack.advance_one_sample(sample_rate);
     }
 }
 
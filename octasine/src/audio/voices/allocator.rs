@@ -0,0 +1,229 @@
+use crate::common::IndexMap;
+
+use super::{MidiPitch, Voice};
+
+/// Number of polyphonic voice slots. Slot 128 is used for the monophonic
+/// voice in audio generation.
+pub const NUM_POLYPHONIC_VOICE_SLOTS: u8 = 128;
+
+/// Note that a voice was stolen from
+#[derive(Debug, Clone, Copy)]
+pub struct StolenNote {
+    pub key: u8,
+    /// Whether voice was still sounding
+    pub active: bool,
+    #[cfg(feature = "clap")]
+    pub clap_note_id: Option<i32>,
+}
+
+/// Allocates polyphonic voices for new notes
+///
+/// Voices are stored by slot in an IndexMap ordered from least to most
+/// recently triggered. New notes get free slots in round-robin order, so
+/// repeated notes on the same key don't cut each other off. When maximum
+/// polyphony is reached, the least recently triggered voice is stolen,
+/// preferring voices with released keys.
+#[derive(Debug, Default)]
+pub struct VoiceAllocator {
+    next_slot: u8,
+}
+
+impl VoiceAllocator {
+    /// Get voice to use for new note and move it to the most recently
+    /// triggered position. Stolen voices are reset (see Voice::reset).
+    /// Returns voice and the note it was stolen from, if any.
+    pub fn allocate<'a>(
+        &mut self,
+        voices: &'a mut IndexMap<u8, Voice>,
+        max_polyphony: usize,
+        key: u8,
+    ) -> (&'a mut Voice, Option<StolenNote>) {
+        let max_polyphony = clamp_max_polyphony(max_polyphony);
+
+        if voices.len() >= max_polyphony {
+            let index = voices
+                .values()
+                .position(|voice| !voice.key_pressed)
+                .unwrap_or(0);
+
+            if let Some((slot, mut voice)) = voices.shift_remove_index(index) {
+                let stolen_note = StolenNote {
+                    key: voice.key(),
+                    active: voice.active,
+                    #[cfg(feature = "clap")]
+                    clap_note_id: voice.clap_note_id,
+                };
+
+                voice.reset(MidiPitch::new(key));
+
+                return (voices.entry(slot).or_insert(voice), Some(stolen_note));
+            }
+        }
+
+        let slot = (0..NUM_POLYPHONIC_VOICE_SLOTS)
+            .map(|i| self.next_slot.wrapping_add(i) % NUM_POLYPHONIC_VOICE_SLOTS)
+            .find(|slot| !voices.contains_key(slot))
+            .expect("fewer voices than slots");
+
+        self.next_slot = (slot + 1) % NUM_POLYPHONIC_VOICE_SLOTS;
+
+        let voice = voices
+            .entry(slot)
+            .or_insert(Voice::new(MidiPitch::new(key), false));
+
+        (voice, None)
+    }
+
+    /// Release and fade out voices exceeding maximum polyphony, e.g., after
+    /// it was lowered. Voices are chosen in the same order as when stealing:
+    /// least recently triggered first, preferring voices with released keys.
+    pub fn trim(voices: &mut IndexMap<u8, Voice>, max_polyphony: usize) {
+        let mut num_excess = voices
+            .len()
+            .saturating_sub(clamp_max_polyphony(max_polyphony));
+
+        for key_pressed in [false, true] {
+            for voice in voices
+                .values_mut()
+                .filter(|voice| voice.key_pressed == key_pressed)
+            {
+                if num_excess == 0 {
+                    return;
+                }
+
+                voice.release_key();
+                voice.kill_envelopes();
+
+                num_excess -= 1;
+            }
+        }
+    }
+}
+
+fn clamp_max_polyphony(max_polyphony: usize) -> usize {
+    max_polyphony.clamp(1, usize::from(NUM_POLYPHONIC_VOICE_SLOTS))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allocate_and_press(
+        allocator: &mut VoiceAllocator,
+        voices: &mut IndexMap<u8, Voice>,
+        max_polyphony: usize,
+        key: u8,
+    ) -> bool {
+        let (voice, opt_stolen_note) = allocator.allocate(voices, max_polyphony, key);
+
+        voice.midi_pitch = MidiPitch::new(key);
+        voice.key_pressed = true;
+        voice.active = true;
+
+        opt_stolen_note.is_some()
+    }
+
+    fn keys(voices: &IndexMap<u8, Voice>) -> Vec<u8> {
+        voices.values().map(|voice| voice.key()).collect()
+    }
+
+    #[test]
+    fn test_round_robin_allocation() {
+        let mut allocator = VoiceAllocator::default();
+        let mut voices = IndexMap::default();
+
+        allocate_and_press(&mut allocator, &mut voices, 8, 60);
+        allocate_and_press(&mut allocator, &mut voices, 8, 60);
+
+        assert_eq!(voices.keys().copied().collect::<Vec<_>>(), vec![0, 1]);
+
+        voices.shift_remove(&0);
+
+        allocate_and_press(&mut allocator, &mut voices, 8, 62);
+
+        assert_eq!(voices.keys().copied().collect::<Vec<_>>(), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_voice_stealing() {
+        let mut allocator = VoiceAllocator::default();
+        let mut voices = IndexMap::default();
+
+        for key in [60, 62, 64] {
+            assert!(!allocate_and_press(&mut allocator, &mut voices, 3, key));
+        }
+
+        // Least recently triggered voice is stolen
+        assert!(allocate_and_press(&mut allocator, &mut voices, 3, 65));
+        assert_eq!(keys(&voices), vec![62, 64, 65]);
+
+        // Voices with released keys are stolen first
+        voices[1].key_pressed = false;
+
+        assert!(allocate_and_press(&mut allocator, &mut voices, 3, 67));
+        assert_eq!(keys(&voices), vec![62, 65, 67]);
+        assert_eq!(voices.len(), 3);
+    }
+
+    #[test]
+    fn test_stolen_voice_is_reset() {
+        let mut allocator = VoiceAllocator::default();
+        let mut voices = IndexMap::default();
+
+        allocate_and_press(&mut allocator, &mut voices, 1, 60);
+
+        voices[0].midi_channel = 5;
+        voices[0].expression.volume = 0.5;
+
+        let (voice, opt_stolen_note) = allocator.allocate(&mut voices, 1, 62);
+
+        assert!(matches!(
+            opt_stolen_note,
+            Some(StolenNote {
+                key: 60,
+                active: true,
+                ..
+            })
+        ));
+        assert_eq!(voice.key(), 62);
+        assert!(!voice.active);
+        assert!(!voice.key_pressed);
+        assert_eq!(voice.midi_channel, 0);
+        assert_eq!(voice.expression.volume, 1.0);
+    }
+
+    #[test]
+    fn test_trim() {
+        let mut allocator = VoiceAllocator::default();
+        let mut voices = IndexMap::default();
+
+        for key in [60, 62, 64, 65] {
+            allocate_and_press(&mut allocator, &mut voices, 8, key);
+        }
+
+        voices[2].key_pressed = false;
+
+        VoiceAllocator::trim(&mut voices, 2);
+
+        // Released voice is trimmed first, then least recently triggered
+        let pressed_keys: Vec<u8> = voices
+            .values()
+            .filter(|voice| voice.key_pressed)
+            .map(|voice| voice.key())
+            .collect();
+
+        assert_eq!(pressed_keys, vec![62, 65]);
+        assert!(voices[0].operators[0]
+            .volume_envelope
+            .get_position()
+            .is_none());
+        assert!(voices[2].operators[0]
+            .volume_envelope
+            .get_position()
+            .is_none());
+        assert!(voices[1].operators[0]
+            .volume_envelope
+            .get_position()
+            .is_some());
+    }
+}
@@ -1,13 +1,16 @@
 use crate::audio::parameters::common::AudioParameter;
 use crate::audio::parameters::OperatorEnvelopeAudioParameters;
 use crate::common::*;
-use crate::parameters::ENVELOPE_CURVE_TAKEOVER_RECIP;
+use crate::parameters::{OperatorReleaseMode, ENVELOPE_CURVE_TAKEOVER_RECIP};
 
 use super::log10_table::Log10Table;
 use super::VoiceDuration;
 
 const INTERPOLATION_DURATION: f64 = 0.00333;
 const KILL_DURATION: f64 = INTERPOLATION_DURATION;
+/// Duration of crossfade from previous volume when retriggering a voice that
+/// is still sounding
+const RESTART_DURATION: f64 = 0.005;
 
 #[derive(Debug, Copy, Clone)]
 pub struct VoiceOperatorVolumeEnvelope {
@@ -19,6 +22,10 @@ pub struct VoiceOperatorVolumeEnvelope {
     /// Value to interpolate from when restarting without keeping initial
     /// volume
     restarting_from_volume: Option<f32>,
+    /// Attack peak level, scaled by key velocity at note-on
+    peak_volume: f32,
+    /// Sustain level factor, scaled by key velocity at note-on
+    sustain_factor: f32,
 }
 
 impl VoiceOperatorVolumeEnvelope {
@@ -37,12 +44,16 @@ impl VoiceOperatorVolumeEnvelope {
 
         self.duration.0 += time_per_sample.0;
 
-        if self.restarting_from_volume.is_some() && self.duration.0 >= INTERPOLATION_DURATION {
+        if self.restarting_from_volume.is_some() && self.duration.0 >= RESTART_DURATION {
             self.restarting_from_volume = None;
         }
 
         if !key_or_sustain_pedal_pressed {
+            let release_mode = parameters.release_mode.get_value();
+
             match self.stage {
+                // Release once attack stage is done
+                Attack if release_mode == OperatorReleaseMode::AfterAttack => (),
                 Attack | Decay | Sustain => {
                     self.stage = Release;
                     self.duration_at_stage_change = self.duration;
@@ -104,18 +115,27 @@ impl VoiceOperatorVolumeEnvelope {
             Attack => Self::calculate_curve(
                 log10table,
                 self.volume_at_stage_change,
-                1.0,
+                self.peak_volume,
                 self.duration_since_stage_change(),
                 parameters.attack_duration.get_value(),
             ),
             Decay => Self::calculate_curve(
                 log10table,
                 self.volume_at_stage_change,
-                parameters.sustain_volume.get_value(),
+                parameters.sustain_volume.get_value() * self.sustain_factor,
                 self.duration_since_stage_change(),
                 parameters.decay_duration.get_value(),
             ),
-            Sustain => parameters.sustain_volume.get_value(),
+            Sustain => parameters.sustain_volume.get_value() * self.sustain_factor,
+            Release if parameters.release_mode.get_value() == OperatorReleaseMode::Linear => {
+                // Division result is NaN for zero release duration, in which
+                // case min returns 1.0
+                let progress = (self.duration_since_stage_change()
+                    / parameters.release_duration.get_value())
+                .min(1.0) as f32;
+
+                self.volume_at_stage_change * (1.0 - progress)
+            }
             Release => Self::calculate_curve(
                 log10table,
                 self.volume_at_stage_change,
@@ -134,7 +154,10 @@ impl VoiceOperatorVolumeEnvelope {
         };
 
         self.last_volume = if let Some(restart_volume) = self.restarting_from_volume {
-            let progress = (self.duration.0 / INTERPOLATION_DURATION) as f32;
+            let progress = ((self.duration.0 / RESTART_DURATION) as f32).min(1.0);
+            // Smoothstep to avoid abrupt changes in slope at fade start and
+            // end, which can be audible as clicks
+            let progress = progress * progress * (3.0 - 2.0 * progress);
 
             progress * volume + (1.0 - progress) * restart_volume
         } else {
@@ -182,6 +205,19 @@ impl VoiceOperatorVolumeEnvelope {
         }
     }
 
+    /// Scale attack peak and sustain levels by key velocity according to
+    /// velocity sensitivity parameters. Call after restarting envelope.
+    pub fn set_velocity_levels(
+        &mut self,
+        parameters: &OperatorEnvelopeAudioParameters,
+        velocity: f32,
+    ) {
+        let factor = |sensitivity: f32| sensitivity * velocity + (1.0 - sensitivity);
+
+        self.peak_volume = factor(parameters.velocity_sensitivity_peak.get_value());
+        self.sustain_factor = factor(parameters.velocity_sensitivity_sustain.get_value());
+    }
+
     pub fn kill(&mut self) {
         self.stage = EnvelopeStage::Kill;
         self.duration_at_stage_change = self.duration;
@@ -192,6 +228,15 @@ impl VoiceOperatorVolumeEnvelope {
     pub fn is_ended(&self) -> bool {
         self.stage == EnvelopeStage::Ended
     }
+
+    /// Current stage and seconds since it started, or None if envelope
+    /// has ended or is being killed
+    pub fn get_position(&self) -> Option<(EnvelopeStage, f64)> {
+        match self.stage {
+            EnvelopeStage::Ended | EnvelopeStage::Kill => None,
+            stage => Some((stage, self.duration_since_stage_change())),
+        }
+    }
 }
 
 impl Default for VoiceOperatorVolumeEnvelope {
@@ -203,6 +248,8 @@ impl Default for VoiceOperatorVolumeEnvelope {
             volume_at_stage_change: 0.0,
             last_volume: 0.0,
             restarting_from_volume: None,
+            peak_volume: 1.0,
+            sustain_factor: 1.0,
         }
     }
 }
@@ -212,6 +259,7 @@ mod tests {
     use assert_approx_eq::assert_approx_eq;
     use quickcheck::{quickcheck, TestResult};
 
+    use crate::audio::parameters::OperatorEnvelopeAudioParameters;
     use crate::parameters::ENVELOPE_MAX_DURATION;
 
     use super::*;
@@ -323,4 +371,66 @@ mod tests {
 
         quickcheck(prop as fn(f32) -> TestResult);
     }
+
+    #[test]
+    fn restart_volume_continuity() {
+        let table = Log10Table::default();
+        let parameters = OperatorEnvelopeAudioParameters::default();
+        let time_per_sample = TimePerSample(1.0 / 44100.0);
+        let mut phase = Phase(0.0);
+
+        let mut envelope = VoiceOperatorVolumeEnvelope::default();
+
+        // Advance to sustain stage
+        for _ in 0..44100 {
+            envelope.advance_one_sample(&parameters, &mut phase, true, time_per_sample);
+            envelope.get_volume(&table, &parameters);
+        }
+
+        let mut previous_volume = envelope.get_volume(&table, &parameters);
+
+        assert!(previous_volume > 0.1);
+
+        envelope.restart(false);
+
+        for _ in 0..(RESTART_DURATION * 44100.0) as usize + 1 {
+            envelope.advance_one_sample(&parameters, &mut phase, true, time_per_sample);
+
+            let volume = envelope.get_volume(&table, &parameters);
+
+            assert!((volume - previous_volume).abs() < 0.01);
+
+            previous_volume = volume;
+        }
+    }
+
+    #[test]
+    fn envelope_timing_independent_of_sample_rate() {
+        const RELEASE_AFTER: f64 = 0.1;
+
+        let parameters = OperatorEnvelopeAudioParameters::default();
+
+        let durations = [44_100.0, 48_000.0, 96_000.0, 192_000.0].map(|sample_rate| {
+            let time_per_sample = TimePerSample(1.0 / sample_rate);
+            let mut phase = Phase(0.0);
+            let mut envelope = VoiceOperatorVolumeEnvelope::default();
+            let mut num_samples = 0usize;
+
+            while !envelope.is_ended() {
+                let key_pressed = num_samples as f64 * time_per_sample.0 < RELEASE_AFTER;
+
+                envelope.advance_one_sample(&parameters, &mut phase, key_pressed, time_per_sample);
+
+                num_samples += 1;
+
+                assert!(num_samples < (sample_rate * 10.0) as usize);
+            }
+
+            num_samples as f64 * time_per_sample.0
+        });
+
+        for duration in durations {
+            assert_approx_eq!(duration, durations[0], 2.0 / 44_100.0);
+        }
+    }
 }
@@ -4,7 +4,10 @@ use crate::{
         parameters::{common::AudioParameter, LfoAudioParameters},
     },
     common::*,
-    parameters::{lfo_mode::LfoMode, lfo_shape::LfoShape},
+    parameters::{
+        lfo_envelope::LfoEnvelopeCurve, lfo_mode::LfoMode, lfo_polarity::LfoPolarity,
+        lfo_shape::LfoShape,
+    },
 };
 
 const INTERPOLATION_DURATION_SHORT: InterpolationDuration = InterpolationDuration::exactly_10ms();
@@ -28,6 +31,12 @@ pub struct VoiceLfo {
     current_shape: Option<LfoShape>,
     phase: Phase,
     last_value: f32,
+    /// Last value after smoothing
+    smoothed_value: f32,
+    /// Last value multiplied by amount
+    last_output: f32,
+    /// Number of samples covered by last call to advance_samples
+    samples_advanced: usize,
     sample_rate: SampleRate,
 }
 
@@ -40,21 +49,29 @@ impl Default for VoiceLfo {
             current_shape: None,
             phase: Phase(0.0),
             last_value: 0.0,
+            smoothed_value: 0.0,
+            last_output: 0.0,
+            samples_advanced: 1,
             sample_rate,
         }
     }
 }
 
 impl VoiceLfo {
-    pub fn advance_one_sample(
+    #[allow(clippy::too_many_arguments)]
+    pub fn advance_samples(
         &mut self,
+        num_samples: usize,
         sample_rate: SampleRate,
         time_per_sample: TimePerSample,
         bpm_lfo_multiplier: BpmLfoMultiplier,
         shape: LfoShape,
         mode: LfoMode,
         frequency: f64,
+        opt_synced_phase: Option<Phase>,
     ) {
+        self.samples_advanced = num_samples;
+
         if let LfoStage::Stopped | LfoStage::OneshotComplete = self.stage {
             return;
         }
@@ -78,9 +95,20 @@ impl VoiceLfo {
             };
         }
 
-        let new_phase = self.phase.0 + frequency * bpm_lfo_multiplier.0 * time_per_sample.0;
+        let cycle_completed = if let Some(synced_phase) = opt_synced_phase {
+            let cycle_completed = synced_phase.0 < self.phase.0;
 
-        self.phase.0 = new_phase.fract();
+            self.phase = synced_phase;
+
+            cycle_completed
+        } else {
+            let new_phase = self.phase.0
+                + frequency * bpm_lfo_multiplier.0 * time_per_sample.0 * num_samples as f64;
+
+            self.phase.0 = new_phase.fract();
+
+            new_phase >= 1.0
+        };
 
         match self.stage {
             LfoStage::Interpolate {
@@ -88,7 +116,7 @@ impl VoiceLfo {
                 mut samples_done,
                 samples_to_interpolate,
             } => {
-                if new_phase >= 1.0 {
+                if cycle_completed {
                     if mode == LfoMode::Once {
                         self.stage = LfoStage::OneshotComplete;
                     } else {
@@ -100,9 +128,9 @@ impl VoiceLfo {
                         };
                     }
                 } else {
-                    samples_done += 1;
+                    samples_done += num_samples;
 
-                    if samples_done == samples_to_interpolate {
+                    if samples_done >= samples_to_interpolate {
                         self.stage = LfoStage::Running;
                     } else {
                         self.stage = LfoStage::Interpolate {
@@ -115,7 +143,7 @@ impl VoiceLfo {
                 }
             }
             LfoStage::Running => {
-                if new_phase >= 1.0 {
+                if cycle_completed {
                     if mode == LfoMode::Once {
                         self.stage = LfoStage::OneshotComplete;
                     } else {
@@ -142,7 +170,14 @@ impl VoiceLfo {
         }
     }
 
-    pub fn get_value(&mut self, amount: f32) -> f32 {
+    pub fn get_value(
+        &mut self,
+        amount: f32,
+        polarity: LfoPolarity,
+        envelope_curve: LfoEnvelopeCurve,
+        envelope_attack: f32,
+        smoothing: f32,
+    ) -> f32 {
         if let LfoStage::Stopped = self.stage {
             return 0.0;
         }
@@ -153,6 +188,12 @@ impl VoiceLfo {
             return 0.0;
         };
 
+        let calculate = |phase| {
+            envelope_curve
+                .calculate(envelope_attack, phase)
+                .unwrap_or_else(|| shape.calculate(phase))
+        };
+
         let value = match self.stage {
             LfoStage::Interpolate {
                 from_value,
@@ -161,9 +202,9 @@ impl VoiceLfo {
             } => {
                 let progress = samples_done as f32 / samples_to_interpolate as f32;
 
-                progress * shape.calculate(self.phase) + (1.0 - progress) * from_value
+                progress * calculate(self.phase) + (1.0 - progress) * from_value
             }
-            LfoStage::Running => shape.calculate(self.phase),
+            LfoStage::Running => calculate(self.phase),
             LfoStage::OneshotComplete => self.last_value,
             LfoStage::Stopped => {
                 unreachable!()
@@ -172,15 +213,60 @@ impl VoiceLfo {
 
         self.last_value = value;
 
-        value * amount
+        let value = self.smooth(value, smoothing);
+
+        self.last_output = if envelope_curve.is_active() {
+            polarity.apply_to_unipolar(value) * amount
+        } else {
+            polarity.apply(value) * amount
+        };
+
+        self.last_output
+    }
+
+    /// One-pole slew towards value with smoothing time constant in seconds
+    fn smooth(&mut self, value: f32, smoothing: f32) -> f32 {
+        if smoothing <= 0.0 {
+            self.smoothed_value = value;
+        } else {
+            let time_constant_samples = f64::from(smoothing) * self.sample_rate.0;
+            let coefficient = 1.0 - (-(self.samples_advanced as f64) / time_constant_samples).exp();
+
+            self.smoothed_value += (value - self.smoothed_value) * coefficient as f32;
+        }
+
+        self.smoothed_value
+    }
+
+    pub fn get_last_output(&self) -> f32 {
+        self.last_output
     }
 
     pub fn restart(&mut self, parameters: &LfoAudioParameters) {
-        self.phase = if parameters.key_sync.get_value() {
-            Phase(0.0)
+        let phase = if parameters.key_sync.get_value() {
+            let spread = f64::from(parameters.key_sync_spread.get_value());
+
+            if spread > 0.0 {
+                Phase(fastrand::f64() * spread)
+            } else {
+                Phase(0.0)
+            }
         } else {
             Phase(fastrand::f64())
         };
+
+        self.restart_with_phase(phase);
+    }
+
+    /// Restart running LFO from phase zero
+    pub fn restart_phase(&mut self) {
+        if !self.is_stopped() {
+            self.restart_with_phase(Phase(0.0));
+        }
+    }
+
+    fn restart_with_phase(&mut self, phase: Phase) {
+        self.phase = phase;
         self.current_shape = None;
 
         match self.stage {
@@ -211,9 +297,83 @@ impl VoiceLfo {
     pub fn envelope_ended(&mut self) {
         self.stage = LfoStage::Stopped;
         self.last_value = 0.0;
+        self.smoothed_value = 0.0;
+        self.last_output = 0.0;
     }
 
     pub fn is_stopped(&self) -> bool {
         matches!(self.stage, LfoStage::Stopped)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use assert_approx_eq::assert_approx_eq;
+
+    use super::*;
+
+    #[test]
+    fn lfo_timing_independent_of_sample_rate() {
+        const DURATION: f64 = 0.3;
+
+        let phases = [44_100.0, 48_000.0, 96_000.0, 192_000.0].map(|sample_rate| {
+            let sample_rate = SampleRate(sample_rate);
+            let mut lfo = VoiceLfo::default();
+
+            lfo.restart_with_phase(Phase(0.0));
+
+            for _ in 0..(DURATION * sample_rate.0).round() as usize {
+                lfo.advance_samples(
+                    1,
+                    sample_rate,
+                    sample_rate.into(),
+                    BpmLfoMultiplier(1.0),
+                    LfoShape::Triangle,
+                    LfoMode::Forever,
+                    1.5,
+                    None,
+                );
+            }
+
+            // Interpolation from initial value is based on time too
+            assert!(matches!(lfo.stage, LfoStage::Running));
+
+            lfo.phase.0
+        });
+
+        for phase in phases {
+            assert_approx_eq!(phase, 0.45, 1e-6);
+        }
+    }
+
+    #[test]
+    fn lfo_smoothing() {
+        let mut lfo = VoiceLfo {
+            sample_rate: SampleRate(44_100.0),
+            ..Default::default()
+        };
+
+        assert_eq!(lfo.smooth(1.0, 0.0), 1.0);
+
+        lfo.envelope_ended();
+
+        // After one time constant, value has covered 1 - 1/e of the distance
+        let mut value = 0.0;
+
+        for _ in 0..441 {
+            value = lfo.smooth(1.0, 0.01);
+        }
+
+        assert_approx_eq!(value, 1.0 - (-1.0f32).exp(), 1e-3);
+
+        // Advancing several samples at a time gives the same result
+        lfo.envelope_ended();
+        lfo.samples_advanced = 21;
+
+        for _ in 0..21 {
+            value = lfo.smooth(1.0, 0.01);
+        }
+
+        assert_approx_eq!(value, 1.0 - (-1.0f32).exp(), 1e-3);
+    }
+}
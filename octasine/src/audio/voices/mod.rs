@@ -1,17 +1,25 @@
+pub mod allocator;
 pub mod envelopes;
 pub mod lfos;
 pub mod log10_table;
+pub mod noise;
+pub mod spread;
+pub mod vibrato;
 
 use array_init::array_init;
 
 use crate::common::*;
+use crate::parameters::legato_envelope_mode::LegatoEnvelopeMode;
+use crate::parameters::OperatorParameter;
 
 use envelopes::*;
 use lfos::*;
+use vibrato::VoiceVibrato;
 
 use super::{
+    gen::lfo::LfoTargetRamps,
     interpolation::{InterpolationDuration, Interpolator},
-    parameters::AudioParameters,
+    parameters::{common::AudioParameter, AudioParameters},
 };
 
 const VELOCITY_INTERPOLATION_DURATION: InterpolationDuration =
@@ -33,6 +41,10 @@ impl KeyVelocity {
     pub fn from_midi_velocity(midi_velocity: u8) -> Self {
         Self(f32::from(midi_velocity) / 127.0)
     }
+
+    pub fn to_midi_velocity(self) -> u8 {
+        (self.0.clamp(0.0, 1.0) * 127.0).round() as u8
+    }
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -72,17 +84,108 @@ pub struct VoiceGlide {
     pub retrigger_lfos: bool,
 }
 
+/// Operator parameter offsets in patch value units, added to LFO additions
+/// during audio generation. Used for clap polyphonic modulation.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct OperatorParameterModulation {
+    pub volume: f32,
+    pub mod_out: f32,
+    pub frequency_fine: f32,
+    /// Only set from note expressions
+    pub panning: f32,
+}
+
+impl OperatorParameterModulation {
+    /// Get offset storage for parameter, if it supports modulation
+    pub fn get_mut(&mut self, parameter: OperatorParameter) -> Option<&mut f32> {
+        match parameter {
+            OperatorParameter::Volume => Some(&mut self.volume),
+            OperatorParameter::ModOut => Some(&mut self.mod_out),
+            OperatorParameter::FrequencyFine => Some(&mut self.frequency_fine),
+            _ => None,
+        }
+    }
+
+    pub fn combine(self, other: Self) -> Self {
+        Self {
+            volume: self.volume + other.volume,
+            mod_out: self.mod_out + other.mod_out,
+            frequency_fine: self.frequency_fine + other.frequency_fine,
+            panning: self.panning + other.panning,
+        }
+    }
+
+    /// Add offset to LFO addition
+    #[inline]
+    pub fn add_to_lfo_addition(offset: f32, lfo_addition: Option<f32>) -> Option<f32> {
+        if offset == 0.0 {
+            lfo_addition
+        } else {
+            Some(lfo_addition.unwrap_or(0.0) + offset)
+        }
+    }
+}
+
+/// Per-note expression state
+#[derive(Debug, Copy, Clone)]
+pub struct VoiceExpression {
+    pub frequency_factor: f64,
+    pub volume: f64,
+    /// Offset in operator panning patch value units
+    pub panning: f32,
+}
+
+impl Default for VoiceExpression {
+    fn default() -> Self {
+        Self {
+            frequency_factor: 1.0,
+            volume: 1.0,
+            panning: 0.0,
+        }
+    }
+}
+
+impl VoiceExpression {
+    pub fn apply(&mut self, expression: NoteExpression) {
+        match expression {
+            NoteExpression::Tuning(semitones) => {
+                self.frequency_factor = (semitones.clamp(-120.0, 120.0) / 12.0).exp2();
+            }
+            NoteExpression::Volume(volume) => {
+                self.volume = volume.clamp(0.0, 4.0);
+            }
+            NoteExpression::Pan(pan) => {
+                self.panning = (pan.clamp(0.0, 1.0) - 0.5) as f32;
+            }
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone)]
 pub struct VoiceOperator {
     pub last_phase: Phase,
+    /// Cross feedback modulation input (left and right) from lower-index
+    /// operators, generated during previous sample
+    pub cross_feedback_input: [f64; 2],
+    /// Whether note key and velocity were within operator ranges at
+    /// note-on
+    pub in_note_range: bool,
     pub volume_envelope: VoiceOperatorVolumeEnvelope,
+    pub parameter_modulation: OperatorParameterModulation,
+    pub noise: noise::NoiseGenerator,
+    pub spread_delay: spread::SpreadDelayLine,
 }
 
 impl Default for VoiceOperator {
     fn default() -> Self {
         Self {
             last_phase: Phase(0.0),
+            cross_feedback_input: [0.0; 2],
+            in_note_range: true,
             volume_envelope: VoiceOperatorVolumeEnvelope::default(),
+            parameter_modulation: Default::default(),
+            noise: Default::default(),
+            spread_delay: Default::default(),
         }
     }
 }
@@ -98,13 +201,22 @@ pub struct Voice {
     key_velocity_interpolator: Interpolator,
     pub operators: [VoiceOperator; NUM_OPERATORS],
     pub lfos: [VoiceLfo; NUM_LFOS],
+    /// LFO target values when LFOs run at control rate
+    pub lfo_target_ramps: LfoTargetRamps,
+    pub vibrato: VoiceVibrato,
+    pub expression: VoiceExpression,
+    /// Random value in range -1.0 to 1.0, sampled at note-on
+    pub note_random_value: f32,
+    /// MIDI channel of note that most recently started voice
+    pub midi_channel: u8,
     #[cfg(feature = "clap")]
     pub clap_note_id: Option<i32>,
 }
 
 impl Voice {
     pub fn new(midi_pitch: MidiPitch, is_monophonic: bool) -> Self {
-        let operators = [VoiceOperator::default(); NUM_OPERATORS];
+        // Initialize separately to get independent noise generators
+        let operators = ::std::array::from_fn(|_| VoiceOperator::default());
 
         Self {
             is_monophonic,
@@ -121,11 +233,30 @@ impl Voice {
             ),
             operators,
             lfos: array_init(|_| VoiceLfo::default()),
+            lfo_target_ramps: Default::default(),
+            vibrato: Default::default(),
+            expression: Default::default(),
+            note_random_value: 0.0,
+            midi_channel: 0,
             #[cfg(feature = "clap")]
             clap_note_id: None,
         }
     }
 
+    /// Reset all state of previous note, e.g., when voice is stolen for a
+    /// new one. Envelope volumes are kept, so that envelopes of new note
+    /// restart from them instead of clicking.
+    pub fn reset(&mut self, midi_pitch: MidiPitch) {
+        let envelopes: [_; NUM_OPERATORS] =
+            ::std::array::from_fn(|i| self.operators[i].volume_envelope);
+
+        *self = Self::new(midi_pitch, self.is_monophonic);
+
+        for (operator, envelope) in self.operators.iter_mut().zip(envelopes) {
+            operator.volume_envelope = envelope;
+        }
+    }
+
     pub fn advance_interpolators_one_sample(&mut self, sample_rate: SampleRate) {
         self.key_velocity_interpolator
             .advance_one_sample(sample_rate, &mut |_| ());
@@ -146,10 +277,18 @@ impl Voice {
         target_key: Option<VoiceGlide>,
         #[cfg_attr(not(feature = "clap"), allow(unused_variables))] opt_clap_note_id: Option<i32>,
     ) {
+        // Monophonic voice receiving new note while previous key is held
+        let legato = self.is_monophonic && self.active && self.key_pressed;
+
         if self.active {
             self.key_velocity_interpolator.set_value(velocity.0)
         } else {
-            self.key_velocity_interpolator.force_set_value(velocity.0)
+            self.key_velocity_interpolator.force_set_value(velocity.0);
+
+            // Don't let output of previous note leak into new one
+            for operator in self.operators.iter_mut() {
+                operator.spread_delay.clear();
+            }
         }
 
         if let Some(key) = initial_key {
@@ -173,14 +312,53 @@ impl Voice {
         }
 
         if retrigger_envelopes {
-            for operator in self.operators.iter_mut() {
-                operator.volume_envelope.restart(self.is_monophonic);
+            let envelope_mode = if legato {
+                parameters.legato_envelope_mode.get_value()
+            } else if self.is_monophonic {
+                LegatoEnvelopeMode::Partial
+            } else {
+                LegatoEnvelopeMode::Retrigger
+            };
+
+            for (operator, operator_parameters) in
+                self.operators.iter_mut().zip(parameters.operators.iter())
+            {
+                let envelope = &mut operator.volume_envelope;
+
+                match envelope_mode {
+                    LegatoEnvelopeMode::Partial => envelope.restart(true),
+                    LegatoEnvelopeMode::Retrigger => envelope.restart(false),
+                    LegatoEnvelopeMode::Continue => continue,
+                }
+
+                envelope.set_velocity_levels(&operator_parameters.volume_envelope, velocity.0);
             }
         }
+
+        // Modulation and expressions are tied to notes, so reset them on
+        // every key press. Key and velocity ranges are evaluated here too.
+        let key = self.key();
+        let midi_velocity = velocity.to_midi_velocity();
+
+        for (operator, operator_parameters) in
+            self.operators.iter_mut().zip(parameters.operators.iter())
+        {
+            operator.parameter_modulation = Default::default();
+            operator.in_note_range = operator_parameters.key_in_range(key)
+                && operator_parameters.velocity_in_range(midi_velocity);
+        }
+
+        self.expression = Default::default();
+        self.note_random_value = fastrand::f32() * 2.0 - 1.0;
+
         if retrigger_lfos {
             for (lfo, parameters) in self.lfos.iter_mut().zip(parameters.lfos.iter()) {
                 lfo.restart(parameters);
             }
+
+            self.lfo_target_ramps.request_update();
+
+            self.vibrato.restart();
         }
 
         #[cfg(feature = "clap")]
@@ -215,6 +393,14 @@ impl Voice {
         self.midi_pitch.key
     }
 
+    /// Pitch interval in semitones from current (possibly gliding) pitch
+    /// to key
+    pub fn semitones_to_key(&self, key: u8) -> f64 {
+        let current_frequency_factor = f64::from(self.pitch_interpolator.get_value());
+
+        12.0 * (MidiPitch::new(key).frequency_factor / current_frequency_factor).log2()
+    }
+
     #[inline]
     pub fn release_key(&mut self) {
         self.key_pressed = false;
@@ -238,8 +424,11 @@ impl Voice {
                 lfo.envelope_ended();
             }
 
+            self.lfo_target_ramps = Default::default();
+
             for operator in self.operators.iter_mut() {
                 operator.last_phase.0 = 0.0;
+                operator.cross_feedback_input = [0.0; 2];
             }
 
             self.active = false;
@@ -0,0 +1,68 @@
+/// Number of stored samples. Fits maximum operator spread at sample rates up
+/// to 192 kHz. Power of two, so wrapping is done with a bit mask.
+const LEN: usize = 1024;
+const MASK: usize = LEN - 1;
+
+/// Short delay line used for operator stereo spread
+///
+/// Fixed size, so that voices can be created without allocating.
+#[derive(Debug, Clone, Copy)]
+pub struct SpreadDelayLine {
+    buffer: [f32; LEN],
+    write_index: usize,
+}
+
+impl Default for SpreadDelayLine {
+    fn default() -> Self {
+        Self {
+            buffer: [0.0; LEN],
+            write_index: 0,
+        }
+    }
+}
+
+impl SpreadDelayLine {
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Store sample and return sample from given number of samples ago,
+    /// linearly interpolated. Delay is clamped to buffer length.
+    #[inline]
+    pub fn process(&mut self, sample: f64, delay_samples: f64) -> f64 {
+        self.buffer[self.write_index] = sample as f32;
+
+        let delay = delay_samples.clamp(0.0, (LEN - 2) as f64);
+        let delay_whole = delay as usize;
+        let fraction = delay - delay_whole as f64;
+
+        let a = f64::from(self.buffer[self.write_index.wrapping_sub(delay_whole) & MASK]);
+        let b = f64::from(self.buffer[self.write_index.wrapping_sub(delay_whole + 1) & MASK]);
+
+        self.write_index = (self.write_index + 1) & MASK;
+
+        a + (b - a) * fraction
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delay_line() {
+        let mut delay_line = SpreadDelayLine::default();
+
+        let output: Vec<f64> = (1..=6)
+            .map(|i| delay_line.process(f64::from(i), 2.5))
+            .collect();
+
+        assert_eq!(output, vec![0.0, 0.0, 0.5, 1.5, 2.5, 3.5]);
+
+        assert_eq!(delay_line.process(7.0, 0.0), 7.0);
+
+        delay_line.clear();
+
+        assert_eq!(delay_line.process(1.0, 1.0), 0.0);
+    }
+}
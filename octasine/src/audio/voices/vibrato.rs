@@ -0,0 +1,78 @@
+use std::f64::consts::TAU;
+
+use crate::common::*;
+
+/// Time over which vibrato depth is faded in once delay has passed
+const FADE_IN_TIME: f64 = 0.25;
+
+#[derive(Debug, Clone, Copy)]
+pub struct VoiceVibrato {
+    phase: Phase,
+    /// Time since note-on
+    time: f64,
+}
+
+impl Default for VoiceVibrato {
+    fn default() -> Self {
+        Self {
+            phase: Phase(0.0),
+            time: 0.0,
+        }
+    }
+}
+
+impl VoiceVibrato {
+    pub fn restart(&mut self) {
+        *self = Default::default();
+    }
+
+    /// Advance one sample and return frequency multiplier
+    pub fn advance_one_sample(
+        &mut self,
+        time_per_sample: TimePerSample,
+        rate: f64,
+        depth_cents: f64,
+        delay: f64,
+    ) -> f64 {
+        let time_since_delay = self.time - delay;
+
+        self.time += time_per_sample.0;
+
+        if (depth_cents == 0.0) | (time_since_delay < 0.0) {
+            return 1.0;
+        }
+
+        let fade_in = (time_since_delay / FADE_IN_TIME).min(1.0);
+        let cents = depth_cents * fade_in * (self.phase.0 * TAU).sin();
+
+        self.phase.0 = (self.phase.0 + rate * time_per_sample.0).fract();
+
+        (cents / 1200.0).exp2()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vibrato_delay_and_depth() {
+        let time_per_sample = TimePerSample(1.0 / 1000.0);
+        let mut vibrato = VoiceVibrato::default();
+
+        // No modulation during delay
+        for _ in 0..500 {
+            assert_eq!(
+                vibrato.advance_one_sample(time_per_sample, 5.0, 100.0, 0.5),
+                1.0
+            );
+        }
+
+        let max = (0..2000)
+            .map(|_| vibrato.advance_one_sample(time_per_sample, 5.0, 100.0, 0.5))
+            .fold(1.0, f64::max);
+
+        // Depth of 100 cents gives at most one semitone up
+        assert!(max > 1.05 && max <= 2.0f64.powf(1.0 / 12.0) + 1e-9);
+    }
+}
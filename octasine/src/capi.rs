@@ -0,0 +1,162 @@
+//! C API for embedding the audio engine in non-Rust hosts
+//!
+//! See `include/octasine.h` for declarations. All functions taking a synth
+//! pointer require it to come from `octasine_synth_new` and not to have been
+//! freed. Null pointers are ignored.
+
+use std::slice;
+
+use crate::synth::Synth;
+
+/// Create synth. Free with `octasine_synth_free`.
+#[no_mangle]
+pub extern "C" fn octasine_synth_new(sample_rate: f64) -> *mut Synth {
+    Box::into_raw(Box::new(Synth::new(sample_rate)))
+}
+
+/// # Safety
+///
+/// Pointer must have been returned by `octasine_synth_new` and not yet freed
+#[no_mangle]
+pub unsafe extern "C" fn octasine_synth_free(synth: *mut Synth) {
+    if !synth.is_null() {
+        drop(Box::from_raw(synth));
+    }
+}
+
+/// # Safety
+///
+/// Pointer must be valid (see module documentation)
+#[no_mangle]
+pub unsafe extern "C" fn octasine_synth_set_sample_rate(synth: *mut Synth, sample_rate: f64) {
+    if let Some(synth) = synth.as_mut() {
+        synth.set_sample_rate(sample_rate);
+    }
+}
+
+/// Enqueue MIDI message of one to three bytes, to be processed at given frame
+/// offset into next rendered buffer. Returns false if message length is
+/// invalid.
+///
+/// # Safety
+///
+/// Synth pointer must be valid (see module documentation) and data must
+/// point to at least len bytes
+#[no_mangle]
+pub unsafe extern "C" fn octasine_synth_send_midi(
+    synth: *mut Synth,
+    data: *const u8,
+    len: usize,
+    delta_frames: u32,
+) -> bool {
+    if data.is_null() || !(1..=3).contains(&len) {
+        return false;
+    }
+
+    if let Some(synth) = synth.as_mut() {
+        let mut message = [0u8; 3];
+
+        message[..len].copy_from_slice(slice::from_raw_parts(data, len));
+
+        synth.send_midi(message, delta_frames);
+
+        true
+    } else {
+        false
+    }
+}
+
+/// # Safety
+///
+/// Pointer must be valid (see module documentation)
+#[no_mangle]
+pub unsafe extern "C" fn octasine_synth_num_parameters(synth: *const Synth) -> u32 {
+    synth
+        .as_ref()
+        .map(|synth| synth.num_parameters() as u32)
+        .unwrap_or(0)
+}
+
+/// Get parameter value in range 0.0 to 1.0. Returns negative value if index
+/// is invalid.
+///
+/// # Safety
+///
+/// Pointer must be valid (see module documentation)
+#[no_mangle]
+pub unsafe extern "C" fn octasine_synth_get_parameter(synth: *const Synth, index: u32) -> f32 {
+    synth
+        .as_ref()
+        .and_then(|synth| synth.get_parameter(index as usize))
+        .unwrap_or(-1.0)
+}
+
+/// Set parameter value in range 0.0 to 1.0
+///
+/// # Safety
+///
+/// Pointer must be valid (see module documentation)
+#[no_mangle]
+pub unsafe extern "C" fn octasine_synth_set_parameter(synth: *mut Synth, index: u32, value: f32) {
+    if let Some(synth) = synth.as_mut() {
+        synth.set_parameter(index as usize, value);
+    }
+}
+
+/// Render len samples into each of the left and right buffers
+///
+/// # Safety
+///
+/// Synth pointer must be valid (see module documentation) and buffers must
+/// hold at least len samples each
+#[no_mangle]
+pub unsafe extern "C" fn octasine_synth_render(
+    synth: *mut Synth,
+    lefts: *mut f32,
+    rights: *mut f32,
+    len: usize,
+) {
+    if lefts.is_null() || rights.is_null() {
+        return;
+    }
+
+    if let Some(synth) = synth.as_mut() {
+        synth.render(
+            slice::from_raw_parts_mut(lefts, len),
+            slice::from_raw_parts_mut(rights, len),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capi_render() {
+        let mut lefts = [0.0f32; 256];
+        let mut rights = [0.0f32; 256];
+
+        unsafe {
+            let synth = octasine_synth_new(44100.0);
+
+            assert!(octasine_synth_num_parameters(synth) > 0);
+            assert!(!octasine_synth_send_midi(synth, [0u8; 4].as_ptr(), 4, 0));
+            assert!(octasine_synth_send_midi(
+                synth,
+                [0x90, 60, 100].as_ptr(),
+                3,
+                0
+            ));
+
+            octasine_synth_set_parameter(synth, 0, 0.5);
+
+            assert_eq!(octasine_synth_get_parameter(synth, 0), 0.5);
+
+            octasine_synth_render(synth, lefts.as_mut_ptr(), rights.as_mut_ptr(), lefts.len());
+            octasine_synth_free(synth);
+        }
+
+        assert!(lefts.iter().any(|s| *s != 0.0));
+    }
+}
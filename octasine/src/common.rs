@@ -1,7 +1,8 @@
-use crate::parameters::ParameterKey;
+use crate::parameters::{Parameter, ParameterKey};
 
 pub const NUM_OPERATORS: usize = 4;
 pub const NUM_LFOS: usize = 4;
+pub const NUM_PATCHES: usize = 128;
 
 pub const OPERATOR_MOD_INDEX_STEPS: [f32; 16] = [
     0.0, 0.01, 0.1, 0.2, 0.5, 1.0, 2.0, 3.0, 5.0, 10.0, 20.0, 35.0, 50.0, 75.0, 100.0, 1000.0,
@@ -12,6 +13,12 @@ pub type IndexSet<K> = indexmap::IndexSet<K, ahash::RandomState>;
 
 pub trait WaveformChoices: PartialEq + Copy {
     fn calculate_for_current(self, phase: Phase) -> f32;
+    /// Calculate value with self-modulation (feedback) applied.
+    ///
+    /// Defaults to ignoring feedback.
+    fn calculate_with_feedback(self, phase: Phase, _feedback: f32) -> f32 {
+        self.calculate_for_current(phase)
+    }
     fn choices() -> &'static [Self];
 }
 
@@ -98,6 +105,55 @@ pub enum NoteEventInner {
     ClapBpm {
         bpm: BeatsPerMinute,
     },
+    /// Host transport state. Song position (in beats) is only set while
+    /// playing
+    Transport {
+        playing: bool,
+        song_position: Option<f64>,
+    },
+    ClapNoteExpression {
+        key: Option<u8>,
+        clap_note_id: Option<i32>,
+        expression: NoteExpression,
+    },
+    /// Polyphonic (or global, if key and note id are unset) parameter
+    /// modulation
+    ClapParameterModulation {
+        parameter: Parameter,
+        key: Option<u8>,
+        clap_note_id: Option<i32>,
+        /// Offset in patch value units (0.0 to 1.0 range)
+        amount: f32,
+    },
+}
+
+impl NoteEventInner {
+    /// MIDI representation of note and MIDI events, used for showing
+    /// received events in GUI
+    pub fn as_midi_data(&self) -> Option<[u8; 3]> {
+        let to_midi_value = |v: f64| (v * 127.0).round().clamp(0.0, 127.0) as u8;
+
+        match *self {
+            Self::Midi { data } => Some(data),
+            Self::ClapNoteOn { key, velocity, .. } => {
+                Some([0x90, key, to_midi_value(velocity).max(1)])
+            }
+            Self::ClapNoteOff { key } => Some([0x80, key, 0]),
+            Self::ClapNotePressure { key, pressure } => Some([0xA0, key, to_midi_value(pressure)]),
+            _ => None,
+        }
+    }
+}
+
+/// Per-note expression, in clap value ranges
+#[derive(Debug, Clone, Copy)]
+pub enum NoteExpression {
+    /// Semitones, -120.0 to 120.0
+    Tuning(f64),
+    /// Linear amplitude, 0.0 to 4.0
+    Volume(f64),
+    /// 0.0 (left) to 1.0 (right)
+    Pan(f64),
 }
 
 #[derive(Debug, Clone, Copy)]
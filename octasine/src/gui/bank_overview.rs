@@ -0,0 +1,207 @@
+use compact_str::CompactString;
+use iced_baseview::alignment::{Horizontal, Vertical};
+use iced_baseview::widget::canvas::{
+    event, Cache, Canvas, Cursor, Frame, Geometry, Path, Program, Stroke, Text,
+};
+use iced_baseview::{mouse, Color, Element, Length, Point, Rectangle, Size};
+
+use crate::common::NUM_PATCHES;
+use crate::sync::GuiSyncHandle;
+
+use super::style::Theme;
+use super::{Message, FONT_SIZE, LINE_HEIGHT};
+
+const COLUMNS: usize = 16;
+const ROWS: usize = NUM_PATCHES / COLUMNS;
+const CELL_SIZE: f32 = LINE_HEIGHT as f32 * 1.5;
+const GRID_WIDTH: f32 = COLUMNS as f32 * CELL_SIZE;
+const GRID_HEIGHT: f32 = ROWS as f32 * CELL_SIZE;
+/// Height of area below grid showing hovered patch name
+const NAME_HEIGHT: f32 = LINE_HEIGHT as f32 * 2.0;
+
+const WIDTH: f32 = GRID_WIDTH + 1.0;
+const HEIGHT: f32 = GRID_HEIGHT + 1.0 + NAME_HEIGHT;
+
+#[derive(Debug, Clone)]
+pub struct Appearance {
+    pub background_color: Color,
+    pub border_color: Color,
+    pub used_color: Color,
+    pub current_color: Color,
+    pub hovered_color: Color,
+    pub text_color: Color,
+}
+
+pub trait StyleSheet {
+    fn appearance(&self) -> Appearance;
+}
+
+#[derive(Default)]
+pub struct BankOverviewState {
+    hovered: Option<usize>,
+}
+
+/// Grid of all patch bank slots, showing which ones contain non-default
+/// patches. Clicking a slot switches to it.
+pub struct BankOverview {
+    patch_index: usize,
+    patch_names: Vec<CompactString>,
+    used_slots: Vec<bool>,
+    cache: Cache,
+}
+
+impl BankOverview {
+    pub fn new<H: GuiSyncHandle>(sync_handle: &H) -> Self {
+        let (patch_index, patch_names) = sync_handle.get_patches();
+
+        Self {
+            patch_index,
+            patch_names,
+            used_slots: sync_handle.get_used_patch_slots(),
+            cache: Cache::new(),
+        }
+    }
+
+    pub fn theme_changed(&mut self) {
+        self.cache.clear();
+    }
+
+    pub fn view(&self) -> Element<Message, Theme> {
+        Canvas::new(self)
+            .width(Length::Fixed(WIDTH))
+            .height(Length::Fixed(HEIGHT))
+            .into()
+    }
+
+    fn cell_rect(index: usize) -> Rectangle {
+        let x = (index % COLUMNS) as f32 * CELL_SIZE;
+        let y = (index / COLUMNS) as f32 * CELL_SIZE;
+
+        Rectangle::new(
+            Point::new(x + 0.5, y + 0.5),
+            Size::new(CELL_SIZE, CELL_SIZE),
+        )
+    }
+
+    fn cell_at(position: Point) -> Option<usize> {
+        if position.x < 0.0
+            || position.y < 0.0
+            || position.x >= GRID_WIDTH
+            || position.y >= GRID_HEIGHT
+        {
+            return None;
+        }
+
+        let column = (position.x / CELL_SIZE) as usize;
+        let row = (position.y / CELL_SIZE) as usize;
+
+        Some(row * COLUMNS + column).filter(|index| *index < NUM_PATCHES)
+    }
+
+    fn draw_grid(&self, frame: &mut Frame, theme: &Theme) {
+        let appearance = theme.appearance();
+
+        for index in 0..NUM_PATCHES {
+            let rect = Self::cell_rect(index);
+            let path = Path::rectangle(rect.position(), rect.size());
+
+            let color = if self.used_slots.get(index).copied().unwrap_or(false) {
+                appearance.used_color
+            } else {
+                appearance.background_color
+            };
+
+            frame.fill(&path, color);
+            frame.stroke(&path, Stroke::default().with_color(appearance.border_color));
+        }
+
+        let rect = Self::cell_rect(self.patch_index);
+
+        frame.stroke(
+            &Path::rectangle(rect.position(), rect.size()),
+            Stroke::default()
+                .with_width(2.0)
+                .with_color(appearance.current_color),
+        );
+    }
+
+    fn draw_hovered(&self, frame: &mut Frame, theme: &Theme, hovered: Option<usize>) {
+        let appearance = theme.appearance();
+
+        if let Some(index) = hovered {
+            let rect = Self::cell_rect(index);
+
+            frame.stroke(
+                &Path::rectangle(rect.position(), rect.size()),
+                Stroke::default()
+                    .with_width(2.0)
+                    .with_color(appearance.hovered_color),
+            );
+        }
+
+        let index = hovered.unwrap_or(self.patch_index);
+
+        if let Some(name) = self.patch_names.get(index) {
+            frame.fill_text(Text {
+                content: name.to_string(),
+                position: Point::new(WIDTH / 2.0, GRID_HEIGHT + 1.0 + NAME_HEIGHT / 2.0),
+                font: theme.font_regular(),
+                size: f32::from(FONT_SIZE),
+                color: appearance.text_color,
+                horizontal_alignment: Horizontal::Center,
+                vertical_alignment: Vertical::Center,
+                ..Default::default()
+            });
+        }
+    }
+}
+
+impl Program<Message, Theme> for BankOverview {
+    type State = BankOverviewState;
+
+    fn draw(
+        &self,
+        state: &Self::State,
+        theme: &Theme,
+        bounds: Rectangle,
+        _cursor: Cursor,
+    ) -> Vec<Geometry> {
+        let grid = self.cache.draw(bounds.size(), |frame| {
+            self.draw_grid(frame, theme);
+        });
+
+        let mut frame = Frame::new(bounds.size());
+
+        self.draw_hovered(&mut frame, theme, state.hovered);
+
+        vec![grid, frame.into_geometry()]
+    }
+
+    fn update(
+        &self,
+        state: &mut Self::State,
+        event: event::Event,
+        bounds: Rectangle,
+        cursor: Cursor,
+    ) -> (event::Status, Option<Message>) {
+        match event {
+            event::Event::Mouse(mouse::Event::CursorMoved { position }) => {
+                state.hovered = bounds
+                    .contains(position)
+                    .then(|| Point::new(position.x - bounds.x, position.y - bounds.y))
+                    .and_then(Self::cell_at);
+            }
+            event::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                // Touch and pen input might not move cursor before pressing
+                if let Some(index) = cursor.position_in(&bounds).and_then(Self::cell_at) {
+                    state.hovered = Some(index);
+
+                    return (event::Status::Captured, Some(Message::ChangePatch(index)));
+                }
+            }
+            _ => (),
+        }
+
+        (event::Status::Ignored, None)
+    }
+}
@@ -4,13 +4,18 @@ use iced_baseview::widget::canvas::{
 };
 use iced_baseview::{Color, Element, Length, Point, Rectangle, Size};
 
+use crate::parameters::anti_alias::AntiAliasValue;
 use crate::parameters::glide_bpm_sync::GlideBpmSyncValue;
 use crate::parameters::glide_mode::{GlideMode, GlideModeValue};
 use crate::parameters::glide_retrigger::GlideRetriggerValue;
 use crate::parameters::lfo_key_sync::LfoKeySyncValue;
 use crate::parameters::lfo_mode::LfoMode;
+use crate::parameters::lfo_polarity::{LfoPolarity, LfoPolarityValue};
+use crate::parameters::lfo_transport_sync::{LfoTransportSync, LfoTransportSyncValue};
 use crate::parameters::list::MasterParameter;
-use crate::parameters::operator_envelope::OperatorEnvelopeGroupValue;
+use crate::parameters::midi_out::MidiOutValue;
+use crate::parameters::operator_noise_stereo::OperatorNoiseStereoValue;
+use crate::parameters::vibrato::VibratoModWheelValue;
 use crate::parameters::voice_mode::{VoiceMode, VoiceModeValue};
 use crate::parameters::{
     LfoActiveValue, LfoBpmSyncValue, LfoModeValue, LfoParameter, OperatorActiveValue,
@@ -57,6 +62,22 @@ pub fn operator_mute_button<H: GuiSyncHandle>(
     )
 }
 
+pub fn operator_noise_stereo_button<H: GuiSyncHandle>(
+    sync_handle: &H,
+    operator_index: usize,
+) -> BooleanButton {
+    BooleanButton::new(
+        sync_handle,
+        Parameter::Operator(operator_index as u8, OperatorParameter::NoiseStereo),
+        "S",
+        LINE_HEIGHT,
+        LINE_HEIGHT,
+        |v| OperatorNoiseStereoValue::new_from_patch(v).get(),
+        |on| OperatorNoiseStereoValue::new_from_audio(on).to_patch(),
+        BooleanButtonStyle::Regular,
+    )
+}
+
 pub fn lfo_bpm_sync_button<H: GuiSyncHandle>(sync_handle: &H, lfo_index: usize) -> BooleanButton {
     BooleanButton::new(
         sync_handle,
@@ -83,88 +104,132 @@ pub fn lfo_key_sync_button<H: GuiSyncHandle>(sync_handle: &H, lfo_index: usize)
     )
 }
 
-pub fn lfo_mode_button<H: GuiSyncHandle>(sync_handle: &H, lfo_index: usize) -> BooleanButton {
+pub fn lfo_transport_sync_button<H: GuiSyncHandle>(
+    sync_handle: &H,
+    lfo_index: usize,
+) -> BooleanButton {
     BooleanButton::new(
         sync_handle,
-        Parameter::Lfo(lfo_index as u8, LfoParameter::Mode),
-        "1",
+        Parameter::Lfo(lfo_index as u8, LfoParameter::TransportSync),
+        "T",
         LINE_HEIGHT,
         LINE_HEIGHT,
-        |v| LfoModeValue::new_from_patch(v).get() == LfoMode::Once,
-        |is_oneshot| {
-            if is_oneshot {
-                LfoModeValue::new_from_audio(LfoMode::Once).to_patch()
+        |v| LfoTransportSyncValue::new_from_patch(v).get() == LfoTransportSync::SongPosition,
+        |on| {
+            if on {
+                LfoTransportSyncValue::new_from_audio(LfoTransportSync::SongPosition).to_patch()
             } else {
-                LfoModeValue::new_from_audio(LfoMode::Forever).to_patch()
+                LfoTransportSyncValue::new_from_audio(LfoTransportSync::Off).to_patch()
             }
         },
         BooleanButtonStyle::Regular,
     )
 }
 
-pub fn lfo_active_button<H: GuiSyncHandle>(sync_handle: &H, lfo_index: usize) -> BooleanButton {
+pub fn lfo_transport_restart_button<H: GuiSyncHandle>(
+    sync_handle: &H,
+    lfo_index: usize,
+) -> BooleanButton {
     BooleanButton::new(
         sync_handle,
-        Parameter::Lfo(lfo_index as u8, LfoParameter::Active),
-        "M",
+        Parameter::Lfo(lfo_index as u8, LfoParameter::TransportSync),
+        "R",
         LINE_HEIGHT,
         LINE_HEIGHT,
-        |v| LfoActiveValue::new_from_patch(v).get() == 0.0,
-        |is_muted| {
-            if is_muted {
-                0.0
+        |v| LfoTransportSyncValue::new_from_patch(v).get() == LfoTransportSync::Restart,
+        |on| {
+            if on {
+                LfoTransportSyncValue::new_from_audio(LfoTransportSync::Restart).to_patch()
             } else {
-                1.0
+                LfoTransportSyncValue::new_from_audio(LfoTransportSync::Off).to_patch()
             }
         },
-        BooleanButtonStyle::Mute,
+        BooleanButtonStyle::Regular,
     )
 }
 
-pub fn envelope_group_a_button<H: GuiSyncHandle>(
+pub fn lfo_polarity_up_button<H: GuiSyncHandle>(
     sync_handle: &H,
-    operator_index: usize,
+    lfo_index: usize,
 ) -> BooleanButton {
     BooleanButton::new(
         sync_handle,
-        Parameter::Operator(operator_index as u8, OperatorParameter::EnvelopeLockGroup),
-        "A",
+        Parameter::Lfo(lfo_index as u8, LfoParameter::Polarity),
+        "+",
         LINE_HEIGHT,
         LINE_HEIGHT,
-        |v| OperatorEnvelopeGroupValue::new_from_patch(v).get() == OperatorEnvelopeGroupValue::A,
-        |is_active| {
-            if is_active {
-                OperatorEnvelopeGroupValue::A.to_patch()
+        |v| LfoPolarityValue::new_from_patch(v).get() == LfoPolarity::UnipolarUp,
+        |on| {
+            if on {
+                LfoPolarityValue::new_from_audio(LfoPolarity::UnipolarUp).to_patch()
             } else {
-                OperatorEnvelopeGroupValue::Off.to_patch()
+                LfoPolarityValue::new_from_audio(LfoPolarity::Bipolar).to_patch()
             }
         },
         BooleanButtonStyle::Regular,
     )
 }
 
-pub fn envelope_group_b_button<H: GuiSyncHandle>(
+pub fn lfo_polarity_down_button<H: GuiSyncHandle>(
     sync_handle: &H,
-    operator_index: usize,
+    lfo_index: usize,
 ) -> BooleanButton {
     BooleanButton::new(
         sync_handle,
-        Parameter::Operator(operator_index as u8, OperatorParameter::EnvelopeLockGroup),
-        "B",
+        Parameter::Lfo(lfo_index as u8, LfoParameter::Polarity),
+        "-",
         LINE_HEIGHT,
         LINE_HEIGHT,
-        |v| OperatorEnvelopeGroupValue::new_from_patch(v).get() == OperatorEnvelopeGroupValue::B,
-        |is_active| {
-            if is_active {
-                OperatorEnvelopeGroupValue::B.to_patch()
+        |v| LfoPolarityValue::new_from_patch(v).get() == LfoPolarity::UnipolarDown,
+        |on| {
+            if on {
+                LfoPolarityValue::new_from_audio(LfoPolarity::UnipolarDown).to_patch()
             } else {
-                OperatorEnvelopeGroupValue::Off.to_patch()
+                LfoPolarityValue::new_from_audio(LfoPolarity::Bipolar).to_patch()
             }
         },
         BooleanButtonStyle::Regular,
     )
 }
 
+pub fn lfo_mode_button<H: GuiSyncHandle>(sync_handle: &H, lfo_index: usize) -> BooleanButton {
+    BooleanButton::new(
+        sync_handle,
+        Parameter::Lfo(lfo_index as u8, LfoParameter::Mode),
+        "1",
+        LINE_HEIGHT,
+        LINE_HEIGHT,
+        |v| LfoModeValue::new_from_patch(v).get() == LfoMode::Once,
+        |is_oneshot| {
+            if is_oneshot {
+                LfoModeValue::new_from_audio(LfoMode::Once).to_patch()
+            } else {
+                LfoModeValue::new_from_audio(LfoMode::Forever).to_patch()
+            }
+        },
+        BooleanButtonStyle::Regular,
+    )
+}
+
+pub fn lfo_active_button<H: GuiSyncHandle>(sync_handle: &H, lfo_index: usize) -> BooleanButton {
+    BooleanButton::new(
+        sync_handle,
+        Parameter::Lfo(lfo_index as u8, LfoParameter::Active),
+        "M",
+        LINE_HEIGHT,
+        LINE_HEIGHT,
+        |v| LfoActiveValue::new_from_patch(v).get() == 0.0,
+        |is_muted| {
+            if is_muted {
+                0.0
+            } else {
+                1.0
+            }
+        },
+        BooleanButtonStyle::Mute,
+    )
+}
+
 pub fn voice_mode_button<H: GuiSyncHandle>(sync_handle: &H) -> BooleanButton {
     BooleanButton::new(
         sync_handle,
@@ -229,6 +294,45 @@ pub fn glide_retrigger_button<H: GuiSyncHandle>(sync_handle: &H) -> BooleanButto
     )
 }
 
+pub fn midi_out_button<H: GuiSyncHandle>(sync_handle: &H) -> BooleanButton {
+    BooleanButton::new(
+        sync_handle,
+        Parameter::Master(MasterParameter::MidiOut),
+        "OUT",
+        LINE_HEIGHT * 2,
+        LINE_HEIGHT,
+        |v| MidiOutValue::new_from_patch(v).get(),
+        |b| MidiOutValue::new_from_audio(b).to_patch(),
+        BooleanButtonStyle::Regular,
+    )
+}
+
+pub fn anti_alias_button<H: GuiSyncHandle>(sync_handle: &H) -> BooleanButton {
+    BooleanButton::new(
+        sync_handle,
+        Parameter::Master(MasterParameter::AntiAlias),
+        "AA",
+        LINE_HEIGHT * 2,
+        LINE_HEIGHT,
+        |v| AntiAliasValue::new_from_patch(v).get(),
+        |b| AntiAliasValue::new_from_audio(b).to_patch(),
+        BooleanButtonStyle::Regular,
+    )
+}
+
+pub fn vibrato_mod_wheel_button<H: GuiSyncHandle>(sync_handle: &H) -> BooleanButton {
+    BooleanButton::new(
+        sync_handle,
+        Parameter::Master(MasterParameter::VibratoModWheel),
+        "MW",
+        LINE_HEIGHT * 2,
+        LINE_HEIGHT,
+        |v| VibratoModWheelValue::new_from_patch(v).get(),
+        |b| VibratoModWheelValue::new_from_audio(b).to_patch(),
+        BooleanButtonStyle::Regular,
+    )
+}
+
 pub struct BooleanButton {
     parameter: WrappedParameter,
     on: bool,
@@ -8,11 +8,9 @@ use iced_baseview::{
     Element, Length,
 };
 
-use super::LINE_HEIGHT;
-
 use super::{
     style::{container::ContainerStyle, Theme},
-    Message,
+    GuiDensity, Message,
 };
 
 pub fn container_l1<'a, T>(contents: T) -> Container<'a, Message, Theme>
@@ -22,11 +20,11 @@ where
     Container::new(contents).style(ContainerStyle::L1)
 }
 
-pub fn container_l2<'a, T>(contents: T) -> Container<'a, Message, Theme>
+pub fn container_l2<'a, T>(density: GuiDensity, contents: T) -> Container<'a, Message, Theme>
 where
     T: Into<Element<'a, Message, Theme>>,
 {
-    let padding_x = LINE_HEIGHT.into();
+    let padding_x = density.padding().into();
     let padding_y = 0.0;
 
     let contents = Row::new()
@@ -44,12 +42,12 @@ where
         .style(ContainerStyle::L2)
 }
 
-pub fn container_l3<'a, T>(contents: T) -> Container<'a, Message, Theme>
+pub fn container_l3<'a, T>(density: GuiDensity, contents: T) -> Container<'a, Message, Theme>
 where
     T: Into<Element<'a, Message, Theme>>,
 {
     let padding_x = 0.0;
-    let padding_y = LINE_HEIGHT.into();
+    let padding_y = density.padding().into();
 
     let contents = Row::new()
         .push(Space::with_width(Length::Fixed(padding_x)))
@@ -66,15 +64,15 @@ where
         .style(ContainerStyle::L3)
 }
 
-pub fn triple_container<'a, T>(contents: T) -> Container<'a, Message, Theme>
+pub fn triple_container<'a, T>(density: GuiDensity, contents: T) -> Container<'a, Message, Theme>
 where
     T: Into<Element<'a, Message, Theme>>,
 {
-    container_l1(container_l2(container_l3(contents)))
+    container_l1(container_l2(density, container_l3(density, contents)))
 }
 
-pub fn space_l2<'a>() -> Container<'a, Message, Theme> {
-    Container::new(Space::with_width(Length::Fixed(LINE_HEIGHT.into())))
+pub fn space_l2<'a>(density: GuiDensity) -> Container<'a, Message, Theme> {
+    Container::new(Space::with_width(Length::Fixed(density.spacing().into())))
 }
 
 pub fn space_l3<'a>() -> Container<'a, Message, Theme> {
@@ -1,3 +1,6 @@
+use std::time::{Duration, Instant};
+
+use compact_str::{format_compact, CompactString};
 use iced_baseview::{
     alignment::Horizontal,
     widget::tooltip::Position,
@@ -12,11 +15,23 @@ use iced_baseview::{
 
 use crate::{
     parameters::{
+        chord_memory::ChordMemoryValue,
         glide_active::{GlideActiveValue, GLIDE_ACTIVE_STEPS},
         glide_time::GlideTimeValue,
+        legato_envelope_mode::{LegatoEnvelopeModeValue, LEGATO_ENVELOPE_MODE_STEPS},
         list::{MasterParameter, Parameter},
         master_pitch_bend_range::{MasterPitchBendRangeDownValue, MasterPitchBendRangeUpValue},
+        master_pitch_bend_smoothing::MasterPitchBendSmoothingValue,
+        max_polyphony::{MaxPolyphonyValue, MAX_POLYPHONY_STEPS},
+        mpe::{
+            MpeMemberPitchBendRangeValue, MpeModulationAmountValue, MpeModulationTargetValue,
+            MpeZoneValue, MPE_ZONE_STEPS,
+        },
+        note_priority::{NotePriorityValue, NOTE_PRIORITY_STEPS},
+        note_random::{NoteRandomAmountValue, NoteRandomTargetValue},
+        operator_key_range::format_key,
         velocity_sensitivity::VelocitySensitivityValue,
+        vibrato::{VibratoDelayValue, VibratoDepthValue, VibratoRateValue},
         MasterFrequencyValue, MasterVolumeValue, ParameterValue,
     },
     sync::GuiSyncHandle,
@@ -25,16 +40,79 @@ use crate::{
 
 use super::{
     boolean_button::{
-        glide_bpm_sync_button, glide_mode_button, glide_retrigger_button, BooleanButton,
+        anti_alias_button, glide_bpm_sync_button, glide_mode_button, glide_retrigger_button,
+        midi_out_button, vibrato_mod_wheel_button, BooleanButton,
     },
     common::{container_l1, container_l2, container_l3, space_l3, tooltip, triple_container},
     knob::{self, OctaSineKnob},
+    lfo_target_picker::NoteModulationTargetPicker,
     mod_matrix::ModulationMatrix,
     patch_picker::PatchPicker,
-    style::{container::ContainerStyle, Theme},
-    Message, FONT_SIZE, LINE_HEIGHT,
+    style::{button::ButtonStyle, container::ContainerStyle, text::TextStyle, Theme},
+    value_text::{TextFormat, ValueText},
+    GuiDensity, Message, FONT_SIZE, LINE_HEIGHT,
 };
 
+/// How long MIDI activity light stays lit after event was received
+const MIDI_ACTIVITY_DURATION: Duration = Duration::from_millis(150);
+
+const NOTE_MODULATION_SOURCES: &[NoteModulationSource] = &[
+    NoteModulationSource::Random,
+    NoteModulationSource::MpePressure,
+    NoteModulationSource::MpeTimbre,
+];
+
+const VIEW_ACTIONS: &[ViewAction] = &[
+    ViewAction::SwitchTheme,
+    ViewAction::SetDensity(GuiDensity::Dense),
+    ViewAction::SetDensity(GuiDensity::Compact),
+    ViewAction::SetDensity(GuiDensity::Comfortable),
+];
+
+/// Color theme and layout density options
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ViewAction {
+    SwitchTheme,
+    SetDensity(GuiDensity),
+}
+
+impl ViewAction {
+    fn to_message(self) -> Message {
+        match self {
+            Self::SwitchTheme => Message::SwitchTheme,
+            Self::SetDensity(density) => Message::SetDensity(density),
+        }
+    }
+}
+
+impl ::std::fmt::Display for ViewAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::SwitchTheme => f.write_str("THEME"),
+            Self::SetDensity(density) => write!(f, "{}", density),
+        }
+    }
+}
+
+/// Per-note modulation source shown in alternative controls
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NoteModulationSource {
+    #[default]
+    Random,
+    MpePressure,
+    MpeTimbre,
+}
+
+impl ::std::fmt::Display for NoteModulationSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Random => "RANDOM",
+            Self::MpePressure => "PRESSURE",
+            Self::MpeTimbre => "TIMBRE",
+        })
+    }
+}
+
 pub struct CornerWidgets {
     pub alternative_controls: bool,
     pub master_volume: OctaSineKnob<MasterVolumeValue>,
@@ -42,13 +120,37 @@ pub struct CornerWidgets {
     pub volume_velocity_sensitivity: OctaSineKnob<VelocitySensitivityValue>,
     pub modulation_matrix: ModulationMatrix,
     pub patch_picker: PatchPicker,
-    pub master_pitch_bend_up: OctaSineKnob<MasterPitchBendRangeUpValue>,
-    pub master_pitch_bend_down: OctaSineKnob<MasterPitchBendRangeDownValue>,
+    pub master_pitch_bend_up: ValueText<MasterPitchBendRangeUpValue>,
+    pub master_pitch_bend_down: ValueText<MasterPitchBendRangeDownValue>,
+    pub master_pitch_bend_smoothing: ValueText<MasterPitchBendSmoothingValue>,
+    pub mpe_zone: f32,
+    pub mpe_member_pitch_bend_range: ValueText<MpeMemberPitchBendRangeValue>,
     pub glide_time: OctaSineKnob<GlideTimeValue>,
     pub glide_bpm_sync: BooleanButton,
     pub glide_mode: BooleanButton,
     pub glide_retrigger: BooleanButton,
     pub glide_active: f32,
+    pub chord_memory: f32,
+    pub legato_envelope_mode: f32,
+    pub note_priority: f32,
+    pub max_polyphony: f32,
+    pub midi_out: BooleanButton,
+    pub anti_alias: BooleanButton,
+    pub note_modulation_source: NoteModulationSource,
+    pub note_random_target: NoteModulationTargetPicker<NoteRandomTargetValue>,
+    pub note_random_amount: ValueText<NoteRandomAmountValue>,
+    pub mpe_pressure_target: NoteModulationTargetPicker<MpeModulationTargetValue>,
+    pub mpe_pressure_amount: ValueText<MpeModulationAmountValue>,
+    pub mpe_timbre_target: NoteModulationTargetPicker<MpeModulationTargetValue>,
+    pub mpe_timbre_amount: ValueText<MpeModulationAmountValue>,
+    pub vibrato_rate: ValueText<VibratoRateValue>,
+    pub vibrato_depth: ValueText<VibratoDepthValue>,
+    pub vibrato_delay: ValueText<VibratoDelayValue>,
+    pub vibrato_mod_wheel: BooleanButton,
+    /// Most recent MIDI event received from host and when it was received
+    pub last_midi_event: Option<([u8; 3], Instant)>,
+    /// Output exceeded 0 dBFS since indicator was last reset
+    pub clipped: bool,
 }
 
 impl CornerWidgets {
@@ -58,16 +160,107 @@ impl CornerWidgets {
         let volume_velocity_sensitivity = knob::master_velocity_sensitivity(sync_handle);
         let modulation_matrix = ModulationMatrix::new(sync_handle);
         let patch_picker = PatchPicker::new(sync_handle);
-        let master_pitch_bend_up = knob::master_pitch_bend_range_up(sync_handle);
-        let master_pitch_bend_down = knob::master_pitch_bend_range_down(sync_handle);
+        // Short signed format, since up and down ranges share a row
+        const PITCH_BEND_UP_TEXT_FORMAT: TextFormat = TextFormat {
+            format: |v| {
+                format_compact!(
+                    "{:+.0}",
+                    MasterPitchBendRangeUpValue::new_from_patch(v).get()
+                )
+            },
+            parse: |text| MasterPitchBendRangeUpValue::new_from_text(text).map(|v| v.to_patch()),
+        };
+        const PITCH_BEND_DOWN_TEXT_FORMAT: TextFormat = TextFormat {
+            format: |v| {
+                format_compact!(
+                    "{:+.0}",
+                    MasterPitchBendRangeDownValue::new_from_patch(v).get()
+                )
+            },
+            parse: |text| MasterPitchBendRangeDownValue::new_from_text(text).map(|v| v.to_patch()),
+        };
+
+        let mut master_pitch_bend_up = ValueText::new(
+            sync_handle,
+            Parameter::Master(MasterParameter::PitchBendRangeUp).into(),
+        );
+        let mut master_pitch_bend_down = ValueText::new(
+            sync_handle,
+            Parameter::Master(MasterParameter::PitchBendRangeDown).into(),
+        );
+
+        master_pitch_bend_up.set_text_format(Some(PITCH_BEND_UP_TEXT_FORMAT));
+        master_pitch_bend_down.set_text_format(Some(PITCH_BEND_DOWN_TEXT_FORMAT));
+
+        let master_pitch_bend_smoothing = ValueText::new(
+            sync_handle,
+            Parameter::Master(MasterParameter::PitchBendSmoothing).into(),
+        );
+        let mpe_zone =
+            sync_handle.get_parameter(Parameter::Master(MasterParameter::MpeZone).into());
+        let mpe_member_pitch_bend_range = ValueText::new(
+            sync_handle,
+            Parameter::Master(MasterParameter::MpeMemberPitchBendRange).into(),
+        );
         let glide_time = knob::glide_time(sync_handle);
 
         let glide_active =
             sync_handle.get_parameter(Parameter::Master(MasterParameter::GlideActive).into());
 
+        let chord_memory =
+            sync_handle.get_parameter(Parameter::Master(MasterParameter::ChordMemory).into());
+
+        let legato_envelope_mode = sync_handle
+            .get_parameter(Parameter::Master(MasterParameter::LegatoEnvelopeMode).into());
+
+        let note_priority =
+            sync_handle.get_parameter(Parameter::Master(MasterParameter::NotePriority).into());
+
+        let max_polyphony =
+            sync_handle.get_parameter(Parameter::Master(MasterParameter::MaxPolyphony).into());
+
         let glide_bpm_sync = glide_bpm_sync_button(sync_handle);
         let glide_mode = glide_mode_button(sync_handle);
         let glide_retrigger = glide_retrigger_button(sync_handle);
+        let midi_out = midi_out_button(sync_handle);
+        let anti_alias = anti_alias_button(sync_handle);
+        let note_random_target = NoteModulationTargetPicker::new(
+            sync_handle,
+            Parameter::Master(MasterParameter::NoteRandomTarget),
+        );
+        let note_random_amount = ValueText::new(
+            sync_handle,
+            Parameter::Master(MasterParameter::NoteRandomAmount).into(),
+        );
+        let mpe_pressure_target = NoteModulationTargetPicker::new(
+            sync_handle,
+            Parameter::Master(MasterParameter::MpePressureTarget),
+        );
+        let mpe_pressure_amount = ValueText::new(
+            sync_handle,
+            Parameter::Master(MasterParameter::MpePressureAmount).into(),
+        );
+        let mpe_timbre_target = NoteModulationTargetPicker::new(
+            sync_handle,
+            Parameter::Master(MasterParameter::MpeTimbreTarget),
+        );
+        let mpe_timbre_amount = ValueText::new(
+            sync_handle,
+            Parameter::Master(MasterParameter::MpeTimbreAmount).into(),
+        );
+        let vibrato_rate = ValueText::new(
+            sync_handle,
+            Parameter::Master(MasterParameter::VibratoRate).into(),
+        );
+        let vibrato_depth = ValueText::new(
+            sync_handle,
+            Parameter::Master(MasterParameter::VibratoDepth).into(),
+        );
+        let vibrato_delay = ValueText::new(
+            sync_handle,
+            Parameter::Master(MasterParameter::VibratoDelay).into(),
+        );
+        let vibrato_mod_wheel = vibrato_mod_wheel_button(sync_handle);
 
         Self {
             alternative_controls: false,
@@ -78,26 +271,81 @@ impl CornerWidgets {
             patch_picker,
             master_pitch_bend_up,
             master_pitch_bend_down,
+            master_pitch_bend_smoothing,
+            mpe_zone,
+            mpe_member_pitch_bend_range,
             glide_active,
             glide_time,
             glide_bpm_sync,
             glide_mode,
             glide_retrigger,
+            chord_memory,
+            legato_envelope_mode,
+            note_priority,
+            max_polyphony,
+            midi_out,
+            anti_alias,
+            note_modulation_source: NoteModulationSource::default(),
+            note_random_target,
+            note_random_amount,
+            mpe_pressure_target,
+            mpe_pressure_amount,
+            mpe_timbre_target,
+            mpe_timbre_amount,
+            vibrato_rate,
+            vibrato_depth,
+            vibrato_delay,
+            vibrato_mod_wheel,
+            last_midi_event: None,
+            clipped: false,
         }
     }
 
+    pub fn set_last_midi_event(&mut self, data: [u8; 3]) {
+        self.last_midi_event = Some((data, Instant::now()));
+    }
+
     pub fn theme_changed(&mut self) {
         self.patch_picker.theme_changed();
         self.modulation_matrix.theme_changed();
         self.glide_bpm_sync.theme_changed();
         self.glide_mode.theme_changed();
         self.glide_retrigger.theme_changed();
+        self.midi_out.theme_changed();
+        self.anti_alias.theme_changed();
+        self.vibrato_mod_wheel.theme_changed();
     }
 
-    pub fn view(&self, theme: &Theme) -> Element<'_, Message, Theme> {
+    pub fn view(&self, theme: &Theme, density: GuiDensity) -> Element<'_, Message, Theme> {
+        let clip_indicator = tooltip(
+            theme,
+            if self.clipped {
+                "Output has exceeded 0 dBFS. Click to reset"
+            } else {
+                "Lights up when output exceeds 0 dBFS"
+            },
+            Position::Bottom,
+            Button::new(
+                Text::new("CLIP")
+                    .font(theme.font_regular())
+                    .height(Length::Fixed(LINE_HEIGHT.into()))
+                    .style(if self.clipped {
+                        TextStyle::Warning
+                    } else {
+                        TextStyle::Regular
+                    }),
+            )
+            .padding(0)
+            .style(ButtonStyle::Value)
+            .on_press(Message::ResetClipIndicator),
+        );
+
+        // Clip indicator takes up the space of the bottom padding
         let mod_matrix = Container::new(
             Column::new()
-                .push(Space::with_height(Length::Fixed(LINE_HEIGHT.into())))
+                .push(Space::with_height(Length::Fixed(f32::from(
+                    density.padding() * 2 - LINE_HEIGHT,
+                ))))
                 .push(
                     Row::new()
                         .push(Space::with_width(Length::Fixed(LINE_HEIGHT.into())))
@@ -105,9 +353,14 @@ impl CornerWidgets {
                         // Allow room for modulation matrix extra pixel
                         .push(Space::with_width(Length::Fixed(f32::from(LINE_HEIGHT - 1)))),
                 )
-                .push(Space::with_height(Length::Fixed(LINE_HEIGHT.into()))),
+                .push(
+                    Row::new()
+                        .push(Space::with_width(Length::Fill))
+                        .push(clip_indicator)
+                        .push(Space::with_width(Length::Fixed(LINE_HEIGHT.into()))),
+                ),
         )
-        .height(Length::Fixed(f32::from(LINE_HEIGHT * 8)))
+        .height(Length::Fixed(f32::from(density.panel_row_height())))
         .width(Length::Fixed(f32::from(LINE_HEIGHT * 7)))
         .style(ContainerStyle::L3);
 
@@ -125,18 +378,58 @@ impl CornerWidgets {
                 .padding(theme.button_padding())
                 .on_press(Message::ToggleAlternativeControls),
             );
-            let theme_button = tooltip(
+            let view_picker = tooltip(
                 theme,
-                "Switch color theme",
+                format!(
+                    "Switch color theme or layout density\n\nDensity changes apply when editor is reopened\n(current layout: {})",
+                    density
+                ),
                 Position::Bottom,
-                Button::new(
-                    Text::new("THEME")
-                        .font(theme.font_regular())
-                        .height(Length::Fixed(LINE_HEIGHT.into()))
-                        .horizontal_alignment(Horizontal::Center),
-                )
-                .on_press(Message::SwitchTheme)
-                .padding(theme.button_padding()),
+                PickList::new(VIEW_ACTIONS, None, ViewAction::to_message)
+                    .font(theme.font_regular())
+                    .text_size(FONT_SIZE)
+                    .padding(theme.picklist_padding())
+                    .placeholder("VIEW")
+                    .width(Length::Fill),
+            );
+
+            let (midi_event_text, midi_event_tooltip, midi_active) = match self.last_midi_event {
+                Some((data, received_at)) => {
+                    let (text, description) = describe_midi_event(data);
+
+                    (
+                        text,
+                        format!("Last MIDI event received:\n{}", description),
+                        received_at.elapsed() < MIDI_ACTIVITY_DURATION,
+                    )
+                }
+                None => (
+                    "MIDI IN".into(),
+                    "No MIDI events received yet".into(),
+                    false,
+                ),
+            };
+            let midi_activity = tooltip(
+                theme,
+                midi_event_tooltip,
+                Position::Bottom,
+                Row::new()
+                    .push(
+                        Text::new("•")
+                            .font(theme.font_heading())
+                            .height(Length::Fixed(LINE_HEIGHT.into()))
+                            .style(if midi_active {
+                                TextStyle::Highlight
+                            } else {
+                                TextStyle::Regular
+                            }),
+                    )
+                    .push(Space::with_width(Length::Fixed(3.0)))
+                    .push(
+                        Text::new(midi_event_text.to_string())
+                            .font(theme.font_regular())
+                            .height(Length::Fixed(LINE_HEIGHT.into())),
+                    ),
             );
 
             Container::new(
@@ -145,7 +438,7 @@ impl CornerWidgets {
                     .width(Length::Fill)
                     .push(controls_button)
                     .push(Space::with_height(Length::Fixed(f32::from(
-                        LINE_HEIGHT / 2 + LINE_HEIGHT / 4,
+                        LINE_HEIGHT / 4,
                     ))))
                     .push(tooltip(
                         theme,
@@ -158,16 +451,17 @@ impl CornerWidgets {
                             .font(theme.font_heading())
                             .horizontal_alignment(Horizontal::Center),
                     ))
+                    .push(midi_activity)
                     .push(Space::with_height(Length::Fixed(f32::from(
-                        LINE_HEIGHT / 2 + LINE_HEIGHT / 4,
+                        LINE_HEIGHT / 4,
                     ))))
-                    .push(theme_button),
+                    .push(view_picker),
             )
             .width(Length::Fixed(f32::from(LINE_HEIGHT * 5)))
             .height(Length::Fixed(f32::from(LINE_HEIGHT * 6)))
         };
 
-        let voice_buttons = {
+        let voice_buttons = if !self.alternative_controls {
             let glide_mode_title = tooltip(
                 theme,
                 "Glide (portamento)\n\nLEG = glide only when playing legato",
@@ -191,7 +485,7 @@ impl CornerWidgets {
 
             let glide_mode = tooltip(
                 theme,
-                "Linear constant rate / linear constant time",
+                "Linear constant rate / linear constant time\n\nIn LCR mode, glide time is time per octave",
                 Position::Top,
                 self.glide_mode.view(),
             );
@@ -230,51 +524,457 @@ impl CornerWidgets {
                     .push(Space::with_height(LINE_HEIGHT / 2))
                     .push(glide_mode),
             )
+        } else {
+            let voices_title = tooltip(
+                theme,
+                "Voice allocation and monophonic voice mode settings",
+                Position::Top,
+                Text::new("VOICES")
+                    .horizontal_alignment(Horizontal::Center)
+                    .font(theme.font_bold())
+                    .height(Length::Fixed(LINE_HEIGHT.into()))
+                    .width(LINE_HEIGHT * 4),
+            );
+
+            let legato_envelope_mode_picker = tooltip(
+                theme,
+                "Envelope behavior on legato notes\n\nPART = restart from current level\nRTRG = restart from zero\nCONT = don't restart",
+                Position::Top,
+                PickList::new(
+                    LEGATO_ENVELOPE_MODE_STEPS,
+                    Some(LegatoEnvelopeModeValue::new_from_patch(self.legato_envelope_mode).get()),
+                    move |option| {
+                        let v = LegatoEnvelopeModeValue::new_from_audio(option).to_patch();
+
+                        Message::ChangeSingleParameterImmediate(
+                            Parameter::Master(MasterParameter::LegatoEnvelopeMode).into(),
+                            v,
+                        )
+                    },
+                )
+                .font(theme.font_regular())
+                .text_size(FONT_SIZE)
+                .padding(theme.picklist_padding())
+                .width(Length::Fill),
+            );
+
+            let note_priority_picker = tooltip(
+                theme,
+                "Note priority: which held key is played",
+                Position::Top,
+                PickList::new(
+                    NOTE_PRIORITY_STEPS,
+                    Some(NotePriorityValue::new_from_patch(self.note_priority).get()),
+                    move |option| {
+                        let v = NotePriorityValue::new_from_audio(option).to_patch();
+
+                        Message::ChangeSingleParameterImmediate(
+                            Parameter::Master(MasterParameter::NotePriority).into(),
+                            v,
+                        )
+                    },
+                )
+                .font(theme.font_regular())
+                .text_size(FONT_SIZE)
+                .padding(theme.picklist_padding())
+                .width(Length::Fill),
+            );
+
+            let max_polyphony_picker = tooltip(
+                theme,
+                "Maximum number of voices in polyphonic mode\n\nWhen reached, the oldest voice is stolen,\npreferring voices with released keys",
+                Position::Top,
+                PickList::new(
+                    MAX_POLYPHONY_STEPS,
+                    Some(MaxPolyphonyValue::new_from_patch(self.max_polyphony).get()),
+                    move |option| {
+                        let v = MaxPolyphonyValue::new_from_audio(option).to_patch();
+
+                        Message::ChangeSingleParameterImmediate(
+                            Parameter::Master(MasterParameter::MaxPolyphony).into(),
+                            v,
+                        )
+                    },
+                )
+                .font(theme.font_regular())
+                .text_size(FONT_SIZE)
+                .padding(theme.picklist_padding())
+                .width(Length::Fill),
+            );
+
+            let anti_alias = tooltip(
+                theme,
+                "Anti-alias: reduce modulation of high-frequency operators",
+                Position::Top,
+                self.anti_alias.view(),
+            );
+
+            Container::new(
+                Column::new()
+                    .width(Length::Fixed(f32::from(LINE_HEIGHT * 4)))
+                    .align_items(Alignment::Center)
+                    .push(voices_title)
+                    .push(Space::with_height(LINE_HEIGHT / 4))
+                    .push(max_polyphony_picker)
+                    .push(Space::with_height(LINE_HEIGHT / 4))
+                    .push(legato_envelope_mode_picker)
+                    .push(Space::with_height(LINE_HEIGHT / 4))
+                    .push(note_priority_picker)
+                    .push(Space::with_height(LINE_HEIGHT / 4))
+                    .push(anti_alias),
+            )
+            .height(Length::Fixed(f32::from(LINE_HEIGHT * 6)))
+        };
+
+        let chord_memory = {
+            let title = tooltip(
+                theme,
+                "Chord memory: play stored chord transposed by each key in polyphonic mode\n\nHold keys and press LEARN to store chord, press LEARN without held keys to turn off",
+                Position::Top,
+                Text::new("CHORD")
+                    .horizontal_alignment(Horizontal::Center)
+                    .font(theme.font_bold())
+                    .height(Length::Fixed(LINE_HEIGHT.into()))
+                    .width(LINE_HEIGHT * 4),
+            );
+
+            let learn_button = Button::new(
+                Text::new("LEARN")
+                    .font(theme.font_regular())
+                    .height(Length::Fixed(LINE_HEIGHT.into()))
+                    .horizontal_alignment(Horizontal::Center),
+            )
+            .padding(theme.button_padding())
+            .on_press(Message::LearnChordMemory);
+
+            let chord = Text::new(
+                ChordMemoryValue::new_from_patch(self.chord_memory)
+                    .get_formatted()
+                    .to_string(),
+            )
+            .horizontal_alignment(Horizontal::Center)
+            .font(theme.font_regular())
+            .height(Length::Fixed(LINE_HEIGHT.into()))
+            .width(LINE_HEIGHT * 4);
+
+            let midi_out = tooltip(
+                theme,
+                "Send notes generated by chord memory to MIDI output",
+                Position::Top,
+                self.midi_out.view(),
+            );
+
+            Container::new(
+                Column::new()
+                    .width(Length::Fixed(f32::from(LINE_HEIGHT * 4)))
+                    .align_items(Alignment::Center)
+                    .push(title)
+                    .push(Space::with_height(LINE_HEIGHT / 2))
+                    .push(learn_button)
+                    .push(Space::with_height(LINE_HEIGHT / 2))
+                    .push(chord)
+                    .push(Space::with_height(LINE_HEIGHT / 2))
+                    .push(midi_out),
+            )
+            .height(Length::Fixed(f32::from(LINE_HEIGHT * 6)))
+        };
+
+        let pitch_bend = {
+            let title = tooltip(
+                theme,
+                "Pitch bend range (upward and downward), smoothing time and MPE settings",
+                Position::Top,
+                Text::new("BEND")
+                    .horizontal_alignment(Horizontal::Center)
+                    .font(theme.font_bold())
+                    .height(Length::Fixed(LINE_HEIGHT.into()))
+                    .width(LINE_HEIGHT * 4),
+            );
+
+            let range = Row::new()
+                .width(Length::Fixed(f32::from(LINE_HEIGHT * 4)))
+                .push(tooltip(
+                    theme,
+                    "Pitch bend range - upward",
+                    Position::Top,
+                    self.master_pitch_bend_up.view(theme),
+                ))
+                .push(tooltip(
+                    theme,
+                    "Pitch bend range - downward",
+                    Position::Top,
+                    self.master_pitch_bend_down.view(theme),
+                ));
+            let smoothing = tooltip(
+                theme,
+                "Pitch bend smoothing time\n\nReduces zipper noise from low-resolution pitch bend",
+                Position::Top,
+                self.master_pitch_bend_smoothing.view(theme),
+            );
+            let mpe_zone = tooltip(
+                theme,
+                "MPE zone\n\nLOW = master channel 1, member channels 2-16\nUP = master channel 16, member channels 1-15\n\nPitch bend on member channels only affects notes started on them",
+                Position::Top,
+                PickList::new(
+                    MPE_ZONE_STEPS,
+                    Some(MpeZoneValue::new_from_patch(self.mpe_zone).get()),
+                    move |option| {
+                        let v = MpeZoneValue::new_from_audio(option).to_patch();
+
+                        Message::ChangeSingleParameterImmediate(
+                            Parameter::Master(MasterParameter::MpeZone).into(),
+                            v,
+                        )
+                    },
+                )
+                .font(theme.font_regular())
+                .text_size(FONT_SIZE)
+                .padding(theme.picklist_padding())
+                .width(Length::Fill),
+            );
+            let mpe_member_pitch_bend_range = tooltip(
+                theme,
+                "Pitch bend range of MPE member channels",
+                Position::Top,
+                self.mpe_member_pitch_bend_range.view(theme),
+            );
+
+            Container::new(
+                Column::new()
+                    .width(Length::Fixed(f32::from(LINE_HEIGHT * 4)))
+                    .align_items(Alignment::Center)
+                    .push(title)
+                    .push(Space::with_height(LINE_HEIGHT / 4))
+                    .push(range)
+                    .push(Space::with_height(LINE_HEIGHT / 4))
+                    .push(smoothing)
+                    .push(Space::with_height(LINE_HEIGHT / 4))
+                    .push(mpe_zone)
+                    .push(Space::with_height(LINE_HEIGHT / 4))
+                    .push(mpe_member_pitch_bend_range),
+            )
+            .height(Length::Fixed(f32::from(LINE_HEIGHT * 6)))
+        };
+
+        let vibrato = {
+            let title = tooltip(
+                theme,
+                "Vibrato applied to all voices, independent of LFOs",
+                Position::Top,
+                Text::new("VIBRATO")
+                    .horizontal_alignment(Horizontal::Center)
+                    .font(theme.font_bold())
+                    .height(Length::Fixed(LINE_HEIGHT.into()))
+                    .width(LINE_HEIGHT * 4),
+            );
+
+            let rate = tooltip(theme, "Rate", Position::Top, self.vibrato_rate.view(theme));
+            let depth = tooltip(
+                theme,
+                "Depth (zero turns vibrato off)",
+                Position::Top,
+                self.vibrato_depth.view(theme),
+            );
+            let delay = tooltip(
+                theme,
+                "Delay from note-on until vibrato fades in",
+                Position::Top,
+                self.vibrato_delay.view(theme),
+            );
+            let mod_wheel = tooltip(
+                theme,
+                "Scale depth by mod wheel",
+                Position::Top,
+                self.vibrato_mod_wheel.view(),
+            );
+
+            Container::new(
+                Column::new()
+                    .width(Length::Fixed(f32::from(LINE_HEIGHT * 4)))
+                    .align_items(Alignment::Center)
+                    .push(title)
+                    .push(Space::with_height(LINE_HEIGHT / 4))
+                    .push(rate)
+                    .push(Space::with_height(LINE_HEIGHT / 4))
+                    .push(depth)
+                    .push(Space::with_height(LINE_HEIGHT / 4))
+                    .push(delay)
+                    .push(Space::with_height(LINE_HEIGHT / 4))
+                    .push(mod_wheel),
+            )
+            .height(Length::Fixed(f32::from(LINE_HEIGHT * 6)))
         };
 
         let top: Element<Message, Theme> = if !self.alternative_controls {
             Row::new()
                 .push(mod_matrix)
-                .push(Space::with_width(Length::Fixed(LINE_HEIGHT.into())))
-                .push(triple_container(self.patch_picker.view(theme)))
+                .push(Space::with_width(Length::Fixed(density.spacing().into())))
+                .push(triple_container(density, self.patch_picker.view(theme)))
                 .into()
         } else {
             Row::new()
                 .push(container_l1(container_l2(
+                    density,
                     Row::new()
-                        .push(container_l3(self.master_frequency.view(theme)))
+                        .push(container_l3(density, self.master_frequency.view(theme)))
                         .push(space_l3())
-                        .push(container_l3(self.volume_velocity_sensitivity.view(theme)))
+                        .push(container_l3(
+                            density,
+                            self.volume_velocity_sensitivity.view(theme),
+                        ))
                         .push(space_l3())
-                        .push(container_l3(self.master_pitch_bend_up.view(theme)))
+                        .push(container_l3(density, pitch_bend))
                         .push(space_l3())
-                        .push(container_l3(self.master_pitch_bend_down.view(theme)))
+                        .push(container_l3(density, vibrato))
                         .push(space_l3())
-                        .push(container_l3(Space::with_width(LINE_HEIGHT * 4))),
+                        .push(container_l3(density, chord_memory)),
                 )))
                 .into()
         };
 
+        let glide_time_or_note_modulation: Element<Message, Theme> = if !self.alternative_controls {
+            self.glide_time.view(theme)
+        } else {
+            let (description, target, amount) = match self.note_modulation_source {
+                NoteModulationSource::Random => (
+                    "Random per note: value sampled at note-on and added to target\n\nAMOUNT = maximum change in either direction",
+                    self.note_random_target.view(theme),
+                    self.note_random_amount.view(theme),
+                ),
+                NoteModulationSource::MpePressure => (
+                    "MPE pressure: channel pressure of member channel that note was started on, added to target\n\nAMOUNT = change at full pressure",
+                    self.mpe_pressure_target.view(theme),
+                    self.mpe_pressure_amount.view(theme),
+                ),
+                NoteModulationSource::MpeTimbre => (
+                    "MPE timbre: CC 74 of member channel that note was started on, added to target\n\nAMOUNT = change at maximum value",
+                    self.mpe_timbre_target.view(theme),
+                    self.mpe_timbre_amount.view(theme),
+                ),
+            };
+
+            let source = tooltip(
+                theme,
+                description,
+                Position::Top,
+                PickList::new(
+                    NOTE_MODULATION_SOURCES,
+                    Some(self.note_modulation_source),
+                    Message::ChangeNoteModulationSource,
+                )
+                .font(theme.font_regular())
+                .text_size(FONT_SIZE)
+                .padding(theme.picklist_padding())
+                .width(Length::Fill),
+            );
+
+            let amount_title = Text::new("AMOUNT")
+                .horizontal_alignment(Horizontal::Center)
+                .font(theme.font_regular())
+                .height(Length::Fixed(LINE_HEIGHT.into()))
+                .width(LINE_HEIGHT * 4);
+
+            Container::new(
+                Column::new()
+                    .width(Length::Fixed(f32::from(LINE_HEIGHT * 4)))
+                    .align_items(Alignment::Center)
+                    .push(source)
+                    .push(Space::with_height(LINE_HEIGHT / 2))
+                    .push(target)
+                    .push(Space::with_height(LINE_HEIGHT / 2))
+                    .push(amount_title)
+                    .push(amount),
+            )
+            .into()
+        };
+
         let bottom = Row::new()
             .push(container_l1(container_l2(
+                density,
                 Row::new()
-                    .push(container_l3(self.master_volume.view(theme)))
+                    .push(container_l3(density, self.master_volume.view(theme)))
                     .push(space_l3())
-                    .push(container_l3(voice_buttons))
+                    .push(container_l3(density, voice_buttons))
                     .push(space_l3())
-                    .push(container_l3(self.glide_time.view(theme))),
+                    .push(container_l3(density, glide_time_or_note_modulation)),
             )))
-            .push(Space::with_width(Length::Fixed(LINE_HEIGHT.into())))
-            .push(triple_container(logo));
+            .push(Space::with_width(Length::Fixed(density.spacing().into())))
+            .push(triple_container(density, logo));
 
         Column::new()
             .push(top)
-            .push(Space::with_height(Length::Fixed(LINE_HEIGHT.into())))
+            .push(Space::with_height(Length::Fixed(density.spacing().into())))
             .push(bottom)
             .into()
     }
 }
 
+/// Short text and full description of MIDI event
+fn describe_midi_event([status, data_1, data_2]: [u8; 3]) -> (CompactString, String) {
+    let channel = (status & 0b_1111) + 1;
+
+    // Note on with zero velocity is note off
+    let kind = match status >> 4 {
+        0b_1001 if data_2 == 0 => 0b_1000,
+        kind => kind,
+    };
+
+    match kind {
+        0b_1000 => (
+            format_compact!("{} OFF", format_key(data_1)),
+            format!("Note off, channel {}, key {}", channel, format_key(data_1)),
+        ),
+        0b_1001 => (
+            format_compact!("{} {}", format_key(data_1), data_2),
+            format!(
+                "Note on, channel {}, key {}, velocity {}",
+                channel,
+                format_key(data_1),
+                data_2
+            ),
+        ),
+        0b_1010 => (
+            format_compact!("AT {}", data_2),
+            format!(
+                "Polyphonic aftertouch, channel {}, key {}, pressure {}",
+                channel,
+                format_key(data_1),
+                data_2
+            ),
+        ),
+        0b_1011 => (
+            format_compact!("CC{} {}", data_1, data_2),
+            format!(
+                "Control change, channel {}, controller {}, value {}",
+                channel, data_1, data_2
+            ),
+        ),
+        0b_1100 => (
+            format_compact!("PC {}", data_1),
+            format!("Program change, channel {}, program {}", channel, data_1),
+        ),
+        0b_1101 => (
+            format_compact!("AT {}", data_1),
+            format!(
+                "Channel aftertouch, channel {}, pressure {}",
+                channel, data_1
+            ),
+        ),
+        0b_1110 => {
+            let value = ((i16::from(data_2) << 7) | i16::from(data_1)) - 8192;
+
+            (
+                format_compact!("PB {:+}", value),
+                format!("Pitch bend, channel {}, value {:+}", channel, value),
+            )
+        }
+        _ => (
+            "MIDI".into(),
+            format!("Status {:#04X}, data {} {}", status, data_1, data_2),
+        ),
+    }
+}
+
 fn get_info_text() -> String {
     format!(
         "OctaSine frequency modulation synthesizer
@@ -15,6 +15,14 @@ pub const SIZE: Size = Size {
 };
 
 pub const DRAGGER_RADIUS: f32 = 4.0;
+/// Draggers can be grabbed from a wider area than they are drawn with, to
+/// make them easier to hit with a finger or pen
+pub const DRAGGER_HITBOX_RADIUS: f32 = 8.0;
+/// Maximum distance between clicks for them to count as a double click.
+/// Taps on touchscreens rarely land on exactly the same pixel.
+pub const DOUBLE_CLICK_MAX_DISTANCE: f32 = 4.0;
+/// Pixels panned per line when scrolling with a mouse wheel
+pub const SCROLL_LINE_PIXELS: f32 = (LINE_HEIGHT * 2) as f32;
 
 pub const ENVELOPE_PATH_SCALE_X: f32 = (WIDTH as f32 - 2.0 * LINE_HEIGHT as f32) / WIDTH as f32;
 pub const ENVELOPE_PATH_SCALE_Y: f32 = 1.0 - (1.0 / 8.0) - (1.0 / 16.0);
@@ -47,6 +55,10 @@ pub struct Appearance {
     pub dragger_border_color: Color,
     pub viewport_indicator_border: Color,
     pub viewport_indicator_border_active: Color,
+    pub group_a_color: Color,
+    pub group_b_color: Color,
+    pub group_badge_text_color: Color,
+    pub playhead_color: Color,
 }
 
 pub trait StyleSheet {
@@ -237,7 +249,7 @@ impl EnvelopeDragger {
     pub fn cursor_overlaps(&self, cursor_position: Point) -> bool {
         let diff = cursor_position - self.center;
 
-        (diff.x.abs() <= self.radius) & (diff.y.abs() <= self.radius)
+        (diff.x.abs() <= DRAGGER_HITBOX_RADIUS) & (diff.y.abs() <= DRAGGER_HITBOX_RADIUS)
     }
 }
 
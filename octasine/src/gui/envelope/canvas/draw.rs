@@ -1,6 +1,10 @@
+use iced_baseview::alignment::{Horizontal, Vertical};
 use iced_baseview::widget::canvas::{Frame, Path, Stroke, Text};
 use iced_baseview::{Point, Size, Vector};
 
+use crate::common::EnvelopeStage;
+use crate::parameters::operator_envelope::{OperatorEnvelopeGroupValue, ENVELOPE_MAX_DURATION};
+
 use crate::gui::style::Theme;
 use crate::gui::{SnapPoint, FONT_SIZE};
 
@@ -195,6 +199,87 @@ impl EnvelopeCanvas {
         frame.fill(&viewport_rect, appearance.background_color);
         frame.stroke(&viewport_rect, border_stroke);
     }
+
+    /// Draw vertical line at envelope position of most recently triggered
+    /// voice
+    pub fn draw_playhead(&self, frame: &mut Frame, theme: &Theme) {
+        let duration = if let Some(duration) = self.get_playhead_duration() {
+            duration
+        } else {
+            return;
+        };
+
+        let appearance = theme.appearance();
+
+        let total_duration = self.viewport_factor * TOTAL_DURATION;
+        let x_offset = self.x_offset / self.viewport_factor;
+
+        let x = (x_offset + duration / total_duration) * self.size.width;
+
+        if x < 0.0 || x > self.size.width {
+            return;
+        }
+
+        let path = Path::line(
+            scale_point_x(self.size, Point::new(x, 0.0)).snap(),
+            scale_point_x(self.size, Point::new(x, self.size.height)).snap(),
+        );
+
+        frame.stroke(
+            &path,
+            Stroke::default()
+                .with_width(1.0)
+                .with_color(appearance.playhead_color),
+        );
+    }
+
+    /// Playhead position in same units as stage durations
+    fn get_playhead_duration(&self) -> Option<f32> {
+        let (stage, seconds) = self.playhead?;
+
+        let duration = seconds / ENVELOPE_MAX_DURATION as f32;
+
+        // Durations might differ slightly from audio thread values while
+        // they are being interpolated
+        match stage {
+            EnvelopeStage::Attack => Some(duration.min(self.attack_duration)),
+            EnvelopeStage::Decay => Some(self.attack_duration + duration.min(self.decay_duration)),
+            EnvelopeStage::Sustain => Some(self.attack_duration + self.decay_duration),
+            EnvelopeStage::Release => Some(
+                self.attack_duration + self.decay_duration + duration.min(self.release_duration),
+            ),
+            EnvelopeStage::Ended | EnvelopeStage::Kill => None,
+        }
+    }
+
+    /// Draw colored badge with lock group name in top left corner
+    pub fn draw_group_badge(&self, frame: &mut Frame, theme: &Theme) {
+        const WIDTH: f32 = 12.0;
+        const HEIGHT: f32 = 12.0;
+
+        let appearance = theme.appearance();
+
+        let (color, name) = match self.group {
+            OperatorEnvelopeGroupValue::Off => return,
+            OperatorEnvelopeGroupValue::A => (appearance.group_a_color, "A"),
+            OperatorEnvelopeGroupValue::B => (appearance.group_b_color, "B"),
+        };
+
+        let size = frame.size();
+        let top_left = scale_point_x(size, Point::new(0.0, 0.0)).snap();
+
+        frame.fill(&Path::rectangle(top_left, Size::new(WIDTH, HEIGHT)), color);
+        frame.fill_text(Text {
+            content: name.into(),
+            position: Point::new(top_left.x + WIDTH / 2.0, top_left.y + HEIGHT / 2.0),
+            font: theme.font_bold(),
+            size: FONT_SIZE as f32,
+            color: appearance.group_badge_text_color,
+            horizontal_alignment: Horizontal::Center,
+            vertical_alignment: Vertical::Center,
+            ..Default::default()
+        });
+    }
 }
 
 fn scale_point_x(size: Size, point: Point) -> Point {
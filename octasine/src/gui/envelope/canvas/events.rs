@@ -1,3 +1,4 @@
+use iced_baseview::mouse::ScrollDelta;
 use iced_baseview::widget::canvas::event;
 use iced_baseview::{Point, Rectangle};
 
@@ -84,7 +85,7 @@ impl EnvelopeCanvas {
         state.last_cursor_position = Point::new(x, y);
 
         if let Some(data) = state.double_click_data {
-            if data.point != state.last_cursor_position {
+            if data.point.distance(state.last_cursor_position) > DOUBLE_CLICK_MAX_DISTANCE {
                 state.double_click_data = None;
             }
         }
@@ -329,6 +330,41 @@ impl EnvelopeCanvas {
             (event_status, opt_message)
         }
     }
+
+    /// Pan viewport, e.g., with two-finger scroll on touchscreens and
+    /// touchpads
+    pub fn handle_wheel_scrolled(
+        &self,
+        state: &mut EnvelopeCanvasState,
+        bounds: Rectangle,
+        delta: ScrollDelta,
+    ) -> (event::Status, Option<Message>) {
+        if !bounds.contains(state.last_cursor_position) {
+            return (event::Status::Ignored, None);
+        }
+
+        let (x, y) = match delta {
+            ScrollDelta::Lines { x, y } => (x * SCROLL_LINE_PIXELS, y * SCROLL_LINE_PIXELS),
+            ScrollDelta::Pixels { x, y } => (x, y),
+        };
+
+        // Envelope can only be panned horizontally, so use vertical scrolling
+        // too when it dominates
+        let pixels = if x.abs() >= y.abs() { x } else { y };
+
+        let x_offset = Self::process_x_offset(
+            self.x_offset + pixels / WIDTH as f32 * self.viewport_factor,
+            self.viewport_factor,
+        );
+
+        let message = Message::EnvelopeChangeViewport {
+            operator_index: self.operator_index,
+            viewport_factor: self.viewport_factor,
+            x_offset,
+        };
+
+        (event::Status::Captured, Some(message))
+    }
 }
 
 // Almost-correct reverse transformation for envelope dragger to duration
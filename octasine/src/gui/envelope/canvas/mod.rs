@@ -2,10 +2,11 @@ mod common;
 mod draw;
 mod events;
 
-use iced_baseview::widget::canvas::{event, Cache, Canvas, Cursor, Geometry, Program};
+use iced_baseview::widget::canvas::{event, Cache, Canvas, Cursor, Frame, Geometry, Program};
 use iced_baseview::{widget::Container, Element, Length, Point, Rectangle, Size};
 
 use crate::audio::voices::log10_table::Log10Table;
+use crate::common::EnvelopeStage;
 use crate::parameters::operator_envelope::{
     OperatorAttackDurationValue, OperatorDecayDurationValue, OperatorEnvelopeGroupValue,
     OperatorReleaseDurationValue, OperatorSustainVolumeValue,
@@ -52,6 +53,8 @@ pub struct EnvelopeCanvas {
     decay_duration_parameter: WrappedParameter,
     sustain_volume_parameter: WrappedParameter,
     release_duration_parameter: WrappedParameter,
+    /// Stage and seconds since it started of most recently triggered voice
+    playhead: Option<(EnvelopeStage, f32)>,
 }
 
 impl EnvelopeCanvas {
@@ -110,6 +113,7 @@ impl EnvelopeCanvas {
             decay_duration_parameter,
             sustain_volume_parameter,
             release_duration_parameter,
+            playhead: None,
         };
 
         let (viewport_factor, x_offset) = envelope.get_zoom_to_fit_data();
@@ -192,9 +196,17 @@ impl EnvelopeCanvas {
             self.group = group;
 
             self.modified_by_automation = !internal;
+
+            self.cache.clear();
         }
     }
 
+    /// Set envelope position of most recently triggered voice. Doesn't
+    /// clear cache, since playhead is drawn separately.
+    pub fn set_playhead(&mut self, opt_position: Option<(EnvelopeStage, f32)>) {
+        self.playhead = opt_position;
+    }
+
     fn update_data(&mut self) {
         self.update_stage_paths();
 
@@ -314,7 +326,13 @@ impl EnvelopeCanvas {
     }
 
     pub(super) fn get_zoom_to_fit_data(&self) -> (f32, f32) {
-        let duration_ratio = self.get_current_duration() / TOTAL_DURATION;
+        Self::get_zoom_to_fit_data_for_duration(self.get_current_duration())
+    }
+
+    /// Get viewport factor and x offset for viewport fitting an envelope
+    /// with given total duration
+    pub fn get_zoom_to_fit_data_for_duration(duration: f32) -> (f32, f32) {
+        let duration_ratio = duration / TOTAL_DURATION;
 
         let mut new_viewport_factor = 1.0;
 
@@ -331,7 +349,7 @@ impl EnvelopeCanvas {
         (new_viewport_factor, new_x_offset)
     }
 
-    fn get_current_duration(&self) -> f32 {
+    pub fn get_current_duration(&self) -> f32 {
         self.attack_duration + self.decay_duration + self.release_duration
     }
 }
@@ -358,9 +376,18 @@ impl Program<Message, Theme> for EnvelopeCanvas {
                 .draw(frame, theme, &state.release_dragger_status);
 
             self.draw_viewport_indicator(frame, theme);
+            self.draw_group_badge(frame, theme);
         });
 
-        vec![geometry]
+        if self.playhead.is_none() {
+            return vec![geometry];
+        }
+
+        let mut frame = Frame::new(bounds.size());
+
+        self.draw_playhead(&mut frame, theme);
+
+        vec![geometry, frame.into_geometry()]
     }
 
     fn update(
@@ -368,7 +395,7 @@ impl Program<Message, Theme> for EnvelopeCanvas {
         state: &mut Self::State,
         event: event::Event,
         bounds: Rectangle,
-        _cursor: Cursor,
+        cursor: Cursor,
     ) -> (event::Status, Option<Message>) {
         match event {
             event::Event::Mouse(iced_baseview::mouse::Event::CursorMoved {
@@ -376,10 +403,21 @@ impl Program<Message, Theme> for EnvelopeCanvas {
             }) => self.handle_cursor_moved(state, bounds, x, y),
             event::Event::Mouse(iced_baseview::mouse::Event::ButtonPressed(
                 iced_baseview::mouse::Button::Left,
-            )) => self.handle_button_pressed(state, bounds),
+            )) => {
+                // Touch and pen input might not move cursor before pressing,
+                // so don't rely on last cursor moved event
+                if let Some(position) = cursor.position() {
+                    state.last_cursor_position = position;
+                }
+
+                self.handle_button_pressed(state, bounds)
+            }
             event::Event::Mouse(iced_baseview::mouse::Event::ButtonReleased(
                 iced_baseview::mouse::Button::Left,
             )) => self.handle_button_released(state),
+            event::Event::Mouse(iced_baseview::mouse::Event::WheelScrolled { delta }) => {
+                self.handle_wheel_scrolled(state, bounds, delta)
+            }
             _ => (event::Status::Ignored, None),
         }
     }
@@ -4,27 +4,24 @@ use iced_baseview::alignment::Horizontal;
 use iced_baseview::widget::tooltip::Position;
 use iced_baseview::Font;
 use iced_baseview::{
-    widget::Button, widget::Column, widget::Row, widget::Space, widget::Text, Alignment, Element,
-    Length,
+    widget::Button, widget::Column, widget::PickList, widget::Row, widget::Space, widget::Text,
+    Alignment, Element, Length,
 };
 
 use crate::parameters::list::{OperatorParameter, Parameter};
-use crate::parameters::operator_envelope::OperatorEnvelopeGroupValue;
-use crate::parameters::ParameterValue;
+use crate::parameters::operator_envelope::{OperatorEnvelopeGroupValue, LOCK_STEPS};
+use crate::parameters::{ParameterValue, WrappedParameter};
 use crate::sync::GuiSyncHandle;
 
-use super::boolean_button::{envelope_group_a_button, envelope_group_b_button, BooleanButton};
 use super::common::{container_l3, tooltip};
 use super::style::Theme;
-use super::{Message, FONT_SIZE, LINE_HEIGHT};
+use super::{GuiDensity, Message, FONT_SIZE, LINE_HEIGHT};
 
 pub struct Envelope {
     operator_index: usize,
     group: OperatorEnvelopeGroupValue,
     group_synced: bool,
     pub widget: canvas::EnvelopeCanvas,
-    pub group_a: BooleanButton,
-    pub group_b: BooleanButton,
 }
 
 impl Envelope {
@@ -44,15 +41,11 @@ impl Envelope {
             group,
             group_synced,
             widget: canvas::EnvelopeCanvas::new(sync_handle, operator_index),
-            group_a: envelope_group_a_button(sync_handle, operator_index),
-            group_b: envelope_group_b_button(sync_handle, operator_index),
         }
     }
 
     pub fn theme_changed(&mut self) {
         self.widget.theme_changed();
-        self.group_a.theme_changed();
-        self.group_b.theme_changed();
     }
 
     pub fn set_group(&mut self, value: f32, internal: bool) {
@@ -60,9 +53,6 @@ impl Envelope {
 
         self.group = group;
         self.widget.set_group(group, internal);
-
-        self.group_a.set_value(value);
-        self.group_b.set_value(value);
     }
 
     pub fn set_group_synced(&mut self, synced: bool) {
@@ -77,7 +67,7 @@ impl Envelope {
         group == self.group && group != OperatorEnvelopeGroupValue::Off
     }
 
-    pub fn view(&self, theme: &Theme) -> Element<Message, Theme> {
+    pub fn view(&self, theme: &Theme, density: GuiDensity) -> Element<Message, Theme> {
         let group_synced: Element<Message, Theme> = if self.group_synced {
             Space::with_width(Length::Fixed(1.0)).into()
         } else {
@@ -146,22 +136,40 @@ impl Envelope {
             "Distribute view to other envelopes",
         );
 
-        let group_a = tooltip(
+        let fit_all = button_with_tooltip(
             theme,
-            "Toggle group A membership",
-            Position::Top,
-            self.group_a.view(),
+            theme.font_regular(),
+            "A",
+            Message::EnvelopeZoomToFitAll,
+            "Zoom all envelopes to fit longest one",
         );
-        let group_b = tooltip(
+
+        let group_parameter: WrappedParameter = Parameter::Operator(
+            self.operator_index as u8,
+            OperatorParameter::EnvelopeLockGroup,
+        )
+        .into();
+
+        let group = tooltip(
             theme,
-            "Toggle group B membership",
+            "Envelope lock group. Changes to an envelope are copied to other members of its group",
             Position::Top,
-            self.group_b.view(),
+            PickList::new(LOCK_STEPS, Some(self.group), move |option| {
+                Message::ChangeSingleParameterImmediate(
+                    group_parameter,
+                    OperatorEnvelopeGroupValue::new_from_audio(option).to_patch(),
+                )
+            })
+            .font(theme.font_regular())
+            .text_size(FONT_SIZE)
+            .padding(theme.picklist_padding())
+            .width(Length::Fixed(f32::from(LINE_HEIGHT * 2 + 3))),
         );
 
         Row::new()
-            .push(container_l3(self.widget.view()))
+            .push(container_l3(density, self.widget.view()))
             .push(container_l3(
+                density,
                 Column::new()
                     .width(Length::Fixed(f32::from(LINE_HEIGHT * 3)))
                     .align_items(Alignment::End)
@@ -169,24 +177,24 @@ impl Envelope {
                         Row::new()
                             .push(group_synced)
                             .push(Space::with_width(Length::Fixed(3.0)))
-                            .push(group_a)
-                            .push(Space::with_width(Length::Fixed(3.0)))
-                            .push(group_b),
+                            .push(group),
                     )
-                    .push(Space::with_height(Length::Fixed(9.0)))
+                    .push(Space::with_height(Length::Fixed(6.0)))
                     .push(
                         Row::new()
                             .push(zoom_out)
                             .push(Space::with_width(Length::Fixed(4.0)))
                             .push(zoom_in),
                     )
-                    .push(Space::with_height(Length::Fixed(6.0)))
+                    .push(Space::with_height(Length::Fixed(4.0)))
                     .push(
                         Row::new()
                             .push(fit)
                             .push(Space::with_width(Length::Fixed(4.0)))
-                            .push(distribute),
-                    ),
+                            .push(fit_all),
+                    )
+                    .push(Space::with_height(Length::Fixed(4.0)))
+                    .push(distribute),
             ))
             .into()
     }
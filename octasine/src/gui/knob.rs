@@ -1,33 +1,47 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
 use iced_audio::{graphics::knob, text_marks, tick_marks, Normal, NormalParam};
 use iced_baseview::widget::tooltip::Position;
-use iced_baseview::widget::Container;
+use iced_baseview::widget::{Button, Container};
 use iced_baseview::{
     alignment::Horizontal, keyboard::Modifiers, widget::Column, widget::Space, widget::Text,
     Alignment, Element, Length,
 };
 
 use crate::parameters::glide_time::GlideTimeValue;
-use crate::parameters::master_pitch_bend_range::{
-    MasterPitchBendRangeDownValue, MasterPitchBendRangeUpValue,
-};
+use crate::parameters::lfo_amount_sensitivity::LfoAmountSensitivityValue;
+use crate::parameters::lfo_envelope::LfoEnvelopeAttackValue;
+use crate::parameters::lfo_key_sync_spread::LfoKeySyncSpreadValue;
+use crate::parameters::lfo_smoothing::LfoSmoothingValue;
+use crate::parameters::operator_frequency_ratio::snap_frequency_ratio_patch_value;
+use crate::parameters::operator_phase_distortion::OperatorPhaseDistortionValue;
+use crate::parameters::operator_spread::OperatorSpreadValue;
+use crate::parameters::operator_wavetable_position::OperatorWavetablePositionValue;
 use crate::parameters::velocity_sensitivity::VelocitySensitivityValue;
 use crate::parameters::{
     LfoAmountValue, LfoFrequencyFreeValue, LfoFrequencyRatioValue, LfoParameter,
-    MasterFrequencyValue, MasterParameter, MasterVolumeValue, OperatorFeedbackValue,
-    OperatorFrequencyFineValue, OperatorFrequencyFreeValue, OperatorFrequencyRatioValue,
-    OperatorMixOutValue, OperatorModOutValue, OperatorPanningValue, OperatorParameter,
-    OperatorVolumeValue, Parameter, ParameterValue, WrappedParameter,
+    LfoTargetDepthValue, MasterFrequencyValue, MasterParameter, MasterVolumeValue,
+    OperatorEnvelopeVelocitySensitivityValue, OperatorFeedbackValue, OperatorFrequencyFineValue,
+    OperatorFrequencyFreeValue, OperatorFrequencyRatioValue, OperatorMixOutValue,
+    OperatorModOutValue, OperatorPanningValue, OperatorParameter, OperatorVolumeValue, Parameter,
+    ParameterValue, WrappedParameter,
 };
-use crate::sync::GuiSyncHandle;
+use crate::sync::{GuiSyncHandle, ParameterLocks};
 
 use super::common::tooltip;
+use super::style::button::ButtonStyle;
 use super::style::knob::KnobStyle;
+use super::style::text::TextStyle;
 use super::style::Theme;
-use super::value_text::ValueText;
+use super::value_text::{TextFormat, ValueText};
 use super::{Message, LINE_HEIGHT};
 
 const KNOB_SIZE: Length = Length::Fixed((LINE_HEIGHT * 2) as f32);
 
+/// How long knob title stays highlighted after host changed value
+const AUTOMATION_HIGHLIGHT_DURATION: Duration = Duration::from_millis(500);
+
 enum TickMarkType {
     MinMaxAndDefault,
 }
@@ -74,42 +88,6 @@ where
     )
 }
 
-pub fn master_pitch_bend_range_up<H>(sync_handle: &H) -> OctaSineKnob<MasterPitchBendRangeUpValue>
-where
-    H: GuiSyncHandle,
-{
-    OctaSineKnob::new_with_values(
-        sync_handle,
-        Parameter::Master(MasterParameter::PitchBendRangeUp),
-        "PB UP",
-        "Pitch bench range - upward",
-        TickMarkType::MinMaxAndDefault,
-        KnobStyle::Bipolar,
-        MasterPitchBendRangeUpValue::default().to_patch(),
-        0.5,
-        0.5,
-    )
-}
-
-pub fn master_pitch_bend_range_down<H>(
-    sync_handle: &H,
-) -> OctaSineKnob<MasterPitchBendRangeDownValue>
-where
-    H: GuiSyncHandle,
-{
-    OctaSineKnob::new_with_values(
-        sync_handle,
-        Parameter::Master(MasterParameter::PitchBendRangeDown),
-        "PB DOWN",
-        "Pitch bench range - downward",
-        TickMarkType::MinMaxAndDefault,
-        KnobStyle::Bipolar,
-        MasterPitchBendRangeDownValue::default().to_patch(),
-        0.5,
-        0.5,
-    )
-}
-
 pub fn glide_time<H>(sync_handle: &H) -> OctaSineKnob<GlideTimeValue>
 where
     H: GuiSyncHandle,
@@ -211,6 +189,23 @@ where
     )
 }
 
+pub fn operator_cross_feedback<H>(
+    sync_handle: &H,
+    operator_index: usize,
+) -> OctaSineKnob<OperatorFeedbackValue>
+where
+    H: GuiSyncHandle,
+{
+    OctaSineKnob::new(
+        sync_handle,
+        Parameter::Operator(operator_index as u8, OperatorParameter::CrossFeedback),
+        "X FB",
+        "Amount of cross feedback to target operator",
+        TickMarkType::MinMaxAndDefault,
+        KnobStyle::Regular,
+    )
+}
+
 pub fn operator_frequency_ratio<H>(
     sync_handle: &H,
     operator_index: usize,
@@ -222,10 +217,11 @@ where
         sync_handle,
         Parameter::Operator(operator_index as u8, OperatorParameter::FrequencyRatio),
         "RATIO",
-        "Frequency - fixed ratios",
+        "Frequency - fixed ratios. Click value to enter custom ratio",
         TickMarkType::MinMaxAndDefault,
         KnobStyle::Bipolar,
     )
+    .snap_value(snap_frequency_ratio_patch_value)
 }
 
 pub fn operator_frequency_free<H>(
@@ -262,6 +258,57 @@ where
     )
 }
 
+pub fn operator_wavetable_position<H>(
+    sync_handle: &H,
+    operator_index: usize,
+) -> OctaSineKnob<OperatorWavetablePositionValue>
+where
+    H: GuiSyncHandle,
+{
+    OctaSineKnob::new(
+        sync_handle,
+        Parameter::Operator(operator_index as u8, OperatorParameter::WavetablePosition),
+        "POS",
+        "Position in loaded wavetable",
+        TickMarkType::MinMaxAndDefault,
+        KnobStyle::Regular,
+    )
+}
+
+pub fn operator_phase_distortion<H>(
+    sync_handle: &H,
+    operator_index: usize,
+) -> OctaSineKnob<OperatorPhaseDistortionValue>
+where
+    H: GuiSyncHandle,
+{
+    OctaSineKnob::new(
+        sync_handle,
+        Parameter::Operator(operator_index as u8, OperatorParameter::PhaseDistortion),
+        "PD",
+        "Phase distortion (sine wave only)",
+        TickMarkType::MinMaxAndDefault,
+        KnobStyle::Regular,
+    )
+}
+
+pub fn operator_spread<H>(
+    sync_handle: &H,
+    operator_index: usize,
+) -> OctaSineKnob<OperatorSpreadValue>
+where
+    H: GuiSyncHandle,
+{
+    OctaSineKnob::new(
+        sync_handle,
+        Parameter::Operator(operator_index as u8, OperatorParameter::Spread),
+        "SPREAD",
+        "Stereo spread: delay right channel output by up to 5 ms",
+        TickMarkType::MinMaxAndDefault,
+        KnobStyle::Regular,
+    )
+}
+
 pub fn operator_feedback_velocity_sensitivity<H>(
     sync_handle: &H,
     operator_index: usize,
@@ -282,6 +329,46 @@ where
     )
 }
 
+pub fn operator_peak_velocity_sensitivity<H>(
+    sync_handle: &H,
+    operator_index: usize,
+) -> OctaSineKnob<OperatorEnvelopeVelocitySensitivityValue>
+where
+    H: GuiSyncHandle,
+{
+    OctaSineKnob::new(
+        sync_handle,
+        Parameter::Operator(
+            operator_index as u8,
+            OperatorParameter::VelocitySensitivityPeak,
+        ),
+        "PEAK VS",
+        "Envelope attack peak level velocity sensitivity",
+        TickMarkType::MinMaxAndDefault,
+        KnobStyle::Regular,
+    )
+}
+
+pub fn operator_sustain_velocity_sensitivity<H>(
+    sync_handle: &H,
+    operator_index: usize,
+) -> OctaSineKnob<OperatorEnvelopeVelocitySensitivityValue>
+where
+    H: GuiSyncHandle,
+{
+    OctaSineKnob::new(
+        sync_handle,
+        Parameter::Operator(
+            operator_index as u8,
+            OperatorParameter::VelocitySensitivitySustain,
+        ),
+        "SUS VS",
+        "Envelope sustain level velocity sensitivity",
+        TickMarkType::MinMaxAndDefault,
+        KnobStyle::Regular,
+    )
+}
+
 pub fn operator_mod_out_velocity_sensitivity<H>(
     sync_handle: &H,
     operator_index: usize,
@@ -350,17 +437,127 @@ where
     )
 }
 
+pub fn lfo_velocity_sensitivity<H>(
+    sync_handle: &H,
+    lfo_index: usize,
+) -> OctaSineKnob<LfoAmountSensitivityValue>
+where
+    H: GuiSyncHandle,
+{
+    OctaSineKnob::new(
+        sync_handle,
+        Parameter::Lfo(lfo_index as u8, LfoParameter::VelocitySensitivity),
+        "VEL",
+        "LFO amount velocity sensitivity",
+        TickMarkType::MinMaxAndDefault,
+        KnobStyle::Regular,
+    )
+}
+
+pub fn lfo_mod_wheel_sensitivity<H>(
+    sync_handle: &H,
+    lfo_index: usize,
+) -> OctaSineKnob<LfoAmountSensitivityValue>
+where
+    H: GuiSyncHandle,
+{
+    OctaSineKnob::new(
+        sync_handle,
+        Parameter::Lfo(lfo_index as u8, LfoParameter::ModWheelSensitivity),
+        "MW",
+        "LFO amount mod wheel sensitivity",
+        TickMarkType::MinMaxAndDefault,
+        KnobStyle::Regular,
+    )
+}
+
+pub fn lfo_envelope_attack<H>(
+    sync_handle: &H,
+    lfo_index: usize,
+) -> OctaSineKnob<LfoEnvelopeAttackValue>
+where
+    H: GuiSyncHandle,
+{
+    OctaSineKnob::new(
+        sync_handle,
+        Parameter::Lfo(lfo_index as u8, LfoParameter::EnvelopeAttack),
+        "ATTACK",
+        "Fraction of envelope cycle spent rising to peak",
+        TickMarkType::MinMaxAndDefault,
+        KnobStyle::Regular,
+    )
+}
+
+pub fn lfo_key_sync_spread<H>(
+    sync_handle: &H,
+    lfo_index: usize,
+) -> OctaSineKnob<LfoKeySyncSpreadValue>
+where
+    H: GuiSyncHandle,
+{
+    OctaSineKnob::new(
+        sync_handle,
+        Parameter::Lfo(lfo_index as u8, LfoParameter::KeySyncSpread),
+        "SPRD",
+        "With key sync on, start each voice at random phase within this fraction of cycle",
+        TickMarkType::MinMaxAndDefault,
+        KnobStyle::Regular,
+    )
+}
+
+pub fn lfo_smoothing<H>(sync_handle: &H, lfo_index: usize) -> OctaSineKnob<LfoSmoothingValue>
+where
+    H: GuiSyncHandle,
+{
+    OctaSineKnob::new(
+        sync_handle,
+        Parameter::Lfo(lfo_index as u8, LfoParameter::Smoothing),
+        "SMOOTH",
+        "Slew time in seconds applied to LFO output, softening abrupt changes",
+        TickMarkType::MinMaxAndDefault,
+        KnobStyle::Regular,
+    )
+}
+
+pub fn lfo_target_depth<H>(
+    sync_handle: &H,
+    lfo_index: usize,
+    parameter: LfoParameter,
+    title: &str,
+) -> OctaSineKnob<LfoTargetDepthValue>
+where
+    H: GuiSyncHandle,
+{
+    OctaSineKnob::new_with_values(
+        sync_handle,
+        Parameter::Lfo(lfo_index as u8, parameter),
+        title,
+        "How much LFO affects additional target, relative to amount",
+        TickMarkType::MinMaxAndDefault,
+        KnobStyle::Bipolar,
+        LfoTargetDepthValue::default().to_patch(),
+        0.5,
+        0.5,
+    )
+}
+
 pub struct OctaSineKnob<P: ParameterValue> {
     text_marks: Option<text_marks::Group>,
     tick_marks: Option<tick_marks::Group>,
     title: String,
     tooltip_text: String,
+    tooltip_details: Option<String>,
     value: NormalParam,
     value_text: ValueText<P>,
     center_value: Normal,
     parameter: WrappedParameter,
     phantom_data: ::std::marker::PhantomData<P>,
     knob_style: KnobStyle,
+    width: u16,
+    snap_value: Option<fn(f32) -> f32>,
+    /// Time of last value change by host
+    automated_at: Option<Instant>,
+    locks: Arc<ParameterLocks>,
 }
 
 impl<P> OctaSineKnob<P>
@@ -420,14 +617,47 @@ where
             tick_marks: Some(tick_marks),
             title: title.to_string(),
             tooltip_text: tooltip_text.to_string(),
+            tooltip_details: None,
             value,
             value_text,
             center_value: Normal::from_clipped(center_value),
             parameter,
             phantom_data: ::std::marker::PhantomData::default(),
             knob_style,
+            width: LINE_HEIGHT * 4,
+            snap_value: None,
+            automated_at: None,
+            locks: sync_handle.get_parameter_locks(),
         }
     }
+    /// Use narrower layout, for when three knobs need to fit where two
+    /// usually do. Title and value text should be short.
+    pub fn narrow(mut self) -> Self {
+        self.width = LINE_HEIGHT * 3;
+
+        self
+    }
+
+    /// Transform patch values set by turning knob
+    fn snap_value(mut self, f: fn(f32) -> f32) -> Self {
+        self.snap_value = Some(f);
+
+        self
+    }
+
+    /// Set text appended to tooltip, e.g., for showing derived values
+    pub fn set_tooltip_details(&mut self, details: Option<String>) {
+        self.tooltip_details = details;
+    }
+
+    pub fn get_value(&self) -> f32 {
+        self.value.value.as_f32()
+    }
+
+    pub fn set_text_format(&mut self, text_format: Option<TextFormat>) {
+        self.value_text.set_text_format(text_format);
+    }
+
     pub fn set_value(&mut self, value: f32) {
         // FIXME
         // if !self.knob_state.is_dragging() {
@@ -438,19 +668,53 @@ where
         self.value_text.set_value(value);
     }
 
+    /// Briefly highlight knob to show that host changed value, e.g.,
+    /// through automation
+    pub fn mark_automated(&mut self) {
+        self.automated_at = Some(Instant::now());
+    }
+
     pub fn view<'a>(&'a self, theme: &Theme) -> Element<Message, Theme> {
+        let automated = self
+            .automated_at
+            .is_some_and(|t| t.elapsed() < AUTOMATION_HIGHLIGHT_DURATION);
+        let locked = self.locks.is_locked(self.parameter.index() as usize);
+
+        let parameter = self.parameter;
+
         let title = Text::new(self.title.clone())
             .horizontal_alignment(Horizontal::Center)
             .font(theme.font_bold())
-            .height(Length::Fixed(LINE_HEIGHT.into()));
-        let title = tooltip(theme, &self.tooltip_text, Position::Top, title);
+            .height(Length::Fixed(LINE_HEIGHT.into()))
+            .style(if automated {
+                TextStyle::Highlight
+            } else if locked {
+                TextStyle::Warning
+            } else {
+                TextStyle::Regular
+            });
+        let title = Button::new(title)
+            .padding(0)
+            .style(ButtonStyle::Value)
+            .on_press(Message::ToggleParameterLock(parameter));
+        let mut tooltip_text = match self.tooltip_details.as_ref() {
+            Some(details) => format!("{}\n\n{}", self.tooltip_text, details),
+            None => self.tooltip_text.clone(),
+        };
+        if locked {
+            tooltip_text.push_str("\n\nLocked: not changed when randomizing or mutating patch");
+        }
+        let title = tooltip(theme, tooltip_text, Position::Top, title);
 
-        let parameter = self.parameter;
+        let snap_value = self.snap_value;
 
         let modifier_keys = Modifiers::SHIFT;
 
         let mut knob: knob::Knob<'a, Message, Theme> = knob::Knob::new(self.value, move |value| {
-            Message::ChangeSingleParameterSetValue(parameter, value.as_f32())
+            let value = value.as_f32();
+            let value = snap_value.map_or(value, |f| f(value));
+
+            Message::ChangeSingleParameterSetValue(parameter, value)
         })
         .on_grab(move || Some(Message::ChangeSingleParameterBegin(parameter)))
         .on_release(move || Some(Message::ChangeSingleParameterEnd(parameter)))
@@ -468,7 +732,7 @@ where
 
         Container::new(
             Column::new()
-                .width(Length::Fixed(f32::from(LINE_HEIGHT * 4)))
+                .width(Length::Fixed(f32::from(self.width)))
                 .align_items(Alignment::Center)
                 .push(title)
                 .push(Space::with_height(Length::Fixed(LINE_HEIGHT.into())))
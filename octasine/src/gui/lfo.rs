@@ -2,24 +2,34 @@ use iced_baseview::widget::tooltip::Position;
 use iced_baseview::widget::Container;
 use iced_baseview::{
     alignment::Horizontal, alignment::Vertical, widget::Column, widget::Row, widget::Space,
-    widget::Text, Element, Length,
+    widget::Text, Alignment, Element, Length,
 };
 
+use crate::parameters::lfo_amount_sensitivity::LfoAmountSensitivityValue;
+use crate::parameters::lfo_envelope::LfoEnvelopeAttackValue;
+use crate::parameters::lfo_key_sync_spread::LfoKeySyncSpreadValue;
+use crate::parameters::lfo_polarity::LfoPolarityValue;
+use crate::parameters::lfo_smoothing::LfoSmoothingValue;
 use crate::parameters::{
     LfoAmountValue, LfoFrequencyFreeValue, LfoFrequencyRatioValue, LfoParameter, LfoShapeValue,
-    Parameter,
+    LfoTargetDepthValue, Parameter, ParameterValue,
 };
 use crate::sync::GuiSyncHandle;
 
 use super::boolean_button::{
-    lfo_active_button, lfo_bpm_sync_button, lfo_key_sync_button, lfo_mode_button, BooleanButton,
+    lfo_active_button, lfo_bpm_sync_button, lfo_key_sync_button, lfo_mode_button,
+    lfo_polarity_down_button, lfo_polarity_up_button, lfo_transport_restart_button,
+    lfo_transport_sync_button, BooleanButton,
 };
 use super::common::{container_l1, container_l2, container_l3, space_l3, tooltip};
 use super::knob::{self, OctaSineKnob};
+use super::lfo_envelope_curve_picker::LfoEnvelopeCurvePicker;
 use super::lfo_target_picker::LfoTargetPicker;
 use super::style::Theme;
 use super::wave_picker::WavePicker;
-use super::{Message, FONT_SIZE, LINE_HEIGHT};
+use super::{GuiDensity, Message, FONT_SIZE, LINE_HEIGHT};
+
+const EXTRA_PICKER_SPACING: u16 = 3;
 
 pub struct LfoWidgets {
     index: usize,
@@ -28,10 +38,27 @@ pub struct LfoWidgets {
     pub mode: BooleanButton,
     pub bpm_sync: BooleanButton,
     pub key_sync: BooleanButton,
+    pub transport_sync: BooleanButton,
+    /// Controls same parameter as transport_sync
+    pub transport_restart: BooleanButton,
     pub frequency_ratio: OctaSineKnob<LfoFrequencyRatioValue>,
     pub frequency_free: OctaSineKnob<LfoFrequencyFreeValue>,
     pub amount: OctaSineKnob<LfoAmountValue>,
     pub active: BooleanButton,
+    pub target_2: LfoTargetPicker,
+    pub target_2_depth: OctaSineKnob<LfoTargetDepthValue>,
+    pub target_3: LfoTargetPicker,
+    pub target_3_depth: OctaSineKnob<LfoTargetDepthValue>,
+    /// Controls same parameter as polarity_down
+    pub polarity_up: BooleanButton,
+    pub polarity_down: BooleanButton,
+    pub velocity_sensitivity: OctaSineKnob<LfoAmountSensitivityValue>,
+    pub mod_wheel_sensitivity: OctaSineKnob<LfoAmountSensitivityValue>,
+    pub envelope_curve: LfoEnvelopeCurvePicker,
+    pub envelope_attack: OctaSineKnob<LfoEnvelopeAttackValue>,
+    pub key_sync_spread: OctaSineKnob<LfoKeySyncSpreadValue>,
+    pub smoothing: OctaSineKnob<LfoSmoothingValue>,
+    pub alternative_controls: bool,
 }
 
 impl LfoWidgets {
@@ -40,27 +67,99 @@ impl LfoWidgets {
 
         Self {
             index: lfo_index,
-            target: LfoTargetPicker::new(sync_handle, lfo_index),
+            target: LfoTargetPicker::new(sync_handle, lfo_index, LfoParameter::Target),
             shape: WavePicker::new(sync_handle, lfo_wave_type_parameter, "SHAPE"),
             mode: lfo_mode_button(sync_handle, lfo_index),
             bpm_sync: lfo_bpm_sync_button(sync_handle, lfo_index),
             key_sync: lfo_key_sync_button(sync_handle, lfo_index),
+            transport_sync: lfo_transport_sync_button(sync_handle, lfo_index),
+            transport_restart: lfo_transport_restart_button(sync_handle, lfo_index),
             frequency_ratio: knob::lfo_frequency_ratio(sync_handle, lfo_index),
             frequency_free: knob::lfo_frequency_free(sync_handle, lfo_index),
             amount: knob::lfo_amount(sync_handle, lfo_index),
             active: lfo_active_button(sync_handle, lfo_index),
+            target_2: LfoTargetPicker::new(sync_handle, lfo_index, LfoParameter::Target2),
+            target_2_depth: knob::lfo_target_depth(
+                sync_handle,
+                lfo_index,
+                LfoParameter::Target2Depth,
+                "DEPTH 2",
+            )
+            .narrow(),
+            target_3: LfoTargetPicker::new(sync_handle, lfo_index, LfoParameter::Target3),
+            target_3_depth: knob::lfo_target_depth(
+                sync_handle,
+                lfo_index,
+                LfoParameter::Target3Depth,
+                "DEPTH 3",
+            )
+            .narrow(),
+            polarity_up: lfo_polarity_up_button(sync_handle, lfo_index),
+            polarity_down: lfo_polarity_down_button(sync_handle, lfo_index),
+            velocity_sensitivity: knob::lfo_velocity_sensitivity(sync_handle, lfo_index).narrow(),
+            mod_wheel_sensitivity: knob::lfo_mod_wheel_sensitivity(sync_handle, lfo_index).narrow(),
+            envelope_curve: LfoEnvelopeCurvePicker::new(sync_handle, lfo_index),
+            envelope_attack: knob::lfo_envelope_attack(sync_handle, lfo_index),
+            key_sync_spread: knob::lfo_key_sync_spread(sync_handle, lfo_index).narrow(),
+            smoothing: knob::lfo_smoothing(sync_handle, lfo_index).narrow(),
+            alternative_controls: false,
         }
     }
 
+    /// Show range of target parameter values covered by LFO at full
+    /// amount in amount knob tooltip
+    pub fn update_amount_readout<H: GuiSyncHandle>(&mut self, sync_handle: &H) {
+        let target = self.target.get_target();
+
+        if let Parameter::None = target {
+            self.amount.set_tooltip_details(None);
+
+            return;
+        }
+
+        let target_value = sync_handle.get_parameter(target.into());
+        let amount = LfoAmountValue::new_from_patch(self.amount.get_value()).get();
+        let polarity = LfoPolarityValue::new_from_patch(
+            sync_handle
+                .get_parameter(Parameter::Lfo(self.index as u8, LfoParameter::Polarity).into()),
+        )
+        .get();
+
+        let (min, max) = if self.envelope_curve.is_active() {
+            (
+                polarity.apply_to_unipolar(0.0),
+                polarity.apply_to_unipolar(1.0),
+            )
+        } else {
+            (polarity.apply(-1.0), polarity.apply(1.0))
+        };
+
+        let format = |addition: f32| {
+            sync_handle.format_parameter_value_as_modulation_target(
+                target.into(),
+                (target_value + addition * amount).clamp(0.0, 1.0),
+            )
+        };
+
+        let (from, to) = (format(min.min(max)), format(min.max(max)));
+
+        self.amount
+            .set_tooltip_details(Some(format!("{}: {} to {}", target.name(), from, to)));
+    }
+
     pub fn theme_changed(&mut self) {
         self.mode.theme_changed();
         self.bpm_sync.theme_changed();
         self.key_sync.theme_changed();
+        self.transport_sync.theme_changed();
+        self.polarity_up.theme_changed();
+        self.polarity_down.theme_changed();
+        self.transport_restart.theme_changed();
         self.active.theme_changed();
         self.shape.theme_changed();
     }
 
-    pub fn view(&self, theme: &Theme) -> Element<Message, Theme> {
+    pub fn view(&self, theme: &Theme, density: GuiDensity) -> Element<Message, Theme> {
         let title = Text::new(format!("LFO {}", self.index + 1))
             .size(FONT_SIZE + FONT_SIZE / 2)
             .height(Length::Fixed(f32::from(FONT_SIZE + FONT_SIZE / 2)))
@@ -89,43 +188,146 @@ impl LfoWidgets {
             Position::Top,
             self.key_sync.view(),
         );
+        let transport_sync = tooltip(
+            theme,
+            "Lock LFO cycle to host song position when BPM sync is on",
+            Position::Top,
+            self.transport_sync.view(),
+        );
+        let transport_restart = tooltip(
+            theme,
+            "Restart LFO cycle when host transport starts",
+            Position::Top,
+            self.transport_restart.view(),
+        );
+
+        // Polarity buttons take the place of transport buttons when
+        // alternative controls are shown
+        let (button_a, button_b) = if self.alternative_controls {
+            let polarity_up = tooltip(
+                theme,
+                "Unipolar mode: only modulate upward",
+                Position::Top,
+                self.polarity_up.view(),
+            );
+            let polarity_down = tooltip(
+                theme,
+                "Unipolar mode: only modulate downward",
+                Position::Top,
+                self.polarity_down.view(),
+            );
+
+            (polarity_up, polarity_down)
+        } else {
+            (transport_restart, transport_sync)
+        };
+
+        let end = if self.alternative_controls {
+            let extra_picker = |text: &'static str, picker| {
+                Row::new()
+                    .align_items(Alignment::Center)
+                    .push(
+                        Text::new(text)
+                            .font(theme.font_bold())
+                            .width(Length::Fixed(f32::from(LINE_HEIGHT * 2))),
+                    )
+                    .push(picker)
+            };
+
+            let extra_pickers = Column::new()
+                .width(Length::Fixed(f32::from(LINE_HEIGHT * 7)))
+                .push(extra_picker("T2", self.target_2.view(theme)))
+                .push(Space::with_height(Length::Fixed(
+                    EXTRA_PICKER_SPACING.into(),
+                )))
+                .push(extra_picker("T3", self.target_3.view(theme)))
+                .push(Space::with_height(Length::Fixed(
+                    EXTRA_PICKER_SPACING.into(),
+                )))
+                .push(tooltip(
+                    theme,
+                    "Envelope mode: run single cycle as attack-decay envelope",
+                    Position::Top,
+                    extra_picker("ENV", self.envelope_curve.view(theme)),
+                ));
+
+            container_l2(
+                density,
+                Row::new()
+                    .push(container_l3(density, extra_pickers))
+                    .push(space_l3())
+                    .push(container_l3(
+                        density,
+                        Row::new()
+                            .push(self.target_2_depth.view(theme))
+                            .push(self.target_3_depth.view(theme))
+                            .push(self.smoothing.view(theme)),
+                    )),
+            )
+        } else {
+            // Shape is not used in envelope mode
+            let shape_or_attack = if self.envelope_curve.is_active() {
+                self.envelope_attack.view(theme)
+            } else {
+                self.shape.view(theme)
+            };
+
+            container_l2(
+                density,
+                Row::new()
+                    .push(container_l3(density, shape_or_attack))
+                    .push(space_l3())
+                    .push(container_l3(density, self.amount.view(theme)))
+                    .push(space_l3())
+                    .push(container_l3(density, self.frequency_ratio.view(theme)))
+                    .push(space_l3())
+                    .push(container_l3(density, self.frequency_free.view(theme))),
+            )
+        };
+
+        let buttons = Row::new()
+            .push(active)
+            .push(Space::with_width(Length::Fixed(3.0)))
+            .push(key_sync)
+            .push(Space::with_width(Length::Fixed(f32::from(
+                LINE_HEIGHT * 3 - 12 - 1,
+            ))))
+            .push(button_a)
+            .push(Space::with_width(Length::Fixed(3.0)))
+            .push(button_b)
+            .push(Space::with_width(Length::Fixed(3.0)))
+            .push(bpm_sync)
+            .push(Space::with_width(Length::Fixed(3.0)))
+            .push(mode);
+
+        let start = Column::new()
+            .push(Space::with_height(Length::Fixed(f32::from(
+                density.padding(),
+            ))))
+            .push(buttons);
+
+        // Amount sensitivity and key sync spread knobs take the place of
+        // title and target picker when alternative controls are shown
+        let start = if self.alternative_controls {
+            start.push(
+                Row::new()
+                    .push(self.velocity_sensitivity.view(theme))
+                    .push(self.mod_wheel_sensitivity.view(theme))
+                    .push(self.key_sync_spread.view(theme)),
+            )
+        } else {
+            start
+                .push(title)
+                .push(Space::with_height(Length::Fixed(f32::from(LINE_HEIGHT))))
+                .push(Row::new().push(self.target.view(theme)))
+        };
 
         container_l1(
             Row::new()
                 .push(Space::with_width(Length::Fixed(f32::from(LINE_HEIGHT))))
-                .push(
-                    Container::new(
-                        Column::new()
-                            .push(Space::with_height(Length::Fixed(f32::from(LINE_HEIGHT))))
-                            .push(
-                                Row::new()
-                                    .push(active)
-                                    .push(Space::with_width(Length::Fixed(3.0)))
-                                    .push(key_sync)
-                                    .push(Space::with_width(Length::Fixed(f32::from(
-                                        LINE_HEIGHT * 5 - 6 - 1,
-                                    ))))
-                                    .push(bpm_sync)
-                                    .push(Space::with_width(Length::Fixed(3.0)))
-                                    .push(mode),
-                            )
-                            .push(title)
-                            .push(Space::with_height(Length::Fixed(f32::from(LINE_HEIGHT))))
-                            .push(Row::new().push(self.target.view(theme))),
-                    )
-                    .width(Length::Fixed(f32::from(LINE_HEIGHT * 9))),
-                )
+                .push(Container::new(start).width(Length::Fixed(f32::from(LINE_HEIGHT * 9))))
                 .push(Space::with_width(Length::Fixed(f32::from(LINE_HEIGHT))))
-                .push(container_l2(
-                    Row::new()
-                        .push(container_l3(self.shape.view(theme)))
-                        .push(space_l3())
-                        .push(container_l3(self.amount.view(theme)))
-                        .push(space_l3())
-                        .push(container_l3(self.frequency_ratio.view(theme)))
-                        .push(space_l3())
-                        .push(container_l3(self.frequency_free.view(theme))),
-                )),
+                .push(end),
         )
         .into()
     }
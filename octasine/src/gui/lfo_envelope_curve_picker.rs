@@ -0,0 +1,70 @@
+use iced_baseview::widget::PickList;
+use iced_baseview::{Element, Length};
+
+use crate::parameters::lfo_envelope::{
+    LfoEnvelopeCurve, LfoEnvelopeCurveValue, LFO_ENVELOPE_CURVE_STEPS,
+};
+use crate::parameters::{LfoParameter, Parameter, ParameterValue, WrappedParameter};
+
+use super::{style::Theme, GuiSyncHandle, Message, FONT_SIZE};
+
+#[derive(Clone, PartialEq, Eq)]
+struct EnvelopeCurve(LfoEnvelopeCurve);
+
+impl ToString for EnvelopeCurve {
+    fn to_string(&self) -> String {
+        LfoEnvelopeCurveValue::new_from_audio(self.0)
+            .get_formatted()
+            .to_string()
+    }
+}
+
+pub struct LfoEnvelopeCurvePicker {
+    options: Vec<EnvelopeCurve>,
+    selected: LfoEnvelopeCurve,
+    parameter: WrappedParameter,
+}
+
+impl LfoEnvelopeCurvePicker {
+    pub fn new<H: GuiSyncHandle>(sync_handle: &H, lfo_index: usize) -> Self {
+        let parameter = Parameter::Lfo(lfo_index as u8, LfoParameter::EnvelopeCurve).into();
+        let sync_value = sync_handle.get_parameter(parameter);
+
+        Self {
+            options: LFO_ENVELOPE_CURVE_STEPS
+                .iter()
+                .copied()
+                .map(EnvelopeCurve)
+                .collect(),
+            selected: LfoEnvelopeCurveValue::new_from_patch(sync_value).get(),
+            parameter,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.selected.is_active()
+    }
+
+    pub fn set_value(&mut self, sync_value: f32) {
+        self.selected = LfoEnvelopeCurveValue::new_from_patch(sync_value).get();
+    }
+
+    pub fn view(&self, theme: &Theme) -> Element<Message, Theme> {
+        let parameter = self.parameter;
+
+        PickList::new(
+            &self.options[..],
+            Some(EnvelopeCurve(self.selected)),
+            move |option| {
+                let sync = LfoEnvelopeCurveValue::new_from_audio(option.0).to_patch();
+
+                Message::ChangeSingleParameterImmediate(parameter, sync)
+            },
+        )
+        .font(theme.font_regular())
+        .text_size(FONT_SIZE)
+        .padding(theme.picklist_padding())
+        .width(Length::Fill)
+        .into()
+    }
+}
@@ -1,7 +1,10 @@
+use std::marker::PhantomData;
+
 use iced_baseview::widget::PickList;
 use iced_baseview::{Element, Length};
 
 use crate::parameters::lfo_target::LfoTargetParameter;
+use crate::parameters::note_random::get_note_random_target_parameters;
 use crate::parameters::{
     get_lfo_target_parameters, Lfo1TargetParameterValue, Lfo2TargetParameterValue,
     Lfo3TargetParameterValue, Lfo4TargetParameterValue, LfoParameter, Parameter, ParameterValue,
@@ -30,8 +33,14 @@ pub struct LfoTargetPicker {
 }
 
 impl LfoTargetPicker {
-    pub fn new<H: GuiSyncHandle>(sync_handle: &H, lfo_index: usize) -> Self {
-        let parameter = Parameter::Lfo(lfo_index as u8, LfoParameter::Target).into();
+    /// Target parameter must be one of LfoParameter::Target, ::Target2 or
+    /// ::Target3
+    pub fn new<H: GuiSyncHandle>(
+        sync_handle: &H,
+        lfo_index: usize,
+        target_parameter: LfoParameter,
+    ) -> Self {
+        let parameter = Parameter::Lfo(lfo_index as u8, target_parameter).into();
         let sync_value = sync_handle.get_parameter(parameter);
         let selected = Self::get_index_from_sync(lfo_index, sync_value);
         let target_parameters = get_lfo_target_parameters(lfo_index);
@@ -76,6 +85,10 @@ impl LfoTargetPicker {
         self.selected = Self::get_index_from_sync(self.lfo_index, sync_value);
     }
 
+    pub fn get_target(&self) -> Parameter {
+        self.options[self.selected].value.parameter()
+    }
+
     pub fn view(&self, theme: &Theme) -> Element<Message, Theme> {
         let lfo_index = self.lfo_index;
         let parameter = self.parameter;
@@ -102,3 +115,66 @@ impl LfoTargetPicker {
         .into()
     }
 }
+
+/// Target picker for per-note modulation sources, i.e., random per note
+/// value and MPE pressure and timbre
+pub struct NoteModulationTargetPicker<V> {
+    parameter: Parameter,
+    options: Vec<LfoTarget>,
+    selected: usize,
+    phantom_data: PhantomData<V>,
+}
+
+impl<V: ParameterValue<Value = LfoTargetParameter>> NoteModulationTargetPicker<V> {
+    pub fn new<H: GuiSyncHandle>(sync_handle: &H, parameter: Parameter) -> Self {
+        let sync_value = sync_handle.get_parameter(parameter.into());
+
+        let options = get_note_random_target_parameters()
+            .iter()
+            .map(|target| LfoTarget {
+                value: *target,
+                title: target.parameter().name().to_uppercase(),
+            })
+            .collect();
+
+        Self {
+            parameter,
+            options,
+            selected: Self::get_index_from_sync(sync_value),
+            phantom_data: PhantomData,
+        }
+    }
+
+    fn get_index_from_sync(sync_value: f32) -> usize {
+        let target = V::new_from_patch(sync_value).get();
+
+        get_note_random_target_parameters()
+            .iter()
+            .position(|t| *t == target)
+            .unwrap()
+    }
+
+    pub fn set_value(&mut self, sync_value: f32) {
+        self.selected = Self::get_index_from_sync(sync_value);
+    }
+
+    pub fn view(&self, theme: &Theme) -> Element<Message, Theme> {
+        let parameter = self.parameter;
+
+        PickList::new(
+            &self.options[..],
+            Some(self.options[self.selected].clone()),
+            move |option| {
+                Message::ChangeSingleParameterImmediate(
+                    parameter.into(),
+                    V::new_from_audio(option.value).to_patch(),
+                )
+            },
+        )
+        .font(theme.font_regular())
+        .text_size(FONT_SIZE)
+        .padding(theme.picklist_padding())
+        .width(Length::Fill)
+        .into()
+    }
+}
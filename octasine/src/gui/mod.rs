@@ -1,22 +1,29 @@
+mod bank_overview;
 mod boolean_button;
 mod common;
 mod corner;
 mod envelope;
 mod knob;
 mod lfo;
+mod lfo_envelope_curve_picker;
 mod lfo_target_picker;
 mod mod_matrix;
 mod mod_target_picker;
 mod operator;
 mod patch_picker;
+mod patch_sheet;
+mod performance;
+mod range_picker;
 pub mod style;
 mod value_text;
+mod velocity_curve;
 mod wave_display;
 mod wave_picker;
 
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::ptr::NonNull;
+use std::sync::Arc;
 
 use anyhow::Context;
 use cfg_if::cfg_if;
@@ -24,32 +31,34 @@ use compact_str::CompactString;
 use iced_aw::native::{Card, Modal};
 use iced_baseview::alignment::Horizontal;
 use iced_baseview::command::Action;
-use iced_baseview::widget::{Button, PickList, Text};
+use iced_baseview::widget::{Button, Checkbox, PickList, Text};
 use iced_baseview::{executor, window::WindowSubs, Application, Command, Subscription};
 use iced_baseview::{
-    widget::Column, widget::Container, widget::Row, widget::Space, window::WindowQueue, Element,
-    Length, Point,
+    widget::Column, widget::Container, widget::Row, widget::Space, window::WindowQueue, Alignment,
+    Element, Length, Point,
 };
 use serde::{Deserialize, Serialize};
 
-use crate::common::NUM_OPERATORS;
+use crate::common::{NUM_OPERATORS, NUM_PATCHES};
+use crate::parameters::chord_memory::{ChordMemory, ChordMemoryValue};
+use crate::parameters::mod_snapshot::ModSnapshotSlotValue;
 use crate::parameters::*;
-use crate::sync::GuiSyncHandle;
+use crate::sync::{GuiSyncHandle, PatchCategory, PatchImportTarget, RandomizeScope, SessionState};
+use crate::wavetable::Wavetable;
 
+use bank_overview::BankOverview;
 use lfo::LfoWidgets;
 use operator::OperatorWidgets;
 use patch_picker::PatchPicker;
+use performance::PerformanceWidgets;
 use style::Theme;
 
-use self::corner::CornerWidgets;
+use self::corner::{CornerWidgets, NoteModulationSource};
 use self::operator::ModTargetPicker;
 use self::style::container::ContainerStyle;
 
 use crate::settings::Settings;
 
-pub const GUI_WIDTH: usize = 12 * 82;
-pub const GUI_HEIGHT: usize = 12 * 55;
-
 const FONT_SIZE: u16 = 12;
 const LINE_HEIGHT: u16 = 12;
 
@@ -61,6 +70,9 @@ const OPEN_SANS_BYTES_BOLD: &[u8] = include_bytes!("../../../contrib/open-sans/O
 const OPEN_SANS_BYTES_EXTRA_BOLD: &[u8] =
     include_bytes!("../../../contrib/open-sans/OpenSans-ExtraBold.ttf");
 
+/// Number of child patches that can be bred at once
+const BREED_NUM_CHILDREN_STEPS: &[usize] = &[1, 2, 4, 8];
+
 pub trait SnapPoint {
     fn snap(self) -> Self;
 }
@@ -78,6 +90,161 @@ impl SnapPoint for Point {
 
 pub struct GuiSettings {
     pub theme: style::Theme,
+    #[serde(default)]
+    pub density: GuiDensity,
+    /// Display operator frequency ratios as semitones and cents
+    #[serde(default)]
+    pub operator_semitone_frequency: [bool; NUM_OPERATORS],
+    #[serde(default)]
+    pub frequency_reference_note: FrequencyReferenceNote,
+    #[serde(default)]
+    pub operators_collapsed: [bool; NUM_OPERATORS],
+    #[serde(default)]
+    pub lfos_collapsed: bool,
+    /// Parameters assigned to performance view knobs
+    #[serde(default)]
+    pub performance_knobs: [Option<ParameterKey>; performance::NUM_ASSIGNABLE_KNOBS],
+    #[serde(default)]
+    pub mutation_strength: MutationStrength,
+}
+
+/// Layout density
+///
+/// Controls spacing between panels, padding inside them and row height,
+/// while knobs, pickers and canvases keep their size. Since hosts query
+/// editor size before opening it, changes take effect the next time the
+/// editor is opened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum GuiDensity {
+    Dense,
+    #[default]
+    Compact,
+    Comfortable,
+}
+
+/// Note used for showing operator frequencies in Hz
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FrequencyReferenceNote(pub u8);
+
+impl Default for FrequencyReferenceNote {
+    fn default() -> Self {
+        Self(69)
+    }
+}
+
+impl FrequencyReferenceNote {
+    pub const STEPS: &'static [Self] = &[
+        Self(24),
+        Self(36),
+        Self(48),
+        Self(60),
+        Self(69),
+        Self(72),
+        Self(84),
+        Self(96),
+    ];
+
+    pub fn frequency(self, master_frequency: f64) -> f64 {
+        master_frequency * 2.0f64.powf((f64::from(self.0) - 69.0) / 12.0)
+    }
+}
+
+impl std::fmt::Display for FrequencyReferenceNote {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        const NOTE_NAMES: [&str; 12] = [
+            "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+        ];
+
+        let octave = i16::from(self.0 / 12) - 1;
+
+        write!(f, "{}{}", NOTE_NAMES[usize::from(self.0 % 12)], octave)
+    }
+}
+
+/// Maximum change of each parameter when mutating patch, in percent of
+/// parameter range
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MutationStrength(pub u8);
+
+impl Default for MutationStrength {
+    fn default() -> Self {
+        Self(5)
+    }
+}
+
+impl MutationStrength {
+    pub const STEPS: &'static [Self] = &[Self(1), Self(2), Self(5), Self(10), Self(20), Self(35)];
+
+    pub fn fraction(self) -> f32 {
+        f32::from(self.0) / 100.0
+    }
+}
+
+impl std::fmt::Display for MutationStrength {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "±{}%", self.0)
+    }
+}
+
+impl GuiDensity {
+    /// Space between panels
+    pub fn spacing(self) -> u16 {
+        match self {
+            Self::Dense => LINE_HEIGHT / 2,
+            Self::Compact => LINE_HEIGHT,
+            Self::Comfortable => LINE_HEIGHT + LINE_HEIGHT / 2,
+        }
+    }
+
+    /// Padding between panel edges and contents
+    pub fn padding(self) -> u16 {
+        match self {
+            Self::Dense => LINE_HEIGHT * 2 / 3,
+            Self::Compact => LINE_HEIGHT,
+            Self::Comfortable => LINE_HEIGHT + LINE_HEIGHT / 2,
+        }
+    }
+
+    /// Height of text rows in collapsed panels and space between rows in
+    /// dialogs
+    pub fn line_height(self) -> u16 {
+        match self {
+            Self::Dense => LINE_HEIGHT * 5 / 6,
+            Self::Compact => LINE_HEIGHT,
+            Self::Comfortable => LINE_HEIGHT + LINE_HEIGHT / 3,
+        }
+    }
+
+    /// Height of an expanded operator or LFO row, or of a corner row
+    fn panel_row_height(self) -> u16 {
+        LINE_HEIGHT * 6 + self.padding() * 2
+    }
+
+    /// Editor size in logical pixels (before system scaling)
+    ///
+    /// Operator rows are widest, with four padded groups and three spaces
+    /// between them. Vertically, there are six panel rows (four operators
+    /// plus two rows of LFOs and corner widgets) and six spaces.
+    pub fn gui_size(self) -> (usize, usize) {
+        let line_height = usize::from(LINE_HEIGHT);
+        let spacing = usize::from(self.spacing());
+        let padding = usize::from(self.padding());
+
+        let width = line_height * 71 + padding * 8 + spacing * 3;
+        let height = line_height * 37 + padding * 12 + spacing * 6;
+
+        (width, height)
+    }
+}
+
+impl std::fmt::Display for GuiDensity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Dense => "DENSE",
+            Self::Compact => "COMPACT",
+            Self::Comfortable => "COMFY",
+        })
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -106,6 +273,12 @@ pub enum Message {
         parameter_2: Option<(WrappedParameter, f32)>,
     },
     ChangePatch(usize),
+    /// Only show patches in category in patch picker (or all if None)
+    ChangePatchCategoryFilter(Option<PatchCategory>),
+    /// Only show patches with names containing text in patch picker
+    ChangePatchSearchText(String),
+    /// Select first patch in patch picker matching filters
+    SelectFirstPatchSearchMatch,
     /// Set viewport, broadcast it to group members
     EnvelopeChangeViewport {
         operator_index: u8,
@@ -117,31 +290,141 @@ pub enum Message {
         viewport_factor: f32,
         x_offset: f32,
     },
+    /// Distribute viewport fitting longest envelope to all envelopes
+    EnvelopeZoomToFitAll,
     SwitchTheme,
+    /// Set GUI density. Takes effect when editor is reopened.
+    SetDensity(GuiDensity),
+    /// Toggle only showing operator heading row
+    ToggleOperatorCollapsed(usize),
+    /// Toggle hiding LFO section
+    ToggleLfosCollapsed,
+    /// Toggle performance view with large knobs and on-screen keyboard
+    TogglePerformanceView,
+    SetPerformanceKnobParameter(usize, Option<Parameter>),
+    /// Play key on on-screen keyboard, releasing previous one
+    SetGuiKeyboardKey(Option<u8>),
+    ShiftGuiKeyboardOctave(i8),
     ToggleAlternativeControls,
+    /// Store currently held keys as chord memory (clears it if no keys are
+    /// held)
+    LearnChordMemory,
+    /// Store current modulation parameter values in selected mod snapshot
+    /// slot (does nothing if no slot is selected)
+    StoreModSnapshot,
     SavePatch,
+    /// Save patch and audio preview rendered with it as WAV file next to it
+    SavePatchWithPreview,
     SaveBank,
+    /// Save parameter sheet of current patch as PNG image
+    ExportPatchSheet,
     LoadBankOrPatch,
     RenamePatch,
+    /// Edit author stored in patch (included in exported patch files)
+    SetPatchAuthor,
+    /// Edit license stored in patch (included in exported patch files)
+    SetPatchLicense,
     ClearPatch,
     ClearBank,
+    /// Set parameters in part of current patch to random values
+    RandomizeParameters(RandomizeScope),
+    /// Nudge parameters in current patch by random amounts of at most
+    /// current mutation strength
+    MutatePatch,
+    SetMutationStrength(MutationStrength),
+    /// Toggle whether parameter is skipped when randomizing or mutating
+    ToggleParameterLock(WrappedParameter),
+    /// Choose parent patches to create child patches from
+    BreedPatches,
     SaveBankOrPatchToFile(PathBuf, Vec<u8>),
+    SavePatchAndPreviewToFile(PathBuf, Vec<u8>),
+    /// Start recording parameter changes, discarding previous capture
+    StartParameterChangeCapture,
+    /// Stop recording parameter changes and save capture as CSV or JSON
+    ExportParameterChangeCapture,
+    /// Load patch bank file directly, or ask for slots to import patch
+    /// files into
     LoadBankOrPatchesFromPaths(Vec<PathBuf>),
+    /// Open file dialog for choosing patch bank or patch file to load into
+    /// new instances
+    PickStartupPatches,
+    /// Set (or clear) patch bank or patch file loaded into new instances
+    SetStartupPatches(Option<PathBuf>),
+    /// Toggle playing preview note when selecting patch
+    TogglePatchAudition,
+    /// Release all keys and quickly fade out all voices
+    Panic,
+    /// Turn off latched clip indicator
+    ResetClipIndicator,
+    /// Toggle automatically releasing keys held for very long while host
+    /// transport is stopped
+    ToggleStuckNoteWatchdog,
+    /// Open file dialog for loading wavetable into operator
+    LoadWavetable(usize),
+    LoadWavetableFromPath(usize, PathBuf),
+    ClearWavetable(usize),
     ChangeParameterByTextInput {
         parameter: WrappedParameter,
         value_text: CompactString,
+        /// Parse text with this function instead of with parameter default
+        parse: Option<fn(&str) -> Option<f32>>,
     },
+    /// Toggle displaying operator frequency ratio as semitones and cents
+    ToggleOperatorSemitoneFrequency(usize),
+    /// Toggle temporarily disabling all LFO modulation (not stored in
+    /// patches)
+    ToggleLfosDisabled,
+    /// Toggle compensating output level for operator mute, listen, volume
+    /// and mix changes (not stored in patches)
+    ToggleAutoGain,
+    /// Toggle summing output to mono for checking mono compatibility (not
+    /// stored in patches)
+    ToggleMonoFoldDown,
+    /// Toggle routing only this operator to output (not stored in patches)
+    ToggleOperatorListen(usize),
+    /// Select per-note modulation source shown in alternative controls
+    ChangeNoteModulationSource(NoteModulationSource),
+    SetFrequencyReferenceNote(FrequencyReferenceNote),
     ModalOpen(ModalAction),
     ModalClose,
     ModalYes,
     /// Currently not used
     ModalSetParameterByChoicesUpdate(CompactString),
+    ModalImportPatchesTargetUpdate(PatchImportTarget),
+    /// Start or stop temporarily loading first patch file into current slot
+    ModalImportPatchesTogglePreview,
+    /// Set first or second parent patch by its name in patch list
+    ModalBreedPatchesParentUpdate(usize, CompactString),
+    ModalBreedPatchesNumChildrenUpdate(usize),
+    ModalBreedPatchesToggleMutation,
 }
 
 #[derive(Debug, Clone)]
 pub enum ModalAction {
     ClearPatch,
     ClearBank,
+    /// Offer saving bank before opening files would overwrite edits
+    LoadWithUnsavedChanges,
+    /// Show which patch bank slots are in use
+    BankOverview,
+    /// Choose mutation strength and repeatedly mutate patch
+    MutatePatch,
+    /// Choose parent patches and number of children to write into empty
+    /// bank slots
+    BreedPatches {
+        patch_names: Vec<CompactString>,
+        parents: [usize; 2],
+        num_children: usize,
+        /// Mutate children with current mutation strength
+        mutate: bool,
+    },
+    /// Choose bank slots to import patch files into
+    ImportPatches {
+        paths: Vec<PathBuf>,
+        target: PatchImportTarget,
+        /// First patch file is temporarily loaded into current slot
+        previewing: bool,
+    },
     /// Currently not used
     SetParameterByChoices {
         parameter: WrappedParameter,
@@ -153,6 +436,10 @@ pub enum ModalAction {
 pub struct OctaSineIcedApplication<H: GuiSyncHandle> {
     sync_handle: H,
     theme: style::Theme,
+    /// Density setting, saved on change
+    density: GuiDensity,
+    /// Density that window was opened with
+    opened_density: GuiDensity,
     operator_1: OperatorWidgets,
     operator_2: OperatorWidgets,
     operator_3: OperatorWidgets,
@@ -161,12 +448,44 @@ pub struct OctaSineIcedApplication<H: GuiSyncHandle> {
     lfo_2: LfoWidgets,
     lfo_3: LfoWidgets,
     lfo_4: LfoWidgets,
+    lfos_collapsed: bool,
     corner: CornerWidgets,
+    performance: PerformanceWidgets,
+    /// Show performance view instead of operators
+    performance_view: bool,
+    bank_overview: BankOverview,
+    /// Directory of patch or bank file last opened or saved
+    last_directory: Option<PathBuf>,
+    mutation_strength: MutationStrength,
     modal_action: Option<ModalAction>,
 }
 
 impl<H: GuiSyncHandle> OctaSineIcedApplication<H> {
     fn set_value(&mut self, parameter: Parameter, v: f32, internal: bool) {
+        self.performance.set_value(&self.sync_handle, parameter, v);
+
+        let frequency_changed = matches!(
+            parameter,
+            Parameter::Master(MasterParameter::Frequency)
+                | Parameter::Operator(
+                    _,
+                    OperatorParameter::FrequencyRatio
+                        | OperatorParameter::FrequencyFree
+                        | OperatorParameter::FrequencyFine
+                )
+        );
+        // LFO amount readouts depend on target parameter values
+        let lfo_amount_readouts_changed = match parameter {
+            Parameter::Lfo(_, p) => matches!(
+                p,
+                LfoParameter::Target
+                    | LfoParameter::Amount
+                    | LfoParameter::Polarity
+                    | LfoParameter::EnvelopeCurve
+            ),
+            _ => true,
+        };
+
         match parameter {
             Parameter::None => (),
             Parameter::Master(MasterParameter::Volume) => self.corner.master_volume.set_value(v),
@@ -196,6 +515,68 @@ impl<H: GuiSyncHandle> OctaSineIcedApplication<H> {
             Parameter::Master(MasterParameter::GlideRetrigger) => {
                 self.corner.glide_retrigger.set_value(v)
             }
+            Parameter::Master(MasterParameter::ChordMemory) => {
+                self.corner.chord_memory = v;
+            }
+            Parameter::Master(MasterParameter::MidiOut) => self.corner.midi_out.set_value(v),
+            Parameter::Master(MasterParameter::Scale) => self.corner.patch_picker.scale = v,
+            Parameter::Master(MasterParameter::LegatoEnvelopeMode) => {
+                self.corner.legato_envelope_mode = v;
+            }
+            Parameter::Master(MasterParameter::NotePriority) => {
+                self.corner.note_priority = v;
+            }
+            Parameter::Master(MasterParameter::MaxPolyphony) => {
+                self.corner.max_polyphony = v;
+            }
+            Parameter::Master(MasterParameter::AntiAlias) => self.corner.anti_alias.set_value(v),
+            Parameter::Master(MasterParameter::NoteRandomTarget) => {
+                self.corner.note_random_target.set_value(v)
+            }
+            Parameter::Master(MasterParameter::NoteRandomAmount) => {
+                self.corner.note_random_amount.set_value(v)
+            }
+            Parameter::Master(MasterParameter::VibratoRate) => {
+                self.corner.vibrato_rate.set_value(v)
+            }
+            Parameter::Master(MasterParameter::VibratoDepth) => {
+                self.corner.vibrato_depth.set_value(v)
+            }
+            Parameter::Master(MasterParameter::VibratoDelay) => {
+                self.corner.vibrato_delay.set_value(v)
+            }
+            Parameter::Master(MasterParameter::VibratoModWheel) => {
+                self.corner.vibrato_mod_wheel.set_value(v)
+            }
+            Parameter::Master(MasterParameter::PitchBendSmoothing) => {
+                self.corner.master_pitch_bend_smoothing.set_value(v)
+            }
+            Parameter::Master(MasterParameter::MpeZone) => {
+                self.corner.mpe_zone = v;
+            }
+            Parameter::Master(MasterParameter::MpeMemberPitchBendRange) => {
+                self.corner.mpe_member_pitch_bend_range.set_value(v)
+            }
+            Parameter::Master(MasterParameter::MpePressureTarget) => {
+                self.corner.mpe_pressure_target.set_value(v)
+            }
+            Parameter::Master(MasterParameter::MpePressureAmount) => {
+                self.corner.mpe_pressure_amount.set_value(v)
+            }
+            Parameter::Master(MasterParameter::MpeTimbreTarget) => {
+                self.corner.mpe_timbre_target.set_value(v)
+            }
+            Parameter::Master(MasterParameter::MpeTimbreAmount) => {
+                self.corner.mpe_timbre_amount.set_value(v)
+            }
+            Parameter::Master(MasterParameter::ModSnapshot) => {
+                self.corner.patch_picker.mod_snapshot = v;
+            }
+            // Patch changes are picked up through have_patches_changed
+            Parameter::Master(MasterParameter::PatchSelect) => (),
+            Parameter::Master(MasterParameter::ScaleRoot) => {
+                self.corner.patch_picker.scale_root = v
+            }
             outer_p @ Parameter::Operator(index, p) => {
                 self.operator_1.wave_display.set_value(outer_p, v);
                 self.operator_2.wave_display.set_value(outer_p, v);
@@ -231,6 +612,10 @@ impl<H: GuiSyncHandle> OctaSineIcedApplication<H> {
                             mod_index.set_value(v)
                         }
 
+                        operator
+                            .wave_type
+                            .set_modulation_index(OperatorModOutValue::new_from_patch(v).get());
+
                         match index {
                             1 => self.corner.modulation_matrix.set_operator_2_mod(v),
                             2 => self.corner.modulation_matrix.set_operator_3_mod(v),
@@ -252,7 +637,12 @@ impl<H: GuiSyncHandle> OctaSineIcedApplication<H> {
                             _ => (),
                         }
                     }
-                    OperatorParameter::Feedback => operator.feedback.set_value(v),
+                    OperatorParameter::Feedback => {
+                        operator.feedback.set_value(v);
+                        operator
+                            .wave_type
+                            .set_feedback(OperatorFeedbackValue::new_from_patch(v).get());
+                    }
                     OperatorParameter::FrequencyRatio => operator.frequency_ratio.set_value(v),
                     OperatorParameter::FrequencyFree => operator.frequency_free.set_value(v),
                     OperatorParameter::FrequencyFine => operator.frequency_fine.set_value(v),
@@ -287,15 +677,42 @@ impl<H: GuiSyncHandle> OctaSineIcedApplication<H> {
                     OperatorParameter::EnvelopeLockGroup => {
                         operator.envelope.set_group(v, internal);
 
-                        // Group buttons don't send message triggering update by themselves
+                        // Group picker doesn't send message triggering update by itself
                         self.update_envelope_group_statuses();
                     }
                     OperatorParameter::VelocitySensitivityModOut => {
-                        operator.mod_out_velocity_sensitivity.set_value(v)
+                        operator.mod_out_velocity_sensitivity.set_value(v);
+                        operator.velocity_curve.set_mod_out(v);
                     }
                     OperatorParameter::VelocitySensitivityFeedback => {
-                        operator.feedback_velocity_sensitivity.set_value(v)
+                        operator.feedback_velocity_sensitivity.set_value(v);
+                        operator.velocity_curve.set_feedback(v);
+                    }
+                    OperatorParameter::ReleaseMode => operator.release_mode = v,
+                    OperatorParameter::VelocitySensitivityPeak => {
+                        operator.peak_velocity_sensitivity.set_value(v);
+                        operator.velocity_curve.set_peak(v);
                     }
+                    OperatorParameter::VelocitySensitivitySustain => {
+                        operator.sustain_velocity_sensitivity.set_value(v);
+                        operator.velocity_curve.set_sustain(v);
+                    }
+                    OperatorParameter::WavetablePosition => {
+                        operator.wavetable_position.set_value(v)
+                    }
+                    OperatorParameter::PhaseDistortion => operator.phase_distortion.set_value(v),
+                    OperatorParameter::CrossFeedback => {
+                        if let Some(cross_feedback) = operator.cross_feedback.as_mut() {
+                            cross_feedback.set_value(v)
+                        }
+                    }
+                    OperatorParameter::CrossFeedbackTarget => operator.cross_feedback_target = v,
+                    OperatorParameter::KeyRangeLow => operator.key_range.set_low(v),
+                    OperatorParameter::KeyRangeHigh => operator.key_range.set_high(v),
+                    OperatorParameter::VelocityRangeLow => operator.velocity_range.set_low(v),
+                    OperatorParameter::VelocityRangeHigh => operator.velocity_range.set_high(v),
+                    OperatorParameter::NoiseStereo => operator.noise_stereo_button.set_value(v),
+                    OperatorParameter::Spread => operator.spread.set_value(v),
                 }
             }
             Parameter::Lfo(index, p) => {
@@ -317,12 +734,105 @@ impl<H: GuiSyncHandle> OctaSineIcedApplication<H> {
                     LfoParameter::Amount => lfo.amount.set_value(v),
                     LfoParameter::Active => lfo.active.set_value(v),
                     LfoParameter::KeySync => lfo.key_sync.set_value(v),
+                    LfoParameter::TransportSync => {
+                        lfo.transport_sync.set_value(v);
+                        lfo.transport_restart.set_value(v);
+                    }
+                    LfoParameter::Target2 => lfo.target_2.set_value(v),
+                    LfoParameter::Target2Depth => lfo.target_2_depth.set_value(v),
+                    LfoParameter::Target3 => lfo.target_3.set_value(v),
+                    LfoParameter::Target3Depth => lfo.target_3_depth.set_value(v),
+                    LfoParameter::Polarity => {
+                        lfo.polarity_up.set_value(v);
+                        lfo.polarity_down.set_value(v);
+                    }
+                    LfoParameter::VelocitySensitivity => lfo.velocity_sensitivity.set_value(v),
+                    LfoParameter::ModWheelSensitivity => lfo.mod_wheel_sensitivity.set_value(v),
+                    LfoParameter::EnvelopeCurve => lfo.envelope_curve.set_value(v),
+                    LfoParameter::EnvelopeAttack => lfo.envelope_attack.set_value(v),
+                    LfoParameter::KeySyncSpread => lfo.key_sync_spread.set_value(v),
+                    LfoParameter::Smoothing => lfo.smoothing.set_value(v),
                 }
             }
         }
+
+        if frequency_changed {
+            self.update_frequency_readouts();
+        }
+        if lfo_amount_readouts_changed {
+            self.update_lfo_amount_readouts();
+        }
+    }
+
+    fn update_lfo_amount_readouts(&mut self) {
+        for lfo in [
+            &mut self.lfo_1,
+            &mut self.lfo_2,
+            &mut self.lfo_3,
+            &mut self.lfo_4,
+        ] {
+            lfo.update_amount_readout(&self.sync_handle);
+        }
+    }
+
+    fn update_frequency_readouts(&mut self) {
+        let master_frequency =
+            MasterFrequencyValue::new_from_patch(self.corner.master_frequency.get_value()).get();
+
+        for operator in [
+            &mut self.operator_1,
+            &mut self.operator_2,
+            &mut self.operator_3,
+            &mut self.operator_4,
+        ] {
+            operator.update_frequency_readout(master_frequency);
+        }
+    }
+
+    fn update_wavetable_names(&mut self) {
+        for (operator_index, operator) in [
+            &mut self.operator_1,
+            &mut self.operator_2,
+            &mut self.operator_3,
+            &mut self.operator_4,
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            operator.wavetable_name = self.sync_handle.get_wavetable_name(operator_index);
+        }
+    }
+
+    fn update_modulation_input_peaks(&mut self) {
+        let peaks = self.sync_handle.take_modulation_input_peaks();
+
+        for (operator, peak) in [
+            &mut self.operator_1,
+            &mut self.operator_2,
+            &mut self.operator_3,
+            &mut self.operator_4,
+        ]
+        .into_iter()
+        .zip(peaks)
+        {
+            operator.update_modulation_input_peak(peak);
+        }
+    }
+
+    fn update_envelope_playheads(&mut self) {
+        for operator_index in 0..NUM_OPERATORS {
+            let opt_position = self.sync_handle.get_envelope_position(operator_index);
+
+            self.get_envelope_by_index(operator_index as u8)
+                .widget
+                .set_playhead(opt_position);
+        }
     }
 
-    fn update_widgets_from_parameters(&mut self) {
+    /// Update widgets with parameter values changed outside of GUI. If
+    /// highlight_automation is set, knobs for changed parameters are
+    /// briefly highlighted.
+    fn update_widgets_from_parameters(&mut self, highlight_automation: bool) {
         let opt_changes = self.sync_handle.get_changed_parameters();
 
         if let Some(changes) = opt_changes {
@@ -330,16 +840,154 @@ impl<H: GuiSyncHandle> OctaSineIcedApplication<H> {
                 if let Some(new_value) = opt_new_value {
                     if let Some(parameter) = Parameter::from_index(index) {
                         self.set_value(parameter, *new_value, false);
+
+                        if highlight_automation {
+                            self.mark_automated(parameter);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn mark_automated(&mut self, parameter: Parameter) {
+        match parameter {
+            Parameter::None => (),
+            Parameter::Master(p) => match p {
+                MasterParameter::Volume => self.corner.master_volume.mark_automated(),
+                MasterParameter::Frequency => self.corner.master_frequency.mark_automated(),
+                MasterParameter::VelocitySensitivityVolume => {
+                    self.corner.volume_velocity_sensitivity.mark_automated()
+                }
+                MasterParameter::GlideTime => self.corner.glide_time.mark_automated(),
+                _ => (),
+            },
+            Parameter::Operator(index, p) => {
+                let operator = match index {
+                    0 => &mut self.operator_1,
+                    1 => &mut self.operator_2,
+                    2 => &mut self.operator_3,
+                    3 => &mut self.operator_4,
+                    _ => panic!("No such operator"),
+                };
+
+                match p {
+                    OperatorParameter::Volume => operator.volume.mark_automated(),
+                    OperatorParameter::MixOut => operator.mix.mark_automated(),
+                    OperatorParameter::Panning => operator.panning.mark_automated(),
+                    OperatorParameter::ModOut => {
+                        if let Some(mod_index) = operator.mod_index.as_mut() {
+                            mod_index.mark_automated()
+                        }
+                    }
+                    OperatorParameter::Feedback => operator.feedback.mark_automated(),
+                    OperatorParameter::FrequencyRatio => operator.frequency_ratio.mark_automated(),
+                    OperatorParameter::FrequencyFree => operator.frequency_free.mark_automated(),
+                    OperatorParameter::FrequencyFine => operator.frequency_fine.mark_automated(),
+                    OperatorParameter::VelocitySensitivityModOut => {
+                        operator.mod_out_velocity_sensitivity.mark_automated()
+                    }
+                    OperatorParameter::VelocitySensitivityFeedback => {
+                        operator.feedback_velocity_sensitivity.mark_automated()
+                    }
+                    OperatorParameter::VelocitySensitivityPeak => {
+                        operator.peak_velocity_sensitivity.mark_automated()
+                    }
+                    OperatorParameter::VelocitySensitivitySustain => {
+                        operator.sustain_velocity_sensitivity.mark_automated()
                     }
+                    OperatorParameter::WavetablePosition => {
+                        operator.wavetable_position.mark_automated()
+                    }
+                    OperatorParameter::PhaseDistortion => {
+                        operator.phase_distortion.mark_automated()
+                    }
+                    OperatorParameter::Spread => operator.spread.mark_automated(),
+                    OperatorParameter::CrossFeedback => {
+                        if let Some(cross_feedback) = operator.cross_feedback.as_mut() {
+                            cross_feedback.mark_automated()
+                        }
+                    }
+                    _ => (),
+                }
+            }
+            Parameter::Lfo(index, p) => {
+                let lfo = match index {
+                    0 => &mut self.lfo_1,
+                    1 => &mut self.lfo_2,
+                    2 => &mut self.lfo_3,
+                    3 => &mut self.lfo_4,
+                    _ => panic!("No such LFO"),
+                };
+
+                match p {
+                    LfoParameter::FrequencyRatio => lfo.frequency_ratio.mark_automated(),
+                    LfoParameter::FrequencyFree => lfo.frequency_free.mark_automated(),
+                    LfoParameter::Amount => lfo.amount.mark_automated(),
+                    LfoParameter::Target2Depth => lfo.target_2_depth.mark_automated(),
+                    LfoParameter::Target3Depth => lfo.target_3_depth.mark_automated(),
+                    LfoParameter::VelocitySensitivity => lfo.velocity_sensitivity.mark_automated(),
+                    LfoParameter::ModWheelSensitivity => lfo.mod_wheel_sensitivity.mark_automated(),
+                    LfoParameter::EnvelopeAttack => lfo.envelope_attack.mark_automated(),
+                    LfoParameter::KeySyncSpread => lfo.key_sync_spread.mark_automated(),
+                    LfoParameter::Smoothing => lfo.smoothing.mark_automated(),
+                    _ => (),
                 }
             }
         }
     }
 
+    /// Store editor state with instance, so that host saves it in project
+    fn save_session_state(&self) {
+        self.sync_handle.set_session_state(SessionState {
+            operators_collapsed: [
+                self.operator_1.collapsed,
+                self.operator_2.collapsed,
+                self.operator_3.collapsed,
+                self.operator_4.collapsed,
+            ],
+            lfos_collapsed: self.lfos_collapsed,
+            performance_view: self.performance_view,
+            patch_category_filter: self.corner.patch_picker.category_filter,
+            patch_search_text: self.corner.patch_picker.search_text.as_str().into(),
+            last_directory: self.last_directory.clone(),
+        });
+    }
+
+    /// Remember directory of opened or saved file
+    fn set_last_directory(&mut self, path: &Path) {
+        if let Some(directory) = path.parent() {
+            self.last_directory = Some(directory.to_owned());
+
+            self.save_session_state();
+        }
+    }
+
     fn save_settings(&self) {
         let settings = Settings {
             schema_version: 1,
-            gui: GuiSettings { theme: self.theme },
+            gui: GuiSettings {
+                theme: self.theme,
+                density: self.density,
+                operator_semitone_frequency: [
+                    self.operator_1.semitone_frequency,
+                    self.operator_2.semitone_frequency,
+                    self.operator_3.semitone_frequency,
+                    self.operator_4.semitone_frequency,
+                ],
+                frequency_reference_note: self.operator_1.frequency_reference_note,
+                operators_collapsed: [
+                    self.operator_1.collapsed,
+                    self.operator_2.collapsed,
+                    self.operator_3.collapsed,
+                    self.operator_4.collapsed,
+                ],
+                lfos_collapsed: self.lfos_collapsed,
+                performance_knobs: self.performance.knob_parameter_keys(),
+                mutation_strength: self.mutation_strength,
+            },
+            // Not edited through GUI state, so keep stored values
+            ..Settings::load_or_default()
         };
 
         if let Err(err) = settings.save() {
@@ -347,6 +995,47 @@ impl<H: GuiSyncHandle> OctaSineIcedApplication<H> {
         }
     }
 
+    fn lfos_view(&self) -> Element<'_, Message, Theme> {
+        let spacing = self.opened_density.spacing();
+
+        if self.lfos_collapsed {
+            let expand_button = Button::new(
+                Text::new("SHOW LFOS")
+                    .font(self.theme.font_regular())
+                    .height(Length::Fixed(LINE_HEIGHT.into()))
+                    .horizontal_alignment(Horizontal::Center),
+            )
+            .padding(self.theme.button_padding())
+            .on_press(Message::ToggleLfosCollapsed);
+
+            return Container::new(expand_button)
+                .width(Length::Fill)
+                .height(Length::Fixed(f32::from(
+                    self.opened_density.panel_row_height() * 2 + spacing,
+                )))
+                .center_x()
+                .center_y()
+                .style(ContainerStyle::L1)
+                .into();
+        }
+
+        Row::new()
+            .push(
+                Column::new()
+                    .push(self.lfo_4.view(&self.theme, self.opened_density))
+                    .push(Space::with_height(Length::Fixed(spacing.into())))
+                    .push(self.lfo_3.view(&self.theme, self.opened_density)),
+            )
+            .push(Space::with_width(Length::Fixed(spacing.into())))
+            .push(
+                Column::new()
+                    .push(self.lfo_2.view(&self.theme, self.opened_density))
+                    .push(Space::with_height(Length::Fixed(spacing.into())))
+                    .push(self.lfo_1.view(&self.theme, self.opened_density)),
+            )
+            .into()
+    }
+
     fn get_envelope_by_index(&mut self, operator_index: u8) -> &mut envelope::Envelope {
         match operator_index {
             0 => &mut self.operator_1.envelope,
@@ -463,6 +1152,22 @@ impl<H: GuiSyncHandle> OctaSineIcedApplication<H> {
     }
 }
 
+impl<H: GuiSyncHandle> Drop for OctaSineIcedApplication<H> {
+    fn drop(&mut self) {
+        // Don't leave on-screen keyboard note hanging when editor is closed
+        if let Some(key) = self.performance.keyboard_key.take() {
+            self.sync_handle.set_gui_key(key, false);
+        }
+
+        if let Some(ModalAction::ImportPatches {
+            previewing: true, ..
+        }) = self.modal_action
+        {
+            self.sync_handle.end_patch_preview();
+        }
+    }
+}
+
 impl<H: GuiSyncHandle> Application for OctaSineIcedApplication<H> {
     type Executor = executor::Default;
     type Message = Message;
@@ -470,23 +1175,58 @@ impl<H: GuiSyncHandle> Application for OctaSineIcedApplication<H> {
     type Theme = Theme;
 
     fn new(sync_handle: Self::Flags) -> (Self, Command<Self::Message>) {
-        let style = sync_handle.get_gui_settings().theme;
-
-        let operator_1 = OperatorWidgets::new(&sync_handle, 0);
-        let operator_2 = OperatorWidgets::new(&sync_handle, 1);
-        let operator_3 = OperatorWidgets::new(&sync_handle, 2);
-        let operator_4 = OperatorWidgets::new(&sync_handle, 3);
+        let gui_settings = sync_handle.get_gui_settings();
+        // Global settings are used for instances without stored state
+        let session = sync_handle
+            .get_session_state()
+            .unwrap_or_else(|| SessionState {
+                operators_collapsed: gui_settings.operators_collapsed,
+                lfos_collapsed: gui_settings.lfos_collapsed,
+                ..Default::default()
+            });
+
+        let mut operator_1 = OperatorWidgets::new(&sync_handle, 0);
+        let mut operator_2 = OperatorWidgets::new(&sync_handle, 1);
+        let mut operator_3 = OperatorWidgets::new(&sync_handle, 2);
+        let mut operator_4 = OperatorWidgets::new(&sync_handle, 3);
+
+        for ((operator, semitone_frequency), collapsed) in [
+            &mut operator_1,
+            &mut operator_2,
+            &mut operator_3,
+            &mut operator_4,
+        ]
+        .into_iter()
+        .zip(gui_settings.operator_semitone_frequency)
+        .zip(session.operators_collapsed)
+        {
+            operator.set_semitone_frequency(semitone_frequency);
+            operator.frequency_reference_note = gui_settings.frequency_reference_note;
+            operator.collapsed = collapsed;
+        }
 
         let lfo_1 = LfoWidgets::new(&sync_handle, 0);
         let lfo_2 = LfoWidgets::new(&sync_handle, 1);
         let lfo_3 = LfoWidgets::new(&sync_handle, 2);
         let lfo_4 = LfoWidgets::new(&sync_handle, 3);
 
-        let corner = CornerWidgets::new(&sync_handle);
+        let mut corner = CornerWidgets::new(&sync_handle);
 
-        let app = Self {
+        corner
+            .patch_picker
+            .set_category_filter(session.patch_category_filter);
+        corner
+            .patch_picker
+            .set_search_text(session.patch_search_text.to_string());
+
+        let performance = PerformanceWidgets::new(&sync_handle, gui_settings.performance_knobs);
+        let bank_overview = BankOverview::new(&sync_handle);
+
+        let mut app = Self {
             sync_handle,
-            theme: style,
+            theme: gui_settings.theme,
+            density: gui_settings.density,
+            opened_density: gui_settings.density,
             operator_1,
             operator_2,
             operator_3,
@@ -495,10 +1235,19 @@ impl<H: GuiSyncHandle> Application for OctaSineIcedApplication<H> {
             lfo_2,
             lfo_3,
             lfo_4,
+            lfos_collapsed: session.lfos_collapsed,
             corner,
+            performance,
+            performance_view: session.performance_view,
+            bank_overview,
+            last_directory: session.last_directory,
+            mutation_strength: gui_settings.mutation_strength,
             modal_action: None,
         };
 
+        app.update_frequency_readouts();
+        app.update_lfo_amount_readouts();
+
         (app, Command::none())
     }
 
@@ -537,15 +1286,43 @@ impl<H: GuiSyncHandle> Application for OctaSineIcedApplication<H> {
 
     fn update(
         &mut self,
-        _window_queue: &mut WindowQueue,
+        window_queue: &mut WindowQueue,
         message: Self::Message,
     ) -> Command<Self::Message> {
         match message {
             Message::Frame => {
-                if self.sync_handle.have_patches_changed() {
+                // Parameter changes caused by switching patch are not
+                // automation
+                let patches_changed = self.sync_handle.have_patches_changed();
+
+                if patches_changed {
+                    let category_filter = self.corner.patch_picker.category_filter;
+                    let search_text = ::std::mem::take(&mut self.corner.patch_picker.search_text);
+
                     self.corner.patch_picker = PatchPicker::new(&self.sync_handle);
+                    self.corner
+                        .patch_picker
+                        .set_category_filter(category_filter);
+                    self.corner.patch_picker.set_search_text(search_text);
+                    self.bank_overview = BankOverview::new(&self.sync_handle);
+                    self.update_wavetable_names();
+                }
+                self.update_widgets_from_parameters(!patches_changed);
+                self.update_modulation_input_peaks();
+                self.update_envelope_playheads();
+
+                if let Some(data) = self.sync_handle.take_last_midi_event() {
+                    self.corner.set_last_midi_event(data);
+                }
+                if self.sync_handle.take_clipped() {
+                    self.corner.clipped = true;
+                }
+
+                if self.performance_view {
+                    self.performance
+                        .keyboard
+                        .set_held_keys(self.sync_handle.get_held_keys());
                 }
-                self.update_widgets_from_parameters();
             }
             Message::NoOp => {}
             Message::EnvelopeChangeViewport {
@@ -569,6 +1346,28 @@ impl<H: GuiSyncHandle> Application for OctaSineIcedApplication<H> {
                         .set_viewport(viewport_factor, x_offset);
                 }
             }
+            Message::EnvelopeZoomToFitAll => {
+                let longest_duration = (0..NUM_OPERATORS)
+                    .map(|i| {
+                        self.get_envelope_by_index(i as u8)
+                            .widget
+                            .get_current_duration()
+                    })
+                    .fold(0.0, f32::max);
+
+                let (viewport_factor, x_offset) =
+                    envelope::canvas::EnvelopeCanvas::get_zoom_to_fit_data_for_duration(
+                        longest_duration,
+                    );
+
+                return self.update(
+                    window_queue,
+                    Message::EnvelopeDistributeViewports {
+                        viewport_factor,
+                        x_offset,
+                    },
+                );
+            }
             Message::ChangeSingleParameterBegin(parameter) => {
                 self.sync_handle.begin_edit(parameter);
             }
@@ -585,6 +1384,16 @@ impl<H: GuiSyncHandle> Application for OctaSineIcedApplication<H> {
 
                 self.sync_handle.set_parameter_immediate(parameter, value);
             }
+            Message::LearnChordMemory => {
+                let parameter = Parameter::Master(MasterParameter::ChordMemory);
+                let chord = ChordMemory::from_held_keys(self.sync_handle.get_held_keys());
+                let value = ChordMemoryValue::new_from_audio(chord).to_patch();
+
+                self.set_value(parameter, value, true);
+
+                self.sync_handle
+                    .set_parameter_immediate(parameter.into(), value);
+            }
             Message::ChangeEnvelopeParametersEnd {
                 operator_index,
                 parameter_1,
@@ -623,6 +1432,33 @@ impl<H: GuiSyncHandle> Application for OctaSineIcedApplication<H> {
             }
             Message::ChangePatch(index) => {
                 self.sync_handle.set_patch_index(index);
+
+                let audition = Settings::load_or_default().patch_audition;
+
+                if audition.enabled {
+                    self.sync_handle.request_audition(
+                        audition.key,
+                        audition.velocity,
+                        audition.duration,
+                    );
+                }
+            }
+            Message::ChangePatchCategoryFilter(category_filter) => {
+                self.corner
+                    .patch_picker
+                    .set_category_filter(category_filter);
+
+                self.save_session_state();
+            }
+            Message::ChangePatchSearchText(search_text) => {
+                self.corner.patch_picker.set_search_text(search_text);
+
+                self.save_session_state();
+            }
+            Message::SelectFirstPatchSearchMatch => {
+                if let Some(index) = self.corner.patch_picker.first_match() {
+                    return self.update(window_queue, Message::ChangePatch(index));
+                }
             }
             Message::SwitchTheme => {
                 let style = if let Theme::Light = self.theme {
@@ -641,170 +1477,343 @@ impl<H: GuiSyncHandle> Application for OctaSineIcedApplication<H> {
                 self.operator_2.theme_changed();
                 self.operator_3.theme_changed();
                 self.operator_4.theme_changed();
+                self.performance.theme_changed();
+                self.bank_overview.theme_changed();
 
                 self.save_settings();
             }
-            Message::ToggleAlternativeControls => {
+            Message::ToggleLfosDisabled => {
+                let disabled = !self.sync_handle.get_lfos_disabled();
+
+                self.sync_handle.set_lfos_disabled(disabled);
+                self.corner.patch_picker.lfos_disabled = disabled;
+            }
+            Message::ChangeNoteModulationSource(source) => {
+                self.corner.note_modulation_source = source;
+            }
+            Message::ToggleAutoGain => {
+                self.sync_handle
+                    .set_auto_gain(!self.sync_handle.get_auto_gain());
+            }
+            Message::ToggleMonoFoldDown => {
+                self.sync_handle
+                    .set_mono_fold_down(!self.sync_handle.get_mono_fold_down());
+            }
+            Message::ToggleOperatorListen(index) => {
+                let opt_listen_operator = if self.sync_handle.get_listen_operator() == Some(index) {
+                    None
+                } else {
+                    Some(index)
+                };
+
+                self.sync_handle.set_listen_operator(opt_listen_operator);
+
+                for (operator_index, operator) in [
+                    &mut self.operator_1,
+                    &mut self.operator_2,
+                    &mut self.operator_3,
+                    &mut self.operator_4,
+                ]
+                .into_iter()
+                .enumerate()
+                {
+                    operator.listening = opt_listen_operator == Some(operator_index);
+                }
+            }
+            Message::ToggleOperatorSemitoneFrequency(index) => {
+                let operator = match index {
+                    0 => &mut self.operator_1,
+                    1 => &mut self.operator_2,
+                    2 => &mut self.operator_3,
+                    3 => &mut self.operator_4,
+                    _ => unreachable!(),
+                };
+
+                operator.set_semitone_frequency(!operator.semitone_frequency);
+
+                self.save_settings();
+            }
+            Message::SetFrequencyReferenceNote(note) => {
                 for operator in [
                     &mut self.operator_1,
                     &mut self.operator_2,
                     &mut self.operator_3,
                     &mut self.operator_4,
                 ] {
-                    operator.alternative_controls = !operator.alternative_controls;
+                    operator.frequency_reference_note = note;
                 }
 
-                self.corner.alternative_controls = !self.corner.alternative_controls;
+                self.update_frequency_readouts();
+                self.save_settings();
             }
-            Message::LoadBankOrPatch => {
-                const TITLE: &str = "Load OctaSine patch bank or patches";
+            Message::SetDensity(density) => {
+                self.density = density;
 
-                return Command::single(Action::Future(Box::pin(async move {
-                    cfg_if!(
-                        if #[cfg(target_os = "macos")] {
-                            let mut builder = rfd::AsyncFileDialog::new()
-                                .set_title(TITLE)
-                                .add_filter("Patch", &["fxp"])
-                                .add_filter("Patch bank", &["fxb"]);
+                self.save_settings();
+            }
+            Message::ToggleOperatorCollapsed(index) => {
+                let operator = match index {
+                    0 => &mut self.operator_1,
+                    1 => &mut self.operator_2,
+                    2 => &mut self.operator_3,
+                    3 => &mut self.operator_4,
+                    _ => unreachable!(),
+                };
 
-                            if let Some(h) = CurrentWindowHandle::get() {
-                                builder = builder.set_parent(&h);
-                            }
+                operator.collapsed = !operator.collapsed;
 
-                            let opt_paths = builder
-                                .pick_files()
-                                .await
-                                .map(|handles|
-                                    handles.into_iter()
-                                        .map(|h| h.path().to_owned())
-                                        .collect::<Vec<PathBuf>>()
-                                );
-                        } else if #[cfg(target_os = "windows")] {
-                            let opt_paths = rfd::AsyncFileDialog::new()
-                                .set_title(TITLE)
-                                .add_filter("Patch", &["fxp"])
-                                .add_filter("Patch bank", &["fxb"])
-                                .pick_files()
-                                .await
-                                .map(|handles|
-                                    handles.into_iter()
-                                        .map(|h| h.path().to_owned())
-                                        .collect::<Vec<PathBuf>>()
-                                );
-                        } else {
-                            let opt_paths = tinyfiledialogs::open_file_dialog_multi(
-                                TITLE,
-                                "",
-                                Some((&["*.fxp", "*.fxb"], "Patch bank or patch files"))
-                            ).map(|strings|
-                                strings.into_iter()
-                                    .map(|s| s.into())
-                                    .collect::<Vec<PathBuf>>()
-                            );
-                        }
-                    );
+                self.save_settings();
+                self.save_session_state();
+            }
+            Message::ToggleLfosCollapsed => {
+                self.lfos_collapsed = !self.lfos_collapsed;
 
-                    if let Some(paths) = opt_paths {
-                        Message::LoadBankOrPatchesFromPaths(paths)
-                    } else {
-                        Message::NoOp
-                    }
-                })));
+                self.save_settings();
+                self.save_session_state();
+            }
+            Message::TogglePerformanceView => {
+                self.performance_view = !self.performance_view;
+
+                self.save_session_state();
+            }
+            Message::SetPerformanceKnobParameter(knob_index, parameter) => {
+                self.performance
+                    .set_knob_parameter(&self.sync_handle, knob_index, parameter);
+
+                self.save_settings();
+            }
+            Message::SetGuiKeyboardKey(opt_key) => {
+                if let Some(key) = self.performance.keyboard_key {
+                    self.sync_handle.set_gui_key(key, false);
+                }
+                if let Some(key) = opt_key {
+                    self.sync_handle.set_gui_key(key, true);
+                }
+
+                self.performance.keyboard_key = opt_key;
+            }
+            Message::ShiftGuiKeyboardOctave(octaves) => {
+                self.performance.shift_keyboard_octave(octaves);
+            }
+            Message::ToggleAlternativeControls => {
+                for operator in [
+                    &mut self.operator_1,
+                    &mut self.operator_2,
+                    &mut self.operator_3,
+                    &mut self.operator_4,
+                ] {
+                    operator.alternative_controls = !operator.alternative_controls;
+                }
+
+                for lfo in [
+                    &mut self.lfo_1,
+                    &mut self.lfo_2,
+                    &mut self.lfo_3,
+                    &mut self.lfo_4,
+                ] {
+                    lfo.alternative_controls = !lfo.alternative_controls;
+                }
+
+                self.corner.alternative_controls = !self.corner.alternative_controls;
+            }
+            Message::LoadBankOrPatch => {
+                if self.sync_handle.have_unsaved_changes() {
+                    self.modal_action = Some(ModalAction::LoadWithUnsavedChanges);
+                } else {
+                    return load_bank_or_patch(self.last_directory.clone());
+                }
             }
             Message::SavePatch => {
-                const TITLE: &str = "Save OctaSine patch";
+                let (patch_filename, patch_bytes) = self.sync_handle.export_patch();
 
+                return save_patch(
+                    patch_filename,
+                    patch_bytes,
+                    self.last_directory.clone(),
+                    false,
+                );
+            }
+            Message::SavePatchWithPreview => {
                 let (patch_filename, patch_bytes) = self.sync_handle.export_patch();
 
+                return save_patch(
+                    patch_filename,
+                    patch_bytes,
+                    self.last_directory.clone(),
+                    true,
+                );
+            }
+            Message::SaveBank => {
+                const TITLE: &str = "Save OctaSine bank";
+                const FILENAME: &str = "OctaSine bank.fxb";
+
+                // Might be triggered from unsaved changes modal
+                self.modal_action = None;
+
+                let bank_bytes = self.sync_handle.export_bank();
+                let opt_directory = self.last_directory.clone();
+
                 return Command::single(Action::Future(Box::pin(async move {
                     cfg_if!(
                         if #[cfg(target_os = "macos")] {
                             let mut builder = rfd::AsyncFileDialog::new()
                                 .set_title(TITLE)
-                                .add_filter("Patch", &["fxp"])
-                                .set_file_name(&*patch_filename);
+                                .add_filter("Patch bank", &["fxb"])
+                                .set_file_name(FILENAME);
 
                             if let Some(h) = CurrentWindowHandle::get() {
                                 builder = builder.set_parent(&h);
                             }
+                            if let Some(directory) = opt_directory {
+                                builder = builder.set_directory(directory);
+                            }
 
                             let opt_path_buf = builder
                                 .save_file()
                                 .await
                                 .map(|handle| handle.path().to_owned());
-                        }
-                        else if #[cfg(target_os = "windows")] {
-                            let opt_path_buf = rfd::AsyncFileDialog::new()
+                        } else if #[cfg(target_os = "windows")] {
+                            let mut builder = rfd::AsyncFileDialog::new()
                                 .set_title(TITLE)
-                                .add_filter("Patch", &["fxp"])
-                                .set_file_name(&*patch_filename)
+                                .add_filter("Patch bank", &["fxb"])
+                                .set_file_name(FILENAME);
+
+                            if let Some(directory) = opt_directory {
+                                builder = builder.set_directory(directory);
+                            }
+
+                            let opt_path_buf = builder
                                 .save_file()
                                 .await
                                 .map(|handle| handle.path().to_owned());
-                        } else {
+                        } else  {
+                            let default_path = match opt_directory {
+                                Some(directory) => {
+                                    directory.join(FILENAME).to_string_lossy().into_owned()
+                                }
+                                None => FILENAME.to_string(),
+                            };
+
                             let opt_path_buf = tinyfiledialogs::save_file_dialog_with_filter(
                                 TITLE,
-                                &patch_filename,
-                                &["*.fxp"],
-                                "Patch"
+                                &default_path,
+                                &["*.fxb"],
+                                ""
                             ).map(|s| s.into());
                         }
                     );
 
                     if let Some(path_buf) = opt_path_buf {
-                        Message::SaveBankOrPatchToFile(path_buf, patch_bytes)
+                        Message::SaveBankOrPatchToFile(path_buf, bank_bytes)
                     } else {
                         Message::NoOp
                     }
                 })));
             }
-            Message::SaveBank => {
-                const TITLE: &str = "Save OctaSine bank";
-                const FILENAME: &str = "OctaSine bank.fxb";
+            Message::ExportPatchSheet => {
+                const TITLE: &str = "Save OctaSine patch sheet";
 
-                let bank_bytes = self.sync_handle.export_bank();
+                let (patch_filename, patch_bytes) = self.sync_handle.export_patch();
+
+                let png_bytes = match patch_sheet::render_patch_sheet_png(&patch_bytes, self.theme)
+                {
+                    Ok(png_bytes) => png_bytes,
+                    Err(err) => {
+                        ::log::error!("Error rendering patch sheet: {:#}", err);
+
+                        return Command::none();
+                    }
+                };
+
+                let filename = Path::new(patch_filename.as_str())
+                    .with_extension("png")
+                    .to_string_lossy()
+                    .into_owned();
+                let opt_directory = self.last_directory.clone();
 
                 return Command::single(Action::Future(Box::pin(async move {
                     cfg_if!(
                         if #[cfg(target_os = "macos")] {
                             let mut builder = rfd::AsyncFileDialog::new()
                                 .set_title(TITLE)
-                                .add_filter("Patch bank", &["fxb"])
-                                .set_file_name(FILENAME);
+                                .add_filter("PNG image", &["png"])
+                                .set_file_name(&filename);
 
                             if let Some(h) = CurrentWindowHandle::get() {
                                 builder = builder.set_parent(&h);
                             }
+                            if let Some(directory) = opt_directory {
+                                builder = builder.set_directory(directory);
+                            }
 
                             let opt_path_buf = builder
                                 .save_file()
                                 .await
                                 .map(|handle| handle.path().to_owned());
                         } else if #[cfg(target_os = "windows")] {
-                            let opt_path_buf = rfd::AsyncFileDialog::new()
+                            let mut builder = rfd::AsyncFileDialog::new()
                                 .set_title(TITLE)
-                                .add_filter("Patch bank", &["fxb"])
-                                .set_file_name(FILENAME)
+                                .add_filter("PNG image", &["png"])
+                                .set_file_name(&filename);
+
+                            if let Some(directory) = opt_directory {
+                                builder = builder.set_directory(directory);
+                            }
+
+                            let opt_path_buf = builder
                                 .save_file()
                                 .await
                                 .map(|handle| handle.path().to_owned());
                         } else  {
+                            let default_path = match opt_directory {
+                                Some(directory) => {
+                                    directory.join(&filename).to_string_lossy().into_owned()
+                                }
+                                None => filename,
+                            };
+
                             let opt_path_buf = tinyfiledialogs::save_file_dialog_with_filter(
                                 TITLE,
-                                FILENAME,
-                                &["*.fxb"],
-                                ""
+                                &default_path,
+                                &["*.png"],
+                                "PNG image"
                             ).map(|s| s.into());
                         }
                     );
 
                     if let Some(path_buf) = opt_path_buf {
-                        Message::SaveBankOrPatchToFile(path_buf, bank_bytes)
+                        Message::SaveBankOrPatchToFile(path_buf, png_bytes)
                     } else {
                         Message::NoOp
                     }
                 })));
             }
+            Message::SetPatchAuthor => {
+                let mut metadata = self.sync_handle.get_current_patch_metadata();
+
+                if let Some(author) = tinyfiledialogs::input_box(
+                    "Change OctaSine patch author",
+                    "Please provide the author of this patch",
+                    &metadata.author,
+                ) {
+                    metadata.author = author.into();
+
+                    self.sync_handle.set_current_patch_metadata(metadata);
+                }
+            }
+            Message::SetPatchLicense => {
+                let mut metadata = self.sync_handle.get_current_patch_metadata();
+
+                if let Some(license) = tinyfiledialogs::input_box(
+                    "Change OctaSine patch license",
+                    "Please provide the license of this patch (e.g., CC-BY-4.0)",
+                    &metadata.license,
+                ) {
+                    metadata.license = license.into();
+
+                    self.sync_handle.set_current_patch_metadata(metadata);
+                }
+            }
             Message::RenamePatch => {
                 if let Some(name) = tinyfiledialogs::input_box(
                     "Change OctaSine patch name",
@@ -814,23 +1823,305 @@ impl<H: GuiSyncHandle> Application for OctaSineIcedApplication<H> {
                     self.sync_handle.set_current_patch_name(&name);
                 }
             }
+            Message::StoreModSnapshot => {
+                let slot =
+                    ModSnapshotSlotValue::new_from_patch(self.corner.patch_picker.mod_snapshot)
+                        .get();
+
+                if let Some(slot_index) = slot.index() {
+                    self.sync_handle.store_mod_snapshot(slot_index);
+                }
+            }
             Message::ClearPatch => {
                 self.modal_action = Some(ModalAction::ClearPatch);
             }
             Message::ClearBank => {
                 self.modal_action = Some(ModalAction::ClearBank);
             }
+            Message::RandomizeParameters(scope) => {
+                self.sync_handle.randomize_parameters(scope);
+            }
+            Message::MutatePatch => {
+                self.sync_handle
+                    .mutate_parameters(RandomizeScope::Patch, self.mutation_strength.fraction());
+            }
+            Message::SetMutationStrength(strength) => {
+                self.mutation_strength = strength;
+
+                self.save_settings();
+            }
+            Message::BreedPatches => {
+                let (patch_index, patch_names) = self.sync_handle.get_patches();
+
+                self.modal_action = Some(ModalAction::BreedPatches {
+                    patch_names,
+                    parents: [patch_index, (patch_index + 1) % NUM_PATCHES],
+                    num_children: 4,
+                    mutate: false,
+                });
+            }
+            Message::ToggleParameterLock(parameter) => {
+                // Knobs read lock state when drawn
+                self.sync_handle
+                    .get_parameter_locks()
+                    .toggle(parameter.index() as usize);
+            }
+            Message::SavePatchAndPreviewToFile(path_buf, patch_bytes) => {
+                let preview_path = path_buf.with_extension("wav");
+                let preview_bytes = crate::synth::render_patch_preview_wav(&patch_bytes);
+
+                if let Err(err) = save_data_to_file(preview_path, preview_bytes) {
+                    ::log::error!("Error saving patch audio preview to file: {:#}", err)
+                }
+
+                return self.update(
+                    window_queue,
+                    Message::SaveBankOrPatchToFile(path_buf, patch_bytes),
+                );
+            }
             Message::SaveBankOrPatchToFile(path_buf, bytes) => {
-                if let Err(err) = save_data_to_file(path_buf, bytes) {
-                    ::log::error!("Error saving patch/patch bank to file: {:#}", err)
+                self.set_last_directory(&path_buf);
+
+                let is_bank = path_buf.extension().and_then(|s| s.to_str()) == Some("fxb");
+
+                match save_data_to_file(path_buf, bytes) {
+                    Ok(()) if is_bank => self.sync_handle.mark_bank_as_saved(),
+                    Ok(()) => (),
+                    Err(err) => {
+                        ::log::error!("Error saving patch/patch bank to file: {:#}", err)
+                    }
                 }
             }
+            Message::StartParameterChangeCapture => {
+                self.sync_handle.start_parameter_change_capture();
+            }
+            Message::ExportParameterChangeCapture => {
+                const TITLE: &str = "Save OctaSine parameter change capture";
+                const FILENAME: &str = "OctaSine parameter changes.csv";
+
+                let log = match self.sync_handle.stop_parameter_change_capture() {
+                    Some(log) => log,
+                    None => {
+                        ::log::warn!("No parameter change capture to export");
+
+                        return Command::none();
+                    }
+                };
+
+                return Command::single(Action::Future(Box::pin(async move {
+                    cfg_if!(
+                        if #[cfg(target_os = "macos")] {
+                            let mut builder = rfd::AsyncFileDialog::new()
+                                .set_title(TITLE)
+                                .add_filter("Parameter change capture", &["csv", "json"])
+                                .set_file_name(FILENAME);
+
+                            if let Some(h) = CurrentWindowHandle::get() {
+                                builder = builder.set_parent(&h);
+                            }
+
+                            let opt_path_buf = builder
+                                .save_file()
+                                .await
+                                .map(|handle| handle.path().to_owned());
+                        } else if #[cfg(target_os = "windows")] {
+                            let opt_path_buf = rfd::AsyncFileDialog::new()
+                                .set_title(TITLE)
+                                .add_filter("Parameter change capture", &["csv", "json"])
+                                .set_file_name(FILENAME)
+                                .save_file()
+                                .await
+                                .map(|handle| handle.path().to_owned());
+                        } else  {
+                            let opt_path_buf = tinyfiledialogs::save_file_dialog_with_filter(
+                                TITLE,
+                                FILENAME,
+                                &["*.csv", "*.json"],
+                                ""
+                            ).map(|s| s.into());
+                        }
+                    );
+
+                    let path_buf: PathBuf = match opt_path_buf {
+                        Some(path_buf) => path_buf,
+                        None => return Message::NoOp,
+                    };
+
+                    let is_json = path_buf.extension().and_then(|s| s.to_str()) == Some("json");
+
+                    let bytes = if is_json {
+                        match log.to_json() {
+                            Ok(json) => json.into_bytes(),
+                            Err(err) => {
+                                ::log::error!("Error serializing parameter changes: {:#}", err);
+
+                                return Message::NoOp;
+                            }
+                        }
+                    } else {
+                        log.to_csv().into_bytes()
+                    };
+
+                    Message::SaveBankOrPatchToFile(path_buf, bytes)
+                })));
+            }
             Message::LoadBankOrPatchesFromPaths(paths) => {
-                self.sync_handle.import_bank_or_patches_from_paths(&paths);
+                if let Some(path) = paths.first() {
+                    self.set_last_directory(path);
+                }
+
+                let contains_bank = paths
+                    .iter()
+                    .any(|path| path.extension().and_then(|s| s.to_str()) == Some("fxb"));
+
+                if contains_bank {
+                    // Bank replaces all patches, so there is no target to choose
+                    self.sync_handle
+                        .import_bank_or_patches_from_paths(&paths, PatchImportTarget::CurrentSlot);
+                } else if !paths.is_empty() {
+                    self.modal_action = Some(ModalAction::ImportPatches {
+                        paths,
+                        target: PatchImportTarget::CurrentSlot,
+                        previewing: false,
+                    });
+                }
+            }
+            Message::PickStartupPatches => {
+                const TITLE: &str = "Choose OctaSine patch bank or patch to load at startup";
+
+                return Command::single(Action::Future(Box::pin(async move {
+                    cfg_if!(
+                        if #[cfg(target_os = "macos")] {
+                            let mut builder = rfd::AsyncFileDialog::new()
+                                .set_title(TITLE)
+                                .add_filter("Patch bank", &["fxb"])
+                                .add_filter("Patch", &["fxp"]);
+
+                            if let Some(h) = CurrentWindowHandle::get() {
+                                builder = builder.set_parent(&h);
+                            }
+
+                            let opt_path_buf = builder
+                                .pick_file()
+                                .await
+                                .map(|handle| handle.path().to_owned());
+                        } else if #[cfg(target_os = "windows")] {
+                            let opt_path_buf = rfd::AsyncFileDialog::new()
+                                .set_title(TITLE)
+                                .add_filter("Patch bank", &["fxb"])
+                                .add_filter("Patch", &["fxp"])
+                                .pick_file()
+                                .await
+                                .map(|handle| handle.path().to_owned());
+                        } else {
+                            let opt_path_buf = tinyfiledialogs::open_file_dialog(
+                                TITLE,
+                                "",
+                                Some((&["*.fxb", "*.fxp"], "Patch bank or patch files"))
+                            ).map(|s| s.into());
+                        }
+                    );
+
+                    if let Some(path_buf) = opt_path_buf {
+                        Message::SetStartupPatches(Some(path_buf))
+                    } else {
+                        Message::NoOp
+                    }
+                })));
+            }
+            Message::SetStartupPatches(opt_path_buf) => {
+                let mut settings = Settings::load_or_default();
+
+                settings.startup_patches = opt_path_buf;
+
+                if let Err(err) = settings.save() {
+                    ::log::error!("Couldn't save settings: {:#}", err)
+                }
+            }
+            Message::TogglePatchAudition => {
+                let mut settings = Settings::load_or_default();
+
+                settings.patch_audition.enabled = !settings.patch_audition.enabled;
+
+                if let Err(err) = settings.save() {
+                    ::log::error!("Couldn't save settings: {:#}", err)
+                }
+            }
+            Message::Panic => {
+                self.sync_handle.request_panic();
+            }
+            Message::ResetClipIndicator => {
+                self.corner.clipped = false;
+            }
+            Message::ToggleStuckNoteWatchdog => {
+                let mut settings = Settings::load_or_default();
+
+                settings.stuck_note_watchdog.enabled = !settings.stuck_note_watchdog.enabled;
+
+                self.sync_handle
+                    .set_stuck_note_timeout(settings.stuck_note_watchdog.get_timeout());
+
+                if let Err(err) = settings.save() {
+                    ::log::error!("Couldn't save settings: {:#}", err)
+                }
+            }
+            Message::LoadWavetable(operator_index) => {
+                const TITLE: &str = "Load wavetable";
+
+                return Command::single(Action::Future(Box::pin(async move {
+                    cfg_if!(
+                        if #[cfg(target_os = "macos")] {
+                            let mut builder = rfd::AsyncFileDialog::new()
+                                .set_title(TITLE)
+                                .add_filter("Wavetable", &["wav"]);
+
+                            if let Some(h) = CurrentWindowHandle::get() {
+                                builder = builder.set_parent(&h);
+                            }
+
+                            let opt_path_buf = builder
+                                .pick_file()
+                                .await
+                                .map(|handle| handle.path().to_owned());
+                        } else if #[cfg(target_os = "windows")] {
+                            let opt_path_buf = rfd::AsyncFileDialog::new()
+                                .set_title(TITLE)
+                                .add_filter("Wavetable", &["wav"])
+                                .pick_file()
+                                .await
+                                .map(|handle| handle.path().to_owned());
+                        } else {
+                            let opt_path_buf = tinyfiledialogs::open_file_dialog(
+                                TITLE,
+                                "",
+                                Some((&["*.wav"], "Wavetable WAV files"))
+                            ).map(|s| s.into());
+                        }
+                    );
+
+                    if let Some(path_buf) = opt_path_buf {
+                        Message::LoadWavetableFromPath(operator_index, path_buf)
+                    } else {
+                        Message::NoOp
+                    }
+                })));
+            }
+            Message::LoadWavetableFromPath(operator_index, path_buf) => {
+                match Wavetable::from_path(&path_buf) {
+                    Ok(wavetable) => {
+                        self.sync_handle
+                            .set_wavetable(operator_index, Some(Arc::new(wavetable)));
+                    }
+                    Err(err) => ::log::error!("Error loading wavetable: {:#}", err),
+                }
+            }
+            Message::ClearWavetable(operator_index) => {
+                self.sync_handle.set_wavetable(operator_index, None);
             }
             Message::ChangeParameterByTextInput {
                 parameter,
                 value_text,
+                parse,
             } => {
                 if let Some(new_text_value) = tinyfiledialogs::input_box(
                     "Change OctaSine parameter value",
@@ -840,10 +2131,14 @@ impl<H: GuiSyncHandle> Application for OctaSineIcedApplication<H> {
                     ),
                     &value_text,
                 ) {
-                    if let Some(value_patch) = self
-                        .sync_handle
-                        .parse_parameter_from_text(parameter, &new_text_value)
-                    {
+                    let opt_value_patch = match parse {
+                        Some(parse) => parse(&new_text_value),
+                        None => self
+                            .sync_handle
+                            .parse_parameter_from_text(parameter, &new_text_value),
+                    };
+
+                    if let Some(value_patch) = opt_value_patch {
                         self.sync_handle
                             .set_parameter_immediate(parameter, value_patch);
                         self.set_value(parameter.parameter(), value_patch, true);
@@ -851,10 +2146,20 @@ impl<H: GuiSyncHandle> Application for OctaSineIcedApplication<H> {
                 }
             }
             Message::ModalOpen(action) => {
+                if let ModalAction::BankOverview = action {
+                    // Slot usage changes on parameter edits too
+                    self.bank_overview = BankOverview::new(&self.sync_handle);
+                }
+
                 self.modal_action = Some(action);
             }
             Message::ModalClose => {
-                self.modal_action = None;
+                if let Some(ModalAction::ImportPatches {
+                    previewing: true, ..
+                }) = self.modal_action.take()
+                {
+                    self.sync_handle.end_patch_preview();
+                }
             }
             Message::ModalYes => match self.modal_action.take() {
                 Some(ModalAction::ClearBank) => {
@@ -863,6 +2168,9 @@ impl<H: GuiSyncHandle> Application for OctaSineIcedApplication<H> {
                 Some(ModalAction::ClearPatch) => {
                     self.sync_handle.clear_patch();
                 }
+                Some(ModalAction::LoadWithUnsavedChanges) => {
+                    return load_bank_or_patch(self.last_directory.clone());
+                }
                 Some(ModalAction::SetParameterByChoices {
                     parameter, choice, ..
                 }) => {
@@ -876,7 +2184,28 @@ impl<H: GuiSyncHandle> Application for OctaSineIcedApplication<H> {
                         self.set_value(parameter.parameter(), value_patch, true);
                     }
                 }
-                None => (),
+                Some(ModalAction::ImportPatches { paths, target, .. }) => {
+                    // Ends any preview before importing
+                    self.sync_handle
+                        .import_bank_or_patches_from_paths(&paths, target);
+                }
+                Some(ModalAction::BreedPatches {
+                    parents,
+                    num_children,
+                    mutate,
+                    ..
+                }) => {
+                    let mutation_strength = mutate.then(|| self.mutation_strength.fraction());
+                    let child_indices =
+                        self.sync_handle
+                            .breed_patches(parents, num_children, mutation_strength);
+
+                    // Switch to first child for auditioning
+                    if let Some(index) = child_indices.first().copied() {
+                        return self.update(window_queue, Message::ChangePatch(index));
+                    }
+                }
+                Some(ModalAction::BankOverview | ModalAction::MutatePatch) | None => (),
             },
             Message::ModalSetParameterByChoicesUpdate(new_choice) => {
                 if let Some(ModalAction::SetParameterByChoices { choice, .. }) =
@@ -885,40 +2214,84 @@ impl<H: GuiSyncHandle> Application for OctaSineIcedApplication<H> {
                     *choice = new_choice.into();
                 }
             }
+            Message::ModalImportPatchesTargetUpdate(new_target) => {
+                if let Some(ModalAction::ImportPatches { target, .. }) = self.modal_action.as_mut()
+                {
+                    *target = new_target;
+                }
+            }
+            Message::ModalBreedPatchesParentUpdate(parent_number, name) => {
+                if let Some(ModalAction::BreedPatches {
+                    patch_names,
+                    parents,
+                    ..
+                }) = self.modal_action.as_mut()
+                {
+                    if let Some(index) = patch_names.iter().position(|n| *n == name) {
+                        parents[parent_number] = index;
+                    }
+                }
+            }
+            Message::ModalBreedPatchesNumChildrenUpdate(new_num_children) => {
+                if let Some(ModalAction::BreedPatches { num_children, .. }) =
+                    self.modal_action.as_mut()
+                {
+                    *num_children = new_num_children;
+                }
+            }
+            Message::ModalBreedPatchesToggleMutation => {
+                if let Some(ModalAction::BreedPatches { mutate, .. }) = self.modal_action.as_mut() {
+                    *mutate = !*mutate;
+                }
+            }
+            Message::ModalImportPatchesTogglePreview => {
+                if let Some(ModalAction::ImportPatches {
+                    paths, previewing, ..
+                }) = self.modal_action.as_mut()
+                {
+                    if *previewing {
+                        self.sync_handle.end_patch_preview();
+                    } else if let Some(path) = paths.first() {
+                        self.sync_handle.start_patch_preview(path);
+                    }
+
+                    *previewing = !*previewing;
+                }
+            }
         }
 
         Command::none()
     }
 
     fn view(&self) -> Element<'_, Self::Message, Self::Theme> {
+        let spacing = self.opened_density.spacing();
+        let padding = self.opened_density.padding();
+        let line_height = self.opened_density.line_height();
+
+        let operators: Element<_, _> = if self.performance_view {
+            self.performance.view(&self.theme, self.opened_density)
+        } else {
+            Column::new()
+                .push(self.operator_4.view(&self.theme, self.opened_density))
+                .push(Space::with_height(Length::Fixed(spacing.into())))
+                .push(self.operator_3.view(&self.theme, self.opened_density))
+                .push(Space::with_height(Length::Fixed(spacing.into())))
+                .push(self.operator_2.view(&self.theme, self.opened_density))
+                .push(Space::with_height(Length::Fixed(spacing.into())))
+                .push(self.operator_1.view(&self.theme, self.opened_density))
+                .into()
+        };
+
         let content = Container::new(
             Column::new()
-                .push(Space::with_height(Length::Fixed(LINE_HEIGHT.into())))
-                .push(self.operator_4.view(&self.theme))
-                .push(Space::with_height(Length::Fixed(LINE_HEIGHT.into())))
-                .push(self.operator_3.view(&self.theme))
-                .push(Space::with_height(Length::Fixed(LINE_HEIGHT.into())))
-                .push(self.operator_2.view(&self.theme))
-                .push(Space::with_height(Length::Fixed(LINE_HEIGHT.into())))
-                .push(self.operator_1.view(&self.theme))
-                .push(Space::with_height(Length::Fixed(LINE_HEIGHT.into())))
+                .push(Space::with_height(Length::Fixed(spacing.into())))
+                .push(operators)
+                .push(Space::with_height(Length::Fixed(spacing.into())))
                 .push(
                     Row::new()
-                        .push(
-                            Column::new()
-                                .push(self.lfo_4.view(&self.theme))
-                                .push(Space::with_height(Length::Fixed(LINE_HEIGHT.into())))
-                                .push(self.lfo_3.view(&self.theme)),
-                        )
-                        .push(Space::with_width(Length::Fixed(LINE_HEIGHT.into())))
-                        .push(
-                            Column::new()
-                                .push(self.lfo_2.view(&self.theme))
-                                .push(Space::with_height(Length::Fixed(LINE_HEIGHT.into())))
-                                .push(self.lfo_1.view(&self.theme)),
-                        )
-                        .push(Space::with_width(Length::Fixed(LINE_HEIGHT.into())))
-                        .push(self.corner.view(&self.theme)),
+                        .push(self.lfos_view())
+                        .push(Space::with_width(Length::Fixed(spacing.into())))
+                        .push(self.corner.view(&self.theme, self.opened_density)),
                 ),
         )
         .height(Length::Fill)
@@ -934,6 +2307,11 @@ impl<H: GuiSyncHandle> Application for OctaSineIcedApplication<H> {
             let heading = match modal_action {
                 ModalAction::ClearBank => "CLEAR ENTIRE PATCH BANK?".into(),
                 ModalAction::ClearPatch => "CLEAR CURRENT PATCH?".into(),
+                ModalAction::LoadWithUnsavedChanges => "BANK HAS UNSAVED CHANGES".into(),
+                ModalAction::BankOverview => "PATCH BANK OVERVIEW".into(),
+                ModalAction::MutatePatch => "MUTATE PATCH".into(),
+                ModalAction::BreedPatches { .. } => "BREED PATCHES".into(),
+                ModalAction::ImportPatches { .. } => "IMPORT PATCHES".into(),
                 ModalAction::SetParameterByChoices { parameter, .. } => {
                     format!("SET {}", parameter.parameter().name().to_uppercase())
                 }
@@ -942,7 +2320,7 @@ impl<H: GuiSyncHandle> Application for OctaSineIcedApplication<H> {
             match modal_action {
                 ModalAction::ClearBank | ModalAction::ClearPatch => {
                     let body = Row::new()
-                        .spacing(LINE_HEIGHT / 2)
+                        .spacing(spacing / 2)
                         .width(Length::Fill)
                         .push(
                             Button::new(Text::new("YES").horizontal_alignment(Horizontal::Center))
@@ -957,14 +2335,245 @@ impl<H: GuiSyncHandle> Application for OctaSineIcedApplication<H> {
 
                     Card::new(Text::new(heading), body)
                         .max_width(LINE_HEIGHT as f32 * 16.0)
-                        .padding(LINE_HEIGHT as f32)
+                        .padding(f32::from(padding))
+                        .into()
+                }
+                ModalAction::LoadWithUnsavedChanges => {
+                    let body = Row::new()
+                        .spacing(spacing / 2)
+                        .width(Length::Fill)
+                        .push(
+                            Button::new(
+                                Text::new("SAVE BANK").horizontal_alignment(Horizontal::Center),
+                            )
+                            .width(Length::Fill)
+                            .on_press(Message::SaveBank),
+                        )
+                        .push(
+                            Button::new(
+                                Text::new("OPEN ANYWAY").horizontal_alignment(Horizontal::Center),
+                            )
+                            .width(Length::Fill)
+                            .on_press(Message::ModalYes),
+                        )
+                        .push(
+                            Button::new(
+                                Text::new("CANCEL").horizontal_alignment(Horizontal::Center),
+                            )
+                            .width(Length::Fill)
+                            .on_press(Message::ModalClose),
+                        );
+
+                    Card::new(Text::new(heading), body)
+                        .max_width(LINE_HEIGHT as f32 * 20.0)
+                        .padding(f32::from(padding))
+                        .into()
+                }
+                ModalAction::BankOverview => {
+                    let body = Column::new()
+                        .spacing(line_height)
+                        .push(Text::new(
+                            "Filled slots contain edited patches. Click a slot to switch to it.",
+                        ))
+                        .push(
+                            Container::new(self.bank_overview.view())
+                                .width(Length::Fill)
+                                .center_x(),
+                        )
+                        .push(
+                            Button::new(
+                                Text::new("CLOSE").horizontal_alignment(Horizontal::Center),
+                            )
+                            .width(Length::Fill)
+                            .on_press(Message::ModalClose),
+                        );
+
+                    Card::new(Text::new(heading), body)
+                        .max_width(LINE_HEIGHT as f32 * 28.0)
+                        .padding(f32::from(padding))
+                        .into()
+                }
+                ModalAction::MutatePatch => {
+                    let body = Column::new()
+                        .spacing(line_height)
+                        .push(Text::new(
+                            "Nudge continuous operator and LFO parameters by random amounts of at most:",
+                        ))
+                        .push(
+                            PickList::new(
+                                MutationStrength::STEPS,
+                                Some(self.mutation_strength),
+                                Message::SetMutationStrength,
+                            )
+                            .width(Length::Fill),
+                        )
+                        .push(Text::new(
+                            "Click knob titles to lock parameters, so that they are left untouched.",
+                        ))
+                        .push(
+                            Row::new()
+                                .spacing(spacing / 2)
+                                .width(Length::Fill)
+                                .push(
+                                    Button::new(
+                                        Text::new("MUTATE")
+                                            .horizontal_alignment(Horizontal::Center),
+                                    )
+                                    .width(Length::Fill)
+                                    .on_press(Message::MutatePatch),
+                                )
+                                .push(
+                                    Button::new(
+                                        Text::new("CLOSE").horizontal_alignment(Horizontal::Center),
+                                    )
+                                    .width(Length::Fill)
+                                    .on_press(Message::ModalClose),
+                                ),
+                        );
+
+                    Card::new(Text::new(heading), body)
+                        .max_width(LINE_HEIGHT as f32 * 20.0)
+                        .padding(f32::from(padding))
+                        .into()
+                }
+                ModalAction::BreedPatches {
+                    patch_names,
+                    parents,
+                    num_children,
+                    mutate,
+                } => {
+                    let parent_picker = |parent_number: usize| {
+                        PickList::new(
+                            patch_names.as_slice(),
+                            patch_names.get(parents[parent_number]).cloned(),
+                            move |name| Message::ModalBreedPatchesParentUpdate(parent_number, name),
+                        )
+                        .width(Length::Fill)
+                    };
+
+                    let body = Column::new()
+                        .spacing(line_height)
+                        .push(Text::new(
+                            "Create patches by randomly combining parameters from two parents. Children are written into empty bank slots.",
+                        ))
+                        .push(parent_picker(0))
+                        .push(parent_picker(1))
+                        .push(
+                            Row::new()
+                                .spacing(spacing / 2)
+                                .align_items(Alignment::Center)
+                                .push(Text::new("CHILDREN"))
+                                .push(PickList::new(
+                                    BREED_NUM_CHILDREN_STEPS,
+                                    Some(*num_children),
+                                    Message::ModalBreedPatchesNumChildrenUpdate,
+                                ))
+                                .push(Space::with_width(Length::Fill))
+                                .push(
+                                    Checkbox::new(
+                                        format!("MUTATE ({})", self.mutation_strength),
+                                        *mutate,
+                                        |_| Message::ModalBreedPatchesToggleMutation,
+                                    )
+                                    .spacing(4),
+                                ),
+                        )
+                        .push(
+                            Row::new()
+                                .spacing(spacing / 2)
+                                .width(Length::Fill)
+                                .push(
+                                    Button::new(
+                                        Text::new("BREED").horizontal_alignment(Horizontal::Center),
+                                    )
+                                    .width(Length::Fill)
+                                    .on_press(Message::ModalYes),
+                                )
+                                .push(
+                                    Button::new(
+                                        Text::new("CANCEL")
+                                            .horizontal_alignment(Horizontal::Center),
+                                    )
+                                    .width(Length::Fill)
+                                    .on_press(Message::ModalClose),
+                                ),
+                        );
+
+                    Card::new(Text::new(heading), body)
+                        .max_width(LINE_HEIGHT as f32 * 20.0)
+                        .padding(f32::from(padding))
+                        .into()
+                }
+                ModalAction::ImportPatches {
+                    paths,
+                    target,
+                    previewing,
+                } => {
+                    let options: Vec<PatchImportTarget> = [
+                        PatchImportTarget::CurrentSlot,
+                        PatchImportTarget::AfterLastUsed,
+                    ]
+                    .into_iter()
+                    .chain((0..NUM_PATCHES).map(PatchImportTarget::Slot))
+                    .collect();
+
+                    let body = Column::new()
+                        .spacing(line_height)
+                        .push(Text::new(format!(
+                            "Replace patches with {} imported patch file(s), starting at:",
+                            paths.len()
+                        )))
+                        .push(
+                            PickList::new(options, Some(*target), |target| {
+                                Message::ModalImportPatchesTargetUpdate(target)
+                            })
+                            .width(Length::Fill),
+                        )
+                        .push(Text::new(if *previewing {
+                            "Previewing first patch file in current slot. It will be restored when preview ends."
+                        } else {
+                            "Preview first patch file in current slot without overwriting it."
+                        }))
+                        .push(
+                            Row::new()
+                                .spacing(spacing / 2)
+                                .width(Length::Fill)
+                                .push(
+                                    Button::new(
+                                        Text::new("IMPORT")
+                                            .horizontal_alignment(Horizontal::Center),
+                                    )
+                                    .width(Length::Fill)
+                                    .on_press(Message::ModalYes),
+                                )
+                                .push(
+                                    Button::new(
+                                        Text::new(if *previewing { "STOP" } else { "PREVIEW" })
+                                            .horizontal_alignment(Horizontal::Center),
+                                    )
+                                    .width(Length::Fill)
+                                    .on_press(Message::ModalImportPatchesTogglePreview),
+                                )
+                                .push(
+                                    Button::new(
+                                        Text::new("CANCEL")
+                                            .horizontal_alignment(Horizontal::Center),
+                                    )
+                                    .width(Length::Fill)
+                                    .on_press(Message::ModalClose),
+                                ),
+                        );
+
+                    Card::new(Text::new(heading), body)
+                        .max_width(LINE_HEIGHT as f32 * 20.0)
+                        .padding(f32::from(padding))
                         .into()
                 }
                 ModalAction::SetParameterByChoices {
                     options, choice, ..
                 } => {
                     let body = Column::new()
-                        .spacing(LINE_HEIGHT)
+                        .spacing(line_height)
                         .push(
                             PickList::new(options, Some(choice.clone()), |choice| {
                                 Message::ModalSetParameterByChoicesUpdate(choice)
@@ -973,7 +2582,7 @@ impl<H: GuiSyncHandle> Application for OctaSineIcedApplication<H> {
                         )
                         .push(
                             Row::new()
-                                .spacing(LINE_HEIGHT / 2)
+                                .spacing(spacing / 2)
                                 .width(Length::Fill)
                                 .push(
                                     Button::new(
@@ -994,7 +2603,7 @@ impl<H: GuiSyncHandle> Application for OctaSineIcedApplication<H> {
 
                     Card::new(Text::new(heading), body)
                         .max_width(LINE_HEIGHT as f32 * 16.0)
-                        .padding(LINE_HEIGHT as f32)
+                        .padding(f32::from(padding))
                         .into()
                 }
             }
@@ -1013,6 +2622,149 @@ impl<H: GuiSyncHandle> Application for OctaSineIcedApplication<H> {
     }
 }
 
+/// Open file dialog for loading patch bank or patches
+fn load_bank_or_patch(opt_directory: Option<PathBuf>) -> Command<Message> {
+    const TITLE: &str = "Load OctaSine patch bank or patches";
+
+    Command::single(Action::Future(Box::pin(async move {
+        cfg_if!(
+            if #[cfg(target_os = "macos")] {
+                let mut builder = rfd::AsyncFileDialog::new()
+                    .set_title(TITLE)
+                    .add_filter("Patch", &["fxp"])
+                    .add_filter("Patch bank", &["fxb"]);
+
+                if let Some(h) = CurrentWindowHandle::get() {
+                    builder = builder.set_parent(&h);
+                }
+                if let Some(directory) = opt_directory {
+                    builder = builder.set_directory(directory);
+                }
+
+                let opt_paths = builder
+                    .pick_files()
+                    .await
+                    .map(|handles|
+                        handles.into_iter()
+                            .map(|h| h.path().to_owned())
+                            .collect::<Vec<PathBuf>>()
+                    );
+            } else if #[cfg(target_os = "windows")] {
+                let mut builder = rfd::AsyncFileDialog::new()
+                    .set_title(TITLE)
+                    .add_filter("Patch", &["fxp"])
+                    .add_filter("Patch bank", &["fxb"]);
+
+                if let Some(directory) = opt_directory {
+                    builder = builder.set_directory(directory);
+                }
+
+                let opt_paths = builder
+                    .pick_files()
+                    .await
+                    .map(|handles|
+                        handles.into_iter()
+                            .map(|h| h.path().to_owned())
+                            .collect::<Vec<PathBuf>>()
+                    );
+            } else {
+                // Trailing separator makes dialog treat path as directory
+                let default_path = opt_directory
+                    .map(|directory| directory.join("").to_string_lossy().into_owned())
+                    .unwrap_or_default();
+
+                let opt_paths = tinyfiledialogs::open_file_dialog_multi(
+                    TITLE,
+                    &default_path,
+                    Some((&["*.fxp", "*.fxb"], "Patch bank or patch files"))
+                ).map(|strings|
+                    strings.into_iter()
+                        .map(|s| s.into())
+                        .collect::<Vec<PathBuf>>()
+                );
+            }
+        );
+
+        if let Some(paths) = opt_paths {
+            Message::LoadBankOrPatchesFromPaths(paths)
+        } else {
+            Message::NoOp
+        }
+    })))
+}
+
+fn save_patch(
+    patch_filename: CompactString,
+    patch_bytes: Vec<u8>,
+    opt_directory: Option<PathBuf>,
+    with_preview: bool,
+) -> Command<Message> {
+    const TITLE: &str = "Save OctaSine patch";
+
+    Command::single(Action::Future(Box::pin(async move {
+        cfg_if!(
+            if #[cfg(target_os = "macos")] {
+                let mut builder = rfd::AsyncFileDialog::new()
+                    .set_title(TITLE)
+                    .add_filter("Patch", &["fxp"])
+                    .set_file_name(&*patch_filename);
+
+                if let Some(h) = CurrentWindowHandle::get() {
+                    builder = builder.set_parent(&h);
+                }
+                if let Some(directory) = opt_directory {
+                    builder = builder.set_directory(directory);
+                }
+
+                let opt_path_buf = builder
+                    .save_file()
+                    .await
+                    .map(|handle| handle.path().to_owned());
+            }
+            else if #[cfg(target_os = "windows")] {
+                let mut builder = rfd::AsyncFileDialog::new()
+                    .set_title(TITLE)
+                    .add_filter("Patch", &["fxp"])
+                    .set_file_name(&*patch_filename);
+
+                if let Some(directory) = opt_directory {
+                    builder = builder.set_directory(directory);
+                }
+
+                let opt_path_buf = builder
+                    .save_file()
+                    .await
+                    .map(|handle| handle.path().to_owned());
+            } else {
+                let default_path = match opt_directory {
+                    Some(directory) => directory
+                        .join(patch_filename.as_str())
+                        .to_string_lossy()
+                        .into_owned(),
+                    None => patch_filename.to_string(),
+                };
+
+                let opt_path_buf = tinyfiledialogs::save_file_dialog_with_filter(
+                    TITLE,
+                    &default_path,
+                    &["*.fxp"],
+                    "Patch"
+                ).map(|s| s.into());
+            }
+        );
+
+        if let Some(path_buf) = opt_path_buf {
+            if with_preview {
+                Message::SavePatchAndPreviewToFile(path_buf, patch_bytes)
+            } else {
+                Message::SaveBankOrPatchToFile(path_buf, patch_bytes)
+            }
+        } else {
+            Message::NoOp
+        }
+    })))
+}
+
 fn save_data_to_file(path_buf: PathBuf, mut bytes: Vec<u8>) -> anyhow::Result<()> {
     let mut file = ::std::fs::File::create(&path_buf)
         .with_context(|| format!("create file {}", path_buf.display()))?;
@@ -1027,9 +2779,11 @@ pub fn get_iced_baseview_settings<H: GuiSyncHandle>(
     sync_handle: H,
     plugin_name: String,
 ) -> iced_baseview::Settings<H> {
+    let (width, height) = sync_handle.get_gui_settings().density.gui_size();
+
     iced_baseview::Settings {
         window: iced_baseview::baseview::WindowOpenOptions {
-            size: iced_baseview::baseview::Size::new(GUI_WIDTH as f64, GUI_HEIGHT as f64),
+            size: iced_baseview::baseview::Size::new(width as f64, height as f64),
             #[cfg(not(target_os = "windows"))]
             scale: iced_baseview::baseview::WindowScalePolicy::SystemScaleFactor,
             // Windows currently needs scale factor 1.0, or GUI contents
@@ -1048,7 +2802,9 @@ pub fn get_iced_baseview_settings<H: GuiSyncHandle>(
             }),
         },
         iced_baseview: iced_baseview::settings::IcedBaseviewSettings {
-            ignore_non_modifier_keys: true,
+            // Key presses are needed for operating modulation matrix with
+            // keyboard
+            ignore_non_modifier_keys: false,
             always_redraw: true,
         },
         flags: sync_handle,
@@ -5,14 +5,16 @@ mod mod_lines;
 mod operator_box;
 mod output_box;
 
+use iced_baseview::keyboard::{self, KeyCode};
 use iced_baseview::widget::canvas::{
-    event, Cache, Canvas, Cursor, Frame, Geometry, Path, Program, Stroke,
+    event, Cache, Canvas, Cursor, Frame, Geometry, Path, Program, Stroke, Text,
 };
-use iced_baseview::{Color, Element, Length, Point, Rectangle, Size};
+use iced_baseview::{mouse, Color, Element, Length, Point, Rectangle, Size};
 
 use crate::parameters::{
     ModTargetStorage, Operator2ModulationTargetValue, Operator3ModulationTargetValue,
-    Operator4ModulationTargetValue, OperatorParameter, Parameter, ParameterValue,
+    Operator4ModulationTargetValue, OperatorMixOutValue, OperatorModOutValue, OperatorParameter,
+    Parameter, ParameterValue,
 };
 use crate::sync::GuiSyncHandle;
 
@@ -26,7 +28,7 @@ use self::operator_box::{OperatorBox, OperatorBoxCanvasState, OperatorBoxChange}
 use self::output_box::OutputBox;
 
 use super::style::Theme;
-use super::{Message, SnapPoint, LINE_HEIGHT};
+use super::{Message, SnapPoint, FONT_SIZE, LINE_HEIGHT};
 
 /// Canvas width
 const WIDTH: u16 = LINE_HEIGHT * 5 + 2;
@@ -43,6 +45,58 @@ const SIZE: Size = Size {
 };
 const OPERATOR_BOX_SCALE: f32 = BIG_BOX_SIZE as f32 / SMALL_BOX_SIZE as f32;
 
+/// Change in mix or modulation patch value per +/- key press
+const KEYBOARD_VALUE_STEP: f32 = 0.01;
+
+/// Matrix node that can be focused for keyboard operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Node {
+    /// Operator box (mix out)
+    Operator(usize),
+    /// Modulation box from operator to operator
+    Modulation(usize, usize),
+}
+
+impl Node {
+    const ALL: [Self; 10] = [
+        Self::Operator(3),
+        Self::Modulation(3, 2),
+        Self::Modulation(3, 1),
+        Self::Modulation(3, 0),
+        Self::Operator(2),
+        Self::Modulation(2, 1),
+        Self::Modulation(2, 0),
+        Self::Operator(1),
+        Self::Modulation(1, 0),
+        Self::Operator(0),
+    ];
+
+    fn grid_position(self) -> (i8, i8) {
+        match self {
+            Self::Operator(index) => (6 - 2 * index as i8, 6 - 2 * index as i8),
+            Self::Modulation(from, to) => (6 - 2 * to as i8, 6 - 2 * from as i8),
+        }
+    }
+
+    /// Closest node in given direction on the same row or column
+    fn neighbour(self, dx: i8, dy: i8) -> Option<Self> {
+        let (x, y) = self.grid_position();
+
+        Self::ALL
+            .iter()
+            .copied()
+            .filter_map(|node| {
+                let (node_x, node_y) = node.grid_position();
+                let distance = (node_x - x) * dx + (node_y - y) * dy;
+                let same_line = if dx == 0 { node_x == x } else { node_y == y };
+
+                (same_line && distance > 0).then_some((node, distance))
+            })
+            .min_by_key(|(_, distance)| *distance)
+            .map(|(node, _)| node)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Appearance {
     pub background_color: Color,
@@ -341,6 +395,53 @@ impl ModulationMatrixComponents {
         self.operator_2_mod_out_lines.draw(frame, theme);
     }
 
+    fn node_rect(&self, node: Node) -> Rectangle {
+        match node {
+            Node::Operator(0) => self.operator_1_box.get_rect(),
+            Node::Operator(1) => self.operator_2_box.get_rect(),
+            Node::Operator(2) => self.operator_3_box.get_rect(),
+            Node::Operator(3) => self.operator_4_box.get_rect(),
+            Node::Modulation(3, 2) => self.operator_4_mod_3_box.get_rect(),
+            Node::Modulation(3, 1) => self.operator_4_mod_2_box.get_rect(),
+            Node::Modulation(3, 0) => self.operator_4_mod_1_box.get_rect(),
+            Node::Modulation(2, 1) => self.operator_3_mod_2_box.get_rect(),
+            Node::Modulation(2, 0) => self.operator_3_mod_1_box.get_rect(),
+            Node::Modulation(1, 0) => self.operator_2_mod_1_box.get_rect(),
+            _ => unreachable!(),
+        }
+    }
+
+    fn modulation_active(&self, from: usize, to: usize) -> bool {
+        match (from, to) {
+            (3, 2) => self.operator_4_mod_3_box.active(),
+            (3, 1) => self.operator_4_mod_2_box.active(),
+            (3, 0) => self.operator_4_mod_1_box.active(),
+            (2, 1) => self.operator_3_mod_2_box.active(),
+            (2, 0) => self.operator_3_mod_1_box.active(),
+            (1, 0) => self.operator_2_mod_1_box.active(),
+            _ => unreachable!(),
+        }
+    }
+
+    fn modulation_toggle_message(&self, from: usize, to: usize) -> Message {
+        match (from, to) {
+            (3, 2) => self.operator_4_mod_3_box.toggle_message(),
+            (3, 1) => self.operator_4_mod_2_box.toggle_message(),
+            (3, 0) => self.operator_4_mod_1_box.toggle_message(),
+            (2, 1) => self.operator_3_mod_2_box.toggle_message(),
+            (2, 0) => self.operator_3_mod_1_box.toggle_message(),
+            (1, 0) => self.operator_2_mod_1_box.toggle_message(),
+            _ => unreachable!(),
+        }
+    }
+
+    fn node_at(&self, point: Point) -> Option<Node> {
+        Node::ALL
+            .iter()
+            .copied()
+            .find(|node| self.node_rect(*node).contains(point))
+    }
+
     fn draw_boxes(&self, state: &CanvasState, frame: &mut Frame, theme: &Theme) {
         self.operator_1_box
             .draw(&state.operator_1_box, frame, theme);
@@ -482,6 +583,146 @@ impl ModulationMatrix {
         frame.fill(&background, appearance.background_color);
         frame.stroke(&background, stroke);
     }
+
+    /// Parameter and current patch value adjusted with +/- keys
+    fn node_parameter_and_value(&self, node: Node) -> (Parameter, f32) {
+        match node {
+            Node::Operator(index) => {
+                let value = match index {
+                    0 => self.parameters.operator_1_mix,
+                    1 => self.parameters.operator_2_mix,
+                    2 => self.parameters.operator_3_mix,
+                    _ => self.parameters.operator_4_mix,
+                };
+
+                (
+                    Parameter::Operator(index as u8, OperatorParameter::MixOut),
+                    value,
+                )
+            }
+            Node::Modulation(from, _) => {
+                let value = match from {
+                    1 => self.parameters.operator_2_mod,
+                    2 => self.parameters.operator_3_mod,
+                    _ => self.parameters.operator_4_mod,
+                };
+
+                (
+                    Parameter::Operator(from as u8, OperatorParameter::ModOut),
+                    value,
+                )
+            }
+        }
+    }
+
+    /// Draw focus outline around node and readout of its value in empty
+    /// lower left corner
+    fn draw_focus(&self, node: Node, frame: &mut Frame, theme: &Theme) {
+        let appearance = theme.appearance();
+
+        let rect = self.components.node_rect(node);
+        let outline = Path::rectangle(
+            Point::new(rect.x - 2.0, rect.y - 2.0),
+            Size::new(rect.width + 4.0, rect.height + 4.0),
+        );
+
+        frame.stroke(
+            &outline,
+            Stroke::default()
+                .with_color(appearance.text_color)
+                .with_width(1.0),
+        );
+
+        let (_, value) = self.node_parameter_and_value(node);
+
+        let (label, value_text) = match node {
+            Node::Operator(index) => (
+                format!("MIX {}", index + 1),
+                OperatorMixOutValue::new_from_patch(value)
+                    .get_formatted()
+                    .to_string(),
+            ),
+            Node::Modulation(from, to) => {
+                let value_text = if self.components.modulation_active(from, to) {
+                    OperatorModOutValue::new_from_patch(value)
+                        .get_formatted()
+                        .to_string()
+                } else {
+                    "OFF".into()
+                };
+
+                (format!("{}>{}", from + 1, to + 1), value_text)
+            }
+        };
+
+        for (i, content) in [label, value_text].into_iter().enumerate() {
+            frame.fill_text(Text {
+                content,
+                position: Point::new(2.0, SIZE.height / 2.0 + i as f32 * f32::from(FONT_SIZE)),
+                font: theme.font_regular(),
+                size: f32::from(FONT_SIZE - 2),
+                color: appearance.text_color,
+                ..Default::default()
+            });
+        }
+    }
+
+    fn handle_key_press(
+        &self,
+        state: &mut CanvasState,
+        node: Node,
+        key_code: KeyCode,
+    ) -> (event::Status, Option<Message>) {
+        let (dx, dy) = match key_code {
+            KeyCode::Left => (-1, 0),
+            KeyCode::Right => (1, 0),
+            KeyCode::Up => (0, -1),
+            KeyCode::Down => (0, 1),
+            KeyCode::Enter | KeyCode::NumpadEnter => {
+                return match node {
+                    Node::Modulation(from, to) => (
+                        event::Status::Captured,
+                        Some(self.components.modulation_toggle_message(from, to)),
+                    ),
+                    Node::Operator(_) => (event::Status::Ignored, None),
+                };
+            }
+            KeyCode::Plus
+            | KeyCode::Equals
+            | KeyCode::NumpadAdd
+            | KeyCode::Minus
+            | KeyCode::NumpadSubtract => {
+                let step = match key_code {
+                    KeyCode::Minus | KeyCode::NumpadSubtract => -KEYBOARD_VALUE_STEP,
+                    _ => KEYBOARD_VALUE_STEP,
+                };
+
+                let (parameter, value) = self.node_parameter_and_value(node);
+
+                return (
+                    event::Status::Captured,
+                    Some(Message::ChangeSingleParameterImmediate(
+                        parameter.into(),
+                        (value + step).clamp(0.0, 1.0),
+                    )),
+                );
+            }
+            KeyCode::Escape => {
+                state.focused_node = None;
+                self.cache.clear();
+
+                return (event::Status::Captured, None);
+            }
+            _ => return (event::Status::Ignored, None),
+        };
+
+        if let Some(neighbour) = node.neighbour(dx, dy) {
+            state.focused_node = Some(neighbour);
+            self.cache.clear();
+        }
+
+        (event::Status::Captured, None)
+    }
 }
 
 #[derive(Default)]
@@ -496,6 +737,10 @@ pub struct CanvasState {
     operator_3_mod_2_box: ModulationBoxCanvasState,
     operator_3_mod_1_box: ModulationBoxCanvasState,
     operator_2_mod_1_box: ModulationBoxCanvasState,
+    /// Node operated with keyboard, set by clicking matrix
+    focused_node: Option<Node>,
+    /// Cursor position relative to canvas, if within it
+    cursor_position: Option<Point>,
 }
 
 impl Program<Message, Theme> for ModulationMatrix {
@@ -513,6 +758,10 @@ impl Program<Message, Theme> for ModulationMatrix {
 
             self.components.draw_lines(frame, theme);
             self.components.draw_boxes(state, frame, theme);
+
+            if let Some(node) = state.focused_node {
+                self.draw_focus(node, frame, theme);
+            }
         });
 
         vec![geometry]
@@ -523,8 +772,51 @@ impl Program<Message, Theme> for ModulationMatrix {
         state: &mut Self::State,
         event: event::Event,
         bounds: Rectangle,
-        _cursor: Cursor,
+        cursor: Cursor,
     ) -> (event::Status, Option<Message>) {
+        // Touch and pen input might not move cursor before pressing, so
+        // update cursor position and hover states first
+        if let event::Event::Mouse(mouse::Event::ButtonPressed(_)) = event {
+            if let Some(position) = cursor.position() {
+                let _ = <Self as Program<Message, Theme>>::update(
+                    self,
+                    state,
+                    event::Event::Mouse(mouse::Event::CursorMoved { position }),
+                    bounds,
+                    cursor,
+                );
+            }
+        }
+
+        match event {
+            event::Event::Mouse(mouse::Event::CursorMoved { position }) => {
+                state.cursor_position = bounds
+                    .contains(position)
+                    .then(|| Point::new(position.x - bounds.x, position.y - bounds.y));
+            }
+            // Clicking matrix focuses node under cursor (or keeps current
+            // focus), clicking elsewhere removes focus
+            event::Event::Mouse(mouse::Event::ButtonPressed(_)) => {
+                let focused_node = state.cursor_position.map(|position| {
+                    self.components
+                        .node_at(position)
+                        .or(state.focused_node)
+                        .unwrap_or(Node::Operator(0))
+                });
+
+                if focused_node != state.focused_node {
+                    state.focused_node = focused_node;
+                    self.cache.clear();
+                }
+            }
+            event::Event::Keyboard(keyboard::Event::KeyPressed { key_code, .. }) => {
+                if let Some(node) = state.focused_node {
+                    return self.handle_key_press(state, node, key_code);
+                }
+            }
+            _ => (),
+        }
+
         let operator_boxes = [
             (
                 &self.components.operator_1_box,
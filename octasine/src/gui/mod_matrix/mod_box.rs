@@ -90,10 +90,23 @@ where
         self.center
     }
 
-    fn active(&self) -> bool {
+    pub fn get_rect(&self) -> Rectangle {
+        self.rect
+    }
+
+    pub fn active(&self) -> bool {
         self.v.index_active(self.target_index)
     }
 
+    /// Message toggling this modulation route
+    pub fn toggle_message(&self) -> Message {
+        let mut v = self.v;
+
+        v.set_index(self.target_index, !self.active());
+
+        Message::ChangeSingleParameterImmediate(self.parameter, P::new_from_audio(v).to_patch())
+    }
+
     pub fn draw(&self, state: &ModulationBoxCanvasState, frame: &mut Frame, theme: &Theme) {
         let apparence = theme.appearance();
 
@@ -152,17 +165,7 @@ where
                 if state.hover && state.click_started {
                     state.click_started = false;
 
-                    let sync_value = {
-                        let mut v = self.v;
-
-                        v.set_index(self.target_index, !self.active());
-
-                        P::new_from_audio(v).to_patch()
-                    };
-
-                    return ModulationBoxCanvasUpdateResult::Update(
-                        Message::ChangeSingleParameterImmediate(self.parameter, sync_value),
-                    );
+                    return ModulationBoxCanvasUpdateResult::Update(self.toggle_message());
                 }
             }
             _ => (),
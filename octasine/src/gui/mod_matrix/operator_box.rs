@@ -89,6 +89,10 @@ impl OperatorBox {
         self.center
     }
 
+    pub fn get_rect(&self) -> Rectangle {
+        self.hitbox
+    }
+
     pub fn update(
         &self,
         state: &mut OperatorBoxCanvasState,
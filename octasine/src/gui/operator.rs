@@ -1,27 +1,52 @@
+use compact_str::CompactString;
 use iced_baseview::widget::tooltip::Position;
 use iced_baseview::{
-    alignment::Horizontal, widget::Column, widget::Container, widget::Row, widget::Space,
-    widget::Text, Alignment, Element, Length,
+    alignment::Horizontal, widget::Button, widget::Column, widget::Container, widget::PickList,
+    widget::Row, widget::Space, widget::Text, Alignment, Element, Length,
 };
 
+use crate::common::NUM_OPERATORS;
+use crate::parameters::operator_cross_feedback_target::{
+    CrossFeedbackTarget, Operator1CrossFeedbackTargetValue, Operator2CrossFeedbackTargetValue,
+    Operator3CrossFeedbackTargetValue,
+};
+use crate::parameters::operator_key_range::{OperatorKeyRangeHighValue, OperatorKeyRangeLowValue};
+use crate::parameters::operator_phase_distortion::OperatorPhaseDistortionValue;
+use crate::parameters::operator_spread::OperatorSpreadValue;
+use crate::parameters::operator_velocity_range::{
+    OperatorVelocityRangeHighValue, OperatorVelocityRangeLowValue,
+};
+use crate::parameters::operator_wavetable_position::OperatorWavetablePositionValue;
+use crate::parameters::utils::{map_patch_value_to_step, map_step_to_patch_value};
 use crate::parameters::velocity_sensitivity::VelocitySensitivityValue;
 use crate::parameters::{
     Operator2ModulationTargetValue, Operator3ModulationTargetValue, Operator4ModulationTargetValue,
-    OperatorFeedbackValue, OperatorFrequencyFineValue, OperatorFrequencyFreeValue,
-    OperatorFrequencyRatioValue, OperatorMixOutValue, OperatorModOutValue, OperatorPanningValue,
-    OperatorParameter, OperatorVolumeValue, OperatorWaveTypeValue, Parameter,
+    OperatorEnvelopeVelocitySensitivityValue, OperatorFeedbackValue, OperatorFrequencyFineValue,
+    OperatorFrequencyFreeValue, OperatorFrequencyRatioValue, OperatorMixOutValue,
+    OperatorModOutValue, OperatorPanningValue, OperatorParameter, OperatorReleaseModeValue,
+    OperatorVolumeValue, OperatorWaveTypeValue, Parameter, ParameterValue, RELEASE_MODE_STEPS,
 };
-use crate::sync::GuiSyncHandle;
+use crate::sync::{GuiSyncHandle, RandomizeScope};
 
-use super::boolean_button::{operator_mute_button, BooleanButton};
+use super::boolean_button::{operator_mute_button, operator_noise_stereo_button, BooleanButton};
 use super::common::{container_l1, container_l2, container_l3, space_l2, space_l3, tooltip};
 use super::envelope::Envelope;
 use super::knob::{self, OctaSineKnob};
 use super::mod_target_picker;
+use super::range_picker::{self, RangePicker};
+use super::style::button::ButtonStyle;
+use super::style::text::TextStyle;
 use super::style::Theme;
+use super::value_text::TextFormat;
+use super::velocity_curve::VelocityCurve;
 use super::wave_display::WaveDisplay;
 use super::wave_picker::WavePicker;
-use super::{Message, FONT_SIZE, LINE_HEIGHT};
+use super::{FrequencyReferenceNote, GuiDensity, Message, FONT_SIZE, LINE_HEIGHT};
+
+/// Modulation input peak (in radians) above which overload indicator is shown
+const MODULATION_INPUT_OVERLOAD_THRESHOLD: f32 = 30.0;
+/// Decay factor applied to held modulation input peak each frame
+const MODULATION_INPUT_PEAK_DECAY: f32 = 0.95;
 
 pub enum ModTargetPicker {
     Operator4(mod_target_picker::ModTargetPicker<Operator4ModulationTargetValue>),
@@ -34,6 +59,7 @@ pub struct OperatorWidgets {
     pub alternative_controls: bool,
     pub volume: OctaSineKnob<OperatorVolumeValue>,
     pub mute_button: BooleanButton,
+    pub noise_stereo_button: BooleanButton,
     pub mix: OctaSineKnob<OperatorMixOutValue>,
     pub panning: OctaSineKnob<OperatorPanningValue>,
     pub wave_type: WavePicker<OperatorWaveTypeValue>,
@@ -45,8 +71,31 @@ pub struct OperatorWidgets {
     pub frequency_fine: OctaSineKnob<OperatorFrequencyFineValue>,
     pub mod_out_velocity_sensitivity: OctaSineKnob<VelocitySensitivityValue>,
     pub feedback_velocity_sensitivity: OctaSineKnob<VelocitySensitivityValue>,
+    pub release_mode: f32,
+    pub semitone_frequency: bool,
+    pub frequency_reference_note: FrequencyReferenceNote,
+    pub peak_velocity_sensitivity: OctaSineKnob<OperatorEnvelopeVelocitySensitivityValue>,
+    pub sustain_velocity_sensitivity: OctaSineKnob<OperatorEnvelopeVelocitySensitivityValue>,
+    pub wavetable_position: OctaSineKnob<OperatorWavetablePositionValue>,
+    pub phase_distortion: OctaSineKnob<OperatorPhaseDistortionValue>,
+    pub spread: OctaSineKnob<OperatorSpreadValue>,
+    pub cross_feedback: Option<OctaSineKnob<OperatorFeedbackValue>>,
+    pub cross_feedback_target: f32,
+    /// Held peak of absolute modulation input, decaying over time
+    pub modulation_input_peak: f32,
+    /// Operator is routed alone to output (not stored in patches)
+    pub listening: bool,
+    pub key_range: RangePicker<OperatorKeyRangeLowValue, OperatorKeyRangeHighValue>,
+    pub velocity_range: RangePicker<OperatorVelocityRangeLowValue, OperatorVelocityRangeHighValue>,
+    /// Name of loaded wavetable, if any
+    pub wavetable_name: Option<CompactString>,
     pub envelope: Envelope,
     pub wave_display: WaveDisplay,
+    /// Shown instead of wave display with alternative controls, next to
+    /// velocity sensitivity knobs
+    pub velocity_curve: VelocityCurve,
+    /// Only show operator heading row (stored in GUI settings)
+    pub collapsed: bool,
 }
 
 impl OperatorWidgets {
@@ -73,14 +122,33 @@ impl OperatorWidgets {
         let wave_type_parameter =
             Parameter::Operator(operator_index as u8, OperatorParameter::WaveType);
 
+        let mut wave_type = WavePicker::new(sync_handle, wave_type_parameter, "WAVE");
+
+        wave_type.set_feedback(
+            OperatorFeedbackValue::new_from_patch(sync_handle.get_parameter(
+                Parameter::Operator(operator_index as u8, OperatorParameter::Feedback).into(),
+            ))
+            .get(),
+        );
+
+        if operator_index != 0 {
+            wave_type.set_modulation_index(
+                OperatorModOutValue::new_from_patch(sync_handle.get_parameter(
+                    Parameter::Operator(operator_index as u8, OperatorParameter::ModOut).into(),
+                ))
+                .get(),
+            );
+        }
+
         Self {
             index: operator_index,
             alternative_controls: false,
             volume: knob::operator_volume(sync_handle, operator_index),
             mute_button: operator_mute_button(sync_handle, operator_index),
+            noise_stereo_button: operator_noise_stereo_button(sync_handle, operator_index),
             mix: knob::operator_mix(sync_handle, operator_index),
             panning: knob::operator_panning(sync_handle, operator_index),
-            wave_type: WavePicker::new(sync_handle, wave_type_parameter, "WAVE"),
+            wave_type,
             mod_index,
             mod_target,
             feedback: knob::operator_feedback(sync_handle, operator_index),
@@ -89,6 +157,8 @@ impl OperatorWidgets {
             frequency_fine: knob::operator_frequency_fine(sync_handle, operator_index),
             envelope: Envelope::new(sync_handle, operator_index),
             wave_display: WaveDisplay::new(sync_handle, operator_index),
+            velocity_curve: VelocityCurve::new(sync_handle, operator_index),
+            collapsed: false,
             mod_out_velocity_sensitivity: knob::operator_mod_out_velocity_sensitivity(
                 sync_handle,
                 operator_index,
@@ -97,19 +167,171 @@ impl OperatorWidgets {
                 sync_handle,
                 operator_index,
             ),
+            release_mode: sync_handle.get_parameter(
+                Parameter::Operator(operator_index as u8, OperatorParameter::ReleaseMode).into(),
+            ),
+            semitone_frequency: false,
+            frequency_reference_note: Default::default(),
+            peak_velocity_sensitivity: knob::operator_peak_velocity_sensitivity(
+                sync_handle,
+                operator_index,
+            ),
+            sustain_velocity_sensitivity: knob::operator_sustain_velocity_sensitivity(
+                sync_handle,
+                operator_index,
+            ),
+            wavetable_position: knob::operator_wavetable_position(sync_handle, operator_index),
+            wavetable_name: sync_handle.get_wavetable_name(operator_index),
+            phase_distortion: knob::operator_phase_distortion(sync_handle, operator_index),
+            spread: knob::operator_spread(sync_handle, operator_index),
+            cross_feedback: (operator_index < NUM_OPERATORS - 1)
+                .then(|| knob::operator_cross_feedback(sync_handle, operator_index)),
+            cross_feedback_target: if operator_index < NUM_OPERATORS - 1 {
+                sync_handle.get_parameter(
+                    Parameter::Operator(
+                        operator_index as u8,
+                        OperatorParameter::CrossFeedbackTarget,
+                    )
+                    .into(),
+                )
+            } else {
+                0.0
+            },
+            modulation_input_peak: 0.0,
+            listening: sync_handle.get_listen_operator() == Some(operator_index),
+            key_range: range_picker::operator_key_range(sync_handle, operator_index),
+            velocity_range: range_picker::operator_velocity_range(sync_handle, operator_index),
         }
     }
 
+    pub fn update_modulation_input_peak(&mut self, peak: f32) {
+        self.modulation_input_peak =
+            peak.max(self.modulation_input_peak * MODULATION_INPUT_PEAK_DECAY);
+    }
+
+    pub fn set_semitone_frequency(&mut self, semitone_frequency: bool) {
+        const SEMITONE_TEXT_FORMAT: TextFormat = TextFormat {
+            format: |v| OperatorFrequencyRatioValue::new_from_patch(v).get_semitone_formatted(),
+            parse: |text| {
+                OperatorFrequencyRatioValue::new_from_semitone_text(text).map(|v| v.to_patch())
+            },
+        };
+
+        self.semitone_frequency = semitone_frequency;
+        self.frequency_ratio
+            .set_text_format(semitone_frequency.then_some(SEMITONE_TEXT_FORMAT));
+    }
+
+    /// Show operator frequency at reference note in frequency knob tooltips
+    pub fn update_frequency_readout(&mut self, master_frequency: f64) {
+        let ratio = OperatorFrequencyRatioValue::new_from_patch(self.frequency_ratio.get_value());
+        let free = OperatorFrequencyFreeValue::new_from_patch(self.frequency_free.get_value());
+        let fine = OperatorFrequencyFineValue::new_from_patch(self.frequency_fine.get_value());
+
+        let frequency = self.frequency_reference_note.frequency(master_frequency)
+            * ratio.get().value
+            * free.get()
+            * fine.get();
+
+        let details = format!(
+            "Frequency at {}: {:.02} Hz",
+            self.frequency_reference_note, frequency
+        );
+
+        self.frequency_ratio
+            .set_tooltip_details(Some(details.clone()));
+        self.frequency_free
+            .set_tooltip_details(Some(details.clone()));
+        self.frequency_fine.set_tooltip_details(Some(details));
+    }
+
     pub fn theme_changed(&mut self) {
         self.mute_button.theme_changed();
+        self.noise_stereo_button.theme_changed();
         self.wave_type.theme_changed();
         self.envelope.theme_changed();
         self.wave_display.theme_changed();
+        self.velocity_curve.theme_changed();
+        self.key_range.theme_changed();
+        self.velocity_range.theme_changed();
     }
 
-    pub fn view(&self, theme: &Theme) -> Element<Message, Theme> {
+    fn cross_feedback_target_steps(&self) -> Option<&'static [CrossFeedbackTarget]> {
+        match self.index {
+            0 => Some(Operator1CrossFeedbackTargetValue::STEPS),
+            1 => Some(Operator2CrossFeedbackTargetValue::STEPS),
+            2 => Some(Operator3CrossFeedbackTargetValue::STEPS),
+            _ => None,
+        }
+    }
+
+    pub fn view(&self, theme: &Theme, density: GuiDensity) -> Element<Message, Theme> {
+        if self.collapsed {
+            return self.view_collapsed(theme, density);
+        }
+
         let heading = {
             let mute_button = tooltip(theme, "Toggle mute", Position::Top, self.mute_button.view());
+            let noise_stereo_button = tooltip(
+                theme,
+                "Toggle stereo noise (independent left and right samples, white noise only)",
+                Position::Top,
+                self.noise_stereo_button.view(),
+            );
+            let semitone_button = tooltip(
+                theme,
+                "Toggle showing frequency ratio as semitones and cents",
+                Position::Top,
+                Button::new(
+                    Text::new(if self.semitone_frequency { "ST" } else { "RAT" })
+                        .font(theme.font_regular())
+                        .height(Length::Fixed(LINE_HEIGHT.into()))
+                        .width(Length::Fixed(f32::from(LINE_HEIGHT * 2)))
+                        .horizontal_alignment(Horizontal::Center),
+                )
+                .padding(0)
+                .style(ButtonStyle::Value)
+                .on_press(Message::ToggleOperatorSemitoneFrequency(self.index)),
+            );
+            let listen_button = tooltip(
+                theme,
+                "Listen: route only this operator to output with full mix",
+                Position::Top,
+                Button::new(
+                    Text::new("L")
+                        .font(theme.font_regular())
+                        .height(Length::Fixed(LINE_HEIGHT.into()))
+                        .width(Length::Fixed(LINE_HEIGHT.into()))
+                        .horizontal_alignment(Horizontal::Center),
+                )
+                .padding(0)
+                .style(if self.listening {
+                    ButtonStyle::Regular
+                } else {
+                    ButtonStyle::Value
+                })
+                .on_press(Message::ToggleOperatorListen(self.index)),
+            );
+            let overload_indicator: Element<Message, Theme> =
+                if self.modulation_input_peak > MODULATION_INPUT_OVERLOAD_THRESHOLD {
+                    tooltip(
+                        theme,
+                        format!(
+                            "Modulation input overload. Peak: {:.1} radians",
+                            self.modulation_input_peak
+                        ),
+                        Position::Top,
+                        Text::new("HOT")
+                            .font(theme.font_bold())
+                            .style(TextStyle::Warning)
+                            .height(Length::Fixed(LINE_HEIGHT.into()))
+                            .width(Length::Fixed(f32::from(LINE_HEIGHT * 2)))
+                            .horizontal_alignment(Horizontal::Center),
+                    )
+                    .into()
+                } else {
+                    Space::with_width(Length::Fixed(f32::from(LINE_HEIGHT * 2))).into()
+                };
 
             Container::new(
                 Column::new()
@@ -121,40 +343,203 @@ impl OperatorWidgets {
                         Row::new()
                             .width(Length::Fill)
                             .push(Space::with_width(Length::Fixed(f32::from(LINE_HEIGHT))))
-                            .push(mute_button),
+                            .push(mute_button)
+                            .push(noise_stereo_button)
+                            .push(Space::with_width(Length::Fill))
+                            .push(overload_indicator)
+                            .push(Space::with_width(Length::Fill))
+                            .push(listen_button)
+                            .push(semitone_button)
+                            .push(Space::with_width(Length::Fixed(f32::from(LINE_HEIGHT)))),
                     )
                     .push(
-                        Text::new(format!("OP {}", self.index + 1))
-                            .size(FONT_SIZE + FONT_SIZE / 2)
-                            .height(Length::Fixed(f32::from(FONT_SIZE + FONT_SIZE / 2)))
-                            .font(theme.font_heading())
-                            .horizontal_alignment(Horizontal::Center),
+                        Row::new()
+                            .width(Length::Fill)
+                            .align_items(Alignment::Center)
+                            .push(Space::with_width(Length::Fixed(f32::from(LINE_HEIGHT * 3))))
+                            .push(
+                                Text::new(format!("OP {}", self.index + 1))
+                                    .size(FONT_SIZE + FONT_SIZE / 2)
+                                    .height(Length::Fixed(f32::from(FONT_SIZE + FONT_SIZE / 2)))
+                                    .font(theme.font_heading())
+                                    .width(Length::Fill)
+                                    .horizontal_alignment(Horizontal::Center),
+                            )
+                            .push(self.randomize_button(theme))
+                            .push(self.collapse_button(theme))
+                            .push(Space::with_width(Length::Fixed(f32::from(LINE_HEIGHT)))),
                     )
                     .push(Space::with_height(Length::Fixed(f32::from(
                         LINE_HEIGHT / 2,
                     ))))
-                    .push(self.wave_display.view(theme)),
+                    .push(if self.alternative_controls {
+                        self.velocity_curve.view(theme)
+                    } else {
+                        self.wave_display.view(theme)
+                    }),
             )
             .width(Length::Fixed(f32::from(LINE_HEIGHT * 8)))
             .height(Length::Fixed(f32::from(LINE_HEIGHT * 7)))
         };
 
-        let group_1 = container_l2(
-            Row::new()
-                .push(container_l3(self.wave_type.view(theme)))
-                .push(space_l3())
-                .push(container_l3(self.volume.view(theme)))
-                .push(space_l3())
-                .push(container_l3(self.panning.view(theme))),
-        );
+        let group_1 = if self.alternative_controls {
+            let wavetable = {
+                let title = tooltip(
+                    theme,
+                    "Wavetable loaded from WAV file. Replaces selected wave type",
+                    Position::Top,
+                    Text::new("TABLE")
+                        .horizontal_alignment(Horizontal::Center)
+                        .font(theme.font_bold())
+                        .height(Length::Fixed(LINE_HEIGHT.into()))
+                        .width(LINE_HEIGHT * 4),
+                );
+
+                let (name, button) = match self.wavetable_name.as_ref() {
+                    Some(name) => (
+                        name.clone(),
+                        Button::new(
+                            Text::new("CLEAR")
+                                .font(theme.font_regular())
+                                .height(Length::Fixed(LINE_HEIGHT.into()))
+                                .width(Length::Fixed(f32::from(LINE_HEIGHT * 3)))
+                                .horizontal_alignment(Horizontal::Center),
+                        )
+                        .on_press(Message::ClearWavetable(self.index)),
+                    ),
+                    None => (
+                        "-".into(),
+                        Button::new(
+                            Text::new("LOAD")
+                                .font(theme.font_regular())
+                                .height(Length::Fixed(LINE_HEIGHT.into()))
+                                .width(Length::Fixed(f32::from(LINE_HEIGHT * 3)))
+                                .horizontal_alignment(Horizontal::Center),
+                        )
+                        .on_press(Message::LoadWavetable(self.index)),
+                    ),
+                };
 
-        let routing_group = {
+                Container::new(
+                    Column::new()
+                        .width(Length::Fixed(f32::from(LINE_HEIGHT * 4)))
+                        .align_items(Alignment::Center)
+                        .push(title)
+                        .push(Space::with_height(LINE_HEIGHT / 2))
+                        .push(button.padding(0).style(ButtonStyle::Value))
+                        .push(Space::with_height(LINE_HEIGHT / 2))
+                        .push(
+                            Text::new(name)
+                                .font(theme.font_regular())
+                                .height(Length::Fixed(LINE_HEIGHT.into()))
+                                .width(LINE_HEIGHT * 4)
+                                .horizontal_alignment(Horizontal::Center),
+                        ),
+                )
+                .height(Length::Fixed(f32::from(LINE_HEIGHT * 6)))
+            };
+
+            container_l2(
+                density,
+                Row::new()
+                    .push(container_l3(density, self.phase_distortion.view(theme)))
+                    .push(space_l3())
+                    .push(container_l3(density, wavetable))
+                    .push(space_l3())
+                    .push(container_l3(density, self.wavetable_position.view(theme))),
+            )
+        } else {
+            container_l2(
+                density,
+                Row::new()
+                    .push(container_l3(density, self.wave_type.view(theme)))
+                    .push(space_l3())
+                    .push(container_l3(density, self.volume.view(theme)))
+                    .push(space_l3())
+                    .push(container_l3(density, self.panning.view(theme))),
+            )
+        };
+
+        let routing_group = if self.alternative_controls {
             let mut group = Row::new()
-                .push(container_l3(self.mix.view(theme)))
+                .push(container_l3(density, self.spread.view(theme)))
                 .push(space_l3());
 
             if let Some(mod_index) = self.mod_index.as_ref() {
-                group = group.push(container_l3(mod_index.view(theme)));
+                group = group.push(container_l3(density, mod_index.view(theme)));
+            } else {
+                group = group.push(Space::with_width(Length::Fixed(f32::from(LINE_HEIGHT * 5))));
+            }
+
+            group = group.push(space_l3());
+
+            match (
+                self.cross_feedback_target_steps(),
+                self.cross_feedback.as_ref(),
+            ) {
+                (Some(steps), Some(cross_feedback)) => {
+                    let parameter = Parameter::Operator(
+                        self.index as u8,
+                        OperatorParameter::CrossFeedbackTarget,
+                    )
+                    .into();
+
+                    let title = tooltip(
+                        theme,
+                        "Operator receiving cross feedback. Input is delayed by one sample",
+                        Position::Top,
+                        Text::new("X TGT")
+                            .horizontal_alignment(Horizontal::Center)
+                            .font(theme.font_bold())
+                            .height(Length::Fixed(LINE_HEIGHT.into()))
+                            .width(LINE_HEIGHT * 3),
+                    );
+
+                    let picker = PickList::new(
+                        steps,
+                        Some(map_patch_value_to_step(steps, self.cross_feedback_target)),
+                        move |option| {
+                            let v = map_step_to_patch_value(steps, option);
+
+                            Message::ChangeSingleParameterImmediate(parameter, v)
+                        },
+                    )
+                    .font(theme.font_regular())
+                    .text_size(FONT_SIZE)
+                    .padding(theme.picklist_padding())
+                    .width(Length::Fixed(f32::from(LINE_HEIGHT * 3)));
+
+                    let target = Container::new(
+                        Column::new()
+                            .width(Length::Fixed(f32::from(LINE_HEIGHT * 3)))
+                            .align_items(Alignment::Center)
+                            .push(title)
+                            .push(Space::with_height(LINE_HEIGHT / 2))
+                            .push(picker),
+                    )
+                    .height(Length::Fixed(f32::from(LINE_HEIGHT * 6)));
+
+                    group = group
+                        .push(container_l3(density, target))
+                        .push(space_l3())
+                        .push(container_l3(density, cross_feedback.view(theme)));
+                }
+                _ => {
+                    group = group
+                        .push(Space::with_width(Length::Fixed(f32::from(LINE_HEIGHT * 3))))
+                        .push(space_l3())
+                        .push(container_l3(density, Space::with_width(LINE_HEIGHT * 4)));
+                }
+            }
+
+            container_l2(density, group)
+        } else {
+            let mut group = Row::new()
+                .push(container_l3(density, self.mix.view(theme)))
+                .push(space_l3());
+
+            if let Some(mod_index) = self.mod_index.as_ref() {
+                group = group.push(container_l3(density, mod_index.view(theme)));
             } else {
                 group = group.push(Space::with_width(Length::Fixed(f32::from(LINE_HEIGHT * 5))));
             }
@@ -163,13 +548,13 @@ impl OperatorWidgets {
 
             match self.mod_target.as_ref() {
                 Some(ModTargetPicker::Operator2(picker)) => {
-                    group = group.push(container_l3(picker.view(theme)))
+                    group = group.push(container_l3(density, picker.view(theme)))
                 }
                 Some(ModTargetPicker::Operator3(picker)) => {
-                    group = group.push(container_l3(picker.view(theme)))
+                    group = group.push(container_l3(density, picker.view(theme)))
                 }
                 Some(ModTargetPicker::Operator4(picker)) => {
-                    group = group.push(container_l3(picker.view(theme)))
+                    group = group.push(container_l3(density, picker.view(theme)))
                 }
                 None => {
                     group = group.push(Space::with_width(Length::Fixed(f32::from(LINE_HEIGHT * 3))))
@@ -177,36 +562,157 @@ impl OperatorWidgets {
             }
 
             group = group.push(space_l3());
-            group = group.push(container_l3(self.feedback.view(theme)));
+            group = group.push(container_l3(density, self.feedback.view(theme)));
 
-            container_l2(group)
+            container_l2(density, group)
         };
 
         let frequency_group = container_l2(
+            density,
             Row::new()
-                .push(container_l3(self.frequency_ratio.view(theme)))
+                .push(container_l3(density, self.frequency_ratio.view(theme)))
                 .push(space_l3())
-                .push(container_l3(self.frequency_free.view(theme)))
+                .push(container_l3(density, self.frequency_free.view(theme)))
                 .push(space_l3())
-                .push(container_l3(self.frequency_fine.view(theme))),
+                .push(container_l3(density, self.frequency_fine.view(theme))),
         );
 
         let end = if self.alternative_controls {
+            let release_mode_and_velocity_range = {
+                let parameter =
+                    Parameter::Operator(self.index as u8, OperatorParameter::ReleaseMode).into();
+
+                let title = tooltip(
+                    theme,
+                    "Envelope release mode\n\nCUR = release from current level\nLIN = release linearly from current level\nATK = complete attack before releasing",
+                    Position::Top,
+                    Text::new("RELEASE")
+                        .horizontal_alignment(Horizontal::Center)
+                        .font(theme.font_bold())
+                        .height(Length::Fixed(LINE_HEIGHT.into()))
+                        .width(LINE_HEIGHT * 4),
+                );
+
+                let picker = PickList::new(
+                    RELEASE_MODE_STEPS,
+                    Some(OperatorReleaseModeValue::new_from_patch(self.release_mode).get()),
+                    move |option| {
+                        let v = OperatorReleaseModeValue::new_from_audio(option).to_patch();
+
+                        Message::ChangeSingleParameterImmediate(parameter, v)
+                    },
+                )
+                .font(theme.font_regular())
+                .text_size(FONT_SIZE)
+                .padding(theme.picklist_padding())
+                .width(Length::Fixed(f32::from(LINE_HEIGHT * 3)));
+
+                let velocity_range_title = tooltip(
+                    theme,
+                    "Velocity range in which operator sounds, evaluated at note-on\n\nDrag to move closest bound",
+                    Position::Top,
+                    Text::new("VELS")
+                        .horizontal_alignment(Horizontal::Center)
+                        .font(theme.font_bold())
+                        .height(Length::Fixed(LINE_HEIGHT.into()))
+                        .width(LINE_HEIGHT * 4),
+                );
+
+                Container::new(
+                    Column::new()
+                        .width(Length::Fixed(f32::from(LINE_HEIGHT * 4)))
+                        .align_items(Alignment::Center)
+                        .push(title)
+                        .push(Space::with_height(LINE_HEIGHT / 2))
+                        .push(picker)
+                        .push(Space::with_height(LINE_HEIGHT / 2))
+                        .push(velocity_range_title)
+                        .push(Space::with_height(LINE_HEIGHT / 2))
+                        .push(self.velocity_range.view()),
+                )
+                .height(Length::Fixed(f32::from(LINE_HEIGHT * 6)))
+            };
+
+            let reference_note_and_key_range = {
+                let title = tooltip(
+                    theme,
+                    "Reference note for frequency in Hz shown in frequency knob tooltips",
+                    Position::Top,
+                    Text::new("REF")
+                        .horizontal_alignment(Horizontal::Center)
+                        .font(theme.font_bold())
+                        .height(Length::Fixed(LINE_HEIGHT.into()))
+                        .width(LINE_HEIGHT * 3),
+                );
+
+                let picker = PickList::new(
+                    FrequencyReferenceNote::STEPS,
+                    Some(self.frequency_reference_note),
+                    Message::SetFrequencyReferenceNote,
+                )
+                .font(theme.font_regular())
+                .text_size(FONT_SIZE)
+                .padding(theme.picklist_padding())
+                .width(Length::Fixed(f32::from(LINE_HEIGHT * 3)));
+
+                let key_range_title = tooltip(
+                    theme,
+                    "Key range in which operator sounds, evaluated at note-on\n\nDrag to move closest bound",
+                    Position::Top,
+                    Text::new("KEYS")
+                        .horizontal_alignment(Horizontal::Center)
+                        .font(theme.font_bold())
+                        .height(Length::Fixed(LINE_HEIGHT.into()))
+                        .width(LINE_HEIGHT * 3),
+                );
+
+                Container::new(
+                    Column::new()
+                        .width(Length::Fixed(f32::from(LINE_HEIGHT * 3)))
+                        .align_items(Alignment::Center)
+                        .push(title)
+                        .push(Space::with_height(LINE_HEIGHT / 2))
+                        .push(picker)
+                        .push(Space::with_height(LINE_HEIGHT / 2))
+                        .push(key_range_title)
+                        .push(Space::with_height(LINE_HEIGHT / 2))
+                        .push(self.key_range.view()),
+                )
+                .height(Length::Fixed(f32::from(LINE_HEIGHT * 6)))
+            };
+
             container_l2(
+                density,
                 Row::new()
                     .push(space_l3())
                     .push(if self.index > 0 {
-                        container_l3(self.mod_out_velocity_sensitivity.view(theme))
+                        container_l3(density, self.mod_out_velocity_sensitivity.view(theme))
                     } else {
-                        container_l3(Space::with_width(LINE_HEIGHT * 4))
+                        container_l3(density, Space::with_width(LINE_HEIGHT * 4))
                     })
                     .push(space_l3())
-                    .push(container_l3(self.feedback_velocity_sensitivity.view(theme)))
-                    .push(space_l3().width(LINE_HEIGHT * 15)),
+                    .push(container_l3(
+                        density,
+                        self.feedback_velocity_sensitivity.view(theme),
+                    ))
+                    .push(space_l3())
+                    .push(container_l3(density, release_mode_and_velocity_range))
+                    .push(space_l3())
+                    .push(container_l3(
+                        density,
+                        self.peak_velocity_sensitivity.view(theme),
+                    ))
+                    .push(space_l3())
+                    .push(container_l3(
+                        density,
+                        self.sustain_velocity_sensitivity.view(theme),
+                    ))
+                    .push(space_l3())
+                    .push(container_l3(density, reference_note_and_key_range)),
             )
         } else {
-            container_l2(self.envelope.view(theme))
-                .height(Length::Fixed(f32::from(LINE_HEIGHT * 8)))
+            container_l2(density, self.envelope.view(theme, density))
+                .height(Length::Fixed(f32::from(density.panel_row_height())))
                 .into()
         };
 
@@ -214,13 +720,82 @@ impl OperatorWidgets {
             Row::new()
                 .push(heading)
                 .push(group_1)
-                .push(space_l2())
+                .push(space_l2(density))
                 .push(routing_group)
-                .push(space_l2())
+                .push(space_l2(density))
                 .push(frequency_group)
-                .push(space_l2())
+                .push(space_l2(density))
                 .push(end),
         )
         .into()
     }
+
+    fn view_collapsed(&self, theme: &Theme, density: GuiDensity) -> Element<Message, Theme> {
+        let mute_button = tooltip(theme, "Toggle mute", Position::Top, self.mute_button.view());
+
+        container_l1(
+            Row::new()
+                .width(Length::Fill)
+                .height(Length::Fixed(f32::from(density.line_height() * 2)))
+                .align_items(Alignment::Center)
+                .push(Space::with_width(Length::Fixed(f32::from(LINE_HEIGHT))))
+                .push(mute_button)
+                .push(Space::with_width(Length::Fixed(f32::from(LINE_HEIGHT))))
+                .push(
+                    Text::new(format!("OP {}", self.index + 1))
+                        .size(FONT_SIZE + FONT_SIZE / 2)
+                        .height(Length::Fixed(f32::from(FONT_SIZE + FONT_SIZE / 2)))
+                        .font(theme.font_heading()),
+                )
+                .push(Space::with_width(Length::Fill))
+                .push(self.randomize_button(theme))
+                .push(self.collapse_button(theme))
+                .push(Space::with_width(Length::Fixed(f32::from(LINE_HEIGHT)))),
+        )
+        .into()
+    }
+
+    fn randomize_button(&self, theme: &Theme) -> Element<Message, Theme> {
+        tooltip(
+            theme,
+            "Randomize operator parameters",
+            Position::Top,
+            Button::new(
+                Text::new("?")
+                    .font(theme.font_regular())
+                    .height(Length::Fixed(LINE_HEIGHT.into()))
+                    .width(Length::Fixed(LINE_HEIGHT.into()))
+                    .horizontal_alignment(Horizontal::Center),
+            )
+            .padding(0)
+            .style(ButtonStyle::Value)
+            .on_press(Message::RandomizeParameters(RandomizeScope::Operator(
+                self.index,
+            ))),
+        )
+        .into()
+    }
+
+    fn collapse_button(&self, theme: &Theme) -> Element<Message, Theme> {
+        tooltip(
+            theme,
+            if self.collapsed {
+                "Expand operator"
+            } else {
+                "Collapse operator to save space"
+            },
+            Position::Top,
+            Button::new(
+                Text::new(if self.collapsed { "+" } else { "−" })
+                    .font(theme.font_regular())
+                    .height(Length::Fixed(LINE_HEIGHT.into()))
+                    .width(Length::Fixed(LINE_HEIGHT.into()))
+                    .horizontal_alignment(Horizontal::Center),
+            )
+            .padding(0)
+            .style(ButtonStyle::Value)
+            .on_press(Message::ToggleOperatorCollapsed(self.index)),
+        )
+        .into()
+    }
 }
@@ -3,44 +3,122 @@ use std::fmt::Display;
 use compact_str::CompactString;
 use iced_baseview::alignment::Horizontal;
 use iced_baseview::widget::tooltip::Position;
-use iced_baseview::widget::{PickList, Row};
+use iced_baseview::widget::{Button, PickList, Row, TextInput};
 use iced_baseview::{
     widget::Column, widget::Container, widget::Space, widget::Text, Element, Length,
 };
 
+use crate::parameters::list::{MasterParameter, Parameter};
+use crate::parameters::mod_snapshot::{ModSnapshotSlotValue, MOD_SNAPSHOT_SLOT_STEPS};
+use crate::parameters::scale_quantization::{
+    ScaleRootValue, ScaleValue, SCALE_ROOT_STEPS, SCALE_STEPS,
+};
+use crate::parameters::ParameterValue;
+use crate::sync::{PatchCategory, RandomizeScope, PATCH_CATEGORIES};
+
 use super::boolean_button::{voice_mode_button, BooleanButton};
 use super::common::tooltip;
+use super::style::button::ButtonStyle;
 use super::LINE_HEIGHT;
-use super::{style::Theme, GuiSyncHandle, Message, FONT_SIZE};
+use super::{style::Theme, GuiSyncHandle, Message, ModalAction, FONT_SIZE};
 
 const ACTIONS: &[Action] = &[
     Action::RenamePatch,
+    Action::SetPatchAuthor,
+    Action::SetPatchLicense,
     Action::SavePatch,
+    Action::SavePatchWithPreview,
     Action::SaveBank,
+    Action::ExportPatchSheet,
     Action::OpenPatchesOrBank,
     Action::ClearPatch,
     Action::ClearBank,
+    Action::ShowBankOverview,
+    Action::RandomizePatch,
+    Action::RandomizeEnvelopes,
+    Action::RandomizeModMatrix,
+    Action::RandomizeLfos,
+    Action::MutatePatch,
+    Action::BreedPatches,
+    Action::StoreModSnapshot,
+    Action::StartChangeCapture,
+    Action::ExportChangeCapture,
+    Action::SetStartupPatches,
+    Action::ClearStartupPatches,
+    Action::TogglePatchAudition,
+    Action::Panic,
+    Action::ToggleStuckNoteWatchdog,
+    Action::ToggleAutoGain,
+    Action::ToggleMonoFoldDown,
+    Action::ToggleLfosCollapsed,
+    Action::TogglePerformanceView,
 ];
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Action {
     RenamePatch,
+    SetPatchAuthor,
+    SetPatchLicense,
     SavePatch,
+    SavePatchWithPreview,
     SaveBank,
+    ExportPatchSheet,
     OpenPatchesOrBank,
     ClearPatch,
     ClearBank,
+    ShowBankOverview,
+    RandomizePatch,
+    RandomizeEnvelopes,
+    RandomizeModMatrix,
+    RandomizeLfos,
+    MutatePatch,
+    BreedPatches,
+    StoreModSnapshot,
+    StartChangeCapture,
+    ExportChangeCapture,
+    SetStartupPatches,
+    ClearStartupPatches,
+    TogglePatchAudition,
+    Panic,
+    ToggleStuckNoteWatchdog,
+    ToggleAutoGain,
+    ToggleMonoFoldDown,
+    ToggleLfosCollapsed,
+    TogglePerformanceView,
 }
 
 impl Action {
     fn to_message(self) -> Message {
         match self {
             Self::RenamePatch => Message::RenamePatch,
+            Self::SetPatchAuthor => Message::SetPatchAuthor,
+            Self::SetPatchLicense => Message::SetPatchLicense,
             Self::SavePatch => Message::SavePatch,
+            Self::SavePatchWithPreview => Message::SavePatchWithPreview,
             Self::SaveBank => Message::SaveBank,
+            Self::ExportPatchSheet => Message::ExportPatchSheet,
             Self::OpenPatchesOrBank => Message::LoadBankOrPatch,
             Self::ClearPatch => Message::ClearPatch,
             Self::ClearBank => Message::ClearBank,
+            Self::ShowBankOverview => Message::ModalOpen(ModalAction::BankOverview),
+            Self::RandomizePatch => Message::RandomizeParameters(RandomizeScope::Patch),
+            Self::RandomizeEnvelopes => Message::RandomizeParameters(RandomizeScope::Envelopes),
+            Self::RandomizeModMatrix => Message::RandomizeParameters(RandomizeScope::ModMatrix),
+            Self::RandomizeLfos => Message::RandomizeParameters(RandomizeScope::Lfos),
+            Self::MutatePatch => Message::ModalOpen(ModalAction::MutatePatch),
+            Self::BreedPatches => Message::BreedPatches,
+            Self::StoreModSnapshot => Message::StoreModSnapshot,
+            Self::StartChangeCapture => Message::StartParameterChangeCapture,
+            Self::ExportChangeCapture => Message::ExportParameterChangeCapture,
+            Self::SetStartupPatches => Message::PickStartupPatches,
+            Self::ClearStartupPatches => Message::SetStartupPatches(None),
+            Self::TogglePatchAudition => Message::TogglePatchAudition,
+            Self::Panic => Message::Panic,
+            Self::ToggleStuckNoteWatchdog => Message::ToggleStuckNoteWatchdog,
+            Self::ToggleAutoGain => Message::ToggleAutoGain,
+            Self::ToggleMonoFoldDown => Message::ToggleMonoFoldDown,
+            Self::ToggleLfosCollapsed => Message::ToggleLfosCollapsed,
+            Self::TogglePerformanceView => Message::TogglePerformanceView,
         }
     }
 }
@@ -49,11 +127,34 @@ impl Display for Action {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::RenamePatch => write!(f, "RENAME PATCH"),
+            Self::SetPatchAuthor => write!(f, "SET PATCH AUTHOR"),
+            Self::SetPatchLicense => write!(f, "SET PATCH LICENSE"),
             Self::SavePatch => write!(f, "SAVE PATCH"),
+            Self::SavePatchWithPreview => write!(f, "SAVE PATCH WITH AUDIO PREVIEW"),
             Self::SaveBank => write!(f, "SAVE BANK"),
+            Self::ExportPatchSheet => write!(f, "EXPORT PATCH SHEET IMAGE"),
             Self::OpenPatchesOrBank => write!(f, "OPEN PATCHES/BANK"),
             Self::ClearPatch => write!(f, "CLEAR PATCH"),
             Self::ClearBank => write!(f, "CLEAR BANK"),
+            Self::ShowBankOverview => write!(f, "SHOW BANK OVERVIEW"),
+            Self::RandomizePatch => write!(f, "RANDOMIZE PATCH"),
+            Self::RandomizeEnvelopes => write!(f, "RANDOMIZE ENVELOPES"),
+            Self::RandomizeModMatrix => write!(f, "RANDOMIZE MOD MATRIX"),
+            Self::RandomizeLfos => write!(f, "RANDOMIZE LFOS"),
+            Self::MutatePatch => write!(f, "MUTATE PATCH.."),
+            Self::BreedPatches => write!(f, "BREED PATCHES.."),
+            Self::StoreModSnapshot => write!(f, "STORE MOD SNAPSHOT"),
+            Self::StartChangeCapture => write!(f, "START CHANGE CAPTURE"),
+            Self::ExportChangeCapture => write!(f, "EXPORT CHANGE CAPTURE"),
+            Self::SetStartupPatches => write!(f, "SET STARTUP BANK/PATCH"),
+            Self::ClearStartupPatches => write!(f, "CLEAR STARTUP BANK/PATCH"),
+            Self::TogglePatchAudition => write!(f, "TOGGLE PATCH PREVIEW NOTE"),
+            Self::Panic => write!(f, "PANIC: RELEASE ALL NOTES"),
+            Self::ToggleStuckNoteWatchdog => write!(f, "TOGGLE STUCK NOTE WATCHDOG"),
+            Self::ToggleAutoGain => write!(f, "TOGGLE AUTO-GAIN"),
+            Self::ToggleMonoFoldDown => write!(f, "TOGGLE MONO CHECK"),
+            Self::ToggleLfosCollapsed => write!(f, "SHOW/HIDE LFO SECTION"),
+            Self::TogglePerformanceView => write!(f, "TOGGLE PERFORMANCE VIEW"),
         }
     }
 }
@@ -62,6 +163,7 @@ impl Display for Action {
 struct Patch {
     index: usize,
     title: CompactString,
+    category: Option<PatchCategory>,
 }
 
 impl Display for Patch {
@@ -70,31 +172,105 @@ impl Display for Patch {
     }
 }
 
+/// Patch category to show in patch picker, or None for all patches
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct CategoryFilter(Option<PatchCategory>);
+
+impl Display for CategoryFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            Some(category) => write!(f, "{}", category),
+            None => write!(f, "ALL"),
+        }
+    }
+}
+
 pub struct PatchPicker {
+    all_patch_options: Vec<Patch>,
+    /// Patch options in selected category with names matching search text
     patch_options: Vec<Patch>,
+    category_filter_options: Vec<CategoryFilter>,
+    pub category_filter: Option<PatchCategory>,
+    pub search_text: String,
     patch_index: usize,
     pub voice_mode_button: BooleanButton,
+    pub scale: f32,
+    pub scale_root: f32,
+    pub mod_snapshot: f32,
+    /// All LFO modulation temporarily disabled for auditioning
+    pub lfos_disabled: bool,
 }
 
 impl PatchPicker {
     pub fn new<H: GuiSyncHandle>(sync_handle: &H) -> Self {
         let (patch_index, patch_names) = sync_handle.get_patches();
 
-        let patch_options = patch_names
+        let all_patch_options: Vec<Patch> = patch_names
             .into_iter()
+            .zip(sync_handle.get_patch_categories())
             .enumerate()
-            .map(|(index, title)| Patch { index, title })
+            .map(|(index, (title, category))| Patch {
+                index,
+                title,
+                category,
+            })
+            .collect();
+        let category_filter_options = ::std::iter::once(CategoryFilter(None))
+            .chain(PATCH_CATEGORIES.iter().map(|c| CategoryFilter(Some(*c))))
             .collect();
 
         let voice_mode_button = voice_mode_button(sync_handle);
+        let scale = sync_handle.get_parameter(Parameter::Master(MasterParameter::Scale).into());
+        let scale_root =
+            sync_handle.get_parameter(Parameter::Master(MasterParameter::ScaleRoot).into());
+        let mod_snapshot =
+            sync_handle.get_parameter(Parameter::Master(MasterParameter::ModSnapshot).into());
 
         Self {
-            patch_options,
+            patch_options: all_patch_options.clone(),
+            all_patch_options,
+            category_filter_options,
+            category_filter: None,
+            search_text: String::new(),
             patch_index,
             voice_mode_button,
+            scale,
+            scale_root,
+            mod_snapshot,
+            lfos_disabled: sync_handle.get_lfos_disabled(),
         }
     }
 
+    pub fn set_category_filter(&mut self, category_filter: Option<PatchCategory>) {
+        self.category_filter = category_filter;
+        self.update_patch_options();
+    }
+
+    pub fn set_search_text(&mut self, search_text: String) {
+        self.search_text = search_text;
+        self.update_patch_options();
+    }
+
+    /// Index of first patch matching category filter and search text
+    pub fn first_match(&self) -> Option<usize> {
+        self.patch_options.first().map(|patch| patch.index)
+    }
+
+    fn update_patch_options(&mut self) {
+        let category_filter = self.category_filter;
+        let search_text = self.search_text.trim().to_lowercase();
+
+        self.patch_options = self
+            .all_patch_options
+            .iter()
+            .filter(|patch| category_filter.is_none() || patch.category == category_filter)
+            .filter(|patch| {
+                search_text.is_empty() || patch.title.to_lowercase().contains(&search_text)
+            })
+            .cloned()
+            .collect();
+    }
+
     pub fn theme_changed(&mut self) {
         self.voice_mode_button.theme_changed();
     }
@@ -102,7 +278,7 @@ impl PatchPicker {
     pub fn view(&self, theme: &Theme) -> Element<Message, Theme> {
         let patch_picker = PickList::new(
             &self.patch_options[..],
-            Some(self.patch_options[self.patch_index].clone()),
+            Some(self.all_patch_options[self.patch_index].clone()),
             |option| Message::ChangePatch(option.index),
         )
         .font(theme.font_regular())
@@ -110,6 +286,34 @@ impl PatchPicker {
         .padding(theme.picklist_padding())
         .width(Length::Fill);
 
+        let search_input = tooltip(
+            theme,
+            "Filter patches by name. Press enter to select first match",
+            Position::Top,
+            TextInput::new("FIND..", &self.search_text)
+                .on_input(Message::ChangePatchSearchText)
+                .on_submit(Message::SelectFirstPatchSearchMatch)
+                .font(theme.font_regular())
+                .size(f32::from(FONT_SIZE))
+                .padding(theme.picklist_padding())
+                .width(Length::Fixed(f32::from(LINE_HEIGHT * 3))),
+        );
+
+        let category_filter_picker = tooltip(
+            theme,
+            "Patch category",
+            Position::Top,
+            PickList::new(
+                &self.category_filter_options[..],
+                Some(CategoryFilter(self.category_filter)),
+                |option| Message::ChangePatchCategoryFilter(option.0),
+            )
+            .font(theme.font_regular())
+            .text_size(FONT_SIZE)
+            .padding(theme.picklist_padding())
+            .width(Length::Fixed(f32::from(LINE_HEIGHT * 3 - LINE_HEIGHT / 2))),
+        );
+
         let action_picker = PickList::new(ACTIONS, None, Action::to_message)
             .font(theme.font_regular())
             .text_size(FONT_SIZE)
@@ -117,6 +321,66 @@ impl PatchPicker {
             .placeholder("ACTIONS..")
             .width(Length::Fill);
 
+        let scale_picker = tooltip(
+            theme,
+            "Quantize incoming notes to scale",
+            Position::Top,
+            PickList::new(
+                SCALE_STEPS,
+                Some(ScaleValue::new_from_patch(self.scale).get()),
+                |option| {
+                    Message::ChangeSingleParameterImmediate(
+                        Parameter::Master(MasterParameter::Scale).into(),
+                        ScaleValue::new_from_audio(option).to_patch(),
+                    )
+                },
+            )
+            .font(theme.font_regular())
+            .text_size(FONT_SIZE)
+            .padding(theme.picklist_padding())
+            .width(Length::Fixed(f32::from(LINE_HEIGHT * 4 - LINE_HEIGHT / 4))),
+        );
+
+        let scale_root_picker = tooltip(
+            theme,
+            "Scale root",
+            Position::Top,
+            PickList::new(
+                SCALE_ROOT_STEPS,
+                Some(ScaleRootValue::new_from_patch(self.scale_root).get()),
+                |option| {
+                    Message::ChangeSingleParameterImmediate(
+                        Parameter::Master(MasterParameter::ScaleRoot).into(),
+                        ScaleRootValue::new_from_audio(option).to_patch(),
+                    )
+                },
+            )
+            .font(theme.font_regular())
+            .text_size(FONT_SIZE)
+            .padding(theme.picklist_padding())
+            .width(Length::Fixed(f32::from(LINE_HEIGHT * 3 - LINE_HEIGHT / 2))),
+        );
+
+        let mod_snapshot_picker = tooltip(
+            theme,
+            "Mod snapshot (store with action menu)",
+            Position::Top,
+            PickList::new(
+                MOD_SNAPSHOT_SLOT_STEPS,
+                Some(ModSnapshotSlotValue::new_from_patch(self.mod_snapshot).get()),
+                |option| {
+                    Message::ChangeSingleParameterImmediate(
+                        Parameter::Master(MasterParameter::ModSnapshot).into(),
+                        ModSnapshotSlotValue::new_from_audio(option).to_patch(),
+                    )
+                },
+            )
+            .font(theme.font_regular())
+            .text_size(FONT_SIZE)
+            .padding(theme.picklist_padding())
+            .width(Length::Fixed(f32::from(LINE_HEIGHT * 3 - LINE_HEIGHT / 2))),
+        );
+
         let voice_mode_button = tooltip(
             theme,
             "Toggle polyphonic / monophonic voice mode",
@@ -124,22 +388,59 @@ impl PatchPicker {
             self.voice_mode_button.view(),
         );
 
+        let lfos_disabled_button = tooltip(
+            theme,
+            if self.lfos_disabled {
+                "LFO modulation is off (not saved in patch). Click to turn it back on"
+            } else {
+                "Temporarily turn off all LFO modulation to hear unmodulated patch"
+            },
+            Position::Top,
+            Button::new(
+                Text::new(if self.lfos_disabled { "OFF" } else { "LFO" })
+                    .font(theme.font_regular())
+                    .height(Length::Fixed(LINE_HEIGHT.into()))
+                    .width(Length::Fixed(f32::from(LINE_HEIGHT * 2)))
+                    .horizontal_alignment(Horizontal::Center),
+            )
+            .padding(0)
+            .style(if self.lfos_disabled {
+                ButtonStyle::Regular
+            } else {
+                ButtonStyle::Value
+            })
+            .on_press(Message::ToggleLfosDisabled),
+        );
+
         Container::new(
             Column::new()
-                .push(action_picker)
+                .push(
+                    Row::new()
+                        .push(action_picker)
+                        .push(Space::with_width(LINE_HEIGHT / 2))
+                        .push(scale_root_picker)
+                        .push(Space::with_width(LINE_HEIGHT / 4))
+                        .push(scale_picker),
+                )
                 .push(Space::with_height(Length::Fixed(f32::from(
                     LINE_HEIGHT / 2 + LINE_HEIGHT / 4,
                 ))))
                 .push(
                     Row::new()
-                        .push(Column::new().width(LINE_HEIGHT * 3))
+                        .push(mod_snapshot_picker)
+                        .push(Space::with_width(LINE_HEIGHT / 2))
                         .push(
                             Text::new("Patch")
                                 .size(f32::from(FONT_SIZE * 3 / 2))
                                 .height(Length::Fixed(f32::from(FONT_SIZE * 3 / 2)))
                                 .font(theme.font_heading())
                                 .horizontal_alignment(Horizontal::Center)
-                                .width(LINE_HEIGHT * 6),
+                                .width(LINE_HEIGHT * 4),
+                        )
+                        .push(
+                            Column::new()
+                                .push(Space::with_height(3))
+                                .push(lfos_disabled_button),
                         )
                         .push(Space::with_width(LINE_HEIGHT / 2))
                         .push(
@@ -151,7 +452,14 @@ impl PatchPicker {
                 .push(Space::with_height(Length::Fixed(f32::from(
                     LINE_HEIGHT / 2 + LINE_HEIGHT / 4,
                 ))))
-                .push(patch_picker),
+                .push(
+                    Row::new()
+                        .push(category_filter_picker)
+                        .push(Space::with_width(LINE_HEIGHT / 4))
+                        .push(search_input)
+                        .push(Space::with_width(LINE_HEIGHT / 4))
+                        .push(patch_picker),
+                ),
         )
         .width(Length::Fixed(f32::from(LINE_HEIGHT * 12)))
         .height(Length::Fixed(f32::from(LINE_HEIGHT * 6)))
@@ -0,0 +1,460 @@
+//! Parameter sheet of current patch rendered to PNG, e.g., for documenting
+//! patches and sharing them online
+//!
+//! iced canvas geometry can only be drawn to a window surface, so the sheet
+//! is drawn offscreen with tiny-skia, using the same fonts and theme colors
+//! as the interface.
+
+use ab_glyph::{point, Font, FontRef, PxScale, ScaleFont};
+use anyhow::Context;
+use iced_baseview::Color;
+use tiny_skia::{Paint, PathBuilder, Pixmap, PremultipliedColorU8, Rect, Stroke, Transform};
+
+use crate::common::{NUM_LFOS, NUM_OPERATORS};
+use crate::parameters::{OperatorParameter, Parameter};
+use crate::sync::inspect::{inspect_patch_bytes, PatchInspection};
+
+use super::style::Theme;
+use super::{OPEN_SANS_BYTES_BOLD, OPEN_SANS_BYTES_SEMI_BOLD};
+
+const COLUMNS: usize = 4;
+const PANEL_WIDTH: f32 = 300.0;
+const PADDING: f32 = 16.0;
+const ROW_HEIGHT: f32 = 16.0;
+const FONT_SIZE: f32 = 12.0;
+const HEADING_FONT_SIZE: f32 = 14.0;
+const TITLE_FONT_SIZE: f32 = 24.0;
+const ENVELOPE_HEIGHT: f32 = 48.0;
+
+const WIDTH: f32 = COLUMNS as f32 * (PANEL_WIDTH + PADDING) + PADDING;
+
+#[derive(Debug, Clone)]
+pub struct Appearance {
+    pub background_color: Color,
+    pub panel_color: Color,
+    pub border_color: Color,
+    pub text_color: Color,
+    /// Color of parameters that have their default value
+    pub default_value_color: Color,
+    pub envelope_color: Color,
+    pub warning_color: Color,
+}
+
+pub trait StyleSheet {
+    fn appearance(&self) -> Appearance;
+}
+
+struct Panel {
+    title: String,
+    /// Attack duration, decay duration, sustain volume and release duration
+    /// patch values
+    envelope: Option<[f32; 4]>,
+    rows: Vec<PanelRow>,
+}
+
+impl Panel {
+    fn new(title: String) -> Self {
+        Self {
+            title,
+            envelope: None,
+            rows: Vec::new(),
+        }
+    }
+
+    fn height(&self) -> f32 {
+        let envelope_height = if self.envelope.is_some() {
+            ENVELOPE_HEIGHT + PADDING / 2.0
+        } else {
+            0.0
+        };
+
+        PADDING + ROW_HEIGHT * 1.5 + envelope_height + self.rows.len() as f32 * ROW_HEIGHT + PADDING
+    }
+}
+
+struct PanelRow {
+    name: String,
+    value: String,
+    is_default: bool,
+}
+
+/// Render parameter sheet of patch, given in fxp format
+pub fn render_patch_sheet_png(patch_bytes: &[u8], theme: Theme) -> anyhow::Result<Vec<u8>> {
+    let patch = inspect_patch_bytes(patch_bytes)?;
+
+    let regular = FontRef::try_from_slice(OPEN_SANS_BYTES_SEMI_BOLD)?;
+    let bold = FontRef::try_from_slice(OPEN_SANS_BYTES_BOLD)?;
+
+    let mut sheet = Sheet::new(&patch, theme.appearance(), regular, bold)?;
+
+    sheet.draw(&patch);
+
+    sheet.pixmap.encode_png().context("encode png")
+}
+
+fn patch_panels(patch: &PatchInspection) -> Vec<Panel> {
+    let mut panels = (0..NUM_OPERATORS)
+        .map(|i| Panel::new(format!("OPERATOR {}", i + 1)))
+        .chain(::std::iter::once(Panel::new("MASTER".into())))
+        .chain((0..NUM_LFOS).map(|i| Panel::new(format!("LFO {}", i + 1))))
+        .collect::<Vec<_>>();
+
+    for p in patch.parameters.iter() {
+        let panel_index = match p.parameter {
+            Parameter::None => continue,
+            Parameter::Operator(index, _) => index as usize,
+            Parameter::Master(_) => NUM_OPERATORS,
+            Parameter::Lfo(index, _) => NUM_OPERATORS + 1 + index as usize,
+        };
+
+        panels[panel_index].rows.push(PanelRow {
+            name: p.name.to_string(),
+            value: p.value.to_string(),
+            is_default: p.is_default,
+        });
+    }
+
+    for (index, panel) in panels.iter_mut().take(NUM_OPERATORS).enumerate() {
+        let get = |parameter| {
+            patch
+                .parameters
+                .iter()
+                .find(|p| p.parameter == Parameter::Operator(index as u8, parameter))
+                .map(|p| p.patch_value)
+                .unwrap_or_default()
+        };
+
+        panel.envelope = Some([
+            get(OperatorParameter::AttackDuration),
+            get(OperatorParameter::DecayDuration),
+            get(OperatorParameter::SustainVolume),
+            get(OperatorParameter::ReleaseDuration),
+        ]);
+    }
+
+    panels
+}
+
+struct Sheet<'a> {
+    pixmap: Pixmap,
+    appearance: Appearance,
+    regular: FontRef<'a>,
+    bold: FontRef<'a>,
+    panels: Vec<Panel>,
+    header_height: f32,
+}
+
+impl<'a> Sheet<'a> {
+    fn new(
+        patch: &PatchInspection,
+        appearance: Appearance,
+        regular: FontRef<'a>,
+        bold: FontRef<'a>,
+    ) -> anyhow::Result<Self> {
+        let panels = patch_panels(patch);
+
+        let header_height =
+            PADDING + TITLE_FONT_SIZE * 1.5 + ROW_HEIGHT * (2 + patch.warnings.len()) as f32;
+
+        let mut column_heights = [header_height; COLUMNS];
+
+        for panel in panels.iter() {
+            let column = shortest_column(&column_heights);
+
+            column_heights[column] += panel.height() + PADDING;
+        }
+
+        let height = column_heights.into_iter().fold(0.0f32, f32::max);
+
+        let pixmap = Pixmap::new(WIDTH as u32, height.ceil() as u32)
+            .context("create pixmap for patch sheet")?;
+
+        Ok(Self {
+            pixmap,
+            appearance,
+            regular,
+            bold,
+            panels,
+            header_height,
+        })
+    }
+
+    fn draw(&mut self, patch: &PatchInspection) {
+        self.pixmap
+            .fill(to_skia_color(self.appearance.background_color));
+
+        self.draw_header(patch);
+
+        let panels = ::std::mem::take(&mut self.panels);
+        let mut column_heights = [self.header_height; COLUMNS];
+
+        for panel in panels.iter() {
+            let column = shortest_column(&column_heights);
+            let x = PADDING + column as f32 * (PANEL_WIDTH + PADDING);
+
+            self.draw_panel(panel, x, column_heights[column]);
+
+            column_heights[column] += panel.height() + PADDING;
+        }
+    }
+
+    fn draw_header(&mut self, patch: &PatchInspection) {
+        let appearance = self.appearance.clone();
+
+        let mut y = PADDING + TITLE_FONT_SIZE;
+
+        self.draw_text(
+            &patch.name,
+            PADDING,
+            y,
+            TITLE_FONT_SIZE,
+            true,
+            appearance.text_color,
+        );
+
+        let details = [
+            patch.category.map(|c| c.to_string()),
+            Some(patch.metadata.author.to_string()).filter(|s| !s.is_empty()),
+            Some(patch.metadata.license.to_string()).filter(|s| !s.is_empty()),
+        ]
+        .into_iter()
+        .flatten()
+        .chain(
+            patch
+                .wavetables
+                .iter()
+                .map(|(i, name)| format!("Operator {} wavetable: {}", i + 1, name)),
+        )
+        .collect::<Vec<_>>()
+        .join("  |  ");
+
+        y += ROW_HEIGHT * 1.5;
+
+        self.draw_text(
+            &details,
+            PADDING,
+            y,
+            FONT_SIZE,
+            false,
+            appearance.default_value_color,
+        );
+
+        for warning in patch.warnings.iter() {
+            y += ROW_HEIGHT;
+
+            self.draw_text(
+                &format!("Warning: {}", warning),
+                PADDING,
+                y,
+                FONT_SIZE,
+                false,
+                appearance.warning_color,
+            );
+        }
+    }
+
+    fn draw_panel(&mut self, panel: &Panel, x: f32, y: f32) {
+        let appearance = self.appearance.clone();
+
+        if let Some(rect) = Rect::from_xywh(x, y, PANEL_WIDTH, panel.height()) {
+            let mut paint = Paint::default();
+
+            paint.set_color(to_skia_color(appearance.panel_color));
+
+            self.pixmap
+                .fill_rect(rect, &paint, Transform::identity(), None);
+
+            let path = PathBuilder::from_rect(rect);
+
+            paint.set_color(to_skia_color(appearance.border_color));
+
+            self.pixmap.stroke_path(
+                &path,
+                &paint,
+                &Stroke::default(),
+                Transform::identity(),
+                None,
+            );
+        }
+
+        let left = x + PADDING;
+        let right = x + PANEL_WIDTH - PADDING;
+
+        let mut y = y + PADDING + HEADING_FONT_SIZE;
+
+        self.draw_text(
+            &panel.title,
+            left,
+            y,
+            HEADING_FONT_SIZE,
+            true,
+            appearance.text_color,
+        );
+
+        y += ROW_HEIGHT / 2.0;
+
+        if let Some(envelope) = panel.envelope {
+            self.draw_envelope(envelope, left, y, right - left);
+
+            y += ENVELOPE_HEIGHT + PADDING / 2.0;
+        }
+
+        for row in panel.rows.iter() {
+            y += ROW_HEIGHT;
+
+            let color = if row.is_default {
+                appearance.default_value_color
+            } else {
+                appearance.text_color
+            };
+
+            let value_width = self.text_width(&row.value, FONT_SIZE, false);
+
+            self.draw_text(&row.name, left, y, FONT_SIZE, false, color);
+            self.draw_text(&row.value, right - value_width, y, FONT_SIZE, false, color);
+        }
+    }
+
+    /// Draw ADSR shape with segment widths proportional to patch values
+    fn draw_envelope(&mut self, envelope: [f32; 4], x: f32, y: f32, width: f32) {
+        let [attack, decay, sustain, release] = envelope;
+
+        // Keep a sustain segment and make zero durations visible
+        let total = attack + decay + release + 1.0;
+        let segment = |duration: f32| (duration + 0.02) / (total + 0.06) * width;
+
+        let bottom = y + ENVELOPE_HEIGHT;
+        let sustain_y = bottom - sustain * ENVELOPE_HEIGHT;
+
+        let attack_x = x + segment(attack);
+        let decay_x = attack_x + segment(decay);
+        let release_x = x + width - segment(release);
+
+        let mut builder = PathBuilder::new();
+
+        builder.move_to(x, bottom);
+        builder.line_to(attack_x, y);
+        builder.line_to(decay_x, sustain_y);
+        builder.line_to(release_x, sustain_y);
+        builder.line_to(x + width, bottom);
+
+        if let Some(path) = builder.finish() {
+            let mut paint = Paint::default();
+
+            paint.set_color(to_skia_color(self.appearance.envelope_color));
+
+            let stroke = Stroke {
+                width: 2.0,
+                ..Default::default()
+            };
+
+            self.pixmap
+                .stroke_path(&path, &paint, &stroke, Transform::identity(), None);
+        }
+    }
+
+    fn font(&self, bold: bool) -> &FontRef<'a> {
+        if bold {
+            &self.bold
+        } else {
+            &self.regular
+        }
+    }
+
+    fn text_width(&self, text: &str, size: f32, bold: bool) -> f32 {
+        let font = self.font(bold).as_scaled(PxScale::from(size));
+
+        let mut width = 0.0;
+        let mut previous = None;
+
+        for c in text.chars() {
+            let glyph_id = font.glyph_id(c);
+
+            if let Some(previous) = previous {
+                width += font.kern(previous, glyph_id);
+            }
+
+            width += font.h_advance(glyph_id);
+            previous = Some(glyph_id);
+        }
+
+        width
+    }
+
+    /// Draw text with baseline at y
+    fn draw_text(&mut self, text: &str, x: f32, y: f32, size: f32, bold: bool, color: Color) {
+        let font = self.font(bold).clone();
+        let scaled_font = font.as_scaled(PxScale::from(size));
+
+        let width = self.pixmap.width() as i32;
+        let height = self.pixmap.height() as i32;
+        let pixels = self.pixmap.pixels_mut();
+
+        let mut caret = x;
+        let mut previous = None;
+
+        for c in text.chars() {
+            let glyph_id = scaled_font.glyph_id(c);
+
+            if let Some(previous) = previous {
+                caret += scaled_font.kern(previous, glyph_id);
+            }
+
+            let glyph = glyph_id.with_scale_and_position(size, point(caret, y));
+
+            caret += scaled_font.h_advance(glyph_id);
+            previous = Some(glyph_id);
+
+            let outlined = match font.outline_glyph(glyph) {
+                Some(outlined) => outlined,
+                None => continue,
+            };
+
+            let bounds = outlined.px_bounds();
+
+            outlined.draw(|glyph_x, glyph_y, coverage| {
+                let pixel_x = bounds.min.x as i32 + glyph_x as i32;
+                let pixel_y = bounds.min.y as i32 + glyph_y as i32;
+
+                if pixel_x < 0 || pixel_y < 0 || pixel_x >= width || pixel_y >= height {
+                    return;
+                }
+
+                let pixel = &mut pixels[(pixel_y * width + pixel_x) as usize];
+
+                // Pixmap is opaque, so premultiplied and straight colors
+                // are the same
+                let blend = |background: u8, foreground: f32| {
+                    let background = f32::from(background) / 255.0;
+
+                    ((background + (foreground - background) * coverage) * 255.0).round() as u8
+                };
+
+                let r = blend(pixel.red(), color.r);
+                let g = blend(pixel.green(), color.g);
+                let b = blend(pixel.blue(), color.b);
+
+                if let Some(blended) = PremultipliedColorU8::from_rgba(r, g, b, 255) {
+                    *pixel = blended;
+                }
+            });
+        }
+    }
+}
+
+fn shortest_column(column_heights: &[f32; COLUMNS]) -> usize {
+    let mut shortest = 0;
+
+    for (i, height) in column_heights.iter().enumerate() {
+        if *height < column_heights[shortest] {
+            shortest = i;
+        }
+    }
+
+    shortest
+}
+
+fn to_skia_color(color: Color) -> tiny_skia::Color {
+    tiny_skia::Color::from_rgba(color.r, color.g, color.b, color.a)
+        .unwrap_or(tiny_skia::Color::BLACK)
+}
@@ -0,0 +1,583 @@
+use std::fmt::Display;
+
+use compact_str::CompactString;
+use iced_audio::{graphics::knob, Normal, NormalParam};
+use iced_baseview::alignment::Horizontal;
+use iced_baseview::widget::canvas::{
+    event, Cache, Canvas, Cursor, Frame, Geometry, Path, Program, Stroke,
+};
+use iced_baseview::widget::tooltip::Position;
+use iced_baseview::widget::{Button, Column, Container, PickList, Row, Space, Text};
+use iced_baseview::{mouse, Alignment, Color, Element, Length, Point, Rectangle, Size};
+
+use crate::parameters::{MasterParameter, Parameter, ParameterKey, WrappedParameter, PARAMETERS};
+use crate::sync::GuiSyncHandle;
+
+use super::common::{container_l1, tooltip};
+use super::style::button::ButtonStyle;
+use super::style::knob::KnobStyle;
+use super::style::Theme;
+use super::{GuiDensity, Message, FONT_SIZE, LINE_HEIGHT};
+
+pub const NUM_ASSIGNABLE_KNOBS: usize = 6;
+
+const KNOB_SIZE: u16 = LINE_HEIGHT * 5;
+const KNOB_WIDTH: u16 = LINE_HEIGHT * 8;
+
+const NUM_OCTAVES: u8 = 4;
+const NUM_WHITE_KEYS: u16 = NUM_OCTAVES as u16 * 7;
+const WHITE_KEY_WIDTH: u16 = LINE_HEIGHT * 2;
+const KEYBOARD_WIDTH: u16 = NUM_WHITE_KEYS * WHITE_KEY_WIDTH + 1;
+const KEYBOARD_HEIGHT: u16 = LINE_HEIGHT * 8;
+/// Black key height relative to keyboard height
+const BLACK_KEY_HEIGHT_RATIO: f32 = 0.6;
+/// Semitone offsets of white keys within octave
+const WHITE_KEY_SEMITONES: [u8; 7] = [0, 2, 4, 5, 7, 9, 11];
+/// Semitone offsets of black keys within octave, with index of white key to
+/// the left of them
+const BLACK_KEY_SEMITONES: [(u8, u8); 5] = [(1, 0), (3, 1), (6, 3), (8, 4), (10, 5)];
+
+const MIN_FIRST_KEY: u8 = 12;
+const MAX_FIRST_KEY: u8 = 127 - NUM_OCTAVES * 12;
+
+/// Parameter choice for assignable knob
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KnobTarget(pub Option<Parameter>);
+
+impl Display for KnobTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            Some(parameter) => write!(f, "{}", parameter.name()),
+            None => write!(f, "-"),
+        }
+    }
+}
+
+fn unassigned_value() -> NormalParam {
+    NormalParam {
+        value: Normal::from_clipped(0.0),
+        default: Normal::from_clipped(0.0),
+    }
+}
+
+/// Large knob for any parameter, with value text
+struct LargeKnob {
+    parameter: Option<WrappedParameter>,
+    value: NormalParam,
+    value_text: CompactString,
+}
+
+impl LargeKnob {
+    fn new<H: GuiSyncHandle>(sync_handle: &H, parameter: Option<Parameter>) -> Self {
+        let mut knob = Self {
+            parameter: None,
+            value: unassigned_value(),
+            value_text: "-".into(),
+        };
+
+        knob.set_parameter(sync_handle, parameter);
+
+        knob
+    }
+
+    fn set_parameter<H: GuiSyncHandle>(&mut self, sync_handle: &H, parameter: Option<Parameter>) {
+        self.parameter = parameter.map(WrappedParameter::from);
+
+        match self.parameter {
+            Some(parameter) => {
+                let value = sync_handle.get_parameter(parameter);
+
+                self.value = NormalParam {
+                    value: Normal::from_clipped(value),
+                    default: Normal::from_clipped(value),
+                };
+                self.value_text = sync_handle.format_parameter_value(parameter, value);
+            }
+            None => {
+                self.value = unassigned_value();
+                self.value_text = "-".into();
+            }
+        }
+    }
+
+    fn set_value<H: GuiSyncHandle>(&mut self, sync_handle: &H, parameter: Parameter, value: f32) {
+        if let Some(wrapped) = self.parameter.filter(|p| p.parameter() == parameter) {
+            self.value.update(Normal::from_clipped(value));
+            self.value_text = sync_handle.format_parameter_value(wrapped, value);
+        }
+    }
+
+    fn view<'a>(
+        &'a self,
+        theme: &Theme,
+        heading: Element<'a, Message, Theme>,
+    ) -> Element<'a, Message, Theme> {
+        let knob: Element<Message, Theme> = match self.parameter {
+            Some(parameter) => knob::Knob::new(self.value, move |value| {
+                Message::ChangeSingleParameterSetValue(parameter, value.as_f32())
+            })
+            .on_grab(move || Some(Message::ChangeSingleParameterBegin(parameter)))
+            .on_release(move || Some(Message::ChangeSingleParameterEnd(parameter)))
+            .size(Length::Fixed(KNOB_SIZE.into()))
+            .style(KnobStyle::Regular)
+            .into(),
+            None => knob::Knob::new(self.value, |_| Message::NoOp)
+                .size(Length::Fixed(KNOB_SIZE.into()))
+                .style(KnobStyle::Regular)
+                .into(),
+        };
+
+        Column::new()
+            .width(Length::Fixed(KNOB_WIDTH.into()))
+            .align_items(Alignment::Center)
+            .push(
+                Container::new(heading)
+                    .height(Length::Fixed(f32::from(LINE_HEIGHT * 2)))
+                    .center_y(),
+            )
+            .push(Space::with_height(Length::Fixed(f32::from(
+                LINE_HEIGHT / 2,
+            ))))
+            .push(knob)
+            .push(Space::with_height(Length::Fixed(f32::from(
+                LINE_HEIGHT / 2,
+            ))))
+            .push(
+                Text::new(self.value_text.clone())
+                    .font(theme.font_regular())
+                    .height(Length::Fixed(LINE_HEIGHT.into()))
+                    .width(Length::Fill)
+                    .horizontal_alignment(Horizontal::Center),
+            )
+            .into()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Appearance {
+    pub white_key_color: Color,
+    pub black_key_color: Color,
+    pub held_key_color: Color,
+    pub border_color: Color,
+}
+
+pub trait StyleSheet {
+    fn appearance(&self) -> Appearance;
+}
+
+#[derive(Default)]
+pub struct KeyboardState {
+    cursor_position: Option<Point>,
+    /// Key pressed with mouse
+    pressed_key: Option<u8>,
+}
+
+/// On-screen keyboard playing notes through audio thread. Keys held by host
+/// or GUI are highlighted.
+pub struct Keyboard {
+    first_key: u8,
+    held_keys: u128,
+    cache: Cache,
+}
+
+impl Keyboard {
+    fn new() -> Self {
+        Self {
+            first_key: 48,
+            held_keys: 0,
+            cache: Cache::new(),
+        }
+    }
+
+    pub fn set_held_keys(&mut self, held_keys: u128) {
+        if held_keys != self.held_keys {
+            self.held_keys = held_keys;
+            self.cache.clear();
+        }
+    }
+
+    fn shift_octave(&mut self, octaves: i8) {
+        let first_key = i16::from(self.first_key) + i16::from(octaves) * 12;
+
+        self.first_key = first_key.clamp(i16::from(MIN_FIRST_KEY), i16::from(MAX_FIRST_KEY)) as u8;
+        self.cache.clear();
+    }
+
+    fn white_key_rect(index: u16) -> Rectangle {
+        Rectangle::new(
+            Point::new(f32::from(index * WHITE_KEY_WIDTH) + 0.5, 0.5),
+            Size::new(WHITE_KEY_WIDTH.into(), f32::from(KEYBOARD_HEIGHT) - 1.0),
+        )
+    }
+
+    fn black_key_rect(white_key_index: u16) -> Rectangle {
+        let width = f32::from(WHITE_KEY_WIDTH) * 0.6;
+        let x = f32::from((white_key_index + 1) * WHITE_KEY_WIDTH) - width / 2.0;
+
+        Rectangle::new(
+            Point::new(x.round() + 0.5, 0.5),
+            Size::new(
+                width.round(),
+                (f32::from(KEYBOARD_HEIGHT) * BLACK_KEY_HEIGHT_RATIO).round(),
+            ),
+        )
+    }
+
+    /// White keys with rectangles
+    fn white_keys(&self) -> impl Iterator<Item = (u8, Rectangle)> + '_ {
+        (0..NUM_OCTAVES).flat_map(move |octave| {
+            WHITE_KEY_SEMITONES
+                .iter()
+                .enumerate()
+                .map(move |(i, semitone)| {
+                    let index = u16::from(octave) * 7 + i as u16;
+
+                    (
+                        self.first_key + octave * 12 + semitone,
+                        Self::white_key_rect(index),
+                    )
+                })
+        })
+    }
+
+    /// Black keys with rectangles
+    fn black_keys(&self) -> impl Iterator<Item = (u8, Rectangle)> + '_ {
+        (0..NUM_OCTAVES).flat_map(move |octave| {
+            BLACK_KEY_SEMITONES
+                .iter()
+                .map(move |(semitone, white_key_index)| {
+                    let index = u16::from(octave) * 7 + u16::from(*white_key_index);
+
+                    (
+                        self.first_key + octave * 12 + semitone,
+                        Self::black_key_rect(index),
+                    )
+                })
+        })
+    }
+
+    fn key_at(&self, position: Point) -> Option<u8> {
+        self.black_keys()
+            .chain(self.white_keys())
+            .find(|(_, rect)| rect.contains(position))
+            .map(|(key, _)| key)
+    }
+
+    fn is_held(&self, key: u8) -> bool {
+        self.held_keys & (1 << key) != 0
+    }
+
+    fn view(&self) -> Element<Message, Theme> {
+        Canvas::new(self)
+            .width(Length::Fixed(KEYBOARD_WIDTH.into()))
+            .height(Length::Fixed(KEYBOARD_HEIGHT.into()))
+            .into()
+    }
+
+    fn draw_keys(&self, frame: &mut Frame, theme: &Theme) {
+        let appearance = theme.appearance();
+
+        for (key, rect) in self.white_keys() {
+            let path = Path::rectangle(rect.position(), rect.size());
+
+            let color = if self.is_held(key) {
+                appearance.held_key_color
+            } else {
+                appearance.white_key_color
+            };
+
+            frame.fill(&path, color);
+            frame.stroke(&path, Stroke::default().with_color(appearance.border_color));
+        }
+
+        for (key, rect) in self.black_keys() {
+            let path = Path::rectangle(rect.position(), rect.size());
+
+            let color = if self.is_held(key) {
+                appearance.held_key_color
+            } else {
+                appearance.black_key_color
+            };
+
+            frame.fill(&path, color);
+            frame.stroke(&path, Stroke::default().with_color(appearance.border_color));
+        }
+    }
+}
+
+impl Program<Message, Theme> for Keyboard {
+    type State = KeyboardState;
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        theme: &Theme,
+        bounds: Rectangle,
+        _cursor: Cursor,
+    ) -> Vec<Geometry> {
+        let geometry = self.cache.draw(bounds.size(), |frame| {
+            self.draw_keys(frame, theme);
+        });
+
+        vec![geometry]
+    }
+
+    fn update(
+        &self,
+        state: &mut Self::State,
+        event: event::Event,
+        bounds: Rectangle,
+        cursor: Cursor,
+    ) -> (event::Status, Option<Message>) {
+        match event {
+            event::Event::Mouse(mouse::Event::CursorMoved { position }) => {
+                state.cursor_position = bounds
+                    .contains(position)
+                    .then(|| Point::new(position.x - bounds.x, position.y - bounds.y));
+
+                // Glide over keys while button is held
+                if let Some(pressed_key) = state.pressed_key {
+                    let opt_key = state.cursor_position.and_then(|p| self.key_at(p));
+
+                    if let Some(key) = opt_key.filter(|key| *key != pressed_key) {
+                        state.pressed_key = Some(key);
+
+                        return (
+                            event::Status::Captured,
+                            Some(Message::SetGuiKeyboardKey(Some(key))),
+                        );
+                    }
+                }
+            }
+            event::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                // Touch and pen input might not move cursor before pressing
+                if let Some(position) = cursor.position_in(&bounds) {
+                    state.cursor_position = Some(position);
+                }
+
+                if let Some(key) = state.cursor_position.and_then(|p| self.key_at(p)) {
+                    state.pressed_key = Some(key);
+
+                    return (
+                        event::Status::Captured,
+                        Some(Message::SetGuiKeyboardKey(Some(key))),
+                    );
+                }
+            }
+            event::Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+                if state.pressed_key.take().is_some() {
+                    return (
+                        event::Status::Captured,
+                        Some(Message::SetGuiKeyboardKey(None)),
+                    );
+                }
+            }
+            _ => (),
+        }
+
+        (event::Status::Ignored, None)
+    }
+}
+
+/// Alternative view for live use with large knobs and on-screen keyboard
+pub struct PerformanceWidgets {
+    master_volume: LargeKnob,
+    knobs: [LargeKnob; NUM_ASSIGNABLE_KNOBS],
+    knob_targets: Vec<KnobTarget>,
+    pub keyboard: Keyboard,
+    /// Key currently played on on-screen keyboard
+    pub keyboard_key: Option<u8>,
+}
+
+impl PerformanceWidgets {
+    pub fn new<H: GuiSyncHandle>(
+        sync_handle: &H,
+        knob_parameters: [Option<ParameterKey>; NUM_ASSIGNABLE_KNOBS],
+    ) -> Self {
+        let knob_targets = ::std::iter::once(KnobTarget(None))
+            .chain(
+                PARAMETERS
+                    .iter()
+                    .filter(|p| !matches!(p, Parameter::None))
+                    .map(|p| KnobTarget(Some(*p))),
+            )
+            .collect();
+
+        let knobs = knob_parameters.map(|opt_key| {
+            let parameter =
+                opt_key.and_then(|key| PARAMETERS.iter().copied().find(|p| p.key() == key));
+
+            LargeKnob::new(sync_handle, parameter)
+        });
+
+        Self {
+            master_volume: LargeKnob::new(
+                sync_handle,
+                Some(Parameter::Master(MasterParameter::Volume)),
+            ),
+            knobs,
+            knob_targets,
+            keyboard: Keyboard::new(),
+            keyboard_key: None,
+        }
+    }
+
+    /// Keys of parameters assigned to knobs, for storing in settings
+    pub fn knob_parameter_keys(&self) -> [Option<ParameterKey>; NUM_ASSIGNABLE_KNOBS] {
+        ::std::array::from_fn(|i| self.knobs[i].parameter.map(|p| p.key()))
+    }
+
+    pub fn set_knob_parameter<H: GuiSyncHandle>(
+        &mut self,
+        sync_handle: &H,
+        knob_index: usize,
+        parameter: Option<Parameter>,
+    ) {
+        self.knobs[knob_index].set_parameter(sync_handle, parameter);
+    }
+
+    pub fn set_value<H: GuiSyncHandle>(
+        &mut self,
+        sync_handle: &H,
+        parameter: Parameter,
+        value: f32,
+    ) {
+        self.master_volume.set_value(sync_handle, parameter, value);
+
+        for knob in self.knobs.iter_mut() {
+            knob.set_value(sync_handle, parameter, value);
+        }
+    }
+
+    pub fn shift_keyboard_octave(&mut self, octaves: i8) {
+        self.keyboard.shift_octave(octaves);
+    }
+
+    pub fn theme_changed(&mut self) {
+        self.keyboard.cache.clear();
+    }
+
+    pub fn view(&self, theme: &Theme, density: GuiDensity) -> Element<Message, Theme> {
+        let heading = Row::new()
+            .align_items(Alignment::Center)
+            .push(Space::with_width(Length::Fixed(LINE_HEIGHT.into())))
+            .push(
+                Text::new("Performance")
+                    .size(FONT_SIZE + FONT_SIZE / 2)
+                    .height(Length::Fixed(f32::from(FONT_SIZE + FONT_SIZE / 2)))
+                    .font(theme.font_heading()),
+            )
+            .push(Space::with_width(Length::Fill))
+            .push(tooltip(
+                theme,
+                "Release all notes and quickly fade out all voices",
+                Position::Left,
+                Button::new(
+                    Text::new("PANIC")
+                        .font(theme.font_regular())
+                        .height(Length::Fixed(LINE_HEIGHT.into()))
+                        .horizontal_alignment(Horizontal::Center),
+                )
+                .padding(theme.button_padding())
+                .on_press(Message::Panic),
+            ))
+            .push(Space::with_width(Length::Fixed(f32::from(LINE_HEIGHT / 2))))
+            .push(tooltip(
+                theme,
+                "Return to editing view",
+                Position::Left,
+                Button::new(
+                    Text::new("EDIT")
+                        .font(theme.font_regular())
+                        .height(Length::Fixed(LINE_HEIGHT.into()))
+                        .horizontal_alignment(Horizontal::Center),
+                )
+                .padding(theme.button_padding())
+                .on_press(Message::TogglePerformanceView),
+            ))
+            .push(Space::with_width(Length::Fixed(LINE_HEIGHT.into())));
+
+        let master_volume_heading = Text::new("MASTER VOLUME")
+            .font(theme.font_bold())
+            .height(Length::Fixed(LINE_HEIGHT.into()))
+            .horizontal_alignment(Horizontal::Center)
+            .into();
+
+        let mut knobs = Row::new()
+            .spacing(LINE_HEIGHT)
+            .push(self.master_volume.view(theme, master_volume_heading));
+
+        for (knob_index, knob) in self.knobs.iter().enumerate() {
+            let picker = PickList::new(
+                &self.knob_targets[..],
+                Some(KnobTarget(knob.parameter.map(|p| p.parameter()))),
+                move |target| Message::SetPerformanceKnobParameter(knob_index, target.0),
+            )
+            .font(theme.font_regular())
+            .text_size(FONT_SIZE)
+            .padding(theme.picklist_padding())
+            .width(Length::Fixed(KNOB_WIDTH.into()));
+
+            knobs = knobs.push(knob.view(theme, picker.into()));
+        }
+
+        let octave_button = |text, octaves: i8| {
+            Button::new(
+                Text::new(text)
+                    .font(theme.font_regular())
+                    .height(Length::Fixed(LINE_HEIGHT.into()))
+                    .width(Length::Fixed(f32::from(LINE_HEIGHT * 2)))
+                    .horizontal_alignment(Horizontal::Center),
+            )
+            .padding(theme.button_padding())
+            .style(ButtonStyle::Value)
+            .on_press(Message::ShiftGuiKeyboardOctave(octaves))
+        };
+
+        let keyboard = Row::new()
+            .align_items(Alignment::Center)
+            .push(Space::with_width(Length::Fill))
+            .push(tooltip(
+                theme,
+                "Shift keyboard down one octave",
+                Position::Top,
+                octave_button("−", -1),
+            ))
+            .push(Space::with_width(Length::Fixed(LINE_HEIGHT.into())))
+            .push(self.keyboard.view())
+            .push(Space::with_width(Length::Fixed(LINE_HEIGHT.into())))
+            .push(tooltip(
+                theme,
+                "Shift keyboard up one octave",
+                Position::Top,
+                octave_button("+", 1),
+            ))
+            .push(Space::with_width(Length::Fill));
+
+        container_l1(
+            Column::new()
+                .width(Length::Fill)
+                .push(Space::with_height(Length::Fixed(LINE_HEIGHT.into())))
+                .push(heading)
+                .push(Space::with_height(Length::Fixed(f32::from(
+                    LINE_HEIGHT * 2,
+                ))))
+                .push(
+                    Row::new()
+                        .push(Space::with_width(Length::Fill))
+                        .push(knobs)
+                        .push(Space::with_width(Length::Fill)),
+                )
+                .push(Space::with_height(Length::Fixed(f32::from(
+                    LINE_HEIGHT * 3,
+                ))))
+                .push(keyboard),
+        )
+        .width(Length::Fill)
+        // Same height as four expanded operators
+        .height(Length::Fixed(f32::from(
+            density.panel_row_height() * 4 + density.spacing() * 3,
+        )))
+        .into()
+    }
+}
@@ -0,0 +1,307 @@
+use std::marker::PhantomData;
+
+use iced_baseview::alignment::{Horizontal, Vertical};
+use iced_baseview::widget::canvas::{
+    event, Cache, Canvas, Cursor, Frame, Geometry, Path, Program, Stroke, Text,
+};
+use iced_baseview::{Color, Element, Length, Point, Rectangle, Size};
+
+use crate::parameters::operator_key_range::{OperatorKeyRangeHighValue, OperatorKeyRangeLowValue};
+use crate::parameters::operator_velocity_range::{
+    OperatorVelocityRangeHighValue, OperatorVelocityRangeLowValue,
+};
+use crate::parameters::{OperatorParameter, Parameter, ParameterValue, WrappedParameter};
+use crate::sync::GuiSyncHandle;
+
+use super::style::Theme;
+use super::{Message, FONT_SIZE, LINE_HEIGHT};
+
+const WIDTH: u16 = LINE_HEIGHT * 3;
+const HEIGHT: u16 = LINE_HEIGHT;
+
+/// Highest value of edited ranges (MIDI key or velocity)
+const MAX_VALUE: u8 = 127;
+
+#[derive(Debug, Clone)]
+pub struct Appearance {
+    pub background_color: Color,
+    pub border_color_active: Color,
+    pub border_color_hovered: Color,
+    pub range_color: Color,
+    pub text_color: Color,
+}
+
+pub trait StyleSheet {
+    fn appearance(&self) -> Appearance;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Bound {
+    Low,
+    High,
+}
+
+#[derive(Default)]
+pub struct CanvasState {
+    cursor_within_bounds: bool,
+    last_cursor_value: u8,
+    dragging: Option<Bound>,
+}
+
+pub fn operator_key_range<H: GuiSyncHandle>(
+    sync_handle: &H,
+    operator_index: usize,
+) -> RangePicker<OperatorKeyRangeLowValue, OperatorKeyRangeHighValue> {
+    RangePicker::new(
+        sync_handle,
+        Parameter::Operator(operator_index as u8, OperatorParameter::KeyRangeLow),
+        Parameter::Operator(operator_index as u8, OperatorParameter::KeyRangeHigh),
+    )
+}
+
+pub fn operator_velocity_range<H: GuiSyncHandle>(
+    sync_handle: &H,
+    operator_index: usize,
+) -> RangePicker<OperatorVelocityRangeLowValue, OperatorVelocityRangeHighValue> {
+    RangePicker::new(
+        sync_handle,
+        Parameter::Operator(operator_index as u8, OperatorParameter::VelocityRangeLow),
+        Parameter::Operator(operator_index as u8, OperatorParameter::VelocityRangeHigh),
+    )
+}
+
+/// Editor for range of values from 0 to 127 (e.g., MIDI key or velocity)
+/// stored in two parameters. Drag to move the bound closest to the cursor.
+pub struct RangePicker<L: ParameterValue, U: ParameterValue> {
+    low_parameter: WrappedParameter,
+    high_parameter: WrappedParameter,
+    low: u8,
+    high: u8,
+    cache: Cache,
+    bounds_path: Path,
+    phantom_data: PhantomData<(L, U)>,
+}
+
+impl<L, U> RangePicker<L, U>
+where
+    L: ParameterValue<Value = u8> + 'static,
+    U: ParameterValue<Value = u8> + 'static,
+{
+    fn new<H: GuiSyncHandle>(
+        sync_handle: &H,
+        low_parameter: Parameter,
+        high_parameter: Parameter,
+    ) -> Self {
+        let low_parameter: WrappedParameter = low_parameter.into();
+        let high_parameter: WrappedParameter = high_parameter.into();
+
+        let bounds_path = Path::rectangle(
+            Point::new(0.5, 0.5),
+            Size::new((WIDTH - 1) as f32, (HEIGHT - 1) as f32),
+        );
+
+        Self {
+            low_parameter,
+            high_parameter,
+            low: L::new_from_patch(sync_handle.get_parameter(low_parameter)).get(),
+            high: U::new_from_patch(sync_handle.get_parameter(high_parameter)).get(),
+            cache: Cache::new(),
+            bounds_path,
+            phantom_data: PhantomData,
+        }
+    }
+
+    pub fn set_low(&mut self, value: f32) {
+        let low = L::new_from_patch(value).get();
+
+        if self.low != low {
+            self.low = low;
+            self.cache.clear();
+        }
+    }
+
+    pub fn set_high(&mut self, value: f32) {
+        let high = U::new_from_patch(value).get();
+
+        if self.high != high {
+            self.high = high;
+            self.cache.clear();
+        }
+    }
+
+    pub fn theme_changed(&mut self) {
+        self.cache.clear();
+    }
+
+    pub fn view(&self) -> Element<Message, Theme> {
+        Canvas::new(self)
+            .width(Length::Fixed(WIDTH.into()))
+            .height(Length::Fixed(HEIGHT.into()))
+            .into()
+    }
+
+    fn value_to_x(value: u8) -> f32 {
+        0.5 + f32::from(value) / f32::from(MAX_VALUE + 1) * f32::from(WIDTH - 1)
+    }
+
+    fn x_to_value(x: f32) -> u8 {
+        let value = ((x - 0.5) / f32::from(WIDTH - 1) * f32::from(MAX_VALUE + 1)).floor();
+
+        value.clamp(0.0, f32::from(MAX_VALUE)) as u8
+    }
+
+    fn draw_range(&self, state: &CanvasState, frame: &mut Frame, theme: &Theme) {
+        let appearance = theme.appearance();
+
+        frame.fill(&self.bounds_path, appearance.background_color);
+
+        let low = self.low.min(self.high);
+        let high = self.low.max(self.high);
+
+        let x = Self::value_to_x(low);
+        let range_path = Path::rectangle(
+            Point::new(x, 0.5),
+            Size::new(Self::value_to_x(high + 1) - x, (HEIGHT - 1) as f32),
+        );
+
+        frame.fill(&range_path, appearance.range_color);
+
+        let border_color = if state.cursor_within_bounds || state.dragging.is_some() {
+            appearance.border_color_hovered
+        } else {
+            appearance.border_color_active
+        };
+
+        frame.stroke(
+            &self.bounds_path,
+            Stroke::default().with_color(border_color),
+        );
+
+        frame.fill_text(Text {
+            content: format!(
+                "{}-{}",
+                L::new_from_audio(low).get_formatted(),
+                U::new_from_audio(high).get_formatted()
+            ),
+            color: appearance.text_color,
+            size: f32::from(FONT_SIZE),
+            font: theme.font_regular(),
+            position: Point::new(f32::from(WIDTH) / 2.0, f32::from(HEIGHT) / 2.0),
+            horizontal_alignment: Horizontal::Center,
+            vertical_alignment: Vertical::Center,
+            ..Default::default()
+        });
+    }
+
+    fn parameter_and_value(&self, bound: Bound, value: u8) -> (WrappedParameter, f32) {
+        match bound {
+            Bound::Low => (self.low_parameter, L::new_from_audio(value).to_patch()),
+            Bound::High => (self.high_parameter, U::new_from_audio(value).to_patch()),
+        }
+    }
+}
+
+impl<L, U> Program<Message, Theme> for RangePicker<L, U>
+where
+    L: ParameterValue<Value = u8> + 'static,
+    U: ParameterValue<Value = u8> + 'static,
+{
+    type State = CanvasState;
+
+    fn draw(
+        &self,
+        state: &Self::State,
+        theme: &Theme,
+        bounds: Rectangle,
+        _cursor: Cursor,
+    ) -> Vec<Geometry> {
+        let geometry = self.cache.draw(bounds.size(), |frame| {
+            self.draw_range(state, frame, theme);
+        });
+
+        vec![geometry]
+    }
+
+    fn update(
+        &self,
+        state: &mut Self::State,
+        event: event::Event,
+        bounds: Rectangle,
+        cursor: Cursor,
+    ) -> (event::Status, Option<Message>) {
+        // Touch and pen input might not move cursor before pressing, so
+        // don't rely on last cursor moved event
+        if let event::Event::Mouse(iced_baseview::mouse::Event::ButtonPressed(_)) = event {
+            if let Some(position) = cursor.position_in(&bounds) {
+                state.cursor_within_bounds = true;
+                state.last_cursor_value = Self::x_to_value(position.x);
+            }
+        }
+
+        match event {
+            event::Event::Mouse(iced_baseview::mouse::Event::CursorMoved { position }) => {
+                let cursor_within_bounds = bounds.contains(position);
+
+                if state.cursor_within_bounds != cursor_within_bounds {
+                    state.cursor_within_bounds = cursor_within_bounds;
+
+                    self.cache.clear();
+                }
+
+                state.last_cursor_value = Self::x_to_value(position.x - bounds.x);
+
+                if let Some(bound) = state.dragging {
+                    let (parameter, value) =
+                        self.parameter_and_value(bound, state.last_cursor_value);
+
+                    return (
+                        event::Status::Captured,
+                        Some(Message::ChangeSingleParameterSetValue(parameter, value)),
+                    );
+                }
+
+                (event::Status::Ignored, None)
+            }
+            event::Event::Mouse(iced_baseview::mouse::Event::ButtonPressed(
+                iced_baseview::mouse::Button::Left,
+            )) if state.cursor_within_bounds => {
+                let value = state.last_cursor_value;
+
+                let low_distance = (i16::from(value) - i16::from(self.low)).abs();
+                let high_distance = (i16::from(value) - i16::from(self.high)).abs();
+
+                let bound = if low_distance <= high_distance && value <= self.high {
+                    Bound::Low
+                } else {
+                    Bound::High
+                };
+
+                state.dragging = Some(bound);
+
+                let (parameter, _) = self.parameter_and_value(bound, value);
+
+                (
+                    event::Status::Captured,
+                    Some(Message::ChangeSingleParameterBegin(parameter)),
+                )
+            }
+            event::Event::Mouse(iced_baseview::mouse::Event::ButtonReleased(
+                iced_baseview::mouse::Button::Left,
+            )) => {
+                if let Some(bound) = state.dragging.take() {
+                    self.cache.clear();
+
+                    let (parameter, _) = self.parameter_and_value(bound, 0);
+
+                    (
+                        event::Status::Captured,
+                        Some(Message::ChangeSingleParameterEnd(parameter)),
+                    )
+                } else {
+                    (event::Status::Ignored, None)
+                }
+            }
+            _ => (event::Status::Ignored, None),
+        }
+    }
+}
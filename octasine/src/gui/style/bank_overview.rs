@@ -0,0 +1,32 @@
+use crate::gui::bank_overview::{Appearance, StyleSheet};
+
+use super::Theme;
+
+impl StyleSheet for Theme {
+    fn appearance(&self) -> Appearance {
+        match self {
+            Self::Light => {
+                use super::colors::light::*;
+                Appearance {
+                    background_color: SURFACE,
+                    border_color: BORDER,
+                    used_color: GRAY_600,
+                    current_color: BLUE,
+                    hovered_color: TEXT,
+                    text_color: TEXT,
+                }
+            }
+            Self::Dark => {
+                use super::colors::dark::*;
+                Appearance {
+                    background_color: GRAY_200,
+                    border_color: BORDER_DARK,
+                    used_color: GRAY_600,
+                    current_color: BLUE,
+                    hovered_color: TEXT,
+                    text_color: TEXT,
+                }
+            }
+        }
+    }
+}
@@ -5,6 +5,7 @@ use crate::{hex, hex_gray};
 pub const RED: Color = hex!(0xEF, 0x53, 0x50);
 pub const BLUE: Color = hex!(0x50, 0x9D, 0xEF);
 pub const GREEN: Color = hex!(0x50, 0xEF, 0xA2);
+pub const ORANGE: Color = hex!(0xEF, 0xA2, 0x50);
 
 pub const GRAY_100: Color = hex_gray!(0x20);
 pub const GRAY_200: Color = hex_gray!(0x2A);
@@ -5,6 +5,7 @@ use crate::{hex, hex_gray};
 pub const RED: Color = hex!(0xEF, 0x00, 0x00);
 pub const BLUE: Color = hex!(0x00, 0x78, 0xEF);
 pub const GREEN: Color = hex!(0x00, 0xEF, 0x78);
+pub const ORANGE: Color = hex!(0xEF, 0x78, 0x00);
 
 pub const GRAY_300: Color = hex_gray!(0x60);
 pub const GRAY_400: Color = hex_gray!(0x77);
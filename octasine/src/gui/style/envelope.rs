@@ -24,6 +24,10 @@ impl StyleSheet for Theme {
                     dragger_border_color: BORDER,
                     viewport_indicator_border: GRAY_300,
                     viewport_indicator_border_active: BLUE,
+                    group_a_color: GREEN,
+                    group_b_color: ORANGE,
+                    group_badge_text_color: Color::WHITE,
+                    playhead_color: ORANGE,
                 }
             }
             Self::Dark => {
@@ -43,6 +47,10 @@ impl StyleSheet for Theme {
                     dragger_border_color: SURFACE,
                     viewport_indicator_border: GRAY_600,
                     viewport_indicator_border_active: BLUE,
+                    group_a_color: GREEN,
+                    group_b_color: ORANGE,
+                    group_badge_text_color: BACKGROUND,
+                    playhead_color: ORANGE,
                 }
             }
         }
@@ -1,6 +1,7 @@
 mod macros;
 
 pub mod application;
+pub mod bank_overview;
 pub mod boolean_button;
 pub mod button;
 pub mod card;
@@ -12,11 +13,15 @@ pub mod knob;
 pub mod menu;
 pub mod mod_matrix;
 pub mod modal;
+pub mod patch_sheet;
+pub mod performance;
 pub mod pick_list;
 pub mod radio;
+pub mod range_picker;
 pub mod scrollable;
 pub mod text;
 pub mod text_input;
+pub mod velocity_curve;
 pub mod wave_display;
 pub mod wave_picker;
 
@@ -0,0 +1,34 @@
+use crate::gui::patch_sheet::{Appearance, StyleSheet};
+
+use super::Theme;
+
+impl StyleSheet for Theme {
+    fn appearance(&self) -> Appearance {
+        match self {
+            Self::Light => {
+                use super::colors::light::*;
+                Appearance {
+                    background_color: BACKGROUND,
+                    panel_color: SURFACE,
+                    border_color: BORDER,
+                    text_color: TEXT,
+                    default_value_color: GRAY_400,
+                    envelope_color: BLUE,
+                    warning_color: RED,
+                }
+            }
+            Self::Dark => {
+                use super::colors::dark::*;
+                Appearance {
+                    background_color: BACKGROUND,
+                    panel_color: GRAY_200,
+                    border_color: BORDER_DARK,
+                    text_color: TEXT,
+                    default_value_color: GRAY_600,
+                    envelope_color: BLUE,
+                    warning_color: RED,
+                }
+            }
+        }
+    }
+}
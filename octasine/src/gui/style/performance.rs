@@ -0,0 +1,28 @@
+use crate::gui::performance::{Appearance, StyleSheet};
+
+use super::Theme;
+
+impl StyleSheet for Theme {
+    fn appearance(&self) -> Appearance {
+        match self {
+            Self::Light => {
+                use super::colors::light::*;
+                Appearance {
+                    white_key_color: SURFACE,
+                    black_key_color: GRAY_300,
+                    held_key_color: BLUE,
+                    border_color: BORDER,
+                }
+            }
+            Self::Dark => {
+                use super::colors::dark::*;
+                Appearance {
+                    white_key_color: GRAY_800,
+                    black_key_color: GRAY_200,
+                    held_key_color: BLUE,
+                    border_color: BORDER_DARK,
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use iced_baseview::Color;
+
+use crate::gui::range_picker::{Appearance, StyleSheet};
+
+use super::Theme;
+
+impl StyleSheet for Theme {
+    fn appearance(&self) -> Appearance {
+        match self {
+            Self::Light => {
+                use super::colors::light::*;
+                Appearance {
+                    background_color: SURFACE,
+                    border_color_active: BORDER,
+                    border_color_hovered: BORDER,
+                    range_color: GRAY_800,
+                    text_color: TEXT,
+                }
+            }
+            Self::Dark => {
+                use super::colors::dark::*;
+                Appearance {
+                    background_color: Color::TRANSPARENT,
+                    border_color_active: BORDER,
+                    border_color_hovered: BORDER_HOVERED,
+                    range_color: GRAY_300,
+                    text_color: TEXT,
+                }
+            }
+        }
+    }
+}
@@ -1,11 +1,38 @@
 use iced_baseview::widget::text::{Appearance, StyleSheet};
 
-use super::Theme;
+use super::{colors, Theme};
+
+#[derive(Default, Clone, Copy)]
+pub enum TextStyle {
+    #[default]
+    Regular,
+    Warning,
+    /// Value was recently changed by host automation
+    Highlight,
+}
 
 impl StyleSheet for Theme {
-    type Style = ();
+    type Style = TextStyle;
 
-    fn appearance(&self, _style: Self::Style) -> Appearance {
-        Appearance { color: None }
+    fn appearance(&self, style: Self::Style) -> Appearance {
+        match style {
+            TextStyle::Regular => Appearance { color: None },
+            TextStyle::Warning => match self {
+                Self::Dark => Appearance {
+                    color: Some(colors::dark::RED),
+                },
+                Self::Light => Appearance {
+                    color: Some(colors::light::RED),
+                },
+            },
+            TextStyle::Highlight => match self {
+                Self::Dark => Appearance {
+                    color: Some(colors::dark::BLUE),
+                },
+                Self::Light => Appearance {
+                    color: Some(colors::light::BLUE),
+                },
+            },
+        }
     }
 }
@@ -0,0 +1,34 @@
+use iced_baseview::Color;
+
+use crate::gui::velocity_curve::{Appearance, StyleSheet};
+
+use super::Theme;
+
+impl StyleSheet for Theme {
+    fn appearance(&self) -> Appearance {
+        match self {
+            Self::Light => {
+                use super::colors::light::*;
+                Appearance {
+                    background_color: SURFACE,
+                    border_color: BORDER,
+                    mod_out_color: BLUE,
+                    feedback_color: GREEN,
+                    peak_color: RED,
+                    sustain_color: GRAY_300,
+                }
+            }
+            Self::Dark => {
+                use super::colors::dark::*;
+                Appearance {
+                    background_color: Color::TRANSPARENT,
+                    border_color: BORDER_DARK,
+                    mod_out_color: BLUE,
+                    feedback_color: GREEN,
+                    peak_color: RED,
+                    sustain_color: GRAY_800,
+                }
+            }
+        }
+    }
+}
@@ -16,6 +16,7 @@ impl StyleSheet for Theme {
                     middle_line_color: GRAY_600,
                     shape_line_color_active: BLUE,
                     shape_line_color_hovered: BLUE,
+                    modulated_line_color: GRAY_500,
                 }
             }
             Self::Dark => {
@@ -27,6 +28,7 @@ impl StyleSheet for Theme {
                     middle_line_color: GRAY_400,
                     shape_line_color_active: BLUE,
                     shape_line_color_hovered: BLUE,
+                    modulated_line_color: GRAY_700,
                 }
             }
         }
@@ -11,10 +11,19 @@ use super::style::button::ButtonStyle;
 use super::LINE_HEIGHT;
 use super::{style::Theme, GuiSyncHandle, Message};
 
+/// Alternative way to format and parse parameter values
+#[derive(Debug, Clone, Copy)]
+pub struct TextFormat {
+    pub format: fn(f32) -> CompactString,
+    pub parse: fn(&str) -> Option<f32>,
+}
+
 #[derive(Debug, Clone)]
 pub struct ValueText<P: ParameterValue> {
     parameter: WrappedParameter,
+    value_patch: f32,
     value_text: CompactString,
+    text_format: Option<TextFormat>,
     phantom_data: PhantomData<P>,
 }
 
@@ -25,13 +34,25 @@ impl<P: ParameterValue> ValueText<P> {
 
         Self {
             parameter,
+            value_patch,
             value_text,
+            text_format: None,
             phantom_data: Default::default(),
         }
     }
 
     pub fn set_value(&mut self, value: f32) {
-        self.value_text = P::new_from_patch(value).get_formatted();
+        self.value_patch = value;
+        self.value_text = match self.text_format {
+            Some(text_format) => (text_format.format)(value),
+            None => P::new_from_patch(value).get_formatted(),
+        };
+    }
+
+    pub fn set_text_format(&mut self, text_format: Option<TextFormat>) {
+        self.text_format = text_format;
+
+        self.set_value(self.value_patch);
     }
 
     pub fn view(&self, theme: &Theme) -> Element<Message, Theme> {
@@ -48,6 +69,7 @@ impl<P: ParameterValue> ValueText<P> {
         .on_press(Message::ChangeParameterByTextInput {
             parameter: self.parameter,
             value_text: self.value_text.clone(),
+            parse: self.text_format.map(|text_format| text_format.parse),
         })
         .into()
     }
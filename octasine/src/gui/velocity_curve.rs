@@ -0,0 +1,179 @@
+use iced_baseview::widget::canvas::{
+    Cache, Canvas, Cursor, Frame, Geometry, Path, Program, Stroke,
+};
+use iced_baseview::widget::tooltip::Position;
+use iced_baseview::{Color, Element, Length, Point, Rectangle, Size};
+
+use crate::parameters::velocity_sensitivity::VelocitySensitivityValue;
+use crate::parameters::{
+    OperatorEnvelopeVelocitySensitivityValue, OperatorParameter, Parameter, ParameterValue,
+};
+use crate::sync::GuiSyncHandle;
+
+use super::common::tooltip;
+use super::style::Theme;
+use super::{Message, LINE_HEIGHT};
+
+const WIDTH: u16 = LINE_HEIGHT * 4 + 4;
+const HEIGHT: u16 = LINE_HEIGHT * 2;
+
+/// Vertical padding so that lines at minimum and maximum level are visible
+const PADDING: f32 = 2.5;
+
+#[derive(Debug, Clone)]
+pub struct Appearance {
+    pub background_color: Color,
+    pub border_color: Color,
+    pub mod_out_color: Color,
+    pub feedback_color: Color,
+    pub peak_color: Color,
+    pub sustain_color: Color,
+}
+
+pub trait StyleSheet {
+    fn appearance(&self) -> Appearance;
+}
+
+/// Display of level factor as function of key velocity for each velocity
+/// sensitivity parameter of an operator
+pub struct VelocityCurve {
+    /// Not available for operator 1, which has no modulation output
+    mod_out: Option<f32>,
+    feedback: f32,
+    peak: f32,
+    sustain: f32,
+    bounds_path: Path,
+    cache: Cache,
+}
+
+impl VelocityCurve {
+    pub fn new<H: GuiSyncHandle>(sync_handle: &H, operator_index: usize) -> Self {
+        let get =
+            |p| sync_handle.get_parameter(Parameter::Operator(operator_index as u8, p).into());
+
+        let bounds_path = Path::rectangle(
+            Point::new(0.5, 0.5),
+            Size::new((WIDTH - 1) as f32, (HEIGHT - 1) as f32),
+        );
+
+        Self {
+            mod_out: (operator_index > 0).then(|| {
+                VelocitySensitivityValue::new_from_patch(get(
+                    OperatorParameter::VelocitySensitivityModOut,
+                ))
+                .get()
+            }),
+            feedback: VelocitySensitivityValue::new_from_patch(get(
+                OperatorParameter::VelocitySensitivityFeedback,
+            ))
+            .get(),
+            peak: OperatorEnvelopeVelocitySensitivityValue::new_from_patch(get(
+                OperatorParameter::VelocitySensitivityPeak,
+            ))
+            .get(),
+            sustain: OperatorEnvelopeVelocitySensitivityValue::new_from_patch(get(
+                OperatorParameter::VelocitySensitivitySustain,
+            ))
+            .get(),
+            bounds_path,
+            cache: Cache::new(),
+        }
+    }
+
+    pub fn set_mod_out(&mut self, value: f32) {
+        if let Some(mod_out) = self.mod_out.as_mut() {
+            *mod_out = VelocitySensitivityValue::new_from_patch(value).get();
+            self.cache.clear();
+        }
+    }
+
+    pub fn set_feedback(&mut self, value: f32) {
+        self.feedback = VelocitySensitivityValue::new_from_patch(value).get();
+        self.cache.clear();
+    }
+
+    pub fn set_peak(&mut self, value: f32) {
+        self.peak = OperatorEnvelopeVelocitySensitivityValue::new_from_patch(value).get();
+        self.cache.clear();
+    }
+
+    pub fn set_sustain(&mut self, value: f32) {
+        self.sustain = OperatorEnvelopeVelocitySensitivityValue::new_from_patch(value).get();
+        self.cache.clear();
+    }
+
+    pub fn theme_changed(&mut self) {
+        self.cache.clear();
+    }
+
+    pub fn view(&self, theme: &Theme) -> Element<Message, Theme> {
+        let canvas = Canvas::new(self)
+            .width(Length::Fixed(WIDTH.into()))
+            .height(Length::Fixed(HEIGHT.into()));
+
+        let text = if self.mod_out.is_some() {
+            "Level by key velocity (low to high)\n\nBlue: modulation output\nGreen: feedback\nRed: envelope peak\nGray: envelope sustain"
+        } else {
+            "Level by key velocity (low to high)\n\nGreen: feedback\nRed: envelope peak\nGray: envelope sustain"
+        };
+
+        tooltip(theme, text, Position::Bottom, canvas).into()
+    }
+
+    /// Line from level at zero velocity to full level at maximum velocity,
+    /// matching how sensitivity is applied in audio generation
+    fn curve_path(sensitivity: f32) -> Path {
+        let level_to_y = |level: f32| PADDING + (1.0 - level) * (f32::from(HEIGHT) - 2.0 * PADDING);
+
+        Path::line(
+            Point::new(0.5, level_to_y(1.0 - sensitivity)),
+            Point::new(f32::from(WIDTH) - 0.5, level_to_y(1.0)),
+        )
+    }
+
+    fn draw_curves(&self, frame: &mut Frame, theme: &Theme) {
+        let appearance = theme.appearance();
+
+        frame.fill(&self.bounds_path, appearance.background_color);
+
+        let mut curves = vec![
+            (self.sustain, appearance.sustain_color),
+            (self.peak, appearance.peak_color),
+            (self.feedback, appearance.feedback_color),
+        ];
+
+        if let Some(mod_out) = self.mod_out {
+            curves.push((mod_out, appearance.mod_out_color));
+        }
+
+        for (sensitivity, color) in curves {
+            frame.stroke(
+                &Self::curve_path(sensitivity),
+                Stroke::default().with_color(color),
+            );
+        }
+
+        frame.stroke(
+            &self.bounds_path,
+            Stroke::default().with_color(appearance.border_color),
+        );
+    }
+}
+
+impl Program<Message, Theme> for VelocityCurve {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        theme: &Theme,
+        bounds: Rectangle,
+        _cursor: Cursor,
+    ) -> Vec<Geometry> {
+        let geometry = self.cache.draw(bounds.size(), |frame| {
+            self.draw_curves(frame, theme);
+        });
+
+        vec![geometry]
+    }
+}
@@ -168,6 +168,19 @@ mod gen {
                         ((feedback * phases.triangle()) + modulation_in + phases).triangle()
                     }
                     WaveType::Saw => ((feedback * phases.saw()) + modulation_in + phases).saw(),
+                    // Display without smoothing, as at very low frequencies
+                    WaveType::SquareBandLimited => {
+                        let dt = Pd::new_zeroed();
+
+                        ((feedback * phases.square_band_limited(dt)) + modulation_in + phases)
+                            .square_band_limited(dt)
+                    }
+                    WaveType::SawBandLimited => {
+                        let dt = Pd::new_zeroed();
+
+                        ((feedback * phases.saw_band_limited(dt)) + modulation_in + phases)
+                            .saw_band_limited(dt)
+                    }
                     WaveType::WhiteNoise => {
                         let mut random_numbers = <Pd as SimdPackedDouble>::Arr::default();
 
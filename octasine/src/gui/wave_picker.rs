@@ -7,6 +7,8 @@ use iced_baseview::{
     Length, Point, Rectangle, Size,
 };
 
+use std::f32::consts::TAU;
+
 use crate::common::{Phase, WaveformChoices};
 use crate::parameters::{Parameter, ParameterValue, WrappedParameter};
 use crate::sync::GuiSyncHandle;
@@ -30,6 +32,7 @@ pub struct Appearance {
     pub border_color_hovered: Color,
     pub shape_line_color_active: Color,
     pub shape_line_color_hovered: Color,
+    pub modulated_line_color: Color,
 }
 
 pub trait StyleSheet {
@@ -80,6 +83,16 @@ where
         }
     }
 
+    /// Set feedback (self-modulation) to use in shape preview
+    pub fn set_feedback(&mut self, feedback: f32) {
+        self.canvas.set_feedback(feedback);
+    }
+
+    /// Set modulation index to use in modulated sine carrier preview
+    pub fn set_modulation_index(&mut self, modulation_index: f32) {
+        self.canvas.set_modulation_index(modulation_index);
+    }
+
     pub fn view(&self, theme: &Theme) -> Element<Message, Theme> {
         let title = Text::new(&self.title)
             .horizontal_alignment(Horizontal::Center)
@@ -110,6 +123,8 @@ struct WavePickerCanvas<P: ParameterValue> {
     cache: Cache,
     bounds_path: Path,
     shape: P::Value,
+    feedback: f32,
+    modulation_index: Option<f32>,
 }
 
 impl<P> WavePickerCanvas<P>
@@ -128,6 +143,8 @@ where
             cache: Cache::new(),
             bounds_path,
             shape,
+            feedback: 0.0,
+            modulation_index: None,
         }
     }
 
@@ -147,6 +164,24 @@ where
         self.cache.clear();
     }
 
+    pub fn set_feedback(&mut self, feedback: f32) {
+        if self.feedback != feedback {
+            self.feedback = feedback;
+            self.cache.clear();
+        }
+    }
+
+    pub fn set_modulation_index(&mut self, modulation_index: f32) {
+        if self.modulation_index != Some(modulation_index) {
+            self.modulation_index = Some(modulation_index);
+            self.cache.clear();
+        }
+    }
+
+    fn calculate_shape(&self, phase: Phase) -> f32 {
+        WaveformChoices::calculate_with_feedback(self.shape, phase, self.feedback)
+    }
+
     fn draw_background(&self, frame: &mut Frame, theme: &Theme) {
         let apparence = theme.appearance();
 
@@ -186,7 +221,7 @@ where
 
         for i in 0..WIDTH - 1 {
             let phase = Phase((i as f64) / (WIDTH - 1) as f64);
-            let y = WaveformChoices::calculate_for_current(self.shape, phase);
+            let y = self.calculate_shape(phase);
 
             let visual_y = HEIGHT_MIDDLE - y * SHAPE_HEIGHT_RANGE;
             let visual_x = 0.5 + i as f32;
@@ -210,6 +245,38 @@ where
 
         frame.stroke(&path, stroke)
     }
+
+    /// Draw approximation of a sine carrier at the same frequency being
+    /// modulated by this wave with current modulation index
+    fn draw_modulated_line(&self, frame: &mut Frame, theme: &Theme) {
+        let modulation_index = match self.modulation_index {
+            Some(modulation_index) if modulation_index > 0.0 => modulation_index,
+            _ => return,
+        };
+
+        let appearance = theme.appearance();
+
+        let mut path = path::Builder::new();
+
+        for i in 0..WIDTH - 1 {
+            let phase = (i as f64) / (WIDTH - 1) as f64;
+            let modulation = modulation_index * self.calculate_shape(Phase(phase));
+            let y = (phase as f32 * TAU + modulation).sin();
+
+            let visual_y = HEIGHT_MIDDLE - y * SHAPE_HEIGHT_RANGE;
+            let visual_x = 0.5 + i as f32;
+
+            if i == 0 {
+                path.move_to(Point::new(visual_x, visual_y))
+            } else {
+                path.line_to(Point::new(visual_x, visual_y))
+            }
+        }
+
+        let stroke = Stroke::default().with_color(appearance.modulated_line_color);
+
+        frame.stroke(&path.build(), stroke)
+    }
 }
 
 impl<P> Program<Message, Theme> for WavePickerCanvas<P>
@@ -229,6 +296,7 @@ where
         let geometry = self.cache.draw(bounds.size(), |frame| {
             self.draw_background(frame, theme);
             self.draw_middle_line(frame, theme);
+            self.draw_modulated_line(frame, theme);
             self.draw_shape_line(state, frame, theme);
             self.draw_border(state, frame, theme);
         });
@@ -1,16 +1,30 @@
+#[cfg(feature = "audit-allocations")]
+pub mod alloc_audit;
 pub mod audio;
+#[cfg(feature = "capi")]
+pub mod capi;
 pub mod common;
 pub mod math;
 pub mod parameters;
 pub mod plugin;
+#[cfg(feature = "python")]
+mod python;
+#[cfg(feature = "scripting")]
+pub mod scripting;
 pub mod settings;
 pub mod simd;
 pub mod sync;
+pub mod synth;
 pub mod utils;
+pub mod wavetable;
 
 #[cfg(feature = "gui")]
 pub mod gui;
 
+#[cfg(all(test, feature = "audit-allocations"))]
+#[global_allocator]
+static GLOBAL_ALLOCATOR: alloc_audit::AuditAllocator = alloc_audit::AuditAllocator;
+
 #[cfg(feature = "clap")]
 #[no_mangle]
 pub static clap_entry: ::clap_sys::entry::clap_plugin_entry = plugin::clap::CLAP_ENTRY;
@@ -21,7 +35,10 @@ pub static clap_entry: ::clap_sys::entry::clap_plugin_entry = plugin::clap::CLAP
 #[cfg(test)]
 mod tests {
     use crate::{
-        audio::AudioState, common::SampleRate, parameters::PARAMETERS, sync::SyncState,
+        audio::AudioState,
+        common::SampleRate,
+        parameters::{MasterParameter, Parameter, PARAMETERS},
+        sync::SyncState,
         utils::update_audio_parameters,
     };
 
@@ -35,8 +52,13 @@ mod tests {
 
         let mut patch_values = Vec::new();
 
-        for i in 0..PARAMETERS.len() {
-            let patch_value = fastrand::f32();
+        for (i, parameter) in PARAMETERS.iter().enumerate() {
+            // Setting patch select to another value would switch patch
+            let patch_value = if *parameter == Parameter::Master(MasterParameter::PatchSelect) {
+                0.0
+            } else {
+                fastrand::f32()
+            };
 
             sync.patches.set_parameter_from_host(i, patch_value);
 
@@ -62,4 +84,55 @@ mod tests {
             assert!(values_approx_eq)
         }
     }
+
+    /// Audio processing, including parameter updates and note events, must
+    /// not allocate
+    #[cfg(feature = "audit-allocations")]
+    #[test]
+    fn test_audio_processing_does_not_allocate() {
+        use crate::alloc_audit::count_allocations;
+        use crate::audio::gen::process_f32_runtime_select;
+        use crate::common::{NoteEvent, NoteEventInner};
+
+        let mut audio = AudioState::default();
+
+        audio.set_sample_rate(SampleRate(44100.0));
+
+        let sync = SyncState::<()>::new(None);
+
+        fastrand::seed(1);
+
+        let mut lefts = [0.0f32; 256];
+        let mut rights = [0.0f32; 256];
+
+        for block in 0..64u8 {
+            for (i, parameter) in PARAMETERS.iter().enumerate() {
+                if *parameter != Parameter::Master(MasterParameter::PatchSelect) {
+                    sync.patches.set_parameter_from_host(i, fastrand::f32());
+                }
+            }
+
+            let events = (0..16u8).map(|i| NoteEvent {
+                delta_frames: u32::from(i) * 16,
+                event: NoteEventInner::Midi {
+                    data: match i % 4 {
+                        0 => [0x90, 32 + block + i, 100],
+                        1 => [0x80, 32 + block + i - 1, 0],
+                        2 => [0xB0, 1, fastrand::u8(..128)],
+                        _ => [0xE0, 0, fastrand::u8(..128)],
+                    },
+                },
+            });
+
+            let ((), allocations) = count_allocations(|| {
+                audio.enqueue_note_events(events);
+
+                process_f32_runtime_select(&mut audio, &mut lefts, &mut rights, 0, |audio| {
+                    update_audio_parameters(audio, &sync);
+                });
+            });
+
+            assert_eq!(allocations, 0, "allocations in block {}", block);
+        }
+    }
 }
@@ -71,3 +71,46 @@ pub fn saw(x: f64) -> f64 {
 
     (y - 0.5) * 2.0
 }
+
+/// Polynomial band-limited step (PolyBLEP) residual, used to smooth
+/// discontinuities over roughly one sample on each side
+///
+/// t is phase in range 0.0 to 1.0 and dt is phase increment per sample.
+#[inline]
+pub fn poly_blep(t: f64, dt: f64) -> f64 {
+    if t < dt {
+        let t = t / dt;
+
+        t + t - t * t - 1.0
+    } else if t > 1.0 - dt {
+        let t = (t - 1.0) / dt;
+
+        t * t + t + t + 1.0
+    } else {
+        0.0
+    }
+}
+
+/// Saw wave with discontinuity smoothed according to phase increment dt,
+/// greatly reducing aliasing
+#[inline]
+pub fn saw_band_limited(x: f64, dt: f64) -> f64 {
+    let dt = dt.abs().min(0.5);
+    let t = x - x.floor();
+
+    (t * 2.0 - 1.0) - poly_blep(t, dt)
+}
+
+/// Square wave with discontinuities smoothed according to phase increment
+/// dt, greatly reducing aliasing
+#[inline]
+pub fn square_band_limited(x: f64, dt: f64) -> f64 {
+    let dt = dt.abs().min(0.5);
+    let t = x - x.floor();
+    let t_half = t + 0.5;
+    let t_half = t_half - t_half.floor();
+
+    let naive = if t < 0.5 { 1.0 } else { -1.0 };
+
+    naive + poly_blep(t, dt) - poly_blep(t_half, dt)
+}
@@ -0,0 +1,165 @@
+use compact_str::CompactString;
+
+use super::{ParameterValue, SerializableRepresentation};
+
+/// Maximum interval (in semitones, above played key) of stored chord
+pub const CHORD_MEMORY_MAX_INTERVAL: u8 = 23;
+
+/// Stored chord as bitmask of intervals above played key, where bit n means
+/// an interval of n + 1 semitones. The played key itself is always included.
+/// An empty mask means that chord memory is turned off.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct ChordMemory(u32);
+
+impl ChordMemory {
+    const MASK: u32 = (1 << CHORD_MEMORY_MAX_INTERVAL) - 1;
+
+    /// Learn chord from bitmask of held keys, relative to lowest held key.
+    /// Keys too far above lowest key are ignored.
+    pub fn from_held_keys(held_keys: u128) -> Self {
+        if held_keys == 0 {
+            return Self::default();
+        }
+
+        let relative = held_keys >> held_keys.trailing_zeros();
+
+        Self(((relative >> 1) as u32) & Self::MASK)
+    }
+
+    pub fn is_active(self) -> bool {
+        self.0 != 0
+    }
+
+    /// Intervals in semitones above played key, not including zero
+    pub fn intervals(self) -> impl Iterator<Item = u8> {
+        (1..=CHORD_MEMORY_MAX_INTERVAL).filter(move |i| self.0 & (1 << (i - 1)) != 0)
+    }
+
+    /// Keys to trigger for played key, including the played key itself
+    pub fn keys(self, key: u8) -> impl Iterator<Item = u8> {
+        ::std::iter::once(key).chain(
+            self.intervals()
+                .filter_map(move |interval| key.checked_add(interval))
+                .filter(|key| *key < 128),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ChordMemoryValue(ChordMemory);
+
+impl ParameterValue for ChordMemoryValue {
+    type Value = ChordMemory;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        let text = text.trim().to_lowercase();
+
+        if text == "off" {
+            return Some(Self::default());
+        }
+
+        let mut mask = 0u32;
+
+        for interval in text.split('-') {
+            let interval: u8 = interval.trim().parse().ok()?;
+
+            match interval {
+                0 => (),
+                i if i <= CHORD_MEMORY_MAX_INTERVAL => mask |= 1 << (i - 1),
+                _ => return None,
+            }
+        }
+
+        Some(Self(ChordMemory(mask)))
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        // Each mask is one step, with 1.0 mapping to the full mask. Rounding
+        // absorbs f32 precision loss, since steps are far wider than it.
+        let mask = (f64::from(value.clamp(0.0, 1.0)) * f64::from(ChordMemory::MASK)).round();
+
+        Self(ChordMemory(mask as u32))
+    }
+    fn to_patch(self) -> f32 {
+        (f64::from(self.0 .0) / f64::from(ChordMemory::MASK)) as f32
+    }
+    fn get_formatted(self) -> CompactString {
+        if !self.0.is_active() {
+            return "OFF".into();
+        }
+
+        let mut text = CompactString::from("0");
+
+        for interval in self.0.intervals() {
+            text.push('-');
+            text.push_str(&interval.to_string());
+        }
+
+        text
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Other(self.get_formatted())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chord_memory_from_held_keys() {
+        let held_keys = (1u128 << 60) | (1 << 64) | (1 << 67);
+        let chord = ChordMemory::from_held_keys(held_keys);
+
+        assert_eq!(chord.intervals().collect::<Vec<_>>(), vec![4, 7]);
+        assert_eq!(chord.keys(50).collect::<Vec<_>>(), vec![50, 54, 57]);
+        assert_eq!(chord.keys(125).collect::<Vec<_>>(), vec![125]);
+
+        assert!(!ChordMemory::from_held_keys(0).is_active());
+        assert!(!ChordMemory::from_held_keys(1 << 60).is_active());
+    }
+
+    #[test]
+    fn test_chord_memory_value_roundtrip() {
+        for mask in [0, 1, 0b1001000, ChordMemory::MASK] {
+            let value = ChordMemoryValue(ChordMemory(mask));
+
+            assert_eq!(
+                ChordMemoryValue::new_from_patch(value.to_patch()).get(),
+                value.get()
+            );
+
+            let text = value.get_formatted();
+
+            assert_eq!(
+                ChordMemoryValue::new_from_text(&text).unwrap().get(),
+                value.get()
+            );
+        }
+    }
+
+    #[test]
+    fn test_chord_memory_value_patch_steps() {
+        for mask in 0..=ChordMemory::MASK {
+            let value = ChordMemoryValue(ChordMemory(mask));
+
+            assert_eq!(
+                ChordMemoryValue::new_from_patch(value.to_patch()).get(),
+                value.get(),
+                "mask {:#b}",
+                mask
+            );
+        }
+
+        assert_eq!(
+            ChordMemoryValue::new_from_patch(1.0).get(),
+            ChordMemory(ChordMemory::MASK)
+        );
+    }
+}
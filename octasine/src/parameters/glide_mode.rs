@@ -9,8 +9,11 @@ pub const GLIDE_MODE_STEPS: &[GlideMode] = &[GlideMode::Lct, GlideMode::Lcr];
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum GlideMode {
+    /// Linear constant time: glide duration is independent of pitch interval
     #[default]
     Lct,
+    /// Linear constant rate: glide duration scales with pitch interval
+    /// (glide time is time per octave)
     Lcr,
 }
 
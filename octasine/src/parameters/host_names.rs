@@ -0,0 +1,404 @@
+//! Parameter names and ordering presented to hosts, e.g., in generic
+//! editors and automation lanes. Related parameters are grouped and
+//! consistently named, e.g., "Op1 Env Attack". Internal names and keys,
+//! which patches depend on, are unaffected.
+
+use compact_str::{format_compact, CompactString};
+use once_cell::sync::Lazy;
+
+use super::{LfoParameter, MasterParameter, OperatorParameter, Parameter, PARAMETERS};
+
+/// Maximum length of short parameter names. VST2 hosts following the
+/// specification truncate parameter names to this length.
+pub const MAX_SHORT_NAME_LEN: usize = 8;
+
+/// Section of master, operator or LFO parameters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HostGroup {
+    General,
+    Voice,
+    Glide,
+    PitchBend,
+    Vibrato,
+    NoteRandom,
+    Mpe,
+    Oscillator,
+    Frequency,
+    Output,
+    Modulation,
+    Envelope,
+    NoteRange,
+    Target,
+    Shape,
+    Sync,
+}
+
+impl HostGroup {
+    /// Prefix for parameter names, empty for general parameters
+    fn name_prefix(self) -> &'static str {
+        match self {
+            Self::General => "",
+            Self::Voice => "Voice",
+            Self::Glide => "Glide",
+            Self::PitchBend => "Pitch Bend",
+            Self::Vibrato => "Vibrato",
+            Self::NoteRandom => "Random",
+            Self::Mpe => "MPE",
+            Self::Oscillator => "Osc",
+            Self::Frequency => "Freq",
+            Self::Output => "Out",
+            Self::Modulation => "Mod",
+            Self::Envelope => "Env",
+            Self::NoteRange => "Range",
+            Self::Target => "Target",
+            Self::Shape => "Shape",
+            Self::Sync => "Sync",
+        }
+    }
+
+    /// Module path component, e.g., for CLAP hosts
+    fn path(self) -> &'static str {
+        match self {
+            Self::General => "",
+            Self::Voice => "Voice",
+            Self::Glide => "Glide",
+            Self::PitchBend => "Pitch bend",
+            Self::Vibrato => "Vibrato",
+            Self::NoteRandom => "Random per note",
+            Self::Mpe => "MPE",
+            Self::Oscillator => "Oscillator",
+            Self::Frequency => "Frequency",
+            Self::Output => "Output",
+            Self::Modulation => "Modulation",
+            Self::Envelope => "Envelope",
+            Self::NoteRange => "Note range",
+            Self::Target => "Targets",
+            Self::Shape => "Shape",
+            Self::Sync => "Sync",
+        }
+    }
+}
+
+/// Master parameters in host order with group, name and short name
+const MASTER: &[(MasterParameter, HostGroup, &str, &str)] = {
+    use HostGroup as G;
+    use MasterParameter::*;
+
+    &[
+        (Volume, G::General, "Master Volume", "MstVol"),
+        (Frequency, G::General, "Master Freq", "MstFreq"),
+        (
+            VelocitySensitivityVolume,
+            G::General,
+            "Master Vol Vel Sens",
+            "MstVelS",
+        ),
+        (AntiAlias, G::General, "Anti-Alias", "AntiAls"),
+        (ModSnapshot, G::General, "Mod Snapshot", "ModSnap"),
+        (PatchSelect, G::General, "Patch Select", "Patch"),
+        (VoiceMode, G::Voice, "Mode", "VoicMode"),
+        (MaxPolyphony, G::Voice, "Max Polyphony", "MaxPoly"),
+        (NotePriority, G::Voice, "Note Priority", "NotePrio"),
+        (LegatoEnvelopeMode, G::Voice, "Legato Env Mode", "LegatoEn"),
+        (ChordMemory, G::Voice, "Chord Memory", "Chord"),
+        (Scale, G::Voice, "Scale", "Scale"),
+        (ScaleRoot, G::Voice, "Scale Root", "ScaleRt"),
+        (MidiOut, G::Voice, "MIDI Out", "MidiOut"),
+        (GlideActive, G::Glide, "Active", "GlideOn"),
+        (GlideTime, G::Glide, "Time", "GlideTm"),
+        (GlideBpmSync, G::Glide, "BPM Sync", "GlideBpm"),
+        (GlideMode, G::Glide, "Mode", "GlideMod"),
+        (GlideRetrigger, G::Glide, "Retrigger", "GlideRtg"),
+        (PitchBendRangeUp, G::PitchBend, "Range Up", "PBUp"),
+        (PitchBendRangeDown, G::PitchBend, "Range Down", "PBDown"),
+        (PitchBendSmoothing, G::PitchBend, "Smoothing", "PBSmooth"),
+        (VibratoRate, G::Vibrato, "Rate", "VibRate"),
+        (VibratoDepth, G::Vibrato, "Depth", "VibDepth"),
+        (VibratoDelay, G::Vibrato, "Delay", "VibDelay"),
+        (VibratoModWheel, G::Vibrato, "Mod Wheel", "VibModWh"),
+        (NoteRandomTarget, G::NoteRandom, "Target", "RndTgt"),
+        (NoteRandomAmount, G::NoteRandom, "Amount", "RndAmt"),
+        (MpeZone, G::Mpe, "Zone", "MPEZone"),
+        (
+            MpeMemberPitchBendRange,
+            G::Mpe,
+            "Member PB Range",
+            "MPEPBRng",
+        ),
+        (MpePressureTarget, G::Mpe, "Pressure Target", "MPEPrTgt"),
+        (MpePressureAmount, G::Mpe, "Pressure Amount", "MPEPrAmt"),
+        (MpeTimbreTarget, G::Mpe, "Timbre Target", "MPETbTgt"),
+        (MpeTimbreAmount, G::Mpe, "Timbre Amount", "MPETbAmt"),
+    ]
+};
+
+/// Operator parameters in host order with group, name and short name
+/// (without operator number)
+const OPERATOR: &[(OperatorParameter, HostGroup, &str, &str)] = {
+    use HostGroup as G;
+    use OperatorParameter::*;
+
+    &[
+        (Active, G::General, "Active", "On"),
+        (WaveType, G::Oscillator, "Wave", "Wave"),
+        (WavetablePosition, G::Oscillator, "WT Position", "WTPos"),
+        (PhaseDistortion, G::Oscillator, "Phase Dist", "PhDist"),
+        (NoiseStereo, G::Oscillator, "Stereo Noise", "StNois"),
+        (FrequencyRatio, G::Frequency, "Ratio", "FrRat"),
+        (FrequencyFree, G::Frequency, "Free", "FrFree"),
+        (FrequencyFine, G::Frequency, "Fine", "FrFine"),
+        (Volume, G::Output, "Volume", "Vol"),
+        (MixOut, G::Output, "Mix", "Mix"),
+        (Panning, G::Output, "Pan", "Pan"),
+        (Spread, G::Output, "Stereo Spread", "Spread"),
+        (ModTargets, G::Modulation, "Target", "ModTgt"),
+        (ModOut, G::Modulation, "Amount", "ModOut"),
+        (
+            VelocitySensitivityModOut,
+            G::Modulation,
+            "Amount Vel Sens",
+            "ModVS",
+        ),
+        (Feedback, G::Modulation, "Feedback", "Fb"),
+        (
+            VelocitySensitivityFeedback,
+            G::Modulation,
+            "Feedback Vel Sens",
+            "FbVS",
+        ),
+        (CrossFeedback, G::Modulation, "Cross FB", "XFb"),
+        (
+            CrossFeedbackTarget,
+            G::Modulation,
+            "Cross FB Target",
+            "XFbTgt",
+        ),
+        (AttackDuration, G::Envelope, "Attack", "EnvA"),
+        (DecayDuration, G::Envelope, "Decay", "EnvD"),
+        (SustainVolume, G::Envelope, "Sustain", "EnvS"),
+        (ReleaseDuration, G::Envelope, "Release", "EnvR"),
+        (ReleaseMode, G::Envelope, "Release Mode", "EnvRM"),
+        (EnvelopeLockGroup, G::Envelope, "Lock Group", "EnvLk"),
+        (
+            VelocitySensitivityPeak,
+            G::Envelope,
+            "Peak Vel Sens",
+            "EnvPVS",
+        ),
+        (
+            VelocitySensitivitySustain,
+            G::Envelope,
+            "Sustain Vel Sens",
+            "EnvSVS",
+        ),
+        (KeyRangeLow, G::NoteRange, "Key Low", "KeyLo"),
+        (KeyRangeHigh, G::NoteRange, "Key High", "KeyHi"),
+        (VelocityRangeLow, G::NoteRange, "Vel Low", "VelLo"),
+        (VelocityRangeHigh, G::NoteRange, "Vel High", "VelHi"),
+    ]
+};
+
+/// LFO parameters in host order with group, name and short name (without
+/// LFO number)
+const LFO: &[(LfoParameter, HostGroup, &str, &str)] = {
+    use HostGroup as G;
+    use LfoParameter::*;
+
+    &[
+        (Active, G::General, "Active", "On"),
+        (Amount, G::General, "Amount", "Amt"),
+        (VelocitySensitivity, G::General, "Amount Vel Sens", "AmtVS"),
+        (ModWheelSensitivity, G::General, "Amount Mod Wheel", "AmtMW"),
+        (Target, G::Target, "1", "Tgt1"),
+        (Target2, G::Target, "2", "Tgt2"),
+        (Target2Depth, G::Target, "2 Depth", "T2Dep"),
+        (Target3, G::Target, "3", "Tgt3"),
+        (Target3Depth, G::Target, "3 Depth", "T3Dep"),
+        (Shape, G::Shape, "Wave", "Shape"),
+        (Polarity, G::Shape, "Polarity", "Polar"),
+        (Mode, G::Shape, "One-Shot", "1Shot"),
+        (Smoothing, G::Shape, "Smoothing", "Smooth"),
+        (EnvelopeCurve, G::Shape, "Env Curve", "EnvCrv"),
+        (EnvelopeAttack, G::Shape, "Env Attack", "EnvAtk"),
+        (FrequencyRatio, G::Frequency, "Ratio", "FrRat"),
+        (FrequencyFree, G::Frequency, "Free", "FrFree"),
+        (BpmSync, G::Frequency, "BPM Sync", "BpmSyn"),
+        (KeySync, G::Sync, "Key", "KeySyn"),
+        (KeySyncSpread, G::Sync, "Key Spread", "KSprd"),
+        (TransportSync, G::Sync, "Transport", "TrSync"),
+    ]
+};
+
+/// Position in host order within section, group and names
+fn lookup(parameter: Parameter) -> Option<(usize, HostGroup, &'static str, &'static str)> {
+    fn find<P: PartialEq>(
+        table: &[(P, HostGroup, &'static str, &'static str)],
+        p: P,
+    ) -> Option<(usize, HostGroup, &'static str, &'static str)> {
+        table
+            .iter()
+            .position(|(q, _, _, _)| *q == p)
+            .map(|i| (i, table[i].1, table[i].2, table[i].3))
+    }
+
+    match parameter {
+        Parameter::None => None,
+        Parameter::Master(p) => find(MASTER, p),
+        Parameter::Operator(_, p) => find(OPERATOR, p),
+        Parameter::Lfo(_, p) => find(LFO, p),
+    }
+}
+
+/// PARAMETERS indices sorted for host listing: master parameters, then
+/// each operator and each LFO, with related parameters adjacent
+static HOST_ORDER: Lazy<Vec<usize>> = Lazy::new(|| {
+    let mut indices: Vec<usize> = (0..PARAMETERS.len()).collect();
+
+    indices.sort_by_key(|i| {
+        let parameter = PARAMETERS[*i];
+        let position = lookup(parameter).map(|(position, ..)| position);
+
+        let section = match parameter {
+            Parameter::None => (0, 0),
+            Parameter::Master(_) => (1, 0),
+            Parameter::Operator(index, _) => (2, index),
+            Parameter::Lfo(index, _) => (3, index),
+        };
+
+        // Unlisted parameters are placed last in their section
+        (section, position.unwrap_or(usize::MAX), *i)
+    });
+
+    indices
+});
+
+/// Get PARAMETERS index of parameter at position in host order
+pub fn parameter_index_in_host_order(host_index: usize) -> Option<usize> {
+    HOST_ORDER.get(host_index).copied()
+}
+
+impl Parameter {
+    /// Name grouped by section for hosts, e.g., "Op1 Env Attack"
+    pub fn host_name(&self) -> CompactString {
+        let (group, name) = match lookup(*self) {
+            Some((_, group, name, _)) => (group, name),
+            None => return self.name(),
+        };
+
+        let section = match self {
+            Self::None | Self::Master(_) => None,
+            Self::Operator(index, _) => Some(format_compact!("Op{}", index + 1)),
+            Self::Lfo(index, _) => Some(format_compact!("LFO{}", index + 1)),
+        };
+
+        let mut host_name = CompactString::default();
+
+        for part in [section.as_deref(), Some(group.name_prefix()), Some(name)]
+            .into_iter()
+            .flatten()
+            .filter(|part| !part.is_empty())
+        {
+            if !host_name.is_empty() {
+                host_name.push(' ');
+            }
+
+            host_name.push_str(part);
+        }
+
+        host_name
+    }
+
+    /// Name of at most MAX_SHORT_NAME_LEN characters for hosts that
+    /// truncate names, e.g., "O1EnvA"
+    pub fn host_short_name(&self) -> CompactString {
+        let short_name = match lookup(*self) {
+            Some((_, _, _, short_name)) => short_name,
+            None => return self.name().chars().take(MAX_SHORT_NAME_LEN).collect(),
+        };
+
+        match self {
+            Self::None | Self::Master(_) => short_name.into(),
+            Self::Operator(index, _) => format_compact!("O{}{}", index + 1, short_name),
+            Self::Lfo(index, _) => format_compact!("L{}{}", index + 1, short_name),
+        }
+    }
+
+    /// CLAP module path of parameter group, e.g., "Operator 1/Envelope"
+    pub fn clap_path(&self) -> CompactString {
+        let group = lookup(*self)
+            .map(|(_, group, ..)| group)
+            .unwrap_or(HostGroup::General);
+
+        let section = match self {
+            Self::None => return "None".into(),
+            Self::Master(_) => CompactString::from("Master"),
+            Self::Operator(index, _) => format_compact!("Operator {}", index + 1),
+            Self::Lfo(index, _) => format_compact!("LFO {}", index + 1),
+        };
+
+        if group == HostGroup::General {
+            section
+        } else {
+            format_compact!("{}/{}", section, group.path())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn test_host_names() {
+        assert_eq!(
+            Parameter::Operator(0, OperatorParameter::AttackDuration).host_name(),
+            "Op1 Env Attack"
+        );
+        assert_eq!(
+            Parameter::Lfo(1, LfoParameter::FrequencyRatio).host_name(),
+            "LFO2 Freq Ratio"
+        );
+        assert_eq!(
+            Parameter::Master(MasterParameter::Volume).host_name(),
+            "Master Volume"
+        );
+
+        let mut names = HashSet::new();
+        let mut short_names = HashSet::new();
+
+        for parameter in PARAMETERS.iter() {
+            assert!(lookup(*parameter).is_some(), "{:?} not listed", parameter);
+
+            let short_name = parameter.host_short_name();
+
+            assert!(short_name.len() <= MAX_SHORT_NAME_LEN, "{}", short_name);
+
+            assert!(names.insert(parameter.host_name()));
+            assert!(short_names.insert(short_name));
+        }
+    }
+
+    #[test]
+    fn test_host_order() {
+        let mut indices = HOST_ORDER.clone();
+
+        indices.sort();
+
+        assert_eq!(indices, (0..PARAMETERS.len()).collect::<Vec<_>>());
+
+        // Operator envelope parameters are adjacent
+        let attack_position = HOST_ORDER
+            .iter()
+            .position(|i| {
+                PARAMETERS[*i] == Parameter::Operator(0, OperatorParameter::AttackDuration)
+            })
+            .unwrap();
+
+        assert_eq!(
+            PARAMETERS[HOST_ORDER[attack_position + 1]],
+            Parameter::Operator(0, OperatorParameter::DecayDuration)
+        );
+    }
+}
@@ -0,0 +1,69 @@
+use compact_str::{format_compact, CompactString};
+
+use super::{
+    utils::{map_patch_value_to_step, map_step_to_patch_value},
+    ParameterValue, SerializableRepresentation,
+};
+
+pub const LEGATO_ENVELOPE_MODE_STEPS: &[LegatoEnvelopeMode] = &[
+    LegatoEnvelopeMode::Partial,
+    LegatoEnvelopeMode::Retrigger,
+    LegatoEnvelopeMode::Continue,
+];
+
+/// Operator envelope behavior on legato notes in monophonic mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LegatoEnvelopeMode {
+    /// Restart attack from current envelope level
+    #[default]
+    Partial,
+    /// Restart attack from zero
+    Retrigger,
+    /// Don't restart envelopes
+    Continue,
+}
+
+impl ::std::fmt::Display for LegatoEnvelopeMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Partial => "PART",
+            Self::Retrigger => "RTRG",
+            Self::Continue => "CONT",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LegatoEnvelopeModeValue(LegatoEnvelopeMode);
+
+impl ParameterValue for LegatoEnvelopeModeValue {
+    type Value = LegatoEnvelopeMode;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        match text.trim().to_lowercase().as_str() {
+            "part" | "partial" => Some(Self(LegatoEnvelopeMode::Partial)),
+            "rtrg" | "retrigger" => Some(Self(LegatoEnvelopeMode::Retrigger)),
+            "cont" | "continue" => Some(Self(LegatoEnvelopeMode::Continue)),
+            _ => None,
+        }
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(map_patch_value_to_step(LEGATO_ENVELOPE_MODE_STEPS, value))
+    }
+    fn to_patch(self) -> f32 {
+        map_step_to_patch_value(LEGATO_ENVELOPE_MODE_STEPS, self.0)
+    }
+    fn get_formatted(self) -> CompactString {
+        format_compact!("{}", self.0)
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Other(self.get_formatted())
+    }
+}
@@ -0,0 +1,158 @@
+use compact_str::{format_compact, CompactString};
+
+use super::utils::*;
+use super::ParameterValue;
+use super::SerializableRepresentation;
+use crate::common::*;
+
+pub const LFO_ENVELOPE_CURVE_STEPS: [LfoEnvelopeCurve; 4] = [
+    LfoEnvelopeCurve::Off,
+    LfoEnvelopeCurve::Linear,
+    LfoEnvelopeCurve::Exponential,
+    LfoEnvelopeCurve::Logarithmic,
+];
+
+/// When not turned off, LFO runs a single cycle as an attack-decay
+/// modulation envelope instead of using its shape
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum LfoEnvelopeCurve {
+    #[default]
+    Off,
+    Linear,
+    Exponential,
+    Logarithmic,
+}
+
+impl LfoEnvelopeCurve {
+    pub fn is_active(self) -> bool {
+        self != Self::Off
+    }
+
+    /// Calculate envelope value in range [0.0, 1.0] for phase of single
+    /// cycle. Attack is fraction of cycle spent rising to peak.
+    ///
+    /// Returns None if envelope is turned off.
+    pub fn calculate(self, attack: f32, phase: Phase) -> Option<f32> {
+        let phase = phase.0 as f32;
+        let attack = attack.clamp(0.0, 1.0);
+
+        let x = if phase < attack {
+            phase / attack
+        } else {
+            1.0 - (phase - attack) / (1.0 - attack).max(f32::EPSILON)
+        };
+        let x = x.clamp(0.0, 1.0);
+
+        match self {
+            Self::Off => None,
+            Self::Linear => Some(x),
+            Self::Exponential => Some(x * x),
+            Self::Logarithmic => Some(1.0 - (1.0 - x) * (1.0 - x)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LfoEnvelopeCurveValue(pub LfoEnvelopeCurve);
+
+impl ParameterValue for LfoEnvelopeCurveValue {
+    type Value = LfoEnvelopeCurve;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        match text.trim().to_lowercase().as_ref() {
+            "off" => Some(Self(LfoEnvelopeCurve::Off)),
+            "lin" | "linear" => Some(Self(LfoEnvelopeCurve::Linear)),
+            "exp" | "exponential" => Some(Self(LfoEnvelopeCurve::Exponential)),
+            "log" | "logarithmic" => Some(Self(LfoEnvelopeCurve::Logarithmic)),
+            _ => None,
+        }
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(map_patch_value_to_step(
+            &LFO_ENVELOPE_CURVE_STEPS[..],
+            value,
+        ))
+    }
+    fn to_patch(self) -> f32 {
+        map_step_to_patch_value(&LFO_ENVELOPE_CURVE_STEPS[..], self.0)
+    }
+    fn get_formatted(self) -> CompactString {
+        match self.0 {
+            LfoEnvelopeCurve::Off => "OFF".into(),
+            LfoEnvelopeCurve::Linear => "LIN".into(),
+            LfoEnvelopeCurve::Exponential => "EXP".into(),
+            LfoEnvelopeCurve::Logarithmic => "LOG".into(),
+        }
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Other(self.get_formatted())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LfoEnvelopeAttackValue(f32);
+
+impl ParameterValue for LfoEnvelopeAttackValue {
+    type Value = f32;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        parse_valid_f32(text, 0.0, 1.0).map(Self)
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(value)
+    }
+    fn to_patch(self) -> f32 {
+        self.0
+    }
+    fn get_formatted(self) -> CompactString {
+        format_compact!("{:.04}", self.0)
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Float(self.0.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_envelope_curve_calculate() {
+        for curve in LFO_ENVELOPE_CURVE_STEPS {
+            if !curve.is_active() {
+                assert!(curve.calculate(0.5, Phase(0.25)).is_none());
+
+                continue;
+            }
+
+            for attack in [0.0, 0.25, 0.5, 1.0] {
+                for i in 0..100 {
+                    let value = curve.calculate(attack, Phase(i as f64 / 100.0)).unwrap();
+
+                    assert!((0.0..=1.0).contains(&value));
+                }
+
+                if attack > 0.0 {
+                    assert_eq!(curve.calculate(attack, Phase(0.0)), Some(0.0));
+                }
+                if attack < 1.0 {
+                    assert_eq!(curve.calculate(attack, Phase(attack as f64)), Some(1.0));
+                }
+            }
+        }
+    }
+}
@@ -25,7 +25,7 @@ impl ParameterValue for LfoModeValue {
     fn new_from_text(text: &str) -> Option<Self> {
         match text.to_lowercase().as_ref() {
             "once" => Some(Self(LfoMode::Once)),
-            "forever" => Some(Self(LfoMode::Forever)),
+            "forever" | "loop" => Some(Self(LfoMode::Forever)),
             _ => None,
         }
     }
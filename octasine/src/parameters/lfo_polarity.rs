@@ -0,0 +1,81 @@
+use compact_str::CompactString;
+
+use super::utils::*;
+use super::ParameterValue;
+use super::SerializableRepresentation;
+
+const LFO_POLARITY_STEPS: [LfoPolarity; 3] = [
+    LfoPolarity::Bipolar,
+    LfoPolarity::UnipolarUp,
+    LfoPolarity::UnipolarDown,
+];
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum LfoPolarity {
+    /// Modulate both upward and downward from base value
+    #[default]
+    Bipolar,
+    /// Only modulate upward from base value
+    UnipolarUp,
+    /// Only modulate downward from base value
+    UnipolarDown,
+}
+
+impl LfoPolarity {
+    /// Map LFO shape output in range [-1.0, 1.0] according to polarity
+    pub fn apply(self, value: f32) -> f32 {
+        match self {
+            Self::Bipolar => value,
+            Self::UnipolarUp => (value + 1.0) * 0.5,
+            Self::UnipolarDown => (value + 1.0) * -0.5,
+        }
+    }
+
+    /// Map value that is already unipolar (e.g., envelope output in range
+    /// [0.0, 1.0]) according to polarity
+    pub fn apply_to_unipolar(self, value: f32) -> f32 {
+        match self {
+            Self::Bipolar | Self::UnipolarUp => value,
+            Self::UnipolarDown => -value,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LfoPolarityValue(pub LfoPolarity);
+
+impl ParameterValue for LfoPolarityValue {
+    type Value = LfoPolarity;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        match text.to_lowercase().as_ref() {
+            "bi" | "bipolar" => Some(Self(LfoPolarity::Bipolar)),
+            "up" | "unipolar up" => Some(Self(LfoPolarity::UnipolarUp)),
+            "down" | "unipolar down" => Some(Self(LfoPolarity::UnipolarDown)),
+            _ => None,
+        }
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(map_patch_value_to_step(&LFO_POLARITY_STEPS[..], value))
+    }
+    fn to_patch(self) -> f32 {
+        map_step_to_patch_value(&LFO_POLARITY_STEPS[..], self.0)
+    }
+    fn get_formatted(self) -> CompactString {
+        match self.0 {
+            LfoPolarity::Bipolar => "BI".into(),
+            LfoPolarity::UnipolarUp => "UP".into(),
+            LfoPolarity::UnipolarDown => "DOWN".into(),
+        }
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Other(self.get_formatted())
+    }
+}
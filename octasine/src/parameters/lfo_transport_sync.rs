@@ -0,0 +1,64 @@
+use compact_str::CompactString;
+
+use super::utils::*;
+use super::ParameterValue;
+use super::SerializableRepresentation;
+
+const LFO_TRANSPORT_SYNC_STEPS: [LfoTransportSync; 3] = [
+    LfoTransportSync::Off,
+    LfoTransportSync::SongPosition,
+    LfoTransportSync::Restart,
+];
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum LfoTransportSync {
+    #[default]
+    Off,
+    /// Lock phase to host song position while transport is playing (only
+    /// when BPM sync is on)
+    SongPosition,
+    /// Restart LFO when host transport starts playing
+    Restart,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LfoTransportSyncValue(pub LfoTransportSync);
+
+impl ParameterValue for LfoTransportSyncValue {
+    type Value = LfoTransportSync;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        match text.to_lowercase().as_ref() {
+            "off" => Some(Self(LfoTransportSync::Off)),
+            "pos" | "song position" => Some(Self(LfoTransportSync::SongPosition)),
+            "rst" | "restart" => Some(Self(LfoTransportSync::Restart)),
+            _ => None,
+        }
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(map_patch_value_to_step(
+            &LFO_TRANSPORT_SYNC_STEPS[..],
+            value,
+        ))
+    }
+    fn to_patch(self) -> f32 {
+        map_step_to_patch_value(&LFO_TRANSPORT_SYNC_STEPS[..], self.0)
+    }
+    fn get_formatted(self) -> CompactString {
+        match self.0 {
+            LfoTransportSync::Off => "OFF".into(),
+            LfoTransportSync::SongPosition => "POS".into(),
+            LfoTransportSync::Restart => "RST".into(),
+        }
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Other(self.get_formatted())
+    }
+}
@@ -117,6 +117,127 @@ pub const PARAMETERS: &[Parameter] = &[
     Parameter::Master(MasterParameter::GlideBpmSync),
     Parameter::Master(MasterParameter::GlideMode),
     Parameter::Master(MasterParameter::GlideRetrigger),
+    Parameter::Lfo(0, LfoParameter::TransportSync),
+    Parameter::Lfo(1, LfoParameter::TransportSync),
+    Parameter::Lfo(2, LfoParameter::TransportSync),
+    Parameter::Lfo(3, LfoParameter::TransportSync),
+    Parameter::Lfo(0, LfoParameter::Target2),
+    Parameter::Lfo(0, LfoParameter::Target2Depth),
+    Parameter::Lfo(0, LfoParameter::Target3),
+    Parameter::Lfo(0, LfoParameter::Target3Depth),
+    Parameter::Lfo(1, LfoParameter::Target2),
+    Parameter::Lfo(1, LfoParameter::Target2Depth),
+    Parameter::Lfo(1, LfoParameter::Target3),
+    Parameter::Lfo(1, LfoParameter::Target3Depth),
+    Parameter::Lfo(2, LfoParameter::Target2),
+    Parameter::Lfo(2, LfoParameter::Target2Depth),
+    Parameter::Lfo(2, LfoParameter::Target3),
+    Parameter::Lfo(2, LfoParameter::Target3Depth),
+    Parameter::Lfo(3, LfoParameter::Target2),
+    Parameter::Lfo(3, LfoParameter::Target2Depth),
+    Parameter::Lfo(3, LfoParameter::Target3),
+    Parameter::Lfo(3, LfoParameter::Target3Depth),
+    Parameter::Lfo(0, LfoParameter::Polarity),
+    Parameter::Lfo(1, LfoParameter::Polarity),
+    Parameter::Lfo(2, LfoParameter::Polarity),
+    Parameter::Lfo(3, LfoParameter::Polarity),
+    Parameter::Lfo(0, LfoParameter::VelocitySensitivity),
+    Parameter::Lfo(0, LfoParameter::ModWheelSensitivity),
+    Parameter::Lfo(1, LfoParameter::VelocitySensitivity),
+    Parameter::Lfo(1, LfoParameter::ModWheelSensitivity),
+    Parameter::Lfo(2, LfoParameter::VelocitySensitivity),
+    Parameter::Lfo(2, LfoParameter::ModWheelSensitivity),
+    Parameter::Lfo(3, LfoParameter::VelocitySensitivity),
+    Parameter::Lfo(3, LfoParameter::ModWheelSensitivity),
+    Parameter::Lfo(0, LfoParameter::EnvelopeCurve),
+    Parameter::Lfo(0, LfoParameter::EnvelopeAttack),
+    Parameter::Lfo(1, LfoParameter::EnvelopeCurve),
+    Parameter::Lfo(1, LfoParameter::EnvelopeAttack),
+    Parameter::Lfo(2, LfoParameter::EnvelopeCurve),
+    Parameter::Lfo(2, LfoParameter::EnvelopeAttack),
+    Parameter::Lfo(3, LfoParameter::EnvelopeCurve),
+    Parameter::Lfo(3, LfoParameter::EnvelopeAttack),
+    Parameter::Lfo(0, LfoParameter::KeySyncSpread),
+    Parameter::Lfo(1, LfoParameter::KeySyncSpread),
+    Parameter::Lfo(2, LfoParameter::KeySyncSpread),
+    Parameter::Lfo(3, LfoParameter::KeySyncSpread),
+    Parameter::Master(MasterParameter::ChordMemory),
+    Parameter::Master(MasterParameter::MidiOut),
+    Parameter::Master(MasterParameter::Scale),
+    Parameter::Master(MasterParameter::ScaleRoot),
+    Parameter::Master(MasterParameter::LegatoEnvelopeMode),
+    Parameter::Master(MasterParameter::NotePriority),
+    Parameter::Operator(0, OperatorParameter::ReleaseMode),
+    Parameter::Operator(1, OperatorParameter::ReleaseMode),
+    Parameter::Operator(2, OperatorParameter::ReleaseMode),
+    Parameter::Operator(3, OperatorParameter::ReleaseMode),
+    Parameter::Operator(0, OperatorParameter::VelocitySensitivityPeak),
+    Parameter::Operator(0, OperatorParameter::VelocitySensitivitySustain),
+    Parameter::Operator(1, OperatorParameter::VelocitySensitivityPeak),
+    Parameter::Operator(1, OperatorParameter::VelocitySensitivitySustain),
+    Parameter::Operator(2, OperatorParameter::VelocitySensitivityPeak),
+    Parameter::Operator(2, OperatorParameter::VelocitySensitivitySustain),
+    Parameter::Operator(3, OperatorParameter::VelocitySensitivityPeak),
+    Parameter::Operator(3, OperatorParameter::VelocitySensitivitySustain),
+    Parameter::Operator(0, OperatorParameter::WavetablePosition),
+    Parameter::Operator(1, OperatorParameter::WavetablePosition),
+    Parameter::Operator(2, OperatorParameter::WavetablePosition),
+    Parameter::Operator(3, OperatorParameter::WavetablePosition),
+    Parameter::Operator(0, OperatorParameter::PhaseDistortion),
+    Parameter::Operator(1, OperatorParameter::PhaseDistortion),
+    Parameter::Operator(2, OperatorParameter::PhaseDistortion),
+    Parameter::Operator(3, OperatorParameter::PhaseDistortion),
+    Parameter::Operator(0, OperatorParameter::CrossFeedback),
+    Parameter::Operator(0, OperatorParameter::CrossFeedbackTarget),
+    Parameter::Operator(1, OperatorParameter::CrossFeedback),
+    Parameter::Operator(1, OperatorParameter::CrossFeedbackTarget),
+    Parameter::Operator(2, OperatorParameter::CrossFeedback),
+    Parameter::Operator(2, OperatorParameter::CrossFeedbackTarget),
+    Parameter::Master(MasterParameter::AntiAlias),
+    Parameter::Operator(0, OperatorParameter::KeyRangeLow),
+    Parameter::Operator(0, OperatorParameter::KeyRangeHigh),
+    Parameter::Operator(1, OperatorParameter::KeyRangeLow),
+    Parameter::Operator(1, OperatorParameter::KeyRangeHigh),
+    Parameter::Operator(2, OperatorParameter::KeyRangeLow),
+    Parameter::Operator(2, OperatorParameter::KeyRangeHigh),
+    Parameter::Operator(3, OperatorParameter::KeyRangeLow),
+    Parameter::Operator(3, OperatorParameter::KeyRangeHigh),
+    Parameter::Operator(0, OperatorParameter::VelocityRangeLow),
+    Parameter::Operator(0, OperatorParameter::VelocityRangeHigh),
+    Parameter::Operator(1, OperatorParameter::VelocityRangeLow),
+    Parameter::Operator(1, OperatorParameter::VelocityRangeHigh),
+    Parameter::Operator(2, OperatorParameter::VelocityRangeLow),
+    Parameter::Operator(2, OperatorParameter::VelocityRangeHigh),
+    Parameter::Operator(3, OperatorParameter::VelocityRangeLow),
+    Parameter::Operator(3, OperatorParameter::VelocityRangeHigh),
+    Parameter::Master(MasterParameter::NoteRandomTarget),
+    Parameter::Master(MasterParameter::NoteRandomAmount),
+    Parameter::Master(MasterParameter::VibratoRate),
+    Parameter::Master(MasterParameter::VibratoDepth),
+    Parameter::Master(MasterParameter::VibratoDelay),
+    Parameter::Master(MasterParameter::VibratoModWheel),
+    Parameter::Master(MasterParameter::PitchBendSmoothing),
+    Parameter::Master(MasterParameter::MpeZone),
+    Parameter::Master(MasterParameter::MpeMemberPitchBendRange),
+    Parameter::Master(MasterParameter::ModSnapshot),
+    Parameter::Master(MasterParameter::PatchSelect),
+    Parameter::Operator(0, OperatorParameter::NoiseStereo),
+    Parameter::Operator(1, OperatorParameter::NoiseStereo),
+    Parameter::Operator(2, OperatorParameter::NoiseStereo),
+    Parameter::Operator(3, OperatorParameter::NoiseStereo),
+    Parameter::Lfo(0, LfoParameter::Smoothing),
+    Parameter::Lfo(1, LfoParameter::Smoothing),
+    Parameter::Lfo(2, LfoParameter::Smoothing),
+    Parameter::Lfo(3, LfoParameter::Smoothing),
+    Parameter::Master(MasterParameter::MpePressureTarget),
+    Parameter::Master(MasterParameter::MpePressureAmount),
+    Parameter::Master(MasterParameter::MpeTimbreTarget),
+    Parameter::Master(MasterParameter::MpeTimbreAmount),
+    Parameter::Operator(0, OperatorParameter::Spread),
+    Parameter::Operator(1, OperatorParameter::Spread),
+    Parameter::Operator(2, OperatorParameter::Spread),
+    Parameter::Operator(3, OperatorParameter::Spread),
+    Parameter::Master(MasterParameter::MaxPolyphony),
 ];
 
 /// Parameter enum used to abstract over parameter indices
@@ -142,6 +263,49 @@ pub enum MasterParameter {
     GlideBpmSync,
     GlideMode,
     GlideRetrigger,
+    /// Chord triggered (transposed) by each played key
+    ChordMemory,
+    /// Send notes generated by chord memory to MIDI output
+    MidiOut,
+    /// Scale that incoming notes are quantized to
+    Scale,
+    ScaleRoot,
+    /// Envelope restart behavior on legato notes in monophonic mode
+    LegatoEnvelopeMode,
+    /// Which held key is played in monophonic mode
+    NotePriority,
+    /// Limit modulation of high-frequency operators to reduce aliasing
+    AntiAlias,
+    /// Parameter that random value sampled at note-on is routed to
+    NoteRandomTarget,
+    /// Amount of random value sampled at note-on
+    NoteRandomAmount,
+    VibratoRate,
+    VibratoDepth,
+    /// Time from note-on until vibrato starts
+    VibratoDelay,
+    /// Scale vibrato depth by mod wheel position
+    VibratoModWheel,
+    /// Time over which incoming pitch bend changes are smoothed
+    PitchBendSmoothing,
+    /// MPE zone, determining master and member channels
+    MpeZone,
+    /// Pitch bend range of MPE member channels
+    MpeMemberPitchBendRange,
+    /// Parameter that channel pressure of MPE member channels is routed to
+    MpePressureTarget,
+    /// Amount of channel pressure of MPE member channels
+    MpePressureAmount,
+    /// Parameter that CC 74 (timbre) of MPE member channels is routed to
+    MpeTimbreTarget,
+    /// Amount of CC 74 (timbre) of MPE member channels
+    MpeTimbreAmount,
+    /// Maximum number of voices in polyphonic mode
+    MaxPolyphony,
+    /// Selected mod snapshot, recalled on change
+    ModSnapshot,
+    /// Current patch index, quantized. Changing it switches patch.
+    PatchSelect,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -164,6 +328,32 @@ pub enum OperatorParameter {
     EnvelopeLockGroup,
     VelocitySensitivityModOut,
     VelocitySensitivityFeedback,
+    /// Envelope behavior when key is released
+    ReleaseMode,
+    /// Velocity sensitivity of envelope attack peak level
+    VelocitySensitivityPeak,
+    /// Velocity sensitivity of envelope sustain level
+    VelocitySensitivitySustain,
+    /// Position in loaded wavetable
+    WavetablePosition,
+    /// Phase distortion amount (sine wave only)
+    PhaseDistortion,
+    /// Amount of output fed back into modulation input of target operator
+    CrossFeedback,
+    /// Operator receiving cross feedback (one sample delayed)
+    CrossFeedbackTarget,
+    /// Lowest key for which operator sounds, evaluated at note-on
+    KeyRangeLow,
+    /// Highest key for which operator sounds, evaluated at note-on
+    KeyRangeHigh,
+    /// Lowest velocity for which operator sounds, evaluated at note-on
+    VelocityRangeLow,
+    /// Highest velocity for which operator sounds, evaluated at note-on
+    VelocityRangeHigh,
+    /// Independent left and right noise samples (white noise only)
+    NoiseStereo,
+    /// Delay of right channel output, widening the sound
+    Spread,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -178,4 +368,26 @@ pub enum LfoParameter {
     Active,
     /// Sync LFO phase to key presses. If turned off, start at random phase
     KeySync,
+    /// Sync LFO phase to host transport
+    TransportSync,
+    /// Additional target, affected by LFO amount multiplied by its depth
+    Target2,
+    Target2Depth,
+    /// Additional target, affected by LFO amount multiplied by its depth
+    Target3,
+    Target3Depth,
+    /// Bipolar or unipolar (upward or downward only) modulation
+    Polarity,
+    /// Scale amount by key velocity
+    VelocitySensitivity,
+    /// Scale amount by mod wheel (CC1)
+    ModWheelSensitivity,
+    /// When not off, run LFO once as attack-decay envelope with this curve
+    EnvelopeCurve,
+    /// Fraction of envelope cycle spent in attack
+    EnvelopeAttack,
+    /// With key sync on, start at random phase within this fraction of cycle
+    KeySyncSpread,
+    /// Slew time applied to output before it reaches targets
+    Smoothing,
 }
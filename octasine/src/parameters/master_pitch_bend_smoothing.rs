@@ -0,0 +1,46 @@
+use compact_str::{format_compact, CompactString};
+
+use super::{utils::parse_valid_f32, ParameterValue, SerializableRepresentation};
+
+const MAX_MS: f32 = 100.0;
+
+/// Time in milliseconds over which incoming pitch bend changes are smoothed
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MasterPitchBendSmoothingValue(f32);
+
+impl MasterPitchBendSmoothingValue {
+    pub fn as_seconds(self) -> f64 {
+        f64::from(self.0) / 1000.0
+    }
+}
+
+impl ParameterValue for MasterPitchBendSmoothingValue {
+    type Value = Self;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        value
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        let text = text
+            .trim()
+            .trim_end_matches(|c: char| c.is_alphabetic() || c.is_whitespace());
+
+        parse_valid_f32(text, 0.0, MAX_MS).map(Self)
+    }
+    fn get(self) -> Self::Value {
+        self
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(value.clamp(0.0, 1.0) * MAX_MS)
+    }
+    fn to_patch(self) -> f32 {
+        self.0 / MAX_MS
+    }
+    fn get_formatted(self) -> CompactString {
+        format_compact!("{:.0} MS", self.0)
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Float(self.0.into())
+    }
+}
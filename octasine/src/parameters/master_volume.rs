@@ -18,7 +18,23 @@ impl ParameterValue for MasterVolumeValue {
         Self(value)
     }
     fn new_from_text(text: &str) -> Option<Self> {
-        parse_valid_f32(text, 0.0, 2.0).map(Self)
+        let text = text.trim().to_lowercase();
+
+        // Formatted values are in dB, while plain numbers are interpreted
+        // as gain
+        if let Some(db) = text.strip_suffix("db") {
+            let db = db.trim();
+
+            if db == "-inf" {
+                return Some(Self(0.0));
+            }
+
+            let db = parse_valid_f32(db, f32::MIN, 20.0 * 2.0f32.log10())?;
+
+            Some(Self(10.0f32.powf(db / 20.0)))
+        } else {
+            parse_valid_f32(&text, 0.0, 2.0).map(Self)
+        }
     }
     fn get(self) -> Self::Value {
         self.0
@@ -1,35 +1,63 @@
+pub mod anti_alias;
+pub mod chord_memory;
 pub mod glide_active;
 pub mod glide_bpm_sync;
 pub mod glide_mode;
 pub mod glide_retrigger;
 pub mod glide_time;
+pub mod host_names;
+pub mod legato_envelope_mode;
 pub mod lfo_active;
 pub mod lfo_amount;
+pub mod lfo_amount_sensitivity;
 pub mod lfo_bpm_sync;
+pub mod lfo_envelope;
 pub mod lfo_frequency_free;
 pub mod lfo_frequency_ratio;
 pub mod lfo_key_sync;
+pub mod lfo_key_sync_spread;
 pub mod lfo_mode;
+pub mod lfo_polarity;
 pub mod lfo_shape;
+pub mod lfo_smoothing;
 pub mod lfo_target;
+pub mod lfo_target_depth;
+pub mod lfo_transport_sync;
 pub mod list;
 pub mod master_frequency;
 pub mod master_pitch_bend_range;
+pub mod master_pitch_bend_smoothing;
 pub mod master_volume;
+pub mod max_polyphony;
+pub mod midi_out;
+pub mod mod_snapshot;
+pub mod mpe;
+pub mod note_priority;
+pub mod note_random;
 pub mod operator_active;
+pub mod operator_cross_feedback_target;
 pub mod operator_envelope;
 pub mod operator_feedback;
 pub mod operator_frequency_fine;
 pub mod operator_frequency_free;
 pub mod operator_frequency_ratio;
+pub mod operator_key_range;
 pub mod operator_mix_out;
 pub mod operator_mod_out;
 pub mod operator_mod_target;
+pub mod operator_noise_stereo;
 pub mod operator_panning;
+pub mod operator_phase_distortion;
+pub mod operator_spread;
+pub mod operator_velocity_range;
 pub mod operator_volume;
 pub mod operator_wave_type;
+pub mod operator_wavetable_position;
+pub mod patch_select;
+pub mod scale_quantization;
 pub mod utils;
 pub mod velocity_sensitivity;
+pub mod vibrato;
 pub mod voice_mode;
 
 use compact_str::{format_compact, CompactString};
@@ -40,8 +68,11 @@ pub use lfo_frequency_free::LfoFrequencyFreeValue;
 pub use lfo_frequency_ratio::LfoFrequencyRatioValue;
 pub use lfo_key_sync::LfoKeySyncValue;
 pub use lfo_mode::LfoModeValue;
+pub use lfo_polarity::LfoPolarityValue;
 pub use lfo_shape::LfoShapeValue;
 pub use lfo_target::*;
+pub use lfo_target_depth::LfoTargetDepthValue;
+pub use lfo_transport_sync::LfoTransportSyncValue;
 pub use list::*;
 pub use master_frequency::MasterFrequencyValue;
 pub use master_pitch_bend_range::{MasterPitchBendRangeDownValue, MasterPitchBendRangeUpValue};
@@ -77,6 +108,12 @@ pub trait ParameterValue: Sized + Default + Copy {
     fn get_formatted(self) -> CompactString;
     fn to_patch(self) -> f32;
 
+    /// Format value for showing LFO modulation ranges. Defaults to regular
+    /// formatting, but may use a more descriptive unit, e.g., dB for volumes
+    fn get_formatted_as_modulation_target(self) -> CompactString {
+        self.get_formatted()
+    }
+
     fn replace_from_patch(&mut self, value: f32) {
         *self = Self::new_from_patch(value);
     }
@@ -116,6 +153,31 @@ impl Parameter {
             Self::Master(MasterParameter::GlideBpmSync) => "Glide bpm sync".into(),
             Self::Master(MasterParameter::GlideMode) => "Glide mode".into(),
             Self::Master(MasterParameter::GlideRetrigger) => "Glide retrigger".into(),
+            Self::Master(MasterParameter::ChordMemory) => "Chord memory".into(),
+            Self::Master(MasterParameter::MidiOut) => "MIDI out".into(),
+            Self::Master(MasterParameter::Scale) => "Scale".into(),
+            Self::Master(MasterParameter::ScaleRoot) => "Scale root".into(),
+            Self::Master(MasterParameter::LegatoEnvelopeMode) => "Legato envelope mode".into(),
+            Self::Master(MasterParameter::NotePriority) => "Note priority".into(),
+            Self::Master(MasterParameter::AntiAlias) => "Anti-alias".into(),
+            Self::Master(MasterParameter::NoteRandomTarget) => "Random per note target".into(),
+            Self::Master(MasterParameter::NoteRandomAmount) => "Random per note amount".into(),
+            Self::Master(MasterParameter::VibratoRate) => "Vibrato rate".into(),
+            Self::Master(MasterParameter::VibratoDepth) => "Vibrato depth".into(),
+            Self::Master(MasterParameter::VibratoDelay) => "Vibrato delay".into(),
+            Self::Master(MasterParameter::VibratoModWheel) => "Vibrato mod wheel".into(),
+            Self::Master(MasterParameter::PitchBendSmoothing) => "Pitch bend smoothing".into(),
+            Self::Master(MasterParameter::MpeZone) => "MPE zone".into(),
+            Self::Master(MasterParameter::MpeMemberPitchBendRange) => {
+                "MPE member pitch bend range".into()
+            }
+            Self::Master(MasterParameter::MpePressureTarget) => "MPE pressure target".into(),
+            Self::Master(MasterParameter::MpePressureAmount) => "MPE pressure amount".into(),
+            Self::Master(MasterParameter::MpeTimbreTarget) => "MPE timbre target".into(),
+            Self::Master(MasterParameter::MpeTimbreAmount) => "MPE timbre amount".into(),
+            Self::Master(MasterParameter::MaxPolyphony) => "Max polyphony".into(),
+            Self::Master(MasterParameter::ModSnapshot) => "Mod snapshot".into(),
+            Self::Master(MasterParameter::PatchSelect) => "Patch select".into(),
             Self::Operator(index, p) => match p {
                 OperatorParameter::Volume => format_compact!("OP {} vol", index + 1),
                 OperatorParameter::Active => format_compact!("OP {} active", index + 1),
@@ -145,6 +207,37 @@ impl Parameter {
                 OperatorParameter::VelocitySensitivityFeedback => {
                     format_compact!("OP {} feedback vs", index + 1)
                 }
+                OperatorParameter::ReleaseMode => {
+                    format_compact!("OP {} release mode", index + 1)
+                }
+                OperatorParameter::VelocitySensitivityPeak => {
+                    format_compact!("OP {} peak vs", index + 1)
+                }
+                OperatorParameter::VelocitySensitivitySustain => {
+                    format_compact!("OP {} sustain vs", index + 1)
+                }
+                OperatorParameter::WavetablePosition => {
+                    format_compact!("OP {} wt position", index + 1)
+                }
+                OperatorParameter::PhaseDistortion => {
+                    format_compact!("OP {} phase dist", index + 1)
+                }
+                OperatorParameter::CrossFeedback => format_compact!("OP {} cross fb", index + 1),
+                OperatorParameter::CrossFeedbackTarget => {
+                    format_compact!("OP {} cross fb target", index + 1)
+                }
+                OperatorParameter::KeyRangeLow => format_compact!("OP {} key low", index + 1),
+                OperatorParameter::KeyRangeHigh => format_compact!("OP {} key high", index + 1),
+                OperatorParameter::VelocityRangeLow => {
+                    format_compact!("OP {} vel low", index + 1)
+                }
+                OperatorParameter::VelocityRangeHigh => {
+                    format_compact!("OP {} vel high", index + 1)
+                }
+                OperatorParameter::NoiseStereo => {
+                    format_compact!("OP {} stereo noise", index + 1)
+                }
+                OperatorParameter::Spread => format_compact!("OP {} spread", index + 1),
             },
             Self::Lfo(index, p) => match p {
                 LfoParameter::Target => format_compact!("LFO {} target", index + 1),
@@ -156,6 +249,26 @@ impl Parameter {
                 LfoParameter::Amount => format_compact!("LFO {} amount", index + 1),
                 LfoParameter::Active => format_compact!("LFO {} active", index + 1),
                 LfoParameter::KeySync => format_compact!("LFO {} key sync", index + 1),
+                LfoParameter::TransportSync => {
+                    format_compact!("LFO {} transport sync", index + 1)
+                }
+                LfoParameter::Target2 => format_compact!("LFO {} target 2", index + 1),
+                LfoParameter::Target2Depth => format_compact!("LFO {} target 2 depth", index + 1),
+                LfoParameter::Target3 => format_compact!("LFO {} target 3", index + 1),
+                LfoParameter::Target3Depth => format_compact!("LFO {} target 3 depth", index + 1),
+                LfoParameter::Polarity => format_compact!("LFO {} polarity", index + 1),
+                LfoParameter::VelocitySensitivity => {
+                    format_compact!("LFO {} amount vel sens", index + 1)
+                }
+                LfoParameter::ModWheelSensitivity => {
+                    format_compact!("LFO {} amount mod wheel", index + 1)
+                }
+                LfoParameter::EnvelopeCurve => format_compact!("LFO {} env curve", index + 1),
+                LfoParameter::EnvelopeAttack => format_compact!("LFO {} env attack", index + 1),
+                LfoParameter::KeySyncSpread => {
+                    format_compact!("LFO {} key sync spread", index + 1)
+                }
+                LfoParameter::Smoothing => format_compact!("LFO {} smoothing", index + 1),
             },
         }
     }
@@ -164,17 +277,15 @@ impl Parameter {
         PARAMETERS.get(index).copied()
     }
 
-    pub const fn to_index(self) -> u8 {
-        parameter_to_index(self)
+    /// Whether hosts may automate parameter. Chord memory packs a whole
+    /// chord into one value, so sweeping through it would trigger arbitrary
+    /// chords. It is only changed through the GUI or text input.
+    pub fn is_automatable(&self) -> bool {
+        !matches!(self, Self::Master(MasterParameter::ChordMemory))
     }
 
-    pub fn clap_path(&self) -> CompactString {
-        match self {
-            Self::None => "None".into(),
-            Self::Master(_) => "Master".into(),
-            Self::Operator(index, _) => format_compact!("Operator {}", *index),
-            Self::Lfo(index, _) => format_compact!("LFO {}", *index),
-        }
+    pub const fn to_index(self) -> u8 {
+        parameter_to_index(self)
     }
 
     pub fn key(&self) -> ParameterKey {
@@ -197,6 +308,31 @@ impl Parameter {
             Self::Master(MasterParameter::GlideBpmSync) => "Glide bpm sync".into(),
             Self::Master(MasterParameter::GlideMode) => "Glide mode".into(),
             Self::Master(MasterParameter::GlideRetrigger) => "Glide retrigger".into(),
+            Self::Master(MasterParameter::ChordMemory) => "Chord memory".into(),
+            Self::Master(MasterParameter::MidiOut) => "MIDI out".into(),
+            Self::Master(MasterParameter::Scale) => "Scale".into(),
+            Self::Master(MasterParameter::ScaleRoot) => "Scale root".into(),
+            Self::Master(MasterParameter::LegatoEnvelopeMode) => "Legato envelope mode".into(),
+            Self::Master(MasterParameter::NotePriority) => "Note priority".into(),
+            Self::Master(MasterParameter::AntiAlias) => "Anti-alias".into(),
+            Self::Master(MasterParameter::NoteRandomTarget) => "Random per note target".into(),
+            Self::Master(MasterParameter::NoteRandomAmount) => "Random per note amount".into(),
+            Self::Master(MasterParameter::VibratoRate) => "Vibrato rate".into(),
+            Self::Master(MasterParameter::VibratoDepth) => "Vibrato depth".into(),
+            Self::Master(MasterParameter::VibratoDelay) => "Vibrato delay".into(),
+            Self::Master(MasterParameter::VibratoModWheel) => "Vibrato mod wheel".into(),
+            Self::Master(MasterParameter::PitchBendSmoothing) => "Pitch bend smoothing".into(),
+            Self::Master(MasterParameter::MpeZone) => "MPE zone".into(),
+            Self::Master(MasterParameter::MpeMemberPitchBendRange) => {
+                "MPE member pitch bend range".into()
+            }
+            Self::Master(MasterParameter::MpePressureTarget) => "MPE pressure target".into(),
+            Self::Master(MasterParameter::MpePressureAmount) => "MPE pressure amount".into(),
+            Self::Master(MasterParameter::MpeTimbreTarget) => "MPE timbre target".into(),
+            Self::Master(MasterParameter::MpeTimbreAmount) => "MPE timbre amount".into(),
+            Self::Master(MasterParameter::MaxPolyphony) => "Max polyphony".into(),
+            Self::Master(MasterParameter::ModSnapshot) => "Mod snapshot".into(),
+            Self::Master(MasterParameter::PatchSelect) => "Patch select".into(),
             Self::Operator(index, p) => match p {
                 OperatorParameter::Volume => format!("OP {} vol", index + 1),
                 OperatorParameter::Active => format!("OP {} active", index + 1),
@@ -220,6 +356,33 @@ impl Parameter {
                 OperatorParameter::VelocitySensitivityFeedback => {
                     format!("OP {} feedback velocity sensitivity", index + 1)
                 }
+                OperatorParameter::ReleaseMode => format!("OP {} release mode", index + 1),
+                OperatorParameter::VelocitySensitivityPeak => {
+                    format!("OP {} peak velocity sensitivity", index + 1)
+                }
+                OperatorParameter::VelocitySensitivitySustain => {
+                    format!("OP {} sustain velocity sensitivity", index + 1)
+                }
+                OperatorParameter::WavetablePosition => {
+                    format!("OP {} wavetable position", index + 1)
+                }
+                OperatorParameter::PhaseDistortion => {
+                    format!("OP {} phase distortion", index + 1)
+                }
+                OperatorParameter::CrossFeedback => format!("OP {} cross feedback", index + 1),
+                OperatorParameter::CrossFeedbackTarget => {
+                    format!("OP {} cross feedback target", index + 1)
+                }
+                OperatorParameter::KeyRangeLow => format!("OP {} key range low", index + 1),
+                OperatorParameter::KeyRangeHigh => format!("OP {} key range high", index + 1),
+                OperatorParameter::VelocityRangeLow => {
+                    format!("OP {} velocity range low", index + 1)
+                }
+                OperatorParameter::VelocityRangeHigh => {
+                    format!("OP {} velocity range high", index + 1)
+                }
+                OperatorParameter::NoiseStereo => format!("OP {} stereo noise", index + 1),
+                OperatorParameter::Spread => format!("OP {} stereo spread", index + 1),
             },
             Self::Lfo(index, p) => match p {
                 LfoParameter::Target => format!("LFO {} target", index + 1),
@@ -231,13 +394,45 @@ impl Parameter {
                 LfoParameter::Amount => format!("LFO {} amount", index + 1),
                 LfoParameter::Active => format!("LFO {} active", index + 1),
                 LfoParameter::KeySync => format!("LFO {} key sync", index + 1),
+                LfoParameter::TransportSync => format!("LFO {} transport sync", index + 1),
+                LfoParameter::Target2 => format!("LFO {} target 2", index + 1),
+                LfoParameter::Target2Depth => format!("LFO {} target 2 depth", index + 1),
+                LfoParameter::Target3 => format!("LFO {} target 3", index + 1),
+                LfoParameter::Target3Depth => format!("LFO {} target 3 depth", index + 1),
+                LfoParameter::Polarity => format!("LFO {} polarity", index + 1),
+                LfoParameter::VelocitySensitivity => {
+                    format!("LFO {} amount velocity sensitivity", index + 1)
+                }
+                LfoParameter::ModWheelSensitivity => {
+                    format!("LFO {} amount mod wheel sensitivity", index + 1)
+                }
+                LfoParameter::EnvelopeCurve => format!("LFO {} envelope curve", index + 1),
+                LfoParameter::EnvelopeAttack => format!("LFO {} envelope attack", index + 1),
+                LfoParameter::KeySyncSpread => format!("LFO {} key sync spread", index + 1),
+                LfoParameter::Smoothing => format!("LFO {} smoothing", index + 1),
             },
         };
 
+        ParameterKey::from_name(&name)
+    }
+}
+
+impl ParameterKey {
+    fn from_name(name: &str) -> Self {
         let hash = seahash::hash(name.as_bytes());
         let first_four_bytes = hash.to_ne_bytes()[..4].try_into().unwrap();
 
-        ParameterKey(u32::from_ne_bytes(first_four_bytes))
+        Self(u32::from_ne_bytes(first_four_bytes))
+    }
+
+    /// Key for read-only LFO output value, which is not part of patches
+    pub fn lfo_output(lfo_index: usize) -> Self {
+        Self::from_name(&format!("LFO {} output", lfo_index + 1))
+    }
+
+    /// Key for plugin bypass toggle, which is not part of patches
+    pub fn bypass() -> Self {
+        Self::from_name("Bypass")
     }
 }
 
@@ -312,12 +507,19 @@ impl From<Parameter> for WrappedParameter {
 mod tests {
     use std::collections::HashSet;
 
+    use crate::common::NUM_LFOS;
+
     use super::{ParameterKey, PARAMETERS};
 
     #[test]
     fn test_parameter_key_uniqueness() {
-        let set: HashSet<ParameterKey> = PARAMETERS.iter().map(|p| p.key()).collect();
-
-        assert_eq!(set.len(), PARAMETERS.len());
+        let set: HashSet<ParameterKey> = PARAMETERS
+            .iter()
+            .map(|p| p.key())
+            .chain((0..NUM_LFOS).map(ParameterKey::lfo_output))
+            .chain(::std::iter::once(ParameterKey::bypass()))
+            .collect();
+
+        assert_eq!(set.len(), PARAMETERS.len() + NUM_LFOS + 1);
     }
 }
@@ -0,0 +1,99 @@
+use compact_str::{format_compact, CompactString};
+
+use super::{
+    utils::{map_patch_value_to_step, map_step_to_patch_value},
+    OperatorParameter, Parameter, ParameterValue, SerializableRepresentation,
+};
+
+pub const NUM_MOD_SNAPSHOTS: usize = 4;
+
+pub const MOD_SNAPSHOT_SLOT_STEPS: &[ModSnapshotSlot] = &[
+    ModSnapshotSlot::Off,
+    ModSnapshotSlot::Slot(0),
+    ModSnapshotSlot::Slot(1),
+    ModSnapshotSlot::Slot(2),
+    ModSnapshotSlot::Slot(3),
+];
+
+/// Returns true if parameter is part of modulation routing and depths, which
+/// are stored in and recalled from mod snapshots
+pub fn is_mod_snapshot_parameter(parameter: Parameter) -> bool {
+    matches!(
+        parameter,
+        Parameter::Operator(
+            _,
+            OperatorParameter::ModTargets
+                | OperatorParameter::ModOut
+                | OperatorParameter::Feedback
+                | OperatorParameter::CrossFeedback
+                | OperatorParameter::CrossFeedbackTarget
+        )
+    )
+}
+
+/// Selected mod snapshot. Selecting a slot recalls its stored values, if any.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ModSnapshotSlot {
+    #[default]
+    Off,
+    Slot(u8),
+}
+
+impl ModSnapshotSlot {
+    pub fn index(self) -> Option<usize> {
+        match self {
+            Self::Off => None,
+            Self::Slot(index) => Some(index as usize),
+        }
+    }
+}
+
+impl ::std::fmt::Display for ModSnapshotSlot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Off => f.write_str("OFF"),
+            Self::Slot(index) => write!(f, "{}", index + 1),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ModSnapshotSlotValue(ModSnapshotSlot);
+
+impl ParameterValue for ModSnapshotSlotValue {
+    type Value = ModSnapshotSlot;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        let text = text.trim().to_lowercase();
+
+        if text == "off" {
+            return Some(Self(ModSnapshotSlot::Off));
+        }
+
+        match text.parse::<u8>() {
+            Ok(n) if (1..=NUM_MOD_SNAPSHOTS as u8).contains(&n) => {
+                Some(Self(ModSnapshotSlot::Slot(n - 1)))
+            }
+            _ => None,
+        }
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(map_patch_value_to_step(MOD_SNAPSHOT_SLOT_STEPS, value))
+    }
+    fn to_patch(self) -> f32 {
+        map_step_to_patch_value(MOD_SNAPSHOT_SLOT_STEPS, self.0)
+    }
+    fn get_formatted(self) -> CompactString {
+        format_compact!("{}", self.0)
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Other(self.get_formatted())
+    }
+}
@@ -0,0 +1,198 @@
+use compact_str::{format_compact, CompactString};
+
+use super::{
+    lfo_target::LfoTargetParameter,
+    note_random::get_note_random_target_parameters,
+    utils::{map_patch_value_to_step, map_step_to_patch_value, parse_valid_f32},
+    Parameter, ParameterValue, SerializableRepresentation,
+};
+
+pub const MPE_ZONE_STEPS: &[MpeZone] = &[MpeZone::Off, MpeZone::Lower, MpeZone::Upper];
+
+const MAX_MEMBER_PITCH_BEND_RANGE: u8 = 96;
+
+/// MPE zone, determining which MIDI channel is the master channel and which
+/// are member channels with per-note pitch bend
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MpeZone {
+    /// All channels are treated the same
+    #[default]
+    Off,
+    /// Master channel 1, member channels 2-16
+    Lower,
+    /// Master channel 16, member channels 1-15
+    Upper,
+}
+
+impl MpeZone {
+    /// Returns true if channel (zero-indexed) is a member channel, meaning
+    /// that its pitch bend only applies to voices started on it
+    pub fn is_member_channel(self, channel: u8) -> bool {
+        match self {
+            Self::Off => false,
+            Self::Lower => (1..=15).contains(&channel),
+            Self::Upper => channel < 15,
+        }
+    }
+}
+
+impl ::std::fmt::Display for MpeZone {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Off => "OFF",
+            Self::Lower => "LOW",
+            Self::Upper => "UP",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MpeZoneValue(MpeZone);
+
+impl ParameterValue for MpeZoneValue {
+    type Value = MpeZone;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        match text.trim().to_lowercase().as_str() {
+            "off" => Some(Self(MpeZone::Off)),
+            "low" | "lower" => Some(Self(MpeZone::Lower)),
+            "up" | "upper" => Some(Self(MpeZone::Upper)),
+            _ => None,
+        }
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(map_patch_value_to_step(MPE_ZONE_STEPS, value))
+    }
+    fn to_patch(self) -> f32 {
+        map_step_to_patch_value(MPE_ZONE_STEPS, self.0)
+    }
+    fn get_formatted(self) -> CompactString {
+        format_compact!("{}", self.0)
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Other(self.get_formatted())
+    }
+}
+
+/// Pitch bend range in semitones of MPE member channels. Master channel uses
+/// regular pitch bend range.
+#[derive(Debug, Clone, Copy)]
+pub struct MpeMemberPitchBendRangeValue(u8);
+
+impl Default for MpeMemberPitchBendRangeValue {
+    fn default() -> Self {
+        // Default according to MPE specification
+        Self(48)
+    }
+}
+
+impl ParameterValue for MpeMemberPitchBendRangeValue {
+    type Value = u8;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value.min(MAX_MEMBER_PITCH_BEND_RANGE))
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        let text = text
+            .trim()
+            .trim_end_matches(|c: char| c.is_alphabetic() || c.is_whitespace());
+        let value = text.parse::<u8>().ok()?;
+
+        (value <= MAX_MEMBER_PITCH_BEND_RANGE).then_some(Self(value))
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self((value.clamp(0.0, 1.0) * f32::from(MAX_MEMBER_PITCH_BEND_RANGE)).round() as u8)
+    }
+    fn to_patch(self) -> f32 {
+        f32::from(self.0) / f32::from(MAX_MEMBER_PITCH_BEND_RANGE)
+    }
+    fn get_formatted(self) -> CompactString {
+        format_compact!("{} SEMIS", self.0)
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Other(self.get_formatted())
+    }
+}
+
+/// Parameter that pressure or timbre of MPE member channels is added to.
+/// Same targets as random per note value.
+#[derive(Debug, Clone, Copy)]
+pub struct MpeModulationTargetValue(pub LfoTargetParameter);
+
+impl Default for MpeModulationTargetValue {
+    fn default() -> Self {
+        Self(LfoTargetParameter::new(Parameter::None))
+    }
+}
+
+impl ParameterValue for MpeModulationTargetValue {
+    type Value = LfoTargetParameter;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(_text: &str) -> Option<Self> {
+        None
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(map_patch_value_to_step(
+            get_note_random_target_parameters(),
+            value,
+        ))
+    }
+    fn to_patch(self) -> f32 {
+        map_step_to_patch_value(get_note_random_target_parameters(), self.0)
+    }
+    fn get_formatted(self) -> CompactString {
+        self.0.parameter().name()
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Other(self.get_formatted())
+    }
+}
+
+/// Change of target patch value at full pressure or timbre
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MpeModulationAmountValue(f32);
+
+impl ParameterValue for MpeModulationAmountValue {
+    type Value = f32;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        parse_valid_f32(text, 0.0, 1.0).map(Self)
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(value)
+    }
+    fn to_patch(self) -> f32 {
+        self.0
+    }
+    fn get_formatted(self) -> CompactString {
+        format_compact!("{:.04}", self.0)
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Float(self.0.into())
+    }
+}
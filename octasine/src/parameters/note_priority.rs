@@ -0,0 +1,66 @@
+use compact_str::{format_compact, CompactString};
+
+use super::{
+    utils::{map_patch_value_to_step, map_step_to_patch_value},
+    ParameterValue, SerializableRepresentation,
+};
+
+pub const NOTE_PRIORITY_STEPS: &[NotePriority] =
+    &[NotePriority::Last, NotePriority::Low, NotePriority::High];
+
+/// Which held key is played in monophonic mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NotePriority {
+    /// Most recently pressed key
+    #[default]
+    Last,
+    /// Lowest held key
+    Low,
+    /// Highest held key
+    High,
+}
+
+impl ::std::fmt::Display for NotePriority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Last => "LAST",
+            Self::Low => "LOW",
+            Self::High => "HIGH",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NotePriorityValue(NotePriority);
+
+impl ParameterValue for NotePriorityValue {
+    type Value = NotePriority;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        match text.trim().to_lowercase().as_str() {
+            "last" => Some(Self(NotePriority::Last)),
+            "low" => Some(Self(NotePriority::Low)),
+            "high" => Some(Self(NotePriority::High)),
+            _ => None,
+        }
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(map_patch_value_to_step(NOTE_PRIORITY_STEPS, value))
+    }
+    fn to_patch(self) -> f32 {
+        map_step_to_patch_value(NOTE_PRIORITY_STEPS, self.0)
+    }
+    fn get_formatted(self) -> CompactString {
+        format_compact!("{}", self.0)
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Other(self.get_formatted())
+    }
+}
@@ -0,0 +1,84 @@
+use compact_str::{format_compact, CompactString};
+
+use super::{
+    lfo_target::{get_lfo_target_parameters, LfoTargetParameter},
+    utils::{map_patch_value_to_step, map_step_to_patch_value, parse_valid_f32},
+    Parameter, ParameterValue, SerializableRepresentation,
+};
+
+/// Parameters that random per note value can be routed to. Same as targets
+/// of LFO 1, since LFO parameters are updated before the value is applied.
+pub fn get_note_random_target_parameters() -> &'static [LfoTargetParameter] {
+    get_lfo_target_parameters(0)
+}
+
+/// Parameter that random value sampled at note-on is added to
+#[derive(Debug, Clone, Copy)]
+pub struct NoteRandomTargetValue(pub LfoTargetParameter);
+
+impl Default for NoteRandomTargetValue {
+    fn default() -> Self {
+        Self(LfoTargetParameter::new(Parameter::None))
+    }
+}
+
+impl ParameterValue for NoteRandomTargetValue {
+    type Value = LfoTargetParameter;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(_text: &str) -> Option<Self> {
+        None
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(map_patch_value_to_step(
+            get_note_random_target_parameters(),
+            value,
+        ))
+    }
+    fn to_patch(self) -> f32 {
+        map_step_to_patch_value(get_note_random_target_parameters(), self.0)
+    }
+    fn get_formatted(self) -> CompactString {
+        self.0.parameter().name()
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Other(self.get_formatted())
+    }
+}
+
+/// Maximum change of target patch value caused by random per note value
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoteRandomAmountValue(f32);
+
+impl ParameterValue for NoteRandomAmountValue {
+    type Value = f32;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        parse_valid_f32(text, 0.0, 1.0).map(Self)
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(value)
+    }
+    fn to_patch(self) -> f32 {
+        self.0
+    }
+    fn get_formatted(self) -> CompactString {
+        format_compact!("{:.04}", self.0)
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Float(self.0.into())
+    }
+}
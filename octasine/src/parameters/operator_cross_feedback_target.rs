@@ -0,0 +1,126 @@
+use compact_str::{format_compact, CompactString};
+
+use super::{
+    utils::{map_patch_value_to_step, map_step_to_patch_value},
+    ParameterValue, SerializableRepresentation,
+};
+
+const OPERATOR_1_STEPS: &[CrossFeedbackTarget] = &[
+    CrossFeedbackTarget::Off,
+    CrossFeedbackTarget::Operator(1),
+    CrossFeedbackTarget::Operator(2),
+    CrossFeedbackTarget::Operator(3),
+];
+const OPERATOR_2_STEPS: &[CrossFeedbackTarget] = &[
+    CrossFeedbackTarget::Off,
+    CrossFeedbackTarget::Operator(2),
+    CrossFeedbackTarget::Operator(3),
+];
+const OPERATOR_3_STEPS: &[CrossFeedbackTarget] =
+    &[CrossFeedbackTarget::Off, CrossFeedbackTarget::Operator(3)];
+
+/// Operator receiving cross feedback. Only operators with a higher index
+/// than the source can be targeted, since they are processed earlier. Their
+/// modulation input is therefore delayed by one sample.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CrossFeedbackTarget {
+    #[default]
+    Off,
+    /// Target operator index
+    Operator(u8),
+}
+
+impl CrossFeedbackTarget {
+    pub fn index(self) -> Option<usize> {
+        match self {
+            Self::Off => None,
+            Self::Operator(index) => Some(index.into()),
+        }
+    }
+}
+
+impl ::std::fmt::Display for CrossFeedbackTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Off => f.write_str("OFF"),
+            Self::Operator(index) => write!(f, "OP {}", index + 1),
+        }
+    }
+}
+
+fn parse_target(steps: &[CrossFeedbackTarget], text: &str) -> Option<CrossFeedbackTarget> {
+    let text = text.trim().to_lowercase();
+
+    if text == "off" {
+        return Some(CrossFeedbackTarget::Off);
+    }
+
+    let operator_number: u8 = text.trim_start_matches("op").trim().parse().ok()?;
+
+    steps
+        .iter()
+        .copied()
+        .find(|step| *step == CrossFeedbackTarget::Operator(operator_number.wrapping_sub(1)))
+}
+
+macro_rules! impl_cross_feedback_target_value {
+    ($name:ident, $steps:ident) => {
+        #[derive(Debug, Clone, Copy, Default)]
+        pub struct $name(CrossFeedbackTarget);
+
+        impl $name {
+            pub const STEPS: &'static [CrossFeedbackTarget] = $steps;
+        }
+
+        impl ParameterValue for $name {
+            type Value = CrossFeedbackTarget;
+
+            fn new_from_audio(value: Self::Value) -> Self {
+                Self(value)
+            }
+            fn new_from_text(text: &str) -> Option<Self> {
+                parse_target($steps, text).map(Self)
+            }
+            fn get(self) -> Self::Value {
+                self.0
+            }
+            fn new_from_patch(value: f32) -> Self {
+                Self(map_patch_value_to_step($steps, value))
+            }
+            fn to_patch(self) -> f32 {
+                map_step_to_patch_value($steps, self.0)
+            }
+            fn get_formatted(self) -> CompactString {
+                format_compact!("{}", self.0)
+            }
+
+            fn get_serializable(&self) -> SerializableRepresentation {
+                SerializableRepresentation::Other(self.get_formatted())
+            }
+        }
+    };
+}
+
+impl_cross_feedback_target_value!(Operator1CrossFeedbackTargetValue, OPERATOR_1_STEPS);
+impl_cross_feedback_target_value!(Operator2CrossFeedbackTargetValue, OPERATOR_2_STEPS);
+impl_cross_feedback_target_value!(Operator3CrossFeedbackTargetValue, OPERATOR_3_STEPS);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cross_feedback_target_text() {
+        assert_eq!(
+            Operator1CrossFeedbackTargetValue::new_from_text("OP 3").map(|v| v.get()),
+            Some(CrossFeedbackTarget::Operator(2))
+        );
+        assert_eq!(
+            Operator2CrossFeedbackTargetValue::new_from_text("off").map(|v| v.get()),
+            Some(CrossFeedbackTarget::Off)
+        );
+        // Only operators with higher index can be targeted
+        assert!(Operator3CrossFeedbackTargetValue::new_from_text("2").is_none());
+        assert!(Operator3CrossFeedbackTargetValue::new_from_text("0").is_none());
+    }
+}
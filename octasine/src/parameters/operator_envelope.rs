@@ -126,7 +126,7 @@ impl ParameterValue for OperatorSustainVolumeValue {
     }
 }
 
-const LOCK_STEPS: &[OperatorEnvelopeGroupValue] = &[
+pub const LOCK_STEPS: &[OperatorEnvelopeGroupValue] = &[
     OperatorEnvelopeGroupValue::Off,
     OperatorEnvelopeGroupValue::A,
     OperatorEnvelopeGroupValue::B,
@@ -145,6 +145,16 @@ impl Default for OperatorEnvelopeGroupValue {
     }
 }
 
+impl ::std::fmt::Display for OperatorEnvelopeGroupValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Off => "-",
+            Self::A => "A",
+            Self::B => "B",
+        })
+    }
+}
+
 impl FromStr for OperatorEnvelopeGroupValue {
     type Err = ();
 
@@ -186,6 +196,101 @@ impl ParameterValue for OperatorEnvelopeGroupValue {
     }
 }
 
+pub const RELEASE_MODE_STEPS: &[OperatorReleaseMode] = &[
+    OperatorReleaseMode::Current,
+    OperatorReleaseMode::Linear,
+    OperatorReleaseMode::AfterAttack,
+];
+
+/// Envelope behavior when key is released
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OperatorReleaseMode {
+    /// Release from current envelope level with regular curve
+    #[default]
+    Current,
+    /// Release linearly from current envelope level
+    Linear,
+    /// Complete attack stage before releasing, e.g. for percussive patches
+    AfterAttack,
+}
+
+impl ::std::fmt::Display for OperatorReleaseMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Current => "CUR",
+            Self::Linear => "LIN",
+            Self::AfterAttack => "ATK",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OperatorReleaseModeValue(OperatorReleaseMode);
+
+impl ParameterValue for OperatorReleaseModeValue {
+    type Value = OperatorReleaseMode;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        match text.trim().to_lowercase().as_str() {
+            "cur" | "current" => Some(Self(OperatorReleaseMode::Current)),
+            "lin" | "linear" => Some(Self(OperatorReleaseMode::Linear)),
+            "atk" | "after attack" => Some(Self(OperatorReleaseMode::AfterAttack)),
+            _ => None,
+        }
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(map_patch_value_to_step(RELEASE_MODE_STEPS, value))
+    }
+    fn to_patch(self) -> f32 {
+        map_step_to_patch_value(RELEASE_MODE_STEPS, self.0)
+    }
+    fn get_formatted(self) -> CompactString {
+        format_compact!("{}", self.0)
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Other(self.get_formatted())
+    }
+}
+
+/// How much envelope peak or sustain level is scaled by key velocity.
+/// Unlike VelocitySensitivityValue, defaults to zero.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OperatorEnvelopeVelocitySensitivityValue(f32);
+
+impl ParameterValue for OperatorEnvelopeVelocitySensitivityValue {
+    type Value = f32;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        parse_valid_f32(text, 0.0, 1.0).map(Self)
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(value)
+    }
+    fn to_patch(self) -> f32 {
+        self.0
+    }
+    fn get_formatted(self) -> CompactString {
+        format_compact!("{:.02}", self.0)
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Float(self.0.into())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,8 +1,9 @@
 use std::f64::consts::PI;
+use std::fmt::Write;
 use std::str::FromStr;
 
 use arrayvec::ArrayString;
-use compact_str::CompactString;
+use compact_str::{format_compact, CompactString};
 use once_cell::sync::Lazy;
 
 use super::utils::*;
@@ -24,6 +25,21 @@ impl Ratio {
 
         Self { name, value }
     }
+
+    /// Ratio not in step table, entered as text. Doesn't allocate, since
+    /// it is called on the audio thread.
+    fn custom(value: f64) -> Self {
+        let mut name = ArrayString::new();
+
+        // Ratios are small enough for name to always fit
+        let _ = if value < 10.0 {
+            write!(name, "{:.3}", value)
+        } else {
+            write!(name, "{:.2}", value)
+        };
+
+        Self { name, value }
+    }
 }
 
 static OPERATOR_RATIO_STEPS: Lazy<Vec<Ratio>> = Lazy::new(|| {
@@ -113,6 +129,75 @@ static OPERATOR_RATIO_STEPS: Lazy<Vec<Ratio>> = Lazy::new(|| {
     ratios
 });
 
+/// Half-width of patch value windows around boundaries between steps.
+///
+/// Patch values inside a window map to custom ratios, interpolated
+/// logarithmically between the two neighbouring steps. Outside of
+/// windows, patch values map to steps as usual. Windows are narrow enough
+/// to not contain the patch values that steps map to.
+fn custom_ratio_window() -> f32 {
+    let len = OPERATOR_RATIO_STEPS.len();
+
+    0.5 / (len * (len - 1)) as f32
+}
+
+fn custom_ratio_from_patch(value: f32) -> Option<Ratio> {
+    let len = OPERATOR_RATIO_STEPS.len();
+    let window = custom_ratio_window();
+
+    let boundary_index = (value * len as f32).round() as usize;
+
+    if boundary_index == 0 || boundary_index >= len {
+        return None;
+    }
+
+    let offset = value - boundary_index as f32 / len as f32;
+
+    if offset.abs() >= window {
+        return None;
+    }
+
+    let lower = OPERATOR_RATIO_STEPS[boundary_index - 1].value;
+    let upper = OPERATOR_RATIO_STEPS[boundary_index].value;
+    let t = ((offset / window + 1.0) / 2.0) as f64;
+
+    Some(Ratio::custom(lower * (upper / lower).powf(t)))
+}
+
+fn custom_ratio_to_patch(ratio: f64) -> f32 {
+    let len = OPERATOR_RATIO_STEPS.len();
+
+    let boundary_index = OPERATOR_RATIO_STEPS
+        .iter()
+        .position(|step| step.value > ratio)
+        .unwrap_or(len - 1)
+        .max(1);
+
+    let lower = OPERATOR_RATIO_STEPS[boundary_index - 1].value;
+    let upper = OPERATOR_RATIO_STEPS[boundary_index].value;
+    let t = ((ratio / lower).ln() / (upper / lower).ln()).clamp(0.0, 1.0) as f32;
+
+    boundary_index as f32 / len as f32 + custom_ratio_window() * (t * 2.0 - 1.0)
+}
+
+/// Snap patch value to the one of the step it maps to, e.g., to prevent
+/// knob from setting custom ratios
+pub fn snap_frequency_ratio_patch_value(value: f32) -> f32 {
+    map_step_to_patch_value(
+        &OPERATOR_RATIO_STEPS[..],
+        map_patch_value_to_step(&OPERATOR_RATIO_STEPS[..], value),
+    )
+}
+
+fn parse_fraction(text: &str) -> Option<f64> {
+    let (numerator, denominator) = text.split_once('/')?;
+
+    let numerator: f64 = numerator.trim().parse().ok()?;
+    let denominator: f64 = denominator.trim().parse().ok()?;
+
+    Some(numerator / denominator)
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct OperatorFrequencyRatioValue(Ratio);
 
@@ -127,6 +212,60 @@ impl Default for OperatorFrequencyRatioValue {
     }
 }
 
+impl OperatorFrequencyRatioValue {
+    /// Use step with exactly this ratio if available, otherwise create
+    /// custom ratio
+    fn new_from_ratio(value: f64) -> Option<Self> {
+        for ratio in OPERATOR_RATIO_STEPS.iter() {
+            if value == ratio.value {
+                return Some(Self(*ratio));
+            }
+        }
+
+        let min = OPERATOR_RATIO_STEPS[0].value;
+        let max = OPERATOR_RATIO_STEPS[OPERATOR_RATIO_STEPS.len() - 1].value;
+
+        if value.is_finite() && (min..=max).contains(&value) {
+            Some(Self(Ratio::custom(value)))
+        } else {
+            None
+        }
+    }
+
+    /// Parse semitone offset relative to note, e.g., "+7", "-12.5" or
+    /// "+7st +2c"
+    pub fn new_from_semitone_text(text: &str) -> Option<Self> {
+        let text = text.trim().to_lowercase().replace("st", " ");
+        let mut parts = text.split_whitespace();
+
+        let semitones: f64 = parts.next()?.parse().ok()?;
+        let cents: f64 = match parts.next() {
+            Some(cents) => cents.strip_suffix('c').unwrap_or(cents).parse().ok()?,
+            None => 0.0,
+        };
+
+        if parts.next().is_some() {
+            return None;
+        }
+
+        Self::new_from_ratio(2.0f64.powf((semitones + cents / 100.0) / 12.0))
+    }
+
+    /// Format as semitones and cents relative to note, e.g., "+7st +2c"
+    pub fn get_semitone_formatted(self) -> CompactString {
+        let total_cents = (1200.0 * self.0.value.log2()).round() as i64;
+
+        let semitones = (total_cents as f64 / 100.0).round() as i64;
+        let cents = total_cents - semitones * 100;
+
+        if cents == 0 {
+            format_compact!("{:+}st", semitones)
+        } else {
+            format_compact!("{:+}st {:+}c", semitones, cents)
+        }
+    }
+}
+
 impl ParameterValue for OperatorFrequencyRatioValue {
     type Value = Ratio;
 
@@ -146,32 +285,34 @@ impl ParameterValue for OperatorFrequencyRatioValue {
             }
         }
 
-        if let Ok(value) = text.parse::<f64>() {
-            if value == 3.14 {
-                return OPERATOR_RATIO_STEPS
-                    .iter()
-                    .find(|r| r.value == PI)
-                    .copied()
-                    .map(Self);
-            }
+        let value = text.parse::<f64>().ok().or_else(|| parse_fraction(&text))?;
 
-            for ratio in OPERATOR_RATIO_STEPS.iter() {
-                if value == ratio.value {
-                    return Some(Self(*ratio));
-                }
-            }
+        if value == 3.14 {
+            return OPERATOR_RATIO_STEPS
+                .iter()
+                .find(|r| r.value == PI)
+                .copied()
+                .map(Self);
         }
 
-        None
+        Self::new_from_ratio(value)
     }
     fn get(self) -> Self::Value {
         self.0
     }
     fn new_from_patch(value: f32) -> Self {
-        Self(map_patch_value_to_step(&OPERATOR_RATIO_STEPS[..], value))
+        if let Some(ratio) = custom_ratio_from_patch(value) {
+            Self(ratio)
+        } else {
+            Self(map_patch_value_to_step(&OPERATOR_RATIO_STEPS[..], value))
+        }
     }
     fn to_patch(self) -> f32 {
-        map_step_to_patch_value(&OPERATOR_RATIO_STEPS[..], self.0)
+        if OPERATOR_RATIO_STEPS.contains(&self.0) {
+            map_step_to_patch_value(&OPERATOR_RATIO_STEPS[..], self.0)
+        } else {
+            custom_ratio_to_patch(self.0.value)
+        }
     }
     fn get_formatted(self) -> CompactString {
         self.0.name.as_str().into()
@@ -201,4 +342,86 @@ mod tests {
         assert!(!ratios.is_empty());
         assert!(ratios.contains(&OperatorFrequencyRatioValue::default().get()));
     }
+
+    #[test]
+    fn test_step_patch_values_not_custom() {
+        for ratio in OPERATOR_RATIO_STEPS.iter() {
+            let patch_value = OperatorFrequencyRatioValue::new_from_audio(*ratio).to_patch();
+
+            assert_eq!(
+                OperatorFrequencyRatioValue::new_from_patch(patch_value).get(),
+                *ratio
+            );
+            assert_eq!(snap_frequency_ratio_patch_value(patch_value), patch_value);
+        }
+    }
+
+    #[test]
+    fn test_custom_ratios() {
+        for (text, expected) in [("1.333", 1.333), ("7/5", 1.4), ("0.9", 0.9), ("20.5", 20.5)] {
+            let value = OperatorFrequencyRatioValue::new_from_text(text).unwrap();
+
+            assert_eq!(value.get().value, expected);
+
+            let ratio = OperatorFrequencyRatioValue::new_from_patch(value.to_patch()).get();
+
+            assert!((ratio.value / expected - 1.0).abs() < 1e-4);
+            assert_eq!(
+                OperatorFrequencyRatioValue::new_from_text(ratio.name.as_str())
+                    .unwrap()
+                    .get()
+                    .name,
+                ratio.name
+            );
+        }
+
+        assert_eq!(
+            OperatorFrequencyRatioValue::new_from_text("4/3")
+                .unwrap()
+                .get()
+                .name
+                .as_str(),
+            "4/3"
+        );
+        assert!(OperatorFrequencyRatioValue::new_from_text("1/0").is_none());
+        assert!(OperatorFrequencyRatioValue::new_from_text("0.01").is_none());
+    }
+
+    #[test]
+    fn test_semitones() {
+        let format = |text| {
+            OperatorFrequencyRatioValue::new_from_text(text)
+                .unwrap()
+                .get_semitone_formatted()
+        };
+
+        assert_eq!(format("1"), "+0st");
+        assert_eq!(format("2"), "+12st");
+        assert_eq!(format("1/2"), "-12st");
+        assert_eq!(format("3/2"), "+7st +2c");
+        assert_eq!(format("5/4"), "+4st -14c");
+
+        for text in ["+12", "12st", "+7st +2c", "-14st +10c", "7 2c"] {
+            let value = OperatorFrequencyRatioValue::new_from_semitone_text(text).unwrap();
+            let formatted = value.get_semitone_formatted();
+
+            assert_eq!(
+                OperatorFrequencyRatioValue::new_from_semitone_text(&formatted)
+                    .unwrap()
+                    .get_semitone_formatted(),
+                formatted
+            );
+        }
+
+        assert_eq!(
+            OperatorFrequencyRatioValue::new_from_semitone_text("+12")
+                .unwrap()
+                .get()
+                .name
+                .as_str(),
+            "2"
+        );
+        assert!(OperatorFrequencyRatioValue::new_from_semitone_text("+7st +2c x").is_none());
+        assert!(OperatorFrequencyRatioValue::new_from_semitone_text("+100").is_none());
+    }
 }
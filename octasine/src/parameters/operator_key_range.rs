@@ -0,0 +1,116 @@
+use compact_str::{format_compact, CompactString};
+
+use super::{ParameterValue, SerializableRepresentation};
+
+pub const MAX_KEY: u8 = 127;
+
+const NOTE_NAMES: [&str; 12] = [
+    "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+];
+
+/// Format MIDI key as note name with octave, where key 60 is C4
+pub fn format_key(key: u8) -> CompactString {
+    let octave = i16::from(key / 12) - 1;
+
+    format_compact!("{}{}", NOTE_NAMES[usize::from(key % 12)], octave)
+}
+
+/// Parse MIDI key number or note name with octave
+fn parse_key(text: &str) -> Option<u8> {
+    let text = text.trim().to_uppercase();
+
+    if let Ok(key) = text.parse::<u8>() {
+        return (key <= MAX_KEY).then_some(key);
+    }
+
+    // Try sharp note names first, since they share prefixes with naturals
+    let (note_index, octave) =
+        NOTE_NAMES.iter().enumerate().rev().find_map(|(i, name)| {
+            Some((i, text.strip_prefix(name)?.trim().parse::<i16>().ok()?))
+        })?;
+
+    let key = (octave + 1) * 12 + note_index as i16;
+
+    (0..=i16::from(MAX_KEY)).contains(&key).then_some(key as u8)
+}
+
+macro_rules! impl_key_range_value {
+    ($(#[$attr:meta])* $name:ident, $default:expr) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone, Copy)]
+        pub struct $name(u8);
+
+        impl Default for $name {
+            fn default() -> Self {
+                Self($default)
+            }
+        }
+
+        impl ParameterValue for $name {
+            type Value = u8;
+
+            fn new_from_audio(value: Self::Value) -> Self {
+                Self(value.min(MAX_KEY))
+            }
+            fn new_from_text(text: &str) -> Option<Self> {
+                parse_key(text).map(Self)
+            }
+            fn get(self) -> Self::Value {
+                self.0
+            }
+            fn new_from_patch(value: f32) -> Self {
+                Self((value.clamp(0.0, 1.0) * f32::from(MAX_KEY)).round() as u8)
+            }
+            fn to_patch(self) -> f32 {
+                f32::from(self.0) / f32::from(MAX_KEY)
+            }
+            fn get_formatted(self) -> CompactString {
+                format_key(self.0)
+            }
+
+            fn get_serializable(&self) -> SerializableRepresentation {
+                SerializableRepresentation::Other(self.get_formatted())
+            }
+        }
+    };
+}
+
+impl_key_range_value!(
+    /// Lowest key for which operator sounds
+    OperatorKeyRangeLowValue,
+    0
+);
+impl_key_range_value!(
+    /// Highest key for which operator sounds
+    OperatorKeyRangeHighValue,
+    MAX_KEY
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_range_text() {
+        assert_eq!(format_key(60), "C4");
+        assert_eq!(format_key(0), "C-1");
+        assert_eq!(format_key(MAX_KEY), "G9");
+
+        for key in 0..=MAX_KEY {
+            assert_eq!(parse_key(&format_key(key)), Some(key));
+            assert_eq!(
+                OperatorKeyRangeLowValue::new_from_patch(
+                    OperatorKeyRangeLowValue::new_from_audio(key).to_patch()
+                )
+                .get(),
+                key
+            );
+        }
+
+        assert_eq!(parse_key("c#3"), Some(49));
+        assert_eq!(parse_key("100"), Some(100));
+        assert_eq!(parse_key("128"), None);
+        assert_eq!(parse_key("A9"), None);
+        assert_eq!(parse_key("H2"), None);
+    }
+}
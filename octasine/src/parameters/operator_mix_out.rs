@@ -36,6 +36,9 @@ impl ParameterValue for OperatorMixOutValue {
     fn get_formatted(self) -> CompactString {
         format_compact!("{:.04}", self.0)
     }
+    fn get_formatted_as_modulation_target(self) -> CompactString {
+        format_compact!("{:.2} dB", 20.0 * self.0.log10())
+    }
 
     fn get_serializable(&self) -> SerializableRepresentation {
         SerializableRepresentation::Float(self.0.into())
@@ -0,0 +1,37 @@
+use compact_str::{format_compact, CompactString};
+
+use super::{utils::parse_valid_f32, ParameterValue, SerializableRepresentation};
+
+const MAX_MS: f32 = 5.0;
+
+/// Stereo spread in milliseconds. The right channel of the operator output
+/// is delayed by this amount, widening the sound through the Haas effect
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OperatorSpreadValue(f32);
+
+impl ParameterValue for OperatorSpreadValue {
+    type Value = f32;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        parse_valid_f32(text, 0.0, MAX_MS).map(Self)
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(value.clamp(0.0, 1.0) * MAX_MS)
+    }
+    fn to_patch(self) -> f32 {
+        self.0 / MAX_MS
+    }
+    fn get_formatted(self) -> CompactString {
+        format_compact!("{:.2} MS", self.0)
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Float(self.0.into())
+    }
+}
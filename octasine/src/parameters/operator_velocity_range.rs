@@ -0,0 +1,59 @@
+use compact_str::{format_compact, CompactString};
+
+use super::{ParameterValue, SerializableRepresentation};
+
+pub const MAX_VELOCITY: u8 = 127;
+
+macro_rules! impl_velocity_range_value {
+    ($(#[$attr:meta])* $name:ident, $default:expr) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone, Copy)]
+        pub struct $name(u8);
+
+        impl Default for $name {
+            fn default() -> Self {
+                Self($default)
+            }
+        }
+
+        impl ParameterValue for $name {
+            type Value = u8;
+
+            fn new_from_audio(value: Self::Value) -> Self {
+                Self(value.min(MAX_VELOCITY))
+            }
+            fn new_from_text(text: &str) -> Option<Self> {
+                let value = text.trim().parse::<u8>().ok()?;
+
+                (value <= MAX_VELOCITY).then_some(Self(value))
+            }
+            fn get(self) -> Self::Value {
+                self.0
+            }
+            fn new_from_patch(value: f32) -> Self {
+                Self((value.clamp(0.0, 1.0) * f32::from(MAX_VELOCITY)).round() as u8)
+            }
+            fn to_patch(self) -> f32 {
+                f32::from(self.0) / f32::from(MAX_VELOCITY)
+            }
+            fn get_formatted(self) -> CompactString {
+                format_compact!("{}", self.0)
+            }
+
+            fn get_serializable(&self) -> SerializableRepresentation {
+                SerializableRepresentation::Other(self.get_formatted())
+            }
+        }
+    };
+}
+
+impl_velocity_range_value!(
+    /// Lowest MIDI velocity for which operator sounds
+    OperatorVelocityRangeLowValue,
+    0
+);
+impl_velocity_range_value!(
+    /// Highest MIDI velocity for which operator sounds
+    OperatorVelocityRangeHighValue,
+    MAX_VELOCITY
+);
@@ -14,6 +14,8 @@ const OPERATOR_WAVEFORMS: &[WaveType] = &[
     WaveType::Square,
     WaveType::Triangle,
     WaveType::Saw,
+    WaveType::SquareBandLimited,
+    WaveType::SawBandLimited,
     WaveType::WhiteNoise,
 ];
 
@@ -24,6 +26,12 @@ pub enum WaveType {
     Square,
     Triangle,
     Saw,
+    /// Square wave with hard edges smoothed depending on frequency,
+    /// reducing aliasing
+    SquareBandLimited,
+    /// Saw wave with hard edge smoothed depending on frequency, reducing
+    /// aliasing
+    SawBandLimited,
     WhiteNoise,
 }
 
@@ -34,6 +42,9 @@ impl WaveformChoices for WaveType {
             Self::Saw => crate::math::wave::saw(phase.0) as f32,
             Self::Triangle => crate::math::wave::triangle(phase.0) as f32,
             Self::Square => crate::math::wave::square(phase.0) as f32,
+            // Display without smoothing, as at very low frequencies
+            Self::SquareBandLimited => crate::math::wave::square_band_limited(phase.0, 0.0) as f32,
+            Self::SawBandLimited => crate::math::wave::saw_band_limited(phase.0, 0.0) as f32,
             Self::WhiteNoise => {
                 // Ensure same numbers are generated each time for GUI
                 // consistency. This will however break if fastrand changes
@@ -45,6 +56,23 @@ impl WaveformChoices for WaveType {
             }
         }
     }
+    /// Approximate audio generation, where sine feedback is in radians
+    /// and feedback for other wave types is in cycles
+    fn calculate_with_feedback(self, phase: Phase, feedback: f32) -> f32 {
+        let feedback = match self {
+            Self::WhiteNoise => return self.calculate_for_current(phase),
+            Self::Sine => feedback as f64 / TAU as f64,
+            Self::Square
+            | Self::Triangle
+            | Self::Saw
+            | Self::SquareBandLimited
+            | Self::SawBandLimited => feedback as f64,
+        };
+
+        let y = self.calculate_for_current(phase) as f64;
+
+        self.calculate_for_current(Phase((phase.0 + feedback * y).rem_euclid(1.0)))
+    }
     fn choices() -> &'static [Self] {
         OPERATOR_WAVEFORMS
     }
@@ -65,6 +93,8 @@ impl ParameterValue for OperatorWaveTypeValue {
             "square" => Some(Self(WaveType::Square)),
             "triangle" => Some(Self(WaveType::Triangle)),
             "saw" => Some(Self(WaveType::Saw)),
+            "square bl" => Some(Self(WaveType::SquareBandLimited)),
+            "saw bl" => Some(Self(WaveType::SawBandLimited)),
             "noise" => Some(Self(WaveType::WhiteNoise)),
             _ => None,
         }
@@ -84,6 +114,8 @@ impl ParameterValue for OperatorWaveTypeValue {
             WaveType::Square => "SQUARE".into(),
             WaveType::Triangle => "TRIANGLE".into(),
             WaveType::Saw => "SAW".into(),
+            WaveType::SquareBandLimited => "SQUARE BL".into(),
+            WaveType::SawBandLimited => "SAW BL".into(),
             WaveType::WhiteNoise => "NOISE".into(),
         }
     }
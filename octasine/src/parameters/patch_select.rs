@@ -0,0 +1,43 @@
+use compact_str::{format_compact, CompactString};
+
+use crate::common::NUM_PATCHES;
+
+use super::{ParameterValue, SerializableRepresentation};
+
+const MAX_PATCH_INDEX: u8 = (NUM_PATCHES - 1) as u8;
+
+/// Index of current patch. Setting it switches patch, allowing patch changes
+/// to be automated in hosts that can't send program changes.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PatchSelectValue(u8);
+
+impl ParameterValue for PatchSelectValue {
+    type Value = u8;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value.min(MAX_PATCH_INDEX))
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        let value = text.trim().parse::<u8>().ok()?;
+
+        (1..=MAX_PATCH_INDEX + 1)
+            .contains(&value)
+            .then_some(Self(value - 1))
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self((value.clamp(0.0, 1.0) * f32::from(MAX_PATCH_INDEX)).round() as u8)
+    }
+    fn to_patch(self) -> f32 {
+        f32::from(self.0) / f32::from(MAX_PATCH_INDEX)
+    }
+    fn get_formatted(self) -> CompactString {
+        format_compact!("{:03}", self.0 + 1)
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Other(self.get_formatted())
+    }
+}
@@ -0,0 +1,274 @@
+use std::fmt::Display;
+
+use compact_str::CompactString;
+
+use super::utils::*;
+use super::ParameterValue;
+use super::SerializableRepresentation;
+
+pub const SCALE_STEPS: &[Scale] = &[
+    Scale::Off,
+    Scale::Major,
+    Scale::NaturalMinor,
+    Scale::HarmonicMinor,
+    Scale::MelodicMinor,
+    Scale::Dorian,
+    Scale::Phrygian,
+    Scale::Lydian,
+    Scale::Mixolydian,
+    Scale::Locrian,
+    Scale::MajorPentatonic,
+    Scale::MinorPentatonic,
+    Scale::Blues,
+    Scale::WholeTone,
+];
+
+pub const SCALE_ROOT_STEPS: &[ScaleRoot] = &[
+    ScaleRoot(0),
+    ScaleRoot(1),
+    ScaleRoot(2),
+    ScaleRoot(3),
+    ScaleRoot(4),
+    ScaleRoot(5),
+    ScaleRoot(6),
+    ScaleRoot(7),
+    ScaleRoot(8),
+    ScaleRoot(9),
+    ScaleRoot(10),
+    ScaleRoot(11),
+];
+
+const NOTE_NAMES: [&str; 12] = [
+    "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+];
+
+/// Scale that incoming notes are snapped to
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Scale {
+    /// Don't quantize incoming notes
+    #[default]
+    Off,
+    Major,
+    NaturalMinor,
+    HarmonicMinor,
+    MelodicMinor,
+    Dorian,
+    Phrygian,
+    Lydian,
+    Mixolydian,
+    Locrian,
+    MajorPentatonic,
+    MinorPentatonic,
+    Blues,
+    WholeTone,
+}
+
+impl Scale {
+    /// Semitone offsets from root of pitch classes included in scale
+    fn intervals(self) -> &'static [u8] {
+        match self {
+            Self::Off => &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+            Self::Major => &[0, 2, 4, 5, 7, 9, 11],
+            Self::NaturalMinor => &[0, 2, 3, 5, 7, 8, 10],
+            Self::HarmonicMinor => &[0, 2, 3, 5, 7, 8, 11],
+            Self::MelodicMinor => &[0, 2, 3, 5, 7, 9, 11],
+            Self::Dorian => &[0, 2, 3, 5, 7, 9, 10],
+            Self::Phrygian => &[0, 1, 3, 5, 7, 8, 10],
+            Self::Lydian => &[0, 2, 4, 6, 7, 9, 11],
+            Self::Mixolydian => &[0, 2, 4, 5, 7, 9, 10],
+            Self::Locrian => &[0, 1, 3, 5, 6, 8, 10],
+            Self::MajorPentatonic => &[0, 2, 4, 7, 9],
+            Self::MinorPentatonic => &[0, 3, 5, 7, 10],
+            Self::Blues => &[0, 3, 5, 6, 7, 10],
+            Self::WholeTone => &[0, 2, 4, 6, 8, 10],
+        }
+    }
+
+    /// Snap key to nearest key in scale, preferring the lower one when
+    /// two keys are equally close
+    pub fn quantize(self, root: ScaleRoot, key: u8) -> u8 {
+        if self == Self::Off {
+            return key;
+        }
+
+        let intervals = self.intervals();
+        let in_scale = |k: i16| {
+            (0..128).contains(&k)
+                && intervals.contains(&((k - i16::from(root.0)).rem_euclid(12) as u8))
+        };
+
+        let key = i16::from(key);
+
+        for distance in 0..12 {
+            for k in [key - distance, key + distance] {
+                if in_scale(k) {
+                    return k as u8;
+                }
+            }
+        }
+
+        key as u8
+    }
+}
+
+impl Display for Scale {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            Self::Off => "OFF",
+            Self::Major => "MAJ",
+            Self::NaturalMinor => "MIN",
+            Self::HarmonicMinor => "HMIN",
+            Self::MelodicMinor => "MMIN",
+            Self::Dorian => "DOR",
+            Self::Phrygian => "PHR",
+            Self::Lydian => "LYD",
+            Self::Mixolydian => "MIX",
+            Self::Locrian => "LOC",
+            Self::MajorPentatonic => "PMAJ",
+            Self::MinorPentatonic => "PMIN",
+            Self::Blues => "BLUES",
+            Self::WholeTone => "WHOLE",
+        };
+
+        write!(f, "{}", text)
+    }
+}
+
+/// Pitch class of scale root, where 0 is C
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct ScaleRoot(pub u8);
+
+impl Display for ScaleRoot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", NOTE_NAMES[usize::from(self.0 % 12)])
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScaleValue(Scale);
+
+impl ParameterValue for ScaleValue {
+    type Value = Scale;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        let text = text.trim().to_uppercase();
+
+        SCALE_STEPS
+            .iter()
+            .find(|scale| scale.to_string() == text)
+            .map(|scale| Self(*scale))
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(map_patch_value_to_step(SCALE_STEPS, value))
+    }
+    fn to_patch(self) -> f32 {
+        map_step_to_patch_value(SCALE_STEPS, self.0)
+    }
+    fn get_formatted(self) -> CompactString {
+        self.0.to_string().into()
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Other(self.get_formatted())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScaleRootValue(ScaleRoot);
+
+impl ParameterValue for ScaleRootValue {
+    type Value = ScaleRoot;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        let text = text.trim().to_uppercase();
+
+        SCALE_ROOT_STEPS
+            .iter()
+            .find(|root| root.to_string() == text)
+            .map(|root| Self(*root))
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(map_patch_value_to_step(SCALE_ROOT_STEPS, value))
+    }
+    fn to_patch(self) -> f32 {
+        map_step_to_patch_value(SCALE_ROOT_STEPS, self.0)
+    }
+    fn get_formatted(self) -> CompactString {
+        self.0.to_string().into()
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Other(self.get_formatted())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scale_quantize() {
+        let c = ScaleRoot(0);
+
+        assert_eq!(Scale::Off.quantize(c, 61), 61);
+        assert_eq!(Scale::Major.quantize(c, 60), 60);
+        // C# is equally close to C and D, lower key is preferred
+        assert_eq!(Scale::Major.quantize(c, 61), 60);
+        assert_eq!(Scale::Major.quantize(ScaleRoot(2), 61), 61);
+        assert_eq!(Scale::MinorPentatonic.quantize(c, 62), 63);
+        assert_eq!(Scale::MajorPentatonic.quantize(c, 127), 127);
+        assert_eq!(Scale::MajorPentatonic.quantize(c, 0), 0);
+
+        for scale in SCALE_STEPS {
+            for root in SCALE_ROOT_STEPS {
+                for key in 0..128 {
+                    let quantized = scale.quantize(*root, key);
+
+                    assert!(quantized < 128);
+                    assert_eq!(scale.quantize(*root, quantized), quantized);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_scale_value_text() {
+        for scale in SCALE_STEPS {
+            let value = ScaleValue::new_from_audio(*scale);
+
+            assert_eq!(
+                ScaleValue::new_from_text(&value.get_formatted())
+                    .unwrap()
+                    .get(),
+                *scale
+            );
+            assert_eq!(ScaleValue::new_from_patch(value.to_patch()).get(), *scale);
+        }
+        for root in SCALE_ROOT_STEPS {
+            let value = ScaleRootValue::new_from_audio(*root);
+
+            assert_eq!(
+                ScaleRootValue::new_from_text(&value.get_formatted())
+                    .unwrap()
+                    .get(),
+                *root
+            );
+            assert_eq!(
+                ScaleRootValue::new_from_patch(value.to_patch()).get(),
+                *root
+            );
+        }
+    }
+}
@@ -75,8 +75,10 @@ pub fn round_to_step(steps: &[f32], value: f32) -> f32 {
     *steps.last().expect("steps are empty")
 }
 
+/// Parse number and clamp it to range, ignoring any trailing unit such as
+/// "Hz" (so that formatted values can be parsed)
 pub fn parse_valid_f32(text: &str, min: f32, max: f32) -> Option<f32> {
-    let value: f32 = text.parse().ok()?;
+    let value: f32 = trim_unit(text).parse().ok()?;
 
     if value.is_infinite() | value.is_nan() {
         None
@@ -85,8 +87,9 @@ pub fn parse_valid_f32(text: &str, min: f32, max: f32) -> Option<f32> {
     }
 }
 
+/// Parse number and clamp it to range, ignoring any trailing unit
 pub fn parse_valid_f64(text: &str, min: f64, max: f64) -> Option<f64> {
-    let value: f64 = text.parse().ok()?;
+    let value: f64 = trim_unit(text).parse().ok()?;
 
     if value.is_infinite() | value.is_nan() {
         None
@@ -95,6 +98,11 @@ pub fn parse_valid_f64(text: &str, min: f64, max: f64) -> Option<f64> {
     }
 }
 
+fn trim_unit(text: &str) -> &str {
+    text.trim()
+        .trim_end_matches(|c: char| c.is_alphabetic() || c.is_whitespace())
+}
+
 #[cfg(test)]
 mod tests {
     use assert_approx_eq::assert_approx_eq;
@@ -0,0 +1,160 @@
+use compact_str::{format_compact, CompactString};
+
+use super::{
+    utils::{map_audio_to_patch_value_with_steps, map_patch_to_audio_value_with_steps},
+    ParameterValue, SerializableRepresentation,
+};
+
+const RATE_STEPS: &[f32] = &[0.5, 2.0, 6.0, 12.0];
+const DEPTH_STEPS: &[f32] = &[0.0, 25.0, 100.0];
+const DELAY_STEPS: &[f32] = &[0.0, 0.5, 2.0];
+
+/// Vibrato rate in Hz
+#[derive(Debug, Clone, Copy)]
+pub struct VibratoRateValue(f32);
+
+impl Default for VibratoRateValue {
+    fn default() -> Self {
+        Self(5.0)
+    }
+}
+
+impl ParameterValue for VibratoRateValue {
+    type Value = f32;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        text.trim()
+            .trim_end_matches(|c: char| c.is_alphabetic() || c.is_whitespace())
+            .parse::<Self::Value>()
+            .ok()
+            .map(|rate| Self(rate.clamp(RATE_STEPS[0], *RATE_STEPS.last().unwrap())))
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(map_patch_to_audio_value_with_steps(RATE_STEPS, value))
+    }
+    fn to_patch(self) -> f32 {
+        map_audio_to_patch_value_with_steps(RATE_STEPS, self.0)
+    }
+    fn get_formatted(self) -> CompactString {
+        format_compact!("{:.2} HZ", self.0)
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Float(self.0.into())
+    }
+}
+
+/// Vibrato depth in cents. Zero turns vibrato off.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VibratoDepthValue(f32);
+
+impl ParameterValue for VibratoDepthValue {
+    type Value = f32;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        text.trim()
+            .trim_end_matches(|c: char| c.is_alphabetic() || c.is_whitespace())
+            .parse::<Self::Value>()
+            .ok()
+            .map(|depth| Self(depth.clamp(0.0, *DEPTH_STEPS.last().unwrap())))
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(map_patch_to_audio_value_with_steps(DEPTH_STEPS, value))
+    }
+    fn to_patch(self) -> f32 {
+        map_audio_to_patch_value_with_steps(DEPTH_STEPS, self.0)
+    }
+    fn get_formatted(self) -> CompactString {
+        format_compact!("{:.0} CENTS", self.0)
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Float(self.0.into())
+    }
+}
+
+/// Time in seconds from note-on until vibrato starts
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VibratoDelayValue(f32);
+
+impl ParameterValue for VibratoDelayValue {
+    type Value = f32;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        text.trim()
+            .trim_end_matches(|c: char| c.is_alphabetic() || c.is_whitespace())
+            .parse::<Self::Value>()
+            .ok()
+            .map(|delay| Self(delay.clamp(0.0, *DELAY_STEPS.last().unwrap())))
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(map_patch_to_audio_value_with_steps(DELAY_STEPS, value))
+    }
+    fn to_patch(self) -> f32 {
+        map_audio_to_patch_value_with_steps(DELAY_STEPS, self.0)
+    }
+    fn get_formatted(self) -> CompactString {
+        format_compact!("{:.2} S", self.0)
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Float(self.0.into())
+    }
+}
+
+/// Scale vibrato depth by mod wheel position
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VibratoModWheelValue(bool);
+
+impl ParameterValue for VibratoModWheelValue {
+    type Value = bool;
+
+    fn new_from_audio(value: Self::Value) -> Self {
+        Self(value)
+    }
+    fn new_from_text(text: &str) -> Option<Self> {
+        match text.trim().to_lowercase().as_str() {
+            "off" => Some(Self(false)),
+            "on" => Some(Self(true)),
+            _ => None,
+        }
+    }
+    fn get(self) -> Self::Value {
+        self.0
+    }
+    fn new_from_patch(value: f32) -> Self {
+        Self(value > 0.5)
+    }
+    fn to_patch(self) -> f32 {
+        if self.0 {
+            1.0
+        } else {
+            0.0
+        }
+    }
+    fn get_formatted(self) -> CompactString {
+        format_compact!("{}", if self.0 { "ON" } else { "OFF" })
+    }
+
+    fn get_serializable(&self) -> SerializableRepresentation {
+        SerializableRepresentation::Other(self.get_formatted())
+    }
+}
@@ -11,9 +11,9 @@ use clap_sys::{
 use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
 
 use crate::{
-    gui::{get_iced_baseview_settings, OctaSineIcedApplication, GUI_HEIGHT, GUI_WIDTH},
+    gui::{get_iced_baseview_settings, OctaSineIcedApplication},
     plugin::clap::{plugin::OctaSine, sync::ClapGuiSyncHandle},
-    sync::SyncState,
+    sync::{GuiSyncHandle, SyncState},
 };
 
 cfg_if! {
@@ -66,12 +66,16 @@ extern "C" fn set_scale(_plugin: *const clap_plugin, _scale: f64) -> bool {
 }
 
 unsafe extern "C" fn get_size(
-    _plugin: *const clap_plugin,
+    plugin: *const clap_plugin,
     width: *mut u32,
     height: *mut u32,
 ) -> bool {
-    *width = GUI_WIDTH as u32;
-    *height = GUI_HEIGHT as u32;
+    let plugin = &*((*plugin).plugin_data as *const OctaSine);
+
+    let (gui_width, gui_height) = plugin.sync.get_gui_settings().density.gui_size();
+
+    *width = gui_width as u32;
+    *height = gui_height as u32;
 
     true
 }
@@ -3,4 +3,5 @@ pub mod gui;
 pub mod note_ports;
 pub mod params;
 pub mod state;
+pub mod tail;
 pub mod voice_info;
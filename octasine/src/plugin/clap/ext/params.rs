@@ -1,12 +1,27 @@
-use std::ffi::{c_char, c_void, CStr, CString};
+use std::{
+    ffi::{c_char, c_void, CStr, CString},
+    ptr::null_mut,
+};
 
 use clap_sys::{
     events::{clap_input_events, clap_output_events},
-    ext::params::{clap_param_info, clap_plugin_params, CLAP_PARAM_IS_AUTOMATABLE},
+    ext::params::{
+        clap_param_info, clap_plugin_params, CLAP_PARAM_IS_AUTOMATABLE, CLAP_PARAM_IS_BYPASS,
+        CLAP_PARAM_IS_MODULATABLE, CLAP_PARAM_IS_MODULATABLE_PER_KEY,
+        CLAP_PARAM_IS_MODULATABLE_PER_NOTE_ID, CLAP_PARAM_IS_READONLY, CLAP_PARAM_IS_STEPPED,
+    },
     plugin::clap_plugin,
 };
-
-use crate::{parameters::ParameterKey, plugin::clap::plugin::OctaSine};
+use compact_str::format_compact;
+
+use crate::{
+    common::NUM_LFOS,
+    parameters::{
+        host_names::parameter_index_in_host_order, OperatorParameter, Parameter, ParameterKey,
+    },
+    plugin::clap::plugin::OctaSine,
+    sync::lfo_output_from_patch_value,
+};
 
 fn make_c_char_arr<const N: usize>(text: &str) -> [c_char; N] {
     let text = CString::new(text).unwrap();
@@ -24,7 +39,21 @@ fn make_c_char_arr<const N: usize>(text: &str) -> [c_char; N] {
 pub unsafe extern "C" fn count(plugin: *const clap_plugin) -> u32 {
     let plugin = &*((*plugin).plugin_data as *const OctaSine);
 
-    plugin.sync.patches.num_parameters() as u32
+    (plugin.sync.patches.num_parameters() + NUM_LFOS + 1) as u32
+}
+
+fn format_bypass(value: f64) -> &'static str {
+    if value >= 0.5 {
+        "On"
+    } else {
+        "Off"
+    }
+}
+
+/// Read-only LFO output parameters are placed after regular parameters,
+/// followed by bypass parameter
+fn get_lfo_output_index(param_id: u32) -> Option<usize> {
+    (0..NUM_LFOS).find(|i| ParameterKey::lfo_output(*i).0 == param_id)
 }
 
 pub unsafe extern "C" fn get_info(
@@ -34,22 +63,85 @@ pub unsafe extern "C" fn get_info(
 ) -> bool {
     let plugin = &*((*plugin).plugin_data as *const OctaSine);
 
-    if let Some(parameter) = plugin
-        .sync
-        .patches
-        .get_parameter_by_index(param_index as usize)
-    {
+    // Parameters are listed in host order, with related parameters
+    // adjacent. Cookies contain index in patch for fast lookup.
+    let opt_index_and_parameter =
+        parameter_index_in_host_order(param_index as usize).and_then(|index| {
+            plugin
+                .sync
+                .patches
+                .get_parameter_by_index(index)
+                .map(|parameter| (index, parameter))
+        });
+
+    if let Some((index, parameter)) = opt_index_and_parameter {
+        let flags = match parameter.parameter.parameter() {
+            Parameter::Operator(
+                _,
+                OperatorParameter::Volume
+                | OperatorParameter::ModOut
+                | OperatorParameter::FrequencyFine,
+            ) => {
+                CLAP_PARAM_IS_AUTOMATABLE
+                    | CLAP_PARAM_IS_MODULATABLE
+                    | CLAP_PARAM_IS_MODULATABLE_PER_NOTE_ID
+                    | CLAP_PARAM_IS_MODULATABLE_PER_KEY
+            }
+            // Value is shown and entered as text only. It isn't marked as
+            // stepped, since host values aren't integers.
+            parameter if !parameter.is_automatable() => 0,
+            _ => CLAP_PARAM_IS_AUTOMATABLE,
+        };
+
         *param_info = clap_param_info {
             id: parameter.parameter.key().0,
-            flags: CLAP_PARAM_IS_AUTOMATABLE,
-            cookie: param_index as usize as *mut c_void,
-            name: make_c_char_arr(&parameter.name),
+            flags,
+            cookie: index as *mut c_void,
+            name: make_c_char_arr(
+                &plugin
+                    .sync
+                    .get_parameter_host_name(index)
+                    .unwrap_or_default(),
+            ),
             module: make_c_char_arr(&parameter.clap_path),
             min_value: 0.0,
             max_value: 1.0,
             default_value: parameter.default_value as f64,
         };
 
+        true
+    } else if let Some(lfo_index) =
+        (param_index as usize).checked_sub(plugin.sync.patches.num_parameters())
+    {
+        if lfo_index == NUM_LFOS {
+            *param_info = clap_param_info {
+                id: ParameterKey::bypass().0,
+                flags: CLAP_PARAM_IS_AUTOMATABLE | CLAP_PARAM_IS_BYPASS | CLAP_PARAM_IS_STEPPED,
+                cookie: null_mut(),
+                name: make_c_char_arr("Bypass"),
+                module: make_c_char_arr(""),
+                min_value: 0.0,
+                max_value: 1.0,
+                default_value: 0.0,
+            };
+
+            return true;
+        }
+        if lfo_index > NUM_LFOS {
+            return false;
+        }
+
+        *param_info = clap_param_info {
+            id: ParameterKey::lfo_output(lfo_index).0,
+            flags: CLAP_PARAM_IS_READONLY,
+            cookie: param_index as usize as *mut c_void,
+            name: make_c_char_arr(&format_compact!("LFO {} output", lfo_index + 1)),
+            module: make_c_char_arr(&format_compact!("LFO {}", lfo_index + 1)),
+            min_value: 0.0,
+            max_value: 1.0,
+            default_value: 0.5,
+        };
+
         true
     } else {
         false
@@ -70,6 +162,14 @@ pub unsafe extern "C" fn get_value(
     {
         *value = p.get_value() as f64;
 
+        true
+    } else if let Some(lfo_index) = get_lfo_output_index(param_id) {
+        *value = plugin.sync.get_lfo_output_patch_value(lfo_index) as f64;
+
+        true
+    } else if param_id == ParameterKey::bypass().0 {
+        *value = if plugin.sync.get_bypass() { 1.0 } else { 0.0 };
+
         true
     } else {
         false
@@ -89,12 +189,21 @@ pub unsafe extern "C" fn value_to_text(
         return false;
     }
 
-    if let Some(parameter) = plugin
+    let opt_text = if let Some(parameter) = plugin
         .sync
         .patches
         .get_parameter_by_key(&ParameterKey(param_id))
     {
-        if let Ok(text) = CString::new((parameter.format)(value as f32).as_str()) {
+        Some((parameter.format)(value as f32))
+    } else if param_id == ParameterKey::bypass().0 {
+        Some(format_bypass(value).into())
+    } else {
+        get_lfo_output_index(param_id)
+            .map(|_| format_compact!("{:.04}", lfo_output_from_patch_value(value as f32)))
+    };
+
+    if let Some(text) = opt_text {
+        if let Ok(text) = CString::new(text.as_str()) {
             let bytes = text.as_bytes_with_nul();
 
             if bytes.len() > c_str_len as usize {
@@ -130,6 +239,20 @@ pub unsafe extern "C" fn text_to_value(
                 *value = v as f64;
             }
 
+            return true;
+        }
+    } else if param_id == ParameterKey::bypass().0 {
+        if let Ok(text) = CStr::from_ptr(text).to_str() {
+            let text = text.trim();
+
+            if text.eq_ignore_ascii_case(format_bypass(1.0)) {
+                *value = 1.0;
+            } else if text.eq_ignore_ascii_case(format_bypass(0.0)) {
+                *value = 0.0;
+            } else {
+                return false;
+            }
+
             return true;
         }
     }
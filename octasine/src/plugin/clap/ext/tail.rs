@@ -0,0 +1,11 @@
+use clap_sys::{ext::tail::clap_plugin_tail, plugin::clap_plugin};
+
+use crate::plugin::clap::plugin::OctaSine;
+
+unsafe extern "C" fn get(plugin: *const clap_plugin) -> u32 {
+    let plugin = &*((*plugin).plugin_data as *const OctaSine);
+
+    plugin.audio.lock().get_tail_length_in_samples()
+}
+
+pub const CONFIG: clap_plugin_tail = clap_plugin_tail { get: Some(get) };
@@ -9,11 +9,14 @@ use atomic_refcell::AtomicRefCell;
 use clap_sys::{
     events::{
         clap_event_header, clap_event_midi, clap_event_note, clap_event_note_expression,
-        clap_event_param_gesture, clap_event_param_value, clap_event_transport, clap_output_events,
-        CLAP_CORE_EVENT_SPACE_ID, CLAP_EVENT_IS_LIVE, CLAP_EVENT_MIDI, CLAP_EVENT_NOTE_END,
-        CLAP_EVENT_NOTE_EXPRESSION, CLAP_EVENT_NOTE_OFF, CLAP_EVENT_NOTE_ON,
-        CLAP_EVENT_PARAM_GESTURE_BEGIN, CLAP_EVENT_PARAM_GESTURE_END, CLAP_EVENT_PARAM_VALUE,
-        CLAP_EVENT_TRANSPORT, CLAP_NOTE_EXPRESSION_PRESSURE, CLAP_TRANSPORT_HAS_TEMPO,
+        clap_event_param_gesture, clap_event_param_mod, clap_event_param_value,
+        clap_event_transport, clap_output_events, CLAP_CORE_EVENT_SPACE_ID, CLAP_EVENT_IS_LIVE,
+        CLAP_EVENT_MIDI, CLAP_EVENT_NOTE_END, CLAP_EVENT_NOTE_EXPRESSION, CLAP_EVENT_NOTE_OFF,
+        CLAP_EVENT_NOTE_ON, CLAP_EVENT_PARAM_GESTURE_BEGIN, CLAP_EVENT_PARAM_GESTURE_END,
+        CLAP_EVENT_PARAM_MOD, CLAP_EVENT_PARAM_VALUE, CLAP_EVENT_TRANSPORT,
+        CLAP_NOTE_EXPRESSION_PAN, CLAP_NOTE_EXPRESSION_PRESSURE, CLAP_NOTE_EXPRESSION_TUNING,
+        CLAP_NOTE_EXPRESSION_VOLUME, CLAP_TRANSPORT_HAS_BEATS_TIMELINE, CLAP_TRANSPORT_HAS_TEMPO,
+        CLAP_TRANSPORT_IS_PLAYING,
     },
     ext::{
         audio_ports::CLAP_EXT_AUDIO_PORTS,
@@ -22,10 +25,15 @@ use clap_sys::{
         note_ports::CLAP_EXT_NOTE_PORTS,
         params::{clap_host_params, CLAP_EXT_PARAMS, CLAP_PARAM_RESCAN_VALUES},
         state::{clap_host_state, CLAP_EXT_STATE},
+        tail::CLAP_EXT_TAIL,
     },
+    fixedpoint::CLAP_BEATTIME_FACTOR,
     host::clap_host,
     plugin::clap_plugin,
-    process::{clap_process, clap_process_status, CLAP_PROCESS_CONTINUE, CLAP_PROCESS_ERROR},
+    process::{
+        clap_process, clap_process_status, CLAP_PROCESS_CONTINUE, CLAP_PROCESS_ERROR,
+        CLAP_PROCESS_SLEEP,
+    },
 };
 use iced_baseview::window::WindowHandle;
 use once_cell::sync::Lazy;
@@ -34,8 +42,12 @@ use ringbuf::{Consumer, Producer, Rb, SharedRb};
 
 use crate::{
     audio::{gen::process_f32_runtime_select, AudioState},
-    common::{BeatsPerMinute, EventToHost, NoteEvent, NoteEventInner, SampleRate},
+    common::{
+        BeatsPerMinute, EventToHost, NoteEvent, NoteEventInner, NoteExpression, SampleRate,
+        NUM_LFOS,
+    },
     parameters::ParameterKey,
+    settings::Settings,
     sync::SyncState,
     utils::{init_logging, update_audio_parameters},
 };
@@ -68,9 +80,13 @@ impl OctaSine {
             host,
         };
 
+        let mut audio = Box::<AudioState>::default();
+
+        audio.set_control_interval(Settings::load_or_default().control_interval);
+
         let plugin = Self {
             host,
-            audio: Default::default(),
+            audio: Mutex::new(audio),
             sync: Arc::new(SyncState::new(Some(gui_sync_handle))),
             gui_event_consumer: Mutex::new(gui_event_consumer),
             gui_parent: Default::default(),
@@ -146,14 +162,14 @@ impl OctaSine {
             return CLAP_PROCESS_ERROR;
         }
 
-        let audio_outputs = &*process.audio_outputs;
+        let audio_output_buffer = &mut *process.audio_outputs;
 
-        if (audio_outputs.channel_count != 2) | audio_outputs.data32.is_null() {
+        if (audio_output_buffer.channel_count != 2) | audio_output_buffer.data32.is_null() {
             return CLAP_PROCESS_ERROR;
         }
 
         let audio_outputs =
-            ::std::slice::from_raw_parts_mut(audio_outputs.data32 as *mut *mut f32, 2);
+            ::std::slice::from_raw_parts_mut(audio_output_buffer.data32 as *mut *mut f32, 2);
 
         if audio_outputs[0].is_null() | audio_outputs[1].is_null() {
             return CLAP_PROCESS_ERROR;
@@ -196,6 +212,7 @@ impl OctaSine {
         let mut process_start_index = 0u32;
         let mut process_end_index = process.frames_count;
         let mut event_index = 0u32;
+        let mut silent = true;
 
         // Split buffer into segments by events, generate audio
         loop {
@@ -221,7 +238,7 @@ impl OctaSine {
                 let lefts = &mut lefts[process_start_index as usize..process_end_index as usize];
                 let rights = &mut rights[process_start_index as usize..process_end_index as usize];
 
-                process_f32_runtime_select(
+                silent &= process_f32_runtime_select(
                     &mut audio,
                     lefts,
                     rights,
@@ -237,6 +254,7 @@ impl OctaSine {
             }
 
             if let Some(process_out_events) = opt_process_out_events {
+                plugin.send_midi_out_events_to_host(process_out_events);
                 plugin.send_note_end_events_to_host(process_out_events);
             }
 
@@ -248,6 +266,44 @@ impl OctaSine {
             process_end_index = process.frames_count;
         }
 
+        let (
+            lfo_outputs,
+            held_keys,
+            modulation_input_peaks,
+            last_midi_event,
+            envelope_positions,
+            clipped,
+        ) = {
+            let mut audio = plugin.audio.lock();
+
+            (
+                audio.get_lfo_outputs(),
+                audio.get_held_keys(),
+                audio.take_modulation_input_peaks(),
+                audio.take_last_midi_event(),
+                audio.get_envelope_positions(),
+                audio.take_clipped(),
+            )
+        };
+
+        let changed_lfo_outputs = plugin.sync.set_lfo_outputs(lfo_outputs);
+
+        plugin.sync.set_held_keys(held_keys);
+        plugin
+            .sync
+            .update_modulation_input_peaks(modulation_input_peaks);
+        plugin.sync.set_last_midi_event(last_midi_event);
+        plugin.sync.set_envelope_positions(envelope_positions);
+        plugin.sync.set_clipped(clipped);
+
+        if let Some(process_out_events) = opt_process_out_events {
+            plugin.send_lfo_output_events_to_host(
+                process_out_events,
+                changed_lfo_outputs,
+                process.frames_count.saturating_sub(1),
+            );
+        }
+
         // Log any unhandled events. Should never happen.
         if let Some((num_events, get_fn)) = opt_in_event_data {
             while event_index < num_events {
@@ -261,7 +317,17 @@ impl OctaSine {
             }
         }
 
-        CLAP_PROCESS_CONTINUE
+        if silent {
+            // Mark both channels as constant (zero) and let host skip calls
+            // until new events arrive
+            audio_output_buffer.constant_mask = 0b11;
+
+            CLAP_PROCESS_SLEEP
+        } else {
+            audio_output_buffer.constant_mask = 0;
+
+            CLAP_PROCESS_CONTINUE
+        }
     }
 
     unsafe extern "C" fn get_extension(
@@ -282,6 +348,8 @@ impl OctaSine {
             &super::ext::voice_info::CONFIG as *const _ as *const c_void
         } else if id == CLAP_EXT_STATE {
             &super::ext::state::CONFIG as *const _ as *const c_void
+        } else if id == CLAP_EXT_TAIL {
+            &super::ext::tail::CONFIG as *const _ as *const c_void
         } else {
             null()
         }
@@ -336,6 +404,26 @@ impl OctaSine {
 
                         self.audio.lock().enqueue_note_event(event);
                     }
+                    CLAP_NOTE_EXPRESSION_TUNING
+                    | CLAP_NOTE_EXPRESSION_VOLUME
+                    | CLAP_NOTE_EXPRESSION_PAN => {
+                        let expression = match event.expression_id {
+                            CLAP_NOTE_EXPRESSION_TUNING => NoteExpression::Tuning(event.value),
+                            CLAP_NOTE_EXPRESSION_VOLUME => NoteExpression::Volume(event.value),
+                            _ => NoteExpression::Pan(event.value),
+                        };
+
+                        let event = NoteEvent {
+                            delta_frames: event.header.time,
+                            event: NoteEventInner::ClapNoteExpression {
+                                key: (event.key >= 0).then_some(event.key as u8),
+                                clap_note_id: (event.note_id >= 0).then_some(event.note_id),
+                                expression,
+                            },
+                        };
+
+                        self.audio.lock().enqueue_note_event(event);
+                    }
                     _ => (),
                 };
             }
@@ -352,6 +440,15 @@ impl OctaSine {
             CLAP_EVENT_PARAM_VALUE => {
                 let event = &*(event_header as *const clap_event_param_value);
 
+                if event.param_id == ParameterKey::bypass().0 {
+                    let bypass = event.value >= 0.5;
+
+                    self.sync.set_bypass(bypass);
+                    self.audio.lock().set_bypass(bypass);
+
+                    return;
+                }
+
                 let opt_index_and_parameter = if event.cookie.is_null() {
                     let key = ParameterKey(event.param_id);
 
@@ -380,6 +477,36 @@ impl OctaSine {
                         .set_parameter_from_patch(p.parameter.parameter(), value)
                 }
             }
+            CLAP_EVENT_PARAM_MOD => {
+                let event = &*(event_header as *const clap_event_param_mod);
+
+                let opt_parameter = if event.cookie.is_null() {
+                    let key = ParameterKey(event.param_id);
+
+                    self.sync
+                        .patches
+                        .get_index_and_parameter_by_key(&key)
+                        .map(|(_, p)| p)
+                } else {
+                    self.sync
+                        .patches
+                        .get_parameter_by_index(event.cookie as u64 as usize)
+                };
+
+                if let Some(p) = opt_parameter {
+                    let event = NoteEvent {
+                        delta_frames: event.header.time,
+                        event: NoteEventInner::ClapParameterModulation {
+                            parameter: p.parameter.parameter(),
+                            key: (event.key >= 0).then_some(event.key as u8),
+                            clap_note_id: (event.note_id >= 0).then_some(event.note_id),
+                            amount: event.amount as f32,
+                        },
+                    };
+
+                    self.audio.lock().enqueue_note_event(event);
+                }
+            }
             CLAP_EVENT_TRANSPORT => {
                 let event = &*(event_header as *const clap_event_transport);
 
@@ -404,6 +531,20 @@ impl OctaSine {
 
             self.audio.lock().enqueue_note_event(event);
         }
+
+        let playing = event.flags & CLAP_TRANSPORT_IS_PLAYING != 0;
+        let song_position = (playing && event.flags & CLAP_TRANSPORT_HAS_BEATS_TIMELINE != 0)
+            .then(|| event.song_pos_beats as f64 / CLAP_BEATTIME_FACTOR as f64);
+
+        let event = NoteEvent {
+            delta_frames: event.header.time,
+            event: NoteEventInner::Transport {
+                playing,
+                song_position,
+            },
+        };
+
+        self.audio.lock().enqueue_note_event(event);
     }
 
     pub unsafe fn send_gui_events_to_host(&self, out_events: &clap_output_events, time: u32) {
@@ -471,6 +612,67 @@ impl OctaSine {
         }
     }
 
+    /// Send value events for read-only LFO output parameters
+    pub unsafe fn send_lfo_output_events_to_host(
+        &self,
+        out_events: &clap_output_events,
+        changed: [bool; NUM_LFOS],
+        time: u32,
+    ) {
+        if let Some(try_push_fn) = out_events.try_push {
+            for (lfo_index, _) in changed.iter().enumerate().filter(|(_, changed)| **changed) {
+                let event = clap_event_param_value {
+                    header: clap_event_header {
+                        size: size_of::<clap_event_param_value>() as u32,
+                        time,
+                        space_id: CLAP_CORE_EVENT_SPACE_ID,
+                        type_: CLAP_EVENT_PARAM_VALUE,
+                        flags: CLAP_EVENT_IS_LIVE,
+                    },
+                    param_id: ParameterKey::lfo_output(lfo_index).0,
+                    cookie: null_mut(),
+                    note_id: -1,
+                    port_index: 0,
+                    channel: -1,
+                    key: -1,
+                    value: self.sync.get_lfo_output_patch_value(lfo_index) as f64,
+                };
+
+                try_push_fn(out_events, &event as *const _ as *const _);
+            }
+        }
+    }
+
+    /// Send notes generated by chord memory to output note port
+    pub fn send_midi_out_events_to_host(&self, out_events: &clap_output_events) {
+        if let Some(try_push_fn) = out_events.try_push {
+            for midi_out_event in self.audio.lock().midi_out_events.pop_iter() {
+                unsafe {
+                    let event = clap_event_note {
+                        header: clap_event_header {
+                            size: size_of::<clap_event_note>() as u32,
+                            time: midi_out_event.sample_index,
+                            space_id: CLAP_CORE_EVENT_SPACE_ID,
+                            type_: if midi_out_event.note_on {
+                                CLAP_EVENT_NOTE_ON
+                            } else {
+                                CLAP_EVENT_NOTE_OFF
+                            },
+                            flags: CLAP_EVENT_IS_LIVE,
+                        },
+                        note_id: -1,
+                        port_index: 0,
+                        channel: 0,
+                        key: midi_out_event.key.into(),
+                        velocity: midi_out_event.velocity.0.into(),
+                    };
+
+                    try_push_fn(out_events, &event as *const _ as *const _);
+                }
+            }
+        }
+    }
+
     pub fn send_note_end_events_to_host(&self, out_events: &clap_output_events) {
         if let Some(try_push_fn) = out_events.try_push {
             for note_ended in self.audio.lock().clap_ended_notes.pop_iter() {
@@ -1,14 +1,21 @@
-use std::{path::PathBuf, sync::Arc};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use clap_sys::host::clap_host;
 use compact_str::CompactString;
 use parking_lot::Mutex;
 
 use crate::{
-    common::EventToHost,
+    common::{EnvelopeStage, EventToHost, NUM_OPERATORS},
     parameters::WrappedParameter,
     settings::Settings,
-    sync::{change_info::MAX_NUM_PARAMETERS, GuiSyncHandle, SyncState},
+    sync::{
+        change_info::MAX_NUM_PARAMETERS, GuiSyncHandle, ParameterLocks, PatchCategory,
+        PatchImportTarget, PatchMetadata, RandomizeScope, SessionState, SyncState,
+    },
+    wavetable::Wavetable,
 };
 
 use super::plugin::EventToHostProducer;
@@ -28,13 +35,7 @@ impl ClapGuiSyncHandle {
             ::log::error!("ClapGuiSyncHandle can't send event due to full buffer");
         }
 
-        unsafe {
-            let host = &*(self.host);
-
-            if let Some(request_process) = host.request_process.as_ref() {
-                request_process(self.host);
-            }
-        }
+        self.request_process();
     }
 
     fn send_events<I: IntoIterator<Item = EventToHost>>(&self, events: I) {
@@ -46,6 +47,10 @@ impl ClapGuiSyncHandle {
             ::log::error!("ClapGuiSyncHandle can't send event or events due to full buffer");
         }
 
+        self.request_process();
+    }
+
+    fn request_process(&self) {
         unsafe {
             let host = &*(self.host);
 
@@ -123,17 +128,33 @@ impl GuiSyncHandle for Arc<SyncState<ClapGuiSyncHandle>> {
             .format_parameter_value(parameter.index() as usize, value)
             .unwrap() // FIXME: unwrap
     }
+    fn format_parameter_value_as_modulation_target(
+        &self,
+        parameter: WrappedParameter,
+        value: f32,
+    ) -> CompactString {
+        self.patches
+            .format_parameter_value_as_modulation_target(parameter.index() as usize, value)
+            .unwrap_or_default()
+    }
     fn get_patches(&self) -> (usize, Vec<CompactString>) {
         let index = self.patches.get_patch_index();
         let names = self.patches.get_patch_names();
 
         (index, names)
     }
+    fn get_patch_categories(&self) -> Vec<Option<PatchCategory>> {
+        self.patches.get_patch_categories()
+    }
+    fn get_used_patch_slots(&self) -> Vec<bool> {
+        self.patches.get_used_patch_slots()
+    }
     fn set_patch_index(&self, index: usize) {
         self.patches.set_patch_index(index);
 
+        // Patch index and names are part of state
         if let Some(host) = &self.host {
-            host.send_event(EventToHost::RescanValues);
+            host.send_events([EventToHost::RescanValues, EventToHost::StateChanged]);
         }
     }
     fn get_current_patch_name(&self) -> CompactString {
@@ -146,6 +167,33 @@ impl GuiSyncHandle for Arc<SyncState<ClapGuiSyncHandle>> {
             host.send_event(EventToHost::StateChanged);
         }
     }
+    fn get_current_patch_metadata(&self) -> PatchMetadata {
+        self.patches.get_current_patch_metadata()
+    }
+    fn set_current_patch_metadata(&self, metadata: PatchMetadata) {
+        self.patches.set_current_patch_metadata(metadata);
+
+        if let Some(host) = &self.host {
+            host.send_event(EventToHost::StateChanged);
+        }
+    }
+    fn get_wavetable_name(&self, operator_index: usize) -> Option<CompactString> {
+        self.patches.get_wavetable_name(operator_index)
+    }
+    fn set_wavetable(&self, operator_index: usize, wavetable: Option<Arc<Wavetable>>) {
+        self.patches.set_wavetable(operator_index, wavetable);
+
+        if let Some(host) = &self.host {
+            host.send_event(EventToHost::StateChanged);
+        }
+    }
+    fn store_mod_snapshot(&self, slot_index: usize) {
+        self.patches.store_mod_snapshot(slot_index);
+
+        if let Some(host) = &self.host {
+            host.send_event(EventToHost::StateChanged);
+        }
+    }
     fn get_changed_parameters(&self) -> Option<[Option<f32>; MAX_NUM_PARAMETERS]> {
         self.patches.get_changed_parameters_from_gui()
     }
@@ -155,6 +203,75 @@ impl GuiSyncHandle for Arc<SyncState<ClapGuiSyncHandle>> {
     fn get_gui_settings(&self) -> crate::gui::GuiSettings {
         Settings::load_or_default().gui
     }
+    fn get_session_state(&self) -> Option<SessionState> {
+        self.patches.get_session_state()
+    }
+    fn set_session_state(&self, state: SessionState) {
+        self.patches.set_session_state(state);
+    }
+    fn get_held_keys(&self) -> u128 {
+        SyncState::get_held_keys(self)
+    }
+    fn take_modulation_input_peaks(&self) -> [f32; NUM_OPERATORS] {
+        SyncState::take_modulation_input_peaks(self)
+    }
+    fn take_last_midi_event(&self) -> Option<[u8; 3]> {
+        SyncState::take_last_midi_event(self)
+    }
+    fn get_envelope_position(&self, operator_index: usize) -> Option<(EnvelopeStage, f32)> {
+        SyncState::get_envelope_position(self, operator_index)
+    }
+    fn take_clipped(&self) -> bool {
+        SyncState::take_clipped(self)
+    }
+    fn set_lfos_disabled(&self, disabled: bool) {
+        SyncState::set_lfos_disabled(self, disabled)
+    }
+    fn get_lfos_disabled(&self) -> bool {
+        SyncState::get_lfos_disabled(self)
+    }
+    fn set_auto_gain(&self, enabled: bool) {
+        SyncState::set_auto_gain(self, enabled)
+    }
+    fn get_auto_gain(&self) -> bool {
+        SyncState::get_auto_gain(self)
+    }
+    fn set_mono_fold_down(&self, enabled: bool) {
+        SyncState::set_mono_fold_down(self, enabled)
+    }
+    fn get_mono_fold_down(&self) -> bool {
+        SyncState::get_mono_fold_down(self)
+    }
+    fn set_listen_operator(&self, opt_operator_index: Option<usize>) {
+        SyncState::set_listen_operator(self, opt_operator_index)
+    }
+    fn get_listen_operator(&self) -> Option<usize> {
+        SyncState::get_listen_operator(self)
+    }
+    fn request_audition(&self, key: u8, velocity: u8, duration: f64) {
+        SyncState::request_audition(self, key, velocity, duration);
+
+        if let Some(handle) = &self.host {
+            handle.request_process();
+        }
+    }
+    fn set_gui_key(&self, key: u8, pressed: bool) {
+        SyncState::set_gui_key(self, key, pressed);
+
+        if let Some(handle) = &self.host {
+            handle.request_process();
+        }
+    }
+    fn request_panic(&self) {
+        SyncState::request_panic(self);
+
+        if let Some(handle) = &self.host {
+            handle.request_process();
+        }
+    }
+    fn set_stuck_note_timeout(&self, opt_timeout: Option<f64>) {
+        SyncState::set_stuck_note_timeout(self, opt_timeout);
+    }
     fn export_patch(&self) -> (CompactString, Vec<u8>) {
         let name = self.patches.get_current_patch().get_fxp_filename();
         let data = self.patches.get_current_patch().export_fxp_bytes();
@@ -164,25 +281,90 @@ impl GuiSyncHandle for Arc<SyncState<ClapGuiSyncHandle>> {
     fn export_bank(&self) -> Vec<u8> {
         self.patches.export_fxb_bytes()
     }
-    fn import_bank_or_patches_from_paths(&self, paths: &[PathBuf]) {
-        self.patches.import_bank_or_patches_from_paths(paths);
+    fn import_bank_or_patches_from_paths(&self, paths: &[PathBuf], target: PatchImportTarget) {
+        self.patches
+            .import_bank_or_patches_from_paths(paths, target);
 
         if let Some(host) = &self.host {
-            host.send_event(EventToHost::RescanValues);
+            host.send_events([EventToHost::RescanValues, EventToHost::StateChanged]);
+        }
+    }
+    fn start_patch_preview(&self, path: &Path) {
+        if let Err(err) = self.patches.start_patch_preview(path) {
+            ::log::error!("failed previewing patch: {:#}", err);
+        }
+
+        if let Some(handle) = &self.host {
+            handle.request_process();
+        }
+    }
+    fn end_patch_preview(&self) {
+        self.patches.end_patch_preview();
+
+        if let Some(handle) = &self.host {
+            handle.request_process();
         }
     }
     fn clear_patch(&self) {
         self.patches.clear_current_patch();
 
         if let Some(host) = &self.host {
-            host.send_event(EventToHost::RescanValues);
+            host.send_events([EventToHost::RescanValues, EventToHost::StateChanged]);
         }
     }
     fn clear_bank(&self) {
         self.patches.clear_bank();
 
         if let Some(host) = &self.host {
-            host.send_event(EventToHost::RescanValues);
+            host.send_events([EventToHost::RescanValues, EventToHost::StateChanged]);
+        }
+    }
+    fn randomize_parameters(&self, scope: RandomizeScope) {
+        self.patches.randomize_parameters(scope);
+
+        if let Some(host) = &self.host {
+            host.send_events([EventToHost::RescanValues, EventToHost::StateChanged]);
+        }
+    }
+    fn mutate_parameters(&self, scope: RandomizeScope, strength: f32) {
+        self.patches.mutate_parameters(scope, strength);
+
+        if let Some(host) = &self.host {
+            host.send_events([EventToHost::RescanValues, EventToHost::StateChanged]);
+        }
+    }
+    fn get_parameter_locks(&self) -> Arc<ParameterLocks> {
+        self.patches.get_parameter_locks()
+    }
+    fn breed_patches(
+        &self,
+        parent_indices: [usize; 2],
+        num_children: usize,
+        mutation_strength: Option<f32>,
+    ) -> Vec<usize> {
+        let child_indices =
+            self.patches
+                .breed_patches(parent_indices, num_children, mutation_strength);
+
+        if let Some(host) = &self.host {
+            host.send_events([EventToHost::RescanValues, EventToHost::StateChanged]);
         }
+
+        child_indices
+    }
+    fn have_unsaved_changes(&self) -> bool {
+        self.patches.have_unsaved_changes()
+    }
+    fn mark_bank_as_saved(&self) {
+        self.patches.mark_as_saved();
+    }
+    fn is_capturing_parameter_changes(&self) -> bool {
+        self.patches.is_capturing_parameter_changes()
+    }
+    fn start_parameter_change_capture(&self) {
+        self.patches.start_parameter_change_capture();
+    }
+    fn stop_parameter_change_capture(&self) -> Option<crate::sync::ParameterChangeLog> {
+        self.patches.stop_parameter_change_capture()
     }
 }
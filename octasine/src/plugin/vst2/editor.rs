@@ -5,7 +5,7 @@ use parking_lot::Mutex;
 use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
 
 use crate::{
-    gui::{get_iced_baseview_settings, Message, GUI_HEIGHT, GUI_WIDTH},
+    gui::{get_iced_baseview_settings, Message},
     plugin::vst2::PLUGIN_SEMVER_NAME,
     sync::GuiSyncHandle,
 };
@@ -35,7 +35,9 @@ impl<H: GuiSyncHandle> Editor<H> {
 
 impl<H: GuiSyncHandle> vst::editor::Editor for Editor<H> {
     fn size(&self) -> (i32, i32) {
-        (GUI_WIDTH as i32, GUI_HEIGHT as i32)
+        let (width, height) = self.sync_state.get_gui_settings().density.gui_size();
+
+        (width as i32, height as i32)
     }
 
     fn position(&self) -> (i32, i32) {
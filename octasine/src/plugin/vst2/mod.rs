@@ -4,14 +4,17 @@ mod sync;
 
 use std::sync::Arc;
 
+use ringbuf::Rb;
 use vst::api::{Events, Supported};
-use vst::event::Event;
+use vst::buffer::SendEventBuffer;
+use vst::event::{Event, MidiEvent};
 use vst::host::Host;
 #[allow(deprecated)]
 use vst::plugin::{CanDo, Category, HostCallback, Info, Plugin, PluginParameters};
 
 use crate::audio::gen::process_f32_runtime_select;
 use crate::audio::AudioState;
+use crate::settings::Settings;
 use crate::sync::SyncState;
 use crate::utils::{init_logging, update_audio_parameters};
 use crate::{common::*, crate_version};
@@ -21,6 +24,7 @@ use super::common::{crate_version_to_vst2_format, PLUGIN_SEMVER_NAME, PLUGIN_UNI
 pub struct OctaSine {
     pub audio: Box<AudioState>,
     pub sync: Arc<SyncState<vst::plugin::HostCallback>>,
+    send_event_buffer: SendEventBuffer,
     #[cfg(feature = "gui")]
     editor: Option<editor::Editor<Arc<SyncState<vst::plugin::HostCallback>>>>,
 }
@@ -42,25 +46,72 @@ impl OctaSine {
         #[cfg(feature = "gui")]
         let editor = editor::Editor::new(sync.clone());
 
+        let mut audio = Box::<AudioState>::default();
+
+        audio.set_control_interval(Settings::load_or_default().control_interval);
+
         Self {
-            audio: Default::default(),
+            audio,
             sync,
+            send_event_buffer: SendEventBuffer::new(512),
             #[cfg(feature = "gui")]
             editor: Some(editor),
         }
     }
 
-    fn get_bpm_from_host(&self) -> Option<BeatsPerMinute> {
-        // Use TEMPO_VALID constant content as mask directly because
+    /// Returns BPM, whether transport is playing and song position in beats
+    /// (if transport is playing)
+    fn get_time_info_from_host(&self) -> (Option<BeatsPerMinute>, bool, Option<f64>) {
+        // Use TimeInfoFlags constant contents as masks directly because
         // of problems with using TimeInfoFlags
-        let mask = 1 << 10;
+        const TRANSPORT_PLAYING: i32 = 1 << 1;
+        const PPQ_POS_VALID: i32 = 1 << 9;
+        const TEMPO_VALID: i32 = 1 << 10;
 
-        let time_info = self.sync.host?.get_time_info(mask)?;
+        let opt_time_info = self
+            .sync
+            .host
+            .and_then(|host| host.get_time_info(PPQ_POS_VALID | TEMPO_VALID));
 
-        if (time_info.flags & mask) != 0 {
-            Some(BeatsPerMinute(time_info.tempo))
+        let time_info = if let Some(time_info) = opt_time_info {
+            time_info
         } else {
-            None
+            return (None, false, None);
+        };
+
+        let opt_bpm =
+            ((time_info.flags & TEMPO_VALID) != 0).then_some(BeatsPerMinute(time_info.tempo));
+        let playing = (time_info.flags & TRANSPORT_PLAYING) != 0;
+        let opt_song_position =
+            (playing && (time_info.flags & PPQ_POS_VALID) != 0).then_some(time_info.ppq_pos);
+
+        (opt_bpm, playing, opt_song_position)
+    }
+
+    /// Send notes generated by chord memory to host
+    fn send_midi_out_events_to_host(&mut self) {
+        if self.audio.midi_out_events.is_empty() {
+            return;
+        }
+
+        if let Some(mut host) = self.sync.host {
+            let events = self
+                .audio
+                .midi_out_events
+                .pop_iter()
+                .map(|event| MidiEvent {
+                    data: event.midi_data(),
+                    delta_frames: event.sample_index as i32,
+                    live: true,
+                    note_length: None,
+                    note_offset: None,
+                    detune: 0,
+                    note_off_velocity: 0,
+                });
+
+            self.send_event_buffer.send_events(events, &mut host);
+        } else {
+            self.audio.midi_out_events.clear();
         }
     }
 }
@@ -73,16 +124,35 @@ impl Plugin for OctaSine {
         let lefts = l.get_mut(0);
         let rights = r.get_mut(0);
 
-        // VST2 spec does not guarantee that events are sent in order
-        self.audio.sort_note_events();
+        let (opt_bpm, playing, opt_song_position) = self.get_time_info_from_host();
 
-        if let Some(bpm) = self.get_bpm_from_host() {
+        if let Some(bpm) = opt_bpm {
             self.audio.set_bpm(bpm);
         }
 
+        self.audio.enqueue_note_event(NoteEvent {
+            delta_frames: 0,
+            event: NoteEventInner::Transport {
+                playing,
+                song_position: opt_song_position,
+            },
+        });
+
         process_f32_runtime_select(&mut self.audio, lefts, rights, 0, |audio_state| {
             update_audio_parameters(audio_state, &self.sync);
         });
+
+        self.sync.set_lfo_outputs(self.audio.get_lfo_outputs());
+        self.sync.set_held_keys(self.audio.get_held_keys());
+        self.sync
+            .update_modulation_input_peaks(self.audio.take_modulation_input_peaks());
+        self.sync
+            .set_last_midi_event(self.audio.take_last_midi_event());
+        self.sync
+            .set_envelope_positions(self.audio.get_envelope_positions());
+        self.sync.set_clipped(self.audio.take_clipped());
+
+        self.send_midi_out_events_to_host();
     }
 
     fn new(host: HostCallback) -> Self {
@@ -98,6 +168,7 @@ impl Plugin for OctaSine {
             category: Category::Synth,
             inputs: 0,
             outputs: 2,
+            midi_outputs: 1,
             presets: self.sync.patches.num_patches() as i32,
             parameters: self.sync.patches.num_parameters() as i32,
             initial_delay: 0,
@@ -132,11 +203,17 @@ impl Plugin for OctaSine {
             CanDo::ReceiveMidiEvent
             | CanDo::ReceiveTimeInfo
             | CanDo::SendEvents
+            | CanDo::SendMidiEvent
             | CanDo::ReceiveEvents => Supported::Yes,
             _ => Supported::Maybe,
         }
     }
 
+    fn get_tail_size(&self) -> isize {
+        // Zero means "default" in VST2, while one means "no tail"
+        (self.audio.get_tail_length_in_samples() as isize).max(1)
+    }
+
     fn get_parameter_object(&mut self) -> Arc<dyn PluginParameters> {
         Arc::clone(&self.sync) as Arc<dyn PluginParameters>
     }
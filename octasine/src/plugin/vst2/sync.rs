@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 #[cfg(feature = "gui")]
 use std::sync::Arc;
 
@@ -6,9 +6,14 @@ use compact_str::CompactString;
 #[cfg(feature = "gui")]
 use vst::host::Host;
 
-use crate::{parameters::WrappedParameter, sync::SyncState};
 #[cfg(feature = "gui")]
-use crate::{settings::Settings, sync::change_info::MAX_NUM_PARAMETERS};
+use crate::{
+    common::{EnvelopeStage, NUM_OPERATORS},
+    settings::Settings,
+    sync::change_info::MAX_NUM_PARAMETERS,
+    wavetable::Wavetable,
+};
+use crate::{parameters::WrappedParameter, sync::SyncState};
 
 impl vst::plugin::PluginParameters for SyncState<vst::plugin::HostCallback> {
     /// Get parameter label for parameter at `index` (e.g. "db", "sec", "ms", "%").
@@ -26,8 +31,7 @@ impl vst::plugin::PluginParameters for SyncState<vst::plugin::HostCallback> {
 
     /// Get the name of parameter at `index`.
     fn get_parameter_name(&self, index: i32) -> String {
-        self.patches
-            .get_parameter_name(index as usize)
+        self.get_parameter_host_name(index as usize)
             .map(String::from)
             .unwrap_or_else(|| "".to_string())
     }
@@ -54,7 +58,10 @@ impl vst::plugin::PluginParameters for SyncState<vst::plugin::HostCallback> {
 
     /// Return whether parameter at `index` can be automated.
     fn can_be_automated(&self, index: i32) -> bool {
-        (index as usize) < self.patches.num_parameters()
+        self.patches
+            .get_parameter_by_index(index as usize)
+            .map(|p| p.parameter.parameter().is_automatable())
+            .unwrap_or(false)
     }
 
     /// Set the current preset to the index specified by `preset`.
@@ -91,7 +98,7 @@ impl vst::plugin::PluginParameters for SyncState<vst::plugin::HostCallback> {
     /// If `preset_chunks` is set to true in plugin info, this should return the raw chunk data for
     /// the current plugin bank.
     fn get_bank_data(&self) -> Vec<u8> {
-        self.patches.export_fxb_bytes()
+        self.patches.export_fxb_bytes_with_session()
     }
 
     /// If `preset_chunks` is set to true in plugin info, this should load a preset from the given
@@ -179,12 +186,27 @@ impl crate::sync::GuiSyncHandle for Arc<SyncState<vst::plugin::HostCallback>> {
             .format_parameter_value(parameter.index() as usize, value)
             .unwrap() // FIXME: unwrap
     }
+    fn format_parameter_value_as_modulation_target(
+        &self,
+        parameter: WrappedParameter,
+        value: f32,
+    ) -> CompactString {
+        self.patches
+            .format_parameter_value_as_modulation_target(parameter.index() as usize, value)
+            .unwrap_or_default()
+    }
     fn get_patches(&self) -> (usize, Vec<CompactString>) {
         let index = self.patches.get_patch_index();
         let names = self.patches.get_patch_names();
 
         (index, names)
     }
+    fn get_patch_categories(&self) -> Vec<Option<crate::sync::PatchCategory>> {
+        self.patches.get_patch_categories()
+    }
+    fn get_used_patch_slots(&self) -> Vec<bool> {
+        self.patches.get_used_patch_slots()
+    }
     fn set_patch_index(&self, index: usize) {
         self.patches.set_patch_index(index);
 
@@ -202,6 +224,25 @@ impl crate::sync::GuiSyncHandle for Arc<SyncState<vst::plugin::HostCallback>> {
             host.update_display();
         }
     }
+    fn get_current_patch_metadata(&self) -> crate::sync::PatchMetadata {
+        self.patches.get_current_patch_metadata()
+    }
+    fn set_current_patch_metadata(&self, metadata: crate::sync::PatchMetadata) {
+        self.patches.set_current_patch_metadata(metadata);
+    }
+    fn get_wavetable_name(&self, operator_index: usize) -> Option<CompactString> {
+        self.patches.get_wavetable_name(operator_index)
+    }
+    fn set_wavetable(&self, operator_index: usize, wavetable: Option<Arc<Wavetable>>) {
+        self.patches.set_wavetable(operator_index, wavetable);
+
+        if let Some(host) = self.host {
+            host.update_display();
+        }
+    }
+    fn store_mod_snapshot(&self, slot_index: usize) {
+        self.patches.store_mod_snapshot(slot_index);
+    }
     fn get_changed_parameters(&self) -> Option<[Option<f32>; MAX_NUM_PARAMETERS]> {
         self.patches.get_changed_parameters_from_gui()
     }
@@ -211,6 +252,63 @@ impl crate::sync::GuiSyncHandle for Arc<SyncState<vst::plugin::HostCallback>> {
     fn get_gui_settings(&self) -> crate::gui::GuiSettings {
         Settings::load_or_default().gui
     }
+    fn get_session_state(&self) -> Option<crate::sync::SessionState> {
+        self.patches.get_session_state()
+    }
+    fn set_session_state(&self, state: crate::sync::SessionState) {
+        self.patches.set_session_state(state);
+    }
+    fn get_held_keys(&self) -> u128 {
+        SyncState::get_held_keys(self)
+    }
+    fn take_modulation_input_peaks(&self) -> [f32; NUM_OPERATORS] {
+        SyncState::take_modulation_input_peaks(self)
+    }
+    fn take_last_midi_event(&self) -> Option<[u8; 3]> {
+        SyncState::take_last_midi_event(self)
+    }
+    fn get_envelope_position(&self, operator_index: usize) -> Option<(EnvelopeStage, f32)> {
+        SyncState::get_envelope_position(self, operator_index)
+    }
+    fn take_clipped(&self) -> bool {
+        SyncState::take_clipped(self)
+    }
+    fn set_lfos_disabled(&self, disabled: bool) {
+        SyncState::set_lfos_disabled(self, disabled)
+    }
+    fn get_lfos_disabled(&self) -> bool {
+        SyncState::get_lfos_disabled(self)
+    }
+    fn set_auto_gain(&self, enabled: bool) {
+        SyncState::set_auto_gain(self, enabled)
+    }
+    fn get_auto_gain(&self) -> bool {
+        SyncState::get_auto_gain(self)
+    }
+    fn set_mono_fold_down(&self, enabled: bool) {
+        SyncState::set_mono_fold_down(self, enabled)
+    }
+    fn get_mono_fold_down(&self) -> bool {
+        SyncState::get_mono_fold_down(self)
+    }
+    fn set_listen_operator(&self, opt_operator_index: Option<usize>) {
+        SyncState::set_listen_operator(self, opt_operator_index)
+    }
+    fn get_listen_operator(&self) -> Option<usize> {
+        SyncState::get_listen_operator(self)
+    }
+    fn request_audition(&self, key: u8, velocity: u8, duration: f64) {
+        SyncState::request_audition(self, key, velocity, duration);
+    }
+    fn set_gui_key(&self, key: u8, pressed: bool) {
+        SyncState::set_gui_key(self, key, pressed);
+    }
+    fn request_panic(&self) {
+        SyncState::request_panic(self);
+    }
+    fn set_stuck_note_timeout(&self, opt_timeout: Option<f64>) {
+        SyncState::set_stuck_note_timeout(self, opt_timeout);
+    }
     fn export_patch(&self) -> (CompactString, Vec<u8>) {
         let name = self.patches.get_current_patch().get_fxp_filename();
         let data = self.patches.get_current_patch().export_fxp_bytes();
@@ -220,17 +318,91 @@ impl crate::sync::GuiSyncHandle for Arc<SyncState<vst::plugin::HostCallback>> {
     fn export_bank(&self) -> Vec<u8> {
         self.patches.export_fxb_bytes()
     }
-    fn import_bank_or_patches_from_paths(&self, paths: &[PathBuf]) {
-        self.patches.import_bank_or_patches_from_paths(paths);
+    fn import_bank_or_patches_from_paths(
+        &self,
+        paths: &[PathBuf],
+        target: crate::sync::PatchImportTarget,
+    ) {
+        self.patches
+            .import_bank_or_patches_from_paths(paths, target);
 
         if let Some(host) = self.host {
             host.update_display();
         }
     }
+    fn start_patch_preview(&self, path: &Path) {
+        if let Err(err) = self.patches.start_patch_preview(path) {
+            ::log::error!("failed previewing patch: {:#}", err);
+        }
+    }
+    fn end_patch_preview(&self) {
+        self.patches.end_patch_preview();
+    }
     fn clear_patch(&self) {
         self.patches.clear_current_patch();
+
+        // Make host fetch reset patch name
+        if let Some(host) = self.host {
+            host.update_display();
+        }
     }
     fn clear_bank(&self) {
         self.patches.clear_bank();
+
+        // Make host fetch reset patch names
+        if let Some(host) = self.host {
+            host.update_display();
+        }
+    }
+    fn randomize_parameters(&self, scope: crate::sync::RandomizeScope) {
+        self.patches.randomize_parameters(scope);
+
+        // Make host fetch new parameter values
+        if let Some(host) = self.host {
+            host.update_display();
+        }
+    }
+    fn mutate_parameters(&self, scope: crate::sync::RandomizeScope, strength: f32) {
+        self.patches.mutate_parameters(scope, strength);
+
+        // Make host fetch new parameter values
+        if let Some(host) = self.host {
+            host.update_display();
+        }
+    }
+    fn get_parameter_locks(&self) -> ::std::sync::Arc<crate::sync::ParameterLocks> {
+        self.patches.get_parameter_locks()
+    }
+    fn breed_patches(
+        &self,
+        parent_indices: [usize; 2],
+        num_children: usize,
+        mutation_strength: Option<f32>,
+    ) -> Vec<usize> {
+        let child_indices =
+            self.patches
+                .breed_patches(parent_indices, num_children, mutation_strength);
+
+        // Make host fetch new patch names
+        if let Some(host) = self.host {
+            host.update_display();
+        }
+
+        child_indices
+    }
+    fn have_unsaved_changes(&self) -> bool {
+        self.patches.have_unsaved_changes()
+    }
+    fn mark_bank_as_saved(&self) {
+        self.patches.mark_as_saved();
+    }
+    fn is_capturing_parameter_changes(&self) -> bool {
+        self.patches.is_capturing_parameter_changes()
+    }
+    fn start_parameter_change_capture(&self) {
+        self.patches.start_parameter_change_capture();
+    }
+    fn stop_parameter_change_capture(&self) -> Option<crate::sync::ParameterChangeLog> {
+        self.patches.stop_parameter_change_capture()
     }
 }
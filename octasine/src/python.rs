@@ -0,0 +1,195 @@
+//! Python module for offline rendering and analysis
+//!
+//! Build with the `python` feature (and without default features), e.g.,
+//! with maturin. Example:
+//!
+//! ```python
+//! import octasine
+//!
+//! synth = octasine.Synth(44100.0)
+//! synth.load_patch_or_bank("bell.fxp")
+//! synth.set_parameter_text("OP 2 mod out", "2.5")
+//! synth.schedule_midi(0, [0x90, 60, 100])
+//! synth.schedule_midi(44100, [0x80, 60, 0])
+//! lefts, rights = synth.render(88200)
+//! ```
+
+use std::collections::VecDeque;
+use std::path::PathBuf;
+
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+
+use crate::synth::Synth;
+
+/// MIDI message scheduled at absolute time in samples
+struct ScheduledMidi {
+    time: u64,
+    data: [u8; 3],
+}
+
+#[pyclass(name = "Synth", unsendable)]
+struct PySynth {
+    synth: Synth,
+    /// Number of samples rendered so far
+    time: u64,
+    /// Scheduled MIDI messages sorted by time
+    scheduled_midi: VecDeque<ScheduledMidi>,
+}
+
+#[pymethods]
+impl PySynth {
+    #[new]
+    fn new(sample_rate: f64) -> Self {
+        Self {
+            synth: Synth::new(sample_rate),
+            time: 0,
+            scheduled_midi: Default::default(),
+        }
+    }
+
+    /// Load patch bank (.fxb) or patch (.fxp) file. Patches are loaded into
+    /// current patch slot.
+    fn load_patch_or_bank(&self, path: PathBuf) -> PyResult<()> {
+        let bytes = ::std::fs::read(&path).map_err(|err| PyIOError::new_err(err.to_string()))?;
+
+        if path.extension().and_then(|s| s.to_str()) == Some("fxb") {
+            self.synth
+                .patches()
+                .import_bank_from_bytes(&bytes)
+                .map_err(|err| PyValueError::new_err(format!("{:#}", err)))
+        } else {
+            self.synth.patches().import_bytes_into_current_patch(&bytes);
+
+            Ok(())
+        }
+    }
+
+    /// Render on given number of threads (see Synth::set_render_threads)
+    fn set_render_threads(&mut self, num_threads: usize) {
+        self.synth.set_render_threads(num_threads);
+    }
+
+    fn set_patch_index(&self, index: usize) -> PyResult<()> {
+        if index < self.synth.patches().num_patches() {
+            self.synth.patches().set_patch_index(index);
+
+            Ok(())
+        } else {
+            Err(PyValueError::new_err("patch index out of range"))
+        }
+    }
+
+    fn parameter_names(&self) -> Vec<String> {
+        (0..self.synth.num_parameters())
+            .filter_map(|index| self.synth.patches().get_parameter_name(index))
+            .map(|name| name.to_string())
+            .collect()
+    }
+
+    /// Get parameter value in range 0.0 to 1.0
+    fn get_parameter(&self, name: &str) -> PyResult<f32> {
+        let index = self.get_parameter_index(name)?;
+
+        Ok(self.synth.get_parameter(index).unwrap_or_default())
+    }
+
+    fn get_parameter_text(&self, name: &str) -> PyResult<String> {
+        let index = self.get_parameter_index(name)?;
+
+        Ok(self
+            .synth
+            .patches()
+            .get_parameter_value_text(index)
+            .map(|text| text.to_string())
+            .unwrap_or_default())
+    }
+
+    /// Set parameter value in range 0.0 to 1.0
+    fn set_parameter(&mut self, name: &str, value: f32) -> PyResult<()> {
+        let index = self.get_parameter_index(name)?;
+
+        self.synth.set_parameter(index, value);
+
+        Ok(())
+    }
+
+    fn set_parameter_text(&mut self, name: &str, text: &str) -> PyResult<()> {
+        let index = self.get_parameter_index(name)?;
+
+        if self.synth.set_parameter_from_text(index, text) {
+            Ok(())
+        } else {
+            Err(PyValueError::new_err(format!(
+                "invalid value for parameter {}: {}",
+                name, text
+            )))
+        }
+    }
+
+    /// Schedule MIDI message of one to three bytes at time in samples since
+    /// synth creation
+    fn schedule_midi(&mut self, time: u64, data: Vec<u8>) -> PyResult<()> {
+        if !(1..=3).contains(&data.len()) {
+            return Err(PyValueError::new_err("MIDI message must be 1-3 bytes"));
+        }
+        if time < self.time {
+            return Err(PyValueError::new_err("time has already been rendered"));
+        }
+
+        let mut message = [0u8; 3];
+
+        message[..data.len()].copy_from_slice(&data);
+
+        let position = self.scheduled_midi.partition_point(|m| m.time <= time);
+
+        self.scheduled_midi.insert(
+            position,
+            ScheduledMidi {
+                time,
+                data: message,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Render given number of samples, returning left and right channels
+    fn render(&mut self, num_samples: usize) -> (Vec<f32>, Vec<f32>) {
+        let end = self.time + num_samples as u64;
+
+        while let Some(midi) = self.scheduled_midi.front() {
+            if midi.time >= end {
+                break;
+            }
+
+            self.synth
+                .send_midi(midi.data, (midi.time - self.time) as u32);
+            self.scheduled_midi.pop_front();
+        }
+
+        let mut lefts = vec![0.0; num_samples];
+        let mut rights = vec![0.0; num_samples];
+
+        self.synth.render(&mut lefts, &mut rights);
+
+        self.time = end;
+
+        (lefts, rights)
+    }
+}
+
+impl PySynth {
+    fn get_parameter_index(&self, name: &str) -> PyResult<usize> {
+        self.synth
+            .get_parameter_index(name)
+            .ok_or_else(|| PyValueError::new_err(format!("no parameter with name: {}", name)))
+    }
+}
+
+#[pymodule]
+fn octasine(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PySynth>()?;
+
+    Ok(())
+}
@@ -0,0 +1,169 @@
+//! Patch generation from Rhai scripts
+//!
+//! Scripts operate on a fresh patch bank through the following functions:
+//!
+//! - `num_patches()`: number of patches in bank
+//! - `select_patch(index)`: make patch with given index the current one
+//! - `set_patch_name(name)`: rename current patch
+//! - `set_parameter(name, text)`: set parameter of current patch from text,
+//!   e.g., `set_parameter("OP 2 mod out", "1.5")`
+//! - `set_parameter_value(name, value)`: set parameter of current patch from
+//!   patch value in range 0.0 to 1.0
+//! - `get_parameter(name)`: get parameter value of current patch as text
+//! - `random()`: random number in range 0.0 to 1.0
+//!
+//! Parameter names are the same as the ones displayed by hosts.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::Context;
+use rhai::{Engine, EvalAltResult};
+
+use crate::sync::PatchBank;
+
+/// Run script and return generated patch bank
+pub fn generate_patch_bank_from_script(script: &str) -> anyhow::Result<PatchBank> {
+    let bank = Arc::new(PatchBank::default());
+
+    {
+        let engine = create_engine(&bank);
+
+        engine
+            .run(script)
+            .map_err(|err| anyhow::anyhow!("run script: {}", err))?;
+    }
+
+    let bank = Arc::try_unwrap(bank)
+        .map_err(|_| anyhow::anyhow!("patch bank still referenced by script engine"))?;
+
+    bank.set_patch_index(0);
+
+    Ok(bank)
+}
+
+pub fn generate_patch_bank_from_script_file(path: &Path) -> anyhow::Result<PatchBank> {
+    let script = ::std::fs::read_to_string(path)
+        .with_context(|| format!("read script file {}", path.display()))?;
+
+    generate_patch_bank_from_script(&script)
+}
+
+fn create_engine(bank: &Arc<PatchBank>) -> Engine {
+    let mut engine = Engine::new();
+
+    {
+        let bank = bank.clone();
+
+        engine.register_fn("num_patches", move || bank.num_patches() as i64);
+    }
+    {
+        let bank = bank.clone();
+
+        engine.register_fn(
+            "select_patch",
+            move |index: i64| -> Result<(), Box<EvalAltResult>> {
+                if (0..bank.num_patches() as i64).contains(&index) {
+                    bank.set_patch_index(index as usize);
+
+                    Ok(())
+                } else {
+                    Err(format!("patch index out of range: {}", index).into())
+                }
+            },
+        );
+    }
+    {
+        let bank = bank.clone();
+
+        engine.register_fn("set_patch_name", move |name: &str| {
+            bank.set_patch_name(name);
+        });
+    }
+    {
+        let bank = bank.clone();
+
+        engine.register_fn(
+            "set_parameter",
+            move |name: &str, text: &str| -> Result<(), Box<EvalAltResult>> {
+                let index = get_parameter_index(&bank, name)?;
+
+                if bank.set_parameter_text_from_host(index, text) {
+                    Ok(())
+                } else {
+                    Err(format!("invalid value for parameter {}: {}", name, text).into())
+                }
+            },
+        );
+    }
+    {
+        let bank = bank.clone();
+
+        engine.register_fn(
+            "set_parameter_value",
+            move |name: &str, value: f64| -> Result<(), Box<EvalAltResult>> {
+                let index = get_parameter_index(&bank, name)?;
+
+                bank.set_parameter_from_host(index, (value as f32).clamp(0.0, 1.0));
+
+                Ok(())
+            },
+        );
+    }
+    {
+        let bank = bank.clone();
+
+        engine.register_fn(
+            "get_parameter",
+            move |name: &str| -> Result<String, Box<EvalAltResult>> {
+                let index = get_parameter_index(&bank, name)?;
+
+                Ok(bank
+                    .get_parameter_value_text(index)
+                    .map(|text| text.to_string())
+                    .unwrap_or_default())
+            },
+        );
+    }
+
+    engine.register_fn("random", fastrand::f64);
+
+    engine
+}
+
+fn get_parameter_index(bank: &PatchBank, name: &str) -> Result<usize, Box<EvalAltResult>> {
+    bank.get_current_patch()
+        .parameters
+        .values()
+        .position(|p| p.name == name)
+        .ok_or_else(|| format!("no parameter with name: {}", name).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_patch_bank_from_script() {
+        let script = r#"
+            for i in 0..4 {
+                select_patch(i);
+                set_patch_name("Generated " + (i + 1));
+                set_parameter("OP 2 mod out", "" + (i + 1));
+            }
+        "#;
+
+        let bank = generate_patch_bank_from_script(script).unwrap();
+
+        assert_eq!(bank.get_patch_index(), 0);
+        assert_eq!(bank.get_patch_name(3).unwrap(), "004: Generated 4");
+
+        bank.set_patch_index(3);
+
+        let index = get_parameter_index(&bank, "OP 2 mod out").unwrap();
+
+        assert_eq!(bank.get_parameter_value_text(index).unwrap(), "4.0000");
+
+        assert!(generate_patch_bank_from_script(r#"set_parameter("Foo", "1")"#).is_err());
+    }
+}
@@ -9,6 +9,30 @@ pub struct Settings {
     pub schema_version: usize,
     #[cfg(feature = "gui")]
     pub gui: super::gui::GuiSettings,
+    /// Patch bank or patch file loaded into new instances instead of
+    /// built-in patches
+    #[serde(default)]
+    pub startup_patches: Option<PathBuf>,
+    /// Number of samples between LFO updates at 48 kHz or lower, scaled up
+    /// at higher sample rates. Higher values reduce CPU usage at the cost of
+    /// modulation smoothness.
+    #[serde(default = "default_control_interval")]
+    pub control_interval: usize,
+    /// Preview note played when selecting patch in GUI
+    #[serde(default)]
+    pub patch_audition: PatchAuditionSettings,
+    /// Automatic release of keys held implausibly long while host
+    /// transport is stopped
+    #[serde(default)]
+    pub stuck_note_watchdog: StuckNoteWatchdogSettings,
+    /// Present parameter names of at most eight characters to hosts, for
+    /// hosts that truncate longer names
+    #[serde(default)]
+    pub short_parameter_names: bool,
+}
+
+fn default_control_interval() -> usize {
+    1
 }
 
 impl Default for Settings {
@@ -17,10 +41,62 @@ impl Default for Settings {
             schema_version: 1,
             #[cfg(feature = "gui")]
             gui: Default::default(),
+            startup_patches: None,
+            control_interval: default_control_interval(),
+            patch_audition: Default::default(),
+            stuck_note_watchdog: Default::default(),
+            short_parameter_names: false,
         }
     }
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PatchAuditionSettings {
+    pub enabled: bool,
+    /// MIDI key
+    pub key: u8,
+    /// MIDI velocity
+    pub velocity: u8,
+    /// Duration in seconds before note is released
+    pub duration: f64,
+}
+
+impl Default for PatchAuditionSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            key: 60,
+            velocity: 100,
+            duration: 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StuckNoteWatchdogSettings {
+    pub enabled: bool,
+    /// Duration in seconds after which held keys are released
+    pub timeout: f64,
+}
+
+impl Default for StuckNoteWatchdogSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            timeout: 120.0,
+        }
+    }
+}
+
+impl StuckNoteWatchdogSettings {
+    /// Timeout in seconds if watchdog is enabled
+    pub fn get_timeout(&self) -> Option<f64> {
+        self.enabled.then_some(self.timeout)
+    }
+}
+
 impl Settings {
     fn get_config_file_path() -> anyhow::Result<PathBuf> {
         get_file_storage_dir().map(|path| path.join("OctaSine.json"))
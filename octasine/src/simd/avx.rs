@@ -26,6 +26,38 @@ impl AvxPackedDouble {
             _mm256_round_pd::<{ TRUNCATE }>(self.0),
         ))
     }
+
+    /// Get phase in range 0.0 to 1.0 and phase increment clamped to 0.5,
+    /// for use in band-limited waveforms
+    #[target_feature(enable = "avx")]
+    #[inline]
+    unsafe fn band_limited_phase(self, phase_increment: Self) -> (__m256d, __m256d) {
+        let t = self - self.floor();
+        let dt = _mm256_min_pd(phase_increment.abs().0, _mm256_set1_pd(0.5));
+
+        (t.0, dt)
+    }
+
+    /// See [crate::math::wave::poly_blep]
+    #[target_feature(enable = "avx")]
+    #[inline]
+    unsafe fn poly_blep(t: __m256d, dt: __m256d) -> __m256d {
+        let one = _mm256_set1_pd(1.0);
+
+        let a = _mm256_div_pd(t, dt);
+        let low = _mm256_sub_pd(_mm256_sub_pd(_mm256_add_pd(a, a), _mm256_mul_pd(a, a)), one);
+
+        let b = _mm256_div_pd(_mm256_sub_pd(t, one), dt);
+        let high = _mm256_add_pd(_mm256_add_pd(_mm256_add_pd(_mm256_mul_pd(b, b), b), b), one);
+
+        let y = _mm256_blendv_pd(
+            _mm256_setzero_pd(),
+            high,
+            _mm256_cmp_pd::<{ _CMP_GT_OQ }>(t, _mm256_sub_pd(one, dt)),
+        );
+
+        _mm256_blendv_pd(y, low, _mm256_cmp_pd::<{ _CMP_LT_OQ }>(t, dt))
+    }
 }
 
 impl SimdPackedDouble for AvxPackedDouble {
@@ -192,6 +224,34 @@ impl SimdPackedDouble for AvxPackedDouble {
             _mm256_sub_pd(y, _mm256_set1_pd(0.5)),
         ))
     }
+    #[target_feature(enable = "avx")]
+    #[inline]
+    unsafe fn saw_band_limited(self, phase_increment: Self) -> Self {
+        let (t, dt) = self.band_limited_phase(phase_increment);
+
+        let naive = _mm256_sub_pd(_mm256_mul_pd(t, _mm256_set1_pd(2.0)), _mm256_set1_pd(1.0));
+
+        Self(_mm256_sub_pd(naive, Self::poly_blep(t, dt)))
+    }
+    #[target_feature(enable = "avx")]
+    #[inline]
+    unsafe fn square_band_limited(self, phase_increment: Self) -> Self {
+        let (t, dt) = self.band_limited_phase(phase_increment);
+
+        let t_half = Self(_mm256_add_pd(t, _mm256_set1_pd(0.5)));
+        let t_half = (t_half - t_half.floor()).0;
+
+        let naive = _mm256_blendv_pd(
+            _mm256_set1_pd(-1.0),
+            _mm256_set1_pd(1.0),
+            _mm256_cmp_pd::<{ _CMP_LT_OQ }>(t, _mm256_set1_pd(0.5)),
+        );
+
+        Self(_mm256_sub_pd(
+            _mm256_add_pd(naive, Self::poly_blep(t, dt)),
+            Self::poly_blep(t_half, dt),
+        ))
+    }
 }
 
 impl Add for AvxPackedDouble {
@@ -1,4 +1,4 @@
-use crate::math::wave::{saw, square, triangle};
+use crate::math::wave::{saw, saw_band_limited, square, square_band_limited, triangle};
 
 use super::{Simd, SimdPackedDouble};
 
@@ -98,6 +98,22 @@ impl SimdPackedDouble for FallbackPackedDouble {
     unsafe fn saw(self) -> Self {
         Self(apply_to_arrays!(saw, self.0))
     }
+    #[inline(always)]
+    unsafe fn saw_band_limited(self, phase_increment: Self) -> Self {
+        Self(apply_to_arrays!(
+            saw_band_limited,
+            self.0,
+            phase_increment.0
+        ))
+    }
+    #[inline(always)]
+    unsafe fn square_band_limited(self, phase_increment: Self) -> Self {
+        Self(apply_to_arrays!(
+            square_band_limited,
+            self.0,
+            phase_increment.0
+        ))
+    }
 }
 
 impl Add for FallbackPackedDouble {
@@ -43,6 +43,10 @@ pub trait SimdPackedDouble: Copy + Add + AddAssign + Sub + Mul {
     unsafe fn triangle(self) -> Self;
     unsafe fn square(self) -> Self;
     unsafe fn saw(self) -> Self;
+    /// Saw wave with discontinuity smoothed according to phase increment
+    unsafe fn saw_band_limited(self, phase_increment: Self) -> Self;
+    /// Square wave with discontinuities smoothed according to phase increment
+    unsafe fn square_band_limited(self, phase_increment: Self) -> Self;
 }
 
 #[cfg(test)]
@@ -91,7 +95,64 @@ mod tests {
         };
     }
 
+    macro_rules! band_limited_wave_test {
+        ($name:ident, $wave_fn:ident) => {
+            #[cfg(target_arch = "x86_64")]
+            #[test]
+            fn $name() {
+                use quickcheck::{quickcheck, TestResult};
+
+                use crate::simd::SimdPackedDouble;
+
+                assert!(is_x86_feature_detected!("avx"));
+
+                fn prop(x: f64, dt: f64) -> TestResult {
+                    if x.is_infinite() || x.is_nan() || dt.is_infinite() || dt.is_nan() {
+                        return TestResult::discard();
+                    }
+
+                    let fallback = unsafe {
+                        super::FallbackPackedDouble::new(x)
+                            .$wave_fn(super::FallbackPackedDouble::new(dt))
+                            .to_arr()
+                    };
+                    let sse2 = unsafe {
+                        super::Sse2PackedDouble::new(x)
+                            .$wave_fn(super::Sse2PackedDouble::new(dt))
+                            .to_arr()
+                    };
+                    let avx = unsafe {
+                        super::AvxPackedDouble::new(x)
+                            .$wave_fn(super::AvxPackedDouble::new(dt))
+                            .to_arr()
+                    };
+
+                    let mut all = fallback.to_vec();
+
+                    all.extend_from_slice(&sse2[..]);
+                    all.extend_from_slice(&avx[..]);
+
+                    let first = *all.get(0).unwrap();
+
+                    for y in all.into_iter() {
+                        if y != first {
+                            dbg!(x, dt, fallback, sse2, avx);
+
+                            return TestResult::failed();
+                        }
+                    }
+
+                    TestResult::passed()
+                }
+
+                quickcheck(prop as fn(f64, f64) -> TestResult);
+            }
+        };
+    }
+
     wave_test!(test_triangle, triangle);
     wave_test!(test_square, square);
     wave_test!(test_saw, saw);
+    band_limited_wave_test!(test_saw_band_limited, saw_band_limited);
+    band_limited_wave_test!(test_square_band_limited, square_band_limited);
 }
@@ -151,6 +151,28 @@ impl SimdPackedDouble for Sse2PackedDouble {
                 .to_arr(),
         )
     }
+    #[inline(always)]
+    unsafe fn saw_band_limited(self, phase_increment: Self) -> Self {
+        // Scalar workaround due to lack of blend instructions
+        Self::from_arr(
+            super::FallbackPackedDouble::from_arr(self.to_arr())
+                .saw_band_limited(super::FallbackPackedDouble::from_arr(
+                    phase_increment.to_arr(),
+                ))
+                .to_arr(),
+        )
+    }
+    #[inline(always)]
+    unsafe fn square_band_limited(self, phase_increment: Self) -> Self {
+        // Scalar workaround due to lack of blend instructions
+        Self::from_arr(
+            super::FallbackPackedDouble::from_arr(self.to_arr())
+                .square_band_limited(super::FallbackPackedDouble::from_arr(
+                    phase_increment.to_arr(),
+                ))
+                .to_arr(),
+        )
+    }
 }
 
 impl Add for Sse2PackedDouble {
@@ -0,0 +1,75 @@
+use std::fmt::Write;
+use std::time::Instant;
+
+use compact_str::CompactString;
+use serde::Serialize;
+
+/// Parameter change with time in seconds since capture was started
+#[derive(Debug, Clone, Serialize)]
+pub struct ParameterChange {
+    pub time: f64,
+    pub parameter: CompactString,
+    pub value: f32,
+    pub text: CompactString,
+}
+
+/// In-memory capture of parameter changes, exportable as CSV or JSON
+#[derive(Debug, Clone)]
+pub struct ParameterChangeLog {
+    start: Instant,
+    changes: Vec<ParameterChange>,
+}
+
+impl Default for ParameterChangeLog {
+    fn default() -> Self {
+        Self {
+            start: Instant::now(),
+            changes: Vec::new(),
+        }
+    }
+}
+
+impl ParameterChangeLog {
+    pub fn push(&mut self, parameter: CompactString, value: f32, text: CompactString) {
+        self.changes.push(ParameterChange {
+            time: self.start.elapsed().as_secs_f64(),
+            parameter,
+            value,
+            text,
+        });
+    }
+
+    pub fn changes(&self) -> &[ParameterChange] {
+        &self.changes
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("time,parameter,value,text\n");
+
+        for change in self.changes.iter() {
+            writeln!(
+                csv,
+                "{:.6},{},{},{}",
+                change.time,
+                escape_csv_field(&change.parameter),
+                change.value,
+                escape_csv_field(&change.text)
+            )
+            .unwrap();
+        }
+
+        csv
+    }
+
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        Ok(serde_json::to_string_pretty(&self.changes)?)
+    }
+}
+
+fn escape_csv_field(field: &str) -> CompactString {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\"")).into()
+    } else {
+        field.into()
+    }
+}
@@ -0,0 +1,70 @@
+//! Factory patches, embedded as JSON
+
+use anyhow::Context;
+use compact_str::CompactString;
+use serde::Deserialize;
+
+use crate::common::IndexMap;
+
+use super::patch_bank::{Patch, PatchCategory};
+
+const FACTORY_PATCHES_JSON: &str = include_str!("factory_patches.json");
+
+#[derive(Deserialize)]
+pub struct FactoryPatch {
+    name: CompactString,
+    category: PatchCategory,
+    /// Parameter values as text, keyed by parameter name. Parameters that
+    /// are not included keep their default values.
+    parameters: IndexMap<CompactString, CompactString>,
+}
+
+impl FactoryPatch {
+    /// Set patch name, category and parameter values. Patch is expected to
+    /// contain default values.
+    pub fn apply(&self, patch: &Patch) -> anyhow::Result<()> {
+        patch.set_name(&self.name);
+        patch.set_category(Some(self.category));
+
+        for (name, text) in self.parameters.iter() {
+            let parameter = patch
+                .parameters
+                .values()
+                .find(|p| p.name == name)
+                .with_context(|| format!("{}: no parameter named {}", self.name, name))?;
+
+            if !parameter.set_from_text(text) {
+                anyhow::bail!("{}: invalid value {} for {}", self.name, text, name);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub fn get_factory_patches() -> Vec<FactoryPatch> {
+    match serde_json::from_str(FACTORY_PATCHES_JSON) {
+        Ok(patches) => patches,
+        Err(err) => {
+            ::log::error!("Failed parsing factory patches: {:#}", err);
+
+            Vec::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_factory_patches_apply() {
+        let factory_patches = get_factory_patches();
+
+        assert!(!factory_patches.is_empty());
+
+        for factory_patch in factory_patches {
+            factory_patch.apply(&Patch::default()).unwrap();
+        }
+    }
+}
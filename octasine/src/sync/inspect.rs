@@ -0,0 +1,237 @@
+//! Summaries of patch and bank files with warnings about suspicious values,
+//! e.g., for maintainers of preset packs
+
+use std::path::Path;
+
+use anyhow::Context;
+use compact_str::CompactString;
+use semver::Version;
+
+use crate::common::{IndexMap, NUM_OPERATORS};
+use crate::parameters::{
+    MasterParameter, MasterVolumeValue, OperatorActiveValue, OperatorMixOutValue,
+    OperatorParameter, OperatorVolumeValue, Parameter, ParameterKey, ParameterValue,
+};
+
+use super::parameters::PatchParameter;
+use super::patch_bank::{Patch, PatchBank, PatchCategory, PatchMetadata};
+use super::serde::get_format_info;
+
+pub struct FileInspection {
+    /// Serialization format version (1 or 2)
+    pub format_version: u8,
+    /// OctaSine version that file was saved with
+    pub octasine_version: Version,
+    /// Bank slot index and inspection of each non-default patch
+    pub patches: Vec<(usize, PatchInspection)>,
+}
+
+pub struct PatchInspection {
+    pub name: String,
+    pub category: Option<PatchCategory>,
+    pub metadata: PatchMetadata,
+    pub parameters: Vec<ParameterInspection>,
+    /// Operator index and name of loaded wavetables
+    pub wavetables: Vec<(usize, CompactString)>,
+    pub warnings: Vec<String>,
+}
+
+pub struct ParameterInspection {
+    pub parameter: Parameter,
+    pub name: CompactString,
+    /// Value in patch format (0.0 to 1.0)
+    pub patch_value: f32,
+    pub value: CompactString,
+    pub is_default: bool,
+}
+
+/// Inspect patch (.fxp) or patch bank (.fxb) file
+pub fn inspect_file(path: &Path) -> anyhow::Result<FileInspection> {
+    let bytes = ::std::fs::read(path).with_context(|| format!("read file {}", path.display()))?;
+    let is_bank = path.extension().and_then(|s| s.to_str()) == Some("fxb");
+
+    let (format_version, octasine_version) =
+        get_format_info(&bytes, is_bank).context("parse file")?;
+
+    let bank = PatchBank::default();
+    let default_parameters = PatchParameter::all();
+
+    let patches = if is_bank {
+        bank.import_bank_from_bytes(&bytes)?;
+
+        bank.patches
+            .iter()
+            .enumerate()
+            .filter(|(_, patch)| !patch.is_default(&default_parameters))
+            .map(|(index, patch)| (index, inspect_patch(patch, &default_parameters)))
+            .collect()
+    } else {
+        let patch = &bank.patches[0];
+
+        patch.update_from_bytes(&bytes)?;
+
+        vec![(0, inspect_patch(patch, &default_parameters))]
+    };
+
+    Ok(FileInspection {
+        format_version,
+        octasine_version,
+        patches,
+    })
+}
+
+/// Inspect patch in fxp format
+pub fn inspect_patch_bytes(bytes: &[u8]) -> anyhow::Result<PatchInspection> {
+    let bank = PatchBank::default();
+    let patch = &bank.patches[0];
+
+    patch.update_from_bytes(bytes)?;
+
+    Ok(inspect_patch(patch, &PatchParameter::all()))
+}
+
+pub fn inspect_patch(
+    patch: &Patch,
+    default_parameters: &IndexMap<ParameterKey, PatchParameter>,
+) -> PatchInspection {
+    let parameters = patch
+        .parameters
+        .values()
+        .zip(default_parameters.values())
+        .filter(|(p, _)| p.parameter.parameter() != Parameter::Master(MasterParameter::PatchSelect))
+        .map(|(p, default)| ParameterInspection {
+            parameter: p.parameter.parameter(),
+            name: p.name.clone(),
+            patch_value: p.get_value(),
+            value: p.get_value_text(),
+            is_default: p.get_value() == default.get_value(),
+        })
+        .collect();
+
+    let wavetables = (0..NUM_OPERATORS)
+        .filter_map(|i| patch.get_wavetable(i).map(|w| (i, w.name.clone())))
+        .collect();
+
+    PatchInspection {
+        name: patch.get_name(),
+        category: patch.get_category(),
+        metadata: patch.get_metadata(),
+        parameters,
+        wavetables,
+        warnings: lint_patch(patch),
+    }
+}
+
+/// Warn about values that are likely mistakes
+fn lint_patch(patch: &Patch) -> Vec<String> {
+    let get = |parameter: Parameter| {
+        patch
+            .parameters
+            .get(&parameter.key())
+            .map(|p| p.get_value())
+            .unwrap_or_default()
+    };
+    let get_operator = |index: usize, parameter| get(Parameter::Operator(index as u8, parameter));
+
+    let mut warnings = Vec::new();
+
+    let master_volume =
+        MasterVolumeValue::new_from_patch(get(Parameter::Master(MasterParameter::Volume))).get();
+
+    if master_volume == 0.0 {
+        warnings.push("master volume is zero".into());
+    }
+
+    let audible_operators = (0..NUM_OPERATORS).filter(|i| {
+        let active =
+            OperatorActiveValue::new_from_patch(get_operator(*i, OperatorParameter::Active)).get();
+        let volume =
+            OperatorVolumeValue::new_from_patch(get_operator(*i, OperatorParameter::Volume)).get();
+        let mix_out =
+            OperatorMixOutValue::new_from_patch(get_operator(*i, OperatorParameter::MixOut)).get();
+
+        active > 0.0 && volume > 0.0 && mix_out > 0.0
+    });
+
+    if audible_operators.count() == 0 {
+        warnings.push("patch is silent: no active operator has non-zero volume and mix".into());
+    }
+
+    for index in 0..NUM_OPERATORS {
+        let envelope_is_zero = [
+            OperatorParameter::AttackDuration,
+            OperatorParameter::DecayDuration,
+            OperatorParameter::SustainVolume,
+            OperatorParameter::ReleaseDuration,
+        ]
+        .into_iter()
+        .all(|p| get_operator(index, p) == 0.0);
+
+        if envelope_is_zero {
+            warnings.push(format!(
+                "operator {} envelope has minimum durations and zero sustain",
+                index + 1
+            ));
+        }
+    }
+
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lint_patch() {
+        let bank = PatchBank::default();
+        let patch = &bank.patches[0];
+
+        assert!(lint_patch(patch).is_empty());
+
+        for index in 0..NUM_OPERATORS {
+            for parameter in [
+                OperatorParameter::AttackDuration,
+                OperatorParameter::DecayDuration,
+                OperatorParameter::SustainVolume,
+                OperatorParameter::ReleaseDuration,
+            ] {
+                patch.parameters[&Parameter::Operator(index as u8, parameter).key()].set_value(0.0);
+            }
+        }
+
+        patch.parameters[&Parameter::Master(MasterParameter::Volume).key()].set_value(0.0);
+
+        let warnings = lint_patch(patch);
+
+        assert_eq!(warnings.len(), 1 + NUM_OPERATORS);
+        assert!(warnings[0].contains("master volume"));
+    }
+
+    #[test]
+    fn test_inspect_file() {
+        let bank = PatchBank::default();
+
+        bank.set_patch_name("Lead");
+
+        let path = ::std::env::temp_dir().join(format!(
+            "octasine-test-inspect-{}.fxp",
+            ::std::process::id()
+        ));
+
+        ::std::fs::write(&path, bank.get_current_patch().export_fxp_bytes()).unwrap();
+
+        let inspection = inspect_file(&path).unwrap();
+
+        ::std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(inspection.format_version, 2);
+        assert_eq!(inspection.patches.len(), 1);
+        assert_eq!(inspection.patches[0].1.name, "Lead");
+        assert!(inspection.patches[0]
+            .1
+            .parameters
+            .iter()
+            .all(|p| p.is_default));
+    }
+}
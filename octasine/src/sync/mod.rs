@@ -1,13 +1,28 @@
 mod atomic_float;
 pub mod change_info;
+mod change_log;
+mod factory_patches;
+pub mod inspect;
 mod parameters;
 mod patch_bank;
 mod serde;
+mod session;
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicU8, Ordering};
 
+pub use change_log::ParameterChangeLog;
 use compact_str::CompactString;
-pub use patch_bank::PatchBank;
+pub use patch_bank::{
+    ParameterLocks, PatchBank, PatchCategory, PatchImportTarget, PatchMetadata, RandomizeScope,
+    PATCH_CATEGORIES,
+};
+pub use session::SessionState;
+
+use crate::common::{EnvelopeStage, NUM_LFOS, NUM_OPERATORS};
+use crate::settings::Settings;
+
+use self::atomic_float::AtomicFloat;
 
 /// Thread-safe state used for parameter and preset calls
 pub struct SyncState<H> {
@@ -15,15 +30,328 @@ pub struct SyncState<H> {
     /// option of leaving this field empty is useful when benchmarking.
     pub host: Option<H>,
     pub patches: PatchBank,
+    /// LFO output values (including amount) of most recently triggered
+    /// voice, as published by audio thread once per processed block
+    lfo_outputs: [AtomicFloat; NUM_LFOS],
+    /// Soft bypass toggle, not stored in patches
+    bypass: AtomicBool,
+    /// Disable all LFO modulation for auditioning unmodulated patch, not
+    /// stored in patches
+    lfos_disabled: AtomicBool,
+    /// Compensate output level for operator mute, listen, volume and mix
+    /// changes, not stored in patches
+    auto_gain: AtomicBool,
+    /// Sum output to mono for checking mono compatibility, not stored in
+    /// patches
+    mono_fold_down: AtomicBool,
+    /// Index plus one of operator routed alone to output for listening, or
+    /// zero if none. Not stored in patches.
+    listen_operator: AtomicU8,
+    /// Pending preview note request from GUI, packed as key, velocity and
+    /// duration in milliseconds, or zero if none
+    audition_request: AtomicU64,
+    /// Bitmask of held keys as published by audio thread once per processed
+    /// block (low and high 64 bits), used for learning chord memory
+    held_keys: [AtomicU64; 2],
+    /// Bitmask of keys held on GUI keyboard (low and high 64 bits)
+    gui_keys: [AtomicU64; 2],
+    /// Peak modulation input magnitude of each operator as published by
+    /// audio thread, reset when read by GUI
+    modulation_input_peaks: [AtomicFloat; NUM_OPERATORS],
+    /// Most recent MIDI event received from host as published by audio
+    /// thread, packed with a flag bit, or zero if none since read by GUI
+    last_midi_event: AtomicU32,
+    /// Volume envelope stage and seconds since it started for each
+    /// operator of most recently triggered voice as published by audio
+    /// thread once per processed block, packed, or zero if none
+    envelope_positions: [AtomicU64; NUM_OPERATORS],
+    /// Whether output exceeded 0 dBFS before limiting since read by GUI
+    clipped: AtomicBool,
+    /// Panic requested from GUI: release all keys and fade out all voices
+    panic_requested: AtomicBool,
+    /// Seconds after which keys held while host transport is stopped are
+    /// released, or zero if stuck note watchdog is disabled
+    stuck_note_timeout: AtomicFloat,
+    /// Present short parameter names to host
+    short_parameter_names: bool,
 }
 
 impl<H> SyncState<H> {
     pub fn new(host: Option<H>) -> Self {
+        let settings = Settings::load_or_default();
+
         Self {
             host,
-            patches: built_in_patch_bank(),
+            patches: startup_patch_bank(&settings),
+            lfo_outputs: ::std::array::from_fn(|_| AtomicFloat::new(0.0)),
+            bypass: AtomicBool::new(false),
+            lfos_disabled: AtomicBool::new(false),
+            auto_gain: AtomicBool::new(false),
+            mono_fold_down: AtomicBool::new(false),
+            listen_operator: AtomicU8::new(0),
+            audition_request: AtomicU64::new(0),
+            held_keys: [AtomicU64::new(0), AtomicU64::new(0)],
+            gui_keys: [AtomicU64::new(0), AtomicU64::new(0)],
+            modulation_input_peaks: ::std::array::from_fn(|_| AtomicFloat::new(0.0)),
+            last_midi_event: AtomicU32::new(0),
+            envelope_positions: ::std::array::from_fn(|_| AtomicU64::new(0)),
+            clipped: AtomicBool::new(false),
+            panic_requested: AtomicBool::new(false),
+            stuck_note_timeout: AtomicFloat::new(stuck_note_timeout_to_atomic(
+                settings.stuck_note_watchdog.get_timeout(),
+            )),
+            short_parameter_names: settings.short_parameter_names,
+        }
+    }
+
+    /// Get name of parameter at index as presented to host
+    pub fn get_parameter_host_name(&self, index: usize) -> Option<CompactString> {
+        self.patches.get_parameter_by_index(index).map(|p| {
+            let parameter = p.parameter.parameter();
+
+            if self.short_parameter_names {
+                parameter.host_short_name()
+            } else {
+                parameter.host_name()
+            }
+        })
+    }
+
+    /// Store LFO output values. Returns array of which values were changed.
+    pub fn set_lfo_outputs(&self, values: [f32; NUM_LFOS]) -> [bool; NUM_LFOS] {
+        ::std::array::from_fn(|i| {
+            let changed = self.lfo_outputs[i].get() != values[i];
+
+            if changed {
+                self.lfo_outputs[i].set(values[i]);
+            }
+
+            changed
+        })
+    }
+
+    pub fn get_lfo_output(&self, lfo_index: usize) -> f32 {
+        self.lfo_outputs[lfo_index].get()
+    }
+
+    pub fn set_bypass(&self, bypass: bool) {
+        self.bypass.store(bypass, Ordering::Relaxed);
+    }
+
+    pub fn get_bypass(&self) -> bool {
+        self.bypass.load(Ordering::Relaxed)
+    }
+
+    pub fn set_lfos_disabled(&self, disabled: bool) {
+        self.lfos_disabled.store(disabled, Ordering::Relaxed);
+    }
+
+    pub fn get_lfos_disabled(&self) -> bool {
+        self.lfos_disabled.load(Ordering::Relaxed)
+    }
+
+    pub fn set_auto_gain(&self, enabled: bool) {
+        self.auto_gain.store(enabled, Ordering::Relaxed);
+    }
+
+    pub fn get_auto_gain(&self) -> bool {
+        self.auto_gain.load(Ordering::Relaxed)
+    }
+
+    pub fn set_mono_fold_down(&self, enabled: bool) {
+        self.mono_fold_down.store(enabled, Ordering::Relaxed);
+    }
+
+    pub fn get_mono_fold_down(&self) -> bool {
+        self.mono_fold_down.load(Ordering::Relaxed)
+    }
+
+    pub fn set_listen_operator(&self, opt_operator_index: Option<usize>) {
+        let value = opt_operator_index.map_or(0, |index| index as u8 + 1);
+
+        self.listen_operator.store(value, Ordering::Relaxed);
+    }
+
+    pub fn get_listen_operator(&self) -> Option<usize> {
+        match self.listen_operator.load(Ordering::Relaxed) {
+            0 => None,
+            value => Some(usize::from(value) - 1),
+        }
+    }
+
+    /// Request preview note with duration in seconds
+    pub fn request_audition(&self, key: u8, velocity: u8, duration: f64) {
+        let duration_ms = (duration.max(0.0) * 1000.0)
+            .round()
+            .min(f64::from(u32::MAX)) as u64;
+
+        // Velocity is at least one, so packed value is never zero
+        let value = u64::from(key.min(127))
+            | (u64::from(velocity.clamp(1, 127)) << 8)
+            | (duration_ms << 16);
+
+        self.audition_request.store(value, Ordering::Relaxed);
+    }
+
+    /// Take pending preview note request: key, velocity and duration in
+    /// seconds
+    pub fn take_audition_request(&self) -> Option<(u8, u8, f64)> {
+        match self.audition_request.swap(0, Ordering::Relaxed) {
+            0 => None,
+            value => Some((
+                value as u8,
+                (value >> 8) as u8,
+                (value >> 16) as f64 / 1000.0,
+            )),
         }
     }
+
+    pub fn set_held_keys(&self, held_keys: u128) {
+        self.held_keys[0].store(held_keys as u64, Ordering::Relaxed);
+        self.held_keys[1].store((held_keys >> 64) as u64, Ordering::Relaxed);
+    }
+
+    pub fn get_held_keys(&self) -> u128 {
+        let low = self.held_keys[0].load(Ordering::Relaxed);
+        let high = self.held_keys[1].load(Ordering::Relaxed);
+
+        u128::from(low) | (u128::from(high) << 64)
+    }
+
+    pub fn set_gui_key(&self, key: u8, pressed: bool) {
+        let key = key.min(127);
+        let bits = &self.gui_keys[usize::from(key / 64)];
+        let mask = 1u64 << (key % 64);
+
+        if pressed {
+            bits.fetch_or(mask, Ordering::Relaxed);
+        } else {
+            bits.fetch_and(!mask, Ordering::Relaxed);
+        }
+    }
+
+    pub fn get_gui_keys(&self) -> u128 {
+        let low = self.gui_keys[0].load(Ordering::Relaxed);
+        let high = self.gui_keys[1].load(Ordering::Relaxed);
+
+        u128::from(low) | (u128::from(high) << 64)
+    }
+
+    /// Store peak modulation input magnitudes, keeping higher values
+    /// that haven't been read yet
+    pub fn update_modulation_input_peaks(&self, peaks: [f32; NUM_OPERATORS]) {
+        for (stored, peak) in self.modulation_input_peaks.iter().zip(peaks) {
+            if peak > stored.get() {
+                stored.set(peak);
+            }
+        }
+    }
+
+    /// Get peak modulation input magnitudes since last call
+    pub fn take_modulation_input_peaks(&self) -> [f32; NUM_OPERATORS] {
+        ::std::array::from_fn(|i| {
+            let peak = self.modulation_input_peaks[i].get();
+
+            self.modulation_input_peaks[i].set(0.0);
+
+            peak
+        })
+    }
+
+    /// Store most recent MIDI event received from host, if any. Earlier
+    /// events not yet read are replaced.
+    pub fn set_last_midi_event(&self, opt_data: Option<[u8; 3]>) {
+        if let Some([status, data_1, data_2]) = opt_data {
+            // Flag bit makes packed value non-zero
+            let value = (1 << 24)
+                | (u32::from(status) << 16)
+                | (u32::from(data_1) << 8)
+                | u32::from(data_2);
+
+            self.last_midi_event.store(value, Ordering::Relaxed);
+        }
+    }
+
+    /// Get most recent MIDI event received from host since last call
+    pub fn take_last_midi_event(&self) -> Option<[u8; 3]> {
+        match self.last_midi_event.swap(0, Ordering::Relaxed) {
+            0 => None,
+            value => Some([(value >> 16) as u8, (value >> 8) as u8, value as u8]),
+        }
+    }
+
+    pub fn set_envelope_positions(&self, positions: [Option<(EnvelopeStage, f32)>; NUM_OPERATORS]) {
+        for (stored, opt_position) in self.envelope_positions.iter().zip(positions) {
+            let value = match opt_position {
+                // Stage index plus one makes packed value non-zero
+                Some((stage, duration)) => {
+                    ((envelope_stage_to_index(stage) + 1) << 32) | u64::from(duration.to_bits())
+                }
+                None => 0,
+            };
+
+            stored.store(value, Ordering::Relaxed);
+        }
+    }
+
+    pub fn get_envelope_position(&self, operator_index: usize) -> Option<(EnvelopeStage, f32)> {
+        match self.envelope_positions[operator_index].load(Ordering::Relaxed) {
+            0 => None,
+            value => Some((
+                envelope_stage_from_index((value >> 32) - 1)?,
+                f32::from_bits(value as u32),
+            )),
+        }
+    }
+
+    /// Store clip flag. Clipping not yet read is not cleared.
+    pub fn set_clipped(&self, clipped: bool) {
+        if clipped {
+            self.clipped.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Returns true if output clipped since last call
+    pub fn take_clipped(&self) -> bool {
+        self.clipped.swap(false, Ordering::Relaxed)
+    }
+
+    pub fn request_panic(&self) {
+        self.panic_requested.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns true once after panic was requested
+    pub fn take_panic_request(&self) -> bool {
+        self.panic_requested.swap(false, Ordering::Relaxed)
+    }
+
+    /// Set stuck note watchdog timeout in seconds, or None to disable it
+    pub fn set_stuck_note_timeout(&self, opt_timeout: Option<f64>) {
+        self.stuck_note_timeout
+            .set(stuck_note_timeout_to_atomic(opt_timeout));
+    }
+
+    pub fn get_stuck_note_timeout(&self) -> Option<f64> {
+        match self.stuck_note_timeout.get() {
+            timeout if timeout > 0.0 => Some(timeout.into()),
+            _ => None,
+        }
+    }
+
+    /// Get LFO output value mapped to range 0.0 to 1.0
+    pub fn get_lfo_output_patch_value(&self, lfo_index: usize) -> f32 {
+        lfo_output_to_patch_value(self.get_lfo_output(lfo_index))
+    }
+}
+
+/// Map LFO output (range -2.0 to 2.0, since max LFO amount is 2.0) to range
+/// 0.0 to 1.0
+pub fn lfo_output_to_patch_value(value: f32) -> f32 {
+    (value * 0.25 + 0.5).clamp(0.0, 1.0)
+}
+
+pub fn lfo_output_from_patch_value(value: f32) -> f32 {
+    (value - 0.5) * 4.0
 }
 
 cfg_if::cfg_if! {
@@ -44,22 +372,135 @@ cfg_if::cfg_if! {
             fn set_parameter_audio_only(&self, parameter: WrappedParameter, value: f32);
             fn get_parameter(&self, parameter: WrappedParameter) -> f32;
             fn format_parameter_value(&self, parameter: WrappedParameter, value: f32) -> CompactString;
+            fn format_parameter_value_as_modulation_target(&self, parameter: WrappedParameter, value: f32) -> CompactString;
             fn get_patches(&self) -> (usize, Vec<CompactString>);
+            fn get_patch_categories(&self) -> Vec<Option<PatchCategory>>;
+            /// For each patch slot, whether it contains a non-default patch
+            fn get_used_patch_slots(&self) -> Vec<bool>;
             fn set_patch_index(&self, index: usize);
             fn get_current_patch_name(&self) -> CompactString;
             fn set_current_patch_name(&self, name: &str);
+            fn get_current_patch_metadata(&self) -> PatchMetadata;
+            fn set_current_patch_metadata(&self, metadata: PatchMetadata);
+            fn get_wavetable_name(&self, operator_index: usize) -> Option<CompactString>;
+            fn set_wavetable(&self, operator_index: usize, wavetable: Option<::std::sync::Arc<crate::wavetable::Wavetable>>);
+            /// Store current modulation parameter values in mod snapshot slot
+            fn store_mod_snapshot(&self, slot_index: usize);
             fn get_changed_parameters(&self) -> Option<[Option<f32>; MAX_NUM_PARAMETERS]>;
             fn have_patches_changed(&self) -> bool;
             fn get_gui_settings(&self) -> crate::gui::GuiSettings;
+            /// Get editor state stored with this instance, if any
+            fn get_session_state(&self) -> Option<SessionState>;
+            fn set_session_state(&self, state: SessionState);
+            /// Get bitmask of keys currently held
+            fn get_held_keys(&self) -> u128;
+            /// Get peak modulation input magnitude of each operator since last call
+            fn take_modulation_input_peaks(&self) -> [f32; NUM_OPERATORS];
+            /// Get most recent MIDI event received from host since last call
+            fn take_last_midi_event(&self) -> Option<[u8; 3]>;
+            /// Get volume envelope stage and seconds since it started of
+            /// most recently triggered voice
+            fn get_envelope_position(&self, operator_index: usize) -> Option<(EnvelopeStage, f32)>;
+            /// Returns true if output exceeded 0 dBFS before limiting since
+            /// last call
+            fn take_clipped(&self) -> bool;
+            /// Temporarily disable all LFO modulation (not stored in patches)
+            fn set_lfos_disabled(&self, disabled: bool);
+            fn get_lfos_disabled(&self) -> bool;
+            /// Compensate output level for operator changes to make
+            /// comparisons fairer (not stored in patches)
+            fn set_auto_gain(&self, enabled: bool);
+            fn get_auto_gain(&self) -> bool;
+            /// Sum output to mono for checking mono compatibility (not
+            /// stored in patches)
+            fn set_mono_fold_down(&self, enabled: bool);
+            fn get_mono_fold_down(&self) -> bool;
+            /// Route only this operator to output (not stored in patches)
+            fn set_listen_operator(&self, opt_operator_index: Option<usize>);
+            fn get_listen_operator(&self) -> Option<usize>;
+            /// Play preview note with duration in seconds
+            fn request_audition(&self, key: u8, velocity: u8, duration: f64);
+            /// Press or release key on GUI keyboard
+            fn set_gui_key(&self, key: u8, pressed: bool);
+            /// Release all keys and fade out all voices
+            fn request_panic(&self);
+            /// Set stuck note watchdog timeout in seconds, or None to
+            /// disable it
+            fn set_stuck_note_timeout(&self, opt_timeout: Option<f64>);
             fn export_patch(&self) -> (CompactString, Vec<u8>);
             fn export_bank(&self) -> Vec<u8>;
-            fn import_bank_or_patches_from_paths(&self, paths: &[PathBuf]);
+            fn import_bank_or_patches_from_paths(&self, paths: &[PathBuf], target: PatchImportTarget);
+            /// Temporarily load patch file into current slot without
+            /// telling host or marking patch as edited
+            fn start_patch_preview(&self, path: &Path);
+            /// Restore patch that was replaced by preview
+            fn end_patch_preview(&self);
             fn clear_patch(&self);
             fn clear_bank(&self);
+            /// Set parameters in scope in current patch to random values
+            fn randomize_parameters(&self, scope: RandomizeScope);
+            /// Nudge continuous parameters in scope in current patch by
+            /// random amounts of at most `strength`
+            fn mutate_parameters(&self, scope: RandomizeScope, strength: f32);
+            fn get_parameter_locks(&self) -> ::std::sync::Arc<ParameterLocks>;
+            /// Write children of two patches into empty slots and return
+            /// their indices
+            fn breed_patches(&self, parent_indices: [usize; 2], num_children: usize, mutation_strength: Option<f32>) -> Vec<usize>;
+            /// Returns true if patches were edited since bank was last
+            /// saved to or loaded from file
+            fn have_unsaved_changes(&self) -> bool;
+            fn mark_bank_as_saved(&self);
+            fn is_capturing_parameter_changes(&self) -> bool;
+            /// Start recording parameter changes with timestamps
+            fn start_parameter_change_capture(&self);
+            /// Stop recording parameter changes and return captured log
+            fn stop_parameter_change_capture(&self) -> Option<ParameterChangeLog>;
         }
     }
 }
 
 fn built_in_patch_bank() -> PatchBank {
-    PatchBank::default()
+    PatchBank::new_with_factory_patches()
+}
+
+/// Patch bank for new instances. Startup patches from settings are imported
+/// into built-in bank if set.
+fn envelope_stage_to_index(stage: EnvelopeStage) -> u64 {
+    match stage {
+        EnvelopeStage::Attack => 0,
+        EnvelopeStage::Decay => 1,
+        EnvelopeStage::Sustain => 2,
+        EnvelopeStage::Release => 3,
+        EnvelopeStage::Ended => 4,
+        EnvelopeStage::Kill => 5,
+    }
+}
+
+fn envelope_stage_from_index(index: u64) -> Option<EnvelopeStage> {
+    match index {
+        0 => Some(EnvelopeStage::Attack),
+        1 => Some(EnvelopeStage::Decay),
+        2 => Some(EnvelopeStage::Sustain),
+        3 => Some(EnvelopeStage::Release),
+        4 => Some(EnvelopeStage::Ended),
+        5 => Some(EnvelopeStage::Kill),
+        _ => None,
+    }
+}
+
+/// Stuck note watchdog timeout in seconds as stored in atomic, with zero
+/// meaning disabled
+fn stuck_note_timeout_to_atomic(opt_timeout: Option<f64>) -> f32 {
+    opt_timeout.map_or(0.0, |timeout| timeout.max(0.0) as f32)
+}
+
+fn startup_patch_bank(settings: &Settings) -> PatchBank {
+    let bank = built_in_patch_bank();
+
+    if let Some(path) = settings.startup_patches.clone() {
+        bank.import_bank_or_patches_from_paths(&[path], PatchImportTarget::CurrentSlot);
+        bank.mark_as_saved();
+    }
+
+    bank
 }
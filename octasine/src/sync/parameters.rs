@@ -3,10 +3,44 @@ use compact_str::CompactString;
 use crate::{
     common::IndexMap,
     parameters::{
-        glide_active::GlideActiveValue, glide_bpm_sync::GlideBpmSyncValue,
-        glide_mode::GlideModeValue, glide_retrigger::GlideRetriggerValue,
-        glide_time::GlideTimeValue, velocity_sensitivity::VelocitySensitivityValue,
-        voice_mode::VoiceModeValue, *,
+        anti_alias::AntiAliasValue,
+        chord_memory::ChordMemoryValue,
+        glide_active::GlideActiveValue,
+        glide_bpm_sync::GlideBpmSyncValue,
+        glide_mode::GlideModeValue,
+        glide_retrigger::GlideRetriggerValue,
+        glide_time::GlideTimeValue,
+        legato_envelope_mode::LegatoEnvelopeModeValue,
+        lfo_amount_sensitivity::LfoAmountSensitivityValue,
+        lfo_envelope::{LfoEnvelopeAttackValue, LfoEnvelopeCurveValue},
+        lfo_key_sync_spread::LfoKeySyncSpreadValue,
+        lfo_smoothing::LfoSmoothingValue,
+        master_pitch_bend_smoothing::MasterPitchBendSmoothingValue,
+        max_polyphony::MaxPolyphonyValue,
+        midi_out::MidiOutValue,
+        mod_snapshot::ModSnapshotSlotValue,
+        mpe::{
+            MpeMemberPitchBendRangeValue, MpeModulationAmountValue, MpeModulationTargetValue,
+            MpeZoneValue,
+        },
+        note_priority::NotePriorityValue,
+        note_random::{NoteRandomAmountValue, NoteRandomTargetValue},
+        operator_cross_feedback_target::{
+            Operator1CrossFeedbackTargetValue, Operator2CrossFeedbackTargetValue,
+            Operator3CrossFeedbackTargetValue,
+        },
+        operator_key_range::{OperatorKeyRangeHighValue, OperatorKeyRangeLowValue},
+        operator_noise_stereo::OperatorNoiseStereoValue,
+        operator_phase_distortion::OperatorPhaseDistortionValue,
+        operator_spread::OperatorSpreadValue,
+        operator_velocity_range::{OperatorVelocityRangeHighValue, OperatorVelocityRangeLowValue},
+        operator_wavetable_position::OperatorWavetablePositionValue,
+        patch_select::PatchSelectValue,
+        scale_quantization::{ScaleRootValue, ScaleValue},
+        velocity_sensitivity::VelocitySensitivityValue,
+        vibrato::{VibratoDelayValue, VibratoDepthValue, VibratoModWheelValue, VibratoRateValue},
+        voice_mode::VoiceModeValue,
+        *,
     },
 };
 
@@ -19,11 +53,15 @@ pub struct PatchParameter {
     pub name: CompactString,
     pub value_from_text: fn(&str) -> Option<f32>,
     pub format: fn(f32) -> CompactString,
+    pub format_as_modulation_target: fn(f32) -> CompactString,
     pub get_serializable: fn(f32) -> SerializableRepresentation,
     pub text_choices: Option<Vec<CompactString>>,
     pub default_value: f32,
     pub clap_path: CompactString,
     pub parameter: WrappedParameter,
+    /// Convert patch value to parameter value and back
+    #[cfg(test)]
+    round_trip: fn(f32) -> f32,
 }
 
 impl PatchParameter {
@@ -60,6 +98,43 @@ impl PatchParameter {
                 MasterParameter::GlideBpmSync => Self::new::<GlideBpmSyncValue>(parameter),
                 MasterParameter::GlideMode => Self::new::<GlideModeValue>(parameter),
                 MasterParameter::GlideRetrigger => Self::new::<GlideRetriggerValue>(parameter),
+                MasterParameter::ChordMemory => Self::new::<ChordMemoryValue>(parameter),
+                MasterParameter::MidiOut => Self::new::<MidiOutValue>(parameter),
+                MasterParameter::Scale => Self::new::<ScaleValue>(parameter),
+                MasterParameter::ScaleRoot => Self::new::<ScaleRootValue>(parameter),
+                MasterParameter::LegatoEnvelopeMode => {
+                    Self::new::<LegatoEnvelopeModeValue>(parameter)
+                }
+                MasterParameter::NotePriority => Self::new::<NotePriorityValue>(parameter),
+                MasterParameter::AntiAlias => Self::new::<AntiAliasValue>(parameter),
+                MasterParameter::NoteRandomTarget => Self::new::<NoteRandomTargetValue>(parameter),
+                MasterParameter::NoteRandomAmount => Self::new::<NoteRandomAmountValue>(parameter),
+                MasterParameter::VibratoRate => Self::new::<VibratoRateValue>(parameter),
+                MasterParameter::VibratoDepth => Self::new::<VibratoDepthValue>(parameter),
+                MasterParameter::VibratoDelay => Self::new::<VibratoDelayValue>(parameter),
+                MasterParameter::VibratoModWheel => Self::new::<VibratoModWheelValue>(parameter),
+                MasterParameter::PitchBendSmoothing => {
+                    Self::new::<MasterPitchBendSmoothingValue>(parameter)
+                }
+                MasterParameter::MpeZone => Self::new::<MpeZoneValue>(parameter),
+                MasterParameter::MpeMemberPitchBendRange => {
+                    Self::new::<MpeMemberPitchBendRangeValue>(parameter)
+                }
+                MasterParameter::MpePressureTarget => {
+                    Self::new::<MpeModulationTargetValue>(parameter)
+                }
+                MasterParameter::MpePressureAmount => {
+                    Self::new::<MpeModulationAmountValue>(parameter)
+                }
+                MasterParameter::MpeTimbreTarget => {
+                    Self::new::<MpeModulationTargetValue>(parameter)
+                }
+                MasterParameter::MpeTimbreAmount => {
+                    Self::new::<MpeModulationAmountValue>(parameter)
+                }
+                MasterParameter::MaxPolyphony => Self::new::<MaxPolyphonyValue>(parameter),
+                MasterParameter::ModSnapshot => Self::new::<ModSnapshotSlotValue>(parameter),
+                MasterParameter::PatchSelect => Self::new::<PatchSelectValue>(parameter),
             },
             Parameter::Operator(index, operator_parameter) => {
                 use OperatorParameter::*;
@@ -81,6 +156,28 @@ impl PatchParameter {
                     SustainVolume => Self::new::<OperatorSustainVolumeValue>(parameter),
                     ReleaseDuration => Self::new::<OperatorReleaseDurationValue>(parameter),
                     EnvelopeLockGroup => Self::new::<OperatorEnvelopeGroupValue>(parameter),
+                    ReleaseMode => Self::new::<OperatorReleaseModeValue>(parameter),
+                    VelocitySensitivityPeak | VelocitySensitivitySustain => {
+                        Self::new::<OperatorEnvelopeVelocitySensitivityValue>(parameter)
+                    }
+                    WavetablePosition => Self::new::<OperatorWavetablePositionValue>(parameter),
+                    PhaseDistortion => Self::new::<OperatorPhaseDistortionValue>(parameter),
+                    KeyRangeLow => Self::new::<OperatorKeyRangeLowValue>(parameter),
+                    KeyRangeHigh => Self::new::<OperatorKeyRangeHighValue>(parameter),
+                    VelocityRangeLow => Self::new::<OperatorVelocityRangeLowValue>(parameter),
+                    VelocityRangeHigh => Self::new::<OperatorVelocityRangeHighValue>(parameter),
+                    NoiseStereo => Self::new::<OperatorNoiseStereoValue>(parameter),
+                    Spread => Self::new::<OperatorSpreadValue>(parameter),
+                    CrossFeedback => match index {
+                        0 | 1 | 2 => Self::new::<OperatorFeedbackValue>(parameter),
+                        _ => panic!("Unsupported parameter"),
+                    },
+                    CrossFeedbackTarget => match index {
+                        0 => Self::new::<Operator1CrossFeedbackTargetValue>(parameter),
+                        1 => Self::new::<Operator2CrossFeedbackTargetValue>(parameter),
+                        2 => Self::new::<Operator3CrossFeedbackTargetValue>(parameter),
+                        _ => panic!("Unsupported parameter"),
+                    },
                     ModTargets => match index {
                         1 => Self::new::<Operator2ModulationTargetValue>(parameter),
                         2 => Self::new::<Operator3ModulationTargetValue>(parameter),
@@ -107,7 +204,8 @@ impl PatchParameter {
                     Shape => Self::new::<LfoShapeValue>(parameter),
                     Amount => Self::new::<LfoAmountValue>(parameter),
                     Active => Self::new::<LfoActiveValue>(parameter),
-                    Target => match index {
+                    Target2Depth | Target3Depth => Self::new::<LfoTargetDepthValue>(parameter),
+                    Target | Target2 | Target3 => match index {
                         0 => Self::new::<Lfo1TargetParameterValue>(parameter),
                         1 => Self::new::<Lfo2TargetParameterValue>(parameter),
                         2 => Self::new::<Lfo3TargetParameterValue>(parameter),
@@ -115,6 +213,15 @@ impl PatchParameter {
                         _ => panic!("Unsupported parameter"),
                     },
                     KeySync => Self::new::<LfoKeySyncValue>(parameter),
+                    TransportSync => Self::new::<LfoTransportSyncValue>(parameter),
+                    Polarity => Self::new::<LfoPolarityValue>(parameter),
+                    VelocitySensitivity | ModWheelSensitivity => {
+                        Self::new::<LfoAmountSensitivityValue>(parameter)
+                    }
+                    EnvelopeCurve => Self::new::<LfoEnvelopeCurveValue>(parameter),
+                    EnvelopeAttack => Self::new::<LfoEnvelopeAttackValue>(parameter),
+                    KeySyncSpread => Self::new::<LfoKeySyncSpreadValue>(parameter),
+                    Smoothing => Self::new::<LfoSmoothingValue>(parameter),
                 }
             }
         }
@@ -126,11 +233,16 @@ impl PatchParameter {
             value: AtomicFloat::new(V::default().to_patch()),
             value_from_text: |v| V::new_from_text(v).map(|v| v.to_patch()),
             format: |v| V::new_from_patch(v).get_formatted(),
+            format_as_modulation_target: |v| {
+                V::new_from_patch(v).get_formatted_as_modulation_target()
+            },
             get_serializable: |v| V::new_from_patch(v).get_serializable(),
             text_choices: V::get_text_choices(),
             default_value: V::default().to_patch(),
             clap_path: parameter.parameter().clap_path(),
             parameter,
+            #[cfg(test)]
+            round_trip: |v| V::new_from_patch(v).to_patch(),
         }
     }
     fn new_with_value<V: ParameterValue>(parameter: WrappedParameter, v: V) -> Self {
@@ -157,6 +269,16 @@ impl PatchParameter {
         (self.get_serializable)(self.value.get())
     }
 
+    /// Returns true if parameter has a continuous range of values rather
+    /// than a set of choices
+    pub fn is_continuous(&self) -> bool {
+        self.text_choices.is_none()
+            && matches!(
+                self.get_serializable(),
+                SerializableRepresentation::Float(_)
+            )
+    }
+
     pub fn set_from_text(&self, text: &str) -> bool {
         if let Some(value) = (self.value_from_text)(text) {
             self.value.set(value);
@@ -170,6 +292,9 @@ impl PatchParameter {
 
 #[cfg(test)]
 mod tests {
+    use quickcheck::{quickcheck, TestResult};
+
+    use crate::parameters::{LfoParameter, MasterParameter, OperatorParameter, Parameter};
     use crate::sync::change_info::MAX_NUM_PARAMETERS;
 
     use super::PatchParameter;
@@ -178,4 +303,96 @@ mod tests {
     fn test_patch_parameters_len() {
         assert!(PatchParameter::all().len() <= MAX_NUM_PARAMETERS);
     }
+
+    /// Converting patch values to parameter values and back must be stable
+    /// after the first conversion and not change formatted values
+    #[test]
+    fn test_patch_value_round_trip() {
+        fn prop(value: f32) -> TestResult {
+            if !(0.0..=1.0).contains(&value) {
+                return TestResult::discard();
+            }
+
+            for p in PatchParameter::all().values() {
+                let once = (p.round_trip)(value);
+                let twice = (p.round_trip)(once);
+
+                if (once - twice).abs() > 1.0e-5 {
+                    return TestResult::error(format!(
+                        "{}: {} -> {} -> {}",
+                        p.name, value, once, twice
+                    ));
+                }
+                if (p.format)(value) != (p.format)(once) {
+                    return TestResult::error(format!(
+                        "{}: {} formatted differently after round trip",
+                        p.name, value
+                    ));
+                }
+            }
+
+            TestResult::passed()
+        }
+
+        quickcheck(prop as fn(f32) -> TestResult);
+    }
+
+    /// Parsing formatted text must yield value with the same formatting (or
+    /// differing only due to float precision)
+    #[test]
+    fn test_formatted_text_round_trip() {
+        fn supports_text_input(parameter: Parameter) -> bool {
+            !matches!(
+                parameter,
+                Parameter::Operator(_, OperatorParameter::ModTargets)
+                    | Parameter::Lfo(
+                        _,
+                        LfoParameter::Target | LfoParameter::Target2 | LfoParameter::Target3
+                    )
+                    | Parameter::Master(
+                        MasterParameter::NoteRandomTarget
+                            | MasterParameter::MpePressureTarget
+                            | MasterParameter::MpeTimbreTarget
+                    )
+            )
+        }
+
+        fn prop(value: f32) -> TestResult {
+            if !(0.0..=1.0).contains(&value) {
+                return TestResult::discard();
+            }
+
+            for p in PatchParameter::all().values() {
+                if !supports_text_input(p.parameter.parameter()) {
+                    continue;
+                }
+
+                let text = (p.format)(value);
+
+                match (p.value_from_text)(&text) {
+                    Some(parsed) if (p.format)(parsed) == text => (),
+                    Some(parsed) if (parsed - value).abs() < 1.0e-4 => (),
+                    Some(parsed) => {
+                        return TestResult::error(format!(
+                            "{}: {} formatted as {} parsed as {}",
+                            p.name,
+                            value,
+                            text,
+                            (p.format)(parsed)
+                        ));
+                    }
+                    None => {
+                        return TestResult::error(format!(
+                            "{}: couldn't parse formatted text {}",
+                            p.name, text
+                        ));
+                    }
+                }
+            }
+
+            TestResult::passed()
+        }
+
+        quickcheck(prop as fn(f32) -> TestResult);
+    }
 }
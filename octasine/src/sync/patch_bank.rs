@@ -1,26 +1,179 @@
 use std::{
     collections::VecDeque,
     io::Read,
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::{
         atomic::{AtomicBool, AtomicUsize, Ordering},
-        Arc,
+        Arc, Mutex,
     },
 };
 
-use arc_swap::ArcSwap;
+use arc_swap::{ArcSwap, ArcSwapOption};
 use array_init::array_init;
 use compact_str::{format_compact, CompactString};
-
-use crate::{common::IndexMap, parameters::ParameterKey};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    common::{IndexMap, NUM_OPERATORS, NUM_PATCHES},
+    parameters::{
+        list::{MasterParameter, OperatorParameter},
+        mod_snapshot::{is_mod_snapshot_parameter, ModSnapshotSlotValue, NUM_MOD_SNAPSHOTS},
+        patch_select::PatchSelectValue,
+        Parameter, ParameterKey, ParameterValue,
+    },
+    wavetable::Wavetable,
+};
 
 use super::change_info::{ParameterChangeInfo, MAX_NUM_PARAMETERS};
+use super::change_log::ParameterChangeLog;
+use super::factory_patches::get_factory_patches;
 use super::parameters::PatchParameter;
 use super::serde::*;
+use super::SessionState;
+
+pub const PATCH_CATEGORIES: &[PatchCategory] = &[
+    PatchCategory::Bass,
+    PatchCategory::Keys,
+    PatchCategory::Pads,
+    PatchCategory::Percussion,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PatchCategory {
+    Bass,
+    Keys,
+    Pads,
+    Percussion,
+}
+
+impl ::std::fmt::Display for PatchCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Bass => "BASS",
+            Self::Keys => "KEYS",
+            Self::Pads => "PADS",
+            Self::Percussion => "PERC",
+        })
+    }
+}
+
+/// Bank slots to import patch files into
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatchImportTarget {
+    /// Current patch and following ones
+    CurrentSlot,
+    /// Patch with given index and following ones
+    Slot(usize),
+    /// Patches following the last one that isn't default
+    AfterLastUsed,
+}
+
+impl ::std::fmt::Display for PatchImportTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::CurrentSlot => f.write_str("CURRENT SLOT"),
+            Self::Slot(index) => write!(f, "SLOT {:03}", index + 1),
+            Self::AfterLastUsed => f.write_str("AFTER LAST USED SLOT"),
+        }
+    }
+}
+
+/// Part of current patch to randomize
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RandomizeScope {
+    /// All operator and LFO parameters
+    Patch,
+    /// Parameters of operator with given index
+    Operator(usize),
+    /// Operator envelope parameters
+    Envelopes,
+    Lfos,
+    /// Operator modulation routing and amount parameters
+    ModMatrix,
+}
+
+impl RandomizeScope {
+    pub fn includes(&self, parameter: Parameter) -> bool {
+        use OperatorParameter::*;
+
+        // Never randomize parameters that could easily silence operators
+        if let Parameter::Operator(
+            _,
+            Active | EnvelopeLockGroup | KeyRangeLow | KeyRangeHigh | VelocityRangeLow
+            | VelocityRangeHigh,
+        ) = parameter
+        {
+            return false;
+        }
+
+        match (self, parameter) {
+            (Self::Patch, Parameter::Operator(..) | Parameter::Lfo(..)) => true,
+            (Self::Operator(index), Parameter::Operator(operator_index, _)) => {
+                *index == operator_index as usize
+            }
+            (
+                Self::Envelopes,
+                Parameter::Operator(
+                    _,
+                    AttackDuration | DecayDuration | SustainVolume | ReleaseDuration,
+                ),
+            ) => true,
+            (Self::Lfos, Parameter::Lfo(..)) => true,
+            (
+                Self::ModMatrix,
+                Parameter::Operator(
+                    _,
+                    MixOut | ModTargets | ModOut | Feedback | CrossFeedback | CrossFeedbackTarget,
+                ),
+            ) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Parameters to leave untouched when randomizing or mutating patch. Locks
+/// apply to all patches and are not stored in them.
+pub struct ParameterLocks([AtomicBool; MAX_NUM_PARAMETERS]);
+
+impl Default for ParameterLocks {
+    fn default() -> Self {
+        Self(array_init(|_| AtomicBool::new(false)))
+    }
+}
+
+impl ParameterLocks {
+    pub fn is_locked(&self, index: usize) -> bool {
+        self.0
+            .get(index)
+            .map_or(false, |locked| locked.load(Ordering::Relaxed))
+    }
+
+    pub fn toggle(&self, index: usize) {
+        if let Some(locked) = self.0.get(index) {
+            locked.fetch_xor(true, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Attribution and licensing info, stored in exported patches
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PatchMetadata {
+    pub author: CompactString,
+    pub license: CompactString,
+}
 
 pub struct Patch {
     name: ArcSwap<String>,
+    category: ArcSwapOption<PatchCategory>,
+    metadata: ArcSwap<PatchMetadata>,
     pub parameters: IndexMap<ParameterKey, PatchParameter>,
+    wavetables: [ArcSwapOption<Wavetable>; NUM_OPERATORS],
+    /// Replaced wavetables that the audio thread might still hold
+    /// references to. Kept around so that they are never deallocated there.
+    retired_wavetables: Mutex<Vec<Arc<Wavetable>>>,
+    /// Stored patch values of modulation routing and depth parameters
+    mod_snapshots: [ArcSwapOption<IndexMap<ParameterKey, f32>>; NUM_MOD_SNAPSHOTS],
 }
 
 impl Default for Patch {
@@ -33,7 +186,12 @@ impl Patch {
     pub fn new(name: &str, parameters: IndexMap<ParameterKey, PatchParameter>) -> Self {
         Self {
             name: ArcSwap::new(Arc::new(Self::process_name(name))),
+            category: Default::default(),
+            metadata: Default::default(),
             parameters,
+            wavetables: Default::default(),
+            retired_wavetables: Default::default(),
+            mod_snapshots: Default::default(),
         }
     }
 
@@ -56,18 +214,95 @@ impl Patch {
         self.name.store(Arc::new(Self::process_name(name)));
     }
 
+    pub fn get_category(&self) -> Option<PatchCategory> {
+        self.category.load().as_deref().copied()
+    }
+
+    pub fn set_category(&self, category: Option<PatchCategory>) {
+        self.category.store(category.map(Arc::new));
+    }
+
+    pub fn get_metadata(&self) -> PatchMetadata {
+        (*self.metadata.load_full()).clone()
+    }
+
+    pub fn set_metadata(&self, metadata: PatchMetadata) {
+        self.metadata.store(Arc::new(metadata));
+    }
+
+    pub fn get_wavetable(&self, operator_index: usize) -> Option<Arc<Wavetable>> {
+        self.wavetables[operator_index].load_full()
+    }
+
+    pub fn set_wavetable(&self, operator_index: usize, wavetable: Option<Arc<Wavetable>>) {
+        if let Some(previous) = self.wavetables[operator_index].swap(wavetable) {
+            let mut retired = self
+                .retired_wavetables
+                .lock()
+                .unwrap_or_else(|err| err.into_inner());
+
+            retired.retain(|wavetable| Arc::strong_count(wavetable) > 1);
+            retired.push(previous);
+        }
+    }
+
+    pub fn get_mod_snapshot(&self, slot_index: usize) -> Option<Arc<IndexMap<ParameterKey, f32>>> {
+        self.mod_snapshots[slot_index].load_full()
+    }
+
+    pub fn set_mod_snapshot(
+        &self,
+        slot_index: usize,
+        snapshot: Option<Arc<IndexMap<ParameterKey, f32>>>,
+    ) {
+        self.mod_snapshots[slot_index].store(snapshot);
+    }
+
+    /// Store current values of modulation parameters in snapshot slot
+    fn store_mod_snapshot(&self, slot_index: usize) {
+        let snapshot = self
+            .parameters
+            .iter()
+            .filter(|(_, p)| is_mod_snapshot_parameter(p.parameter.parameter()))
+            .map(|(key, p)| (*key, p.get_value()))
+            .collect();
+
+        self.set_mod_snapshot(slot_index, Some(Arc::new(snapshot)));
+    }
+
+    /// Returns true if patch is indistinguishable from one cleared with
+    /// given parameters. Patch select parameter is ignored since it tracks
+    /// the patch index.
+    pub fn is_default(&self, default_parameters: &IndexMap<ParameterKey, PatchParameter>) -> bool {
+        self.name.load().as_str() == "-"
+            && self.get_category().is_none()
+            && self.get_metadata() == PatchMetadata::default()
+            && self.wavetables.iter().all(|w| w.load().is_none())
+            && self.mod_snapshots.iter().all(|s| s.load().is_none())
+            && self
+                .parameters
+                .values()
+                .zip(default_parameters.values())
+                .filter(|(p, _)| {
+                    p.parameter.parameter() != Parameter::Master(MasterParameter::PatchSelect)
+                })
+                .all(|(p, default)| p.get_value() == default.get_value())
+    }
+
     fn process_name(name: &str) -> String {
         name.chars()
             .filter(|c| c.is_ascii_graphic() || *c == ' ')
             .collect()
     }
 
-    fn update_from_bytes(&self, bytes: &[u8]) -> anyhow::Result<()> {
+    pub fn update_from_bytes(&self, bytes: &[u8]) -> anyhow::Result<()> {
         update_patch_from_bytes(self, bytes)
     }
 
     fn set_from_patch_parameters(&self, parameters: &IndexMap<ParameterKey, PatchParameter>) {
         self.set_name("-");
+        self.set_category(None);
+        self.set_metadata(Default::default());
 
         for (parameter, default_value) in self
             .parameters
@@ -76,16 +311,81 @@ impl Patch {
         {
             parameter.set_value(default_value);
         }
+
+        for operator_index in 0..NUM_OPERATORS {
+            self.set_wavetable(operator_index, None);
+        }
+
+        for slot_index in 0..NUM_MOD_SNAPSHOTS {
+            self.set_mod_snapshot(slot_index, None);
+        }
+    }
+}
+
+/// Patch contents kept while another patch is previewed in its slot
+struct PatchSnapshot {
+    name: String,
+    category: Option<PatchCategory>,
+    metadata: PatchMetadata,
+    values: Vec<f32>,
+    wavetables: [Option<Arc<Wavetable>>; NUM_OPERATORS],
+    mod_snapshots: [Option<Arc<IndexMap<ParameterKey, f32>>>; NUM_MOD_SNAPSHOTS],
+}
+
+impl PatchSnapshot {
+    fn new(patch: &Patch) -> Self {
+        Self {
+            name: patch.get_name(),
+            category: patch.get_category(),
+            metadata: patch.get_metadata(),
+            values: patch.parameters.values().map(|p| p.get_value()).collect(),
+            wavetables: array_init(|i| patch.get_wavetable(i)),
+            mod_snapshots: array_init(|i| patch.get_mod_snapshot(i)),
+        }
+    }
+
+    fn restore(self, patch: &Patch) {
+        patch.set_name(&self.name);
+        patch.set_category(self.category);
+        patch.set_metadata(self.metadata);
+
+        for (parameter, value) in patch.parameters.values().zip(self.values) {
+            parameter.set_value(value);
+        }
+
+        for (operator_index, wavetable) in self.wavetables.into_iter().enumerate() {
+            patch.set_wavetable(operator_index, wavetable);
+        }
+
+        for (slot_index, snapshot) in self.mod_snapshots.into_iter().enumerate() {
+            patch.set_mod_snapshot(slot_index, snapshot);
+        }
     }
 }
 
 pub struct PatchBank {
-    pub patches: [Patch; 128],
+    pub patches: [Patch; NUM_PATCHES],
     patch_index: AtomicUsize,
     parameter_change_info_audio: ParameterChangeInfo,
     pub parameter_change_info_gui: ParameterChangeInfo,
     patches_changed: AtomicBool,
     envelope_viewports_changed: AtomicBool,
+    wavetables_changed: AtomicBool,
+    /// Set when patches are edited from GUI, cleared when bank is saved to
+    /// or loaded from file
+    unsaved_changes: AtomicBool,
+    /// Set while parameter changes are being captured, to skip locking
+    /// change log otherwise
+    capturing_parameter_changes: AtomicBool,
+    parameter_change_log: Mutex<Option<ParameterChangeLog>>,
+    /// Set while a patch file is previewed, to skip locking preview
+    /// snapshot otherwise
+    previewing_patch: AtomicBool,
+    /// Index and original contents of slot that patch file is previewed in
+    patch_preview: Mutex<Option<(usize, PatchSnapshot)>>,
+    /// Editor state saved by host along with bank
+    session_state: ArcSwapOption<SessionState>,
+    parameter_locks: Arc<ParameterLocks>,
 }
 
 impl Default for PatchBank {
@@ -103,9 +403,32 @@ impl PatchBank {
             parameter_change_info_gui: ParameterChangeInfo::default(),
             patches_changed: AtomicBool::new(false),
             envelope_viewports_changed: AtomicBool::new(false),
+            wavetables_changed: AtomicBool::new(true),
+            unsaved_changes: AtomicBool::new(false),
+            capturing_parameter_changes: AtomicBool::new(false),
+            parameter_change_log: Mutex::new(None),
+            previewing_patch: AtomicBool::new(false),
+            patch_preview: Mutex::new(None),
+            session_state: Default::default(),
+            parameter_locks: Default::default(),
         }
     }
 
+    /// Bank with factory patches in first slots
+    pub fn new_with_factory_patches() -> Self {
+        let bank = Self::default();
+
+        for (patch, factory_patch) in bank.patches.iter().zip(get_factory_patches()) {
+            if let Err(err) = factory_patch.apply(patch) {
+                ::log::error!("Failed loading factory patch: {:#}", err);
+            }
+        }
+
+        bank.mark_parameters_as_changed();
+
+        bank
+    }
+
     // Utils
 
     pub fn get_parameter_by_index(&self, index: usize) -> Option<&PatchParameter> {
@@ -134,8 +457,11 @@ impl PatchBank {
     }
 
     fn mark_parameters_as_changed(&self) {
+        self.update_patch_select_parameter();
+
         self.parameter_change_info_audio.mark_all_as_changed();
         self.parameter_change_info_gui.mark_all_as_changed();
+        self.wavetables_changed.store(true, Ordering::SeqCst);
     }
 
     // Number of patches / parameters
@@ -149,6 +475,59 @@ impl PatchBank {
     }
 }
 
+// Track unsaved changes
+impl PatchBank {
+    pub fn have_unsaved_changes(&self) -> bool {
+        self.unsaved_changes.load(Ordering::SeqCst)
+    }
+
+    pub fn mark_as_saved(&self) {
+        self.unsaved_changes.store(false, Ordering::SeqCst);
+    }
+
+    fn mark_as_edited(&self) {
+        self.unsaved_changes.store(true, Ordering::SeqCst);
+    }
+}
+
+// Capture parameter changes
+impl PatchBank {
+    pub fn is_capturing_parameter_changes(&self) -> bool {
+        self.capturing_parameter_changes.load(Ordering::SeqCst)
+    }
+
+    /// Start capturing parameter changes, discarding any previous capture
+    pub fn start_parameter_change_capture(&self) {
+        *self.parameter_change_log.lock().unwrap() = Some(ParameterChangeLog::default());
+
+        self.capturing_parameter_changes
+            .store(true, Ordering::SeqCst);
+    }
+
+    pub fn stop_parameter_change_capture(&self) -> Option<ParameterChangeLog> {
+        self.capturing_parameter_changes
+            .store(false, Ordering::SeqCst);
+
+        self.parameter_change_log.lock().unwrap().take()
+    }
+
+    fn capture_parameter_change(&self, index: usize) {
+        if !self.is_capturing_parameter_changes() {
+            return;
+        }
+
+        if let Some(parameter) = self.get_parameter_by_index(index) {
+            if let Some(log) = self.parameter_change_log.lock().unwrap().as_mut() {
+                log.push(
+                    parameter.name.clone(),
+                    parameter.get_value(),
+                    parameter.get_value_text(),
+                );
+            }
+        }
+    }
+}
+
 // Manage patches
 impl PatchBank {
     pub fn get_patch_index(&self) -> usize {
@@ -185,15 +564,221 @@ impl PatchBank {
             .collect()
     }
 
+    pub fn get_patch_categories(&self) -> Vec<Option<PatchCategory>> {
+        self.patches.iter().map(Patch::get_category).collect()
+    }
+
+    /// For each patch slot, whether it contains a non-default patch
+    pub fn get_used_patch_slots(&self) -> Vec<bool> {
+        let default_parameters = PatchParameter::all();
+
+        self.patches
+            .iter()
+            .map(|p| !p.is_default(&default_parameters))
+            .collect()
+    }
+
+    pub fn get_current_patch_metadata(&self) -> PatchMetadata {
+        self.get_current_patch().get_metadata()
+    }
+
+    pub fn set_current_patch_metadata(&self, metadata: PatchMetadata) {
+        self.get_current_patch().set_metadata(metadata);
+        self.mark_as_edited();
+    }
+
     pub fn set_patch_name(&self, name: &str) {
         self.get_current_patch().set_name(name);
         self.patches_changed.store(true, Ordering::SeqCst);
+        self.mark_as_edited();
     }
 
     /// Only used from GUI
     pub fn have_patches_changed(&self) -> bool {
         self.patches_changed.fetch_and(false, Ordering::SeqCst)
     }
+
+    /// Set patch select parameter of current patch to current patch index
+    fn update_patch_select_parameter(&self) {
+        let index = Parameter::Master(MasterParameter::PatchSelect).to_index() as usize;
+        let value = PatchSelectValue::new_from_audio(self.get_patch_index() as u8).to_patch();
+
+        if let Some(parameter) = self.get_parameter_by_index(index) {
+            parameter.set_value(value);
+        }
+    }
+
+    /// If parameter at index is patch select parameter and it was set to
+    /// another patch, switch to that patch. Parameter changes are then
+    /// interpolated by audio parameters just like for other patch changes.
+    fn switch_patch_if_selected(&self, index: usize, previous_value: f32) {
+        if index != Parameter::Master(MasterParameter::PatchSelect).to_index() as usize {
+            return;
+        }
+
+        if let Some(parameter) = self.get_parameter_by_index(index) {
+            let patch_index =
+                PatchSelectValue::new_from_patch(parameter.get_value()).get() as usize;
+
+            if patch_index != self.get_patch_index() {
+                // Keep previous patch in sync with its index
+                parameter.set_value(previous_value);
+
+                self.set_patch_index(patch_index);
+            }
+        }
+    }
+}
+
+// Preview patch files without overwriting patches
+impl PatchBank {
+    pub fn is_previewing_patch(&self) -> bool {
+        self.previewing_patch.load(Ordering::SeqCst)
+    }
+
+    /// Temporarily load patch file into current slot. Original contents are
+    /// restored when preview ends. Patch isn't marked as edited.
+    pub fn start_patch_preview(&self, path: &Path) -> anyhow::Result<()> {
+        let bytes = read_file(path)?;
+
+        let mut opt_preview = self
+            .patch_preview
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+
+        let index = self.get_patch_index();
+        let patch = &self.patches[index];
+
+        // Keep original contents if already previewing another file
+        let snapshot = match opt_preview.take() {
+            Some((preview_index, snapshot)) if preview_index == index => snapshot,
+            Some((preview_index, snapshot)) => {
+                snapshot.restore(&self.patches[preview_index]);
+
+                PatchSnapshot::new(patch)
+            }
+            None => PatchSnapshot::new(patch),
+        };
+
+        if let Err(err) = patch.update_from_bytes(&bytes) {
+            snapshot.restore(patch);
+
+            self.previewing_patch.store(false, Ordering::SeqCst);
+            self.mark_patch_contents_as_changed();
+
+            return Err(err);
+        }
+
+        *opt_preview = Some((index, snapshot));
+
+        self.previewing_patch.store(true, Ordering::SeqCst);
+        self.mark_patch_contents_as_changed();
+
+        Ok(())
+    }
+
+    /// Restore contents of slot that patch file was previewed in, if any
+    pub fn end_patch_preview(&self) {
+        if !self.is_previewing_patch() {
+            return;
+        }
+
+        let opt_preview = self
+            .patch_preview
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .take();
+
+        self.previewing_patch.store(false, Ordering::SeqCst);
+
+        if let Some((index, snapshot)) = opt_preview {
+            snapshot.restore(&self.patches[index]);
+
+            self.mark_patch_contents_as_changed();
+        }
+    }
+
+    fn mark_patch_contents_as_changed(&self) {
+        self.mark_parameters_as_changed();
+        self.patches_changed.store(true, Ordering::SeqCst);
+        self.envelope_viewports_changed
+            .store(true, Ordering::SeqCst);
+    }
+}
+
+// Manage wavetables
+impl PatchBank {
+    pub fn get_wavetable_name(&self, operator_index: usize) -> Option<CompactString> {
+        self.get_current_patch()
+            .get_wavetable(operator_index)
+            .map(|wavetable| wavetable.name.clone())
+    }
+
+    pub fn set_wavetable(&self, operator_index: usize, wavetable: Option<Arc<Wavetable>>) {
+        self.get_current_patch()
+            .set_wavetable(operator_index, wavetable);
+
+        self.wavetables_changed.store(true, Ordering::SeqCst);
+        self.patches_changed.store(true, Ordering::SeqCst);
+        self.mark_as_edited();
+    }
+
+    /// Get current patch wavetables if they might have changed since last
+    /// call. Only used from audio thread.
+    pub fn get_changed_wavetables(&self) -> Option<[Option<Arc<Wavetable>>; NUM_OPERATORS]> {
+        if self.wavetables_changed.swap(false, Ordering::SeqCst) {
+            let patch = self.get_current_patch();
+
+            Some(::std::array::from_fn(|i| patch.get_wavetable(i)))
+        } else {
+            None
+        }
+    }
+}
+
+// Manage mod snapshots
+impl PatchBank {
+    /// Store current modulation parameter values in snapshot slot of
+    /// current patch
+    pub fn store_mod_snapshot(&self, slot_index: usize) {
+        if slot_index < NUM_MOD_SNAPSHOTS {
+            self.get_current_patch().store_mod_snapshot(slot_index);
+            self.mark_as_edited();
+        }
+    }
+
+    /// If parameter at index is the mod snapshot selector and the selected
+    /// slot was changed, recall values stored in the newly selected slot
+    fn recall_mod_snapshot_if_selected(&self, index: usize, previous_value: f32) {
+        if index != Parameter::Master(MasterParameter::ModSnapshot).to_index() as usize {
+            return;
+        }
+
+        let patch = self.get_current_patch();
+
+        let previous = ModSnapshotSlotValue::new_from_patch(previous_value).get();
+        let selected = match patch.parameters.get_index(index) {
+            Some((_, p)) => ModSnapshotSlotValue::new_from_patch(p.get_value()).get(),
+            None => return,
+        };
+
+        if selected == previous {
+            return;
+        }
+
+        if let Some(slot_index) = selected.index() {
+            if let Some(snapshot) = patch.mod_snapshots[slot_index].load().as_ref() {
+                for (key, value) in snapshot.iter() {
+                    if let Some((index, _, parameter)) = patch.parameters.get_full(key) {
+                        parameter.set_value(*value);
+
+                        self.parameter_change_info_audio.mark_as_changed(index);
+                        self.parameter_change_info_gui.mark_as_changed(index);
+                    }
+                }
+            }
+        }
+    }
 }
 
 // Get parameter changes
@@ -238,17 +823,39 @@ impl PatchBank {
             .get_index(index)
             .map(|(_, p)| (p.format)(value))
     }
+
+    pub fn format_parameter_value_as_modulation_target(
+        &self,
+        index: usize,
+        value: f32,
+    ) -> Option<CompactString> {
+        self.get_current_patch()
+            .parameters
+            .get_index(index)
+            .map(|(_, p)| (p.format_as_modulation_target)(value))
+    }
 }
 
 // Set parameters
 impl PatchBank {
+    /// Handle side effects of setting parameters with special meaning
+    fn handle_parameter_set(&self, index: usize, previous_value: f32) {
+        self.capture_parameter_change(index);
+        self.recall_mod_snapshot_if_selected(index, previous_value);
+        self.switch_patch_if_selected(index, previous_value);
+    }
+
     pub fn set_parameter_from_gui(&self, index: usize, value: f32) {
         let opt_parameter = self.get_parameter_by_index(index);
 
         if let Some(parameter) = opt_parameter {
+            let previous_value = parameter.get_value();
+
             parameter.set_value(value.min(1.0).max(0.0));
 
             self.parameter_change_info_audio.mark_as_changed(index);
+            self.mark_as_edited();
+            self.handle_parameter_set(index, previous_value);
         }
     }
 
@@ -256,10 +863,13 @@ impl PatchBank {
         let opt_parameter = self.get_parameter_by_index(index);
 
         if let Some(parameter) = opt_parameter {
+            let previous_value = parameter.get_value();
+
             parameter.set_value(value);
 
             self.parameter_change_info_audio.mark_as_changed(index);
             self.parameter_change_info_gui.mark_as_changed(index);
+            self.handle_parameter_set(index, previous_value);
         }
     }
 
@@ -267,9 +877,12 @@ impl PatchBank {
         let opt_parameter = self.get_parameter_by_index(index);
 
         if let Some(parameter) = opt_parameter {
+            let previous_value = parameter.get_value();
+
             if parameter.set_from_text(value) {
                 self.parameter_change_info_audio.mark_as_changed(index);
                 self.parameter_change_info_gui.mark_as_changed(index);
+                self.handle_parameter_set(index, previous_value);
 
                 return true;
             }
@@ -282,8 +895,12 @@ impl PatchBank {
         let opt_parameter = self.get_parameter_by_index(index);
 
         if let Some(parameter) = opt_parameter {
+            let previous_value = parameter.get_value();
+
             if parameter.set_from_text(value) {
                 self.parameter_change_info_audio.mark_as_changed(index);
+                self.mark_as_edited();
+                self.handle_parameter_set(index, previous_value);
 
                 return true;
             }
@@ -295,7 +912,11 @@ impl PatchBank {
 
 // Import / export
 impl PatchBank {
-    pub fn import_bank_or_patches_from_paths(&self, paths: &[PathBuf]) {
+    /// Import patch bank file, or otherwise patch files into slots
+    /// starting at target. Imported patches replace existing ones.
+    pub fn import_bank_or_patches_from_paths(&self, paths: &[PathBuf], target: PatchImportTarget) {
+        self.end_patch_preview();
+
         let mut bank_file_bytes = Vec::new();
         let mut patch_file_bytes = VecDeque::new();
 
@@ -327,8 +948,15 @@ impl PatchBank {
                 }
             }
             None => {
-                // Import serde patches into current and following patches
-                let mut patch_iterator = self.patches[self.get_patch_index()..].iter().peekable();
+                let start_index = self.get_import_start_index(target);
+
+                if start_index >= self.patches.len() {
+                    ::log::warn!("No free patch slots to import patches into");
+
+                    return;
+                }
+
+                let mut patch_iterator = self.patches[start_index..].iter().peekable();
 
                 for patch_bytes in patch_file_bytes {
                     if patch_iterator.peek().is_none() {
@@ -350,12 +978,34 @@ impl PatchBank {
                 self.patches_changed.store(true, Ordering::SeqCst);
                 self.envelope_viewports_changed
                     .store(true, Ordering::SeqCst);
+                self.mark_as_edited();
+
+                if start_index != self.get_patch_index() {
+                    self.set_patch_index(start_index);
+                }
             }
         }
     }
 
+    /// Index of first patch slot to import into. Might be out of bounds
+    /// if last slot is in use.
+    fn get_import_start_index(&self, target: PatchImportTarget) -> usize {
+        match target {
+            PatchImportTarget::CurrentSlot => self.get_patch_index(),
+            PatchImportTarget::Slot(index) => index,
+            PatchImportTarget::AfterLastUsed => self
+                .get_used_patch_slots()
+                .into_iter()
+                .rposition(|used| used)
+                .map(|index| index + 1)
+                .unwrap_or(0),
+        }
+    }
+
     /// Import bytes into current bank, set sync parameters
     pub fn import_bank_from_bytes(&self, bytes: &[u8]) -> anyhow::Result<()> {
+        self.end_patch_preview();
+
         match update_bank_from_bytes(self, bytes) {
             Ok(opt_selected_patch_index) => {
                 self.set_patch_index(
@@ -364,6 +1014,7 @@ impl PatchBank {
                         .unwrap_or(0),
                 );
                 self.mark_parameters_as_changed();
+                self.mark_as_saved();
                 self.patches_changed.store(true, Ordering::SeqCst);
                 self.envelope_viewports_changed
                     .store(true, Ordering::SeqCst);
@@ -375,6 +1026,8 @@ impl PatchBank {
     }
 
     pub fn import_bytes_into_current_patch(&self, bytes: &[u8]) {
+        self.end_patch_preview();
+
         match self.get_current_patch().update_from_bytes(bytes) {
             Ok(()) => {
                 self.mark_parameters_as_changed();
@@ -388,6 +1041,17 @@ impl PatchBank {
         }
     }
 
+    /// Returns None if editor hasn't stored any session state yet
+    pub fn get_session_state(&self) -> Option<SessionState> {
+        self.session_state.load_full().map(|state| (*state).clone())
+    }
+
+    /// Doesn't count as unsaved change, since it isn't part of bank files
+    pub fn set_session_state(&self, state: SessionState) {
+        self.session_state.store(Some(Arc::new(state)));
+    }
+
+    /// Export bank and session state for saving by host
     pub fn export_plain_bytes(&self) -> Vec<u8> {
         let mut buffer = Vec::new();
 
@@ -396,8 +1060,14 @@ impl PatchBank {
         buffer
     }
 
+    /// Export bank for saving to file
     pub fn export_fxb_bytes(&self) -> Vec<u8> {
-        serialize_bank_fxb_bytes(self).expect("serialize preset bank")
+        serialize_bank_fxb_bytes(self, false).expect("serialize preset bank")
+    }
+
+    /// Export bank and session state for saving by host
+    pub fn export_fxb_bytes_with_session(&self) -> Vec<u8> {
+        serialize_bank_fxb_bytes(self, true).expect("serialize preset bank")
     }
 
     pub fn new_from_bytes(bytes: &[u8]) -> Self {
@@ -414,16 +1084,148 @@ impl PatchBank {
 // Clear data
 impl PatchBank {
     pub fn clear_current_patch(&self) {
+        self.end_patch_preview();
+
         self.get_current_patch()
             .set_from_patch_parameters(&PatchParameter::all());
 
         self.mark_parameters_as_changed();
+        self.mark_as_edited();
         self.patches_changed.store(true, Ordering::SeqCst);
         self.envelope_viewports_changed
             .store(true, Ordering::SeqCst);
     }
 
+    pub fn get_parameter_locks(&self) -> Arc<ParameterLocks> {
+        self.parameter_locks.clone()
+    }
+
+    pub fn randomize_parameters(&self, scope: RandomizeScope) {
+        self.end_patch_preview();
+
+        for (index, parameter) in self.get_current_patch().parameters.values().enumerate() {
+            if scope.includes(parameter.parameter.parameter())
+                && !self.parameter_locks.is_locked(index)
+            {
+                parameter.set_value(fastrand::f32());
+            }
+        }
+
+        self.mark_patch_contents_as_changed();
+        self.mark_as_edited();
+    }
+
+    /// Nudge continuous parameters in scope by random amounts of at most
+    /// `strength` (fraction of parameter range) in either direction
+    pub fn mutate_parameters(&self, scope: RandomizeScope, strength: f32) {
+        self.end_patch_preview();
+
+        for (index, parameter) in self.get_current_patch().parameters.values().enumerate() {
+            if scope.includes(parameter.parameter.parameter())
+                && parameter.is_continuous()
+                && !self.parameter_locks.is_locked(index)
+            {
+                let offset = (fastrand::f32() * 2.0 - 1.0) * strength;
+
+                parameter.set_value((parameter.get_value() + offset).clamp(0.0, 1.0));
+            }
+        }
+
+        self.mark_patch_contents_as_changed();
+        self.mark_as_edited();
+    }
+
+    /// Write children of two parent patches into empty slots, taking each
+    /// parameter from a random parent and optionally mutating continuous
+    /// ones. Returns indices of slots written to.
+    pub fn breed_patches(
+        &self,
+        parent_indices: [usize; 2],
+        num_children: usize,
+        mutation_strength: Option<f32>,
+    ) -> Vec<usize> {
+        self.end_patch_preview();
+
+        let parents = if let [Some(a), Some(b)] = parent_indices.map(|i| self.patches.get(i)) {
+            [a, b]
+        } else {
+            ::log::error!("breed patches: invalid parent indices {:?}", parent_indices);
+
+            return Vec::new();
+        };
+
+        let child_indices: Vec<usize> = self
+            .get_used_patch_slots()
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, used)| (!used).then_some(index))
+            .take(num_children)
+            .collect();
+
+        if child_indices.len() < num_children {
+            ::log::warn!(
+                "breed patches: only {} of {} children fit in empty slots",
+                child_indices.len(),
+                num_children
+            );
+        }
+
+        let random_parent = || parents[fastrand::usize(..2)];
+
+        for (child_number, index) in child_indices.iter().copied().enumerate() {
+            let child = &self.patches[index];
+
+            child.set_name(&format!(
+                "{} x {} {}",
+                parents[0].get_name(),
+                parents[1].get_name(),
+                child_number + 1
+            ));
+            child.set_category(random_parent().get_category());
+            child.set_metadata(Default::default());
+
+            for (parameter_index, child_parameter) in child.parameters.values().enumerate() {
+                if child_parameter.parameter.parameter()
+                    == Parameter::Master(MasterParameter::PatchSelect)
+                {
+                    continue;
+                }
+
+                let mut value = random_parent().parameters[parameter_index].get_value();
+
+                if let Some(strength) =
+                    mutation_strength.filter(|_| child_parameter.is_continuous())
+                {
+                    value = (value + (fastrand::f32() * 2.0 - 1.0) * strength).clamp(0.0, 1.0);
+                }
+
+                child_parameter.set_value(value);
+            }
+
+            for operator_index in 0..NUM_OPERATORS {
+                child.set_wavetable(
+                    operator_index,
+                    random_parent().get_wavetable(operator_index),
+                );
+            }
+
+            for slot_index in 0..NUM_MOD_SNAPSHOTS {
+                child.set_mod_snapshot(slot_index, None);
+            }
+        }
+
+        if !child_indices.is_empty() {
+            self.wavetables_changed.store(true, Ordering::SeqCst);
+            self.mark_patch_contents_as_changed();
+            self.mark_as_edited();
+        }
+
+        child_indices
+    }
+
     pub fn clear_bank(&self) {
+        self.end_patch_preview();
+
         let default_parameters = PatchParameter::all();
 
         for patch in self.patches.iter() {
@@ -433,6 +1235,8 @@ impl PatchBank {
         self.set_patch_index(0);
 
         self.mark_parameters_as_changed();
+        // Nothing left to lose
+        self.mark_as_saved();
         self.patches_changed.store(true, Ordering::SeqCst);
         self.envelope_viewports_changed
             .store(true, Ordering::SeqCst);
@@ -441,7 +1245,10 @@ impl PatchBank {
 
 #[cfg(test)]
 pub mod tests {
-    use crate::sync::built_in_patch_bank;
+    use crate::{
+        parameters::{list::LfoParameter, mod_snapshot::ModSnapshotSlot},
+        sync::built_in_patch_bank,
+    };
 
     use super::*;
 
@@ -483,6 +1290,10 @@ pub mod tests {
                     .values()
                     .zip(patch_2.parameters.values())
                     .zip(patch_3.parameters.values())
+                    // Patch select value is set to patch index on import
+                    .filter(|((p1, _), _)| {
+                        p1.parameter.parameter() != Parameter::Master(MasterParameter::PatchSelect)
+                    })
                 {
                     let values = [p1, p2, p3]
                         .into_iter()
@@ -496,6 +1307,438 @@ pub mod tests {
         }
     }
 
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_mod_snapshot_recall() {
+        let bank = PatchBank::default();
+
+        let (mod_out_index, mod_out) = bank
+            .get_index_and_parameter_by_key(
+                &Parameter::Operator(1, OperatorParameter::ModOut).key(),
+            )
+            .unwrap();
+        let selector_index = Parameter::Master(MasterParameter::ModSnapshot).to_index() as usize;
+        let slot_2 = ModSnapshotSlotValue::new_from_audio(ModSnapshotSlot::Slot(1)).to_patch();
+
+        mod_out.set_value(0.25);
+        bank.store_mod_snapshot(1);
+        mod_out.set_value(0.75);
+
+        bank.set_parameter_from_host(selector_index, slot_2);
+
+        assert_eq!(bank.get_parameter_value(mod_out_index), Some(0.25));
+
+        // Reselecting same slot doesn't overwrite later changes
+        mod_out.set_value(0.5);
+        bank.set_parameter_from_host(selector_index, slot_2);
+
+        assert_eq!(bank.get_parameter_value(mod_out_index), Some(0.5));
+
+        // Snapshots are kept when exporting and importing
+        let bank_2 = PatchBank::new_from_bytes(&bank.export_fxb_bytes());
+
+        assert_eq!(
+            bank_2.get_current_patch().get_mod_snapshot(1),
+            bank.get_current_patch().get_mod_snapshot(1)
+        );
+    }
+
+    #[test]
+    fn test_patch_select_parameter() {
+        let bank = PatchBank::default();
+
+        let index = Parameter::Master(MasterParameter::PatchSelect).to_index() as usize;
+
+        bank.set_parameter_from_host(index, PatchSelectValue::new_from_audio(9).to_patch());
+
+        assert_eq!(bank.get_patch_index(), 9);
+        assert_eq!(
+            PatchSelectValue::new_from_patch(bank.patches[0].parameters[index].get_value()).get(),
+            0
+        );
+
+        bank.set_patch_index(3);
+
+        assert_eq!(
+            PatchSelectValue::new_from_patch(bank.get_parameter_value(index).unwrap()).get(),
+            3
+        );
+    }
+
+    /// Corrupted data must not cause panics
+    #[test]
+    fn test_import_corrupted_bytes() {
+        fastrand::seed(123);
+
+        let bank = PatchBank::default();
+        let patch_bytes = bank.get_current_patch().export_fxp_bytes();
+        let bank_bytes = bank.export_plain_bytes();
+
+        for len in (0..patch_bytes.len()).step_by(patch_bytes.len() / 64 + 1) {
+            bank.import_bytes_into_current_patch(&patch_bytes[..len]);
+        }
+        for len in (0..bank_bytes.len()).step_by(bank_bytes.len() / 16 + 1) {
+            let _ = bank.import_bank_from_bytes(&bank_bytes[..len]);
+        }
+
+        for _ in 0..64 {
+            let mut bytes = patch_bytes.clone();
+
+            for _ in 0..4 {
+                bytes[fastrand::usize(..patch_bytes.len())] = fastrand::u8(..);
+            }
+
+            bank.import_bytes_into_current_patch(&bytes);
+        }
+        for _ in 0..8 {
+            let mut bytes = bank_bytes.clone();
+
+            for _ in 0..4 {
+                bytes[fastrand::usize(..bank_bytes.len())] = fastrand::u8(..);
+            }
+
+            let _ = bank.import_bank_from_bytes(&bytes);
+        }
+    }
+
+    #[test]
+    fn test_unsaved_changes() {
+        let bank = PatchBank::default();
+
+        assert!(!bank.have_unsaved_changes());
+
+        bank.set_parameter_from_host(0, 0.5);
+
+        assert!(!bank.have_unsaved_changes());
+
+        bank.set_parameter_from_gui(0, 0.25);
+
+        assert!(bank.have_unsaved_changes());
+
+        bank.import_bank_from_bytes(&bank.export_fxb_bytes())
+            .unwrap();
+
+        assert!(!bank.have_unsaved_changes());
+    }
+
+    #[test]
+    fn test_parameter_change_capture() {
+        let bank = PatchBank::default();
+
+        bank.set_parameter_from_gui(0, 0.5);
+
+        bank.start_parameter_change_capture();
+
+        bank.set_parameter_from_gui(0, 0.25);
+        bank.set_parameter_from_host(1, 0.75);
+
+        let log = bank.stop_parameter_change_capture().unwrap();
+
+        bank.set_parameter_from_gui(0, 0.5);
+
+        let changes = log.changes();
+
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].parameter, bank.get_parameter_name(0).unwrap());
+        assert_eq!(changes[0].value, 0.25);
+        assert_eq!(changes[1].value, 0.75);
+        assert!(changes[0].time <= changes[1].time);
+
+        assert_eq!(log.to_csv().lines().count(), 3);
+        assert!(log.to_json().is_ok());
+
+        assert!(bank.stop_parameter_change_capture().is_none());
+    }
+
+    #[test]
+    fn test_patch_metadata_export_import() {
+        let metadata = PatchMetadata {
+            author: "Someone".into(),
+            license: "CC-BY-4.0".into(),
+        };
+
+        let patch_1 = Patch::default();
+
+        patch_1.set_metadata(metadata.clone());
+
+        let patch_2 = Patch::default();
+
+        patch_2
+            .update_from_bytes(&patch_1.export_fxp_bytes())
+            .unwrap();
+
+        assert_eq!(patch_2.get_metadata(), metadata);
+    }
+
+    #[test]
+    fn test_used_patch_slots() {
+        let bank = PatchBank::default();
+
+        assert!(bank.get_used_patch_slots().iter().all(|used| !used));
+
+        // Switching patch changes patch select parameter of previous patch
+        bank.set_patch_index(2);
+
+        assert!(bank.get_used_patch_slots().iter().all(|used| !used));
+
+        bank.set_parameter_from_gui(0, 0.123);
+
+        let used = bank.get_used_patch_slots();
+
+        assert!(used[2]);
+        assert_eq!(used.iter().filter(|used| **used).count(), 1);
+
+        bank.clear_current_patch();
+
+        assert!(!bank.get_used_patch_slots()[2]);
+
+        bank.set_patch_name("Lead");
+
+        assert!(bank.get_used_patch_slots()[2]);
+    }
+
+    #[test]
+    fn test_randomize_parameters() {
+        fastrand::seed(1);
+
+        let bank = PatchBank::default();
+        let default_parameters = PatchParameter::all();
+
+        let changed = |parameter: Parameter| {
+            let key = parameter.key();
+
+            bank.get_current_patch().parameters[&key].get_value()
+                != default_parameters[&key].get_value()
+        };
+
+        bank.randomize_parameters(RandomizeScope::Operator(1));
+
+        assert!(changed(Parameter::Operator(1, OperatorParameter::Volume)));
+        assert!(changed(Parameter::Operator(1, OperatorParameter::ModOut)));
+        assert!(!changed(Parameter::Operator(1, OperatorParameter::Active)));
+        assert!(!changed(Parameter::Operator(
+            1,
+            OperatorParameter::KeyRangeLow
+        )));
+        assert!(!changed(Parameter::Operator(0, OperatorParameter::Volume)));
+        assert!(!changed(Parameter::Lfo(0, LfoParameter::Amount)));
+        assert!(bank.have_unsaved_changes());
+
+        bank.clear_current_patch();
+        bank.randomize_parameters(RandomizeScope::Envelopes);
+
+        assert!(changed(Parameter::Operator(
+            3,
+            OperatorParameter::AttackDuration
+        )));
+        assert!(!changed(Parameter::Operator(3, OperatorParameter::Volume)));
+
+        bank.clear_current_patch();
+        bank.randomize_parameters(RandomizeScope::Patch);
+
+        assert!(changed(Parameter::Lfo(2, LfoParameter::Amount)));
+        assert!(!changed(Parameter::Master(MasterParameter::Volume)));
+    }
+
+    #[test]
+    fn test_mutate_parameters() {
+        fastrand::seed(2);
+
+        let bank = PatchBank::default();
+        let volume = Parameter::Operator(0, OperatorParameter::Volume);
+        let panning = Parameter::Operator(0, OperatorParameter::Panning);
+        let wave_type = Parameter::Operator(0, OperatorParameter::WaveType);
+
+        let get = |parameter: Parameter| {
+            bank.get_current_patch().parameters[&parameter.key()].get_value()
+        };
+        let initial_volume = get(volume);
+        let initial_panning = get(panning);
+        let initial_wave_type = get(wave_type);
+
+        let (panning_index, _) = bank.get_index_and_parameter_by_key(&panning.key()).unwrap();
+
+        bank.get_parameter_locks().toggle(panning_index);
+        bank.mutate_parameters(RandomizeScope::Patch, 0.05);
+
+        assert_ne!(get(volume), initial_volume);
+        assert!((get(volume) - initial_volume).abs() <= 0.05);
+        assert_eq!(get(panning), initial_panning);
+        assert_eq!(get(wave_type), initial_wave_type);
+        assert!(bank.have_unsaved_changes());
+    }
+
+    #[test]
+    fn test_breed_patches() {
+        fastrand::seed(3);
+
+        let bank = PatchBank::default();
+        let volume = Parameter::Operator(0, OperatorParameter::Volume).key();
+
+        for (index, (name, value)) in [("A", 0.2), ("B", 0.8)].into_iter().enumerate() {
+            bank.patches[index].set_name(name);
+            bank.patches[index].parameters[&volume].set_value(value);
+        }
+
+        bank.patches[3].set_name("C");
+
+        let children = bank.breed_patches([0, 1], 3, None);
+
+        assert_eq!(children, vec![2, 4, 5]);
+
+        for index in children {
+            let child = &bank.patches[index];
+            let value = child.parameters[&volume].get_value();
+
+            assert!(child.get_name().starts_with("A x B"));
+            assert!(value == 0.2 || value == 0.8);
+        }
+
+        assert_eq!(bank.patches[3].get_name(), "C");
+        assert!(bank.have_unsaved_changes());
+    }
+
+    #[test]
+    fn test_import_patches_into_target() {
+        let dir =
+            ::std::env::temp_dir().join(format!("octasine-test-import-{}", ::std::process::id()));
+
+        ::std::fs::create_dir_all(&dir).unwrap();
+
+        let paths: Vec<PathBuf> = ["A", "B"]
+            .into_iter()
+            .map(|name| {
+                let patch = Patch::default();
+
+                patch.set_name(name);
+
+                let path = dir.join(patch.get_fxp_filename().as_str());
+
+                ::std::fs::write(&path, patch.export_fxp_bytes()).unwrap();
+
+                path
+            })
+            .collect();
+
+        let bank = PatchBank::default();
+
+        bank.import_bank_or_patches_from_paths(&paths, PatchImportTarget::Slot(10));
+
+        assert_eq!(bank.get_patch_index(), 10);
+        assert_eq!(bank.patches[10].get_name(), "A");
+        assert_eq!(bank.patches[11].get_name(), "B");
+        assert_eq!(bank.patches[0].get_name(), "-");
+
+        bank.set_patch_index(0);
+        bank.import_bank_or_patches_from_paths(&paths, PatchImportTarget::AfterLastUsed);
+
+        assert_eq!(bank.get_patch_index(), 12);
+        assert_eq!(bank.patches[12].get_name(), "A");
+        assert_eq!(bank.patches[13].get_name(), "B");
+
+        bank.import_bank_or_patches_from_paths(&paths, PatchImportTarget::CurrentSlot);
+
+        assert_eq!(bank.get_patch_index(), 12);
+        assert_eq!(bank.patches[14].get_name(), "-");
+
+        // Only as many patches as fit are imported
+        bank.import_bank_or_patches_from_paths(&paths, PatchImportTarget::Slot(NUM_PATCHES - 1));
+
+        assert_eq!(bank.patches[NUM_PATCHES - 1].get_name(), "A");
+
+        // No slots left after last used one
+        bank.set_patch_index(0);
+        bank.import_bank_or_patches_from_paths(&paths, PatchImportTarget::AfterLastUsed);
+
+        assert_eq!(bank.get_patch_index(), 0);
+
+        ::std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_patch_preview() {
+        let dir =
+            ::std::env::temp_dir().join(format!("octasine-test-preview-{}", ::std::process::id()));
+
+        ::std::fs::create_dir_all(&dir).unwrap();
+
+        let previewed = Patch::default();
+
+        previewed.set_name("Previewed");
+        previewed.parameters[0].set_value(0.123);
+
+        let path = dir.join(previewed.get_fxp_filename().as_str());
+
+        ::std::fs::write(&path, previewed.export_fxp_bytes()).unwrap();
+
+        let bank = PatchBank::default();
+
+        bank.set_patch_name("Original");
+        bank.set_parameter_from_gui(0, 0.75);
+        bank.mark_as_saved();
+
+        bank.start_patch_preview(&path).unwrap();
+
+        assert!(bank.is_previewing_patch());
+        assert_eq!(bank.get_current_patch_name(), "Previewed");
+        assert_eq!(bank.get_parameter_value(0), Some(0.123));
+        assert!(!bank.have_unsaved_changes());
+
+        // Previewing another file keeps original contents
+        bank.start_patch_preview(&path).unwrap();
+        bank.end_patch_preview();
+
+        assert!(!bank.is_previewing_patch());
+        assert_eq!(bank.get_current_patch_name(), "Original");
+        assert_eq!(bank.get_parameter_value(0), Some(0.75));
+
+        // Failed preview leaves patch untouched
+        assert!(bank.start_patch_preview(&dir.join("missing.fxp")).is_err());
+        assert!(!bank.is_previewing_patch());
+        assert_eq!(bank.get_current_patch_name(), "Original");
+
+        ::std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_session_state_export_import() {
+        let session = SessionState {
+            lfos_collapsed: true,
+            patch_category_filter: Some(PatchCategory::Pads),
+            patch_search_text: "bell".into(),
+            last_directory: Some(PathBuf::from("/tmp")),
+            ..Default::default()
+        };
+
+        let bank_1 = PatchBank::default();
+
+        assert!(bank_1.get_session_state().is_none());
+
+        bank_1.set_session_state(session.clone());
+
+        assert!(!bank_1.have_unsaved_changes());
+
+        for bytes in [
+            bank_1.export_plain_bytes(),
+            bank_1.export_fxb_bytes_with_session(),
+        ] {
+            let bank_2 = PatchBank::new_from_bytes(&bytes);
+
+            assert_eq!(bank_2.get_session_state(), Some(session.clone()));
+        }
+
+        // Bank files don't contain session state and don't replace it
+        let bank_2 = PatchBank::new_from_bytes(&bank_1.export_fxb_bytes());
+
+        assert!(bank_2.get_session_state().is_none());
+
+        bank_1
+            .import_bank_from_bytes(&bank_2.export_fxb_bytes())
+            .unwrap();
+
+        assert_eq!(bank_1.get_session_state(), Some(session));
+    }
+
     #[test]
     fn test_load_built_in_patches() {
         let preset_bank = built_in_patch_bank();
@@ -3,6 +3,9 @@ mod v1;
 mod v2;
 
 use std::io::Write;
+use std::sync::Arc;
+
+use semver::Version;
 
 use super::patch_bank::{Patch, PatchBank};
 
@@ -27,11 +30,17 @@ pub fn update_bank_from_bytes(bank: &PatchBank, bytes: &[u8]) -> anyhow::Result<
             &default_serde_patch
         };
 
-        for (key, parameter) in patch.parameters.iter() {
-            if let Some(serde_parameter) = serde_patch.parameters.get(key) {
-                parameter.set_value(serde_parameter.value_patch);
-            }
-        }
+        set_patch_parameters(patch, serde_patch);
+
+        patch.set_category(serde_patch.category);
+        patch.set_metadata(serde_patch.metadata.clone());
+        set_patch_wavetables(patch, serde_patch);
+        set_patch_mod_snapshots(patch, serde_patch);
+    }
+
+    // Bank files don't contain session state, so keep current one
+    if let Some(session) = serde_bank.session {
+        bank.set_session_state(session);
     }
 
     Ok(serde_bank.selected_patch_index)
@@ -46,25 +55,77 @@ pub fn update_patch_from_bytes(patch: &Patch, bytes: &[u8]) -> anyhow::Result<()
     };
 
     patch.set_name(serde_patch.name.as_str());
+    patch.set_category(serde_patch.category);
+    patch.set_metadata(serde_patch.metadata.clone());
 
+    set_patch_parameters(patch, &serde_patch);
+    set_patch_wavetables(patch, &serde_patch);
+    set_patch_mod_snapshots(patch, &serde_patch);
+
+    Ok(())
+}
+
+/// Set parameter values, ignoring invalid ones from corrupted data
+fn set_patch_parameters(patch: &Patch, serde_patch: &v2::SerdePatch) {
     for (key, parameter) in patch.parameters.iter() {
         if let Some(serde_parameter) = serde_patch.parameters.get(key) {
-            parameter.set_value(serde_parameter.value_patch);
+            let value = serde_parameter.value_patch;
+
+            if value.is_finite() {
+                parameter.set_value(value.clamp(0.0, 1.0));
+            } else {
+                ::log::warn!("ignoring invalid value {} for parameter {:?}", value, key);
+            }
         }
     }
+}
 
-    Ok(())
+fn set_patch_wavetables(patch: &Patch, serde_patch: &v2::SerdePatch) {
+    for (operator_index, wavetable) in serde_patch.wavetables.iter().enumerate() {
+        patch.set_wavetable(operator_index, wavetable.clone().map(Arc::new));
+    }
+}
+
+fn set_patch_mod_snapshots(patch: &Patch, serde_patch: &v2::SerdePatch) {
+    for (slot_index, snapshot) in serde_patch.mod_snapshots.iter().enumerate() {
+        patch.set_mod_snapshot(slot_index, snapshot.clone().map(Arc::new));
+    }
+}
+
+/// Get serialization format version (1 or 2) and OctaSine version that
+/// patch or bank was saved with
+pub fn get_format_info(bytes: &[u8], is_bank: bool) -> anyhow::Result<(u8, Version)> {
+    if v2::bytes_are_v2(bytes) {
+        let octasine_version = if is_bank {
+            v2::SerdePatchBank::from_bytes(bytes)?.octasine_version
+        } else {
+            v2::SerdePatch::from_bytes(bytes)?.octasine_version
+        };
+
+        Ok((2, octasine_version))
+    } else {
+        let octasine_version = if is_bank {
+            v1::SerdePatchBank::from_bytes(bytes)?.octasine_version
+        } else {
+            v1::SerdePatch::from_bytes(bytes)?.octasine_version
+        };
+
+        Ok((1, v1::parse_version(&octasine_version)?))
+    }
 }
 
 pub fn serialize_bank_plain_bytes<W: Write>(
     writer: &mut W,
     bank: &PatchBank,
 ) -> anyhow::Result<()> {
-    v2::SerdePatchBank::new(bank).serialize_plain(writer)
+    v2::SerdePatchBank::new(bank, true).serialize_plain(writer)
 }
 
-pub fn serialize_bank_fxb_bytes(bank: &PatchBank) -> anyhow::Result<Vec<u8>> {
-    v2::SerdePatchBank::new(bank).serialize_fxb_bytes()
+pub fn serialize_bank_fxb_bytes(
+    bank: &PatchBank,
+    include_session: bool,
+) -> anyhow::Result<Vec<u8>> {
+    v2::SerdePatchBank::new(bank, include_session).serialize_fxb_bytes()
 }
 
 pub fn serialize_patch_fxp_bytes(patch: &Patch) -> anyhow::Result<Vec<u8>> {
@@ -9,10 +9,10 @@ const SUFFIX: &[u8] = b"\n\nOCTASINE-GZ-DATA-V1-END\n\n";
 pub struct SerdePatchParameterValue(String);
 
 impl SerdePatchParameterValue {
-    pub fn as_f32(&self) -> f32 {
+    pub fn as_f32(&self) -> anyhow::Result<f32> {
         self.0
             .parse()
-            .expect("deserialize SerdePresetParameterValue")
+            .map_err(|err| anyhow::anyhow!("parse parameter value {:?}: {}", self.0, err))
     }
 
     fn deserialize<'de, D>(deserializer: D) -> Result<Self, D::Error>
@@ -1,11 +1,17 @@
 use semver::Version;
 
-use crate::parameters::{OperatorParameter, Parameter, SerializableRepresentation};
+use crate::parameters::operator_wave_type::OperatorWaveTypeValue;
+use crate::parameters::{
+    OperatorParameter, Parameter, ParameterValue, SerializableRepresentation, PARAMETERS,
+};
+use crate::sync::patch_bank::Patch;
 
 use super::SerdePatch;
 
-pub const COMPATIBILITY_CHANGES: &[(Version, fn(&mut SerdePatch))] =
-    &[(Version::new(0, 8, 5), compat_0_8_5)];
+pub const COMPATIBILITY_CHANGES: &[(Version, fn(&mut SerdePatch))] = &[
+    (Version::new(0, 8, 5), compat_0_8_5),
+    (Version::new(0, 9, 2), compat_0_9_2),
+];
 
 /// New operator wave forms
 ///
@@ -20,7 +26,16 @@ pub fn compat_0_8_5(patch: &mut SerdePatch) {
     ];
 
     for key in parameter_keys {
-        let p = patch.parameters.get_mut(&key).unwrap();
+        let p = match patch.parameters.get_mut(&key) {
+            Some(p) => p,
+            None => {
+                ::log::error!(
+                    "converting patch for 0.8.5 compatibility: missing operator wave type parameter"
+                );
+
+                continue;
+            }
+        };
 
         match &p.value_serializable {
             SerializableRepresentation::Other(s) => {
@@ -49,3 +64,147 @@ pub fn compat_0_8_5(patch: &mut SerdePatch) {
         }
     }
 }
+
+/// New band-limited operator wave forms
+///
+/// Patch values of existing wave forms changed, so recalculate them from
+/// serialized wave form names when they no longer match
+pub fn compat_0_9_2(patch: &mut SerdePatch) {
+    let parameter_keys = [
+        Parameter::Operator(0, OperatorParameter::WaveType).key(),
+        Parameter::Operator(1, OperatorParameter::WaveType).key(),
+        Parameter::Operator(2, OperatorParameter::WaveType).key(),
+        Parameter::Operator(3, OperatorParameter::WaveType).key(),
+    ];
+
+    for key in parameter_keys {
+        let p = match patch.parameters.get_mut(&key) {
+            Some(p) => p,
+            None => {
+                ::log::error!(
+                    "converting patch for 0.9.2 compatibility: missing operator wave type parameter"
+                );
+
+                continue;
+            }
+        };
+
+        match &p.value_serializable {
+            SerializableRepresentation::Other(s) => match OperatorWaveTypeValue::new_from_text(s) {
+                // Keep values that already map to correct wave form
+                Some(value)
+                    if OperatorWaveTypeValue::new_from_patch(p.value_patch).get()
+                        != value.get() =>
+                {
+                    p.value_patch = value.to_patch();
+                }
+                Some(_) => (),
+                None => {
+                    ::log::error!(
+                        "converting patch for 0.9.2 compatibility: unrecognized operator wave type: {}",
+                        s
+                    );
+                }
+            },
+            SerializableRepresentation::Float(v) => {
+                ::log::error!(
+                    "converting patch for 0.9.2 compatibility: incorrect serializable representation for operator wave type: {}",
+                    v
+                );
+            }
+        }
+    }
+}
+
+/// Add parameters missing from patches saved by versions prior to their
+/// introduction, so that they don't keep values from patches previously
+/// occupying the slot. They get the values of a cleared patch, which for all
+/// parameters added so far leaves the sound of older patches unchanged.
+pub fn add_missing_parameters(patch: &mut SerdePatch) {
+    let missing: Vec<Parameter> = PARAMETERS
+        .iter()
+        .copied()
+        .filter(|parameter| !patch.parameters.contains_key(&parameter.key()))
+        .collect();
+
+    if missing.is_empty() {
+        return;
+    }
+
+    let mut default_parameters = SerdePatch::new(&Patch::default()).parameters;
+
+    for parameter in missing.iter() {
+        let key = parameter.key();
+
+        if let Some(default_parameter) = default_parameters.swap_remove(&key) {
+            patch.parameters.insert(key, default_parameter);
+        }
+    }
+
+    ::log::info!(
+        "patch \"{}\" saved by OctaSine {}: using default values for missing parameters: {}",
+        patch.name,
+        patch.octasine_version,
+        missing
+            .iter()
+            .map(|parameter| parameter.name())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parameters::MasterParameter;
+    use crate::sync::serde::update_patch_from_bytes;
+
+    use super::*;
+
+    #[test]
+    fn test_add_missing_parameters() {
+        let key = Parameter::Master(MasterParameter::NoteRandomAmount).key();
+
+        let mut serde_patch = SerdePatch::new(&Patch::default());
+
+        serde_patch.parameters.shift_remove(&key);
+
+        let bytes = serde_patch.serialize_fxp_bytes().unwrap();
+
+        let patch = Patch::default();
+        let default_value = patch.parameters[&key].get_value();
+
+        patch.parameters[&key].set_value(0.75);
+
+        update_patch_from_bytes(&patch, &bytes).unwrap();
+
+        assert_eq!(patch.parameters[&key].get_value(), default_value);
+    }
+
+    #[test]
+    fn test_compat_0_9_2() {
+        use crate::parameters::operator_wave_type::WaveType;
+
+        let key = Parameter::Operator(0, OperatorParameter::WaveType).key();
+
+        let mut serde_patch = SerdePatch::new(&Patch::default());
+
+        serde_patch.octasine_version = Version::new(0, 9, 1);
+
+        let parameter = serde_patch.parameters.get_mut(&key).unwrap();
+
+        // Patch value of saw in 0.9.1
+        parameter.value_patch = 0.75;
+        parameter.value_serializable = SerializableRepresentation::Other("SAW".into());
+
+        let bytes = serde_patch.serialize_fxp_bytes().unwrap();
+
+        let patch = Patch::default();
+
+        update_patch_from_bytes(&patch, &bytes).unwrap();
+
+        assert_eq!(
+            OperatorWaveTypeValue::new_from_patch(patch.parameters[&key].get_value()).get(),
+            WaveType::Saw
+        );
+    }
+}
@@ -8,12 +8,16 @@ use semver::Version;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 use crate::{
-    common::IndexMap,
-    parameters::{Parameter, ParameterKey, SerializableRepresentation},
-    sync::patch_bank::{Patch, PatchBank},
+    common::{IndexMap, NUM_OPERATORS},
+    parameters::{
+        mod_snapshot::NUM_MOD_SNAPSHOTS, Parameter, ParameterKey, SerializableRepresentation,
+    },
+    sync::patch_bank::{Patch, PatchBank, PatchCategory, PatchMetadata},
+    sync::SessionState,
+    wavetable::Wavetable,
 };
 
-use self::compat::COMPATIBILITY_CHANGES;
+use self::compat::{add_missing_parameters, COMPATIBILITY_CHANGES};
 
 use super::common::{make_fxb, make_fxp};
 
@@ -22,20 +26,24 @@ const PREFIX_GZ: &[u8] = b"\n\nOCTASINE-DATA-V2-GZ\n\n";
 
 #[derive(Serialize, Deserialize)]
 pub struct SerdePatchBank {
-    octasine_version: Version,
+    pub octasine_version: Version,
     pub patches: Vec<SerdePatch>,
     #[serde(default)]
     pub selected_patch_index: Option<u8>,
+    /// Only present in data saved by host
+    #[serde(default)]
+    pub session: Option<SessionState>,
 }
 
 impl SerdePatchBank {
-    pub fn new(bank: &PatchBank) -> Self {
+    pub fn new(bank: &PatchBank, include_session: bool) -> Self {
         let patches = bank.patches.iter().map(SerdePatch::new).collect();
 
         Self {
             octasine_version: get_octasine_version(),
             patches,
             selected_patch_index: Some(bank.get_patch_index() as u8),
+            session: include_session.then(|| bank.get_session_state()).flatten(),
         }
     }
 
@@ -51,6 +59,7 @@ impl SerdePatchBank {
             octasine_version,
             patches: v2_patches,
             selected_patch_index: None,
+            session: None,
         })
     }
 
@@ -79,9 +88,20 @@ impl SerdePatchBank {
 
 #[derive(Serialize, Deserialize)]
 pub struct SerdePatch {
-    octasine_version: Version,
+    pub octasine_version: Version,
     pub name: CompactString,
+    #[serde(default)]
+    pub category: Option<PatchCategory>,
+    /// Author and license. Missing in files from older versions.
+    #[serde(default)]
+    pub metadata: PatchMetadata,
     pub parameters: IndexMap<ParameterKey, SerdePatchParameter>,
+    /// Wavetables loaded into operators
+    #[serde(default)]
+    pub wavetables: [Option<Wavetable>; NUM_OPERATORS],
+    /// Stored modulation parameter values
+    #[serde(default)]
+    pub mod_snapshots: [Option<IndexMap<ParameterKey, f32>>; NUM_MOD_SNAPSHOTS],
 }
 
 impl SerdePatch {
@@ -104,7 +124,17 @@ impl SerdePatch {
         Self {
             octasine_version: get_octasine_version(),
             name: patch.get_name().into(),
+            category: patch.get_category(),
+            metadata: patch.get_metadata(),
             parameters,
+            wavetables: ::std::array::from_fn(|i| {
+                patch.get_wavetable(i).map(|wavetable| (*wavetable).clone())
+            }),
+            mod_snapshots: ::std::array::from_fn(|i| {
+                patch
+                    .get_mod_snapshot(i)
+                    .map(|snapshot| (*snapshot).clone())
+            }),
         }
     }
 
@@ -114,7 +144,8 @@ impl SerdePatch {
         let mut v2_parameters = Self::new(&Patch::default()).parameters;
 
         for (index, v1_parameter) in v1.parameters.into_iter().enumerate() {
-            let parameter = Parameter::from_index(index).ok_or_else(|| anyhow::anyhow!(""))?;
+            let parameter = Parameter::from_index(index)
+                .ok_or_else(|| anyhow::anyhow!("no parameter with index {}", index))?;
 
             let v2_parameter = v2_parameters
                 .get_mut(&parameter.key())
@@ -122,7 +153,7 @@ impl SerdePatch {
 
             *v2_parameter = SerdePatchParameter {
                 index,
-                value_patch: v1_parameter.value_float.as_f32(),
+                value_patch: v1_parameter.value_float.as_f32()?,
                 value_serializable: SerializableRepresentation::Other(
                     v1_parameter.value_text.into(),
                 ),
@@ -132,7 +163,11 @@ impl SerdePatch {
         let mut patch = Self {
             octasine_version,
             name: v1.name.into(),
+            category: None,
+            metadata: Default::default(),
             parameters: v2_parameters,
+            wavetables: Default::default(),
+            mod_snapshots: Default::default(),
         };
 
         patch.run_compatibility_changes();
@@ -157,13 +192,14 @@ impl SerdePatch {
     }
 
     fn run_compatibility_changes(&mut self) {
+        // Changes are ordered by version, oldest first
         for (changed_in_version, f) in COMPATIBILITY_CHANGES {
             if self.octasine_version < *changed_in_version {
                 f(self);
-            } else {
-                break;
             }
         }
+
+        add_missing_parameters(self);
     }
 }
 
@@ -0,0 +1,22 @@
+use std::path::PathBuf;
+
+use compact_str::CompactString;
+use serde::{Deserialize, Serialize};
+
+use crate::common::NUM_OPERATORS;
+
+use super::PatchCategory;
+
+/// Per-instance editor state, saved by host along with patch bank so that
+/// reopening a project restores it. Not included in bank files.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionState {
+    pub operators_collapsed: [bool; NUM_OPERATORS],
+    pub lfos_collapsed: bool,
+    pub performance_view: bool,
+    pub patch_category_filter: Option<PatchCategory>,
+    pub patch_search_text: CompactString,
+    /// Directory of patch or bank file last opened or saved
+    pub last_directory: Option<PathBuf>,
+}
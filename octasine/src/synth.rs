@@ -0,0 +1,653 @@
+//! Facade for using the audio engine as a regular Rust library
+//!
+//! Plugin and GUI support is not needed, so depend on this crate with
+//! `default-features = false`.
+//!
+//! ```no_run
+//! use octasine::synth::Synth;
+//!
+//! let mut synth = Synth::new(44100.0);
+//!
+//! let mut lefts = [0.0f32; 256];
+//! let mut rights = [0.0f32; 256];
+//!
+//! synth.note_on(60, 100);
+//! synth.render(&mut lefts, &mut rights);
+//! synth.note_off(60);
+//! synth.render(&mut lefts, &mut rights);
+//! ```
+
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread::JoinHandle;
+
+use crate::audio::gen::{process_f32_runtime_select, process_master_f32};
+use crate::audio::AudioState;
+use crate::common::{BeatsPerMinute, NoteEvent, NoteEventInner, SampleRate};
+use crate::parameters::glide_active::{GlideActive, GlideActiveValue};
+use crate::parameters::voice_mode::{VoiceMode, VoiceModeValue};
+use crate::parameters::{MasterParameter, Parameter, ParameterValue};
+use crate::sync::PatchBank;
+use crate::utils::{set_audio_parameters, update_audio_parameters_from_patch_bank};
+
+/// Maximum number of threads used for rendering
+pub const MAX_RENDER_THREADS: usize = 16;
+
+/// Sample rate of rendered patch previews
+pub const PREVIEW_SAMPLE_RATE: u32 = 44_100;
+/// Time that preview notes are held before being released
+const PREVIEW_HOLD_SECONDS: f64 = 2.0;
+/// Time rendered after preview notes are released
+const PREVIEW_RELEASE_SECONDS: f64 = 2.0;
+/// C major chord starting at middle C
+const PREVIEW_CHORD: [u8; 3] = [60, 64, 67];
+const PREVIEW_VELOCITY: u8 = 100;
+
+/// Synthesizer engine with built-in patch bank
+pub struct Synth {
+    /// Main engine. When rendering on multiple threads, it doesn't render
+    /// any voices, but applies master processing to summed output of
+    /// parallel engines.
+    audio: Box<AudioState>,
+    patches: PatchBank,
+    /// Engines rendering voices when rendering on multiple threads. The
+    /// first one is rendered on the calling thread.
+    parallel_engines: Vec<ParallelEngine>,
+    /// Threads rendering all parallel engines but the first one, kept
+    /// alive between calls to render
+    workers: Vec<RenderWorker>,
+    /// Parallel engine that received most recent note-on for each key
+    key_engines: [u8; 128],
+    sample_rate: SampleRate,
+    bpm: BeatsPerMinute,
+    control_interval: usize,
+}
+
+/// Engine rendering a subset of notes on its own thread, with master
+/// processing turned off
+struct ParallelEngine {
+    audio: Box<AudioState>,
+    lefts: Vec<f32>,
+    rights: Vec<f32>,
+}
+
+impl ParallelEngine {
+    fn render(&mut self) {
+        process_f32_runtime_select(
+            &mut self.audio,
+            &mut self.lefts,
+            &mut self.rights,
+            0,
+            |_| (),
+        );
+    }
+}
+
+/// Thread rendering parallel engines sent to it and sending them back
+struct RenderWorker {
+    engine_sender: Option<Sender<ParallelEngine>>,
+    engine_receiver: Receiver<ParallelEngine>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl RenderWorker {
+    fn new() -> Self {
+        let (engine_sender, worker_engine_receiver) = channel::<ParallelEngine>();
+        let (worker_engine_sender, engine_receiver) = channel();
+
+        let handle = ::std::thread::Builder::new()
+            .name("octasine-render".into())
+            .spawn(move || {
+                for mut engine in worker_engine_receiver {
+                    engine.render();
+
+                    if worker_engine_sender.send(engine).is_err() {
+                        break;
+                    }
+                }
+            })
+            .expect("spawn render thread");
+
+        Self {
+            engine_sender: Some(engine_sender),
+            engine_receiver,
+            handle: Some(handle),
+        }
+    }
+
+    fn send(&self, engine: ParallelEngine) {
+        if let Some(sender) = self.engine_sender.as_ref() {
+            sender.send(engine).expect("render thread exited");
+        }
+    }
+
+    fn receive(&self) -> ParallelEngine {
+        self.engine_receiver.recv().expect("render thread exited")
+    }
+}
+
+impl Drop for RenderWorker {
+    fn drop(&mut self) {
+        // Closing channel makes thread exit
+        self.engine_sender = None;
+
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Synth {
+    pub fn new(sample_rate: f64) -> Self {
+        let mut audio = Box::<AudioState>::default();
+
+        audio.set_sample_rate(SampleRate(sample_rate));
+
+        Self {
+            audio,
+            patches: PatchBank::new_with_factory_patches(),
+            parallel_engines: Vec::new(),
+            workers: Vec::new(),
+            key_engines: [0; 128],
+            sample_rate: SampleRate(sample_rate),
+            bpm: BeatsPerMinute::default(),
+            control_interval: 1,
+        }
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = SampleRate(sample_rate);
+
+        for audio in self.engines_mut() {
+            audio.set_sample_rate(SampleRate(sample_rate));
+        }
+    }
+
+    /// Set number of samples between LFO updates at 48 kHz or lower. Higher
+    /// values reduce CPU usage at the cost of modulation smoothness.
+    pub fn set_control_interval(&mut self, samples: usize) {
+        self.control_interval = samples;
+
+        for audio in self.engines_mut() {
+            audio.set_control_interval(samples);
+        }
+    }
+
+    /// Set tempo used by BPM-synced LFOs
+    pub fn set_bpm(&mut self, bpm: f64) {
+        self.bpm = BeatsPerMinute(bpm);
+
+        for audio in self.engines_mut() {
+            audio.set_bpm(BeatsPerMinute(bpm));
+        }
+    }
+
+    /// Render on given number of threads (at most MAX_RENDER_THREADS) to
+    /// speed up offline rendering of dense polyphonic material. Notes are
+    /// distributed by key over separate engines, one per thread, whose
+    /// output is summed before master processing (e.g., limiting) is
+    /// applied. In monophonic voice mode and when glide is active, all notes
+    /// are sent to the first engine, since voices then depend on each other.
+    ///
+    /// Threads are kept alive until thread count is changed or synth is
+    /// dropped. Parameter changes are only applied at the start of each
+    /// rendered buffer when using more than one thread. Change thread count
+    /// while no notes are playing, since sounding notes are cut.
+    pub fn set_render_threads(&mut self, num_threads: usize) {
+        let num_threads = num_threads.clamp(1, MAX_RENDER_THREADS);
+        let num_parallel_engines = if num_threads == 1 { 0 } else { num_threads };
+
+        self.parallel_engines.truncate(num_parallel_engines);
+        self.workers
+            .truncate(num_parallel_engines.saturating_sub(1));
+
+        while self.workers.len() < num_parallel_engines.saturating_sub(1) {
+            self.workers.push(RenderWorker::new());
+        }
+
+        while self.parallel_engines.len() < num_parallel_engines {
+            let mut audio = Box::<AudioState>::default();
+
+            audio.set_master_processing(false);
+            audio.set_sample_rate(self.sample_rate);
+            audio.set_bpm(self.bpm);
+            audio.set_control_interval(self.control_interval);
+
+            let values = (0..self.patches.num_parameters())
+                .map(|index| self.patches.get_parameter_value(index))
+                .collect::<Vec<_>>();
+            let patch = self.patches.get_current_patch();
+
+            set_audio_parameters(&mut audio, &values);
+            audio.set_wavetables(::std::array::from_fn(|i| patch.get_wavetable(i)));
+
+            self.parallel_engines.push(ParallelEngine {
+                audio,
+                lefts: Vec::new(),
+                rights: Vec::new(),
+            });
+        }
+    }
+
+    /// Patch bank, e.g., for switching patches or importing fxb/fxp data
+    pub fn patches(&self) -> &PatchBank {
+        &self.patches
+    }
+
+    /// Start note at beginning of next rendered buffer
+    pub fn note_on(&mut self, key: u8, velocity: u8) {
+        self.send_midi([0x90, key.min(127), velocity.min(127)], 0);
+    }
+
+    /// Release note at beginning of next rendered buffer
+    pub fn note_off(&mut self, key: u8) {
+        self.send_midi([0x80, key.min(127), 0], 0);
+    }
+
+    /// Enqueue MIDI message to be processed at given frame offset into next
+    /// rendered buffer
+    pub fn send_midi(&mut self, data: [u8; 3], delta_frames: u32) {
+        let event = NoteEvent {
+            delta_frames,
+            event: NoteEventInner::Midi { data },
+        };
+
+        if self.parallel_engines.is_empty() {
+            self.audio.enqueue_note_event(event);
+
+            return;
+        }
+
+        let key = usize::from(data[1] & 0x7F);
+
+        let opt_engine_index = match data[0] >> 4 {
+            0b_1001 if data[2] != 0 => {
+                let engine_index = if self.voices_are_independent() {
+                    key % self.parallel_engines.len()
+                } else {
+                    0
+                };
+
+                self.key_engines[key] = engine_index as u8;
+
+                Some(engine_index)
+            }
+            // Note off (including note on with zero velocity) and
+            // polyphonic aftertouch
+            0b_1000..=0b_1010 => Some(usize::from(self.key_engines[key])),
+            _ => None,
+        };
+
+        match opt_engine_index {
+            Some(engine_index) => {
+                if let Some(engine) = self.parallel_engines.get_mut(engine_index) {
+                    engine.audio.enqueue_note_event(event);
+                }
+            }
+            None => {
+                for engine in self.parallel_engines.iter_mut() {
+                    engine.audio.enqueue_note_event(event);
+                }
+            }
+        }
+    }
+
+    pub fn num_parameters(&self) -> usize {
+        self.patches.num_parameters()
+    }
+
+    /// Get index of parameter with given name (as displayed by hosts)
+    pub fn get_parameter_index(&self, name: &str) -> Option<usize> {
+        self.patches
+            .get_current_patch()
+            .parameters
+            .values()
+            .position(|p| p.name == name)
+    }
+
+    /// Get parameter value in range 0.0 to 1.0
+    pub fn get_parameter(&self, index: usize) -> Option<f32> {
+        self.patches.get_parameter_value(index)
+    }
+
+    /// Set parameter value in range 0.0 to 1.0
+    pub fn set_parameter(&mut self, index: usize, value: f32) {
+        self.patches
+            .set_parameter_from_host(index, value.clamp(0.0, 1.0));
+    }
+
+    /// Set parameter from text, e.g., "1.5" or "SAW". Returns false if text
+    /// could not be parsed.
+    pub fn set_parameter_from_text(&mut self, index: usize, text: &str) -> bool {
+        self.patches.set_parameter_text_from_host(index, text)
+    }
+
+    /// Render stereo audio, processing notes and parameter changes received
+    /// since last call. Buffers must have equal length.
+    pub fn render(&mut self, lefts: &mut [f32], rights: &mut [f32]) {
+        assert_eq!(lefts.len(), rights.len());
+
+        if !self.parallel_engines.is_empty() {
+            self.render_parallel(lefts, rights);
+
+            return;
+        }
+
+        let patches = &self.patches;
+
+        process_f32_runtime_select(&mut self.audio, lefts, rights, 0, |audio| {
+            update_audio_parameters_from_patch_bank(audio, patches);
+        });
+    }
+
+    fn render_parallel(&mut self, lefts: &mut [f32], rights: &mut [f32]) {
+        let opt_changed_parameters = self.patches.get_changed_parameters_from_audio();
+        let opt_changed_wavetables = self.patches.get_changed_wavetables();
+
+        for audio in self.engines_mut() {
+            if let Some(values) = opt_changed_parameters.as_ref() {
+                set_audio_parameters(audio, values);
+            }
+            if let Some(wavetables) = opt_changed_wavetables.as_ref() {
+                audio.set_wavetables(wavetables.clone());
+            }
+        }
+
+        for engine in self.parallel_engines.iter_mut() {
+            engine.lefts.clear();
+            engine.lefts.resize(lefts.len(), 0.0);
+            engine.rights.clear();
+            engine.rights.resize(rights.len(), 0.0);
+        }
+
+        let mut engines = ::std::mem::take(&mut self.parallel_engines);
+
+        for (worker, engine) in self.workers.iter().zip(engines.drain(1..)) {
+            worker.send(engine);
+        }
+
+        engines[0].render();
+
+        engines.extend(self.workers.iter().map(RenderWorker::receive));
+
+        self.parallel_engines = engines;
+
+        lefts.fill(0.0);
+        rights.fill(0.0);
+
+        for engine in self.parallel_engines.iter() {
+            for (output, sample) in lefts.iter_mut().zip(engine.lefts.iter()) {
+                *output += sample;
+            }
+            for (output, sample) in rights.iter_mut().zip(engine.rights.iter()) {
+                *output += sample;
+            }
+        }
+
+        process_master_f32(&mut self.audio, lefts, rights);
+    }
+
+    /// Render C major chord with current patch, or middle C only in
+    /// monophonic voice mode. Notes are held for a few seconds, after which
+    /// rendering continues for a few seconds more to capture release.
+    pub fn render_preview(&mut self) -> (Vec<f32>, Vec<f32>) {
+        let voice_mode = VoiceModeValue::new_from_patch(
+            self.patches
+                .get_parameter_value(
+                    Parameter::Master(MasterParameter::VoiceMode).to_index() as usize
+                )
+                .unwrap_or_default(),
+        );
+        let keys = if voice_mode.get() == VoiceMode::Polyphonic {
+            &PREVIEW_CHORD[..]
+        } else {
+            &PREVIEW_CHORD[..1]
+        };
+
+        let hold_frames = (PREVIEW_HOLD_SECONDS * self.sample_rate.0) as usize;
+        let release_frames = (PREVIEW_RELEASE_SECONDS * self.sample_rate.0) as usize;
+
+        let mut lefts = vec![0.0f32; hold_frames + release_frames];
+        let mut rights = vec![0.0f32; hold_frames + release_frames];
+
+        for key in keys.iter().copied() {
+            self.note_on(key, PREVIEW_VELOCITY);
+        }
+
+        self.render(&mut lefts[..hold_frames], &mut rights[..hold_frames]);
+
+        for key in keys.iter().copied() {
+            self.note_off(key);
+        }
+
+        self.render(&mut lefts[hold_frames..], &mut rights[hold_frames..]);
+
+        (lefts, rights)
+    }
+
+    fn engines_mut(&mut self) -> impl Iterator<Item = &mut AudioState> {
+        ::std::iter::once(&mut *self.audio).chain(
+            self.parallel_engines
+                .iter_mut()
+                .map(|engine| &mut *engine.audio),
+        )
+    }
+
+    /// Returns false if voices might affect each other, e.g., in monophonic
+    /// voice mode, in which case notes can't be distributed over engines
+    fn voices_are_independent(&self) -> bool {
+        let get_value = |parameter| {
+            self.patches
+                .get_parameter_value(Parameter::Master(parameter).to_index() as usize)
+                .unwrap_or_default()
+        };
+
+        let voice_mode = VoiceModeValue::new_from_patch(get_value(MasterParameter::VoiceMode));
+        let glide_active =
+            GlideActiveValue::new_from_patch(get_value(MasterParameter::GlideActive));
+
+        (voice_mode.get() == VoiceMode::Polyphonic) & (glide_active.get() == GlideActive::Off)
+    }
+}
+
+/// Render preview of patch file contents (see [Synth::render_preview]) as
+/// 16-bit stereo WAV file, e.g., for including in shared patch packs
+pub fn render_patch_preview_wav(patch_bytes: &[u8]) -> Vec<u8> {
+    let mut synth = Synth::new(f64::from(PREVIEW_SAMPLE_RATE));
+
+    synth.patches().import_bytes_into_current_patch(patch_bytes);
+
+    let (lefts, rights) = synth.render_preview();
+
+    encode_wav(PREVIEW_SAMPLE_RATE, &lefts, &rights)
+}
+
+/// Encode stereo audio as 16-bit PCM WAV file
+fn encode_wav(sample_rate: u32, lefts: &[f32], rights: &[f32]) -> Vec<u8> {
+    const NUM_CHANNELS: u16 = 2;
+    const BYTES_PER_SAMPLE: u16 = 2;
+
+    let data_len = (lefts.len() * usize::from(NUM_CHANNELS * BYTES_PER_SAMPLE)) as u32;
+    let block_align = NUM_CHANNELS * BYTES_PER_SAMPLE;
+
+    let mut bytes = Vec::with_capacity(44 + data_len as usize);
+
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+    bytes.extend_from_slice(b"WAVE");
+
+    bytes.extend_from_slice(b"fmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    bytes.extend_from_slice(&NUM_CHANNELS.to_le_bytes());
+    bytes.extend_from_slice(&sample_rate.to_le_bytes());
+    bytes.extend_from_slice(&(sample_rate * u32::from(block_align)).to_le_bytes());
+    bytes.extend_from_slice(&block_align.to_le_bytes());
+    bytes.extend_from_slice(&(BYTES_PER_SAMPLE * 8).to_le_bytes());
+
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_len.to_le_bytes());
+
+    for (left, right) in lefts.iter().zip(rights.iter()) {
+        for sample in [left, right] {
+            let sample = (sample.clamp(-1.0, 1.0) * f32::from(i16::MAX)) as i16;
+
+            bytes.extend_from_slice(&sample.to_le_bytes());
+        }
+    }
+
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_synth_render() {
+        let mut synth = Synth::new(44100.0);
+
+        let mut lefts = [0.0f32; 512];
+        let mut rights = [0.0f32; 512];
+
+        synth.render(&mut lefts, &mut rights);
+
+        assert!(lefts.iter().chain(rights.iter()).all(|s| *s == 0.0));
+
+        synth.note_on(60, 100);
+        synth.render(&mut lefts, &mut rights);
+
+        assert!(lefts.iter().chain(rights.iter()).any(|s| *s != 0.0));
+
+        let index = synth.get_parameter_index("Master volume").unwrap();
+
+        assert!(synth.set_parameter_from_text(index, "0.5"));
+        assert!(!synth.set_parameter_from_text(index, "foo"));
+    }
+
+    #[test]
+    fn test_render_patch_preview_wav() {
+        let patch_bytes = Synth::new(44100.0)
+            .patches()
+            .get_current_patch()
+            .export_fxp_bytes();
+
+        let wav = render_patch_preview_wav(&patch_bytes);
+
+        let num_frames = ((PREVIEW_HOLD_SECONDS + PREVIEW_RELEASE_SECONDS)
+            * f64::from(PREVIEW_SAMPLE_RATE)) as usize;
+
+        assert_eq!(&wav[0..4], b"RIFF");
+        assert_eq!(&wav[8..12], b"WAVE");
+        assert_eq!(wav.len(), 44 + num_frames * 4);
+        assert!(wav[44..].iter().any(|b| *b != 0));
+    }
+
+    /// Output should be the same regardless of thread count, including
+    /// master processing, which is applied once to summed output
+    #[test]
+    fn test_synth_render_parallel() {
+        const KEYS: ::std::ops::Range<u8> = 36..84;
+
+        let render = |num_threads: usize, master_volume: f32| {
+            let mut synth = Synth::new(44100.0);
+
+            synth.set_render_threads(num_threads);
+            synth.audio.set_mono_fold_down(true);
+
+            let index = synth.get_parameter_index("Master volume").unwrap();
+
+            synth.set_parameter(index, master_volume);
+
+            for operator in 1..=4 {
+                for name in ["vol", "mix out"] {
+                    let index = synth
+                        .get_parameter_index(&format!("OP {} {}", operator, name))
+                        .unwrap();
+
+                    synth.set_parameter(index, 1.0);
+                }
+            }
+
+            let mut lefts = vec![0.0f32; 2048];
+            let mut rights = vec![0.0f32; 2048];
+
+            for (i, key) in KEYS.enumerate() {
+                synth.send_midi([0x90, key, 100], i as u32 * 16);
+            }
+
+            synth.render(&mut lefts[..1024], &mut rights[..1024]);
+
+            for key in KEYS {
+                synth.note_off(key);
+            }
+
+            synth.render(&mut lefts[1024..], &mut rights[1024..]);
+
+            (lefts, rights, synth.audio.take_clipped())
+        };
+
+        // Only summed output of all keys clips at full master volume
+        for (master_volume, expect_clipped) in [(0.5, false), (1.0, true)] {
+            let (reference_lefts, reference_rights, reference_clipped) = render(1, master_volume);
+            let (lefts, rights, clipped) = render(4, master_volume);
+
+            assert!(reference_lefts.iter().any(|s| *s != 0.0));
+            assert_eq!(reference_clipped, expect_clipped);
+            assert_eq!(clipped, expect_clipped);
+
+            for (i, (a, b)) in lefts
+                .iter()
+                .chain(rights.iter())
+                .zip(reference_lefts.iter().chain(reference_rights.iter()))
+                .enumerate()
+            {
+                assert!((a - b).abs() < 1e-5, "sample {}: {} != {}", i, a, b);
+            }
+        }
+    }
+
+    /// Note should sound for the same duration regardless of sample rate
+    #[test]
+    fn test_synth_render_sample_rates() {
+        const NOTE_DURATION: f64 = 0.1;
+
+        let durations = [44_100.0, 48_000.0, 96_000.0, 192_000.0].map(|sample_rate| {
+            let mut synth = Synth::new(sample_rate);
+
+            let note_off_frame = (NOTE_DURATION * sample_rate) as usize;
+            let mut lefts = vec![0.0f32; note_off_frame];
+            let mut rights = vec![0.0f32; note_off_frame];
+
+            synth.note_on(60, 100);
+            synth.render(&mut lefts, &mut rights);
+            synth.note_off(60);
+
+            let mut num_frames = note_off_frame;
+
+            lefts.resize(64, 0.0);
+            rights.resize(64, 0.0);
+
+            loop {
+                synth.render(&mut lefts, &mut rights);
+
+                match lefts.iter().rposition(|s| *s != 0.0) {
+                    Some(i) if i == lefts.len() - 1 => num_frames += lefts.len(),
+                    Some(i) => {
+                        num_frames += i + 1;
+
+                        break;
+                    }
+                    None => break,
+                }
+
+                assert!(num_frames < (sample_rate * 10.0) as usize);
+            }
+
+            num_frames as f64 / sample_rate
+        });
+
+        for duration in durations {
+            assert!((duration - durations[0]).abs() < 0.001, "{:?}", durations);
+        }
+    }
+}
@@ -1,6 +1,10 @@
 use std::path::PathBuf;
 
-use crate::{audio::AudioState, parameters::Parameter, sync::SyncState};
+use crate::{
+    audio::AudioState,
+    parameters::Parameter,
+    sync::{PatchBank, SyncState},
+};
 
 #[macro_export]
 macro_rules! crate_version {
@@ -10,12 +14,41 @@ macro_rules! crate_version {
 }
 
 pub fn update_audio_parameters<T>(audio: &mut AudioState, sync: &SyncState<T>) {
-    if let Some(indeces) = sync.patches.get_changed_parameters_from_audio() {
-        for (index, opt_new_value) in indeces.iter().enumerate() {
-            if let Some(new_value) = opt_new_value {
-                if let Some(parameter) = Parameter::from_index(index) {
-                    audio.set_parameter_from_patch(parameter, *new_value);
-                }
+    update_audio_parameters_from_patch_bank(audio, &sync.patches);
+
+    audio.set_lfos_disabled(sync.get_lfos_disabled());
+    audio.set_listen_operator(sync.get_listen_operator());
+    audio.set_auto_gain(sync.get_auto_gain());
+    audio.set_mono_fold_down(sync.get_mono_fold_down());
+    audio.set_stuck_note_timeout(sync.get_stuck_note_timeout());
+
+    if sync.take_panic_request() {
+        audio.panic();
+    }
+
+    if let Some((key, velocity, duration)) = sync.take_audition_request() {
+        audio.start_audition(key, velocity, duration);
+    }
+
+    audio.set_gui_keys(sync.get_gui_keys());
+}
+
+pub fn update_audio_parameters_from_patch_bank(audio: &mut AudioState, patches: &PatchBank) {
+    if let Some(indeces) = patches.get_changed_parameters_from_audio() {
+        set_audio_parameters(audio, &indeces);
+    }
+
+    if let Some(wavetables) = patches.get_changed_wavetables() {
+        audio.set_wavetables(wavetables);
+    }
+}
+
+/// Set parameters with values (patch values indexed by parameter index)
+pub fn set_audio_parameters(audio: &mut AudioState, values: &[Option<f32>]) {
+    for (index, opt_new_value) in values.iter().enumerate() {
+        if let Some(new_value) = opt_new_value {
+            if let Some(parameter) = Parameter::from_index(index) {
+                audio.set_parameter_from_patch(parameter, *new_value);
             }
         }
     }
@@ -0,0 +1,287 @@
+//! Single-cycle wavetables for operator wavetable mode
+
+use std::path::Path;
+
+use anyhow::Context;
+use compact_str::CompactString;
+use serde::{Deserialize, Serialize};
+
+/// Number of samples in each wavetable frame. Loaded frames are resampled to
+/// this length.
+pub const WAVETABLE_FRAME_LEN: usize = 2048;
+pub const WAVETABLE_MAX_FRAMES: usize = 256;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Wavetable {
+    pub name: CompactString,
+    /// Frames of WAVETABLE_FRAME_LEN samples each, stored consecutively
+    samples: Vec<f32>,
+}
+
+impl Wavetable {
+    /// Create wavetable from consecutive frames of frame_len samples each
+    pub fn from_samples(name: &str, samples: &[f32], frame_len: usize) -> anyhow::Result<Self> {
+        if frame_len == 0 || samples.len() < frame_len {
+            return Err(anyhow::anyhow!("wavetable doesn't contain a full frame"));
+        }
+
+        let mut resampled = Vec::new();
+
+        for frame in samples.chunks_exact(frame_len).take(WAVETABLE_MAX_FRAMES) {
+            for i in 0..WAVETABLE_FRAME_LEN {
+                let position = (i * frame_len) as f64 / WAVETABLE_FRAME_LEN as f64;
+                let index = position as usize;
+                let fract = (position - index as f64) as f32;
+
+                let a = frame[index];
+                let b = frame[(index + 1) % frame_len];
+
+                resampled.push(a + (b - a) * fract);
+            }
+        }
+
+        // Normalize to peak volume of 1.0
+        let peak = resampled.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+
+        if peak > 0.0 {
+            for sample in resampled.iter_mut() {
+                *sample /= peak;
+            }
+        }
+
+        Ok(Self {
+            name: name.into(),
+            samples: resampled,
+        })
+    }
+
+    /// Load WAV file. Frame length is read from Serum-style "clm " chunks
+    /// if present. Otherwise, files with a length divisible by
+    /// WAVETABLE_FRAME_LEN are split into frames of that length and other
+    /// files are treated as a single frame.
+    pub fn from_wav_bytes(name: &str, bytes: &[u8]) -> anyhow::Result<Self> {
+        let wav = parse_wav(bytes)?;
+
+        let frame_len = match wav.frame_len {
+            Some(frame_len) => frame_len,
+            None if wav.samples.len() % WAVETABLE_FRAME_LEN == 0 => WAVETABLE_FRAME_LEN,
+            None => wav.samples.len(),
+        };
+
+        Self::from_samples(name, &wav.samples, frame_len)
+    }
+
+    pub fn from_path(path: &Path) -> anyhow::Result<Self> {
+        let bytes = ::std::fs::read(path)
+            .with_context(|| format!("read wavetable file {}", path.display()))?;
+        let name = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("-");
+
+        Self::from_wav_bytes(name, &bytes)
+    }
+
+    pub fn num_frames(&self) -> usize {
+        self.samples.len() / WAVETABLE_FRAME_LEN
+    }
+
+    /// Get sample at phase (0.0 to 1.0) and position (0.0 to 1.0),
+    /// interpolating linearly between samples and frames
+    pub fn sample(&self, phase: f64, position: f64) -> f64 {
+        let num_frames = self.num_frames();
+
+        if num_frames == 0 {
+            return 0.0;
+        }
+
+        let frame_position = position.clamp(0.0, 1.0) * (num_frames - 1) as f64;
+        let frame_index = frame_position as usize;
+        let frame_fract = frame_position - frame_index as f64;
+
+        let a = self.sample_frame(frame_index, phase);
+
+        if frame_fract == 0.0 {
+            a
+        } else {
+            let b = self.sample_frame((frame_index + 1).min(num_frames - 1), phase);
+
+            a + (b - a) * frame_fract
+        }
+    }
+
+    fn sample_frame(&self, frame_index: usize, phase: f64) -> f64 {
+        let frame = &self.samples
+            [frame_index * WAVETABLE_FRAME_LEN..(frame_index + 1) * WAVETABLE_FRAME_LEN];
+
+        let position = phase.rem_euclid(1.0) * WAVETABLE_FRAME_LEN as f64;
+        let index = (position as usize).min(WAVETABLE_FRAME_LEN - 1);
+        let fract = position - index as f64;
+
+        let a = f64::from(frame[index]);
+        let b = f64::from(frame[(index + 1) % WAVETABLE_FRAME_LEN]);
+
+        a + (b - a) * fract
+    }
+}
+
+struct WavData {
+    /// Samples of first channel
+    samples: Vec<f32>,
+    frame_len: Option<usize>,
+}
+
+fn parse_wav(bytes: &[u8]) -> anyhow::Result<WavData> {
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return Err(anyhow::anyhow!("not a WAV file"));
+    }
+
+    let mut format = None;
+    let mut data = None;
+    let mut frame_len = None;
+
+    let mut rest = &bytes[12..];
+
+    while rest.len() >= 8 {
+        let id = &rest[0..4];
+        let len = u32::from_le_bytes(rest[4..8].try_into().unwrap()) as usize;
+        let chunk = rest
+            .get(8..8 + len)
+            .ok_or_else(|| anyhow::anyhow!("truncated WAV chunk"))?;
+
+        match id {
+            b"fmt " => {
+                if chunk.len() < 16 {
+                    return Err(anyhow::anyhow!("invalid WAV format chunk"));
+                }
+
+                let mut tag = u16::from_le_bytes([chunk[0], chunk[1]]);
+                let channels = u16::from_le_bytes([chunk[2], chunk[3]]);
+                let bits = u16::from_le_bytes([chunk[14], chunk[15]]);
+
+                // WAVE_FORMAT_EXTENSIBLE, read actual format from sub-format
+                if tag == 0xFFFE && chunk.len() >= 26 {
+                    tag = u16::from_le_bytes([chunk[24], chunk[25]]);
+                }
+
+                format = Some((tag, channels.max(1) as usize, bits));
+            }
+            b"data" => {
+                data = Some(chunk);
+            }
+            b"clm " => {
+                // Serum wavetable marker, e.g., "<!>2048 ..."
+                frame_len = ::std::str::from_utf8(chunk)
+                    .ok()
+                    .and_then(|s| s.strip_prefix("<!>"))
+                    .and_then(|s| s.split_whitespace().next())
+                    .and_then(|s| s.parse().ok());
+            }
+            _ => (),
+        }
+
+        // Chunks are padded to even lengths
+        rest = rest.get(8 + len + len % 2..).unwrap_or(&[]);
+    }
+
+    let (tag, channels, bits) = format.ok_or_else(|| anyhow::anyhow!("no WAV format chunk"))?;
+    let data = data.ok_or_else(|| anyhow::anyhow!("no WAV data chunk"))?;
+
+    let bytes_per_sample = usize::from(bits / 8);
+
+    if bytes_per_sample == 0 {
+        return Err(anyhow::anyhow!("unsupported WAV bit depth {}", bits));
+    }
+
+    let samples = data
+        .chunks_exact(bytes_per_sample * channels)
+        .map(|frame| {
+            let s = &frame[..bytes_per_sample];
+
+            match (tag, bits) {
+                (1, 8) => Ok((f32::from(s[0]) - 128.0) / 128.0),
+                (1, 16) => Ok(f32::from(i16::from_le_bytes([s[0], s[1]])) / 32768.0),
+                (1, 24) => Ok((i32::from_le_bytes([0, s[0], s[1], s[2]]) >> 8) as f32 / 8388608.0),
+                (1, 32) => Ok(i32::from_le_bytes([s[0], s[1], s[2], s[3]]) as f32 / 2147483648.0),
+                (3, 32) => Ok(f32::from_le_bytes([s[0], s[1], s[2], s[3]])),
+                (3, 64) => Ok(f64::from_le_bytes(s.try_into().unwrap()) as f32),
+                _ => Err(anyhow::anyhow!(
+                    "unsupported WAV format {} with bit depth {}",
+                    tag,
+                    bits
+                )),
+            }
+        })
+        .collect::<anyhow::Result<Vec<f32>>>()?;
+
+    Ok(WavData { samples, frame_len })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wav_bytes(samples: &[i16], clm: Option<&str>) -> Vec<u8> {
+        let mut chunks = Vec::new();
+
+        chunks.extend_from_slice(b"fmt ");
+        chunks.extend_from_slice(&16u32.to_le_bytes());
+        chunks.extend_from_slice(&1u16.to_le_bytes());
+        chunks.extend_from_slice(&1u16.to_le_bytes());
+        chunks.extend_from_slice(&44100u32.to_le_bytes());
+        chunks.extend_from_slice(&88200u32.to_le_bytes());
+        chunks.extend_from_slice(&2u16.to_le_bytes());
+        chunks.extend_from_slice(&16u16.to_le_bytes());
+
+        if let Some(clm) = clm {
+            chunks.extend_from_slice(b"clm ");
+            chunks.extend_from_slice(&(clm.len() as u32).to_le_bytes());
+            chunks.extend_from_slice(clm.as_bytes());
+
+            if clm.len() % 2 == 1 {
+                chunks.push(0);
+            }
+        }
+
+        chunks.extend_from_slice(b"data");
+        chunks.extend_from_slice(&(samples.len() as u32 * 2).to_le_bytes());
+
+        for sample in samples {
+            chunks.extend_from_slice(&sample.to_le_bytes());
+        }
+
+        let mut bytes = Vec::new();
+
+        bytes.extend_from_slice(b"RIFF");
+        bytes.extend_from_slice(&(chunks.len() as u32 + 4).to_le_bytes());
+        bytes.extend_from_slice(b"WAVE");
+        bytes.extend_from_slice(&chunks);
+
+        bytes
+    }
+
+    #[test]
+    fn test_wavetable_from_wav() {
+        // Two frames of 256 samples: rising ramp and constant
+        let samples: Vec<i16> = (0..256)
+            .map(|i| (i * 64) as i16)
+            .chain((0..256).map(|_| 16384))
+            .collect();
+
+        let wavetable =
+            Wavetable::from_wav_bytes("test", &wav_bytes(&samples, Some("<!>256 00000000")))
+                .unwrap();
+
+        assert_eq!(wavetable.num_frames(), 2);
+        assert!((wavetable.sample(0.5, 0.0) - 0.5).abs() < 0.01);
+        assert!((wavetable.sample(0.5, 1.0) - 1.0).abs() < 0.01);
+        assert!((wavetable.sample(0.5, 0.5) - 0.75).abs() < 0.01);
+
+        // Without frame length information, file is a single cycle
+        let wavetable = Wavetable::from_wav_bytes("test", &wav_bytes(&samples, None)).unwrap();
+
+        assert_eq!(wavetable.num_frames(), 1);
+
+        assert!(Wavetable::from_wav_bytes("test", b"RIFF").is_err());
+    }
+}